@@ -7,6 +7,7 @@
 use std::{
     fmt::{Debug, Display},
     mem::size_of,
+    ops::Range,
     sync::Arc,
 };
 
@@ -15,7 +16,10 @@ use committable::{Commitment, Committable, RawCommitmentBuilder};
 use hotshot_types::{
     data::{BlockError, Leaf2},
     traits::{
-        block_contents::{BlockHeader, BuilderFee, EncodeBytes, TestableBlock, Transaction},
+        block_contents::{
+            BlockHeader, BuilderFee, EncodeBytes, NamespaceId, NamespaceTable, NamespacedMetadata,
+            NamespacedTransaction, TestableBlock, Transaction,
+        },
         node_implementation::NodeType,
         BlockPayload, ValidatedState,
     },
@@ -27,6 +31,7 @@ use serde::{Deserialize, Serialize};
 use sha3::{Digest, Keccak256};
 use thiserror::Error;
 use time::OffsetDateTime;
+use utils::anytrace;
 use vbs::version::Version;
 
 use crate::{
@@ -38,8 +43,22 @@ use crate::{
 
 /// The transaction in a [`TestBlockPayload`].
 #[derive(Default, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Debug)]
-#[serde(try_from = "Vec<u8>")]
-pub struct TestTransaction(Vec<u8>);
+#[serde(try_from = "UncheckedTestTransaction")]
+pub struct TestTransaction {
+    /// The namespace the transaction belongs to
+    namespace: NamespaceId,
+    /// The transaction's bytes
+    bytes: Vec<u8>,
+}
+
+/// A [`TestTransaction`] as deserialized, before its length is checked
+#[derive(Deserialize)]
+struct UncheckedTestTransaction {
+    /// The namespace the transaction belongs to
+    namespace: NamespaceId,
+    /// The transaction's bytes
+    bytes: Vec<u8>,
+}
 
 #[derive(Debug, Error)]
 pub enum TransactionError {
@@ -55,6 +74,15 @@ impl TryFrom<Vec<u8>> for TestTransaction {
     }
 }
 
+impl TryFrom<UncheckedTestTransaction> for TestTransaction {
+    type Error = TransactionError;
+
+    fn try_from(value: UncheckedTestTransaction) -> Result<Self, Self::Error> {
+        Self::try_new_in_namespace(value.namespace, value.bytes)
+            .ok_or(TransactionError::TransactionTooLong)
+    }
+}
+
 impl TestTransaction {
     /// Construct a new transaction
     ///
@@ -68,21 +96,35 @@ impl TestTransaction {
     /// Returns `None` if `bytes.len()` > `u32::MAX`
     /// for cross-platform compatibility
     pub fn try_new(bytes: Vec<u8>) -> Option<Self> {
+        Self::try_new_in_namespace(0, bytes)
+    }
+
+    /// Construct a new transaction in namespace `namespace`
+    ///
+    /// # Panics
+    /// If `bytes.len()` > `u32::MAX`
+    pub fn new_in_namespace(namespace: NamespaceId, bytes: Vec<u8>) -> Self {
+        Self::try_new_in_namespace(namespace, bytes).expect("Vector too long")
+    }
+
+    /// Construct a new transaction in namespace `namespace`.
+    /// Returns `None` if `bytes.len()` > `u32::MAX`
+    pub fn try_new_in_namespace(namespace: NamespaceId, bytes: Vec<u8>) -> Option<Self> {
         if u32::try_from(bytes.len()).is_err() {
             None
         } else {
-            Some(Self(bytes))
+            Some(Self { namespace, bytes })
         }
     }
 
     /// Get reference to raw bytes of transaction
     pub fn bytes(&self) -> &Vec<u8> {
-        &self.0
+        &self.bytes
     }
 
     /// Convert transaction to raw vector of bytes
     pub fn into_bytes(self) -> Vec<u8> {
-        self.0
+        self.bytes
     }
 
     /// Encode a list of transactions into bytes.
@@ -95,13 +137,13 @@ impl TestTransaction {
         for txn in transactions {
             // The transaction length is converted from `usize` to `u32` to ensure consistent
             // number of bytes on different platforms.
-            let txn_size = u32::try_from(txn.0.len())
+            let txn_size = u32::try_from(txn.bytes.len())
                 .expect("Invalid transaction length")
                 .to_le_bytes();
 
             // Concatenate the bytes of the transaction size and the transaction itself.
             encoded.extend(txn_size);
-            encoded.extend(&txn.0);
+            encoded.extend(&txn.bytes);
         }
 
         encoded
@@ -112,9 +154,12 @@ impl Committable for TestTransaction {
     fn commit(&self) -> Commitment<Self> {
         let builder = committable::RawCommitmentBuilder::new("Txn Comm");
         let mut hasher = Keccak256::new();
-        hasher.update(&self.0);
+        hasher.update(&self.bytes);
         let generic_array = hasher.finalize();
-        builder.generic_byte_array(&generic_array).finalize()
+        builder
+            .u64_field("namespace", u64::from(self.namespace))
+            .generic_byte_array(&generic_array)
+            .finalize()
     }
 
     fn tag() -> String {
@@ -125,7 +170,13 @@ impl Committable for TestTransaction {
 impl Transaction for TestTransaction {
     fn minimum_block_size(&self) -> u64 {
         // the estimation on transaction size is the length of the transaction
-        self.0.len() as u64
+        self.bytes.len() as u64
+    }
+}
+
+impl NamespacedTransaction for TestTransaction {
+    fn namespace(&self) -> NamespaceId {
+        self.namespace
     }
 }
 
@@ -165,14 +216,42 @@ impl<TYPES: NodeType> TestableBlock<TYPES> for TestBlockPayload {
     }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct TestMetadata {
     pub num_transactions: u64,
+    /// Where each namespace's transactions lie in the encoded payload
+    pub namespaces: NamespaceTable,
+}
+
+impl TestMetadata {
+    /// The metadata of a payload of `transactions`, which are already grouped by namespace
+    #[must_use]
+    pub fn new(transactions: &[TestTransaction]) -> Self {
+        let (_, namespaces) = NamespaceTable::encode_transactions(
+            transactions.iter().cloned(),
+            TestTransaction::encode,
+        );
+
+        Self {
+            num_transactions: transactions.len() as u64,
+            namespaces,
+        }
+    }
 }
 
 impl EncodeBytes for TestMetadata {
     fn encode(&self) -> Arc<[u8]> {
-        Arc::new([])
+        self.namespaces.encode()
+    }
+}
+
+impl NamespacedMetadata for TestMetadata {
+    fn namespace_range(&self, ns: NamespaceId) -> Option<Range<usize>> {
+        self.namespaces.namespace_range(ns)
+    }
+
+    fn validate_namespaces(&self, payload_len: usize) -> anytrace::Result<()> {
+        self.namespaces.validate_namespaces(payload_len)
     }
 }
 
@@ -195,10 +274,10 @@ impl<TYPES: NodeType> BlockPayload<TYPES> for TestBlockPayload {
         _validated_state: &Self::ValidatedState,
         _instance_state: &Self::Instance,
     ) -> Result<(Self, Self::Metadata), Self::Error> {
-        let txns_vec: Vec<TestTransaction> = transactions.into_iter().collect();
-        let metadata = TestMetadata {
-            num_transactions: txns_vec.len() as u64,
-        };
+        // Each namespace's transactions are laid out together, in the order they were given.
+        let mut txns_vec: Vec<TestTransaction> = transactions.into_iter().collect();
+        txns_vec.sort_by_key(NamespacedTransaction::namespace);
+        let metadata = TestMetadata::new(&txns_vec);
         Ok((
             Self {
                 transactions: txns_vec,
@@ -207,7 +286,7 @@ impl<TYPES: NodeType> BlockPayload<TYPES> for TestBlockPayload {
         ))
     }
 
-    fn from_bytes(encoded_transactions: &[u8], _metadata: &Self::Metadata) -> Self {
+    fn from_bytes(encoded_transactions: &[u8], metadata: &Self::Metadata) -> Self {
         let mut transactions = Vec::new();
         let mut current_index = 0;
         while current_index < encoded_transactions.len() {
@@ -217,11 +296,21 @@ impl<TYPES: NodeType> BlockPayload<TYPES> for TestBlockPayload {
             txn_len_bytes.copy_from_slice(&encoded_transactions[current_index..txn_start_index]);
             let txn_len: usize = u32::from_le_bytes(txn_len_bytes) as usize;
 
-            // Get the transaction.
+            // Get the transaction, and the namespace whose range it lies in.
             let next_index = txn_start_index + txn_len;
-            transactions.push(TestTransaction(
-                encoded_transactions[txn_start_index..next_index].to_vec(),
-            ));
+            let namespace = metadata
+                .namespaces
+                .namespaces()
+                .find(|ns| {
+                    metadata
+                        .namespace_range(*ns)
+                        .is_some_and(|range| range.contains(&current_index))
+                })
+                .unwrap_or_default();
+            transactions.push(TestTransaction {
+                namespace,
+                bytes: encoded_transactions[txn_start_index..next_index].to_vec(),
+            });
             current_index = next_index;
         }
 
@@ -229,18 +318,13 @@ impl<TYPES: NodeType> BlockPayload<TYPES> for TestBlockPayload {
     }
 
     fn empty() -> (Self, Self::Metadata) {
-        (
-            Self::genesis(),
-            TestMetadata {
-                num_transactions: 0,
-            },
-        )
+        (Self::genesis(), TestMetadata::default())
     }
 
     fn builder_commitment(&self, _metadata: &Self::Metadata) -> BuilderCommitment {
         let mut digest = sha2::Sha256::new();
         for txn in &self.transactions {
-            digest.update(&txn.0);
+            digest.update(&txn.bytes);
         }
         BuilderCommitment::from_raw_digest(digest.finalize())
     }
@@ -251,6 +335,23 @@ impl<TYPES: NodeType> BlockPayload<TYPES> for TestBlockPayload {
     ) -> impl 'a + Iterator<Item = Self::Transaction> {
         self.transactions.iter().cloned()
     }
+
+    fn transaction_range(&self, _metadata: &Self::Metadata, index: usize) -> Option<Range<usize>> {
+        // Each transaction is encoded as its length followed by its bytes.
+        let start = self.transactions[..index.min(self.transactions.len())]
+            .iter()
+            .map(|txn| size_of::<u32>() + txn.bytes.len())
+            .sum::<usize>()
+            + size_of::<u32>();
+
+        self.transactions
+            .get(index)
+            .map(|txn| start..start + txn.bytes.len())
+    }
+
+    fn validate_metadata(metadata: &Self::Metadata, payload_len: usize) -> anytrace::Result<()> {
+        metadata.validate_namespaces(payload_len)
+    }
 }
 
 /// A [`BlockHeader`] that commits to [`TestBlockPayload`].
@@ -357,15 +458,11 @@ impl<
         builder_commitment: BuilderCommitment,
         _metadata: <TYPES::BlockPayload as BlockPayload<TYPES>>::Metadata,
     ) -> Self {
-        let metadata = TestMetadata {
-            num_transactions: 0,
-        };
-
         Self {
             block_number: 0,
             payload_commitment,
             builder_commitment,
-            metadata,
+            metadata: TestMetadata::default(),
             timestamp: 0,
             random: 0,
         }
@@ -390,6 +487,10 @@ impl<
     fn get_auction_results(&self) -> Option<TYPES::AuctionResult> {
         Some(TYPES::AuctionResult { urls: vec![] })
     }
+
+    fn timestamp(&self) -> Option<u64> {
+        Some(self.timestamp)
+    }
 }
 
 impl Committable for TestBlockHeader {
@@ -20,6 +20,7 @@ use hotshot::traits::{
     NodeImplementation,
 };
 use hotshot_types::{
+    commit_scheme::DefaultCommitScheme,
     data::{EpochNumber, ViewNumber},
     signature_key::{BLSPubKey, BuilderKey},
     traits::node_implementation::{NodeType, Versions},
@@ -62,6 +63,7 @@ impl NodeType for TestTypes {
     type InstanceState = TestInstanceState;
     type Membership = StaticCommittee<TestTypes>;
     type BuilderSignatureKey = BuilderKey;
+    type CommitScheme = DefaultCommitScheme;
 }
 
 #[derive(
@@ -92,6 +94,7 @@ impl NodeType for TestTypesRandomizedLeader {
     type InstanceState = TestInstanceState;
     type Membership = RandomizedCommittee<TestTypesRandomizedLeader>;
     type BuilderSignatureKey = BuilderKey;
+    type CommitScheme = DefaultCommitScheme;
 }
 
 #[derive(
@@ -126,6 +129,7 @@ impl<CONFIG: QuorumFilterConfig> NodeType for TestTypesRandomizedCommitteeMember
     type Membership =
         RandomizedCommitteeMembers<TestTypesRandomizedCommitteeMembers<CONFIG>, CONFIG>;
     type BuilderSignatureKey = BuilderKey;
+    type CommitScheme = DefaultCommitScheme;
 }
 
 #[derive(
@@ -156,6 +160,7 @@ impl NodeType for TestConsecutiveLeaderTypes {
     type InstanceState = TestInstanceState;
     type Membership = StaticCommitteeLeaderForTwoViews<TestConsecutiveLeaderTypes>;
     type BuilderSignatureKey = BuilderKey;
+    type CommitScheme = DefaultCommitScheme;
 }
 
 /// The Push CDN implementation
@@ -253,6 +258,22 @@ impl Versions for MarketplaceTestVersions {
     type Epochs = StaticVersion<0, 4>;
 }
 
+#[derive(Clone, Debug, Copy)]
+pub struct EpochUpgradeTestVersions {}
+
+impl Versions for EpochUpgradeTestVersions {
+    type Base = StaticVersion<0, 3>;
+    type Upgrade = StaticVersion<0, 4>;
+    const UPGRADE_HASH: [u8; 32] = [
+        1, 0, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0,
+        0, 0,
+    ];
+
+    type Marketplace = StaticVersion<0, 3>;
+
+    type Epochs = StaticVersion<0, 4>;
+}
+
 #[derive(Clone, Debug, Copy)]
 pub struct EpochsTestVersions {}
 
@@ -273,7 +294,8 @@ impl Versions for EpochsTestVersions {
 mod tests {
     use committable::{Commitment, Committable};
     use hotshot_types::{
-        message::UpgradeLock, simple_vote::VersionedVoteData,
+        message::UpgradeLock,
+        simple_vote::{VersionedVoteData, VoteRole, Voteable},
         traits::node_implementation::ConsensusTime,
     };
     use serde::{Deserialize, Serialize};
@@ -293,6 +315,10 @@ mod tests {
         }
     }
 
+    impl Voteable for TestData {
+        const ROLE: VoteRole = VoteRole::Quorum;
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     /// Test that the view number affects the commitment post-marketplace
     async fn test_versioned_commitment_includes_view() {
@@ -302,11 +328,13 @@ mod tests {
 
         let view_0 = <TestTypes as NodeType>::View::new(0);
         let view_1 = <TestTypes as NodeType>::View::new(1);
+        let epoch = <TestTypes as NodeType>::Epoch::new(0);
 
         let versioned_data_0 =
             VersionedVoteData::<TestTypes, TestData, MarketplaceTestVersions>::new(
                 data,
                 view_0,
+                epoch,
                 &upgrade_lock,
             )
             .await
@@ -315,6 +343,7 @@ mod tests {
             VersionedVoteData::<TestTypes, TestData, MarketplaceTestVersions>::new(
                 data,
                 view_1,
+                epoch,
                 &upgrade_lock,
             )
             .await
@@ -6,7 +6,10 @@
 
 use std::{
     collections::{BTreeMap, HashMap},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 
 use anyhow::{bail, Result};
@@ -66,6 +69,11 @@ pub struct TestStorage<TYPES: NodeType> {
     inner: Arc<RwLock<TestStorageState<TYPES>>>,
     /// `should_return_err` is a testing utility to validate negative cases.
     pub should_return_err: bool,
+    /// Number of upcoming `append_da` calls which fail before storage starts succeeding again,
+    /// used to simulate transient storage failures.
+    pub transient_append_da_failures: Arc<AtomicUsize>,
+    /// Number of times the undecided state has been written
+    pub undecided_state_writes: Arc<AtomicUsize>,
     pub delay_config: DelayConfig,
     pub decided_upgrade_certificate: Arc<RwLock<Option<UpgradeCertificate<TYPES>>>>,
 }
@@ -75,6 +83,8 @@ impl<TYPES: NodeType> Default for TestStorage<TYPES> {
         Self {
             inner: Arc::new(RwLock::new(TestStorageState::default())),
             should_return_err: false,
+            transient_append_da_failures: Arc::new(AtomicUsize::new(0)),
+            undecided_state_writes: Arc::new(AtomicUsize::new(0)),
             delay_config: DelayConfig::default(),
             decided_upgrade_certificate: Arc::new(RwLock::new(None)),
         }
@@ -135,6 +145,13 @@ impl<TYPES: NodeType> Storage<TYPES> for TestStorage<TYPES> {
         if self.should_return_err {
             bail!("Failed to append VID proposal to storage");
         }
+        if self
+            .transient_append_da_failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+        {
+            bail!("Transient failure appending DA proposal to storage");
+        }
         Self::run_delay_settings_from_config(&self.delay_config).await;
         let mut inner = self.inner.write().await;
         inner
@@ -244,6 +261,7 @@ impl<TYPES: NodeType> Storage<TYPES> for TestStorage<TYPES> {
             bail!("Failed to update high qc to storage");
         }
         Self::run_delay_settings_from_config(&self.delay_config).await;
+        self.undecided_state_writes.fetch_add(1, Ordering::SeqCst);
         Ok(())
     }
     async fn update_decided_upgrade_certificate(
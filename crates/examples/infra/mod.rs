@@ -383,7 +383,8 @@ pub trait RunDa<
 
         // Create the quorum membership from all nodes, specifying the committee
         // as the known da nodes
-        let memberships = <TYPES as NodeType>::Membership::new(all_nodes, da_nodes);
+        let memberships = <TYPES as NodeType>::Membership::try_new(all_nodes, da_nodes)
+            .expect("Known nodes must not contain duplicate keys");
 
         let marketplace_config = MarketplaceConfig {
             auction_results_provider: TestAuctionResultsProvider::<TYPES>::default().into(),
@@ -531,6 +532,8 @@ pub trait RunDa<
         let num_eligible_leaders = context
             .hotshot
             .memberships
+            .read()
+            .await
             .committee_leaders(TYPES::View::genesis(), TYPES::Epoch::genesis())
             .len();
         let total_num_views = usize::try_from(consensus.locked_view().u64()).unwrap();
@@ -739,7 +742,8 @@ where
         // Create the qurorum membership from the list of known nodes
         let all_nodes = config.config.known_nodes_with_stake.clone();
         let da_nodes = config.config.known_da_nodes.clone();
-        let quorum_membership = TYPES::Membership::new(all_nodes, da_nodes);
+        let quorum_membership = TYPES::Membership::try_new(all_nodes, da_nodes)
+            .expect("Known nodes must not contain duplicate keys");
 
         // Derive the bind address
         let bind_address =
@@ -33,7 +33,10 @@ pub mod helpers;
 use std::{
     collections::{BTreeMap, HashMap},
     num::NonZeroUsize,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 
@@ -42,7 +45,11 @@ use async_lock::RwLock;
 use async_trait::async_trait;
 use futures::join;
 use hotshot_task::task::{ConsensusTaskRegistry, NetworkTaskRegistry};
-use hotshot_task_impls::{events::HotShotEvent, helpers::broadcast_event};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    helpers::broadcast_event,
+    network::{tag_network_id, Participation},
+};
 // Internal
 /// Reexport error type
 pub use hotshot_types::error::HotShotError;
@@ -54,6 +61,7 @@ use hotshot_types::{
     message::{convert_proposal, DataMessage, Message, MessageKind, Proposal},
     simple_certificate::{QuorumCertificate, QuorumCertificate2, UpgradeCertificate},
     traits::{
+        block_contents::{AcceptAllTxnValidator, TxnValidator},
         consensus_api::ConsensusApi,
         election::Membership,
         network::ConnectedNetwork,
@@ -63,6 +71,8 @@ use hotshot_types::{
         storage::Storage,
         EncodeBytes,
     },
+    vid::set_vid_fault_fraction,
+    vote_metrics::VoteMetrics,
     HotShotConfig,
 };
 // -- Rexports
@@ -70,7 +80,7 @@ use hotshot_types::{
 /// Reexport rand crate
 pub use rand;
 use tokio::{spawn, time::sleep};
-use tracing::{debug, instrument, trace};
+use tracing::{debug, instrument, trace, warn};
 
 use crate::{
     tasks::{add_consensus_tasks, add_network_tasks},
@@ -106,8 +116,8 @@ pub struct SystemContext<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versi
     /// The underlying network
     pub network: Arc<I::Network>,
 
-    /// Memberships used by consensus
-    pub memberships: Arc<TYPES::Membership>,
+    /// Memberships used by consensus, shared by every task of this node
+    pub memberships: Arc<RwLock<TYPES::Membership>>,
 
     /// the metrics that the implementor is using.
     metrics: Arc<ConsensusMetricsValue>,
@@ -151,6 +161,18 @@ pub struct SystemContext<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versi
 
     /// Marketplace config for this instance of HotShot
     pub marketplace_config: MarketplaceConfig<TYPES, I>,
+
+    /// Check run on transactions received from the network before they reach the mempool
+    pub transaction_validator: Arc<dyn TxnValidator<TYPES>>,
+
+    /// Metrics of the votes accumulated by this node's tasks
+    pub vote_metrics: Arc<VoteMetrics>,
+
+    /// Whether this node takes part in consensus, or only follows it
+    pub participation: Arc<Participation>,
+
+    /// Whether consensus has been started, so it is only ever started once
+    started: Arc<AtomicBool>,
 }
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> Clone
     for SystemContext<TYPES, I, V>
@@ -176,6 +198,10 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> Clone
             storage: Arc::clone(&self.storage),
             upgrade_lock: self.upgrade_lock.clone(),
             marketplace_config: self.marketplace_config.clone(),
+            transaction_validator: Arc::clone(&self.transaction_validator),
+            vote_metrics: Arc::clone(&self.vote_metrics),
+            participation: Arc::clone(&self.participation),
+            started: Arc::clone(&self.started),
         }
     }
 }
@@ -273,7 +299,10 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
         let (mut external_tx, mut external_rx) = external_channel;
 
         let upgrade_lock =
-            UpgradeLock::<TYPES, V>::from_certificate(&initializer.decided_upgrade_certificate);
+            UpgradeLock::<TYPES, V>::from_certificate(&initializer.decided_upgrade_certificate)
+                .with_vote_transition_views(config.vote_transition_views)
+                .with_signature_cache(config.signature_cache_capacity)
+                .with_commitment_cache(config.commitment_cache_capacity);
 
         // Allow overflow on the external channel, otherwise sending to it may block.
         external_rx.set_overflow(true);
@@ -348,6 +377,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
         // Our own copy of the receiver is inactive so it doesn't count.
         external_tx.set_await_active(false);
 
+        let participation = Arc::new(Participation::new(config.participation_mode));
         let inner: Arc<SystemContext<TYPES, I, V>> = Arc::new(SystemContext {
             id: nonce,
             consensus: OuterConsensus::new(consensus),
@@ -358,7 +388,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
             start_view: initializer.start_view,
             start_epoch: initializer.start_epoch,
             network,
-            memberships: Arc::new(memberships),
+            memberships: Arc::new(RwLock::new(memberships)),
             metrics: Arc::clone(&consensus_metrics),
             internal_event_stream: (internal_tx, internal_rx.deactivate()),
             output_event_stream: (external_tx.clone(), external_rx.clone().deactivate()),
@@ -367,13 +397,31 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
             storage: Arc::new(RwLock::new(storage)),
             upgrade_lock,
             marketplace_config,
+            transaction_validator: Arc::new(AcceptAllTxnValidator),
+            vote_metrics: Arc::new(VoteMetrics::default()),
+            participation,
+            started: Arc::default(),
         });
 
         inner
     }
 
+    /// Returns a copy of this [`SystemContext`] which validates transactions received from the
+    /// network with `validator` instead of admitting all of them.
+    ///
+    /// This must be called before the tasks are started, i.e. on a context created with `new`.
+    #[must_use]
+    pub fn with_transaction_validator(&self, validator: impl TxnValidator<TYPES>) -> Arc<Self> {
+        Arc::new(Self {
+            transaction_validator: Arc::new(validator),
+            ..self.clone()
+        })
+    }
+
     /// "Starts" consensus by sending a `Qc2Formed`, `ViewChange` events
     ///
+    /// Consensus is only started once, any later call does nothing
+    ///
     /// # Panics
     /// Panics if sending genesis fails
     #[instrument(skip_all, target = "SystemContext", fields(id = self.id))]
@@ -381,6 +429,11 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
         #[cfg(all(feature = "rewind", not(debug_assertions)))]
         compile_error!("Cannot run rewind in production builds!");
 
+        if self.started.swap(true, Ordering::SeqCst) {
+            warn!("Consensus was already started, not starting it again");
+            return;
+        }
+
         debug!("Starting Consensus");
         let consensus = self.consensus.read().await;
 
@@ -496,6 +549,13 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
         let serialized_message = self.upgrade_lock.serialize(&message).await.map_err(|err| {
             HotShotError::FailedToSerialize(format!("failed to serialize transaction: {err}"))
         })?;
+        let serialized_message = tag_network_id(self.config.network_id, serialized_message);
+
+        let da_committee_members = self
+            .memberships
+            .read()
+            .await
+            .da_committee_members(view_number, TYPES::Epoch::new(1));
 
         spawn(async move {
             join! {
@@ -509,7 +569,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
                 api
                     .network.da_broadcast_message(
                         serialized_message,
-                        api.memberships.da_committee_members(view_number, TYPES::Epoch::new(1)).iter().cloned().collect(),
+                        da_committee_members.into_iter().collect(),
                         BroadcastDelay::None,
                     ),
                 api
@@ -587,7 +647,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
     /// To construct a [`SystemContext`] without setting up tasks, use `fn new` instead.
     /// # Errors
     ///
-    /// Can throw an error if `Self::new` fails.
+    /// Can throw an error if the config is invalid, or if `Self::new` fails.
     #[allow(clippy::too_many_arguments)]
     pub async fn init(
         public_key: TYPES::SignatureKey,
@@ -608,6 +668,9 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> SystemContext<T
         ),
         HotShotError<TYPES>,
     > {
+        config.validate().map_err(HotShotError::InvalidConfig)?;
+        set_vid_fault_fraction(config.vid_fault_fraction);
+
         let hotshot = Self::new(
             public_key,
             private_key,
@@ -17,13 +17,15 @@ use futures::{
     future::{BoxFuture, FutureExt},
     stream, StreamExt,
 };
-use hotshot_task::task::Task;
+use hotshot_task::{queue::QueueDepth, task::Task, watchdog::Watchdog};
 #[cfg(feature = "rewind")]
 use hotshot_task_impls::rewind::RewindTaskState;
 use hotshot_task_impls::{
     da::DaTaskState,
     events::HotShotEvent,
-    network::{NetworkEventTaskState, NetworkMessageTaskState},
+    network::{
+        strip_network_id, NetworkEventTaskState, NetworkMessageTaskState, ReceivedJustifyQcs,
+    },
     request::NetworkRequestState,
     response::{run_response_task, NetworkResponseState},
     transactions::TransactionTaskState,
@@ -33,7 +35,7 @@ use hotshot_task_impls::{
 };
 use hotshot_types::{
     consensus::{Consensus, OuterConsensus},
-    constants::EVENT_CHANNEL_SIZE,
+    constants::{DA_EVENT_QUEUE_CAPACITY, DA_EVENT_QUEUE_HIGH_WATERMARK, EVENT_CHANNEL_SIZE},
     message::{Message, UpgradeLock},
     traits::{
         network::ConnectedNetwork,
@@ -82,7 +84,7 @@ pub fn add_response_task<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versi
 ) {
     let state = NetworkResponseState::<TYPES>::new(
         handle.hotshot.consensus(),
-        (*handle.hotshot.memberships).clone().into(),
+        Arc::clone(&handle.hotshot.memberships),
         handle.public_key().clone(),
         handle.private_key().clone(),
         handle.hotshot.id,
@@ -117,6 +119,36 @@ pub fn add_queue_len_task<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Vers
     handle.network_registry.register(task_handle);
 }
 
+/// Add a watchdog which reports (and optionally restarts) consensus tasks that stop making
+/// progress while events are queued for them.
+///
+/// Only monitors the tasks registered so far, so should be added after them.
+pub fn add_task_watchdog<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>(
+    handle: &mut SystemContextHandle<TYPES, I, V>,
+) {
+    let watchdog = Watchdog::new(
+        handle.consensus_registry.task_progress(),
+        handle.internal_event_stream.0.clone(),
+        handle.hotshot.config.task_stall_timeout,
+        handle.hotshot.config.restart_stalled_tasks,
+    );
+    let shutdown_signal = create_shutdown_event_monitor(handle).fuse();
+    let task_handle = spawn(async move {
+        futures::pin_mut!(shutdown_signal);
+        loop {
+            futures::select! {
+                () = shutdown_signal => {
+                    return;
+                },
+                () = sleep(watchdog.check_interval()).fuse() => {
+                    watchdog.check();
+                }
+            }
+        }
+    });
+    handle.network_registry.register(task_handle);
+}
+
 /// Add the network task to handle messages and publish events.
 #[allow(clippy::missing_panics_doc)]
 pub fn add_network_message_task<
@@ -133,9 +165,15 @@ pub fn add_network_message_task<
         external_event_stream: handle.output_event_stream.0.clone(),
         public_key: handle.public_key().clone(),
         transactions_cache: lru::LruCache::new(NonZeroUsize::new(100_000).unwrap()),
+        transaction_validator: Arc::clone(&handle.hotshot.transaction_validator),
+        metrics: Arc::clone(&handle.hotshot.metrics),
+        consensus: OuterConsensus::new(handle.hotshot.consensus()),
+        max_message_age_views: handle.hotshot.config.max_message_age_views,
+        received_justify_qcs: ReceivedJustifyQcs::default(),
     };
 
     let upgrade_lock = handle.hotshot.upgrade_lock.clone();
+    let network_id = handle.hotshot.config.network_id;
 
     let network = Arc::clone(channel);
     let mut state = network_state.clone();
@@ -163,8 +201,17 @@ pub fn add_network_message_task<
                         }
                     };
 
+                    // Drop messages from other networks before decoding them
+                    let message = match strip_network_id(network_id, &message) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            tracing::debug!("{e}");
+                            continue;
+                        }
+                    };
+
                     // Deserialize the message
-                    let deserialized_message: Message<TYPES> = match upgrade_lock.deserialize(&message).await {
+                    let deserialized_message: Message<TYPES> = match upgrade_lock.deserialize(message).await {
                         Ok(message) => message,
                         Err(e) => {
                             tracing::error!("Failed to deserialize message: {:?}", e);
@@ -190,17 +237,22 @@ pub fn add_network_event_task<
 >(
     handle: &mut SystemContextHandle<TYPES, I, V>,
     network: Arc<NET>,
-    membership: TYPES::Membership,
+    membership: Arc<RwLock<TYPES::Membership>>,
 ) {
     let network_state: NetworkEventTaskState<_, V, _, _> = NetworkEventTaskState {
         network,
         view: TYPES::View::genesis(),
         epoch: TYPES::Epoch::genesis(),
+        max_epoch_skip: handle.hotshot.config.max_epoch_skip,
         membership,
         storage: Arc::clone(&handle.storage()),
         consensus: OuterConsensus::new(handle.consensus()),
         upgrade_lock: handle.hotshot.upgrade_lock.clone(),
+        network_id: handle.hotshot.config.network_id,
         transmit_tasks: BTreeMap::new(),
+        participation: Arc::clone(&handle.hotshot.participation),
+        batch_justify_qcs: handle.hotshot.config.batch_justify_qcs,
+        last_justify_qc: None,
     };
     let task = Task::new(
         network_state,
@@ -216,7 +268,18 @@ pub async fn add_consensus_tasks<TYPES: NodeType, I: NodeImplementation<TYPES>,
 ) {
     handle.add_task(ViewSyncTaskState::<TYPES, V>::create_from(handle).await);
     handle.add_task(VidTaskState::<TYPES, I>::create_from(handle).await);
-    handle.add_task(DaTaskState::<TYPES, I, V>::create_from(handle).await);
+
+    // The DA task is the first to fall behind when the node is overwhelmed, so watch its queue
+    let metrics = Arc::clone(&handle.hotshot.metrics);
+    handle.add_queued_task(
+        DaTaskState::<TYPES, I, V>::create_from(handle).await,
+        DA_EVENT_QUEUE_CAPACITY,
+        Arc::new(QueueDepth::new(
+            "DaTaskState",
+            DA_EVENT_QUEUE_HIGH_WATERMARK,
+        )),
+        move |depth| metrics.da_event_queue_len.set(depth),
+    );
     handle.add_task(TransactionTaskState::<TYPES, I, V>::create_from(handle).await);
 
     {
@@ -255,6 +318,7 @@ pub async fn add_consensus_tasks<TYPES: NodeType, I: NodeImplementation<TYPES>,
     add_queue_len_task(handle);
     #[cfg(feature = "rewind")]
     handle.add_task(RewindTaskState::<TYPES>::create_from(&handle).await);
+    add_task_watchdog(handle);
 }
 
 /// Creates a monitor for shutdown events.
@@ -517,7 +581,11 @@ where
     fn add_network_event_tasks(&self, handle: &mut SystemContextHandle<TYPES, I, V>) {
         let network = Arc::clone(&handle.network);
 
-        self.add_network_event_task(handle, Arc::clone(&network), (*handle.memberships).clone());
+        self.add_network_event_task(
+            handle,
+            Arc::clone(&network),
+            Arc::clone(&handle.memberships),
+        );
     }
 
     /// Adds a `NetworkEventTaskState` task. Can be reimplemented to modify its behaviour.
@@ -525,7 +593,7 @@ where
         &self,
         handle: &mut SystemContextHandle<TYPES, I, V>,
         channel: Arc<<I as NodeImplementation<TYPES>>::Network>,
-        membership: TYPES::Membership,
+        membership: Arc<RwLock<TYPES::Membership>>,
     ) {
         add_network_event_task(handle, channel, membership);
     }
@@ -563,6 +631,6 @@ pub fn add_network_event_tasks<TYPES: NodeType, I: NodeImplementation<TYPES>, V:
     add_network_event_task(
         handle,
         Arc::clone(&handle.network),
-        (*handle.memberships).clone(),
+        Arc::clone(&handle.memberships),
     );
 }
@@ -5,22 +5,31 @@
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     sync::{atomic::AtomicBool, Arc},
 };
 
 use async_trait::async_trait;
 use chrono::Utc;
 use hotshot_task_impls::{
-    builder::BuilderClient, consensus::ConsensusTaskState, da::DaTaskState,
-    quorum_proposal::QuorumProposalTaskState, quorum_proposal_recv::QuorumProposalRecvTaskState,
-    quorum_vote::QuorumVoteTaskState, request::NetworkRequestState, rewind::RewindTaskState,
-    transactions::TransactionTaskState, upgrade::UpgradeTaskState, vid::VidTaskState,
+    builder::BuilderClient,
+    consensus::ConsensusTaskState,
+    da::DaTaskState,
+    helpers::VotingWarmup,
+    quorum_proposal::QuorumProposalTaskState,
+    quorum_proposal_recv::QuorumProposalRecvTaskState,
+    quorum_vote::QuorumVoteTaskState,
+    request::NetworkRequestState,
+    rewind::RewindTaskState,
+    transactions::{FifoSelection, TransactionTaskState},
+    upgrade::UpgradeTaskState,
+    vid::VidTaskState,
     view_sync::ViewSyncTaskState,
 };
 use hotshot_types::{
     consensus::OuterConsensus,
     traits::{
+        block_contents::vid_commitment,
         consensus_api::ConsensusApi,
         node_implementation::{ConsensusTime, NodeImplementation, NodeType},
     },
@@ -51,7 +60,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             consensus: OuterConsensus::new(handle.hotshot.consensus()),
             view: handle.cur_view().await,
             delay: handle.hotshot.config.data_request_delay,
-            membership: (*handle.hotshot.memberships).clone(),
+            membership: Arc::clone(&handle.hotshot.memberships),
             public_key: handle.public_key().clone(),
             private_key: handle.private_key().clone(),
             id: handle.hotshot.id,
@@ -71,7 +80,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             output_event_stream: handle.hotshot.external_event_stream.0.clone(),
             cur_view: handle.cur_view().await,
             cur_epoch: handle.cur_epoch().await,
-            quorum_membership: (*handle.hotshot.memberships).clone().into(),
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
+            quorum_membership: Arc::clone(&handle.hotshot.memberships),
             vote_collectors: BTreeMap::default(),
             public_key: handle.public_key().clone(),
             private_key: handle.private_key().clone(),
@@ -85,6 +95,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             start_voting_time: handle.hotshot.config.start_voting_time,
             stop_voting_time: handle.hotshot.config.stop_voting_time,
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
+            late_vote_grace: handle.hotshot.config.late_vote_grace,
+            vote_metrics: Arc::clone(&handle.hotshot.vote_metrics),
         };
 
         #[cfg(feature = "example-upgrade")]
@@ -92,7 +104,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             output_event_stream: handle.hotshot.external_event_stream.0.clone(),
             cur_view: handle.cur_view().await,
             cur_epoch: handle.cur_epoch().await,
-            membership: (*handle.hotshot.memberships).clone().into(),
+            membership: Arc::clone(&handle.hotshot.memberships),
             network: Arc::clone(&handle.hotshot.network),
             vote_collector: None.into(),
             public_key: handle.public_key().clone(),
@@ -120,8 +132,9 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             consensus: OuterConsensus::new(handle.hotshot.consensus()),
             cur_view: handle.cur_view().await,
             cur_epoch: handle.cur_epoch().await,
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
             network: Arc::clone(&handle.hotshot.network),
-            membership: (*handle.hotshot.memberships).clone().into(),
+            membership: Arc::clone(&handle.hotshot.memberships),
             public_key: handle.public_key().clone(),
             private_key: handle.private_key().clone(),
             id: handle.hotshot.id,
@@ -137,16 +150,36 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
         Self {
             consensus: OuterConsensus::new(handle.hotshot.consensus()),
             output_event_stream: handle.hotshot.external_event_stream.0.clone(),
-            membership: (*handle.hotshot.memberships).clone().into(),
+            membership: Arc::clone(&handle.hotshot.memberships),
             network: Arc::clone(&handle.hotshot.network),
             cur_view: handle.cur_view().await,
             cur_epoch: handle.cur_epoch().await,
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
+            voting_warmup: VotingWarmup::new(
+                handle.cur_view().await,
+                handle.hotshot.config.warmup_views,
+            ),
+            future_proposal_buffer: BTreeMap::new(),
+            max_future_buffer: handle.hotshot.config.max_future_buffer,
+            max_da_payload_bytes: handle.hotshot.config.max_da_payload_bytes,
+            da_vote_timeout: handle.hotshot.config.da_vote_timeout,
+            da_vote_timers: BTreeMap::new(),
+            da_certificate_views: BTreeSet::new(),
+            early_vote_policy: handle.hotshot.config.early_vote_policy,
+            early_votes: BTreeMap::new(),
+            proposal_views: BTreeSet::new(),
+            proposal_hashes: BTreeMap::new(),
             vote_collectors: BTreeMap::default(),
             public_key: handle.public_key().clone(),
             private_key: handle.private_key().clone(),
             id: handle.hotshot.id,
             storage: Arc::clone(&handle.storage),
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
+            late_vote_grace: handle.hotshot.config.late_vote_grace,
+            vote_metrics: Arc::clone(&handle.hotshot.vote_metrics),
+            storage_retries: handle.hotshot.config.da_storage_retries,
+            storage_retry_backoff: handle.hotshot.config.da_storage_retry_backoff,
+            vid_commitment_fn: vid_commitment,
         }
     }
 }
@@ -162,7 +195,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             cur_view,
             next_view: cur_view,
             cur_epoch: handle.cur_epoch().await,
-            membership: (*handle.hotshot.memberships).clone().into(),
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
+            membership: Arc::clone(&handle.hotshot.memberships),
             public_key: handle.public_key().clone(),
             private_key: handle.private_key().clone(),
             num_timeouts_tracked: 0,
@@ -174,6 +208,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             id: handle.hotshot.id,
             last_garbage_collected_view: TYPES::View::new(0),
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
+            late_vote_grace: handle.hotshot.config.late_vote_grace,
+            vote_metrics: Arc::clone(&handle.hotshot.vote_metrics),
         }
     }
 }
@@ -189,7 +225,12 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             consensus: OuterConsensus::new(handle.hotshot.consensus()),
             cur_view: handle.cur_view().await,
             cur_epoch: handle.cur_epoch().await,
-            membership: (*handle.hotshot.memberships).clone().into(),
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
+            target_block_bytes: handle.hotshot.config.target_block_bytes,
+            proposal_source: None,
+            empty_block_policy: handle.hotshot.config.empty_block_policy,
+            selection_policy: Arc::new(FifoSelection),
+            membership: Arc::clone(&handle.hotshot.memberships),
             public_key: handle.public_key().clone(),
             private_key: handle.private_key().clone(),
             instance_state: handle.hotshot.instance_state(),
@@ -233,14 +274,20 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             latest_voted_view: handle.cur_view().await,
             vote_dependencies: BTreeMap::new(),
             network: Arc::clone(&handle.hotshot.network),
-            membership: (*handle.hotshot.memberships).clone().into(),
+            membership: Arc::clone(&handle.hotshot.memberships),
             drb_computations: BTreeMap::new(),
             output_event_stream: handle.hotshot.external_event_stream.0.clone(),
             id: handle.hotshot.id,
             storage: Arc::clone(&handle.storage),
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
             epoch_height: handle.hotshot.config.epoch_height,
+            checkpoint_policy: handle.hotshot.config.checkpoint_policy,
             consensus_metrics,
+            voting_warmup: VotingWarmup::new(
+                handle.cur_view().await,
+                handle.hotshot.config.warmup_views,
+            ),
+            max_undecided_views: handle.hotshot.config.max_undecided_views,
         }
     }
 }
@@ -257,7 +304,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             proposal_dependencies: BTreeMap::new(),
             consensus: OuterConsensus::new(consensus),
             instance_state: handle.hotshot.instance_state(),
-            quorum_membership: (*handle.hotshot.memberships).clone().into(),
+            quorum_membership: Arc::clone(&handle.hotshot.memberships),
             public_key: handle.public_key().clone(),
             private_key: handle.private_key().clone(),
             storage: Arc::clone(&handle.storage),
@@ -267,6 +314,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
             epoch_height: handle.hotshot.config.epoch_height,
             highest_qc: handle.hotshot.consensus.read().await.high_qc().clone(),
+            max_undecided_views: handle.hotshot.config.max_undecided_views,
         }
     }
 }
@@ -284,7 +332,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             consensus: OuterConsensus::new(consensus),
             cur_view: handle.cur_view().await,
             cur_epoch: handle.cur_epoch().await,
-            quorum_membership: (*handle.hotshot.memberships).clone().into(),
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
+            quorum_membership: Arc::clone(&handle.hotshot.memberships),
             timeout: handle.hotshot.config.next_view_timeout,
             output_event_stream: handle.hotshot.external_event_stream.0.clone(),
             storage: Arc::clone(&handle.storage),
@@ -292,6 +341,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             id: handle.hotshot.id,
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
             epoch_height: handle.hotshot.config.epoch_height,
+            checkpoint_policy: handle.hotshot.config.checkpoint_policy,
+            max_clock_skew: handle.hotshot.config.max_clock_skew,
         }
     }
 }
@@ -308,19 +359,24 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> CreateTaskState
             private_key: handle.private_key().clone(),
             instance_state: handle.hotshot.instance_state(),
             network: Arc::clone(&handle.hotshot.network),
-            membership: (*handle.hotshot.memberships).clone().into(),
+            membership: Arc::clone(&handle.hotshot.memberships),
             vote_collectors: BTreeMap::default(),
             timeout_vote_collectors: BTreeMap::default(),
             cur_view: handle.cur_view().await,
             cur_view_time: Utc::now().timestamp(),
             cur_epoch: handle.cur_epoch().await,
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
             output_event_stream: handle.hotshot.external_event_stream.0.clone(),
             timeout_task: spawn(async {}),
             timeout: handle.hotshot.config.next_view_timeout,
             consensus: OuterConsensus::new(consensus),
             id: handle.hotshot.id,
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
+            late_vote_grace: handle.hotshot.config.late_vote_grace,
+            vote_metrics: Arc::clone(&handle.hotshot.vote_metrics),
             epoch_height: handle.hotshot.config.epoch_height,
+            quorum_formation_timeout: handle.hotshot.config.quorum_formation_timeout,
+            abandoned_quorum_views: BTreeSet::new(),
         }
     }
 }
@@ -4,11 +4,11 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::{cmp::max, collections::BTreeMap, num::NonZeroU64};
+use std::{collections::BTreeMap, num::NonZeroU64};
 
 use hotshot_types::{
     traits::{
-        election::Membership,
+        election::{threshold_above, upgrade_threshold_for, LeaderProof, Membership},
         node_implementation::NodeType,
         signature_key::{SignatureKey, StakeTableEntryType},
     },
@@ -16,7 +16,7 @@ use hotshot_types::{
 };
 use primitive_types::U256;
 use rand::{rngs::StdRng, Rng};
-use utils::anytrace::Result;
+use utils::anytrace::*;
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 
@@ -217,6 +217,36 @@ impl<TYPES: NodeType> Membership<TYPES> for RandomizedCommittee<TYPES> {
         Ok(TYPES::SignatureKey::public_key(&res))
     }
 
+    /// Sign the seed of the view, binding the leader's claim to this view and committee
+    fn leader_proof(
+        &self,
+        view: TYPES::View,
+        epoch: <TYPES as NodeType>::Epoch,
+        private_key: &<TYPES::SignatureKey as SignatureKey>::PrivateKey,
+    ) -> Result<LeaderProof<TYPES::SignatureKey>> {
+        let signature = TYPES::SignatureKey::sign(private_key, &self.view_seed(view, epoch))
+            .wrap()
+            .context(error!("Failed to sign the leader proof for view {view}"))?;
+
+        Ok(LeaderProof::Signed(signature))
+    }
+
+    /// Check that `key` is the selected leader, and signed the seed of the view
+    fn verify_leader_proof(
+        &self,
+        view: TYPES::View,
+        epoch: <TYPES as NodeType>::Epoch,
+        key: &TYPES::SignatureKey,
+        proof: &LeaderProof<TYPES::SignatureKey>,
+    ) -> bool {
+        let LeaderProof::Signed(signature) = proof else {
+            return false;
+        };
+
+        self.leader(view, epoch).is_ok_and(|leader| leader == *key)
+            && key.validate(signature, &self.view_seed(view, epoch))
+    }
+
     /// Get the total number of nodes in the committee
     fn total_nodes(&self, _epoch: <TYPES as NodeType>::Epoch) -> usize {
         self.stake_table.len()
@@ -227,25 +257,21 @@ impl<TYPES: NodeType> Membership<TYPES> for RandomizedCommittee<TYPES> {
     }
     /// Get the voting success threshold for the committee
     fn success_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.stake_table.len() as u64 * 2) / 3) + 1).unwrap()
+        threshold_above(self.stake_table.len(), 2, 3).expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting success threshold for the committee
     fn da_success_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.da_stake_table.len() as u64 * 2) / 3) + 1).unwrap()
+        threshold_above(self.da_stake_table.len(), 2, 3).expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting failure threshold for the committee
     fn failure_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.stake_table.len() as u64) / 3) + 1).unwrap()
+        threshold_above(self.stake_table.len(), 1, 3).expect("1/3 is a valid threshold fraction")
     }
 
     /// Get the voting upgrade threshold for the committee
     fn upgrade_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(max(
-            (self.stake_table.len() as u64 * 9) / 10,
-            ((self.stake_table.len() as u64 * 2) / 3) + 1,
-        ))
-        .unwrap()
+        upgrade_threshold_for(self.stake_table.len())
     }
 }
@@ -5,7 +5,6 @@
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
 use std::{
-    cmp::max,
     collections::{BTreeMap, BTreeSet},
     marker::PhantomData,
     num::NonZeroU64,
@@ -13,7 +12,7 @@ use std::{
 
 use hotshot_types::{
     traits::{
-        election::Membership,
+        election::{threshold_above, upgrade_threshold_for, Membership},
         node_implementation::{ConsensusTime, NodeType},
         signature_key::{SignatureKey, StakeTableEntryType},
     },
@@ -330,24 +329,24 @@ impl<TYPES: NodeType, CONFIG: QuorumFilterConfig> Membership<TYPES>
     /// Get the voting success threshold for the committee
     fn success_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
         let len = self.total_nodes(epoch);
-        NonZeroU64::new(((len as u64 * 2) / 3) + 1).unwrap()
+        threshold_above(len, 2, 3).expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting success threshold for the committee
     fn da_success_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
         let len = self.da_total_nodes(epoch);
-        NonZeroU64::new(((len as u64 * 2) / 3) + 1).unwrap()
+        threshold_above(len, 2, 3).expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting failure threshold for the committee
     fn failure_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
         let len = self.total_nodes(epoch);
-        NonZeroU64::new(((len as u64) / 3) + 1).unwrap()
+        threshold_above(len, 1, 3).expect("1/3 is a valid threshold fraction")
     }
 
     /// Get the voting upgrade threshold for the committee
     fn upgrade_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
         let len = self.total_nodes(epoch);
-        NonZeroU64::new(max((len as u64 * 9) / 10, ((len as u64 * 2) / 3) + 1)).unwrap()
+        upgrade_threshold_for(len)
     }
 }
@@ -4,18 +4,21 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::{cmp::max, collections::BTreeMap, num::NonZeroU64};
+use std::{collections::BTreeMap, num::NonZeroU64};
 
 use hotshot_types::{
     traits::{
-        election::Membership,
+        election::{threshold_above, upgrade_threshold_for, Membership, MembershipChange},
         node_implementation::NodeType,
         signature_key::{SignatureKey, StakeTableEntryType},
     },
     PeerConfig,
 };
 use primitive_types::U256;
-use utils::anytrace::Result;
+use utils::anytrace::*;
+
+/// A stake table entry
+type StakeTableEntry<TYPES> = <<TYPES as NodeType>::SignatureKey as SignatureKey>::StakeTableEntry;
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 /// The static committee election
@@ -38,6 +41,277 @@ pub struct StaticCommittee<T: NodeType> {
     /// The nodes on the committee and their stake, indexed by public key
     indexed_da_stake_table:
         BTreeMap<T::SignatureKey, <T::SignatureKey as SignatureKey>::StakeTableEntry>,
+
+    /// Scheduled bonding and unbonding events, as the stake each node holds from a given view on.
+    /// Each committee keeps its own, so a reconfiguration starts without any.
+    stake_changes: BTreeMap<T::SignatureKey, BTreeMap<T::View, U256>>,
+
+    /// The most stake a single vote counts for towards a certificate, if capped
+    max_vote_weight: Option<u64>,
+
+    /// Whether view sync skips relays led by the leader of the view which failed
+    skip_failed_leader_relays: bool,
+
+    /// The committees replacing this one, by the epoch from which each is in effect
+    reconfigurations: BTreeMap<T::Epoch, StaticCommittee<T>>,
+}
+
+impl<TYPES: NodeType> StaticCommittee<TYPES> {
+    /// Schedule `pub_key`'s stake in `epoch` to become `stake` from `view_number` onwards, e.g.
+    /// to model stake which unbonds (`stake` of zero) at a future view.
+    ///
+    /// The change applies to the committee in effect in `epoch`, and so to every epoch until the
+    /// next reconfiguration, which does not inherit it.
+    pub fn schedule_stake_change(
+        &mut self,
+        pub_key: TYPES::SignatureKey,
+        epoch: TYPES::Epoch,
+        view_number: TYPES::View,
+        stake: U256,
+    ) {
+        self.for_epoch_mut(epoch)
+            .stake_changes
+            .entry(pub_key)
+            .or_default()
+            .insert(view_number, stake);
+    }
+
+    /// Cap the stake a single vote counts for towards a certificate at `max_vote_weight`, or lift
+    /// the cap with `None`. Every node of the network must set the same cap.
+    pub fn set_max_vote_weight(&mut self, max_vote_weight: Option<u64>) {
+        self.max_vote_weight = max_vote_weight;
+    }
+
+    /// Make view sync skip relays led by the leader of the view which failed. Every node of the
+    /// network must make the same choice.
+    pub fn set_skip_failed_leader_relays(&mut self, skip: bool) {
+        self.skip_failed_leader_relays = skip;
+    }
+
+    /// The stake changes scheduled for `pub_key` in `epoch`, as the stake it holds from each view
+    /// on
+    #[must_use]
+    pub fn scheduled_stake_changes(
+        &self,
+        pub_key: &TYPES::SignatureKey,
+        epoch: TYPES::Epoch,
+    ) -> Vec<(TYPES::View, U256)> {
+        self.for_epoch(epoch)
+            .stake_changes
+            .get(pub_key)
+            .map(|changes| {
+                changes
+                    .iter()
+                    .map(|(view, stake)| (*view, *stake))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The committee in effect in `epoch`
+    fn for_epoch(&self, epoch: TYPES::Epoch) -> &Self {
+        self.reconfigurations
+            .range(..=epoch)
+            .next_back()
+            .map_or(self, |(_, committee)| committee)
+    }
+
+    /// The committee in effect in `epoch`, mutably
+    fn for_epoch_mut(&mut self, epoch: TYPES::Epoch) -> &mut Self {
+        let Some(scheduled) = self
+            .reconfigurations
+            .range(..=epoch)
+            .next_back()
+            .map(|(scheduled, _)| *scheduled)
+        else {
+            return self;
+        };
+
+        self.reconfigurations
+            .get_mut(&scheduled)
+            .expect("the reconfiguration was just found")
+    }
+
+    /// Update the stake tables of this committee and their indices in place
+    fn apply_change_in_place(&mut self, change: MembershipChange<TYPES>) -> Result<()> {
+        match change {
+            MembershipChange::Add { peer, da } => {
+                let entry = peer.stake_table_entry;
+                let key = TYPES::SignatureKey::public_key(&entry);
+                ensure!(
+                    !self.indexed_stake_table.contains_key(&key)
+                        && !self.indexed_da_stake_table.contains_key(&key),
+                    error!("Cannot add {key}, it is already on the committee")
+                );
+
+                // Nodes without stake are left out, as when creating the committee
+                if entry.stake() == U256::zero() {
+                    return Ok(());
+                }
+
+                self.eligible_leaders.push(entry.clone());
+                if da {
+                    Self::insert_entry(
+                        &mut self.da_stake_table,
+                        &mut self.indexed_da_stake_table,
+                        entry.clone(),
+                    );
+                }
+                Self::insert_entry(&mut self.stake_table, &mut self.indexed_stake_table, entry);
+            }
+            MembershipChange::Remove(key) => {
+                let removed =
+                    Self::remove_entry(&mut self.stake_table, &mut self.indexed_stake_table, &key);
+                let removed_da = Self::remove_entry(
+                    &mut self.da_stake_table,
+                    &mut self.indexed_da_stake_table,
+                    &key,
+                );
+                ensure!(
+                    removed || removed_da,
+                    error!("Cannot remove {key}, it is not on the committee")
+                );
+                self.eligible_leaders
+                    .retain(|entry| TYPES::SignatureKey::public_key(entry) != key);
+            }
+            MembershipChange::UpdateStake { key, stake: 0 } => {
+                self.apply_change_in_place(MembershipChange::Remove(key))?;
+            }
+            MembershipChange::UpdateStake { key, stake } => {
+                ensure!(
+                    self.indexed_stake_table.contains_key(&key)
+                        || self.indexed_da_stake_table.contains_key(&key),
+                    error!("Cannot update the stake of {key}, it is not on the committee")
+                );
+
+                let entry = key.stake_table_entry(stake);
+                Self::replace_entry(
+                    &mut self.stake_table,
+                    &mut self.indexed_stake_table,
+                    &key,
+                    &entry,
+                );
+                Self::replace_entry(
+                    &mut self.da_stake_table,
+                    &mut self.indexed_da_stake_table,
+                    &key,
+                    &entry,
+                );
+                if let Some(leader) = self
+                    .eligible_leaders
+                    .iter_mut()
+                    .find(|leader| TYPES::SignatureKey::public_key(leader) == key)
+                {
+                    *leader = entry;
+                }
+            }
+            MembershipChange::RotateKey { key, new_key } => {
+                ensure!(
+                    !self.indexed_stake_table.contains_key(&new_key)
+                        && !self.indexed_da_stake_table.contains_key(&new_key),
+                    error!("Cannot rotate {key} to {new_key}, it is already on the committee")
+                );
+
+                let rotated = Self::rotate_entry(
+                    &mut self.stake_table,
+                    &mut self.indexed_stake_table,
+                    &key,
+                    &new_key,
+                );
+                let rotated_da = Self::rotate_entry(
+                    &mut self.da_stake_table,
+                    &mut self.indexed_da_stake_table,
+                    &key,
+                    &new_key,
+                );
+                ensure!(
+                    rotated || rotated_da,
+                    error!("Cannot rotate {key}, it is not on the committee")
+                );
+
+                if let Some(leader) = self
+                    .eligible_leaders
+                    .iter_mut()
+                    .find(|leader| TYPES::SignatureKey::public_key(leader) == key)
+                {
+                    *leader = new_key
+                        .stake_table_entry(u64::try_from(leader.stake()).unwrap_or(u64::MAX));
+                }
+                if let Some(changes) = self.stake_changes.remove(&key) {
+                    self.stake_changes.insert(new_key, changes);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Append `entry` to `stake_table` and add it to its index, leaving the signer positions of
+    /// the existing members unchanged
+    fn insert_entry(
+        stake_table: &mut Vec<StakeTableEntry<TYPES>>,
+        indexed_stake_table: &mut BTreeMap<TYPES::SignatureKey, StakeTableEntry<TYPES>>,
+        entry: StakeTableEntry<TYPES>,
+    ) {
+        indexed_stake_table.insert(TYPES::SignatureKey::public_key(&entry), entry.clone());
+        stake_table.push(entry);
+    }
+
+    /// Remove `key` from `stake_table` and its index, returning whether it was present
+    fn remove_entry(
+        stake_table: &mut Vec<StakeTableEntry<TYPES>>,
+        indexed_stake_table: &mut BTreeMap<TYPES::SignatureKey, StakeTableEntry<TYPES>>,
+        key: &TYPES::SignatureKey,
+    ) -> bool {
+        if indexed_stake_table.remove(key).is_none() {
+            return false;
+        }
+        stake_table.retain(|entry| TYPES::SignatureKey::public_key(entry) != *key);
+
+        true
+    }
+
+    /// Move the entry for `key` in `stake_table` and its index over to `new_key`, keeping its
+    /// stake and position, and return whether it was present
+    fn rotate_entry(
+        stake_table: &mut [StakeTableEntry<TYPES>],
+        indexed_stake_table: &mut BTreeMap<TYPES::SignatureKey, StakeTableEntry<TYPES>>,
+        key: &TYPES::SignatureKey,
+        new_key: &TYPES::SignatureKey,
+    ) -> bool {
+        let Some(entry) = indexed_stake_table.remove(key) else {
+            return false;
+        };
+        let entry = new_key.stake_table_entry(u64::try_from(entry.stake()).unwrap_or(u64::MAX));
+        if let Some(position) = stake_table
+            .iter()
+            .position(|existing| TYPES::SignatureKey::public_key(existing) == *key)
+        {
+            stake_table[position].clone_from(&entry);
+        }
+        indexed_stake_table.insert(new_key.clone(), entry);
+
+        true
+    }
+
+    /// Replace the entry for `key` in `stake_table` and its index, if present
+    fn replace_entry(
+        stake_table: &mut [StakeTableEntry<TYPES>],
+        indexed_stake_table: &mut BTreeMap<TYPES::SignatureKey, StakeTableEntry<TYPES>>,
+        key: &TYPES::SignatureKey,
+        entry: &StakeTableEntry<TYPES>,
+    ) {
+        let Some(indexed) = indexed_stake_table.get_mut(key) else {
+            return;
+        };
+        indexed.clone_from(entry);
+        if let Some(position) = stake_table
+            .iter()
+            .position(|existing| TYPES::SignatureKey::public_key(existing) == *key)
+        {
+            stake_table[position].clone_from(entry);
+        }
+    }
 }
 
 impl<TYPES: NodeType> Membership<TYPES> for StaticCommittee<TYPES> {
@@ -95,32 +369,37 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommittee<TYPES> {
             da_stake_table: da_members,
             indexed_stake_table,
             indexed_da_stake_table,
+            stake_changes: BTreeMap::new(),
+            max_vote_weight: None,
+            skip_failed_leader_relays: false,
+            reconfigurations: BTreeMap::new(),
         }
     }
 
     /// Get the stake table for the current view
     fn stake_table(
         &self,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> Vec<<<TYPES as NodeType>::SignatureKey as SignatureKey>::StakeTableEntry> {
-        self.stake_table.clone()
+        self.for_epoch(epoch).stake_table.clone()
     }
 
     /// Get the stake table for the current view
     fn da_stake_table(
         &self,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> Vec<<<TYPES as NodeType>::SignatureKey as SignatureKey>::StakeTableEntry> {
-        self.da_stake_table.clone()
+        self.for_epoch(epoch).da_stake_table.clone()
     }
 
     /// Get all members of the committee for the current view
     fn committee_members(
         &self,
         _view_number: <TYPES as NodeType>::View,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> std::collections::BTreeSet<<TYPES as NodeType>::SignatureKey> {
-        self.stake_table
+        self.for_epoch(epoch)
+            .stake_table
             .iter()
             .map(TYPES::SignatureKey::public_key)
             .collect()
@@ -130,9 +409,10 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommittee<TYPES> {
     fn da_committee_members(
         &self,
         _view_number: <TYPES as NodeType>::View,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> std::collections::BTreeSet<<TYPES as NodeType>::SignatureKey> {
-        self.da_stake_table
+        self.for_epoch(epoch)
+            .da_stake_table
             .iter()
             .map(TYPES::SignatureKey::public_key)
             .collect()
@@ -142,9 +422,10 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommittee<TYPES> {
     fn committee_leaders(
         &self,
         _view_number: <TYPES as NodeType>::View,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> std::collections::BTreeSet<<TYPES as NodeType>::SignatureKey> {
-        self.eligible_leaders
+        self.for_epoch(epoch)
+            .eligible_leaders
             .iter()
             .map(TYPES::SignatureKey::public_key)
             .collect()
@@ -154,29 +435,65 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommittee<TYPES> {
     fn stake(
         &self,
         pub_key: &<TYPES as NodeType>::SignatureKey,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> Option<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry> {
         // Only return the stake if it is above zero
-        self.indexed_stake_table.get(pub_key).cloned()
+        self.for_epoch(epoch)
+            .indexed_stake_table
+            .get(pub_key)
+            .cloned()
     }
 
     /// Get the DA stake table entry for a public key
     fn da_stake(
         &self,
         pub_key: &<TYPES as NodeType>::SignatureKey,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> Option<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry> {
         // Only return the stake if it is above zero
-        self.indexed_da_stake_table.get(pub_key).cloned()
+        self.for_epoch(epoch)
+            .indexed_da_stake_table
+            .get(pub_key)
+            .cloned()
+    }
+
+    /// Get the stake of a node at the given view, applying any scheduled stake changes
+    fn effective_stake_at_view(
+        &self,
+        pub_key: &<TYPES as NodeType>::SignatureKey,
+        view_number: <TYPES as NodeType>::View,
+        epoch: <TYPES as NodeType>::Epoch,
+    ) -> U256 {
+        let committee = self.for_epoch(epoch);
+        let Some(entry) = committee.indexed_stake_table.get(pub_key) else {
+            return U256::zero();
+        };
+
+        committee
+            .stake_changes
+            .get(pub_key)
+            .and_then(|changes| changes.range(..=view_number).next_back())
+            .map_or(entry.stake(), |(_, stake)| *stake)
+    }
+
+    /// Get the cap set with [`StaticCommittee::set_max_vote_weight`]
+    fn max_vote_weight(&self) -> Option<u64> {
+        self.max_vote_weight
+    }
+
+    /// Get the choice made with [`StaticCommittee::set_skip_failed_leader_relays`]
+    fn skip_failed_leader_relays(&self) -> bool {
+        self.skip_failed_leader_relays
     }
 
     /// Check if a node has stake in the committee
     fn has_stake(
         &self,
         pub_key: &<TYPES as NodeType>::SignatureKey,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> bool {
-        self.indexed_stake_table
+        self.for_epoch(epoch)
+            .indexed_stake_table
             .get(pub_key)
             .is_some_and(|x| x.stake() > U256::zero())
     }
@@ -185,9 +502,10 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommittee<TYPES> {
     fn has_da_stake(
         &self,
         pub_key: &<TYPES as NodeType>::SignatureKey,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> bool {
-        self.indexed_da_stake_table
+        self.for_epoch(epoch)
+            .indexed_da_stake_table
             .get(pub_key)
             .is_some_and(|x| x.stake() > U256::zero())
     }
@@ -196,42 +514,77 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommittee<TYPES> {
     fn lookup_leader(
         &self,
         view_number: TYPES::View,
-        _epoch: <TYPES as NodeType>::Epoch,
+        epoch: <TYPES as NodeType>::Epoch,
     ) -> Result<TYPES::SignatureKey> {
+        let committee = self.for_epoch(epoch);
         #[allow(clippy::cast_possible_truncation)]
-        let index = *view_number as usize % self.eligible_leaders.len();
-        let res = self.eligible_leaders[index].clone();
+        let index = *view_number as usize % committee.eligible_leaders.len();
+        let res = committee.eligible_leaders[index].clone();
         Ok(TYPES::SignatureKey::public_key(&res))
     }
 
     /// Get the total number of nodes in the committee
-    fn total_nodes(&self, _epoch: <TYPES as NodeType>::Epoch) -> usize {
-        self.stake_table.len()
+    fn total_nodes(&self, epoch: <TYPES as NodeType>::Epoch) -> usize {
+        self.for_epoch(epoch).stake_table.len()
     }
 
     /// Get the total number of DA nodes in the committee
-    fn da_total_nodes(&self, _epoch: <TYPES as NodeType>::Epoch) -> usize {
-        self.da_stake_table.len()
+    fn da_total_nodes(&self, epoch: <TYPES as NodeType>::Epoch) -> usize {
+        self.for_epoch(epoch).da_stake_table.len()
     }
 
     /// Get the voting success threshold for the committee
-    fn success_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.stake_table.len() as u64 * 2) / 3) + 1).unwrap()
+    fn success_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
+        threshold_above(self.for_epoch(epoch).stake_table.len(), 2, 3)
+            .expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting success threshold for the committee
-    fn da_success_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.da_stake_table.len() as u64 * 2) / 3) + 1).unwrap()
+    fn da_success_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
+        threshold_above(self.for_epoch(epoch).da_stake_table.len(), 2, 3)
+            .expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting failure threshold for the committee
-    fn failure_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.stake_table.len() as u64) / 3) + 1).unwrap()
+    fn failure_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
+        threshold_above(self.for_epoch(epoch).stake_table.len(), 1, 3)
+            .expect("1/3 is a valid threshold fraction")
     }
 
     /// Get the voting upgrade threshold for the committee
-    fn upgrade_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        let len = self.stake_table.len();
-        NonZeroU64::new(max((len as u64 * 9) / 10, ((len as u64 * 2) / 3) + 1)).unwrap()
+    fn upgrade_threshold(&self, epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
+        let len = self.for_epoch(epoch).stake_table.len();
+        upgrade_threshold_for(len)
+    }
+
+    /// Replace the committee from `epoch` onwards, dropping any reconfiguration scheduled for a
+    /// later epoch. Earlier epochs keep their committee.
+    fn reconfigure(
+        &mut self,
+        epoch: <TYPES as NodeType>::Epoch,
+        committee_members: Vec<PeerConfig<<TYPES as NodeType>::SignatureKey>>,
+        da_members: Vec<PeerConfig<<TYPES as NodeType>::SignatureKey>>,
+    ) -> Result<()> {
+        let committee = Self::try_new(committee_members, da_members)
+            .context(error!("Invalid committee for epoch {epoch}"))?;
+        ensure!(
+            !committee.stake_table.is_empty(),
+            error!("The committee for epoch {epoch} holds no stake")
+        );
+
+        self.reconfigurations
+            .retain(|scheduled, _| *scheduled < epoch);
+        self.reconfigurations.insert(epoch, committee);
+
+        Ok(())
+    }
+
+    /// Update the stake tables of the most recent committee in place. Thresholds are derived from
+    /// the table sizes, so they follow automatically.
+    fn apply_change(&mut self, change: MembershipChange<TYPES>) -> Result<()> {
+        match self.reconfigurations.last_entry() {
+            Some(mut latest) => latest.get_mut().apply_change_in_place(change),
+            None => self.apply_change_in_place(change),
+        }
     }
 }
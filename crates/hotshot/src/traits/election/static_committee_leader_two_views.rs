@@ -8,7 +8,7 @@ use std::{collections::BTreeMap, num::NonZeroU64};
 
 use hotshot_types::{
     traits::{
-        election::Membership,
+        election::{threshold_above, Membership},
         node_implementation::NodeType,
         signature_key::{SignatureKey, StakeTableEntryType},
     },
@@ -39,6 +39,17 @@ pub struct StaticCommitteeLeaderForTwoViews<T: NodeType> {
     /// The nodes on the committee and their stake, indexed by public key
     indexed_da_stake_table:
         BTreeMap<T::SignatureKey, <T::SignatureKey as SignatureKey>::StakeTableEntry>,
+
+    /// Whether view sync skips relays led by the leader of the view which failed
+    skip_failed_leader_relays: bool,
+}
+
+impl<TYPES: NodeType> StaticCommitteeLeaderForTwoViews<TYPES> {
+    /// Make view sync skip relays led by the leader of the view which failed. Every node of the
+    /// network must make the same choice.
+    pub fn set_skip_failed_leader_relays(&mut self, skip: bool) {
+        self.skip_failed_leader_relays = skip;
+    }
 }
 
 impl<TYPES: NodeType> Membership<TYPES> for StaticCommitteeLeaderForTwoViews<TYPES> {
@@ -96,9 +107,16 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommitteeLeaderForTwoViews<TYP
             da_stake_table: da_members,
             indexed_stake_table,
             indexed_da_stake_table,
+            skip_failed_leader_relays: false,
         }
     }
 
+    /// Get the choice made with
+    /// [`StaticCommitteeLeaderForTwoViews::set_skip_failed_leader_relays`]
+    fn skip_failed_leader_relays(&self) -> bool {
+        self.skip_failed_leader_relays
+    }
+
     /// Get the stake table for the current view
     fn stake_table(
         &self,
@@ -218,21 +236,21 @@ impl<TYPES: NodeType> Membership<TYPES> for StaticCommitteeLeaderForTwoViews<TYP
 
     /// Get the voting success threshold for the committee
     fn success_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.stake_table.len() as u64 * 2) / 3) + 1).unwrap()
+        threshold_above(self.stake_table.len(), 2, 3).expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting success threshold for the committee
     fn da_success_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.da_stake_table.len() as u64 * 2) / 3) + 1).unwrap()
+        threshold_above(self.da_stake_table.len(), 2, 3).expect("2/3 is a valid threshold fraction")
     }
 
     /// Get the voting failure threshold for the committee
     fn failure_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.stake_table.len() as u64) / 3) + 1).unwrap()
+        threshold_above(self.stake_table.len(), 1, 3).expect("1/3 is a valid threshold fraction")
     }
 
     /// Get the voting upgrade threshold for the committee
     fn upgrade_threshold(&self, _epoch: <TYPES as NodeType>::Epoch) -> NonZeroU64 {
-        NonZeroU64::new(((self.stake_table.len() as u64 * 9) / 10) + 1).unwrap()
+        threshold_above(self.stake_table.len(), 9, 10).expect("9/10 is a valid threshold fraction")
     }
 }
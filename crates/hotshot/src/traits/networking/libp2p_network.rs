@@ -43,6 +43,7 @@ use hotshot_types::{
         node_implementation::{ConsensusTime, NodeType},
         signature_key::{PrivateSignatureKey, SignatureKey},
     },
+    vid::ProtocolSchemes,
     BoxSyncFuture,
 };
 use libp2p_identity::{
@@ -84,6 +85,8 @@ pub struct Libp2pMetricsValue {
     pub num_failed_messages: Box<dyn Counter>,
     /// Whether or not the network is considered ready
     pub is_ready: Box<dyn Gauge>,
+    /// The number of direct messages delayed or dropped by the per-peer rate limit
+    pub num_throttled_messages: Box<dyn Gauge>,
 }
 
 impl Libp2pMetricsValue {
@@ -97,6 +100,7 @@ impl Libp2pMetricsValue {
             num_connected_peers: subgroup.create_gauge("num_connected_peers".into(), None),
             num_failed_messages: subgroup.create_counter("num_failed_messages".into(), None),
             is_ready: subgroup.create_gauge("is_ready".into(), None),
+            num_throttled_messages: subgroup.create_gauge("num_throttled_messages".into(), None),
         }
     }
 }
@@ -419,10 +423,11 @@ impl<T: NodeType> Libp2pNetwork<T> {
             construct_auth_message(pub_key, &keypair.public().to_peer_id(), priv_key)
                 .with_context(|| "Failed to construct auth message")?;
 
-        // Set the auth message and stake table
+        // Set the auth message, stake table and the schemes peers must share
         config_builder
             .stake_table(Some(quorum_membership))
-            .auth_message(Some(auth_message));
+            .auth_message(Some(auth_message))
+            .schemes(Some(ProtocolSchemes::current()));
 
         // The replication factor is the minimum of [the default and 2/3 the number of nodes]
         let Some(default_replication_factor) = DEFAULT_REPLICATION_FACTOR else {
@@ -672,6 +677,13 @@ impl<T: NodeType> Libp2pNetwork<T> {
                     NetworkError::ChannelSendError(format!("failed to send gossip message: {err}"))
                 })?;
             }
+            NetworkEvent::RelayedRequest(msg, _pid) => {
+                sender.try_send(msg).map_err(|err| {
+                    NetworkError::ChannelSendError(format!(
+                        "failed to send relayed request message: {err}"
+                    ))
+                })?;
+            }
             DirectRequest(msg, _pid, chan) => {
                 sender.try_send(msg).map_err(|err| {
                     NetworkError::ChannelSendError(format!(
@@ -728,7 +740,10 @@ impl<T: NodeType> Libp2pNetwork<T> {
                             NetworkEvent::IsBootstrapped => {
                                 is_bootstrapped.store(true, Ordering::Relaxed);
                             }
-                            GossipMsg(_) | DirectRequest(_, _, _) | DirectResponse(_, _) => {
+                            GossipMsg(_)
+                            | DirectRequest(_, _, _)
+                            | DirectResponse(_, _)
+                            | NetworkEvent::RelayedRequest(_, _) => {
                                 let _ = handle.handle_recvd_events(message, &sender);
                             }
                             NetworkEvent::ConnectedPeersUpdate(num_peers) => {
@@ -745,6 +760,87 @@ impl<T: NodeType> Libp2pNetwork<T> {
             }
         });
     }
+
+    /// Send `message` directly to `recipient`, waiting for it to acknowledge the message if
+    /// `acked` is set
+    async fn send_direct(
+        &self,
+        message: Vec<u8>,
+        recipient: T::SignatureKey,
+        acked: bool,
+    ) -> Result<(), NetworkError> {
+        // If we're not ready, return an error
+        if !self.is_ready() {
+            self.inner.metrics.num_failed_messages.add(1);
+            return Err(NetworkError::NotReadyYet);
+        };
+
+        // short circuit if we're dming ourselves
+        if recipient == self.inner.pk {
+            // panic if we already shut down?
+            self.inner.sender.try_send(message).map_err(|_x| {
+                self.inner.metrics.num_failed_messages.add(1);
+                NetworkError::ShutDown
+            })?;
+            return Ok(());
+        }
+
+        let pid = match self
+            .inner
+            .handle
+            .lookup_node(&recipient.to_bytes(), self.inner.dht_timeout)
+            .await
+        {
+            Ok(pid) => pid,
+            Err(err) => {
+                self.inner.metrics.num_failed_messages.add(1);
+                return Err(NetworkError::LookupError(format!(
+                    "failed to look up node for direct message: {err}"
+                )));
+            }
+        };
+
+        #[cfg(feature = "hotshot-testing")]
+        {
+            let metrics = self.inner.metrics.clone();
+            if let Some(ref config) = &self.inner.reliability_config {
+                let handle = Arc::clone(&self.inner.handle);
+
+                let fut = config.clone().chaos_send_msg(
+                    message,
+                    Arc::new(move |msg: Vec<u8>| {
+                        let handle_2 = Arc::clone(&handle);
+                        let metrics_2 = metrics.clone();
+                        boxed_sync(async move {
+                            if let Err(e) = handle_2.direct_request_no_serialize(pid, msg).await {
+                                metrics_2.num_failed_messages.add(1);
+                                warn!("Failed to broadcast to libp2p: {:?}", e);
+                            }
+                        })
+                    }),
+                );
+                spawn(fut);
+                return Ok(());
+            }
+        }
+
+        let result = if acked {
+            self.inner.handle.direct_request_acked(pid, message).await
+        } else {
+            self.inner.handle.direct_request(pid, &message).await
+        };
+        self.inner
+            .metrics
+            .num_throttled_messages
+            .set(usize::try_from(self.inner.handle.throttled_messages()).unwrap_or(usize::MAX));
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.inner.metrics.num_failed_messages.add(1);
+                Err(e)
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -872,68 +968,16 @@ impl<T: NodeType> ConnectedNetwork<T::SignatureKey> for Libp2pNetwork<T> {
         message: Vec<u8>,
         recipient: T::SignatureKey,
     ) -> Result<(), NetworkError> {
-        // If we're not ready, return an error
-        if !self.is_ready() {
-            self.inner.metrics.num_failed_messages.add(1);
-            return Err(NetworkError::NotReadyYet);
-        };
-
-        // short circuit if we're dming ourselves
-        if recipient == self.inner.pk {
-            // panic if we already shut down?
-            self.inner.sender.try_send(message).map_err(|_x| {
-                self.inner.metrics.num_failed_messages.add(1);
-                NetworkError::ShutDown
-            })?;
-            return Ok(());
-        }
-
-        let pid = match self
-            .inner
-            .handle
-            .lookup_node(&recipient.to_bytes(), self.inner.dht_timeout)
-            .await
-        {
-            Ok(pid) => pid,
-            Err(err) => {
-                self.inner.metrics.num_failed_messages.add(1);
-                return Err(NetworkError::LookupError(format!(
-                    "failed to look up node for direct message: {err}"
-                )));
-            }
-        };
-
-        #[cfg(feature = "hotshot-testing")]
-        {
-            let metrics = self.inner.metrics.clone();
-            if let Some(ref config) = &self.inner.reliability_config {
-                let handle = Arc::clone(&self.inner.handle);
-
-                let fut = config.clone().chaos_send_msg(
-                    message,
-                    Arc::new(move |msg: Vec<u8>| {
-                        let handle_2 = Arc::clone(&handle);
-                        let metrics_2 = metrics.clone();
-                        boxed_sync(async move {
-                            if let Err(e) = handle_2.direct_request_no_serialize(pid, msg) {
-                                metrics_2.num_failed_messages.add(1);
-                                warn!("Failed to broadcast to libp2p: {:?}", e);
-                            }
-                        })
-                    }),
-                );
-                spawn(fut);
-                return Ok(());
-            }
-        }
+        self.send_direct(message, recipient, false).await
+    }
 
-        match self.inner.handle.direct_request(pid, &message) {
-            Ok(()) => Ok(()),
-            Err(e) => {
-                self.inner.metrics.num_failed_messages.add(1);
-                Err(e)
-            }
-        }
+    #[instrument(name = "Libp2pNetwork::direct_message_acked", skip_all)]
+    async fn direct_message_acked(
+        &self,
+        message: Vec<u8>,
+        recipient: T::SignatureKey,
+    ) -> Result<(), NetworkError> {
+        self.send_direct(message, recipient, true).await
     }
 
     /// Receive one or many messages from the underlying network.
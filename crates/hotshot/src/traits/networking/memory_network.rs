@@ -13,7 +13,7 @@ use core::time::Duration;
 use std::{
     fmt::Debug,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
 };
@@ -81,6 +81,9 @@ struct MemoryNetworkInner<K: SignatureKey> {
 
     /// config to introduce unreliability to the network
     reliability_config: Option<Box<dyn NetworkReliability>>,
+
+    /// Whether the network is paused, dropping everything sent to or from it
+    paused: AtomicBool,
 }
 
 /// In memory only network simulator.
@@ -145,6 +148,7 @@ impl<K: SignatureKey> MemoryNetwork<K> {
                 master_map: Arc::clone(master_map),
                 in_flight_message_count,
                 reliability_config,
+                paused: AtomicBool::new(false),
             }),
         };
         // Insert our public key into the master map
@@ -161,8 +165,17 @@ impl<K: SignatureKey> MemoryNetwork<K> {
         mn
     }
 
-    /// Send a [`Vec<u8>`] message to the inner `input`
+    /// Whether the network is paused
+    fn is_paused(&self) -> bool {
+        self.inner.paused.load(Ordering::Relaxed)
+    }
+
+    /// Send a [`Vec<u8>`] message to the inner `input`, dropping it if we are paused
     async fn input(&self, message: Vec<u8>) -> Result<(), SendError<Vec<u8>>> {
+        if self.is_paused() {
+            trace!("Network paused, dropping incoming message");
+            return Ok(());
+        }
         self.inner
             .in_flight_message_count
             .fetch_add(1, Ordering::Relaxed);
@@ -223,11 +236,11 @@ impl<K: SignatureKey + 'static> ConnectedNetwork<K> for MemoryNetwork<K> {
     async fn wait_for_ready(&self) {}
 
     fn pause(&self) {
-        unimplemented!("Pausing not implemented for the Memory network");
+        self.inner.paused.store(true, Ordering::Relaxed);
     }
 
     fn resume(&self) {
-        unimplemented!("Resuming not implemented for the Memory network");
+        self.inner.paused.store(false, Ordering::Relaxed);
     }
 
     #[instrument(name = "MemoryNetwork::shut_down")]
@@ -249,6 +262,10 @@ impl<K: SignatureKey + 'static> ConnectedNetwork<K> for MemoryNetwork<K> {
         topic: Topic,
         _broadcast_delay: BroadcastDelay,
     ) -> Result<(), NetworkError> {
+        if self.is_paused() {
+            trace!("Network paused, dropping broadcast message");
+            return Ok(());
+        }
         trace!(?message, "Broadcasting message");
         for node in self
             .inner
@@ -299,6 +316,10 @@ impl<K: SignatureKey + 'static> ConnectedNetwork<K> for MemoryNetwork<K> {
         recipients: Vec<K>,
         _broadcast_delay: BroadcastDelay,
     ) -> Result<(), NetworkError> {
+        if self.is_paused() {
+            trace!("Network paused, dropping DA broadcast message");
+            return Ok(());
+        }
         trace!(?message, "Broadcasting message to DA");
         for node in self
             .inner
@@ -348,6 +369,10 @@ impl<K: SignatureKey + 'static> ConnectedNetwork<K> for MemoryNetwork<K> {
 
     #[instrument(name = "MemoryNetwork::direct_message")]
     async fn direct_message(&self, message: Vec<u8>, recipient: K) -> Result<(), NetworkError> {
+        if self.is_paused() {
+            trace!("Network paused, dropping direct message");
+            return Ok(());
+        }
         // debug!(?message, ?recipient, "Sending direct message");
         // Bincode the message
         trace!("Message bincoded, finding recipient");
@@ -10,14 +10,15 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Ok, Result};
 use async_broadcast::{InactiveReceiver, Receiver, Sender};
-use async_lock::RwLock;
+use async_lock::{RwLock, RwLockWriteGuard};
 use committable::{Commitment, Committable};
 use futures::Stream;
 use hotshot_task::{
     dependency::{Dependency, EventDependency},
+    queue::{EventQueue, QueueDepth},
     task::{ConsensusTaskRegistry, NetworkTaskRegistry, Task, TaskState},
 };
-use hotshot_task_impls::{events::HotShotEvent, helpers::broadcast_event};
+use hotshot_task_impls::{events::HotShotEvent, helpers::broadcast_event, network::tag_network_id};
 use hotshot_types::{
     consensus::Consensus,
     data::{Leaf2, QuorumProposal2},
@@ -32,6 +33,7 @@ use hotshot_types::{
         signature_key::SignatureKey,
     },
     vote::HasViewNumber,
+    vote_metrics::ExchangeMetrics,
 };
 use tracing::instrument;
 
@@ -68,8 +70,8 @@ pub struct SystemContextHandle<TYPES: NodeType, I: NodeImplementation<TYPES>, V:
     /// Networks used by the instance of hotshot
     pub network: Arc<I::Network>,
 
-    /// Memberships used by consensus
-    pub memberships: Arc<TYPES::Membership>,
+    /// Memberships used by consensus, shared with every task of this node
+    pub memberships: Arc<RwLock<TYPES::Membership>>,
 
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
@@ -89,6 +91,27 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
         self.consensus_registry.run_task(task);
     }
 
+    /// Adds a hotshot consensus-related task fed through a queue of at most `capacity` events,
+    /// tracking how far behind it falls in `depth`. `report` is passed the depth each event
+    /// arrives at.
+    pub fn add_queued_task<S: TaskState<Event = HotShotEvent<TYPES>> + 'static>(
+        &mut self,
+        task_state: S,
+        capacity: usize,
+        depth: Arc<QueueDepth>,
+        report: impl Fn(usize) + Send + 'static,
+    ) {
+        let (queue, receiver) = EventQueue::new(
+            self.internal_event_stream.1.activate_cloned(),
+            capacity,
+            depth,
+        );
+        self.network_registry.register(queue.run(report));
+
+        let task = Task::new(task_state, self.internal_event_stream.0.clone(), receiver);
+        self.consensus_registry.run_task(task);
+    }
+
     /// obtains a stream to expose to the user
     pub fn event_stream(&self) -> impl Stream<Item = Event<TYPES>> {
         self.output_event_stream.1.activate_cloned()
@@ -109,7 +132,10 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
             sender: self.public_key().clone(),
             kind: MessageKind::External(msg),
         };
-        let serialized_message = self.hotshot.upgrade_lock.serialize(&message).await?;
+        let serialized_message = tag_network_id(
+            self.hotshot.config.network_id,
+            self.hotshot.upgrade_lock.serialize(&message).await?,
+        );
 
         match recipients {
             RecipientList::Broadcast => {
@@ -157,7 +183,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
             signed_proposal_request.commit().as_ref(),
         )?;
 
-        let mem = (*self.memberships).clone();
+        let mem = Arc::clone(&self.memberships);
+        let upgrade_lock = self.hotshot.upgrade_lock.clone();
         let receiver = self.internal_event_stream.1.activate_cloned();
         let sender = self.internal_event_stream.0.clone();
         Ok(async move {
@@ -187,7 +214,12 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
                 if let HotShotEvent::QuorumProposalResponseRecv(quorum_proposal) = hs_event.as_ref()
                 {
                     // Make sure that the quorum_proposal is valid
-                    if let Err(err) = quorum_proposal.validate_signature(&mem, epoch) {
+                    let membership_reader = mem.read().await;
+                    let validation = quorum_proposal
+                        .validate_signature(&membership_reader, epoch, &upgrade_lock)
+                        .await;
+                    drop(membership_reader);
+                    if let Err(err) = validation {
                         tracing::warn!("Invalid Proposal Received after Request.  Err {:?}", err);
                         continue;
                     }
@@ -308,6 +340,31 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
         self.consensus_registry.shutdown().await;
     }
 
+    /// Stop voting, proposing and forming certificates, e.g. for maintenance, without shutting
+    /// down.
+    ///
+    /// The node keeps following the chain, relaying messages and serving payloads meanwhile.
+    pub fn pause_participation(&self) {
+        tracing::warn!("Pausing consensus participation");
+        self.hotshot.participation.pause();
+    }
+
+    /// Take part in consensus again after [`Self::pause_participation`].
+    ///
+    /// The node re-engages from the view after the one it is in now, once it has caught up with
+    /// the rest of the network. A node configured as a read replica stays paused.
+    pub async fn resume_participation(&self) {
+        let cur_view = self.cur_view().await;
+        tracing::warn!("Resuming consensus participation after view {cur_view:?}");
+        self.hotshot.participation.resume(*cur_view);
+    }
+
+    /// Whether consensus participation is paused
+    #[must_use]
+    pub fn is_participation_paused(&self) -> bool {
+        self.hotshot.participation.is_paused()
+    }
+
     /// return the timeout for a view of the underlying `SystemContext`
     #[must_use]
     pub fn next_view_timeout(&self) -> u64 {
@@ -318,7 +375,6 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
     ///
     /// # Errors
     /// Returns an error if the leader cannot be calculated
-    #[allow(clippy::unused_async)] // async for API compatibility reasons
     pub async fn leader(
         &self,
         view_number: TYPES::View,
@@ -326,10 +382,23 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
     ) -> Result<TYPES::SignatureKey> {
         self.hotshot
             .memberships
+            .read()
+            .await
             .leader(view_number, epoch_number)
             .context("Failed to lookup leader")
     }
 
+    /// Get mutable access to the membership shared by every task of this node, e.g. to apply a
+    /// `MembershipChange` or reconfigure a later epoch while consensus is running.
+    ///
+    /// Tasks see the change on their next membership lookup, so it applies from whatever view each
+    /// task is handling at that point, and votes or certificates already in flight are checked
+    /// against the changed committee. Prefer changes scoped to a later epoch, and otherwise apply
+    /// them between views. Tasks wait on the lock while the guard is held, so drop it promptly.
+    pub async fn membership_mut(&self) -> RwLockWriteGuard<'_, TYPES::Membership> {
+        self.memberships.write().await
+    }
+
     // Below is for testing only:
     /// Wrapper to get this node's public key
     #[cfg(feature = "hotshot-testing")]
@@ -364,6 +433,15 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions>
         self.hotshot.consensus.read().await.cur_epoch()
     }
 
+    /// Snapshot of the metrics of the votes this node has accumulated, which can be rendered for
+    /// Prometheus with [`ExchangeMetrics::to_prometheus`].
+    ///
+    /// This only reads counters, so it is cheap to call and never blocks consensus.
+    #[must_use]
+    pub fn export_metrics(&self) -> ExchangeMetrics {
+        self.hotshot.vote_metrics.export()
+    }
+
     /// Provides a reference to the underlying storage for this [`SystemContext`], allowing access to
     /// historical data
     #[must_use]
@@ -0,0 +1,40 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use async_trait::async_trait;
+use libp2p::{core::ConnectedPoint, Multiaddr};
+use libp2p_identity::PeerId;
+
+/// What is known about a connection once its transport handshake has completed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Handshake {
+    /// The address of the remote peer
+    pub remote_addr: Multiaddr,
+    /// Whether we dialed the connection, rather than the remote peer
+    pub outgoing: bool,
+}
+
+impl Handshake {
+    /// The handshake of a connection established at `endpoint`
+    pub(crate) fn new(endpoint: &ConnectedPoint) -> Self {
+        Self {
+            remote_addr: endpoint.get_remote_address().clone(),
+            outgoing: endpoint.is_dialer(),
+        }
+    }
+}
+
+/// Application-level authorization of peers, on top of the stake table authentication done by
+/// the transport.
+///
+/// Consulted for every connection, incoming or dialed, after its transport handshake and before
+/// the peer is admitted. Connections to peers which aren't authorized are closed. It runs on the
+/// network event loop, so it should answer quickly.
+#[async_trait]
+pub trait ConnectionAuthorizer: Send + Sync {
+    /// Whether `peer`, which completed `handshake`, may be admitted
+    async fn authorize(&self, peer: &PeerId, handshake: &Handshake) -> bool;
+}
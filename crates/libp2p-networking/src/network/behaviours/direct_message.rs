@@ -6,7 +6,11 @@
 
 use std::collections::HashMap;
 
-use libp2p::request_response::{Event, Message, OutboundRequestId, ResponseChannel};
+use futures::channel::oneshot::Sender;
+use hotshot_types::traits::network::NetworkError;
+use libp2p::request_response::{
+    Event, Message, OutboundFailure, OutboundRequestId, ResponseChannel,
+};
 use libp2p_identity::PeerId;
 use tokio::{spawn, sync::mpsc::UnboundedSender, time::sleep};
 use tracing::{debug, error, warn};
@@ -25,6 +29,37 @@ pub struct DMRequest {
     pub backoff: ExponentialBackoff,
     /// the number of remaining retries before giving up
     pub(crate) retry_count: u8,
+    /// reports whether the peer acknowledged the message
+    pub(crate) result_sender: Option<Sender<Result<(), NetworkError>>>,
+}
+
+impl DMRequest {
+    /// Report the outcome of the request to its sender, if it is waiting for one
+    fn report(&mut self, result: Result<(), NetworkError>) {
+        if let Some(result_sender) = self.result_sender.take() {
+            let _ = result_sender.send(result);
+        }
+    }
+}
+
+/// The error reported to the sender of a direct message which failed with `error`
+#[must_use]
+pub fn outbound_failure_to_network_error(error: &OutboundFailure) -> NetworkError {
+    match error {
+        OutboundFailure::DialFailure => {
+            NetworkError::DialFailure("failed to dial the peer".to_string())
+        }
+        OutboundFailure::Timeout => {
+            NetworkError::Timeout("timed out waiting for the peer to acknowledge".to_string())
+        }
+        OutboundFailure::ConnectionClosed => {
+            NetworkError::StreamReset("connection closed before the peer acknowledged".to_string())
+        }
+        OutboundFailure::UnsupportedProtocols => NetworkError::ProtocolNegotiationFailed(
+            "peer does not support the direct message protocol".to_string(),
+        ),
+        OutboundFailure::Io(e) => NetworkError::StreamReset(e.to_string()),
+    }
 }
 
 /// Wrapper metadata around libp2p's request response
@@ -67,20 +102,20 @@ impl DMBehaviour {
             } => {
                 warn!("Outbound message failure to {:?}: {:?}", peer, error);
                 if let Some(mut req) = self.in_progress_rr.remove(&request_id) {
-                    if req.retry_count == 0 {
+                    let Some(retry_tx) = retry_tx.filter(|_| req.retry_count > 0) else {
+                        req.report(Err(outbound_failure_to_network_error(&error)));
                         return None;
-                    }
+                    };
                     req.retry_count -= 1;
-                    if let Some(retry_tx) = retry_tx {
-                        spawn(async move {
-                            sleep(req.backoff.next_timeout(false)).await;
-                            let _ = retry_tx.send(ClientRequest::DirectRequest {
-                                pid: peer,
-                                contents: req.data,
-                                retry_count: req.retry_count,
-                            });
+                    spawn(async move {
+                        sleep(req.backoff.next_timeout(false)).await;
+                        let _ = retry_tx.send(ClientRequest::DirectRequest {
+                            pid: peer,
+                            contents: req.data,
+                            retry_count: req.retry_count,
+                            result_sender: req.result_sender,
                         });
-                    }
+                    });
                 }
                 None
             }
@@ -100,8 +135,9 @@ impl DMBehaviour {
                     response: msg,
                 } => {
                     // success, finished.
-                    if let Some(req) = self.in_progress_rr.remove(&request_id) {
+                    if let Some(mut req) = self.in_progress_rr.remove(&request_id) {
                         debug!("Received direct response {:?}", msg);
+                        req.report(Ok(()));
                         Some(NetworkEvent::DirectResponse(msg, req.peer_id))
                     } else {
                         warn!("Received response for unknown request id {:?}", request_id);
@@ -131,3 +167,100 @@ impl DMBehaviour {
         self.in_progress_rr.insert(request_id, req);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use libp2p::{
+        request_response::{Config, ProtocolSupport},
+        StreamProtocol,
+    };
+
+    use super::*;
+    use crate::network::cbor::{Behaviour, Cbor};
+
+    /// Start a direct message to a random peer, and return the receiver for its outcome
+    fn start_request(
+        dm: &mut DMBehaviour,
+    ) -> (
+        PeerId,
+        OutboundRequestId,
+        futures::channel::oneshot::Receiver<Result<(), NetworkError>>,
+    ) {
+        let mut request_response: Behaviour<Vec<u8>, Vec<u8>> = Behaviour::with_codec(
+            Cbor::new(1024, 1024),
+            [(
+                StreamProtocol::new("/HotShot/direct_message/1.0"),
+                ProtocolSupport::Full,
+            )],
+            Config::default(),
+        );
+        let peer_id = PeerId::random();
+        let request_id = request_response.send_request(&peer_id, vec![1]);
+
+        let (result_sender, result_receiver) = futures::channel::oneshot::channel();
+        dm.add_direct_request(
+            DMRequest {
+                peer_id,
+                data: vec![1],
+                backoff: ExponentialBackoff::default(),
+                retry_count: 1,
+                result_sender: Some(result_sender),
+            },
+            request_id,
+        );
+
+        (peer_id, request_id, result_receiver)
+    }
+
+    #[test]
+    fn test_dial_failure_reaches_the_sender() {
+        let mut dm = DMBehaviour::default();
+        let (peer, request_id, mut result) = start_request(&mut dm);
+
+        dm.handle_dm_event(
+            Event::OutboundFailure {
+                peer,
+                request_id,
+                error: OutboundFailure::DialFailure,
+            },
+            None,
+        );
+
+        assert!(matches!(
+            result.try_recv(),
+            Ok(Some(Err(NetworkError::DialFailure(_))))
+        ));
+    }
+
+    #[test]
+    fn test_stream_reset_reaches_the_sender() {
+        let mut dm = DMBehaviour::default();
+        let (peer, request_id, mut result) = start_request(&mut dm);
+
+        dm.handle_dm_event(
+            Event::OutboundFailure {
+                peer,
+                request_id,
+                error: OutboundFailure::Io(std::io::ErrorKind::ConnectionReset.into()),
+            },
+            None,
+        );
+
+        assert!(matches!(
+            result.try_recv(),
+            Ok(Some(Err(NetworkError::StreamReset(_))))
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_protocol_is_distinguished_from_peer_loss() {
+        assert!(matches!(
+            outbound_failure_to_network_error(&OutboundFailure::UnsupportedProtocols),
+            NetworkError::ProtocolNegotiationFailed(_)
+        ));
+        assert!(matches!(
+            outbound_failure_to_network_error(&OutboundFailure::ConnectionClosed),
+            NetworkError::StreamReset(_)
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::collections::HashMap;
+
+use libp2p::swarm::ConnectionId;
+use libp2p_identity::PeerId;
+
+/// What to do with several connections to the same peer, e.g. after two nodes dial each other
+/// simultaneously
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateConnectionPolicy {
+    /// Keep every connection
+    #[default]
+    KeepAll,
+    /// Keep the connection dialed by the peer with the lower `PeerId`, and close the ones dialed
+    /// the other way. Both peers make the same choice, so they settle on the same connection.
+    KeepLowerDialer,
+}
+
+/// Tracks the established connections to each peer, and picks the redundant ones to close
+#[derive(Debug)]
+pub(crate) struct ConnectionDeduplicator {
+    /// The policy to apply
+    policy: DuplicateConnectionPolicy,
+    /// Our own peer id
+    local_peer_id: PeerId,
+    /// The connections to each peer, and whether each was dialed by the lower of the two peers
+    connections: HashMap<PeerId, Vec<(ConnectionId, bool)>>,
+}
+
+impl ConnectionDeduplicator {
+    /// Create a deduplicator for the node `local_peer_id`
+    pub(crate) fn new(policy: DuplicateConnectionPolicy, local_peer_id: PeerId) -> Self {
+        Self {
+            policy,
+            local_peer_id,
+            connections: HashMap::new(),
+        }
+    }
+
+    /// Record an established connection to `peer`, which we dialed if `we_dialed`, and return
+    /// the connections to `peer` which should now be closed.
+    ///
+    /// Connections dialed by the higher peer are only closed once one dialed by the lower peer
+    /// exists, so a peer is never left without a connection.
+    pub(crate) fn established(
+        &mut self,
+        peer: PeerId,
+        connection: ConnectionId,
+        we_dialed: bool,
+    ) -> Vec<ConnectionId> {
+        let dialed_by_lower = we_dialed == (self.local_peer_id < peer);
+        let connections = self.connections.entry(peer).or_default();
+        connections.push((connection, dialed_by_lower));
+
+        if self.policy == DuplicateConnectionPolicy::KeepAll
+            || !connections
+                .iter()
+                .any(|(_, dialed_by_lower)| *dialed_by_lower)
+        {
+            return Vec::new();
+        }
+
+        connections
+            .iter()
+            .filter(|(_, dialed_by_lower)| !dialed_by_lower)
+            .map(|(connection, _)| *connection)
+            .collect()
+    }
+
+    /// Record that a connection to `peer` was closed
+    pub(crate) fn closed(&mut self, peer: PeerId, connection: ConnectionId) {
+        if let Some(connections) = self.connections.get_mut(&peer) {
+            connections.retain(|(id, _)| *id != connection);
+            if connections.is_empty() {
+                self.connections.remove(&peer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Two distinct peer ids, the lower one first
+    fn ordered_peers() -> (PeerId, PeerId) {
+        let (a, b) = (PeerId::random(), PeerId::random());
+        if a < b {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    }
+
+    #[test]
+    fn test_both_peers_keep_the_same_connection() {
+        let (lower, higher) = ordered_peers();
+        let mut at_lower =
+            ConnectionDeduplicator::new(DuplicateConnectionPolicy::KeepLowerDialer, lower);
+        let mut at_higher =
+            ConnectionDeduplicator::new(DuplicateConnectionPolicy::KeepLowerDialer, higher);
+
+        // The connection dialed by the higher peer arrives first, and is kept until the other
+        // one is established
+        let higher_dial = ConnectionId::new_unchecked(1);
+        let lower_dial = ConnectionId::new_unchecked(2);
+        assert!(at_lower.established(higher, higher_dial, false).is_empty());
+        assert!(at_higher.established(lower, higher_dial, true).is_empty());
+
+        assert_eq!(
+            at_lower.established(higher, lower_dial, true),
+            vec![higher_dial]
+        );
+        assert_eq!(
+            at_higher.established(lower, lower_dial, false),
+            vec![higher_dial]
+        );
+
+        at_lower.closed(higher, higher_dial);
+        assert_eq!(at_lower.connections[&higher], vec![(lower_dial, true)]);
+    }
+
+    #[test]
+    fn test_keep_all_closes_nothing() {
+        let (lower, higher) = ordered_peers();
+        let mut dedup = ConnectionDeduplicator::new(DuplicateConnectionPolicy::KeepAll, lower);
+
+        assert!(dedup
+            .established(higher, ConnectionId::new_unchecked(1), false)
+            .is_empty());
+        assert!(dedup
+            .established(higher, ConnectionId::new_unchecked(2), true)
+            .is_empty());
+    }
+}
@@ -4,10 +4,10 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use hotshot_types::traits::signature_key::SignatureKey;
+use hotshot_types::traits::{network::NetworkError, signature_key::SignatureKey};
 use libp2p::{
     autonat,
-    gossipsub::{Behaviour as GossipBehaviour, Event as GossipEvent, IdentTopic},
+    gossipsub::{Behaviour as GossipBehaviour, Event as GossipEvent, IdentTopic, PublishError},
     identify::{Behaviour as IdentifyBehaviour, Event as IdentifyEvent},
     kad::store::MemoryStore,
     request_response::{OutboundRequestId, ResponseChannel},
@@ -19,7 +19,9 @@ use tracing::{debug, error};
 
 use super::{
     behaviours::dht::store::{file_backed::FileBackedStore, validated::ValidatedStore},
-    cbor, NetworkEventInternal,
+    cbor,
+    relay::RelayEnvelope,
+    NetworkEventInternal,
 };
 
 /// Overarching network behaviour performing:
@@ -50,6 +52,10 @@ pub struct NetworkDef<K: SignatureKey + 'static> {
     #[debug(skip)]
     pub direct_message: cbor::Behaviour<Vec<u8>, Vec<u8>>,
 
+    /// purpose: messaging peers we can't connect to directly through a relay
+    #[debug(skip)]
+    pub relay: cbor::Behaviour<RelayEnvelope, Vec<u8>>,
+
     /// Auto NAT behaviour to determine if we are publicly reachable and
     /// by which address
     #[debug(skip)]
@@ -64,6 +70,7 @@ impl<K: SignatureKey + 'static> NetworkDef<K> {
         dht: libp2p::kad::Behaviour<FileBackedStore<ValidatedStore<MemoryStore, K>>>,
         identify: IdentifyBehaviour,
         direct_message: super::cbor::Behaviour<Vec<u8>, Vec<u8>>,
+        relay: super::cbor::Behaviour<RelayEnvelope, Vec<u8>>,
         autonat: autonat::Behaviour,
     ) -> NetworkDef<K> {
         Self {
@@ -71,6 +78,7 @@ impl<K: SignatureKey + 'static> NetworkDef<K> {
             dht,
             identify,
             direct_message,
+            relay,
             autonat,
         }
     }
@@ -93,9 +101,26 @@ impl<K: SignatureKey + 'static> NetworkDef<K> {
 /// Gossip functions
 impl<K: SignatureKey + 'static> NetworkDef<K> {
     /// Publish a given gossip
-    pub fn publish_gossip(&mut self, topic: IdentTopic, contents: Vec<u8>) {
-        if let Err(e) = self.gossipsub.publish(topic, contents) {
-            tracing::warn!("Failed to publish gossip message. Error: {:?}", e);
+    ///
+    /// # Errors
+    /// - [`NetworkError::NoPeers`] if there are no peers to publish to
+    /// - [`NetworkError::MessageSendError`] if publishing failed for any other reason
+    pub fn publish_gossip(
+        &mut self,
+        topic: IdentTopic,
+        contents: Vec<u8>,
+    ) -> Result<(), NetworkError> {
+        match self.gossipsub.publish(topic, contents) {
+            // A duplicate has already been published, so there is nothing left to do.
+            Ok(_) | Err(PublishError::Duplicate) => Ok(()),
+            Err(PublishError::InsufficientPeers) => {
+                tracing::warn!("Failed to publish gossip message: no peers");
+                Err(NetworkError::NoPeers)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to publish gossip message. Error: {:?}", e);
+                Err(NetworkError::MessageSendError(e.to_string()))
+            }
         }
     }
     /// Subscribe to a given topic
@@ -124,6 +149,15 @@ impl<K: SignatureKey + 'static> NetworkDef<K> {
     pub fn add_direct_response(&mut self, chan: ResponseChannel<Vec<u8>>, msg: Vec<u8>) {
         let _ = self.direct_message.send_response(chan, msg);
     }
+
+    /// Send a direct message through the relay `relay`
+    pub fn add_relayed_request(
+        &mut self,
+        relay: PeerId,
+        envelope: RelayEnvelope,
+    ) -> OutboundRequestId {
+        self.relay.send_request(&relay, envelope)
+    }
 }
 
 impl From<GossipEvent> for NetworkEventInternal {
@@ -149,6 +183,12 @@ impl From<libp2p::request_response::Event<Vec<u8>, Vec<u8>>> for NetworkEventInt
     }
 }
 
+impl From<libp2p::request_response::Event<RelayEnvelope, Vec<u8>>> for NetworkEventInternal {
+    fn from(value: libp2p::request_response::Event<RelayEnvelope, Vec<u8>>) -> Self {
+        Self::RelayEvent(value)
+    }
+}
+
 impl From<libp2p::autonat::Event> for NetworkEventInternal {
     fn from(event: libp2p::autonat::Event) -> Self {
         Self::AutonatEvent(event)
@@ -4,12 +4,20 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
+/// Application-level authorization of peers
+pub mod authorization;
 /// networking behaviours wrapping libp2p's behaviours
 pub mod behaviours;
+/// Closing of redundant connections to the same peer
+pub mod connection_dedup;
 /// defines the swarm and network definition (internal)
 mod def;
 /// functionality of a libp2p network node
 mod node;
+/// Per-peer rate limiting of direct messages
+pub mod rate_limit;
+/// Routing of direct messages through relays when peers can't connect directly
+pub mod relay;
 /// Alternative Libp2p transport implementations
 pub mod transport;
 
@@ -19,7 +27,10 @@ pub mod cbor;
 use std::{collections::HashSet, fmt::Debug};
 
 use futures::channel::oneshot::Sender;
-use hotshot_types::traits::{network::NetworkError, node_implementation::NodeType};
+use hotshot_types::{
+    traits::{network::NetworkError, node_implementation::NodeType},
+    vid::ProtocolSchemes,
+};
 use libp2p::{
     build_multiaddr,
     core::{muxing::StreamMuxerBox, transport::Boxed},
@@ -52,8 +63,8 @@ pub enum ClientRequest {
     BeginBootstrap,
     /// kill the swarm
     Shutdown,
-    /// broadcast a serialized message
-    GossipMsg(String, Vec<u8>),
+    /// broadcast a serialized message, reporting whether it could be published
+    GossipMsg(String, Vec<u8>, Option<Sender<Result<(), NetworkError>>>),
     /// subscribe to a topic
     Subscribe(String, Option<Sender<()>>),
     /// unsubscribe from a topic
@@ -66,6 +77,8 @@ pub enum ClientRequest {
         contents: Vec<u8>,
         /// number of retries
         retry_count: u8,
+        /// reports whether the peer acknowledged the message
+        result_sender: Option<Sender<Result<(), NetworkError>>>,
     },
     /// client request to send a direct reply to a message
     DirectResponse(ResponseChannel<Vec<u8>>, Vec<u8>),
@@ -115,6 +128,8 @@ pub enum NetworkEvent {
     DirectRequest(Vec<u8>, PeerId, ResponseChannel<Vec<u8>>),
     /// Recv-ed a direct response from a node (that hopefully was initiated by this node)
     DirectResponse(Vec<u8>, PeerId),
+    /// Recv-ed a direct message from a node through a relay, already acknowledged
+    RelayedRequest(Vec<u8>, PeerId),
     /// Report that kademlia has successfully bootstrapped into the network
     IsBootstrapped,
     /// The number of connected peers has possibly changed
@@ -134,6 +149,8 @@ pub enum NetworkEventInternal {
     GossipEvent(Box<GossipEvent>),
     /// a direct message event
     DMEvent(libp2p::request_response::Event<Vec<u8>, Vec<u8>>),
+    /// a relayed direct message event
+    RelayEvent(libp2p::request_response::Event<relay::RelayEnvelope, Vec<u8>>),
     /// a autonat event
     AutonatEvent(libp2p::autonat::Event),
 }
@@ -152,7 +169,8 @@ type BoxedTransport = Boxed<(PeerId, StreamMuxerBox)>;
 
 /// Generates an authenticated transport checked against the stake table.
 /// If the stake table or authentication message is not provided, the transport will
-/// not participate in stake table authentication.
+/// not participate in stake table authentication. If the schemes are provided, only peers
+/// using the same ones are connected to.
 ///
 /// # Errors
 /// If we could not create a DNS transport
@@ -161,6 +179,7 @@ pub async fn gen_transport<T: NodeType>(
     identity: Keypair,
     stake_table: Option<T::Membership>,
     auth_message: Option<Vec<u8>>,
+    schemes: Option<ProtocolSchemes>,
 ) -> Result<BoxedTransport, NetworkError> {
     // Create the initial `Quic` transport
     let transport = {
@@ -171,7 +190,7 @@ pub async fn gen_transport<T: NodeType>(
 
     // Require authentication against the stake table
     let transport: StakeTableAuthentication<_, T, _> =
-        StakeTableAuthentication::new(transport, stake_table, auth_message);
+        StakeTableAuthentication::new(transport, stake_table, auth_message, schemes);
 
     // Support DNS resolution
     let transport = {
@@ -15,7 +15,8 @@ use std::{
     collections::{HashMap, HashSet},
     iter,
     num::{NonZeroU32, NonZeroUsize},
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 use futures::{channel::mpsc, SinkExt, StreamExt};
@@ -65,10 +66,15 @@ use super::{
     gen_transport, BoxedTransport, ClientRequest, NetworkDef, NetworkError, NetworkEvent,
     NetworkEventInternal,
 };
-use crate::network::behaviours::{
-    dht::{DHTBehaviour, DHTProgress, KadPutQuery, NUM_REPLICATED_TO_TRUST},
-    direct_message::{DMBehaviour, DMRequest},
-    exponential_backoff::ExponentialBackoff,
+use crate::network::{
+    authorization::{ConnectionAuthorizer, Handshake},
+    behaviours::{
+        dht::{DHTBehaviour, DHTProgress, KadPutQuery, NUM_REPLICATED_TO_TRUST},
+        direct_message::{DMBehaviour, DMRequest},
+        exponential_backoff::ExponentialBackoff,
+    },
+    connection_dedup::ConnectionDeduplicator,
+    relay::{RelayEnvelope, RelayState, Route},
 };
 
 /// Maximum size of a message
@@ -96,6 +102,17 @@ pub struct NetworkNode<T: NodeType> {
     dht_handler: DHTBehaviour<T::SignatureKey>,
     /// Channel to resend requests, set to Some when we call `spawn_listeners`
     resend_tx: Option<UnboundedSender<ClientRequest>>,
+    /// Picks the redundant connections to close
+    connection_dedup: ConnectionDeduplicator,
+    /// Routes direct messages through relays when peers can't be reached directly
+    relay_state: RelayState,
+    /// Decides which peers to admit, if any are to be turned away
+    #[debug(skip)]
+    authorizer: Option<Arc<dyn ConnectionAuthorizer>>,
+    /// The most peers to stay connected to, if limited
+    max_peers: Option<NonZeroUsize>,
+    /// Gossipsub configuration, used to name the topics we publish and subscribe to
+    gossip_config: GossipConfig,
 }
 
 impl<T: NodeType> NetworkNode<T> {
@@ -168,11 +185,12 @@ impl<T: NodeType> NetworkNode<T> {
         // Get the `PeerId` from the `KeyPair`
         let peer_id = PeerId::from(keypair.public());
 
-        // Generate the transport from the keypair, stake table, and auth message
+        // Generate the transport from the keypair, stake table, auth message and schemes
         let transport: BoxedTransport = gen_transport::<T>(
             keypair.clone(),
             config.stake_table.clone(),
             config.auth_message.clone(),
+            config.schemes.clone(),
         )
         .await?;
 
@@ -290,6 +308,19 @@ impl<T: NodeType> NetworkNode<T> {
                     rrconfig.clone(),
                 );
 
+            let relay: super::cbor::Behaviour<RelayEnvelope, Vec<u8>> = RequestResponse::with_codec(
+                Cbor::new(
+                    config.request_response_config.request_size_maximum,
+                    config.request_response_config.response_size_maximum,
+                ),
+                [(
+                    StreamProtocol::new("/HotShot/relay/1.0"),
+                    ProtocolSupport::Full,
+                )]
+                .into_iter(),
+                rrconfig.clone(),
+            );
+
             let autonat_config = autonat::Config {
                 only_global_ips: false,
                 ..Default::default()
@@ -300,6 +331,7 @@ impl<T: NodeType> NetworkNode<T> {
                 kadem,
                 identify,
                 direct_message,
+                relay,
                 autonat::Behaviour::new(peer_id, autonat_config),
             );
 
@@ -332,6 +364,14 @@ impl<T: NodeType> NetworkNode<T> {
                     .unwrap_or(NonZeroUsize::new(4).unwrap()),
             ),
             resend_tx: None,
+            connection_dedup: ConnectionDeduplicator::new(
+                config.duplicate_connection_policy,
+                peer_id,
+            ),
+            relay_state: RelayState::new(config.relay_config.clone(), peer_id),
+            authorizer: config.authorizer.clone(),
+            max_peers: config.max_peers,
+            gossip_config: config.gossip_config.clone(),
         })
     }
 
@@ -443,11 +483,15 @@ impl<T: NodeType> NetworkNode<T> {
 
                         return Ok(true);
                     }
-                    ClientRequest::GossipMsg(topic, contents) => {
-                        behaviour.publish_gossip(Topic::new(topic.clone()), contents.clone());
+                    ClientRequest::GossipMsg(topic, contents, result_sender) => {
+                        let topic = Topic::new(self.gossip_config.topic_name(&topic));
+                        let result = behaviour.publish_gossip(topic, contents);
+                        if let Some(result_sender) = result_sender {
+                            let _ = result_sender.send(result);
+                        }
                     }
                     ClientRequest::Subscribe(t, chan) => {
-                        behaviour.subscribe_gossip(&t);
+                        behaviour.subscribe_gossip(&self.gossip_config.topic_name(&t));
                         if let Some(chan) = chan {
                             if chan.send(()).is_err() {
                                 error!("finished subscribing but response channel dropped");
@@ -455,7 +499,7 @@ impl<T: NodeType> NetworkNode<T> {
                         }
                     }
                     ClientRequest::Unsubscribe(t, chan) => {
-                        behaviour.unsubscribe_gossip(&t);
+                        behaviour.unsubscribe_gossip(&self.gossip_config.topic_name(&t));
                         if let Some(chan) = chan {
                             if chan.send(()).is_err() {
                                 error!("finished unsubscribing but response channel dropped");
@@ -466,7 +510,16 @@ impl<T: NodeType> NetworkNode<T> {
                         pid,
                         contents,
                         retry_count,
+                        result_sender,
                     } => {
+                        if let Route::Relay(relay) = self.relay_state.route(pid, Instant::now()) {
+                            debug!("Sending direct request to {:?} through {:?}", pid, relay);
+                            let envelope = RelayEnvelope::new(self.peer_id, pid, contents);
+                            let id = behaviour.add_relayed_request(relay, envelope);
+                            self.relay_state.add_relayed_request(id, result_sender);
+                            return Ok(false);
+                        }
+
                         debug!("Sending direct request to {:?}", pid);
                         let id = behaviour.add_direct_request(pid, contents.clone());
                         let req = DMRequest {
@@ -474,6 +527,7 @@ impl<T: NodeType> NetworkNode<T> {
                             data: contents,
                             backoff: ExponentialBackoff::default(),
                             retry_count,
+                            result_sender,
                         };
                         self.direct_message_state.add_direct_request(req, id);
                     }
@@ -511,7 +565,7 @@ impl<T: NodeType> NetworkNode<T> {
         #[allow(deprecated)]
         match event {
             SwarmEvent::ConnectionEstablished {
-                connection_id: _,
+                connection_id,
                 peer_id,
                 endpoint,
                 num_established,
@@ -530,13 +584,43 @@ impl<T: NodeType> NetworkNode<T> {
                     );
                 }
 
+                // Turn the peer away if it isn't authorized
+                if let Some(authorizer) = self.authorizer.clone() {
+                    let handshake = Handshake::new(&endpoint);
+                    if !authorizer.authorize(&peer_id, &handshake).await {
+                        warn!("Closing connection {connection_id:?} to unauthorized {peer_id:?}");
+                        self.swarm.close_connection(connection_id);
+                        return Ok(());
+                    }
+                }
+
+                // Turn a new peer away if we are already connected to as many as allowed
+                if let Some(max_peers) = self.max_peers {
+                    if num_established.get() == 1 && self.num_connected() > max_peers.get() {
+                        debug!("Closing connection {connection_id:?} to {peer_id:?}, at max peers");
+                        self.swarm.close_connection(connection_id);
+                        return Ok(());
+                    }
+                }
+
+                // Close any connections to the peer made redundant by this one
+                let dialer = endpoint.is_dialer();
+                for redundant in self
+                    .connection_dedup
+                    .established(peer_id, connection_id, dialer)
+                {
+                    debug!("Closing redundant connection {redundant:?} to {peer_id:?}");
+                    self.swarm.close_connection(redundant);
+                }
+                self.relay_state.connection_established(peer_id);
+
                 // Send the number of connected peers to the client
                 send_to_client
                     .send(NetworkEvent::ConnectedPeersUpdate(self.num_connected()))
                     .map_err(|err| NetworkError::ChannelSendError(err.to_string()))?;
             }
             SwarmEvent::ConnectionClosed {
-                connection_id: _,
+                connection_id,
                 peer_id,
                 endpoint,
                 num_established,
@@ -553,6 +637,10 @@ impl<T: NodeType> NetworkNode<T> {
                         peer_id, endpoint, cause
                     );
                 }
+                self.connection_dedup.closed(peer_id, connection_id);
+                if num_established == 0 {
+                    self.relay_state.connection_closed(peer_id);
+                }
 
                 // Send the number of connected peers to the client
                 send_to_client
@@ -636,6 +724,9 @@ impl<T: NodeType> NetworkNode<T> {
                     NetworkEventInternal::DMEvent(e) => self
                         .direct_message_state
                         .handle_dm_event(e, self.resend_tx.clone()),
+                    NetworkEventInternal::RelayEvent(e) => self
+                        .relay_state
+                        .handle_relay_event(e, &mut self.swarm.behaviour_mut().relay),
                     NetworkEventInternal::AutonatEvent(e) => {
                         match e {
                             autonat::Event::InboundProbe(_) => {}
@@ -674,6 +765,9 @@ impl<T: NodeType> NetworkNode<T> {
                 error,
             } => {
                 warn!("Outgoing connection error to {:?}: {:?}", peer_id, error);
+                if let Some(peer_id) = peer_id {
+                    self.relay_state.dial_failed(peer_id, Instant::now());
+                }
             }
             SwarmEvent::IncomingConnectionError {
                 connection_id: _,
@@ -761,3 +855,501 @@ impl<T: NodeType> NetworkNode<T> {
         self.peer_id
     }
 }
+
+#[cfg(test)]
+mod test {
+    use async_trait::async_trait;
+    use hotshot_example_types::node_types::TestTypes;
+    use hotshot_types::vid::ProtocolSchemes;
+    use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
+
+    use super::*;
+    use crate::network::{connection_dedup::DuplicateConnectionPolicy, relay::RelayConfig};
+
+    /// A node closing redundant connections, listening on a local port
+    async fn listening_node() -> (NetworkNode<TestTypes>, Multiaddr) {
+        let config = NetworkNodeConfigBuilder::<TestTypes>::default()
+            .to_connect_addrs(HashSet::new())
+            .duplicate_connection_policy(DuplicateConnectionPolicy::KeepLowerDialer)
+            .build()
+            .unwrap();
+        let mut node = NetworkNode::new(config).await.unwrap();
+        let addr = node
+            .start_listen("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap())
+            .await
+            .unwrap();
+
+        (node, addr)
+    }
+
+    /// The number of established connections of `node`
+    fn num_connections(node: &NetworkNode<TestTypes>) -> u32 {
+        node.swarm
+            .network_info()
+            .connection_counters()
+            .num_established()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_simultaneous_dials_settle_on_one_connection() {
+        let (mut a, a_addr) = listening_node().await;
+        let (mut b, b_addr) = listening_node().await;
+
+        // Dial each other at the same time
+        a.swarm
+            .dial(
+                DialOpts::peer_id(b.peer_id)
+                    .addresses(vec![b_addr])
+                    .condition(PeerCondition::Always)
+                    .build(),
+            )
+            .unwrap();
+        b.swarm
+            .dial(
+                DialOpts::peer_id(a.peer_id)
+                    .addresses(vec![a_addr])
+                    .condition(PeerCondition::Always)
+                    .build(),
+            )
+            .unwrap();
+
+        // Drive both nodes for a while
+        let (tx, _rx) = unbounded_channel();
+        let _ = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                select! {
+                    Some(event) = a.swarm.next() => {
+                        a.handle_swarm_events(event, &tx).await.unwrap();
+                    }
+                    Some(event) = b.swarm.next() => {
+                        b.handle_swarm_events(event, &tx).await.unwrap();
+                    }
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(num_connections(&a), 1);
+        assert_eq!(num_connections(&b), 1);
+        assert_eq!(a.connected_pids(), HashSet::from([b.peer_id]));
+    }
+
+    /// A node listening on a local port, which routes through `relay` after a short timeout
+    async fn relayed_node(keypair: Keypair, relay: PeerId) -> (NetworkNode<TestTypes>, Multiaddr) {
+        let config = NetworkNodeConfigBuilder::<TestTypes>::default()
+            .keypair(keypair)
+            .to_connect_addrs(HashSet::new())
+            .relay_config(Some(RelayConfig {
+                relays: vec![relay],
+                direct_timeout: Duration::from_millis(100),
+            }))
+            .build()
+            .unwrap();
+        let mut node = NetworkNode::new(config).await.unwrap();
+        let addr = node
+            .start_listen("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap())
+            .await
+            .unwrap();
+
+        (node, addr)
+    }
+
+    /// Drive the three nodes for `duration`, sending the events of each to its own channel
+    async fn drive(
+        a: &mut NetworkNode<TestTypes>,
+        b: &mut NetworkNode<TestTypes>,
+        relay: &mut NetworkNode<TestTypes>,
+        txs: &[UnboundedSender<NetworkEvent>],
+        duration: Duration,
+    ) {
+        let _ = tokio::time::timeout(duration, async {
+            loop {
+                select! {
+                    Some(event) = a.swarm.next() => {
+                        a.handle_swarm_events(event, &txs[0]).await.unwrap();
+                    }
+                    Some(event) = b.swarm.next() => {
+                        b.handle_swarm_events(event, &txs[1]).await.unwrap();
+                    }
+                    Some(event) = relay.swarm.next() => {
+                        relay.handle_swarm_events(event, &txs[2]).await.unwrap();
+                    }
+                }
+            }
+        })
+        .await;
+    }
+
+    /// Have `node` send `contents` to `peer`, and return the receiver for the outcome
+    async fn send_direct(
+        node: &mut NetworkNode<TestTypes>,
+        peer: PeerId,
+        contents: Vec<u8>,
+    ) -> futures::channel::oneshot::Receiver<Result<(), NetworkError>> {
+        let (result_sender, result) = futures::channel::oneshot::channel();
+        node.handle_client_requests(Some(ClientRequest::DirectRequest {
+            pid: peer,
+            contents,
+            retry_count: 1,
+            result_sender: Some(result_sender),
+        }))
+        .await
+        .unwrap();
+
+        result
+    }
+
+    /// The messages received by a node so far, and whether each came through a relay
+    fn received(rx: &mut UnboundedReceiver<NetworkEvent>) -> Vec<(Vec<u8>, PeerId, bool)> {
+        let mut messages = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                NetworkEvent::DirectRequest(msg, peer, _) => messages.push((msg, peer, false)),
+                NetworkEvent::RelayedRequest(msg, peer) => messages.push((msg, peer, true)),
+                _ => {}
+            }
+        }
+
+        messages
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unreachable_peer_is_reached_through_relay() {
+        let relay_keypair = Keypair::generate_ed25519();
+        let relay_id = PeerId::from(relay_keypair.public());
+        let (mut relay, relay_addr) = relayed_node(relay_keypair, relay_id).await;
+        let (mut a, _) = relayed_node(Keypair::generate_ed25519(), relay_id).await;
+        let (mut b, b_addr) = relayed_node(Keypair::generate_ed25519(), relay_id).await;
+        let (txs, mut rxs): (Vec<_>, Vec<_>) = (0..3).map(|_| unbounded_channel()).unzip();
+
+        // Both peers reach the relay, but `a` has no address to reach `b` directly
+        for node in [&mut a, &mut b] {
+            node.swarm
+                .dial(
+                    DialOpts::peer_id(relay_id)
+                        .addresses(vec![relay_addr.clone()])
+                        .build(),
+                )
+                .unwrap();
+        }
+        drive(&mut a, &mut b, &mut relay, &txs, Duration::from_secs(2)).await;
+
+        // The first message is tried directly, and fails
+        let mut first = send_direct(&mut a, b.peer_id, vec![1]).await;
+        drive(&mut a, &mut b, &mut relay, &txs, Duration::from_secs(1)).await;
+        assert!(matches!(first.try_recv(), Ok(Some(Err(_)))));
+
+        // Once the timeout has passed, messages go through the relay
+        let mut second = send_direct(&mut a, b.peer_id, vec![2]).await;
+        drive(&mut a, &mut b, &mut relay, &txs, Duration::from_secs(2)).await;
+        assert!(matches!(second.try_recv(), Ok(Some(Ok(())))));
+        assert_eq!(received(&mut rxs[1]), vec![(vec![2], a.peer_id, true)]);
+
+        // And go direct again once the peers can connect
+        a.swarm
+            .dial(DialOpts::peer_id(b.peer_id).addresses(vec![b_addr]).build())
+            .unwrap();
+        drive(&mut a, &mut b, &mut relay, &txs, Duration::from_secs(2)).await;
+        let _third = send_direct(&mut a, b.peer_id, vec![3]).await;
+        drive(&mut a, &mut b, &mut relay, &txs, Duration::from_secs(2)).await;
+        assert_eq!(received(&mut rxs[1]), vec![(vec![3], a.peer_id, false)]);
+    }
+
+    /// Admits only the peers in a known set
+    struct KnownPeers(HashSet<PeerId>);
+
+    #[async_trait]
+    impl ConnectionAuthorizer for KnownPeers {
+        async fn authorize(&self, peer: &PeerId, _handshake: &Handshake) -> bool {
+            self.0.contains(peer)
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_unauthorized_peers_are_disconnected() {
+        let (mut known, _) = listening_node().await;
+        let (mut unknown, _) = listening_node().await;
+
+        // A node admitting only `known`
+        let authorizer: Arc<dyn ConnectionAuthorizer> =
+            Arc::new(KnownPeers(HashSet::from([known.peer_id])));
+        let config = NetworkNodeConfigBuilder::<TestTypes>::default()
+            .to_connect_addrs(HashSet::new())
+            .authorizer(Some(authorizer))
+            .build()
+            .unwrap();
+        let mut node = NetworkNode::new(config).await.unwrap();
+        let addr = node
+            .start_listen("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap())
+            .await
+            .unwrap();
+        let (txs, _rxs): (Vec<_>, Vec<_>) = (0..3).map(|_| unbounded_channel()).unzip();
+
+        for peer in [&mut known, &mut unknown] {
+            peer.swarm
+                .dial(
+                    DialOpts::peer_id(node.peer_id)
+                        .addresses(vec![addr.clone()])
+                        .build(),
+                )
+                .unwrap();
+        }
+        drive(
+            &mut known,
+            &mut unknown,
+            &mut node,
+            &txs,
+            Duration::from_secs(2),
+        )
+        .await;
+
+        assert_eq!(node.connected_pids(), HashSet::from([known.peer_id]));
+        assert!(known.connected_pids().contains(&node.peer_id));
+        assert!(!unknown.connected_pids().contains(&node.peer_id));
+    }
+
+    /// A node listening on a local port, which only connects to peers using `schemes`
+    async fn node_with_schemes(schemes: ProtocolSchemes) -> (NetworkNode<TestTypes>, Multiaddr) {
+        let config = NetworkNodeConfigBuilder::<TestTypes>::default()
+            .to_connect_addrs(HashSet::new())
+            .schemes(Some(schemes))
+            .build()
+            .unwrap();
+        let mut node = NetworkNode::new(config).await.unwrap();
+        let addr = node
+            .start_listen("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap())
+            .await
+            .unwrap();
+
+        (node, addr)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_peers_using_other_digest_are_refused_at_handshake() {
+        let sha256 = ProtocolSchemes::current();
+        let blake3 = ProtocolSchemes {
+            digest: "blake3".to_string(),
+            ..ProtocolSchemes::current()
+        };
+        let (mut a, a_addr) = node_with_schemes(sha256.clone()).await;
+        let (mut b, _) = node_with_schemes(blake3).await;
+        let (mut c, _) = node_with_schemes(sha256).await;
+        let (txs, _rxs): (Vec<_>, Vec<_>) = (0..3).map(|_| unbounded_channel()).unzip();
+
+        for peer in [&mut b, &mut c] {
+            peer.swarm
+                .dial(
+                    DialOpts::peer_id(a.peer_id)
+                        .addresses(vec![a_addr.clone()])
+                        .build(),
+                )
+                .unwrap();
+        }
+        drive(&mut b, &mut c, &mut a, &txs, Duration::from_secs(2)).await;
+
+        // Only the peer using the same digest is connected, on both sides of the handshake
+        assert_eq!(a.connected_pids(), HashSet::from([c.peer_id]));
+        assert!(c.connected_pids().contains(&a.peer_id));
+        assert!(b.connected_pids().is_empty());
+    }
+
+    /// The most peers each node of the overlay stays connected to
+    const MAX_PEERS: usize = 12;
+
+    /// The most hops between the publisher and any node of the overlay
+    const MAX_HOPS: usize = 6;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gossip_reaches_every_node_through_bounded_overlay() {
+        let topic = "overlay".to_string();
+        let mut handles: Vec<NetworkNodeHandle<TestTypes>> = Vec::new();
+        let mut receivers = Vec::new();
+
+        // Every node joins through the nodes 1, 2, 4, ... places before it
+        for id in 0..50 {
+            let to_connect_addrs = (0..6)
+                .filter_map(|shift| id.checked_sub(1 << shift))
+                .map(|peer| (handles[peer].peer_id(), handles[peer].listen_addr()))
+                .collect();
+            let config = NetworkNodeConfigBuilder::<TestTypes>::default()
+                .bind_address(Some("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap()))
+                .to_connect_addrs(to_connect_addrs)
+                .max_peers(NonZeroUsize::new(MAX_PEERS))
+                .build()
+                .unwrap();
+            let (receiver, handle) = spawn_network_node(config, id).await.unwrap();
+            handle.begin_bootstrap().unwrap();
+            let _ = tokio::time::timeout(Duration::from_secs(5), async {
+                while handle.num_connected().await.unwrap() == 0 {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+            })
+            .await;
+            handles.push(handle);
+            receivers.push(receiver);
+        }
+
+        // Give the overlay and the gossip mesh time to settle
+        for handle in &handles {
+            handle.subscribe(topic.clone()).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let mut connections = Vec::new();
+        for handle in &handles {
+            let pids = handle.connected_pids().await.unwrap();
+            assert!(
+                pids.len() <= MAX_PEERS,
+                "node {} has {} peers",
+                handle.id(),
+                pids.len()
+            );
+            connections.push(pids);
+        }
+
+        // Every node is within a few hops of the publisher
+        let ids: HashMap<PeerId, usize> = handles
+            .iter()
+            .map(|handle| (handle.peer_id(), handle.id()))
+            .collect();
+        let mut hops = vec![None; handles.len()];
+        hops[0] = Some(0);
+        let mut queue = std::collections::VecDeque::from([0]);
+        while let Some(node) = queue.pop_front() {
+            for peer in &connections[node] {
+                if let Some(&next) = ids.get(peer) {
+                    if hops[next].is_none() {
+                        hops[next] = hops[node].map(|hops| hops + 1);
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        for (id, hops) in hops.iter().enumerate() {
+            assert!(
+                hops.is_some_and(|hops| hops <= MAX_HOPS),
+                "node {id} is {hops:?} hops away"
+            );
+        }
+
+        // A message gossiped by the first node reaches all the others
+        handles[0].gossip(topic, &[1, 2, 3]).unwrap();
+        for (id, receiver) in receivers.iter_mut().enumerate().skip(1) {
+            let msg = tokio::time::timeout(Duration::from_secs(10), async {
+                loop {
+                    if let NetworkEvent::GossipMsg(msg) = receiver.recv().await.unwrap() {
+                        break msg;
+                    }
+                }
+            })
+            .await
+            .unwrap_or_else(|_| panic!("node {id} did not receive the message"));
+            assert_eq!(msg, vec![1, 2, 3]);
+        }
+
+        for handle in &handles {
+            handle.shutdown().await.unwrap();
+        }
+    }
+
+    /// A node on a small network which names its topics with `topic_prefix`
+    async fn node_with_topic_prefix(
+        id: usize,
+        topic_prefix: &str,
+        to_connect: Option<&NetworkNodeHandle<TestTypes>>,
+    ) -> (NetworkNodeReceiver, NetworkNodeHandle<TestTypes>) {
+        let gossip_config = GossipConfig {
+            mesh_n: 2,
+            mesh_n_low: 1,
+            mesh_n_high: 4,
+            mesh_outbound_min: 1,
+            topic_prefix: topic_prefix.to_string(),
+            ..GossipConfig::default()
+        };
+        let config = NetworkNodeConfigBuilder::<TestTypes>::default()
+            .bind_address(Some("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap()))
+            .to_connect_addrs(
+                to_connect
+                    .map(|peer| (peer.peer_id(), peer.listen_addr()))
+                    .into_iter()
+                    .collect(),
+            )
+            .gossip_config(gossip_config)
+            .build()
+            .unwrap();
+        let (receiver, handle) = spawn_network_node(config, id).await.unwrap();
+        handle.begin_bootstrap().unwrap();
+
+        (receiver, handle)
+    }
+
+    /// The next gossip message `receiver` receives
+    async fn next_gossip(receiver: &mut NetworkNodeReceiver) -> Vec<u8> {
+        loop {
+            if let NetworkEvent::GossipMsg(msg) = receiver.recv().await.unwrap() {
+                return msg;
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gossip_stays_within_topic_prefix() {
+        let topic = "global".to_string();
+        let (_publisher_rx, publisher) = node_with_topic_prefix(0, "app-a/", None).await;
+        let (mut same_rx, same) = node_with_topic_prefix(1, "app-a/", Some(&publisher)).await;
+        let (mut other_rx, other) = node_with_topic_prefix(2, "app-b/", Some(&publisher)).await;
+        let handles = [publisher, same, other];
+
+        for handle in &handles {
+            handle.subscribe(topic.clone()).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        handles[0].gossip(topic, &[1, 2, 3]).unwrap();
+
+        // Only the node sharing the publisher's prefix is subscribed to the same topic
+        let received = tokio::time::timeout(Duration::from_secs(10), next_gossip(&mut same_rx))
+            .await
+            .unwrap();
+        assert_eq!(received, vec![1, 2, 3]);
+        assert!(
+            tokio::time::timeout(Duration::from_secs(2), next_gossip(&mut other_rx))
+                .await
+                .is_err()
+        );
+
+        for handle in &handles {
+            handle.shutdown().await.unwrap();
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_acked_sends_report_errors_to_the_caller() {
+        let config = NetworkNodeConfigBuilder::<TestTypes>::default()
+            .bind_address(Some("/ip4/127.0.0.1/udp/0/quic-v1".parse().unwrap()))
+            .to_connect_addrs(HashSet::new())
+            .build()
+            .unwrap();
+        let (_receiver, handle) = spawn_network_node(config, 0).await.unwrap();
+        let unknown = PeerId::random();
+
+        // Plain sends are handed to the swarm without waiting for the outcome
+        handle.direct_request(unknown, &[1]).await.unwrap();
+        handle.gossip("global".to_string(), &[1]).unwrap();
+
+        // while acked sends tell the caller why the message was not delivered
+        let direct = tokio::time::timeout(
+            Duration::from_secs(10),
+            handle.direct_request_acked(unknown, vec![1]),
+        )
+        .await
+        .unwrap();
+        assert!(
+            matches!(direct, Err(NetworkError::DialFailure(_))),
+            "{direct:?}"
+        );
+        let gossip = handle.gossip_acked("global".to_string(), vec![1]).await;
+        assert!(matches!(gossip, Err(NetworkError::NoPeers)), "{gossip:?}");
+
+        handle.shutdown().await.unwrap();
+    }
+}
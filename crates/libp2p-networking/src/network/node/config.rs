@@ -4,13 +4,17 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::{collections::HashSet, num::NonZeroUsize, time::Duration};
+use std::{collections::HashSet, num::NonZeroUsize, sync::Arc, time::Duration};
 
-use hotshot_types::traits::node_implementation::NodeType;
+use hotshot_types::{traits::node_implementation::NodeType, vid::ProtocolSchemes};
 use libp2p::{identity::Keypair, Multiaddr};
 use libp2p_identity::PeerId;
 
 use super::MAX_GOSSIP_MSG_SIZE;
+use crate::network::{
+    authorization::ConnectionAuthorizer, connection_dedup::DuplicateConnectionPolicy,
+    rate_limit::PeerRateLimitConfig, relay::RelayConfig,
+};
 
 /// The default Kademlia replication factor
 pub const DEFAULT_REPLICATION_FACTOR: Option<NonZeroUsize> = NonZeroUsize::new(10);
@@ -60,9 +64,40 @@ pub struct NetworkNodeConfig<T: NodeType> {
     #[builder(default)]
     pub auth_message: Option<Vec<u8>>,
 
+    /// The schemes payloads are hashed, committed to and dispersed with. If supplied, peers
+    /// using other schemes are refused during the handshake. Either all nodes supply them or
+    /// none do
+    #[builder(default)]
+    pub schemes: Option<ProtocolSchemes>,
+
     #[builder(default)]
     /// The timeout for DHT lookups.
     pub dht_timeout: Option<Duration>,
+
+    /// Limits on the direct messages sent to each peer. If not supplied, sends are not limited
+    #[builder(default)]
+    pub rate_limit: Option<PeerRateLimitConfig>,
+
+    /// What to do with redundant connections to the same peer
+    #[builder(default)]
+    pub duplicate_connection_policy: DuplicateConnectionPolicy,
+
+    /// The relays to route direct messages through when a peer can't be reached directly. If
+    /// not supplied, messages are only ever sent directly
+    #[builder(default)]
+    pub relay_config: Option<RelayConfig>,
+
+    /// Decides which peers to admit once their connection is established. If not supplied,
+    /// every peer passing the stake table authentication is admitted
+    #[builder(default)]
+    #[debug(skip)]
+    pub authorizer: Option<Arc<dyn ConnectionAuthorizer>>,
+
+    /// The most peers to stay connected to, bounding the degree of this node in the overlay.
+    /// Gossip still reaches every node through the peers' own connections. If not supplied,
+    /// connections are not limited
+    #[builder(default)]
+    pub max_peers: Option<NonZeroUsize>,
 }
 
 /// Configuration for Libp2p's Gossipsub
@@ -124,6 +159,10 @@ pub struct GossipConfig {
 
     /// Minimum number of peers to emit gossip to during a heartbeat
     pub gossip_lazy: usize,
+
+    /// Prefixed to the name of every topic we publish or subscribe to, so our topics don't
+    /// collide with those of other libp2p services on the same network
+    pub topic_prefix: String,
 }
 
 impl Default for GossipConfig {
@@ -156,10 +195,20 @@ impl Default for GossipConfig {
             gossip_lazy: 6,
 
             max_transmit_size: MAX_GOSSIP_MSG_SIZE, // The maximum gossip message size
+
+            topic_prefix: String::new(), // Topics are named as given
         }
     }
 }
 
+impl GossipConfig {
+    /// The name of `topic` on the network, with our topic prefix applied
+    #[must_use]
+    pub fn topic_name(&self, topic: &str) -> String {
+        format!("{}{topic}", self.topic_prefix)
+    }
+}
+
 /// Configuration for Libp2p's request-response
 #[derive(Clone, Debug)]
 pub struct RequestResponseConfig {
@@ -4,7 +4,12 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::{collections::HashSet, fmt::Debug, time::Duration};
+use std::{
+    collections::HashSet,
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use hotshot_types::traits::{network::NetworkError, node_implementation::NodeType};
 use libp2p::{request_response::ResponseChannel, Multiaddr};
@@ -17,7 +22,9 @@ use tracing::{debug, info, instrument};
 
 use crate::network::{
     behaviours::dht::record::{Namespace, RecordKey, RecordValue},
-    gen_multiaddr, ClientRequest, NetworkEvent, NetworkNode, NetworkNodeConfig,
+    gen_multiaddr,
+    rate_limit::{PeerRateLimiter, RateLimitDecision},
+    ClientRequest, NetworkEvent, NetworkNode, NetworkNodeConfig,
 };
 
 /// A handle containing:
@@ -39,6 +46,9 @@ pub struct NetworkNodeHandle<T: NodeType> {
 
     /// human readable id
     id: usize,
+
+    /// limits the direct messages sent to each peer, if configured
+    rate_limiter: Option<Arc<PeerRateLimiter>>,
 }
 
 /// internal network node receiver
@@ -103,12 +113,17 @@ pub async fn spawn_network_node<T: NodeType>(
         recv_kill: None,
     };
 
+    let rate_limiter = config
+        .rate_limit
+        .clone()
+        .map(|rate_limit| Arc::new(PeerRateLimiter::new(rate_limit)));
     let handle = NetworkNodeHandle::<T> {
         network_config: config,
         send_network: send_chan,
         listen_addr,
         peer_id,
         id,
+        rate_limiter,
     };
     Ok((receiver, handle))
 }
@@ -131,6 +146,14 @@ impl<T: NodeType> NetworkNodeHandle<T> {
         self.send_request(req)
     }
 
+    /// The number of direct messages which were delayed or dropped by the rate limit
+    #[must_use]
+    pub fn throttled_messages(&self) -> u64 {
+        self.rate_limiter
+            .as_ref()
+            .map_or(0, |rate_limiter| rate_limiter.throttled_messages())
+    }
+
     /// Get a reference to the network node handle's listen addr.
     #[must_use]
     pub fn listen_addr(&self) -> Multiaddr {
@@ -332,28 +355,80 @@ impl<T: NodeType> NetworkNodeHandle<T> {
     /// Make a direct request to `peer_id` containing `msg`
     /// # Errors
     /// - Will return [`NetworkError::ChannelSendError`] when underlying `NetworkNode` has been killed
-    /// - Will return [`NetworkError::FailedToSerialize`] when unable to serialize `msg`
-    pub fn direct_request(&self, pid: PeerId, msg: &[u8]) -> Result<(), NetworkError> {
-        self.direct_request_no_serialize(pid, msg.to_vec())
+    /// - Will return [`NetworkError::RateLimited`] when the message was dropped by the rate limit
+    pub async fn direct_request(&self, pid: PeerId, msg: &[u8]) -> Result<(), NetworkError> {
+        self.direct_request_no_serialize(pid, msg.to_vec()).await
     }
 
     /// Make a direct request to `peer_id` containing `msg` without serializing
+    ///
+    /// The request is handed to the swarm without waiting for it to be delivered, so delivery
+    /// failures are only logged. Use [`Self::direct_request_acked`] to learn about them.
+    ///
+    /// If a rate limit is configured, a message over the peer's budget is held back or dropped,
+    /// depending on the limit's policy.
     /// # Errors
     /// - Will return [`NetworkError::ChannelSendError`] when underlying `NetworkNode` has been killed
-    /// - Will return [`NetworkError::FailedToSerialize`] when unable to serialize `msg`
-    pub fn direct_request_no_serialize(
+    /// - Will return [`NetworkError::RateLimited`] when the message was dropped by the rate limit
+    pub async fn direct_request_no_serialize(
         &self,
         pid: PeerId,
         contents: Vec<u8>,
     ) -> Result<(), NetworkError> {
+        self.rate_limit(pid, contents.len()).await?;
         let req = ClientRequest::DirectRequest {
             pid,
             contents,
             retry_count: 1,
+            result_sender: None,
         };
         self.send_request(req)
     }
 
+    /// Make a direct request to `peer_id` containing `contents`, and wait for the peer to
+    /// acknowledge it
+    /// # Errors
+    /// - Will return [`NetworkError::ChannelSendError`] when underlying `NetworkNode` has been killed
+    /// - Will return [`NetworkError::RateLimited`] when the message was dropped by the rate limit
+    /// - Will return the swarm's error, e.g. [`NetworkError::DialFailure`] or
+    ///   [`NetworkError::StreamReset`], when the message could not be delivered
+    pub async fn direct_request_acked(
+        &self,
+        pid: PeerId,
+        contents: Vec<u8>,
+    ) -> Result<(), NetworkError> {
+        self.rate_limit(pid, contents.len()).await?;
+        let (s, r) = futures::channel::oneshot::channel();
+        let req = ClientRequest::DirectRequest {
+            pid,
+            contents,
+            retry_count: 1,
+            result_sender: Some(s),
+        };
+        self.send_request(req)?;
+        r.await.map_err(|_| NetworkError::RequestCancelled)?
+    }
+
+    /// Hold back a direct message of `len` bytes to `pid` for as long as the rate limit requires
+    /// # Errors
+    /// - Will return [`NetworkError::RateLimited`] when the message must be dropped
+    async fn rate_limit(&self, pid: PeerId, len: usize) -> Result<(), NetworkError> {
+        let Some(rate_limiter) = &self.rate_limiter else {
+            return Ok(());
+        };
+        match rate_limiter.check(pid, len, Instant::now()) {
+            RateLimitDecision::Send => Ok(()),
+            RateLimitDecision::Delay(delay) => {
+                debug!("Delaying direct message to {pid} by {delay:?}");
+                sleep(delay).await;
+                Ok(())
+            }
+            RateLimitDecision::Drop => Err(NetworkError::RateLimited(format!(
+                "dropped direct message to {pid}"
+            ))),
+        }
+    }
+
     /// Reply with `msg` to a request over `chan`
     /// # Errors
     /// - Will return [`NetworkError::ChannelSendError`] when underlying `NetworkNode` has been killed
@@ -392,10 +467,21 @@ impl<T: NodeType> NetworkNodeHandle<T> {
     /// - Will return [`NetworkError::ChannelSendError`] when underlying `NetworkNode` has been killed
     /// - Will return [`NetworkError::FailedToSerialize`] when unable to serialize `msg`
     pub fn gossip_no_serialize(&self, topic: String, msg: Vec<u8>) -> Result<(), NetworkError> {
-        let req = ClientRequest::GossipMsg(topic, msg);
+        let req = ClientRequest::GossipMsg(topic, msg, None);
         self.send_request(req)
     }
 
+    /// Gossip a message to peers, and wait for the swarm to report whether it was published
+    /// # Errors
+    /// - Will return [`NetworkError::ChannelSendError`] when underlying `NetworkNode` has been killed
+    /// - Will return [`NetworkError::NoPeers`] when there are no peers to gossip to
+    pub async fn gossip_acked(&self, topic: String, msg: Vec<u8>) -> Result<(), NetworkError> {
+        let (s, r) = futures::channel::oneshot::channel();
+        let req = ClientRequest::GossipMsg(topic, msg, Some(s));
+        self.send_request(req)?;
+        r.await.map_err(|_| NetworkError::RequestCancelled)?
+    }
+
     /// Tell libp2p about known network nodes
     /// # Errors
     /// - Will return [`NetworkError::ChannelSendError`] when underlying `NetworkNode` has been killed
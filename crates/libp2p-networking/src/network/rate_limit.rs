@@ -0,0 +1,249 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    collections::HashMap,
+    num::{NonZeroU32, NonZeroU64},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use libp2p_identity::PeerId;
+use parking_lot::Mutex;
+
+/// What to do with a message which exceeds a peer's rate limit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThrottlePolicy {
+    /// Hold the message back until the peer's budget allows it
+    #[default]
+    Queue,
+    /// Drop the message
+    Drop,
+}
+
+/// Limits on the messages sent directly to any single peer.
+///
+/// Each limit is a token bucket which refills at the given rate and holds up to one second's
+/// worth of tokens, so short bursts up to the rate are sent immediately.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeerRateLimitConfig {
+    /// The maximum number of messages per second to a single peer, if limited
+    pub messages_per_sec: Option<NonZeroU32>,
+    /// The maximum number of bytes per second to a single peer, if limited
+    pub bytes_per_sec: Option<NonZeroU64>,
+    /// What to do with messages exceeding either limit
+    pub policy: ThrottlePolicy,
+}
+
+/// The outcome of checking a message against a peer's rate limit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitDecision {
+    /// The message may be sent now
+    Send,
+    /// The message may be sent once the delay has passed
+    Delay(Duration),
+    /// The message must be dropped
+    Drop,
+}
+
+/// A token bucket refilling at `rate` tokens per second, holding at most `rate` tokens.
+///
+/// The balance may go negative when a message is admitted ahead of its budget; later messages
+/// then wait for the debt to be repaid.
+#[derive(Clone, Debug)]
+struct TokenBucket {
+    /// Tokens added per second, and the capacity of the bucket
+    rate: f64,
+    /// The current balance
+    tokens: f64,
+    /// When the balance was last refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// A full bucket
+    fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            rate,
+            tokens: rate,
+            last_refill: now,
+        }
+    }
+
+    /// Add the tokens accumulated since the last refill
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_refill = now;
+    }
+
+    /// How long until the bucket holds `cost` tokens. A cost above the capacity only has to wait
+    /// for a full bucket.
+    fn wait_for(&self, cost: f64) -> Duration {
+        let deficit = cost.min(self.rate) - self.tokens;
+        if deficit <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(deficit / self.rate)
+        }
+    }
+}
+
+/// The token buckets of a single peer
+#[derive(Clone, Debug, Default)]
+struct PeerBuckets {
+    /// Limits the number of messages
+    messages: Option<TokenBucket>,
+    /// Limits the number of bytes
+    bytes: Option<TokenBucket>,
+}
+
+/// Per-peer rate limiting of directly sent messages
+#[derive(Debug)]
+pub struct PeerRateLimiter {
+    /// The limits applied to every peer
+    config: PeerRateLimitConfig,
+    /// The buckets of each peer we have sent to
+    peers: Mutex<HashMap<PeerId, PeerBuckets>>,
+    /// The number of messages which were delayed or dropped
+    throttled: AtomicU64,
+}
+
+impl PeerRateLimiter {
+    /// Create a limiter applying `config` to every peer
+    #[must_use]
+    pub fn new(config: PeerRateLimitConfig) -> Self {
+        Self {
+            config,
+            peers: Mutex::new(HashMap::new()),
+            throttled: AtomicU64::new(0),
+        }
+    }
+
+    /// Check whether a message of `len` bytes may be sent to `peer` at `now`.
+    ///
+    /// Messages which are sent or delayed are charged against the peer's budget, dropped
+    /// messages are not.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn check(&self, peer: PeerId, len: usize, now: Instant) -> RateLimitDecision {
+        let mut peers = self.peers.lock();
+        let buckets = peers.entry(peer).or_insert_with(|| PeerBuckets {
+            messages: self
+                .config
+                .messages_per_sec
+                .map(|rate| TokenBucket::new(f64::from(rate.get()), now)),
+            bytes: self
+                .config
+                .bytes_per_sec
+                .map(|rate| TokenBucket::new(rate.get() as f64, now)),
+        });
+
+        let mut charges: Vec<(&mut TokenBucket, f64)> = [
+            (&mut buckets.messages, 1.0),
+            (&mut buckets.bytes, len as f64),
+        ]
+        .into_iter()
+        .filter_map(|(bucket, cost)| bucket.as_mut().map(|bucket| (bucket, cost)))
+        .collect();
+
+        let mut wait = Duration::ZERO;
+        for (bucket, cost) in &mut charges {
+            bucket.refill(now);
+            wait = wait.max(bucket.wait_for(*cost));
+        }
+
+        if !wait.is_zero() {
+            self.throttled.fetch_add(1, Ordering::Relaxed);
+            if self.config.policy == ThrottlePolicy::Drop {
+                return RateLimitDecision::Drop;
+            }
+        }
+
+        for (bucket, cost) in charges {
+            bucket.tokens -= cost;
+        }
+
+        if wait.is_zero() {
+            RateLimitDecision::Send
+        } else {
+            RateLimitDecision::Delay(wait)
+        }
+    }
+
+    /// The number of messages which were delayed or dropped so far
+    #[must_use]
+    pub fn throttled_messages(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A limiter allowing 10 messages and 1000 bytes per second to each peer
+    fn limiter(policy: ThrottlePolicy) -> PeerRateLimiter {
+        PeerRateLimiter::new(PeerRateLimitConfig {
+            messages_per_sec: NonZeroU32::new(10),
+            bytes_per_sec: NonZeroU64::new(1000),
+            policy,
+        })
+    }
+
+    #[test]
+    fn test_dropped_egress_is_capped() {
+        let limiter = limiter(ThrottlePolicy::Drop);
+        let peer = PeerId::random();
+        let start = Instant::now();
+
+        // Offer 100 messages per second for 5 seconds
+        let mut sent = 0;
+        for tick in 0..500 {
+            let now = start + Duration::from_millis(tick * 10);
+            if limiter.check(peer, 10, now) == RateLimitDecision::Send {
+                sent += 1;
+            }
+        }
+        // The initial burst, then 10 per second
+        assert!((50..=60).contains(&sent), "sent {sent} messages");
+        assert_eq!(limiter.throttled_messages(), 500 - sent);
+
+        // Other peers have their own budget
+        assert_eq!(
+            limiter.check(PeerId::random(), 10, start),
+            RateLimitDecision::Send
+        );
+    }
+
+    #[test]
+    fn test_queued_egress_is_paced() {
+        let limiter = limiter(ThrottlePolicy::Queue);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        // 30 messages at once: the first 10 go out immediately, the rest are spread out
+        let mut last_send = Duration::ZERO;
+        for _ in 0..30 {
+            match limiter.check(peer, 10, now) {
+                RateLimitDecision::Send => {}
+                RateLimitDecision::Delay(delay) => last_send = delay,
+                RateLimitDecision::Drop => panic!("Queued messages are never dropped"),
+            }
+        }
+        assert!(last_send >= Duration::from_millis(1990), "{last_send:?}");
+        assert_eq!(limiter.throttled_messages(), 20);
+
+        // The byte limit applies as well, and a message above it waits for a full bucket
+        let peer = PeerId::random();
+        assert_eq!(limiter.check(peer, 1000, now), RateLimitDecision::Send);
+        let RateLimitDecision::Delay(delay) = limiter.check(peer, 1500, now) else {
+            panic!("Expected the message to be delayed");
+        };
+        assert!(delay >= Duration::from_millis(990), "{delay:?}");
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use futures::channel::oneshot::Sender;
+use hotshot_types::traits::network::NetworkError;
+use libp2p::request_response::{Event, Message, OutboundRequestId, ResponseChannel};
+use libp2p_identity::PeerId;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::{behaviours::direct_message::outbound_failure_to_network_error, cbor, NetworkEvent};
+
+/// Configuration for routing direct messages through relay nodes when a peer can't be reached
+/// directly
+#[derive(Clone, Debug)]
+pub struct RelayConfig {
+    /// The relay nodes, in order of preference. A node forwards messages for others only if it
+    /// is in this list.
+    pub relays: Vec<PeerId>,
+    /// How long to keep trying to reach a peer directly before routing through a relay
+    pub direct_timeout: Duration,
+}
+
+/// A direct message routed through a relay. The relay reads the peer ids, but never the payload.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RelayEnvelope {
+    /// The peer which sent the message
+    source: Vec<u8>,
+    /// The peer the message is for
+    target: Vec<u8>,
+    /// The message itself
+    payload: Vec<u8>,
+}
+
+impl RelayEnvelope {
+    /// Wrap a message from `source` to `target`
+    #[must_use]
+    pub fn new(source: PeerId, target: PeerId, payload: Vec<u8>) -> Self {
+        Self {
+            source: source.to_bytes(),
+            target: target.to_bytes(),
+            payload,
+        }
+    }
+}
+
+/// How to send a direct message to a peer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Route {
+    /// Over a direct connection to the peer
+    Direct,
+    /// Through the given relay
+    Relay(PeerId),
+}
+
+/// Decides whether to reach each peer directly or through a relay, and tracks the messages in
+/// flight through relays
+#[derive(Debug)]
+pub(crate) struct RelayState {
+    /// The relay configuration, if relaying is enabled
+    config: Option<RelayConfig>,
+    /// Our own peer id
+    local_peer_id: PeerId,
+    /// The peers we have a direct connection to
+    connected: HashSet<PeerId>,
+    /// When we started trying to reach each peer we are not connected to
+    unreachable_since: HashMap<PeerId, Instant>,
+    /// Our own messages sent through a relay, and the senders waiting for their outcome
+    sent: HashMap<OutboundRequestId, Option<Sender<Result<(), NetworkError>>>>,
+    /// Messages we forwarded as a relay, and the channels to acknowledge them on
+    forwarded: HashMap<OutboundRequestId, ResponseChannel<Vec<u8>>>,
+}
+
+impl RelayState {
+    /// Create the relay state for the node `local_peer_id`
+    pub(crate) fn new(config: Option<RelayConfig>, local_peer_id: PeerId) -> Self {
+        Self {
+            config,
+            local_peer_id,
+            connected: HashSet::new(),
+            unreachable_since: HashMap::new(),
+            sent: HashMap::new(),
+            forwarded: HashMap::new(),
+        }
+    }
+
+    /// Choose how to send a message to `peer` at time `now`.
+    ///
+    /// A peer we are not connected to is tried directly until the direct timeout has passed
+    /// since the first attempt, and through a relay after that, preferring the relays we are
+    /// connected to. Once a direct connection is established, messages go direct again.
+    pub(crate) fn route(&mut self, peer: PeerId, now: Instant) -> Route {
+        let Some(config) = &self.config else {
+            return Route::Direct;
+        };
+        if self.connected.contains(&peer) {
+            return Route::Direct;
+        }
+
+        let since = *self.unreachable_since.entry(peer).or_insert(now);
+        if now.saturating_duration_since(since) < config.direct_timeout {
+            return Route::Direct;
+        }
+
+        let mut relays = config
+            .relays
+            .iter()
+            .filter(|relay| **relay != peer && **relay != self.local_peer_id);
+        let relay = relays
+            .clone()
+            .find(|relay| self.connected.contains(relay))
+            .or_else(|| relays.next());
+
+        relay.map_or(Route::Direct, |relay| Route::Relay(*relay))
+    }
+
+    /// Record that a direct connection to `peer` was established
+    pub(crate) fn connection_established(&mut self, peer: PeerId) {
+        self.connected.insert(peer);
+        self.unreachable_since.remove(&peer);
+    }
+
+    /// Record that the last direct connection to `peer` was closed
+    pub(crate) fn connection_closed(&mut self, peer: PeerId) {
+        self.connected.remove(&peer);
+    }
+
+    /// Record that dialing `peer` at time `now` failed
+    pub(crate) fn dial_failed(&mut self, peer: PeerId, now: Instant) {
+        if !self.connected.contains(&peer) {
+            self.unreachable_since.entry(peer).or_insert(now);
+        }
+    }
+
+    /// Track a message we sent through a relay as request `request_id`
+    pub(crate) fn add_relayed_request(
+        &mut self,
+        request_id: OutboundRequestId,
+        result_sender: Option<Sender<Result<(), NetworkError>>>,
+    ) {
+        self.sent.insert(request_id, result_sender);
+    }
+
+    /// Whether we forward messages for other peers
+    fn is_relay(&self) -> bool {
+        self.config
+            .as_ref()
+            .is_some_and(|config| config.relays.contains(&self.local_peer_id))
+    }
+
+    /// Handle an event of the relay protocol, returning the message to deliver to the client
+    /// if one was relayed to us
+    pub(crate) fn handle_relay_event(
+        &mut self,
+        event: Event<RelayEnvelope, Vec<u8>>,
+        relay: &mut cbor::Behaviour<RelayEnvelope, Vec<u8>>,
+    ) -> Option<NetworkEvent> {
+        match event {
+            Event::Message { peer, message } => match message {
+                Message::Request {
+                    request, channel, ..
+                } => self.handle_envelope(peer, request, channel, relay),
+                Message::Response {
+                    request_id,
+                    response,
+                } => {
+                    if let Some(channel) = self.forwarded.remove(&request_id) {
+                        // Pass the target's acknowledgement back to the sender
+                        let _ = relay.send_response(channel, response);
+                    } else if let Some(Some(result_sender)) = self.sent.remove(&request_id) {
+                        let _ = result_sender.send(Ok(()));
+                    }
+                    None
+                }
+            },
+            Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+            } => {
+                warn!("Relayed message failure to {:?}: {:?}", peer, error);
+                // Dropping the channel of a forwarded message reports the failure to its sender
+                self.forwarded.remove(&request_id);
+                if let Some(Some(result_sender)) = self.sent.remove(&request_id) {
+                    let _ = result_sender.send(Err(outbound_failure_to_network_error(&error)));
+                }
+                None
+            }
+            Event::InboundFailure { peer, error, .. } => {
+                warn!(
+                    "Inbound relayed message failure from {:?}: {:?}",
+                    peer, error
+                );
+                None
+            }
+            Event::ResponseSent { .. } => None,
+        }
+    }
+
+    /// Handle an envelope received from `peer`: deliver it if it is for us, or forward it if we
+    /// are a relay
+    fn handle_envelope(
+        &mut self,
+        peer: PeerId,
+        envelope: RelayEnvelope,
+        channel: ResponseChannel<Vec<u8>>,
+        relay: &mut cbor::Behaviour<RelayEnvelope, Vec<u8>>,
+    ) -> Option<NetworkEvent> {
+        let (Ok(source), Ok(target)) = (
+            PeerId::from_bytes(&envelope.source),
+            PeerId::from_bytes(&envelope.target),
+        ) else {
+            warn!(
+                "Received a relayed message with invalid peer ids from {:?}",
+                peer
+            );
+            return None;
+        };
+
+        if target == self.local_peer_id {
+            // The relay's channel can't be handed to the client, so acknowledge here
+            let _ = relay.send_response(channel, Vec::new());
+            return Some(NetworkEvent::RelayedRequest(envelope.payload, source));
+        }
+
+        if !self.is_relay() {
+            warn!("Refusing to relay a message from {:?}: not a relay", peer);
+            return None;
+        }
+        if source != peer {
+            warn!(
+                "Refusing to relay a message from {:?} claiming to be from {:?}",
+                peer, source
+            );
+            return None;
+        }
+
+        debug!("Relaying a message from {:?} to {:?}", source, target);
+        let request_id = relay.send_request(&target, envelope);
+        self.forwarded.insert(request_id, channel);
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The relay state of a fresh node using `relays`
+    fn relay_state(relays: Vec<PeerId>) -> RelayState {
+        RelayState::new(
+            Some(RelayConfig {
+                relays,
+                direct_timeout: Duration::from_secs(1),
+            }),
+            PeerId::random(),
+        )
+    }
+
+    #[test]
+    fn test_unreachable_peer_is_routed_through_relay_until_connected() {
+        let (relay, peer) = (PeerId::random(), PeerId::random());
+        let mut state = relay_state(vec![relay]);
+        let start = Instant::now();
+
+        // Tried directly until the timeout passes
+        assert_eq!(state.route(peer, start), Route::Direct);
+        assert_eq!(
+            state.route(peer, start + Duration::from_millis(500)),
+            Route::Direct
+        );
+        assert_eq!(
+            state.route(peer, start + Duration::from_secs(1)),
+            Route::Relay(relay)
+        );
+
+        // Back to direct once connected
+        state.connection_established(peer);
+        assert_eq!(
+            state.route(peer, start + Duration::from_secs(2)),
+            Route::Direct
+        );
+
+        // And the timeout starts over after losing the connection
+        state.connection_closed(peer);
+        let lost = start + Duration::from_secs(3);
+        assert_eq!(state.route(peer, lost), Route::Direct);
+        assert_eq!(
+            state.route(peer, lost + Duration::from_secs(1)),
+            Route::Relay(relay)
+        );
+    }
+
+    #[test]
+    fn test_connected_relay_is_preferred() {
+        let (first, second, peer) = (PeerId::random(), PeerId::random(), PeerId::random());
+        let mut state = relay_state(vec![first, second, peer]);
+        let start = Instant::now();
+        state.dial_failed(peer, start);
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(state.route(peer, later), Route::Relay(first));
+        state.connection_established(second);
+        assert_eq!(state.route(peer, later), Route::Relay(second));
+    }
+
+    #[test]
+    fn test_no_relays_means_direct() {
+        let mut state = RelayState::new(None, PeerId::random());
+        let peer = PeerId::random();
+        let start = Instant::now();
+
+        assert_eq!(state.route(peer, start), Route::Direct);
+        assert_eq!(
+            state.route(peer, start + Duration::from_secs(60)),
+            Route::Direct
+        );
+    }
+}
@@ -8,10 +8,13 @@ use std::{
 
 use anyhow::{ensure, Context, Result as AnyhowResult};
 use futures::{future::poll_fn, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use hotshot_types::traits::{
-    election::Membership,
-    node_implementation::{ConsensusTime, NodeType},
-    signature_key::SignatureKey,
+use hotshot_types::{
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+        signature_key::SignatureKey,
+    },
+    vid::ProtocolSchemes,
 };
 use libp2p::{
     core::{muxing::StreamMuxerExt, transport::TransportEvent, StreamMuxer},
@@ -21,7 +24,7 @@ use libp2p::{
 use pin_project::pin_project;
 use serde::{Deserialize, Serialize};
 use tokio::time::timeout;
-use tracing::warn;
+use tracing::{error, warn};
 
 /// The maximum size of an authentication message. This is used to prevent
 /// DoS attacks by sending large messages.
@@ -47,6 +50,9 @@ pub struct StakeTableAuthentication<T: Transport, Types: NodeType, C: StreamMuxe
     /// A pre-signed message that we send to the remote peer for authentication
     pub auth_message: Arc<Option<Vec<u8>>>,
 
+    /// The schemes we check the remote peer uses too
+    pub schemes: Arc<Option<ProtocolSchemes>>,
+
     /// Phantom data for the connection type
     pd: std::marker::PhantomData<C>,
 }
@@ -62,11 +68,13 @@ impl<T: Transport, Types: NodeType, C: StreamMuxer + Unpin> StakeTableAuthentica
         inner: T,
         stake_table: Option<Types::Membership>,
         auth_message: Option<Vec<u8>>,
+        schemes: Option<ProtocolSchemes>,
     ) -> Self {
         Self {
             inner,
             stake_table: Arc::from(stake_table),
             auth_message: Arc::from(auth_message),
+            schemes: Arc::from(schemes),
             pd: std::marker::PhantomData,
         }
     }
@@ -140,6 +148,41 @@ impl<T: Transport, Types: NodeType, C: StreamMuxer + Unpin> StakeTableAuthentica
         Ok(())
     }
 
+    /// Exchange our schemes with the remote peer, and check that it uses the same ones.
+    ///
+    /// `outgoing` is a boolean that indicates if the connection is incoming or outgoing. We
+    /// always send ours before failing, so the remote peer learns of the mismatch too.
+    ///
+    /// # Errors
+    /// - If we fail to read from or write to the stream
+    /// - If the remote peer's schemes are invalid
+    /// - If the remote peer uses different schemes
+    pub async fn verify_schemes<S: AsyncRead + AsyncWrite + Unpin>(
+        stream: &mut S,
+        schemes: &ProtocolSchemes,
+        outgoing: bool,
+    ) -> AnyhowResult<()> {
+        let ours = bincode::serialize(schemes).with_context(|| "Failed to serialize schemes")?;
+
+        // Send ours first when dialing, and read theirs first otherwise
+        if outgoing {
+            write_length_delimited(stream, &ours).await?;
+        }
+        let message = read_length_delimited(stream, MAX_AUTH_MESSAGE_SIZE).await;
+        if !outgoing {
+            write_length_delimited(stream, &ours).await?;
+        }
+
+        let theirs: ProtocolSchemes =
+            bincode::deserialize(&message?).with_context(|| "Failed to deserialize schemes")?;
+        ensure!(
+            theirs == *schemes,
+            "Incompatible schemes: we use {schemes}, the peer uses {theirs}"
+        );
+
+        Ok(())
+    }
+
     /// Wrap the supplied future in an upgrade that performs the authentication handshake.
     ///
     /// `outgoing` is a boolean that indicates if the connection is incoming or outgoing.
@@ -149,6 +192,7 @@ impl<T: Transport, Types: NodeType, C: StreamMuxer + Unpin> StakeTableAuthentica
         outgoing: bool,
         stake_table: Arc<Option<Types::Membership>>,
         auth_message: Arc<Option<Vec<u8>>>,
+        schemes: Arc<Option<ProtocolSchemes>>,
     ) -> UpgradeFuture<T>
     where
         T::Error: From<<C as StreamMuxer>::Error> + From<IoError>,
@@ -213,6 +257,20 @@ impl<T: Transport, Types: NodeType, C: StreamMuxer + Unpin> StakeTableAuthentica
                         })?;
                 }
 
+                // Check that the remote peer uses the same schemes as us
+                if let Some(schemes) = schemes.as_ref() {
+                    Self::verify_schemes(&mut substream, schemes, outgoing)
+                        .await
+                        .map_err(|e| {
+                            error!(
+                                "Remote peer {} is incompatible: {:?}",
+                                stream.as_peer_id(),
+                                e
+                            );
+                            IoError::new(IoErrorKind::Other, e)
+                        })?;
+                }
+
                 Ok(stream)
             })
             .await
@@ -321,10 +379,17 @@ where
         // Clone the necessary fields
         let auth_message = Arc::clone(&self.auth_message);
         let stake_table = Arc::clone(&self.stake_table);
+        let schemes = Arc::clone(&self.schemes);
 
         // If the dial was successful, perform the authentication handshake on top
         match res {
-            Ok(dial) => Ok(Self::gen_handshake(dial, true, stake_table, auth_message)),
+            Ok(dial) => Ok(Self::gen_handshake(
+                dial,
+                true,
+                stake_table,
+                auth_message,
+                schemes,
+            )),
             Err(err) => Err(err),
         }
     }
@@ -342,10 +407,17 @@ where
         // Clone the necessary fields
         let auth_message = Arc::clone(&self.auth_message);
         let stake_table = Arc::clone(&self.stake_table);
+        let schemes = Arc::clone(&self.schemes);
 
         // If the dial was successful, perform the authentication handshake on top
         match res {
-            Ok(dial) => Ok(Self::gen_handshake(dial, false, stake_table, auth_message)),
+            Ok(dial) => Ok(Self::gen_handshake(
+                dial,
+                false,
+                stake_table,
+                auth_message,
+                schemes,
+            )),
             Err(err) => Err(err),
         }
     }
@@ -370,10 +442,11 @@ where
                     // Clone the necessary fields
                     let auth_message = Arc::clone(&self.auth_message);
                     let stake_table = Arc::clone(&self.stake_table);
+                    let schemes = Arc::clone(&self.schemes);
 
                     // Generate the handshake upgrade future (inbound)
                     let auth_upgrade =
-                        Self::gen_handshake(upgrade, false, stake_table, auth_message);
+                        Self::gen_handshake(upgrade, false, stake_table, auth_message, schemes);
 
                     // Return the new event
                     TransportEvent::Incoming {
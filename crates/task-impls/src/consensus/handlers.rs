@@ -24,8 +24,10 @@ use vbs::version::StaticVersionType;
 
 use super::ConsensusTaskState;
 use crate::{
-    consensus::Versions, events::HotShotEvent, helpers::broadcast_event,
-    vote_collection::handle_vote,
+    consensus::Versions,
+    events::HotShotEvent,
+    helpers::{advance_epoch, broadcast_event},
+    vote_collection::{ensure_undecided, handle_vote, remove_decided_collectors},
 };
 
 /// Handle a `QuorumVoteRecv` event.
@@ -39,13 +41,17 @@ pub(crate) async fn handle_quorum_vote_recv<
     sender: &Sender<Arc<HotShotEvent<TYPES>>>,
     task_state: &mut ConsensusTaskState<TYPES, I, V>,
 ) -> Result<()> {
-    let is_vote_leaf_extended = task_state
-        .consensus
-        .read()
-        .await
-        .is_leaf_extended(vote.data.leaf_commit);
+    let (is_vote_leaf_extended, decided_view) = {
+        let consensus_reader = task_state.consensus.read().await;
+        (
+            consensus_reader.is_leaf_extended(vote.data.leaf_commit),
+            consensus_reader.last_decided_view(),
+        )
+    };
     let we_are_leader = task_state
         .membership
+        .read()
+        .await
         .leader(vote.view_number() + 1, task_state.cur_epoch)?
         == task_state.public_key;
     ensure!(
@@ -56,6 +62,29 @@ pub(crate) async fn handle_quorum_vote_recv<
         )
     );
 
+    let view = vote.view_number();
+    remove_decided_collectors(&mut task_state.vote_collectors, decided_view);
+    ensure_undecided(&task_state.vote_collectors, view, decided_view)?;
+
+    if let Some(timeout) = task_state.quorum_formation_timeout {
+        if task_state
+            .vote_collectors
+            .get(&view)
+            .is_some_and(|collector| {
+                collector.certificate_formed_at.is_none()
+                    && collector.started_at.elapsed() > timeout
+            })
+        {
+            tracing::warn!("QC for view {view:?} did not form within {timeout:?}, abandoning it");
+            task_state.vote_collectors.remove(&view);
+            task_state.abandoned_quorum_views.insert(view);
+        }
+        ensure!(
+            !task_state.abandoned_quorum_views.contains(&view),
+            info!("Ignoring vote for view {view:?}, whose QC was abandoned")
+        );
+    }
+
     handle_vote(
         &mut task_state.vote_collectors,
         vote,
@@ -67,6 +96,8 @@ pub(crate) async fn handle_quorum_vote_recv<
         sender,
         &task_state.upgrade_lock,
         !is_vote_leaf_extended,
+        &task_state.vote_metrics,
+        task_state.late_vote_grace,
     )
     .await?;
 
@@ -88,6 +119,8 @@ pub(crate) async fn handle_timeout_vote_recv<
     ensure!(
         task_state
             .membership
+            .read()
+            .await
             .leader(vote.view_number() + 1, task_state.cur_epoch)?
             == task_state.public_key,
         info!(
@@ -96,6 +129,14 @@ pub(crate) async fn handle_timeout_vote_recv<
         )
     );
 
+    let decided_view = task_state.consensus.read().await.last_decided_view();
+    remove_decided_collectors(&mut task_state.timeout_vote_collectors, decided_view);
+    ensure_undecided(
+        &task_state.timeout_vote_collectors,
+        vote.view_number(),
+        decided_view,
+    )?;
+
     handle_vote(
         &mut task_state.timeout_vote_collectors,
         vote,
@@ -107,6 +148,8 @@ pub(crate) async fn handle_timeout_vote_recv<
         sender,
         &task_state.upgrade_lock,
         true,
+        &task_state.vote_metrics,
+        task_state.late_vote_grace,
     )
     .await?;
 
@@ -132,6 +175,8 @@ pub async fn send_high_qc<TYPES: NodeType, V: Versions, I: NodeImplementation<TY
     let high_qc = task_state.consensus.read().await.high_qc().clone();
     let leader = task_state
         .membership
+        .read()
+        .await
         .leader(new_view_number, TYPES::Epoch::new(0))?;
     broadcast_event(
         Arc::new(HotShotEvent::HighQcSend(
@@ -157,9 +202,14 @@ pub(crate) async fn handle_view_change<
     sender: &Sender<Arc<HotShotEvent<TYPES>>>,
     task_state: &mut ConsensusTaskState<TYPES, I, V>,
 ) -> Result<()> {
-    if epoch_number > task_state.cur_epoch {
-        task_state.cur_epoch = epoch_number;
-        tracing::info!("Progress: entered epoch {:>6}", *epoch_number);
+    let prev_epoch = task_state.cur_epoch;
+    advance_epoch::<TYPES>(
+        &mut task_state.cur_epoch,
+        epoch_number,
+        task_state.max_epoch_skip,
+    );
+    if task_state.cur_epoch != prev_epoch {
+        tracing::info!("Progress: entered epoch {:>6}", *task_state.cur_epoch);
     }
 
     ensure!(
@@ -184,12 +234,20 @@ pub(crate) async fn handle_view_change<
 
     // Move this node to the next view
     task_state.cur_view = new_view_number;
+    task_state.abandoned_quorum_views = task_state
+        .abandoned_quorum_views
+        .split_off(&old_view_number);
     task_state
         .consensus
         .write()
         .await
         .update_view(new_view_number)?;
 
+    // Drop the collectors for views which have decided since we last looked
+    let decided_view = task_state.consensus.read().await.last_decided_view();
+    remove_decided_collectors(&mut task_state.vote_collectors, decided_view);
+    remove_decided_collectors(&mut task_state.timeout_vote_collectors, decided_view);
+
     // If we have a decided upgrade certificate, the protocol version may also have been upgraded.
     let decided_upgrade_certificate_read = task_state
         .upgrade_lock
@@ -232,6 +290,8 @@ pub(crate) async fn handle_view_change<
     let cur_view_time = Utc::now().timestamp();
     if task_state
         .membership
+        .read()
+        .await
         .leader(old_view_number, task_state.cur_epoch)?
         == task_state.public_key
     {
@@ -282,9 +342,16 @@ pub(crate) async fn handle_timeout<TYPES: NodeType, I: NodeImplementation<TYPES>
         "Timeout event is for an old view"
     );
 
+    ensure!(
+        task_state.consensus.read().await.safety_fork().is_none(),
+        error!("Halted after a safety fork, not sending a timeout vote")
+    );
+
     ensure!(
         task_state
             .membership
+            .read()
+            .await
             .has_stake(&task_state.public_key, task_state.cur_epoch),
         debug!(
             "We were not chosen for the consensus committee for view {:?}",
@@ -295,6 +362,7 @@ pub(crate) async fn handle_timeout<TYPES: NodeType, I: NodeImplementation<TYPES>
     let vote = TimeoutVote::create_signed_vote(
         TimeoutData::<TYPES> { view: view_number },
         view_number,
+        task_state.cur_epoch,
         &task_state.public_key,
         &task_state.private_key,
         &task_state.upgrade_lock,
@@ -336,6 +404,8 @@ pub(crate) async fn handle_timeout<TYPES: NodeType, I: NodeImplementation<TYPES>
         .add(1);
     if task_state
         .membership
+        .read()
+        .await
         .leader(view_number, task_state.cur_epoch)?
         == task_state.public_key
     {
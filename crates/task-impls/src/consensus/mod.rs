@@ -4,9 +4,10 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
 
 use async_broadcast::{Receiver, Sender};
+use async_lock::RwLock;
 use async_trait::async_trait;
 use hotshot_task::task::TaskState;
 use hotshot_types::{
@@ -19,6 +20,7 @@ use hotshot_types::{
         node_implementation::{NodeImplementation, NodeType, Versions},
         signature_key::SignatureKey,
     },
+    vote_metrics::VoteMetrics,
 };
 use tokio::task::JoinHandle;
 use tracing::instrument;
@@ -47,7 +49,7 @@ pub struct ConsensusTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V:
     pub network: Arc<I::Network>,
 
     /// Membership for Quorum Certs/votes
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// A map of `QuorumVote` collector tasks.
     pub vote_collectors: VoteCollectorsMap<TYPES, QuorumVote2<TYPES>, QuorumCertificate2<TYPES>, V>,
@@ -65,6 +67,9 @@ pub struct ConsensusTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V:
     /// The epoch number that this node is currently executing in.
     pub cur_epoch: TYPES::Epoch,
 
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
+
     /// Output events to application
     pub output_event_stream: async_broadcast::Sender<Event<TYPES>>,
 
@@ -83,8 +88,20 @@ pub struct ConsensusTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V:
     /// Lock for a decided upgrade
     pub upgrade_lock: UpgradeLock<TYPES, V>,
 
+    /// How long votes are still recorded for participation after their certificate formed
+    pub late_vote_grace: Duration,
+
+    /// Metrics of the votes this node accumulates
+    pub vote_metrics: Arc<VoteMetrics>,
+
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+
+    /// How long we wait for a QC to form once votes for a view arrive, if bounded
+    pub quorum_formation_timeout: Option<Duration>,
+
+    /// Views whose QC did not form within the quorum formation timeout, and were given up on
+    pub abandoned_quorum_views: BTreeSet<TYPES::View>,
 }
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> ConsensusTaskState<TYPES, I, V> {
     /// Handles a consensus event received on the event stream
@@ -4,38 +4,52 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::{marker::PhantomData, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_broadcast::{Receiver, Sender};
 use async_lock::RwLock;
 use async_trait::async_trait;
 use hotshot_task::task::TaskState;
 use hotshot_types::{
-    consensus::{Consensus, OuterConsensus},
+    consensus::{Consensus, EarlyVotePolicy, OuterConsensus},
     data::{DaProposal, PackedBundle},
     event::{Event, EventType},
     message::{Proposal, UpgradeLock},
     simple_certificate::DaCertificate,
     simple_vote::{DaData, DaVote},
     traits::{
-        block_contents::vid_commitment,
-        election::Membership,
-        network::ConnectedNetwork,
+        election::{CommitteeDiff, Membership},
+        network::{ConnectedNetwork, NetworkError},
         node_implementation::{NodeImplementation, NodeType, Versions},
         signature_key::SignatureKey,
         storage::Storage,
+        BlockPayload,
     },
+    utils::CommitmentHex,
+    vid::VidCommitment,
     vote::HasViewNumber,
+    vote_metrics::VoteMetrics,
 };
 use sha2::{Digest, Sha256};
-use tokio::{spawn, task::spawn_blocking};
+use tokio::{
+    spawn,
+    task::{spawn_blocking, JoinHandle},
+    time::sleep,
+};
 use tracing::instrument;
 use utils::anytrace::*;
 
 use crate::{
     events::HotShotEvent,
-    helpers::broadcast_event,
-    vote_collection::{handle_vote, VoteCollectorsMap},
+    helpers::{advance_epoch, broadcast_event, VotingWarmup},
+    vote_collection::{
+        ensure_undecided, handle_vote, remove_decided_collectors, VoteCollectorsMap,
+    },
 };
 
 /// Tracks state of a DA task
@@ -55,7 +69,7 @@ pub struct DaTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Version
     /// Membership for the DA committee and quorum committee.
     /// We need the latter only for calculating the proper VID scheme
     /// from the number of nodes in the quorum.
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// The underlying network
     pub network: Arc<I::Network>,
@@ -77,9 +91,438 @@ pub struct DaTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Version
 
     /// Lock for a decided upgrade
     pub upgrade_lock: UpgradeLock<TYPES, V>,
+
+    /// How long votes are still recorded for participation after their certificate formed
+    pub late_vote_grace: Duration,
+
+    /// Metrics of the votes this node accumulates
+    pub vote_metrics: Arc<VoteMetrics>,
+
+    /// Number of times to retry appending a DA proposal to storage before giving up
+    pub storage_retries: usize,
+
+    /// Base backoff between storage retries, doubled after each failed attempt
+    pub storage_retry_backoff: Duration,
+
+    /// Function used to compute the VID commitment of a DA proposal's payload
+    pub vid_commitment_fn: fn(&[u8], usize) -> VidCommitment,
+
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
+
+    /// Warm-up period before we vote, if we joined behind the network
+    pub voting_warmup: VotingWarmup<TYPES>,
+
+    /// DA proposals for views beyond the next one, with their senders, replayed once we reach
+    /// their view
+    pub future_proposal_buffer:
+        BTreeMap<TYPES::View, (Proposal<TYPES, DaProposal<TYPES>>, TYPES::SignatureKey)>,
+
+    /// Furthest number of views beyond the next one that a DA proposal is buffered for
+    pub max_future_buffer: u64,
+
+    /// Largest payload, in bytes, we hash or compute VID over for a received DA proposal
+    pub max_da_payload_bytes: usize,
+
+    /// How long we wait for a DA certificate to form once we begin collecting votes for it
+    pub da_vote_timeout: Duration,
+
+    /// Timers of the views whose DA votes we are collecting, each announcing a `DaVoteTimeout`
+    /// unless cancelled by its certificate forming or the view passing
+    pub da_vote_timers: BTreeMap<TYPES::View, JoinHandle<()>>,
+
+    /// Views we lead whose DA certificate formed, so votes arriving after it don't start their
+    /// timer again
+    pub da_certificate_views: BTreeSet<TYPES::View>,
+
+    /// What to do with a DA vote received before the proposal for its view
+    pub early_vote_policy: EarlyVotePolicy,
+
+    /// DA votes held until the proposal for their view is validated or sent, with the time the
+    /// first of them arrived
+    pub early_votes: BTreeMap<TYPES::View, (Instant, Vec<DaVote<TYPES>>)>,
+
+    /// Views whose DA proposal we validated or sent, whose votes are handled on arrival
+    pub proposal_views: BTreeSet<TYPES::View>,
+
+    /// The leader and payload hash of the first validly signed DA proposal received for each view,
+    /// to detect a leader signing another payload for the same view
+    pub proposal_hashes: BTreeMap<TYPES::View, (TYPES::SignatureKey, [u8; 32])>,
 }
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYPES, I, V> {
+    /// Append the DA proposal to storage, retrying with exponential backoff up to
+    /// `storage_retries` times so that a transient storage failure does not cost us the vote.
+    async fn append_da_with_retry(
+        &self,
+        proposal: &Proposal<TYPES, DaProposal<TYPES>>,
+        payload_commitment: VidCommitment,
+    ) -> Result<()> {
+        let mut backoff = self.storage_retry_backoff;
+        let mut attempt = 0;
+
+        loop {
+            let result = self
+                .storage
+                .write()
+                .await
+                .append_da(proposal, payload_commitment)
+                .await;
+
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.storage_retries => {
+                    attempt += 1;
+                    tracing::warn!(
+                        payload_commitment = %payload_commitment.to_hex(),
+                        "Failed to append DA proposal to storage (attempt {attempt}), retrying in {backoff:?}: {e:?}"
+                    );
+                    self.consensus
+                        .read()
+                        .await
+                        .metrics
+                        .da_storage_retries
+                        .add(1);
+                    sleep(backoff).await;
+                    backoff = backoff.saturating_mul(2);
+                }
+                Err(e) => {
+                    self.consensus
+                        .read()
+                        .await
+                        .metrics
+                        .da_storage_failures
+                        .add(1);
+                    return Err(e).wrap().context(error!(
+                        "Failed to append DA proposal to storage after {} attempts",
+                        attempt + 1
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Validate a DA proposal received from the network, or buffer it if it is for a future view
+    async fn handle_da_proposal_recv(
+        &mut self,
+        proposal: &Proposal<TYPES, DaProposal<TYPES>>,
+        sender: &TYPES::SignatureKey,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+    ) -> Result<()> {
+        let sender = sender.clone();
+        tracing::debug!(
+            "DA proposal received for view: {:?}",
+            proposal.data.view_number()
+        );
+        // ED NOTE: Assuming that the next view leader is the one who sends DA proposal for this view
+        let view = proposal.data.view_number();
+
+        // Checked before anything else, as hashing an oversized payload is the expensive part
+        let payload_bytes = proposal.data.encoded_transactions.len();
+        ensure!(
+            payload_bytes <= self.max_da_payload_bytes,
+            warn!(
+                "Throwing away DA proposal for view {view:?} with a {payload_bytes} byte payload, \
+                 over the limit of {} bytes",
+                self.max_da_payload_bytes
+            )
+        );
+        TYPES::BlockPayload::validate_metadata(&proposal.data.metadata, payload_bytes).context(
+            warn!("Throwing away DA proposal for view {view:?} with bad metadata"),
+        )?;
+
+        // Allow a DA proposal that is one view older, in case we have voted on a quorum
+        // proposal and updated the view.
+        //
+        // Anything older is discarded because it is no longer relevant.
+        ensure!(
+            self.cur_view <= view + 1,
+            "Throwing away DA proposal that is more than one view older"
+        );
+        ensure!(
+            *view <= (*self.cur_view + 1).saturating_add(self.max_future_buffer),
+            info!("Throwing away DA proposal for view {view:?}, too far ahead of our view")
+        );
+
+        let encoded_transactions_hash = Sha256::digest(&proposal.data.encoded_transactions);
+        let membership_reader = self.membership.read().await;
+        let view_leader_key = membership_reader.leader(view, self.cur_epoch)?;
+        ensure!(
+            view_leader_key == sender,
+            warn!(
+                "DA proposal doesn't have expected leader key for view {} \n DA proposal is: {:?}",
+                *view,
+                proposal.data.clone()
+            )
+        );
+        ensure!(
+            membership_reader.verify_leader_proof(
+                view,
+                self.cur_epoch,
+                &view_leader_key,
+                &proposal.leader_proof
+            ),
+            warn!("DA proposal for view {view:?} does not prove its leader was selected")
+        );
+        drop(membership_reader);
+
+        ensure!(
+            view_leader_key.validate(&proposal.signature, &encoded_transactions_hash),
+            warn!("Could not verify proposal.")
+        );
+
+        // Only signed proposals are recorded, so a conflict is always the leader's own doing
+        self.detect_equivocation(
+            view,
+            &sender,
+            encoded_transactions_hash.into(),
+            event_stream,
+        )
+        .await;
+
+        // Hold on to a proposal for a view beyond the next one until we reach it. It is checked
+        // against the leader again then, in case the epoch changed in between.
+        if view > self.cur_view + 1 {
+            self.buffer_future_proposal(proposal.clone(), sender);
+            return Ok(());
+        }
+
+        ensure!(
+            !self
+                .consensus
+                .read()
+                .await
+                .saved_payloads()
+                .contains_key(&view),
+            info!(
+              "Received DA proposal for view {:?} but we already have a payload for that view.  Throwing it away",
+              view
+            )
+        );
+
+        broadcast_event(
+            Arc::new(HotShotEvent::DaProposalValidated(proposal.clone(), sender)),
+            event_stream,
+        )
+        .await;
+
+        Ok(())
+    }
+
+    /// Record the payload hash of a signed DA proposal for `view`, announcing an equivocation if
+    /// its leader already signed a different payload for the view
+    async fn detect_equivocation(
+        &mut self,
+        view: TYPES::View,
+        leader: &TYPES::SignatureKey,
+        hash: [u8; 32],
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+    ) {
+        let (first_leader, first_hash) = self
+            .proposal_hashes
+            .entry(view)
+            .or_insert_with(|| (leader.clone(), hash));
+        if *first_leader != *leader || *first_hash == hash {
+            return;
+        }
+
+        tracing::warn!("Leader {leader} signed conflicting DA proposals for view {view:?}");
+        broadcast_event(
+            Arc::new(HotShotEvent::DaProposalEquivocation {
+                view,
+                leader: leader.clone(),
+                first_hash: *first_hash,
+                second_hash: hash,
+            }),
+            event_stream,
+        )
+        .await;
+    }
+
+    /// Buffer a signed DA proposal for a future view. Only the first proposal received for a view
+    /// is kept, and the views are bounded by `max_future_buffer`, so the buffer stays small.
+    fn buffer_future_proposal(
+        &mut self,
+        proposal: Proposal<TYPES, DaProposal<TYPES>>,
+        sender: TYPES::SignatureKey,
+    ) {
+        let view = proposal.data.view_number();
+        tracing::debug!("Buffering DA proposal for future view {view:?}");
+        self.future_proposal_buffer
+            .entry(view)
+            .or_insert((proposal, sender));
+    }
+
+    /// Handle the buffered DA proposals which are no longer for future views
+    async fn replay_buffered_proposals(&mut self, event_stream: &Sender<Arc<HotShotEvent<TYPES>>>) {
+        let future = self.future_proposal_buffer.split_off(&(self.cur_view + 2));
+        let ready = std::mem::replace(&mut self.future_proposal_buffer, future);
+
+        for (view, (proposal, sender)) in ready {
+            if let Err(e) = self
+                .handle_da_proposal_recv(&proposal, &sender, event_stream)
+                .await
+            {
+                tracing::debug!("Buffered DA proposal for view {view:?} was not applied: {e}");
+            }
+        }
+    }
+
+    /// Handle a DA vote, if we are the DA leader for its view
+    async fn handle_da_vote_recv(
+        &mut self,
+        vote: &DaVote<TYPES>,
+        event: &Arc<HotShotEvent<TYPES>>,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+    ) -> Result<()> {
+        // Check if we are the leader and the vote is from the sender.
+        let view = vote.view_number();
+
+        let membership_reader = self.membership.read().await;
+        ensure!(
+            membership_reader.leader(view, self.cur_epoch)? == self.public_key,
+            debug!(
+                "We are not the DA committee leader for view {} are we leader for next view? {}",
+                *view,
+                membership_reader.leader(view + 1, self.cur_epoch)? == self.public_key
+            )
+        );
+        drop(membership_reader);
+
+        let decided_view = self.consensus.read().await.last_decided_view();
+        remove_decided_collectors(&mut self.vote_collectors, decided_view);
+        ensure_undecided(&self.vote_collectors, view, decided_view)?;
+
+        let certificate = handle_vote(
+            &mut self.vote_collectors,
+            vote,
+            self.public_key.clone(),
+            &self.membership,
+            self.cur_epoch,
+            self.id,
+            event,
+            event_stream,
+            &self.upgrade_lock,
+            true,
+            &self.vote_metrics,
+            self.late_vote_grace,
+        )
+        .await?;
+        self.update_da_vote_timer(view, certificate.is_some(), event_stream);
+
+        Ok(())
+    }
+
+    /// Start the DA vote timer for `view` when we begin collecting its votes, and cancel it once
+    /// its certificate forms. The collector is removed as soon as the certificate forms unless
+    /// late votes are recorded, so whether it formed is passed in rather than read from it.
+    fn update_da_vote_timer(
+        &mut self,
+        view: TYPES::View,
+        certificate_formed: bool,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+    ) {
+        if certificate_formed {
+            self.da_certificate_views.insert(view);
+            if let Some(timer) = self.da_vote_timers.remove(&view) {
+                timer.abort();
+            }
+            return;
+        }
+        if self.da_certificate_views.contains(&view) {
+            return;
+        }
+
+        let timeout = self.da_vote_timeout;
+        let sender = event_stream.clone();
+        self.da_vote_timers.entry(view).or_insert_with(|| {
+            spawn(async move {
+                sleep(timeout).await;
+                tracing::warn!("DA certificate for view {view:?} did not form within {timeout:?}");
+                broadcast_event(Arc::new(HotShotEvent::DaVoteTimeout(view)), &sender).await;
+            })
+        });
+    }
+
+    /// Hold a DA vote until the proposal for its view is validated or sent, dropping it if the
+    /// buffer is full. Votes held for longer than `ttl` are dropped first.
+    fn buffer_early_vote(&mut self, vote: DaVote<TYPES>, max_votes: usize, ttl: Duration) {
+        self.early_votes
+            .retain(|_, (received, _)| received.elapsed() < ttl);
+
+        let buffered: usize = self
+            .early_votes
+            .values()
+            .map(|(_, votes)| votes.len())
+            .sum();
+        let view = vote.view_number();
+        if buffered >= max_votes {
+            tracing::debug!("Early DA vote buffer is full, dropping vote for view {view:?}");
+            return;
+        }
+
+        tracing::debug!("Holding DA vote for view {view:?} until its proposal arrives");
+        self.early_votes
+            .entry(view)
+            .or_insert_with(|| (Instant::now(), Vec::new()))
+            .1
+            .push(vote);
+    }
+
+    /// Note that the proposal for `view` was validated or sent, and handle any votes held for it
+    async fn release_early_votes(
+        &mut self,
+        view: TYPES::View,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+    ) {
+        let EarlyVotePolicy::Buffer { ttl, .. } = self.early_vote_policy else {
+            return;
+        };
+        self.proposal_views.insert(view);
+
+        let Some((received, votes)) = self.early_votes.remove(&view) else {
+            return;
+        };
+        if received.elapsed() >= ttl {
+            tracing::debug!("Dropping expired early DA votes for view {view:?}");
+            return;
+        }
+
+        for vote in votes {
+            let event = Arc::new(HotShotEvent::DaVoteRecv(vote.clone()));
+            if let Err(e) = self.handle_da_vote_recv(&vote, &event, event_stream).await {
+                tracing::debug!("Early DA vote for view {view:?} was not applied: {e}");
+            }
+        }
+    }
+
+    /// Notify the application if the committee changed when leaving `prev_epoch`
+    async fn notify_committee_change(&self, view: TYPES::View, prev_epoch: TYPES::Epoch) {
+        let membership_reader = self.membership.read().await;
+        let diff = CommitteeDiff::<TYPES>::between(
+            &membership_reader.committee_members(view, prev_epoch),
+            &membership_reader.committee_members(view, self.cur_epoch),
+        );
+        drop(membership_reader);
+        if diff.is_empty() {
+            return;
+        }
+
+        let CommitteeDiff { added, removed } = diff;
+        broadcast_event(
+            Event {
+                view_number: view,
+                event: EventType::CommitteeChanged {
+                    epoch: self.cur_epoch,
+                    added,
+                    removed,
+                },
+            },
+            &self.output_event_stream,
+        )
+        .await;
+    }
+
     /// main task event handler
     #[instrument(skip_all, fields(id = self.id, view = *self.cur_view, epoch = *self.cur_epoch), name = "DA Main Task", level = "error", target = "DaTaskState")]
     pub async fn handle(
@@ -89,57 +532,15 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
     ) -> Result<()> {
         match event.as_ref() {
             HotShotEvent::DaProposalRecv(proposal, sender) => {
-                let sender = sender.clone();
-                tracing::debug!(
-                    "DA proposal received for view: {:?}",
-                    proposal.data.view_number()
-                );
-                // ED NOTE: Assuming that the next view leader is the one who sends DA proposal for this view
-                let view = proposal.data.view_number();
-
-                // Allow a DA proposal that is one view older, in case we have voted on a quorum
-                // proposal and updated the view.
-                //
-                // Anything older is discarded because it is no longer relevant.
-                ensure!(
-                    self.cur_view <= view + 1,
-                    "Throwing away DA proposal that is more than one view older"
-                );
-
-                ensure!(
-                    !self
-                      .consensus
-                      .read()
-                      .await
-                      .saved_payloads()
-                      .contains_key(&view),
-                    info!(
-                      "Received DA proposal for view {:?} but we already have a payload for that view.  Throwing it away",
-                      view
-                    )
-                );
-
-                let encoded_transactions_hash = Sha256::digest(&proposal.data.encoded_transactions);
-                let view_leader_key = self.membership.leader(view, self.cur_epoch)?;
-                ensure!(
-                    view_leader_key == sender,
-                    warn!(
-                      "DA proposal doesn't have expected leader key for view {} \n DA proposal is: {:?}",
-                      *view,
-                      proposal.data.clone()
-                    )
-                );
-
-                ensure!(
-                    view_leader_key.validate(&proposal.signature, &encoded_transactions_hash),
-                    warn!("Could not verify proposal.")
-                );
-
-                broadcast_event(
-                    Arc::new(HotShotEvent::DaProposalValidated(proposal.clone(), sender)),
-                    &event_stream,
-                )
-                .await;
+                let metrics = Arc::clone(&self.consensus.read().await.metrics);
+                metrics.da_proposals_received.add(1);
+                if let Err(e) = self
+                    .handle_da_proposal_recv(proposal, sender, &event_stream)
+                    .await
+                {
+                    metrics.da_proposals_rejected.add(1);
+                    return Err(e);
+                }
             }
             HotShotEvent::DaProposalValidated(proposal, sender) => {
                 let cur_view = self.consensus.read().await.cur_view();
@@ -151,6 +552,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
                     proposal.data.view_number()
                   )
                 );
+                self.release_early_votes(proposal.data.view_number(), &event_stream)
+                    .await;
 
                 // Proposal is fresh and valid, notify the application layer
                 broadcast_event(
@@ -167,6 +570,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
 
                 ensure!(
                     self.membership
+                        .read()
+                        .await
                         .has_da_stake(&self.public_key, self.cur_epoch),
                     debug!(
                         "We were not chosen for consensus committee on {:?}",
@@ -175,44 +580,72 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
                 );
 
                 let txns = Arc::clone(&proposal.data.encoded_transactions);
-                let num_nodes = self.membership.total_nodes(self.cur_epoch);
-                let payload_commitment =
-                    spawn_blocking(move || vid_commitment(&txns, num_nodes)).await;
-                let payload_commitment = payload_commitment.unwrap();
-                self.storage
-                    .write()
-                    .await
-                    .append_da(proposal, payload_commitment)
-                    .await
-                    .wrap()
-                    .context(error!("Failed to append DA proposal to storage"))?;
+                let num_nodes = self.membership.read().await.total_nodes(self.cur_epoch);
+                let vid_commitment_fn = self.vid_commitment_fn;
+                let payload_commitment = match spawn_blocking(move || {
+                    let start = Instant::now();
+                    (vid_commitment_fn(&txns, num_nodes), start.elapsed())
+                })
+                .await
+                {
+                    Ok((payload_commitment, elapsed)) => {
+                        self.consensus
+                            .read()
+                            .await
+                            .metrics
+                            .da_vid_commitment_duration
+                            .add_point(elapsed.as_secs_f64() * 1000.0);
+                        payload_commitment
+                    }
+                    Err(e) => {
+                        broadcast_event(
+                            Arc::new(HotShotEvent::DaProposalRejected(
+                                proposal.clone(),
+                                sender.clone(),
+                            )),
+                            &event_stream,
+                        )
+                        .await;
+                        bail!(error!(
+                            "Failed to compute VID commitment for DA proposal in view {:?}: {e}",
+                            proposal.data.view_number()
+                        ));
+                    }
+                };
+                self.append_da_with_retry(proposal, payload_commitment)
+                    .await?;
                 let view_number = proposal.data.view_number();
-                // Generate and send vote
-                let vote = DaVote::create_signed_vote(
-                    DaData {
-                        payload_commit: payload_commitment,
-                    },
-                    view_number,
-                    &self.public_key,
-                    &self.private_key,
-                    &self.upgrade_lock,
-                )
-                .await?;
-
-                tracing::debug!("Sending vote to the DA leader {:?}", vote.view_number());
-
-                broadcast_event(Arc::new(HotShotEvent::DaVoteSend(vote)), &event_stream).await;
-                let mut consensus_writer = self.consensus.write().await;
-
-                // Ensure this view is in the view map for garbage collection.
-
-                if let Err(e) = consensus_writer.update_da_view(view_number, payload_commitment) {
-                    tracing::trace!("{e:?}");
+                self.voting_warmup.observe(view_number);
+                if self.voting_warmup.may_vote(view_number) {
+                    // Generate and send vote
+                    let vote = DaVote::create_signed_vote(
+                        DaData {
+                            payload_commit: payload_commitment,
+                        },
+                        view_number,
+                        self.cur_epoch,
+                        &self.public_key,
+                        &self.private_key,
+                        &self.upgrade_lock,
+                    )
+                    .await?;
+
+                    tracing::debug!(
+                        payload_commitment = %payload_commitment.to_hex(),
+                        "Sending vote to the DA leader {:?}",
+                        vote.view_number()
+                    );
+
+                    broadcast_event(Arc::new(HotShotEvent::DaVoteSend(vote)), &event_stream).await;
+                    self.consensus.read().await.metrics.da_votes_sent.add(1);
+                } else {
+                    tracing::debug!("Still warming up, not voting in view {:?}", view_number);
                 }
-
-                // Record the payload we have promised to make available.
-                if let Err(e) = consensus_writer.update_saved_payloads(
+                // Ensure this view is in the view map for garbage collection, and record the
+                // payload we have promised to make available.
+                if let Err(e) = self.consensus.write().await.update_da_view_and_payload(
                     view_number,
+                    payload_commitment,
                     Arc::clone(&proposal.data.encoded_transactions),
                 ) {
                     tracing::trace!("{e:?}");
@@ -255,36 +688,47 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
                 }
             }
             HotShotEvent::DaVoteRecv(ref vote) => {
-                tracing::debug!("DA vote recv, Main Task {:?}", vote.view_number());
-                // Check if we are the leader and the vote is from the sender.
-                let view = vote.view_number();
-
-                ensure!(
-                    self.membership.leader(view, self.cur_epoch)? == self.public_key,
-                    debug!(
-                      "We are not the DA committee leader for view {} are we leader for next view? {}",
-                      *view,
-                      self.membership.leader(view + 1, self.cur_epoch)? == self.public_key
-                    )
+                tracing::debug!(
+                    payload_commitment = %vote.data.payload_commit.to_hex(),
+                    "DA vote recv, Main Task {:?}",
+                    vote.view_number()
                 );
 
-                handle_vote(
-                    &mut self.vote_collectors,
-                    vote,
-                    self.public_key.clone(),
-                    &self.membership,
-                    self.cur_epoch,
-                    self.id,
-                    &event,
-                    &event_stream,
-                    &self.upgrade_lock,
-                    true,
-                )
-                .await?;
+                if let EarlyVotePolicy::Buffer { max_votes, ttl } = self.early_vote_policy {
+                    if !self.proposal_views.contains(&vote.view_number()) {
+                        self.buffer_early_vote(vote.clone(), max_votes, ttl);
+                        return Ok(());
+                    }
+                }
+
+                self.handle_da_vote_recv(vote, &event, &event_stream)
+                    .await?;
+            }
+            HotShotEvent::DaVoteSendFailed(vote, error) => {
+                let metrics = Arc::clone(&self.consensus.read().await.metrics);
+                match error {
+                    // The leader answered, but would not take the vote
+                    NetworkError::ProtocolNegotiationFailed(_) | NetworkError::RateLimited(_) => {
+                        metrics.da_votes_refused.add(1);
+                        tracing::warn!(
+                            "DA leader refused our vote for view {:?}: {error}",
+                            vote.view_number()
+                        );
+                    }
+                    _ => {
+                        metrics.da_votes_undelivered.add(1);
+                        tracing::warn!(
+                            "Our DA vote for view {:?} did not reach the leader: {error}",
+                            vote.view_number()
+                        );
+                    }
+                }
             }
             HotShotEvent::ViewChange(view, epoch) => {
-                if *epoch > self.cur_epoch {
-                    self.cur_epoch = *epoch;
+                let prev_epoch = self.cur_epoch;
+                advance_epoch::<TYPES>(&mut self.cur_epoch, *epoch, self.max_epoch_skip);
+                if self.cur_epoch != prev_epoch {
+                    self.notify_committee_change(*view, prev_epoch).await;
                 }
 
                 let view = *view;
@@ -297,6 +741,19 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
                     tracing::info!("View changed by more than 1 going to view {:?}", view);
                 }
                 self.cur_view = view;
+                let decided_view = self.consensus.read().await.last_decided_view();
+                remove_decided_collectors(&mut self.vote_collectors, decided_view);
+                // Votes for views before the previous one can no longer form a useful certificate
+                self.proposal_views = self.proposal_views.split_off(&(view - 1));
+                self.early_votes = self.early_votes.split_off(&(view - 1));
+                self.proposal_hashes = self.proposal_hashes.split_off(&(view - 1));
+                self.da_certificate_views = self.da_certificate_views.split_off(&view);
+                let current_timers = self.da_vote_timers.split_off(&view);
+                let passed_timers = std::mem::replace(&mut self.da_vote_timers, current_timers);
+                for timer in passed_timers.into_values() {
+                    timer.abort();
+                }
+                self.replay_buffered_proposals(&event_stream).await;
             }
             HotShotEvent::BlockRecv(packed_bundle) => {
                 let PackedBundle::<TYPES> {
@@ -307,6 +764,13 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
                 } = packed_bundle;
                 let view_number = *view_number;
 
+                // Replicas would reject the proposal, so there is no point in sending it
+                TYPES::BlockPayload::validate_metadata(metadata, encoded_transactions.len())
+                    .context(warn!(
+                        "Not proposing the block for view {view_number:?}, its metadata does not \
+                         match its payload"
+                    ))?;
+
                 // quick hash the encoded txns with sha256
                 let encoded_transactions_hash = Sha256::digest(encoded_transactions);
 
@@ -325,6 +789,11 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
                 let message = Proposal {
                     data,
                     signature,
+                    leader_proof: self.membership.read().await.leader_proof(
+                        view_number,
+                        self.cur_epoch,
+                        &self.private_key,
+                    )?,
                     _pd: PhantomData,
                 };
 
@@ -336,6 +805,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> DaTaskState<TYP
                     &event_stream,
                 )
                 .await;
+                self.release_early_votes(view_number, &event_stream).await;
             }
             _ => {}
         }
@@ -359,5 +829,9 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TaskState
         self.handle(event, sender.clone()).await
     }
 
-    fn cancel_subtasks(&mut self) {}
+    fn cancel_subtasks(&mut self) {
+        for timer in std::mem::take(&mut self.da_vote_timers).into_values() {
+            timer.abort();
+        }
+    }
 }
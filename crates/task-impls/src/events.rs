@@ -26,8 +26,11 @@ use hotshot_types::{
         ViewSyncPreCommitVote,
     },
     traits::{
-        block_contents::BuilderFee, network::DataRequest, node_implementation::NodeType,
-        signature_key::SignatureKey, BlockPayload,
+        block_contents::BuilderFee,
+        network::{DataRequest, NetworkError},
+        node_implementation::NodeType,
+        signature_key::SignatureKey,
+        BlockPayload,
     },
     utils::BuilderCommitment,
     vid::VidCommitment,
@@ -41,6 +44,12 @@ impl<TYPES: NodeType> TaskEvent for HotShotEvent<TYPES> {
     fn shutdown_event() -> Self {
         HotShotEvent::Shutdown
     }
+
+    fn task_stalled_event(task_id: &str) -> Self {
+        HotShotEvent::TaskStalled {
+            task_id: task_id.to_string(),
+        }
+    }
 }
 
 /// Wrapper type for the event to notify tasks that a proposal for a view is missing
@@ -83,6 +92,9 @@ pub enum HotShotEvent<TYPES: NodeType> {
     DaProposalRecv(Proposal<TYPES, DaProposal<TYPES>>, TYPES::SignatureKey),
     /// A DA proposal has been validated; handled by the DA task and VID task
     DaProposalValidated(Proposal<TYPES, DaProposal<TYPES>>, TYPES::SignatureKey),
+    /// A validated DA proposal was dropped without a vote, e.g. because its VID commitment could
+    /// not be computed; emitted by the DA task
+    DaProposalRejected(Proposal<TYPES, DaProposal<TYPES>>, TYPES::SignatureKey),
     /// A DA vote has been received by the network; handled by the DA task
     DaVoteRecv(DaVote<TYPES>),
     /// A Data Availability Certificate (DAC) has been received by the network; handled by the consensus task
@@ -247,6 +259,63 @@ pub enum HotShotEvent<TYPES: NodeType> {
         TYPES::SignatureKey,
         TYPES::SignatureKey,
     ),
+
+    /// A task has been handling a single event for longer than the stall timeout while other
+    /// events are queued; emitted by the task watchdog
+    TaskStalled {
+        /// Identifier of the stalled task
+        task_id: String,
+    },
+
+    /// Two valid QCs certify different leaves for the same view; the node has halted. The
+    /// certificates are evidence of the equivocating signers.
+    SafetyFork {
+        /// The forked view
+        view: TYPES::View,
+        /// The QC we saw first
+        qc_a: QuorumCertificate2<TYPES>,
+        /// The conflicting QC
+        qc_b: QuorumCertificate2<TYPES>,
+    },
+
+    /// The leader of a view signed two DA proposals for it with different payloads. The leader's
+    /// signatures over both hashes are evidence for slashing.
+    DaProposalEquivocation {
+        /// The view of both proposals
+        view: TYPES::View,
+        /// The leader which signed both proposals
+        leader: TYPES::SignatureKey,
+        /// Hash of the encoded transactions of the proposal we received first
+        first_hash: [u8; 32],
+        /// Hash of the encoded transactions of the conflicting proposal
+        second_hash: [u8; 32],
+    },
+
+    /// The DA certificate for a view we lead did not form within the DA vote timeout after we
+    /// began collecting votes for it
+    DaVoteTimeout(TYPES::View),
+
+    /// A DA vote of ours could not be delivered to the DA leader; emitted by the network task
+    /// with the network's error, so the DA task can tell an unreachable leader from one that
+    /// refused the vote
+    DaVoteSendFailed(DaVote<TYPES>, NetworkError),
+
+    /// Too many views are undecided, so we neither propose nor vote in `view` until decisions
+    /// catch up
+    UndecidedBacklogFull {
+        /// The view we paused in
+        view: TYPES::View,
+        /// Number of views the high QC is ahead of the last decided view
+        undecided_views: u64,
+    },
+
+    /// We received the proposal for a view without its justify QC, and don't have the QC it
+    /// references, so the full proposal should be requested
+    JustifyQcMissing(TYPES::View),
+
+    /// We asked every DA member of a view for our VID share and none of them sent it before the
+    /// request timeout, so the request task gave up on the view
+    VidRequestFailed(TYPES::View),
 }
 
 impl<TYPES: NodeType> HotShotEvent<TYPES> {
@@ -271,10 +340,11 @@ impl<TYPES: NodeType> HotShotEvent<TYPES> {
             }
             HotShotEvent::DaProposalRecv(proposal, _)
             | HotShotEvent::DaProposalValidated(proposal, _)
+            | HotShotEvent::DaProposalRejected(proposal, _)
             | HotShotEvent::DaProposalSend(proposal, _) => Some(proposal.data.view_number()),
-            HotShotEvent::DaVoteRecv(vote) | HotShotEvent::DaVoteSend(vote) => {
-                Some(vote.view_number())
-            }
+            HotShotEvent::DaVoteRecv(vote)
+            | HotShotEvent::DaVoteSend(vote)
+            | HotShotEvent::DaVoteSendFailed(vote, _) => Some(vote.view_number()),
             HotShotEvent::QcFormed(cert) => match cert {
                 either::Left(qc) => Some(qc.view_number()),
                 either::Right(tc) => Some(tc.view_number()),
@@ -301,7 +371,8 @@ impl<TYPES: NodeType> HotShotEvent<TYPES> {
             HotShotEvent::BlockRecv(packed_bundle) => Some(packed_bundle.view_number),
             HotShotEvent::Shutdown
             | HotShotEvent::TransactionSend(_, _)
-            | HotShotEvent::TransactionsRecv(_) => None,
+            | HotShotEvent::TransactionsRecv(_)
+            | HotShotEvent::TaskStalled { .. } => None,
             HotShotEvent::VidDisperseSend(proposal, _) => Some(proposal.data.view_number()),
             HotShotEvent::VidShareRecv(_, proposal) | HotShotEvent::VidShareValidated(proposal) => {
                 Some(proposal.data.view_number())
@@ -329,6 +400,12 @@ impl<TYPES: NodeType> HotShotEvent<TYPES> {
             HotShotEvent::HighQcRecv(qc, _) | HotShotEvent::HighQcSend(qc, ..) => {
                 Some(qc.view_number())
             }
+            HotShotEvent::SafetyFork { view, .. }
+            | HotShotEvent::DaProposalEquivocation { view, .. }
+            | HotShotEvent::UndecidedBacklogFull { view, .. }
+            | HotShotEvent::DaVoteTimeout(view)
+            | HotShotEvent::JustifyQcMissing(view)
+            | HotShotEvent::VidRequestFailed(view) => Some(*view),
         }
     }
 }
@@ -369,6 +446,11 @@ impl<TYPES: NodeType> Display for HotShotEvent<TYPES> {
                 "DaProposalValidated(view_number={:?})",
                 proposal.data.view_number()
             ),
+            HotShotEvent::DaProposalRejected(proposal, _) => write!(
+                f,
+                "DaProposalRejected(view_number={:?})",
+                proposal.data.view_number()
+            ),
             HotShotEvent::DaVoteRecv(vote) => {
                 write!(f, "DaVoteRecv(view_number={:?})", vote.view_number())
             }
@@ -597,6 +679,43 @@ impl<TYPES: NodeType> Display for HotShotEvent<TYPES> {
             HotShotEvent::HighQcSend(qc, ..) => {
                 write!(f, "HighQcSend(view_number={:?}", qc.view_number())
             }
+            HotShotEvent::TaskStalled { task_id } => {
+                write!(f, "TaskStalled(task_id={task_id})")
+            }
+            HotShotEvent::SafetyFork { view, .. } => {
+                write!(f, "SafetyFork(view_number={view:?})")
+            }
+            HotShotEvent::DaProposalEquivocation { view, leader, .. } => {
+                write!(
+                    f,
+                    "DaProposalEquivocation(view_number={view:?}, leader={leader})"
+                )
+            }
+            HotShotEvent::DaVoteTimeout(view) => {
+                write!(f, "DaVoteTimeout(view_number={view:?})")
+            }
+            HotShotEvent::VidRequestFailed(view) => {
+                write!(f, "VidRequestFailed(view_number={view:?})")
+            }
+            HotShotEvent::DaVoteSendFailed(vote, error) => {
+                write!(
+                    f,
+                    "DaVoteSendFailed(view_number={:?}, error={error})",
+                    vote.view_number()
+                )
+            }
+            HotShotEvent::UndecidedBacklogFull {
+                view,
+                undecided_views,
+            } => {
+                write!(
+                    f,
+                    "UndecidedBacklogFull(view_number={view:?}, undecided_views={undecided_views})"
+                )
+            }
+            HotShotEvent::JustifyQcMissing(view) => {
+                write!(f, "JustifyQcMissing(view_number={view:?})")
+            }
         }
     }
 }
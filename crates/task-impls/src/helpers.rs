@@ -25,6 +25,7 @@ use hotshot_types::{
         election::Membership,
         node_implementation::{NodeImplementation, NodeType, Versions},
         signature_key::SignatureKey,
+        storage::Storage,
         BlockPayload, ValidatedState,
     },
     utils::{epoch_from_block_number, Terminator, View, ViewInner},
@@ -36,6 +37,133 @@ use utils::anytrace::*;
 
 use crate::{events::HotShotEvent, quorum_proposal_recv::ValidationInfo, request::REQUEST_TIMEOUT};
 
+/// Record the valid QC `qc`, halting this node if it conflicts with a QC seen earlier for its
+/// view.
+///
+/// On a new fork, both QCs are broadcast in a [`HotShotEvent::SafetyFork`] as evidence.
+///
+/// # Errors
+/// If this node is halted, by a fork found now or an earlier one
+pub(crate) async fn check_safety_fork<TYPES: NodeType>(
+    qc: &QuorumCertificate2<TYPES>,
+    consensus: &OuterConsensus<TYPES>,
+    sender: &Sender<Arc<HotShotEvent<TYPES>>>,
+) -> Result<()> {
+    let mut consensus_writer = consensus.write().await;
+    if let Some(view) = consensus_writer.safety_fork() {
+        bail!(error!("Halted by the safety fork in view {view:?}"));
+    }
+    let Some(conflicting) = consensus_writer.record_qc(qc) else {
+        return Ok(());
+    };
+    consensus_writer.halt_on_safety_fork(qc.view_number);
+    drop(consensus_writer);
+
+    broadcast_event(
+        Arc::new(HotShotEvent::SafetyFork {
+            view: qc.view_number,
+            qc_a: conflicting,
+            qc_b: qc.clone(),
+        }),
+        sender,
+    )
+    .await;
+    bail!(error!(
+        "Conflicting QCs for view {:?}, halting",
+        qc.view_number
+    ));
+}
+
+/// Check that at most `max_undecided_views` views are undecided, broadcasting
+/// [`HotShotEvent::UndecidedBacklogFull`] if there are more.
+///
+/// Nothing is dropped from the undecided chain, we only stop extending it until decisions catch up.
+///
+/// # Errors
+/// If the backlog is full, so we should neither propose nor vote in `view`
+pub(crate) async fn check_undecided_backlog<TYPES: NodeType>(
+    view: TYPES::View,
+    consensus: &OuterConsensus<TYPES>,
+    max_undecided_views: u64,
+    sender: &Sender<Arc<HotShotEvent<TYPES>>>,
+) -> Result<()> {
+    let undecided_views = consensus.read().await.undecided_views();
+    if undecided_views <= max_undecided_views {
+        return Ok(());
+    }
+
+    broadcast_event(
+        Arc::new(HotShotEvent::UndecidedBacklogFull {
+            view,
+            undecided_views,
+        }),
+        sender,
+    )
+    .await;
+    bail!(warn!(
+        "{undecided_views} views are undecided, pausing in view {view:?} until decisions catch up"
+    ));
+}
+
+/// Write the undecided leaves and states of `consensus` to `storage`.
+///
+/// They are copied out under a read lock which is released before the write, so storage I/O
+/// never holds up consensus.
+///
+/// # Errors
+/// If the storage write fails
+pub async fn checkpoint_undecided_state<TYPES: NodeType, S: Storage<TYPES>>(
+    consensus: &OuterConsensus<TYPES>,
+    storage: &RwLock<S>,
+) -> Result<()> {
+    let consensus_reader = consensus.read().await;
+    let leaves = consensus_reader.saved_leaves().clone();
+    let state = consensus_reader.validated_state_map().clone();
+    drop(consensus_reader);
+
+    storage
+        .write()
+        .await
+        .update_undecided_state2(leaves, state)
+        .await
+        .wrap()
+        .context(error!("Failed to update undecided state"))
+}
+
+/// Broadcast a signed request for the proposal for `view_number`, which any node holding the
+/// proposal answers.
+///
+/// # Errors
+/// If we fail to sign the request
+pub(crate) async fn request_proposal<TYPES: NodeType>(
+    view_number: TYPES::View,
+    public_key: TYPES::SignatureKey,
+    private_key: &<TYPES::SignatureKey as SignatureKey>::PrivateKey,
+    event_sender: &Sender<Arc<HotShotEvent<TYPES>>>,
+) -> Result<()> {
+    // We need to be able to sign this request before submitting it to the network. Compute the
+    // payload first.
+    let signed_proposal_request = ProposalRequestPayload {
+        view_number,
+        key: public_key,
+    };
+
+    // Finally, compute the signature for the payload.
+    let signature =
+        TYPES::SignatureKey::sign(private_key, signed_proposal_request.commit().as_ref())
+            .wrap()
+            .context(error!("Failed to sign proposal. This should never happen."))?;
+
+    // First, broadcast that we need a proposal to the current leader
+    broadcast_event(
+        HotShotEvent::QuorumProposalRequestSend(signed_proposal_request, signature).into(),
+        event_sender,
+    )
+    .await;
+
+    Ok(())
+}
+
 /// Trigger a request to the network for a proposal for a view and wait for the response or timeout.
 #[instrument(skip_all)]
 #[allow(clippy::too_many_arguments)]
@@ -43,36 +171,23 @@ pub(crate) async fn fetch_proposal<TYPES: NodeType, V: Versions>(
     view_number: TYPES::View,
     event_sender: Sender<Arc<HotShotEvent<TYPES>>>,
     event_receiver: Receiver<Arc<HotShotEvent<TYPES>>>,
-    quorum_membership: Arc<TYPES::Membership>,
+    quorum_membership: Arc<RwLock<TYPES::Membership>>,
     consensus: OuterConsensus<TYPES>,
     sender_public_key: TYPES::SignatureKey,
     sender_private_key: <TYPES::SignatureKey as SignatureKey>::PrivateKey,
     upgrade_lock: &UpgradeLock<TYPES, V>,
 ) -> Result<(Leaf2<TYPES>, View<TYPES>)> {
-    // We need to be able to sign this request before submitting it to the network. Compute the
-    // payload first.
-    let signed_proposal_request = ProposalRequestPayload {
+    request_proposal(
         view_number,
-        key: sender_public_key,
-    };
-
-    // Finally, compute the signature for the payload.
-    let signature = TYPES::SignatureKey::sign(
+        sender_public_key,
         &sender_private_key,
-        signed_proposal_request.commit().as_ref(),
-    )
-    .wrap()
-    .context(error!("Failed to sign proposal. This should never happen."))?;
-
-    // First, broadcast that we need a proposal to the current leader
-    broadcast_event(
-        HotShotEvent::QuorumProposalRequestSend(signed_proposal_request, signature).into(),
         &event_sender,
     )
-    .await;
+    .await?;
 
     let mem = Arc::clone(&quorum_membership);
     let cur_epoch = consensus.read().await.cur_epoch();
+    let lock = upgrade_lock.clone();
     // Make a background task to await the arrival of the event data.
     let Ok(Some(proposal)) =
         // We want to explicitly timeout here so we aren't waiting around for the data.
@@ -104,7 +219,13 @@ pub(crate) async fn fetch_proposal<TYPES: NodeType, V: Versions>(
                         hs_event.as_ref()
                     {
                         // Make sure that the quorum_proposal is valid
-                        if quorum_proposal.validate_signature(&mem, cur_epoch).is_ok() {
+                        let membership_reader = mem.read().await;
+                        let valid = quorum_proposal
+                            .validate_signature(&membership_reader, cur_epoch, &lock)
+                            .await
+                            .is_ok();
+                        drop(membership_reader);
+                        if valid {
                             proposal = Some(quorum_proposal.clone());
                         }
 
@@ -124,14 +245,12 @@ pub(crate) async fn fetch_proposal<TYPES: NodeType, V: Versions>(
     let view_number = proposal.data.view_number();
     let justify_qc = proposal.data.justify_qc.clone();
 
-    if !justify_qc
-        .is_valid_cert(
-            quorum_membership.stake_table(cur_epoch),
-            quorum_membership.success_threshold(cur_epoch),
-            upgrade_lock,
-        )
-        .await
-    {
+    let membership_reader = quorum_membership.read().await;
+    let justify_qc_valid = justify_qc
+        .is_valid_cert(&membership_reader, cur_epoch, upgrade_lock)
+        .await;
+    drop(membership_reader);
+    if !justify_qc_valid {
         bail!("Invalid justify_qc in proposal for view {}", *view_number);
     }
     let mut consensus_writer = consensus.write().await;
@@ -428,7 +547,7 @@ pub(crate) async fn parent_leaf_and_state<TYPES: NodeType, V: Versions>(
     next_proposal_view_number: TYPES::View,
     event_sender: &Sender<Arc<HotShotEvent<TYPES>>>,
     event_receiver: &Receiver<Arc<HotShotEvent<TYPES>>>,
-    quorum_membership: Arc<TYPES::Membership>,
+    quorum_membership: Arc<RwLock<TYPES::Membership>>,
     public_key: TYPES::SignatureKey,
     private_key: <TYPES::SignatureKey as SignatureKey>::PrivateKey,
     consensus: OuterConsensus<TYPES>,
@@ -437,8 +556,12 @@ pub(crate) async fn parent_leaf_and_state<TYPES: NodeType, V: Versions>(
 ) -> Result<(Leaf2<TYPES>, Arc<<TYPES as NodeType>::ValidatedState>)> {
     let consensus_reader = consensus.read().await;
     let cur_epoch = consensus_reader.cur_epoch();
+    let leader = quorum_membership
+        .read()
+        .await
+        .leader(next_proposal_view_number, cur_epoch)?;
     ensure!(
-        quorum_membership.leader(next_proposal_view_number, cur_epoch)? == public_key,
+        leader == public_key,
         info!(
             "Somehow we formed a QC but are not the leader for the next view {:?}",
             next_proposal_view_number
@@ -536,13 +659,15 @@ pub async fn validate_proposal_safety_and_liveness<
     }
 
     let cur_epoch = validation_info.cur_epoch;
+    let membership_reader = validation_info.quorum_membership.read().await;
     UpgradeCertificate::validate(
         &proposal.data.upgrade_certificate,
-        &validation_info.quorum_membership,
+        &membership_reader,
         cur_epoch,
         &validation_info.upgrade_lock,
     )
     .await?;
+    drop(membership_reader);
 
     // Validate that the upgrade certificate is re-attached, if we saw one on the parent
     proposed_leaf
@@ -649,6 +774,7 @@ pub(crate) async fn validate_proposal_view_and_certs<
     V: Versions,
 >(
     proposal: &Proposal<TYPES, QuorumProposal2<TYPES>>,
+    sender: &TYPES::SignatureKey,
     validation_info: &ValidationInfo<TYPES, I, V>,
 ) -> Result<()> {
     let view_number = proposal.data.view_number();
@@ -658,11 +784,26 @@ pub(crate) async fn validate_proposal_view_and_certs<
         proposal.data.clone()
     );
 
+    let membership_reader = validation_info.quorum_membership.read().await;
+
+    // Checked before the signature, which fails less clearly if the unstaked leader was replaced
+    proposal
+        .validate_leader_stake(
+            &membership_reader,
+            validation_info.cur_epoch,
+            sender,
+            &validation_info.upgrade_lock,
+        )
+        .await?;
+
     // Validate the proposal's signature. This should also catch if the leaf_commitment does not equal our calculated parent commitment
-    proposal.validate_signature(
-        &validation_info.quorum_membership,
-        validation_info.cur_epoch,
-    )?;
+    proposal
+        .validate_signature(
+            &membership_reader,
+            validation_info.cur_epoch,
+            &validation_info.upgrade_lock,
+        )
+        .await?;
 
     // Verify a timeout certificate OR a view sync certificate exists and is valid.
     if proposal.data.justify_qc.view_number() != view_number - 1 {
@@ -683,12 +824,8 @@ pub(crate) async fn validate_proposal_view_and_certs<
                 ensure!(
                     timeout_cert
                         .is_valid_cert(
-                            validation_info
-                                .quorum_membership
-                                .stake_table(validation_info.cur_epoch),
-                            validation_info
-                                .quorum_membership
-                                .success_threshold(validation_info.cur_epoch),
+                            &membership_reader,
+                            validation_info.cur_epoch,
                             &validation_info.upgrade_lock
                         )
                         .await,
@@ -708,12 +845,8 @@ pub(crate) async fn validate_proposal_view_and_certs<
                 ensure!(
                     view_sync_cert
                         .is_valid_cert(
-                            validation_info
-                                .quorum_membership
-                                .stake_table(validation_info.cur_epoch),
-                            validation_info
-                                .quorum_membership
-                                .success_threshold(validation_info.cur_epoch),
+                            &membership_reader,
+                            validation_info.cur_epoch,
                             &validation_info.upgrade_lock
                         )
                         .await,
@@ -727,7 +860,7 @@ pub(crate) async fn validate_proposal_view_and_certs<
     // Note that we don't do anything with the certificate directly if this passes; it eventually gets stored as part of the leaf if nothing goes wrong.
     UpgradeCertificate::validate(
         &proposal.data.upgrade_certificate,
-        &validation_info.quorum_membership,
+        &membership_reader,
         validation_info.cur_epoch,
         &validation_info.upgrade_lock,
     )
@@ -736,6 +869,86 @@ pub(crate) async fn validate_proposal_view_and_certs<
     Ok(())
 }
 
+/// Advance `cur_epoch` towards the `epoch` of a view change, by at most `max_epoch_skip` epochs.
+///
+/// Every task which tracks the current epoch applies view changes through this, so that they
+/// agree on it. The view change itself always goes ahead, so a node catching up across more
+/// epochs than that gets there over several view changes.
+pub fn advance_epoch<TYPES: NodeType>(
+    cur_epoch: &mut TYPES::Epoch,
+    epoch: TYPES::Epoch,
+    max_epoch_skip: u64,
+) {
+    if epoch <= *cur_epoch {
+        return;
+    }
+
+    let furthest = (**cur_epoch).saturating_add(max_epoch_skip);
+    if *epoch > furthest {
+        tracing::warn!(
+            "View change from epoch {:?} to epoch {:?} skips more than {} epochs, only advancing \
+             to epoch {}",
+            *cur_epoch,
+            epoch,
+            max_epoch_skip,
+            furthest
+        );
+        *cur_epoch = TYPES::Epoch::new(furthest);
+    } else {
+        *cur_epoch = epoch;
+    }
+}
+
+/// Tracks the warm-up period of a node which joined behind the network, during which it
+/// observes views but does not vote on them.
+#[derive(Clone, Debug)]
+pub struct VotingWarmup<TYPES: NodeType> {
+    /// The view the node started in
+    start_view: TYPES::View,
+    /// Number of views to observe before voting, after joining behind the network
+    warmup_views: u64,
+    /// The first view observed from the network
+    first_observed_view: Option<TYPES::View>,
+    /// The highest view observed from the network
+    tip: TYPES::View,
+}
+
+impl<TYPES: NodeType> VotingWarmup<TYPES> {
+    /// Create the warm-up tracker for a node which started in `start_view`
+    #[must_use]
+    pub fn new(start_view: TYPES::View, warmup_views: u64) -> Self {
+        Self {
+            start_view,
+            warmup_views,
+            first_observed_view: None,
+            tip: start_view,
+        }
+    }
+
+    /// Record that the network has reached `view`
+    pub fn observe(&mut self, view: TYPES::View) {
+        self.first_observed_view.get_or_insert(view);
+        self.tip = self.tip.max(view);
+    }
+
+    /// Whether the node may vote in `view`.
+    ///
+    /// A node which started alongside the network votes right away. One which joined behind it
+    /// first observes `warmup_views` views, and then votes only once it is within one view of
+    /// the highest view it has observed.
+    #[must_use]
+    pub fn may_vote(&self, view: TYPES::View) -> bool {
+        let Some(first_observed_view) = self.first_observed_view else {
+            return true;
+        };
+        if self.warmup_views == 0 || *first_observed_view <= *self.start_view + 1 {
+            return true;
+        }
+
+        *view >= *first_observed_view + self.warmup_views && *view + 1 >= *self.tip
+    }
+}
+
 /// Helper function to send events and log errors
 pub async fn broadcast_event<E: Clone + std::fmt::Debug>(event: E, sender: &Sender<E>) {
     match sender.broadcast_direct(event).await {
@@ -7,22 +7,29 @@
 use std::{
     collections::{BTreeMap, HashMap},
     hash::{DefaultHasher, Hash, Hasher},
-    sync::Arc,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 use async_broadcast::{Receiver, Sender};
 use async_lock::RwLock;
 use async_trait::async_trait;
+use committable::{Commitment, Committable};
 use hotshot_task::task::TaskState;
 use hotshot_types::{
-    consensus::OuterConsensus,
-    data::{VidDisperse, VidDisperseShare},
+    consensus::{ConsensusMetricsValue, OuterConsensus, ParticipationMode},
+    data::{QuorumProposal, QuorumProposalWithQcRef, VidDisperse, VidDisperseShare, ViewNumber},
     event::{Event, EventType, HotShotAction},
     message::{
         convert_proposal, DaConsensusMessage, DataMessage, GeneralConsensusMessage, Message,
-        MessageKind, Proposal, SequencingMessage, UpgradeLock,
+        MessageClass, MessageKind, Proposal, SequencingMessage, UpgradeLock,
     },
+    simple_certificate::QuorumCertificate,
     traits::{
+        block_contents::TxnValidator,
         election::Membership,
         network::{
             BroadcastDelay, ConnectedNetwork, RequestKind, ResponseMessage, Topic, TransmitType,
@@ -39,9 +46,221 @@ use utils::anytrace::*;
 
 use crate::{
     events::{HotShotEvent, HotShotTaskCompleted},
-    helpers::broadcast_event,
+    helpers::{advance_epoch, broadcast_event},
 };
 
+/// Length of the network id tag prefixed to every message on a network with a non-default id
+pub const NETWORK_ID_TAG_LEN: usize = 8;
+
+/// Number of epochs ahead whose committee we connect to when entering a new epoch
+pub const COMMITTEE_PRE_DIAL_LOOKAHEAD_EPOCHS: u64 = 1;
+
+/// Whether this node takes part in consensus, shared between its handle and its network task.
+///
+/// While paused, the node keeps following the chain, relaying messages and answering requests,
+/// but sends none of its own votes, proposals or certificates. A read replica stays paused.
+#[derive(Debug, Default)]
+pub struct Participation {
+    /// The first view we take part in, `u64::MAX` while paused
+    engaged_from: AtomicU64,
+    /// Whether we are a read replica, which never takes part
+    read_replica: bool,
+}
+
+impl Participation {
+    /// Participation of a node in `mode`, paused for good if it is a read replica
+    #[must_use]
+    pub fn new(mode: ParticipationMode) -> Self {
+        let read_replica = mode == ParticipationMode::ReadReplica;
+        Self {
+            engaged_from: AtomicU64::new(if read_replica { u64::MAX } else { 0 }),
+            read_replica,
+        }
+    }
+
+    /// Stop taking part in consensus
+    pub fn pause(&self) {
+        self.engaged_from.store(u64::MAX, Ordering::Relaxed);
+    }
+
+    /// Take part in consensus again from the view after `cur_view`, so we never act in a view
+    /// we were paused for part of
+    pub fn resume(&self, cur_view: u64) {
+        if self.read_replica {
+            tracing::warn!("Read replicas never take part in consensus, staying paused");
+            return;
+        }
+        self.engaged_from
+            .store(cur_view.saturating_add(1), Ordering::Relaxed);
+    }
+
+    /// Whether we are paused
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.engaged_from.load(Ordering::Relaxed) == u64::MAX
+    }
+
+    /// Whether we are a read replica, which never takes part in consensus
+    #[must_use]
+    pub fn is_read_replica(&self) -> bool {
+        self.read_replica
+    }
+
+    /// Whether we take part in consensus in `view`
+    #[must_use]
+    pub fn is_engaged(&self, view: u64) -> bool {
+        view >= self.engaged_from.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether `event` sends a vote, proposal or certificate of ours, which we only do while taking
+/// part in consensus
+fn is_participation<TYPES: NodeType>(event: &HotShotEvent<TYPES>) -> bool {
+    matches!(
+        event,
+        HotShotEvent::QuorumProposalSend(..)
+            | HotShotEvent::QuorumVoteSend(_)
+            | HotShotEvent::ExtendedQuorumVoteSend(_)
+            | HotShotEvent::VidDisperseSend(..)
+            | HotShotEvent::DaProposalSend(..)
+            | HotShotEvent::DaVoteSend(_)
+            | HotShotEvent::DacSend(..)
+            | HotShotEvent::ViewSyncPreCommitVoteSend(_)
+            | HotShotEvent::ViewSyncCommitVoteSend(_)
+            | HotShotEvent::ViewSyncFinalizeVoteSend(_)
+            | HotShotEvent::ViewSyncPreCommitCertificate2Send(..)
+            | HotShotEvent::ViewSyncCommitCertificate2Send(..)
+            | HotShotEvent::ViewSyncFinalizeCertificate2Send(..)
+            | HotShotEvent::TimeoutVoteSend(_)
+            | HotShotEvent::UpgradeProposalSend(..)
+            | HotShotEvent::UpgradeVoteSend(_)
+            | HotShotEvent::HighQcSend(..)
+    )
+}
+
+/// Prefix a serialized message with the id of the network it is sent on.
+///
+/// The default network id of 0 is not tagged, so a network which does not set one keeps the
+/// wire format it always had.
+#[must_use]
+pub fn tag_network_id(network_id: u64, message: Vec<u8>) -> Vec<u8> {
+    if network_id == 0 {
+        return message;
+    }
+
+    let mut tagged = Vec::with_capacity(NETWORK_ID_TAG_LEN + message.len());
+    tagged.extend_from_slice(&network_id.to_le_bytes());
+    tagged.extend(message);
+    tagged
+}
+
+/// Strip the network id tag from a message received from the network, before it is decoded.
+///
+/// Messages on the default network id of 0 are not tagged, and are returned as they are.
+///
+/// # Errors
+/// If the message is not tagged, or was sent on a different network
+pub fn strip_network_id(network_id: u64, message: &[u8]) -> Result<&[u8]> {
+    if network_id == 0 {
+        return Ok(message);
+    }
+
+    let Some((tag, payload)) = message.split_first_chunk::<NETWORK_ID_TAG_LEN>() else {
+        bail!(warn!("Dropping message without a network id tag"));
+    };
+    let tag = u64::from_le_bytes(*tag);
+    ensure!(
+        tag == network_id,
+        warn!("Dropping message from network {tag}, expected network {network_id}")
+    );
+
+    Ok(payload)
+}
+
+/// Number of justify QCs of received proposals kept to restore proposals referencing them
+pub const JUSTIFY_QC_CACHE_CAPACITY: usize = 16;
+
+/// A proposal waiting for the justify QC it references, with the node which sent it
+type AwaitingProposal<TYPES> = (
+    Proposal<TYPES, QuorumProposalWithQcRef<TYPES>>,
+    <TYPES as NodeType>::SignatureKey,
+);
+
+/// The justify QCs of recently received proposals, used to restore the proposals which reference
+/// their justify QC by commitment
+#[derive(Clone)]
+pub struct ReceivedJustifyQcs<TYPES: NodeType> {
+    /// The QCs, by commitment
+    qcs: lru::LruCache<Commitment<QuorumCertificate<TYPES>>, QuorumCertificate<TYPES>>,
+    /// Proposals referencing a QC we have not received yet, by view
+    awaiting: BTreeMap<TYPES::View, AwaitingProposal<TYPES>>,
+}
+
+impl<TYPES: NodeType> Default for ReceivedJustifyQcs<TYPES> {
+    fn default() -> Self {
+        Self {
+            qcs: lru::LruCache::new(NonZeroUsize::new(JUSTIFY_QC_CACHE_CAPACITY).unwrap()),
+            awaiting: BTreeMap::new(),
+        }
+    }
+}
+
+impl<TYPES: NodeType> ReceivedJustifyQcs<TYPES> {
+    /// Record the justify QC of a received proposal, and return the awaiting proposals it
+    /// restores
+    pub fn insert(
+        &mut self,
+        justify_qc: &QuorumCertificate<TYPES>,
+    ) -> Vec<(Proposal<TYPES, QuorumProposal<TYPES>>, TYPES::SignatureKey)> {
+        let commitment = justify_qc.commit();
+        self.qcs.put(commitment, justify_qc.clone());
+
+        let (restored, awaiting): (BTreeMap<_, _>, _) = std::mem::take(&mut self.awaiting)
+            .into_iter()
+            .partition(|(_, (proposal, _))| proposal.data.justify_qc == commitment);
+        self.awaiting = awaiting;
+
+        restored
+            .into_values()
+            .filter_map(|(proposal, sender)| Some((self.restore(proposal)?, sender)))
+            .collect()
+    }
+
+    /// Restore `proposal` with the justify QC it references, if we have it. Otherwise, keep it
+    /// until the QC arrives, dropping the awaiting proposals from before `cur_view`.
+    pub fn restore_or_await(
+        &mut self,
+        proposal: Proposal<TYPES, QuorumProposalWithQcRef<TYPES>>,
+        sender: TYPES::SignatureKey,
+        cur_view: TYPES::View,
+    ) -> Option<Proposal<TYPES, QuorumProposal<TYPES>>> {
+        if self.qcs.contains(&proposal.data.justify_qc) {
+            return self.restore(proposal);
+        }
+
+        self.awaiting = self.awaiting.split_off(&cur_view);
+        self.awaiting
+            .insert(proposal.data.view_number, (proposal, sender));
+
+        None
+    }
+
+    /// Restore `proposal` with the justify QC it references, if we have it
+    fn restore(
+        &mut self,
+        proposal: Proposal<TYPES, QuorumProposalWithQcRef<TYPES>>,
+    ) -> Option<Proposal<TYPES, QuorumProposal<TYPES>>> {
+        let justify_qc = self.qcs.get(&proposal.data.justify_qc)?.clone();
+
+        Some(Proposal {
+            data: proposal.data.with_justify_qc(justify_qc)?,
+            signature: proposal.signature,
+            leader_proof: proposal.leader_proof,
+            _pd: proposal._pd,
+        })
+    }
+}
+
 /// the network message task state
 #[derive(Clone)]
 pub struct NetworkMessageTaskState<TYPES: NodeType> {
@@ -56,6 +275,21 @@ pub struct NetworkMessageTaskState<TYPES: NodeType> {
 
     /// Transaction Cache to ignore previously seen transatctions
     pub transactions_cache: lru::LruCache<u64, ()>,
+
+    /// Check run on each transaction before it is admitted to the mempool
+    pub transaction_validator: Arc<dyn TxnValidator<TYPES>>,
+
+    /// Metrics for rejected transactions
+    pub metrics: Arc<ConsensusMetricsValue>,
+
+    /// Reference to consensus, used to look up the current view
+    pub consensus: OuterConsensus<TYPES>,
+
+    /// Consensus messages more than this many views behind the current view are dropped on arrival
+    pub max_message_age_views: u64,
+
+    /// Justify QCs of received proposals, for the proposals which reference them by commitment
+    pub received_justify_qcs: ReceivedJustifyQcs<TYPES>,
 }
 
 impl<TYPES: NodeType> NetworkMessageTaskState<TYPES> {
@@ -69,15 +303,51 @@ impl<TYPES: NodeType> NetworkMessageTaskState<TYPES> {
         match message.kind {
             // Handle consensus messages
             MessageKind::Consensus(consensus_message) => {
+                let (view, class) = consensus_message.routing_key();
+
+                // Late gossip for a long-past view is only wasted work for the tasks. Proposal
+                // requests and responses are for past views by design, to help nodes catch up.
+                let cur_view = self.consensus.read().await.cur_view();
+                let catch_up = class == MessageClass::InternalTrigger
+                    || matches!(
+                        consensus_message,
+                        SequencingMessage::General(GeneralConsensusMessage::ProposalResponse(_))
+                    );
+                if !catch_up && (*view).saturating_add(self.max_message_age_views) < *cur_view {
+                    tracing::debug!(
+                        "Dropping {class:?} message for view {view:?}, we are already in view \
+                         {cur_view:?}"
+                    );
+                    return;
+                }
+
+                tracing::trace!("Routing {class:?} message for view {view:?}");
+
                 let event = match consensus_message {
                     SequencingMessage::General(general_message) => match general_message {
                         GeneralConsensusMessage::Proposal(proposal) => {
+                            self.justify_qc_received(&proposal.data.justify_qc).await;
                             HotShotEvent::QuorumProposalRecv(convert_proposal(proposal), sender)
                         }
+                        GeneralConsensusMessage::ProposalWithQcRef(proposal) => {
+                            let view = proposal.data.view_number;
+                            match self.received_justify_qcs.restore_or_await(
+                                proposal,
+                                sender.clone(),
+                                cur_view,
+                            ) {
+                                Some(proposal) => HotShotEvent::QuorumProposalRecv(
+                                    convert_proposal(proposal),
+                                    sender,
+                                ),
+                                None => HotShotEvent::JustifyQcMissing(view),
+                            }
+                        }
                         GeneralConsensusMessage::ProposalRequested(req, sig) => {
                             HotShotEvent::QuorumProposalRequestRecv(req, sig)
                         }
                         GeneralConsensusMessage::ProposalResponse(proposal) => {
+                            self.justify_qc_received(&proposal.data.justify_qc).await;
                             HotShotEvent::QuorumProposalResponseRecv(convert_proposal(proposal))
                         }
                         GeneralConsensusMessage::Vote(vote) => {
@@ -140,6 +410,11 @@ impl<TYPES: NodeType> NetworkMessageTaskState<TYPES> {
                     if self.transactions_cache.put(hasher.finish(), ()).is_some() {
                         return;
                     }
+                    if let Err(e) = self.transaction_validator.validate(&transaction) {
+                        tracing::warn!("Rejected transaction from {sender:?}: {e}");
+                        self.metrics.rejected_transactions.add(1);
+                        return;
+                    }
                     broadcast_event(
                         Arc::new(HotShotEvent::TransactionsRecv(vec![transaction])),
                         &self.internal_event_stream,
@@ -191,6 +466,20 @@ impl<TYPES: NodeType> NetworkMessageTaskState<TYPES> {
             }
         }
     }
+
+    /// Record the justify QC of a received proposal, and pass on the proposals it restores
+    async fn justify_qc_received(&mut self, justify_qc: &QuorumCertificate<TYPES>) {
+        for (proposal, sender) in self.received_justify_qcs.insert(justify_qc) {
+            broadcast_event(
+                Arc::new(HotShotEvent::QuorumProposalRecv(
+                    convert_proposal(proposal),
+                    sender,
+                )),
+                &self.internal_event_stream,
+            )
+            .await;
+        }
+    }
 }
 
 /// network event task state
@@ -206,16 +495,26 @@ pub struct NetworkEventTaskState<
     pub view: TYPES::View,
     /// epoch number
     pub epoch: TYPES::Epoch,
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
     /// network memberships
-    pub membership: TYPES::Membership,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
     /// Storage to store actionable events
     pub storage: Arc<RwLock<S>>,
     /// Shared consensus state
     pub consensus: OuterConsensus<TYPES>,
     /// Lock for a decided upgrade
     pub upgrade_lock: UpgradeLock<TYPES, V>,
+    /// Identifier of the network messages are sent on
+    pub network_id: u64,
     /// map view number to transmit tasks
     pub transmit_tasks: BTreeMap<TYPES::View, Vec<JoinHandle<()>>>,
+    /// Whether we take part in consensus, or only follow it
+    pub participation: Arc<Participation>,
+    /// Whether a proposal sharing the justify QC of our previous one references it by commitment
+    pub batch_justify_qcs: bool,
+    /// The justify QC of the last proposal we sent
+    pub last_justify_qc: Option<Commitment<QuorumCertificate<TYPES>>>,
 }
 
 #[async_trait]
@@ -231,10 +530,10 @@ impl<
     async fn handle_event(
         &mut self,
         event: Arc<Self::Event>,
-        _sender: &Sender<Arc<Self::Event>>,
+        sender: &Sender<Arc<Self::Event>>,
         _receiver: &Receiver<Arc<Self::Event>>,
     ) -> Result<()> {
-        self.handle(event).await;
+        self.handle(event, sender).await;
 
         Ok(())
     }
@@ -249,19 +548,40 @@ impl<
         S: Storage<TYPES> + 'static,
     > NetworkEventTaskState<TYPES, V, NET, S>
 {
-    /// Handle the given event.
+    /// Handle the given event, reporting on `event_stream` what fails to send.
     ///
     /// Returns the completion status.
     #[instrument(skip_all, fields(view = *self.view), name = "Network Task", level = "error")]
-    pub async fn handle(&mut self, event: Arc<HotShotEvent<TYPES>>) {
+    pub async fn handle(
+        &mut self,
+        event: Arc<HotShotEvent<TYPES>>,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+    ) {
+        if self.is_suppressed(&event) {
+            return;
+        }
         let mut maybe_action = None;
         if let Some((sender, message_kind, transmit)) =
             self.parse_event(event, &mut maybe_action).await
         {
-            self.spawn_transmit_task(message_kind, maybe_action, transmit, sender);
+            self.spawn_transmit_task(message_kind, maybe_action, transmit, sender, event_stream)
+                .await;
         };
     }
 
+    /// Whether `event` must not be sent because we are not taking part in consensus in its view
+    fn is_suppressed(&self, event: &HotShotEvent<TYPES>) -> bool {
+        let suppressed = is_participation(event)
+            && event
+                .view_number()
+                .is_some_and(|view| !self.participation.is_engaged(*view));
+        if suppressed {
+            tracing::debug!("Not taking part in consensus, dropping {event}");
+        }
+
+        suppressed
+    }
+
     /// handle `VidDisperseSend`
     async fn handle_vid_disperse_proposal(
         &self,
@@ -281,7 +601,7 @@ impl<
                 )),
             };
             let serialized_message = match self.upgrade_lock.serialize(&message).await {
-                Ok(serialized) => serialized,
+                Ok(serialized) => tag_network_id(self.network_id, serialized),
                 Err(e) => {
                     tracing::error!("Failed to serialize message: {}", e);
                     continue;
@@ -387,7 +707,7 @@ impl<
             HotShotEvent::QuorumVoteSend(vote) => {
                 *maybe_action = Some(HotShotAction::Vote);
                 let view_number = vote.view_number() + 1;
-                let leader = match self.membership.leader(view_number, self.epoch) {
+                let leader = match self.membership.read().await.leader(view_number, self.epoch) {
                     Ok(l) => l,
                     Err(e) => {
                         tracing::warn!(
@@ -448,7 +768,7 @@ impl<
             HotShotEvent::DaVoteSend(vote) => {
                 *maybe_action = Some(HotShotAction::DaVote);
                 let view_number = vote.view_number();
-                let leader = match self.membership.leader(view_number, self.epoch) {
+                let leader = match self.membership.read().await.leader(view_number, self.epoch) {
                     Ok(l) => l,
                     Err(e) => {
                         tracing::warn!(
@@ -480,7 +800,7 @@ impl<
             }
             HotShotEvent::ViewSyncPreCommitVoteSend(vote) => {
                 let view_number = vote.view_number() + vote.date().relay;
-                let leader = match self.membership.leader(view_number, self.epoch) {
+                let leader = match self.membership.read().await.leader(view_number, self.epoch) {
                     Ok(l) => l,
                     Err(e) => {
                         tracing::warn!(
@@ -503,7 +823,7 @@ impl<
             HotShotEvent::ViewSyncCommitVoteSend(vote) => {
                 *maybe_action = Some(HotShotAction::ViewSyncVote);
                 let view_number = vote.view_number() + vote.date().relay;
-                let leader = match self.membership.leader(view_number, self.epoch) {
+                let leader = match self.membership.read().await.leader(view_number, self.epoch) {
                     Ok(l) => l,
                     Err(e) => {
                         tracing::warn!(
@@ -526,7 +846,7 @@ impl<
             HotShotEvent::ViewSyncFinalizeVoteSend(vote) => {
                 *maybe_action = Some(HotShotAction::ViewSyncVote);
                 let view_number = vote.view_number() + vote.date().relay;
-                let leader = match self.membership.leader(view_number, self.epoch) {
+                let leader = match self.membership.read().await.leader(view_number, self.epoch) {
                     Ok(l) => l,
                     Err(e) => {
                         tracing::warn!(
@@ -570,7 +890,7 @@ impl<
             HotShotEvent::TimeoutVoteSend(vote) => {
                 *maybe_action = Some(HotShotAction::Vote);
                 let view_number = vote.view_number() + 1;
-                let leader = match self.membership.leader(view_number, self.epoch) {
+                let leader = match self.membership.read().await.leader(view_number, self.epoch) {
                     Ok(l) => l,
                     Err(e) => {
                         tracing::warn!(
@@ -599,7 +919,7 @@ impl<
             HotShotEvent::UpgradeVoteSend(vote) => {
                 tracing::error!("Sending upgrade vote!");
                 let view_number = vote.view_number();
-                let leader = match self.membership.leader(view_number, self.epoch) {
+                let leader = match self.membership.read().await.leader(view_number, self.epoch) {
                     Ok(l) => l,
                     Err(e) => {
                         tracing::warn!(
@@ -620,13 +940,24 @@ impl<
             }
             HotShotEvent::ViewChange(view, epoch) => {
                 self.view = view;
-                if epoch > self.epoch {
-                    self.epoch = epoch;
+                let prev_epoch = self.epoch;
+                advance_epoch::<TYPES>(&mut self.epoch, epoch, self.max_epoch_skip);
+                if self.epoch != prev_epoch {
+                    // Connect to the upcoming committees well before their epochs start.
+                    let upcoming_committees = self
+                        .membership
+                        .read()
+                        .await
+                        .upcoming_committee(self.epoch, COMMITTEE_PRE_DIAL_LOOKAHEAD_EPOCHS);
+                    for (_, members) in upcoming_committees {
+                        self.network
+                            .pre_dial_committee(ViewNumber::new(*view), members);
+                    }
                 }
                 self.cancel_tasks(view);
                 let net = Arc::clone(&self.network);
                 let epoch = self.epoch.u64();
-                let mem = self.membership.clone();
+                let mem = self.membership.read().await.clone();
                 spawn(async move {
                     net.update_view::<TYPES>(view.saturating_sub(1), epoch, &mem)
                         .await;
@@ -659,13 +990,41 @@ impl<
         }
     }
 
+    /// The message to send instead of `message_kind` if it is a proposal sharing the justify QC
+    /// of the last proposal we sent, which references the QC by commitment.
+    fn reference_sent_justify_qc(
+        &mut self,
+        message_kind: &MessageKind<TYPES>,
+    ) -> Option<MessageKind<TYPES>> {
+        let MessageKind::Consensus(SequencingMessage::General(GeneralConsensusMessage::Proposal(
+            proposal,
+        ))) = message_kind
+        else {
+            return None;
+        };
+        let justify_qc = proposal.data.justify_qc.commit();
+        if !self.batch_justify_qcs || self.last_justify_qc.replace(justify_qc) != Some(justify_qc) {
+            return None;
+        }
+
+        Some(MessageKind::<TYPES>::from_consensus_message(
+            SequencingMessage::General(GeneralConsensusMessage::ProposalWithQcRef(
+                convert_proposal(proposal.clone()),
+            )),
+        ))
+    }
+
     /// Creates a network message and spawns a task that transmits it on the wire.
-    fn spawn_transmit_task(
+    ///
+    /// A DA vote waits for the DA leader to acknowledge it, and a `DaVoteSendFailed` carrying the
+    /// network's error is sent on `event_stream` if it could not be delivered.
+    async fn spawn_transmit_task(
         &mut self,
         message_kind: MessageKind<TYPES>,
         maybe_action: Option<HotShotAction>,
         transmit: TransmitType<TYPES>,
         sender: TYPES::SignatureKey,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
     ) {
         let broadcast_delay = match &message_kind {
             MessageKind::Consensus(
@@ -674,7 +1033,15 @@ impl<
             ) => BroadcastDelay::View(*message_kind.view_number()),
             _ => BroadcastDelay::None,
         };
-        let message = Message {
+        let qc_ref = self.reference_sent_justify_qc(&message_kind);
+        let da_vote = match &message_kind {
+            MessageKind::Consensus(SequencingMessage::Da(DaConsensusMessage::DaVote(vote))) => {
+                Some(vote.clone())
+            }
+            _ => None,
+        };
+        let event_stream = event_stream.clone();
+        let mut message = Message {
             sender,
             kind: message_kind,
         };
@@ -682,11 +1049,14 @@ impl<
         let committee_topic = Topic::Global;
         let da_committee = self
             .membership
+            .read()
+            .await
             .da_committee_members(view_number, self.epoch);
         let network = Arc::clone(&self.network);
         let storage = Arc::clone(&self.storage);
         let consensus = OuterConsensus::new(Arc::clone(&self.consensus.inner_consensus));
         let upgrade_lock = self.upgrade_lock.clone();
+        let network_id = self.network_id;
         let handle = spawn(async move {
             if NetworkEventTaskState::<TYPES, V, NET, S>::maybe_record_action(
                 maybe_action,
@@ -708,8 +1078,13 @@ impl<
                 }
             }
 
+            // The full proposal is stored, but its justify QC only goes over the wire once
+            if let Some(qc_ref) = qc_ref {
+                message.kind = qc_ref;
+            }
+
             let serialized_message = match upgrade_lock.serialize(&message).await {
-                Ok(serialized) => serialized,
+                Ok(serialized) => tag_network_id(network_id, serialized),
                 Err(e) => {
                     tracing::error!("Failed to serialize message: {}", e);
                     return;
@@ -717,6 +1092,11 @@ impl<
             };
 
             let transmit_result = match transmit {
+                TransmitType::Direct(recipient) if da_vote.is_some() => {
+                    network
+                        .direct_message_acked(serialized_message, recipient)
+                        .await
+                }
                 TransmitType::Direct(recipient) => {
                     network.direct_message(serialized_message, recipient).await
                 }
@@ -738,7 +1118,16 @@ impl<
 
             match transmit_result {
                 Ok(()) => {}
-                Err(e) => tracing::warn!("Failed to send message task: {:?}", e),
+                Err(e) => {
+                    tracing::warn!("Failed to send message task: {:?}", e);
+                    if let Some(vote) = da_vote {
+                        broadcast_event(
+                            Arc::new(HotShotEvent::DaVoteSendFailed(vote, e)),
+                            &event_stream,
+                        )
+                        .await;
+                    }
+                }
             }
         });
         self.transmit_tasks
@@ -791,19 +1180,35 @@ pub mod test {
         > NetworkEventTaskStateModifier<TYPES, V, NET, S>
     {
         /// Handles the received event modifying it before sending on the network.
-        pub async fn handle(&mut self, event: Arc<HotShotEvent<TYPES>>) {
+        pub async fn handle(
+            &mut self,
+            event: Arc<HotShotEvent<TYPES>>,
+            event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+        ) {
+            if self.is_suppressed(&event) {
+                return;
+            }
             let mut maybe_action = None;
             if let Some((mut sender, mut message_kind, mut transmit)) =
                 self.parse_event(event, &mut maybe_action).await
             {
                 // Modify the values acquired by parsing the event.
+                let membership_reader = self.membership.read().await;
                 (self.modifier)(
                     &mut sender,
                     &mut message_kind,
                     &mut transmit,
-                    &self.membership,
+                    &membership_reader,
                 );
-                self.spawn_transmit_task(message_kind, maybe_action, transmit, sender);
+                drop(membership_reader);
+                self.spawn_transmit_task(
+                    message_kind,
+                    maybe_action,
+                    transmit,
+                    sender,
+                    event_stream,
+                )
+                .await;
             }
         }
     }
@@ -821,10 +1226,10 @@ pub mod test {
         async fn handle_event(
             &mut self,
             event: Arc<Self::Event>,
-            _sender: &Sender<Arc<Self::Event>>,
+            sender: &Sender<Arc<Self::Event>>,
             _receiver: &Receiver<Arc<Self::Event>>,
         ) -> Result<()> {
-            self.handle(event).await;
+            self.handle(event, sender).await;
 
             Ok(())
         }
@@ -21,7 +21,7 @@ use hotshot_task::dependency_task::HandleDepOutput;
 use hotshot_types::{
     consensus::{CommitmentAndMetadata, OuterConsensus},
     data::{Leaf2, QuorumProposal, VidDisperse, ViewChangeEvidence},
-    message::Proposal,
+    message::{quorum_proposal_signing_bytes, Proposal},
     simple_certificate::{QuorumCertificate2, UpgradeCertificate},
     traits::{
         block_contents::BlockHeader,
@@ -37,7 +37,7 @@ use vbs::version::StaticVersionType;
 
 use crate::{
     events::HotShotEvent,
-    helpers::{broadcast_event, parent_leaf_and_state},
+    helpers::{broadcast_event, check_undecided_backlog, parent_leaf_and_state},
     quorum_proposal::{UpgradeLock, Versions},
 };
 
@@ -71,6 +71,9 @@ pub struct ProposalDependencyHandle<TYPES: NodeType, V: Versions> {
     /// The view number to propose for.
     pub view_number: TYPES::View,
 
+    /// The epoch whose committee selected us as the leader of `view_number`.
+    pub epoch_number: TYPES::Epoch,
+
     /// The event sender.
     pub sender: Sender<Arc<HotShotEvent<TYPES>>>,
 
@@ -81,7 +84,7 @@ pub struct ProposalDependencyHandle<TYPES: NodeType, V: Versions> {
     pub instance_state: Arc<TYPES::InstanceState>,
 
     /// Membership for Quorum Certs/votes
-    pub quorum_membership: Arc<TYPES::Membership>,
+    pub quorum_membership: Arc<RwLock<TYPES::Membership>>,
 
     /// Our public key
     pub public_key: TYPES::SignatureKey,
@@ -113,6 +116,9 @@ pub struct ProposalDependencyHandle<TYPES: NodeType, V: Versions> {
 
     /// The highest_qc we've seen at the start of this task
     pub highest_qc: QuorumCertificate2<TYPES>,
+
+    /// Most undecided views before we stop proposing
+    pub max_undecided_views: u64,
 }
 
 impl<TYPES: NodeType, V: Versions> ProposalDependencyHandle<TYPES, V> {
@@ -123,13 +129,13 @@ impl<TYPES: NodeType, V: Versions> ProposalDependencyHandle<TYPES, V> {
     ) -> Option<QuorumCertificate2<TYPES>> {
         while let Ok(event) = rx.recv_direct().await {
             if let HotShotEvent::HighQcRecv(qc, _sender) = event.as_ref() {
+                let membership_reader = self.quorum_membership.read().await;
                 if qc
                     .is_valid_cert(
                         // TODO take epoch from `qc`
                         // https://github.com/EspressoSystems/HotShot/issues/3917
-                        self.quorum_membership.stake_table(TYPES::Epoch::new(0)),
-                        self.quorum_membership
-                            .success_threshold(TYPES::Epoch::new(0)),
+                        &membership_reader,
+                        TYPES::Epoch::new(0),
                         &self.upgrade_lock,
                     )
                     .await
@@ -311,14 +317,22 @@ impl<TYPES: NodeType, V: Versions> ProposalDependencyHandle<TYPES, V> {
             "Proposed leaf parent does not equal high qc"
         );
 
-        let signature =
-            TYPES::SignatureKey::sign(&self.private_key, proposed_leaf.commit().as_ref())
-                .wrap()
-                .context(error!("Failed to compute proposed_leaf.commit()"))?;
+        let signed_bytes = quorum_proposal_signing_bytes::<TYPES, V>(&proposal, version);
+        let signature = TYPES::SignatureKey::sign(&self.private_key, &signed_bytes)
+            .wrap()
+            .context(error!("Failed to sign the proposal"))?;
+
+        let leader_proof = self
+            .quorum_membership
+            .read()
+            .await
+            .leader_proof(self.view_number, self.epoch_number, &self.private_key)
+            .context(error!("Failed to prove we lead the view"))?;
 
         let message = Proposal {
             data: proposal,
             signature,
+            leader_proof,
             _pd: PhantomData,
         };
         tracing::debug!(
@@ -420,6 +434,18 @@ impl<TYPES: NodeType, V: Versions> HandleDepOutput for ProposalDependencyHandle<
             timeout_certificate.map(ViewChangeEvidence::Timeout)
         };
 
+        if let Err(e) = check_undecided_backlog(
+            self.view_number,
+            &self.consensus,
+            self.max_undecided_views,
+            &self.sender,
+        )
+        .await
+        {
+            tracing::warn!("Not proposing; {e:#}");
+            return;
+        }
+
         if let Err(e) = self
             .publish_proposal(
                 commit_and_metadata.unwrap(),
@@ -48,7 +48,7 @@ pub struct QuorumProposalTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>
     pub instance_state: Arc<TYPES::InstanceState>,
 
     /// Membership for Quorum Certs/votes
-    pub quorum_membership: Arc<TYPES::Membership>,
+    pub quorum_membership: Arc<RwLock<TYPES::Membership>>,
 
     /// Our public key
     pub public_key: TYPES::SignatureKey,
@@ -84,6 +84,9 @@ pub struct QuorumProposalTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>
 
     /// The highest_qc we've seen at the start of this task
     pub highest_qc: QuorumCertificate2<TYPES>,
+
+    /// Most undecided views before we stop proposing
+    pub max_undecided_views: u64,
 }
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
@@ -267,7 +270,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
     /// without losing the data that it received, as the dependency task would otherwise have no
     /// ability to receive the event and, thus, would never propose.
     #[instrument(skip_all, fields(id = self.id, latest_proposed_view = *self.latest_proposed_view), name = "Create dependency task", level = "error")]
-    fn create_dependency_task_if_new(
+    async fn create_dependency_task_if_new(
         &mut self,
         view_number: TYPES::View,
         epoch_number: TYPES::Epoch,
@@ -276,8 +279,13 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
         event: Arc<HotShotEvent<TYPES>>,
     ) -> Result<()> {
         // Don't even bother making the task if we are not entitled to propose anyway.
+        let leader = self
+            .quorum_membership
+            .read()
+            .await
+            .leader(view_number, epoch_number)?;
         ensure!(
-            self.quorum_membership.leader(view_number, epoch_number)? == self.public_key,
+            leader == self.public_key,
             debug!("We are not the leader of the next view")
         );
 
@@ -304,6 +312,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
             ProposalDependencyHandle {
                 latest_proposed_view: self.latest_proposed_view,
                 view_number,
+                epoch_number,
                 sender: event_sender,
                 receiver: event_receiver,
                 quorum_membership: Arc::clone(&self.quorum_membership),
@@ -317,6 +326,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                 id: self.id,
                 view_start_time: Instant::now(),
                 highest_qc: self.highest_qc.clone(),
+                max_undecided_views: self.max_undecided_views,
             },
         );
         self.proposal_dependencies
@@ -381,7 +391,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                         event_receiver,
                         event_sender,
                         Arc::clone(&event),
-                    )?;
+                    )
+                    .await?;
                 }
                 either::Left(qc) => {
                     // Only update if the qc is from a newer view
@@ -415,7 +426,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                         event_receiver,
                         event_sender,
                         Arc::clone(&event),
-                    )?;
+                    )
+                    .await?;
                 }
             },
             HotShotEvent::SendPayloadCommitmentAndMetadata(
@@ -435,19 +447,19 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                     event_receiver,
                     event_sender,
                     Arc::clone(&event),
-                )?;
+                )
+                .await?;
             }
             HotShotEvent::ViewSyncFinalizeCertificate2Recv(certificate) => {
                 let epoch_number = self.consensus.read().await.cur_epoch();
 
+                let membership_reader = self.quorum_membership.read().await;
+                let certificate_valid = certificate
+                    .is_valid_cert(&membership_reader, epoch_number, &self.upgrade_lock)
+                    .await;
+                drop(membership_reader);
                 ensure!(
-                    certificate
-                        .is_valid_cert(
-                            self.quorum_membership.stake_table(epoch_number),
-                            self.quorum_membership.success_threshold(epoch_number),
-                            &self.upgrade_lock
-                        )
-                        .await,
+                    certificate_valid,
                     warn!(
                         "View Sync Finalize certificate {:?} was invalid",
                         certificate.data()
@@ -462,7 +474,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                     event_receiver,
                     event_sender,
                     event,
-                )?;
+                )
+                .await?;
             }
             HotShotEvent::QuorumProposalPreliminarilyValidated(proposal) => {
                 let view_number = proposal.data.view_number();
@@ -478,7 +491,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                     event_receiver,
                     event_sender,
                     Arc::clone(&event),
-                )?;
+                )
+                .await?;
             }
             HotShotEvent::QuorumProposalSend(proposal, _) => {
                 let view = proposal.data.view_number();
@@ -497,7 +511,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                     event_receiver,
                     event_sender,
                     Arc::clone(&event),
-                )?;
+                )
+                .await?;
             }
             HotShotEvent::ViewChange(view, _) | HotShotEvent::Timeout(view) => {
                 self.cancel_tasks(*view);
@@ -505,13 +520,13 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
             HotShotEvent::HighQcSend(qc, ..) => {
                 ensure!(qc.view_number() > self.highest_qc.view_number());
                 let epoch_number = self.consensus.read().await.cur_epoch();
+                let membership_reader = self.quorum_membership.read().await;
+                let qc_valid = qc
+                    .is_valid_cert(&membership_reader, epoch_number, &self.upgrade_lock)
+                    .await;
+                drop(membership_reader);
                 ensure!(
-                    qc.is_valid_cert(
-                        self.quorum_membership.stake_table(epoch_number),
-                        self.quorum_membership.success_threshold(epoch_number),
-                        &self.upgrade_lock
-                    )
-                    .await,
+                    qc_valid,
                     warn!("Quorum certificate {:?} was invalid", qc.data())
                 );
                 self.highest_qc = qc.clone();
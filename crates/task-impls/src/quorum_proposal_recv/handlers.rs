@@ -6,10 +6,13 @@
 
 #![allow(dead_code)]
 
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use async_broadcast::{broadcast, Receiver, Sender};
-use async_lock::RwLockUpgradableReadGuard;
+use async_lock::{RwLock, RwLockUpgradableReadGuard};
 use committable::Committable;
 use hotshot_types::{
     consensus::OuterConsensus,
@@ -36,11 +39,45 @@ use super::{QuorumProposalRecvTaskState, ValidationInfo};
 use crate::{
     events::HotShotEvent,
     helpers::{
-        broadcast_event, fetch_proposal, validate_proposal_safety_and_liveness,
+        broadcast_event, check_safety_fork, fetch_proposal, validate_proposal_safety_and_liveness,
         validate_proposal_view_and_certs,
     },
     quorum_proposal_recv::{UpgradeLock, Versions},
 };
+
+/// Flag `proposal` if the timestamp of its block header is further than `max_clock_skew` from
+/// local time.
+///
+/// The proposal is processed on its view number either way: a skewed timestamp may just as well
+/// mean that our own clock is off.
+async fn check_clock_skew<TYPES: NodeType>(
+    proposal: &QuorumProposal2<TYPES>,
+    max_clock_skew: Duration,
+    consensus: &OuterConsensus<TYPES>,
+) {
+    let Some(timestamp) = proposal.block_header.timestamp() else {
+        return;
+    };
+    let timestamp = Duration::from_secs(timestamp);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let skew = if timestamp > now {
+        timestamp - now
+    } else {
+        now - timestamp
+    };
+
+    if skew > max_clock_skew {
+        tracing::warn!(
+            "Proposal for view {} has a timestamp {skew:?} away from local time, more than the \
+             {max_clock_skew:?} allowed",
+            *proposal.view_number()
+        );
+        consensus.read().await.metrics.clock_skewed_proposals.add(1);
+    }
+}
+
 /// Update states in the event that the parent state is not found for a given `proposal`.
 #[instrument(skip_all)]
 async fn validate_proposal_liveness<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>(
@@ -59,17 +96,22 @@ async fn validate_proposal_liveness<TYPES: NodeType, I: NodeImplementation<TYPES
         tracing::trace!("{e:?}");
     }
 
-    if let Err(e) = validation_info
-        .storage
-        .write()
-        .await
-        .update_undecided_state2(
-            consensus_writer.saved_leaves().clone(),
-            consensus_writer.validated_state_map().clone(),
-        )
-        .await
+    if validation_info
+        .checkpoint_policy
+        .checkpoint_on_view(*leaf.view_number())
     {
-        tracing::warn!("Couldn't store undecided state.  Error: {:?}", e);
+        if let Err(e) = validation_info
+            .storage
+            .write()
+            .await
+            .update_undecided_state2(
+                consensus_writer.saved_leaves().clone(),
+                consensus_writer.validated_state_map().clone(),
+            )
+            .await
+        {
+            tracing::warn!("Couldn't store undecided state.  Error: {:?}", e);
+        }
     }
 
     let liveness_check =
@@ -100,7 +142,7 @@ fn spawn_fetch_proposal<TYPES: NodeType, V: Versions>(
     view: TYPES::View,
     event_sender: Sender<Arc<HotShotEvent<TYPES>>>,
     event_receiver: Receiver<Arc<HotShotEvent<TYPES>>>,
-    membership: Arc<TYPES::Membership>,
+    membership: Arc<RwLock<TYPES::Membership>>,
     consensus: OuterConsensus<TYPES>,
     sender_public_key: TYPES::SignatureKey,
     sender_private_key: <TYPES::SignatureKey as SignatureKey>::PrivateKey,
@@ -145,30 +187,38 @@ pub(crate) async fn handle_quorum_proposal_recv<
 ) -> Result<()> {
     let quorum_proposal_sender_key = quorum_proposal_sender_key.clone();
 
-    validate_proposal_view_and_certs(proposal, &validation_info)
+    validate_proposal_view_and_certs(proposal, &quorum_proposal_sender_key, &validation_info)
         .await
         .context(warn!("Failed to validate proposal view or attached certs"))?;
 
+    check_clock_skew(
+        &proposal.data,
+        validation_info.max_clock_skew,
+        &validation_info.consensus,
+    )
+    .await;
+
     let view_number = proposal.data.view_number();
     let justify_qc = proposal.data.justify_qc.clone();
 
-    if !justify_qc
+    let membership_reader = validation_info.quorum_membership.read().await;
+    let justify_qc_valid = justify_qc
         .is_valid_cert(
-            validation_info
-                .quorum_membership
-                .stake_table(validation_info.cur_epoch),
-            validation_info
-                .quorum_membership
-                .success_threshold(validation_info.cur_epoch),
+            &membership_reader,
+            validation_info.cur_epoch,
             &validation_info.upgrade_lock,
         )
-        .await
-    {
+        .await;
+    drop(membership_reader);
+    if !justify_qc_valid {
         let consensus_reader = validation_info.consensus.read().await;
         consensus_reader.metrics.invalid_qc.update(1);
         bail!("Invalid justify_qc in proposal for view {}", *view_number);
     }
 
+    // Stop following the chain once two valid QCs disagree on a view
+    check_safety_fork(&justify_qc, &validation_info.consensus, event_sender).await?;
+
     broadcast_event(
         Arc::new(HotShotEvent::QuorumProposalPreliminarilyValidated(
             proposal.clone(),
@@ -6,7 +6,7 @@
 
 #![allow(unused_imports)]
 
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use async_broadcast::{broadcast, Receiver, Sender};
 use async_lock::RwLock;
@@ -23,6 +23,7 @@ use hotshot_types::{
     traits::{
         node_implementation::{ConsensusTime, NodeImplementation, NodeType, Versions},
         signature_key::SignatureKey,
+        storage::CheckpointPolicy,
     },
     vote::{Certificate, HasViewNumber},
 };
@@ -34,7 +35,9 @@ use vbs::version::Version;
 use self::handlers::handle_quorum_proposal_recv;
 use crate::{
     events::{HotShotEvent, ProposalMissing},
-    helpers::{broadcast_event, fetch_proposal, parent_leaf_and_state},
+    helpers::{
+        advance_epoch, broadcast_event, fetch_proposal, parent_leaf_and_state, request_proposal,
+    },
 };
 /// Event handlers for this task.
 mod handlers;
@@ -58,7 +61,7 @@ pub struct QuorumProposalRecvTaskState<TYPES: NodeType, I: NodeImplementation<TY
     pub cur_epoch: TYPES::Epoch,
 
     /// Membership for Quorum Certs/votes
-    pub quorum_membership: Arc<TYPES::Membership>,
+    pub quorum_membership: Arc<RwLock<TYPES::Membership>>,
 
     /// View timeout from config.
     pub timeout: u64,
@@ -81,6 +84,15 @@ pub struct QuorumProposalRecvTaskState<TYPES: NodeType, I: NodeImplementation<TY
 
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
+
+    /// How often the undecided state is checkpointed to storage
+    pub checkpoint_policy: CheckpointPolicy,
+
+    /// How far the timestamp of a proposal may be from local time before it is flagged
+    pub max_clock_skew: Duration,
 }
 
 /// all the info we need to validate a proposal.  This makes it easy to spawn an effemeral task to
@@ -97,7 +109,7 @@ pub(crate) struct ValidationInfo<TYPES: NodeType, I: NodeImplementation<TYPES>,
     /// Reference to consensus. The replica will require a write lock on this.
     pub(crate) consensus: OuterConsensus<TYPES>,
     /// Membership for Quorum Certs/votes
-    pub quorum_membership: Arc<TYPES::Membership>,
+    pub quorum_membership: Arc<RwLock<TYPES::Membership>>,
     /// Output events to application
     pub output_event_stream: async_broadcast::Sender<Event<TYPES>>,
     /// This node's storage ref
@@ -106,6 +118,10 @@ pub(crate) struct ValidationInfo<TYPES: NodeType, I: NodeImplementation<TYPES>,
     pub(crate) upgrade_lock: UpgradeLock<TYPES, V>,
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+    /// How often the undecided state is checkpointed to storage
+    pub checkpoint_policy: CheckpointPolicy,
+    /// How far the timestamp of a proposal may be from local time before it is flagged
+    pub max_clock_skew: Duration,
 }
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
@@ -150,6 +166,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                     storage: Arc::clone(&self.storage),
                     upgrade_lock: self.upgrade_lock.clone(),
                     epoch_height: self.epoch_height,
+                    checkpoint_policy: self.checkpoint_policy,
+                    max_clock_skew: self.max_clock_skew,
                 };
                 match handle_quorum_proposal_recv(
                     proposal,
@@ -164,10 +182,21 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>
                     Err(e) => debug!(?e, "Failed to validate the proposal"),
                 }
             }
-            HotShotEvent::ViewChange(view, epoch) => {
-                if *epoch > self.cur_epoch {
-                    self.cur_epoch = *epoch;
+            HotShotEvent::JustifyQcMissing(view) => {
+                // The full proposal carries the QC, which restores the proposal we received
+                if let Err(e) = request_proposal(
+                    *view,
+                    self.public_key.clone(),
+                    &self.private_key,
+                    &event_sender,
+                )
+                .await
+                {
+                    warn!("Failed to request the justify QC for view {view:?}: {e:?}");
                 }
+            }
+            HotShotEvent::ViewChange(view, epoch) => {
+                advance_epoch::<TYPES>(&mut self.cur_epoch, *epoch, self.max_epoch_skip);
                 if self.cur_view >= *view {
                     return;
                 }
@@ -14,7 +14,7 @@ use hotshot_types::{
     consensus::OuterConsensus,
     data::{Leaf2, QuorumProposal2, VidDisperseShare},
     drb::compute_drb_result,
-    event::{Event, EventType},
+    event::{DecidedLeafSummary, Event, EventType},
     message::{Proposal, UpgradeLock},
     simple_vote::{QuorumData2, QuorumVote2},
     traits::{
@@ -22,7 +22,7 @@ use hotshot_types::{
         election::Membership,
         node_implementation::{ConsensusTime, NodeImplementation, NodeType},
         signature_key::SignatureKey,
-        storage::Storage,
+        storage::{CheckpointPolicy, Storage},
         ValidatedState,
     },
     utils::epoch_from_block_number,
@@ -37,8 +37,8 @@ use super::QuorumVoteTaskState;
 use crate::{
     events::HotShotEvent,
     helpers::{
-        broadcast_event, decide_from_proposal, decide_from_proposal_2, fetch_proposal,
-        LeafChainTraversalOutcome,
+        broadcast_event, checkpoint_undecided_state, decide_from_proposal, decide_from_proposal_2,
+        fetch_proposal, LeafChainTraversalOutcome,
     },
     quorum_vote::Versions,
 };
@@ -136,23 +136,44 @@ pub(crate) async fn handle_quorum_proposal_validated<
             consensus_writer.last_decided_view()
         );
 
-        // We don't need to hold this while we broadcast
+        // We don't need to hold this while we checkpoint or broadcast
         drop(consensus_writer);
 
+        if task_state.checkpoint_policy.checkpoint_on_decide() {
+            if let Err(e) =
+                checkpoint_undecided_state(&task_state.consensus, &task_state.storage).await
+            {
+                tracing::warn!("Failed to checkpoint undecided state on decide: {e:#}");
+            }
+        }
+
+        // This is never none if we've reached a new decide, so this is safe to unwrap.
+        let decide_qc = Arc::new(new_decide_qc.unwrap());
+
         // Send an update to everyone saying that we've reached a decide
         broadcast_event(
             Event {
                 view_number: decided_view_number,
                 event: EventType::Decide {
                     leaf_chain: Arc::new(leaf_views.clone()),
-                    // This is never none if we've reached a new decide, so this is safe to unwrap.
-                    qc: Arc::new(new_decide_qc.unwrap()),
+                    qc: Arc::clone(&decide_qc),
                     block_size: included_txns.map(|txns| txns.len().try_into().unwrap()),
                 },
             },
             &task_state.output_event_stream,
         )
         .await;
+        broadcast_event(
+            Event {
+                view_number: decided_view_number,
+                event: EventType::DecideSummary {
+                    leaf_chain: DecidedLeafSummary::newly_decided(&leaf_views, old_decided_view),
+                    qc: decide_qc,
+                },
+            },
+            &task_state.output_event_stream,
+        )
+        .await;
         tracing::debug!("Successfully sent decide event");
 
         // Start the DRB computation two epochs in advance, if the decided block is the last but
@@ -192,6 +213,8 @@ pub(crate) async fn handle_quorum_proposal_validated<
                 // Skip if we are not in the committee of the next epoch.
                 if task_state
                     .membership
+                    .read()
+                    .await
                     .has_stake(&task_state.public_key, current_epoch_number + 1)
                 {
                     let new_epoch_number = current_epoch_number + 2;
@@ -229,13 +252,14 @@ pub(crate) async fn update_shared_state<
     consensus: OuterConsensus<TYPES>,
     sender: Sender<Arc<HotShotEvent<TYPES>>>,
     receiver: InactiveReceiver<Arc<HotShotEvent<TYPES>>>,
-    quorum_membership: Arc<TYPES::Membership>,
+    quorum_membership: Arc<RwLock<TYPES::Membership>>,
     public_key: TYPES::SignatureKey,
     private_key: <TYPES::SignatureKey as SignatureKey>::PrivateKey,
     upgrade_lock: UpgradeLock<TYPES, V>,
     view_number: TYPES::View,
     instance_state: Arc<TYPES::InstanceState>,
     storage: Arc<RwLock<I::Storage>>,
+    checkpoint_policy: CheckpointPolicy,
     proposed_leaf: &Leaf2<TYPES>,
     vid_share: &Proposal<TYPES, VidDisperseShare<TYPES>>,
     parent_view_number: Option<TYPES::View>,
@@ -331,21 +355,14 @@ pub(crate) async fn update_shared_state<
         tracing::trace!("{e:?}");
     }
 
-    // Kick back our updated structures for downstream usage.
-    let new_leaves = consensus_writer.saved_leaves().clone();
-    let new_state = consensus_writer.validated_state_map().clone();
     drop(consensus_writer);
 
-    // Send the new state up to the sequencer.
-    storage
-        .write()
-        .await
-        .update_undecided_state2(new_leaves, new_state)
-        .await
-        .wrap()
-        .context(error!("Failed to update undecided state"))?;
+    if !checkpoint_policy.checkpoint_on_view(*view_number) {
+        return Ok(());
+    }
 
-    Ok(())
+    // Send the new state up to the sequencer.
+    checkpoint_undecided_state(&consensus, &storage).await
 }
 
 /// Submits the `QuorumVoteSend` event if all the dependencies are met.
@@ -353,7 +370,7 @@ pub(crate) async fn update_shared_state<
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn submit_vote<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions>(
     sender: Sender<Arc<HotShotEvent<TYPES>>>,
-    quorum_membership: Arc<TYPES::Membership>,
+    quorum_membership: Arc<RwLock<TYPES::Membership>>,
     public_key: TYPES::SignatureKey,
     private_key: <TYPES::SignatureKey as SignatureKey>::PrivateKey,
     upgrade_lock: UpgradeLock<TYPES, V>,
@@ -365,7 +382,10 @@ pub(crate) async fn submit_vote<TYPES: NodeType, I: NodeImplementation<TYPES>, V
     extended_vote: bool,
 ) -> Result<()> {
     ensure!(
-        quorum_membership.has_stake(&public_key, epoch_number),
+        quorum_membership
+            .read()
+            .await
+            .has_stake(&public_key, epoch_number),
         info!(
             "We were not chosen for quorum committee on {:?}",
             view_number
@@ -378,6 +398,7 @@ pub(crate) async fn submit_vote<TYPES: NodeType, I: NodeImplementation<TYPES>, V
             leaf_commit: leaf.commit(),
         },
         view_number,
+        epoch_number,
         &public_key,
         &private_key,
         &upgrade_lock,
@@ -26,7 +26,7 @@ use hotshot_types::{
         election::Membership,
         node_implementation::{ConsensusTime, NodeImplementation, NodeType, Versions},
         signature_key::SignatureKey,
-        storage::Storage,
+        storage::{CheckpointPolicy, Storage},
     },
     utils::epoch_from_block_number,
     vid::vid_scheme,
@@ -40,7 +40,7 @@ use vbs::version::StaticVersionType;
 
 use crate::{
     events::HotShotEvent,
-    helpers::broadcast_event,
+    helpers::{broadcast_event, check_undecided_backlog, VotingWarmup},
     quorum_vote::handlers::{handle_quorum_proposal_validated, submit_vote, update_shared_state},
 };
 
@@ -69,7 +69,7 @@ pub struct VoteDependencyHandle<TYPES: NodeType, I: NodeImplementation<TYPES>, V
     /// Immutable instance state
     pub instance_state: Arc<TYPES::InstanceState>,
     /// Membership for Quorum certs/votes.
-    pub quorum_membership: Arc<TYPES::Membership>,
+    pub quorum_membership: Arc<RwLock<TYPES::Membership>>,
     /// Reference to the storage.
     pub storage: Arc<RwLock<I::Storage>>,
     /// View number to vote on.
@@ -86,6 +86,12 @@ pub struct VoteDependencyHandle<TYPES: NodeType, I: NodeImplementation<TYPES>, V
     pub id: u64,
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+    /// How often the undecided state is checkpointed to storage
+    pub checkpoint_policy: CheckpointPolicy,
+    /// Whether we may vote in this view, or are still warming up
+    pub may_vote: bool,
+    /// Most undecided views before we stop voting
+    pub max_undecided_views: u64,
 }
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions> HandleDepOutput
@@ -96,6 +102,11 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions> Handl
     #[allow(clippy::too_many_lines)]
     #[instrument(skip_all, fields(id = self.id, view = *self.view_number))]
     async fn handle_dep_result(self, res: Self::Output) {
+        if let Some(fork_view) = self.consensus.read().await.safety_fork() {
+            tracing::error!("Halted after a safety fork in view {fork_view:?}, not voting");
+            return;
+        }
+
         let mut payload_commitment = None;
         let mut leaf = None;
         let mut vid_share = None;
@@ -201,6 +212,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions> Handl
             self.view_number,
             Arc::clone(&self.instance_state),
             Arc::clone(&self.storage),
+            self.checkpoint_policy,
             &leaf,
             &vid_share,
             parent_view_number,
@@ -227,6 +239,26 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + 'static, V: Versions> Handl
         )
         .await;
 
+        if !self.may_vote {
+            tracing::debug!(
+                "Still warming up, not voting in view {:?}",
+                self.view_number
+            );
+            return;
+        }
+
+        if let Err(e) = check_undecided_backlog(
+            self.view_number,
+            &self.consensus,
+            self.max_undecided_views,
+            &self.sender,
+        )
+        .await
+        {
+            tracing::warn!("Not voting; {e:#}");
+            return;
+        }
+
         if let Err(e) = submit_vote::<TYPES, I, V>(
             self.sender.clone(),
             Arc::clone(&self.quorum_membership),
@@ -273,7 +305,7 @@ pub struct QuorumVoteTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V:
     pub network: Arc<I::Network>,
 
     /// Membership for Quorum certs/votes and DA committee certs/votes.
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// Table for the in-progress DRB computation tasks.
     pub drb_computations: BTreeMap<TYPES::Epoch, JoinHandle<DrbResult>>,
@@ -295,6 +327,15 @@ pub struct QuorumVoteTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V:
 
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+
+    /// How often the undecided state is checkpointed to storage
+    pub checkpoint_policy: CheckpointPolicy,
+
+    /// Warm-up period before we vote, if we joined behind the network
+    pub voting_warmup: VotingWarmup<TYPES>,
+
+    /// Most undecided views before we stop voting
+    pub max_undecided_views: u64,
 }
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> QuorumVoteTaskState<TYPES, I, V> {
@@ -355,6 +396,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> QuorumVoteTaskS
         if self.vote_dependencies.contains_key(&view_number) {
             return;
         }
+        self.voting_warmup.observe(view_number);
 
         let mut quorum_proposal_dependency = self.create_event_dependency(
             VoteDependency::QuorumProposal,
@@ -392,6 +434,9 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> QuorumVoteTaskS
                 id: self.id,
                 epoch_height: self.epoch_height,
                 consensus_metrics: Arc::clone(&self.consensus_metrics),
+                checkpoint_policy: self.checkpoint_policy,
+                may_vote: self.voting_warmup.may_vote(view_number),
+                max_undecided_views: self.max_undecided_views,
             },
         );
         self.vote_dependencies
@@ -494,15 +539,12 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> QuorumVoteTaskS
 
                 let cur_epoch = self.consensus.read().await.cur_epoch();
                 // Validate the DAC.
-                ensure!(
-                    cert.is_valid_cert(
-                        self.membership.da_stake_table(cur_epoch),
-                        self.membership.da_success_threshold(cur_epoch),
-                        &self.upgrade_lock
-                    )
-                    .await,
-                    warn!("Invalid DAC")
-                );
+                let membership_reader = self.membership.read().await;
+                let cert_valid = cert
+                    .is_valid_cert(&membership_reader, cur_epoch, &self.upgrade_lock)
+                    .await;
+                drop(membership_reader);
+                ensure!(cert_valid, warn!("Invalid DAC"));
 
                 // Add to the storage.
                 self.consensus
@@ -537,27 +579,56 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> QuorumVoteTaskS
                 );
 
                 // ensure that the VID share was sent by a DA member OR the view leader
+                let membership_reader = self.membership.read().await;
                 ensure!(
-                    self.membership
+                    membership_reader
                         .da_committee_members(view, cur_epoch)
                         .contains(sender)
-                        || *sender == self.membership.leader(view, cur_epoch)?,
+                        || *sender == membership_reader.leader(view, cur_epoch)?,
                     "VID share was not sent by a DA member or the view leader."
                 );
+                let num_nodes = membership_reader.total_nodes(cur_epoch);
+                drop(membership_reader);
 
                 // NOTE: `verify_share` returns a nested `Result`, so we must check both the inner
                 // and outer results
-                match vid_scheme(self.membership.total_nodes(cur_epoch)).verify_share(
+                match vid_scheme(num_nodes).verify_share(
                     &disperse.data.share,
                     &disperse.data.common,
                     payload_commitment,
                 ) {
                     Ok(Err(())) | Err(_) => {
-                        bail!("Failed to verify VID share");
+                        bail!(warn!("Failed to verify VID share"));
                     }
                     Ok(Ok(())) => {}
                 }
 
+                // The share only proves it is consistent with its own payload commitment, so check
+                // that against the commitment this view has already certified or proposed, if any.
+                // Otherwise a malicious peer could poison the reconstruction set.
+                let expected_commitment = {
+                    let consensus_reader = self.consensus.read().await;
+                    consensus_reader
+                        .saved_da_certs()
+                        .get(&view)
+                        .map(|cert| cert.data.payload_commit)
+                        .or_else(|| {
+                            consensus_reader
+                                .last_proposals()
+                                .get(&view)
+                                .map(|proposal| proposal.data.block_header.payload_commitment())
+                        })
+                };
+                if let Some(expected_commitment) = expected_commitment {
+                    ensure!(
+                        *payload_commitment == expected_commitment,
+                        warn!(
+                            "VID share for view {:?} does not match the view's payload commitment",
+                            view
+                        )
+                    );
+                }
+
                 self.consensus
                     .write()
                     .await
@@ -668,6 +739,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> QuorumVoteTaskS
             proposal.data.view_number(),
             Arc::clone(&self.instance_state),
             Arc::clone(&self.storage),
+            self.checkpoint_policy,
             &proposed_leaf,
             &updated_vid,
             Some(parent_leaf.view_number()),
@@ -702,6 +774,13 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> QuorumVoteTaskS
             .read()
             .await
             .is_leaf_extended(proposed_leaf.commit());
+        if !self.voting_warmup.may_vote(proposal.data.view_number()) {
+            tracing::debug!(
+                "Still warming up, not voting in view {:?}",
+                proposal.data.view_number()
+            );
+            return;
+        }
         if let Err(e) = submit_vote::<TYPES, I, V>(
             event_sender.clone(),
             Arc::clone(&self.membership),
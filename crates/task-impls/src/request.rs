@@ -14,6 +14,7 @@ use std::{
 };
 
 use async_broadcast::{Receiver, Sender};
+use async_lock::RwLock;
 use async_trait::async_trait;
 use hotshot_task::{
     dependency::{Dependency, EventDependency},
@@ -60,7 +61,7 @@ pub struct NetworkRequestState<TYPES: NodeType, I: NodeImplementation<TYPES>> {
     /// Delay before requesting peers
     pub delay: Duration,
     /// Membership (Here containing only DA)
-    pub membership: TYPES::Membership,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
     /// This nodes public key
     pub public_key: TYPES::SignatureKey,
     /// This nodes private/signing key, used to sign requests.
@@ -108,7 +109,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> TaskState for NetworkRequest
                         .vid_shares()
                         .contains_key(&prop_view)
                 {
-                    self.spawn_requests(prop_view, cur_epoch, sender, receiver);
+                    self.spawn_requests(prop_view, cur_epoch, sender, receiver)
+                        .await;
                 }
                 Ok(())
             }
@@ -140,7 +142,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> TaskState for NetworkRequest
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>> NetworkRequestState<TYPES, I> {
     /// Creates and signs the payload, then will create a request task
-    fn spawn_requests(
+    async fn spawn_requests(
         &mut self,
         view: TYPES::View,
         epoch: TYPES::Epoch,
@@ -158,13 +160,16 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> NetworkRequestState<TYPES, I
                 receiver.clone(),
                 view,
                 epoch,
-            );
+            )
+            .await;
         }
     }
 
     /// Creates a task that will request a VID share from a DA member and wait for the `HotShotEvent::VidResponseRecv`event
     /// If we get the VID disperse share, broadcast `HotShotEvent::VidShareRecv` and terminate task
-    fn create_vid_request_task(
+    /// If no DA member sends it within `REQUEST_TIMEOUT` of being asked, broadcast
+    /// `HotShotEvent::VidRequestFailed` once every member was asked and terminate task
+    async fn create_vid_request_task(
         &mut self,
         request: RequestKind<TYPES>,
         signature: Signature<TYPES>,
@@ -180,17 +185,18 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> NetworkRequestState<TYPES, I
         let public_key = self.public_key.clone();
 
         // Get the committee members for the view and the leader, if applicable
-        let mut da_committee_for_view = self.membership.da_committee_members(view, epoch);
-        if let Ok(leader) = self.membership.leader(view, epoch) {
+        let membership_reader = self.membership.read().await;
+        let mut da_committee_for_view = membership_reader.da_committee_members(view, epoch);
+        if let Ok(leader) = membership_reader.leader(view, epoch) {
             da_committee_for_view.insert(leader);
         }
 
         // Get committee members for view
-        let mut recipients: Vec<TYPES::SignatureKey> = self
-            .membership
+        let mut recipients: Vec<TYPES::SignatureKey> = membership_reader
             .da_committee_members(view, epoch)
             .into_iter()
             .collect();
+        drop(membership_reader);
         // Randomize the recipients so all replicas don't overload the same 1 recipients
         // and so we don't implicitly rely on the same replica all the time.
         recipients.shuffle(&mut thread_rng());
@@ -240,11 +246,12 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> NetworkRequestState<TYPES, I
                         return;
                     }
                 } else {
-                    // This shouldnt be possible `recipients_it.next()` should clone original and start over if `None`
+                    // Each DA member is asked once, so the collection can't stall indefinitely
                     tracing::warn!(
                         "Sent VID request to all available DA members and got no response for view: {:?}",
                         view
                     );
+                    broadcast_event(Arc::new(HotShotEvent::VidRequestFailed(view)), &sender).await;
                     return;
                 }
             }
@@ -7,9 +7,10 @@
 use std::{sync::Arc, time::Duration};
 
 use async_broadcast::{Receiver, Sender};
+use async_lock::RwLock;
 use committable::Committable;
 use hotshot_types::{
-    consensus::{Consensus, LockedConsensusState, OuterConsensus},
+    consensus::{Consensus, LockedConsensusState, OuterConsensus, VidShareCache},
     data::VidDisperseShare,
     message::Proposal,
     traits::{
@@ -32,7 +33,7 @@ pub struct NetworkResponseState<TYPES: NodeType> {
     /// Locked consensus state
     consensus: LockedConsensusState<TYPES>,
     /// Quorum membership for checking if requesters have state
-    quorum: Arc<TYPES::Membership>,
+    quorum: Arc<RwLock<TYPES::Membership>>,
     /// This replicas public key
     pub_key: TYPES::SignatureKey,
     /// This replicas private key
@@ -45,7 +46,7 @@ impl<TYPES: NodeType> NetworkResponseState<TYPES> {
     /// Create the network request state with the info it needs
     pub fn new(
         consensus: LockedConsensusState<TYPES>,
-        quorum: Arc<TYPES::Membership>,
+        quorum: Arc<RwLock<TYPES::Membership>>,
         pub_key: TYPES::SignatureKey,
         private_key: <TYPES::SignatureKey as SignatureKey>::PrivateKey,
         id: u64,
@@ -65,6 +66,7 @@ impl<TYPES: NodeType> NetworkResponseState<TYPES> {
         mut receiver: Receiver<Arc<HotShotEvent<TYPES>>>,
         event_sender: Sender<Arc<HotShotEvent<TYPES>>>,
     ) {
+        let vid_share_cache = self.consensus.read().await.vid_share_cache();
         loop {
             match receiver.recv_direct().await {
                 Ok(event) => {
@@ -72,13 +74,15 @@ impl<TYPES: NodeType> NetworkResponseState<TYPES> {
                     match event.as_ref() {
                         HotShotEvent::VidRequestRecv(request, sender) => {
                             // Verify request is valid
-                            if !self.valid_sender(sender, self.consensus.read().await.cur_epoch())
+                            let cur_epoch = self.consensus.read().await.cur_epoch();
+                            if !self.valid_sender(sender, cur_epoch).await
                                 || !valid_signature::<TYPES>(request, sender)
                             {
                                 continue;
                             }
-                            if let Some(proposal) =
-                                self.get_or_calc_vid_share(request.view, sender).await
+                            if let Some(proposal) = self
+                                .get_or_calc_vid_share(&vid_share_cache, request.view, sender)
+                                .await
                             {
                                 broadcast_event(
                                     HotShotEvent::VidResponseSend(
@@ -136,18 +140,15 @@ impl<TYPES: NodeType> NetworkResponseState<TYPES> {
     #[instrument(skip_all, target = "NetworkResponseState", fields(id = self.id))]
     async fn get_or_calc_vid_share(
         &self,
+        vid_share_cache: &VidShareCache<TYPES>,
         view: TYPES::View,
         key: &TYPES::SignatureKey,
     ) -> Option<Proposal<TYPES, VidDisperseShare<TYPES>>> {
-        let consensus_reader = self.consensus.read().await;
-        if let Some(view) = consensus_reader.vid_shares().get(&view) {
-            if let Some(share) = view.get(key) {
-                return Some(share.clone());
-            }
+        if let Some(share) = vid_share_cache.get(&self.consensus, view, key).await {
+            return Some(share);
         }
 
-        let cur_epoch = consensus_reader.cur_epoch();
-        drop(consensus_reader);
+        let cur_epoch = self.consensus.read().await.cur_epoch();
 
         if Consensus::calculate_and_update_vid(
             OuterConsensus::new(Arc::clone(&self.consensus)),
@@ -170,19 +171,12 @@ impl<TYPES: NodeType> NetworkResponseState<TYPES> {
             )
             .await?;
         }
-        return self
-            .consensus
-            .read()
-            .await
-            .vid_shares()
-            .get(&view)?
-            .get(key)
-            .cloned();
+        return vid_share_cache.get(&self.consensus, view, key).await;
     }
 
     /// Makes sure the sender is allowed to send a request in the given epoch.
-    fn valid_sender(&self, sender: &TYPES::SignatureKey, epoch: TYPES::Epoch) -> bool {
-        self.quorum.has_stake(sender, epoch)
+    async fn valid_sender(&self, sender: &TYPES::SignatureKey, epoch: TYPES::Epoch) -> bool {
+        self.quorum.read().await.has_stake(sender, epoch)
     }
 }
 
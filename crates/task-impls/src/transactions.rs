@@ -10,18 +10,19 @@ use std::{
 };
 
 use async_broadcast::{Receiver, Sender};
+use async_lock::RwLock;
 use async_trait::async_trait;
 use futures::{future::join_all, stream::FuturesUnordered, StreamExt};
 use hotshot_builder_api::v0_1::block_info::AvailableBlockInfo;
 use hotshot_task::task::TaskState;
 use hotshot_types::{
-    consensus::OuterConsensus,
+    consensus::{EmptyBlockPolicy, OuterConsensus},
     data::{null_block, PackedBundle},
     event::{Event, EventType},
     message::UpgradeLock,
     traits::{
         auction_results_provider::AuctionResultsProvider,
-        block_contents::{precompute_vid_commitment, BuilderFee, EncodeBytes},
+        block_contents::{precompute_vid_commitment, BuilderFee, EncodeBytes, Transaction},
         election::Membership,
         node_implementation::{ConsensusTime, HasUrls, NodeImplementation, NodeType, Versions},
         signature_key::{BuilderSignatureKey, SignatureKey},
@@ -42,7 +43,7 @@ use crate::{
         v0_1::BuilderClient as BuilderClientBase, v0_99::BuilderClient as BuilderClientMarketplace,
     },
     events::{HotShotEvent, HotShotTaskCompleted},
-    helpers::broadcast_event,
+    helpers::{advance_epoch, broadcast_event},
 };
 
 // Parameters for builder querying algorithm
@@ -76,6 +77,66 @@ pub struct BuilderResponse<TYPES: NodeType> {
     pub precompute_data: Option<VidPrecomputeData>,
 }
 
+/// The transactions a [`ProposalSource`] provides for a block
+pub struct BlockContents<TYPES: NodeType> {
+    /// Transactions to include, in order
+    pub transactions: Vec<TYPES::Transaction>,
+}
+
+/// A source the leader pulls the transactions for its block from when it is time to propose,
+/// such as an external sequencer or a priority queue, in place of the builders
+#[async_trait]
+pub trait ProposalSource<TYPES: NodeType>: Send + Sync {
+    /// The transactions to propose in `view`, which should not exceed `max_bytes` in total
+    async fn next_block(&self, view: TYPES::View, max_bytes: u64) -> BlockContents<TYPES>;
+}
+
+/// How the leader chooses the transactions for its block when more are available than fit
+pub trait SelectionPolicy<TYPES: NodeType>: Send + Sync {
+    /// The transactions to include out of `candidates`, in block order, which must not exceed
+    /// `max_bytes` in total.
+    ///
+    /// `candidates` are in order of arrival, oldest first.
+    fn select(
+        &self,
+        candidates: &[TYPES::Transaction],
+        max_bytes: usize,
+    ) -> Vec<TYPES::Transaction>;
+}
+
+/// Selects transactions in order of arrival, stopping at the first one which doesn't fit
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FifoSelection;
+
+impl<TYPES: NodeType> SelectionPolicy<TYPES> for FifoSelection {
+    fn select(
+        &self,
+        candidates: &[TYPES::Transaction],
+        max_bytes: usize,
+    ) -> Vec<TYPES::Transaction> {
+        let max_bytes = u64::try_from(max_bytes).unwrap_or(u64::MAX);
+        let mut block_bytes = 0u64;
+        candidates
+            .iter()
+            .take_while(|transaction| {
+                block_bytes = block_bytes.saturating_add(transaction.minimum_block_size());
+                block_bytes <= max_bytes
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// What the leader does next about a view it has no transactions for
+enum EmptyBlockAction {
+    /// Propose an empty block
+    Propose,
+    /// Ask for transactions again
+    Retry,
+    /// Propose nothing
+    Skip,
+}
+
 /// Tracks state of a Transaction task
 pub struct TransactionTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> {
     /// The state's api
@@ -94,7 +155,7 @@ pub struct TransactionTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V
     pub consensus: OuterConsensus<TYPES>,
 
     /// Membership for the quorum
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// Builder 0.1 API clients
     pub builder_clients: Vec<BuilderClientBase<TYPES>>,
@@ -119,6 +180,21 @@ pub struct TransactionTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>, V
 
     /// fallback builder url
     pub fallback_builder_url: Url,
+
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
+
+    /// Target size in bytes of the transactions in a block we propose
+    pub target_block_bytes: u64,
+
+    /// Source to pull the transactions for our blocks from, if not the builders
+    pub proposal_source: Option<Arc<dyn ProposalSource<TYPES>>>,
+
+    /// What to do when there are no transactions to propose
+    pub empty_block_policy: EmptyBlockPolicy,
+
+    /// How to choose the transactions for our block when they don't all fit
+    pub selection_policy: Arc<dyn SelectionPolicy<TYPES>>,
 }
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTaskState<TYPES, I, V> {
@@ -136,7 +212,10 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
             }
         };
 
-        if version < V::Marketplace::VERSION {
+        if let Some(source) = self.proposal_source.clone() {
+            self.propose_from_source(source.as_ref(), event_stream, block_view, version)
+                .await
+        } else if version < V::Marketplace::VERSION {
             self.handle_view_change_legacy(event_stream, block_view)
                 .await
         } else {
@@ -145,6 +224,144 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
         }
     }
 
+    /// Propose the block `source` provides for `block_view`, carrying the null builder fee since
+    /// no builder was involved
+    async fn propose_from_source(
+        &mut self,
+        source: &dyn ProposalSource<TYPES>,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+        block_view: TYPES::View,
+        version: Version,
+    ) -> Option<HotShotTaskCompleted> {
+        let mut contents = source.next_block(block_view, self.target_block_bytes).await;
+        if contents.transactions.is_empty() {
+            match self.empty_block_action(block_view).await {
+                EmptyBlockAction::Propose => {}
+                EmptyBlockAction::Retry => {
+                    contents = source.next_block(block_view, self.target_block_bytes).await;
+                }
+                EmptyBlockAction::Skip => return None,
+            }
+        }
+
+        let BlockContents { transactions } = contents;
+        let validated_state = self.consensus.read().await.decided_state();
+        let (block_payload, metadata) = match TYPES::BlockPayload::from_transactions(
+            self.select_to_target(&transactions),
+            &validated_state,
+            &Arc::clone(&self.instance_state),
+        )
+        .await
+        {
+            Ok(block) => block,
+            Err(e) => {
+                tracing::error!("Failed to build a block from the proposal source: {e}");
+                return None;
+            }
+        };
+
+        let Some(fee) = null_block::builder_fee::<TYPES, V>(
+            self.membership.read().await.total_nodes(self.cur_epoch),
+            version,
+            *block_view,
+        ) else {
+            tracing::error!("Failed to calculate null block fee.");
+            return None;
+        };
+        let auction_result =
+            (version >= V::Marketplace::VERSION).then(TYPES::AuctionResult::default);
+
+        broadcast_event(
+            Arc::new(HotShotEvent::BlockRecv(PackedBundle::new(
+                block_payload.encode(),
+                metadata,
+                block_view,
+                vec1::vec1![fee],
+                None,
+                auction_result,
+            ))),
+            event_stream,
+        )
+        .await;
+
+        None
+    }
+
+    /// What to do about `block_view`, for which there are no transactions, under the empty block
+    /// policy.
+    ///
+    /// A view is only skipped when every certified block is already decided. Otherwise a proposal
+    /// is needed to extend the chain far enough to decide them, so an empty block is proposed to
+    /// keep consensus live.
+    async fn empty_block_action(&self, block_view: TYPES::View) -> EmptyBlockAction {
+        match self.empty_block_policy {
+            EmptyBlockPolicy::ProposeEmpty => EmptyBlockAction::Propose,
+            EmptyBlockPolicy::ProposeAfterDelay(delay) => {
+                tracing::debug!("No transactions for view {block_view:?}, waiting {delay:?}");
+                sleep(delay).await;
+                EmptyBlockAction::Retry
+            }
+            EmptyBlockPolicy::SkipView => {
+                let consensus = self.consensus.read().await;
+                if consensus.high_qc().view_number > consensus.last_decided_view() {
+                    tracing::info!(
+                        "Proposing an empty block for view {block_view:?} to decide earlier blocks"
+                    );
+                    EmptyBlockAction::Propose
+                } else {
+                    tracing::info!("No transactions for view {block_view:?}, skipping the view");
+                    EmptyBlockAction::Skip
+                }
+            }
+        }
+    }
+
+    /// Whether proposing `block` would include any transactions
+    fn has_transactions(block: &BuilderResponse<TYPES>) -> bool {
+        block.block_payload.num_transactions(&block.metadata) > 0
+    }
+
+    /// Whether `bundle` carries any transactions
+    fn bundle_has_transactions(bundle: &PackedBundle<TYPES>) -> bool {
+        TYPES::BlockPayload::from_bytes(&bundle.encoded_transactions, &bundle.metadata)
+            .num_transactions(&bundle.metadata)
+            > 0
+    }
+
+    /// Request a block for `block_view` from the builders, unless we are between versions
+    async fn request_block(&self, block_view: TYPES::View) -> Option<BuilderResponse<TYPES>> {
+        if self
+            .upgrade_lock
+            .decided_upgrade_certificate
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|cert| cert.upgrading_in(block_view))
+        {
+            None
+        } else {
+            self.wait_for_block(block_view).await
+        }
+    }
+
+    /// Let the selection policy choose the `transactions` which fit in `target_block_bytes`.
+    ///
+    /// This happens before the block, and so its commitment, is built. Transactions which are
+    /// not chosen are not kept here: they remain with the builders which offered them, to be
+    /// proposed by a later leader.
+    fn select_to_target(&self, transactions: &[TYPES::Transaction]) -> Vec<TYPES::Transaction> {
+        let max_bytes = usize::try_from(self.target_block_bytes).unwrap_or(usize::MAX);
+        let block_transactions = self.selection_policy.select(transactions, max_bytes);
+        if block_transactions.len() < transactions.len() {
+            tracing::debug!(
+                "Leaving {} transactions over the target block size to a later view",
+                transactions.len() - block_transactions.len()
+            );
+        }
+
+        block_transactions
+    }
+
     /// legacy view change handler
     #[instrument(skip_all, fields(id = self.id, view = *self.cur_view), name = "Transaction task", level = "error", target = "TransactionTaskState")]
     pub async fn handle_view_change_legacy(
@@ -160,21 +377,14 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
             }
         };
 
-        // Request a block from the builder unless we are between versions.
-        let block = {
-            if self
-                .upgrade_lock
-                .decided_upgrade_certificate
-                .read()
-                .await
-                .as_ref()
-                .is_some_and(|cert| cert.upgrading_in(block_view))
-            {
-                None
-            } else {
-                self.wait_for_block(block_view).await
+        let mut block = self.request_block(block_view).await;
+        if !block.as_ref().is_some_and(Self::has_transactions) {
+            match self.empty_block_action(block_view).await {
+                EmptyBlockAction::Propose => {}
+                EmptyBlockAction::Retry => block = self.request_block(block_view).await.or(block),
+                EmptyBlockAction::Skip => return None,
             }
-        };
+        }
 
         if let Some(BuilderResponse {
             block_payload,
@@ -210,12 +420,10 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
                 .number_of_empty_blocks_proposed
                 .add(1);
 
-            let membership_total_nodes = self.membership.total_nodes(self.cur_epoch);
-            let Some(null_fee) = null_block::builder_fee::<TYPES, V>(
-                self.membership.total_nodes(self.cur_epoch),
-                version,
-                *block_view,
-            ) else {
+            let membership_total_nodes = self.membership.read().await.total_nodes(self.cur_epoch);
+            let Some(null_fee) =
+                null_block::builder_fee::<TYPES, V>(membership_total_nodes, version, *block_view)
+            else {
                 tracing::error!("Failed to get null fee");
                 return None;
             };
@@ -331,7 +539,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
             .wrap()
             .context(warn!("Failed to receive a bundle from any builder."))?;
         let (block_payload, metadata) = TYPES::BlockPayload::from_transactions(
-            transactions,
+            self.select_to_target(&transactions),
             &validated_state,
             &Arc::clone(&self.instance_state),
         )
@@ -350,17 +558,15 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
     }
 
     /// Produce a null block
-    pub fn null_block(
+    pub async fn null_block(
         &self,
         block_view: TYPES::View,
         version: Version,
     ) -> Option<PackedBundle<TYPES>> {
-        let membership_total_nodes = self.membership.total_nodes(self.cur_epoch);
-        let Some(null_fee) = null_block::builder_fee::<TYPES, V>(
-            self.membership.total_nodes(self.cur_epoch),
-            version,
-            *block_view,
-        ) else {
+        let membership_total_nodes = self.membership.read().await.total_nodes(self.cur_epoch);
+        let Some(null_fee) =
+            null_block::builder_fee::<TYPES, V>(membership_total_nodes, version, *block_view)
+        else {
             tracing::error!("Failed to calculate null block fee.");
             return None;
         };
@@ -397,10 +603,23 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
             }
         };
 
-        let packed_bundle = match self
+        let mut block = self
             .produce_block_marketplace(block_view, task_start_time)
-            .await
-        {
+            .await;
+        if !block.as_ref().is_ok_and(Self::bundle_has_transactions) {
+            match self.empty_block_action(block_view).await {
+                EmptyBlockAction::Propose => {}
+                EmptyBlockAction::Retry => {
+                    block = self
+                        .produce_block_marketplace(block_view, Instant::now())
+                        .await
+                        .or(block);
+                }
+                EmptyBlockAction::Skip => return None,
+            }
+        }
+
+        let packed_bundle = match block {
             Ok(b) => b,
             Err(e) => {
                 tracing::info!(
@@ -409,7 +628,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
                     e
                 );
 
-                let null_block = self.null_block(block_view, version)?;
+                let null_block = self.null_block(block_view, version).await?;
 
                 // Increment the metric for number of empty blocks proposed
                 self.consensus
@@ -469,9 +688,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
                 .await;
             }
             HotShotEvent::ViewChange(view, epoch) => {
-                if *epoch > self.cur_epoch {
-                    self.cur_epoch = *epoch;
-                }
+                advance_epoch::<TYPES>(&mut self.cur_epoch, *epoch, self.max_epoch_skip);
                 let view = TYPES::View::new(std::cmp::max(1, **view));
                 ensure!(
                     *view > *self.cur_view,
@@ -480,7 +697,8 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
                     )
                 );
                 self.cur_view = view;
-                if self.membership.leader(view, self.cur_epoch)? == self.public_key {
+                let leader = self.membership.read().await.leader(view, self.cur_epoch)?;
+                if leader == self.public_key {
                     self.handle_view_change(&event_stream, view).await;
                     return Ok(());
                 }
@@ -716,6 +934,18 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
         };
 
         for (block_info, builder_idx) in available_blocks {
+            // The builder commitment and fee signature are bound to the block as offered, so a
+            // block over the target size can't be trimmed down to it, only passed over. Its
+            // transactions stay with the builder for a later view.
+            if block_info.block_size > self.target_block_bytes {
+                tracing::debug!(
+                    "Skipping available block of {} bytes, over the target of {} bytes",
+                    block_info.block_size,
+                    self.target_block_bytes
+                );
+                continue;
+            }
+
             // Verify signature over chosen block.
             if !block_info.sender.validate_block_info_signature(
                 &block_info.signature,
@@ -738,6 +968,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
                 }
             };
 
+            let num_nodes = self.membership.read().await.total_nodes(self.cur_epoch);
             let response = {
                 let client = &self.builder_clients[builder_idx];
 
@@ -745,7 +976,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> TransactionTask
                 // builder for VID computation.
                 let (block, header_input) = if version >= V::Epochs::VERSION {
                     futures::join! {
-                        client.claim_block_with_num_nodes(block_info.block_hash.clone(), view_number.u64(), self.public_key.clone(), &request_signature, self.membership.total_nodes(self.cur_epoch)) ,
+                        client.claim_block_with_num_nodes(block_info.block_hash.clone(), view_number.u64(), self.public_key.clone(), &request_signature, num_nodes) ,
                         client.claim_block_header_input(block_info.block_hash.clone(), view_number.u64(), self.public_key.clone(), &request_signature)
                     }
                 } else {
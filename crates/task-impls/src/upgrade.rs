@@ -4,9 +4,14 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::{marker::PhantomData, sync::Arc, time::SystemTime};
+use std::{
+    marker::PhantomData,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use async_broadcast::{Receiver, Sender};
+use async_lock::RwLock;
 use async_trait::async_trait;
 use committable::Committable;
 use hotshot_task::task::TaskState;
@@ -26,6 +31,7 @@ use hotshot_types::{
         signature_key::SignatureKey,
     },
     vote::HasViewNumber,
+    vote_metrics::VoteMetrics,
 };
 use tracing::instrument;
 use utils::anytrace::*;
@@ -33,7 +39,7 @@ use vbs::version::StaticVersionType;
 
 use crate::{
     events::HotShotEvent,
-    helpers::broadcast_event,
+    helpers::{advance_epoch, broadcast_event},
     vote_collection::{handle_vote, VoteCollectorsMap},
 };
 
@@ -48,8 +54,11 @@ pub struct UpgradeTaskState<TYPES: NodeType, V: Versions> {
     /// Epoch number this node is executing in.
     pub cur_epoch: TYPES::Epoch,
 
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
+
     /// Membership for Quorum Certs/votes
-    pub quorum_membership: Arc<TYPES::Membership>,
+    pub quorum_membership: Arc<RwLock<TYPES::Membership>>,
 
     /// A map of `UpgradeVote` collector tasks
     pub vote_collectors: VoteCollectorsMap<TYPES, UpgradeVote<TYPES>, UpgradeCertificate<TYPES>, V>,
@@ -89,6 +98,12 @@ pub struct UpgradeTaskState<TYPES: NodeType, V: Versions> {
 
     /// Lock for a decided upgrade
     pub upgrade_lock: UpgradeLock<TYPES, V>,
+
+    /// How long votes are still recorded for participation after their certificate formed
+    pub late_vote_grace: Duration,
+
+    /// Metrics of the votes this node accumulates
+    pub vote_metrics: Arc<VoteMetrics>,
 }
 
 impl<TYPES: NodeType, V: Versions> UpgradeTaskState<TYPES, V> {
@@ -179,13 +194,26 @@ impl<TYPES: NodeType, V: Versions> UpgradeTaskState<TYPES, V> {
                 );
 
                 // We then validate that the proposal was issued by the leader for the view.
-                let view_leader_key = self.quorum_membership.leader(view, self.cur_epoch)?;
+                let membership_reader = self.quorum_membership.read().await;
+                let view_leader_key = membership_reader.leader(view, self.cur_epoch)?;
                 ensure!(
                     view_leader_key == *sender,
                     info!(
                         "Upgrade proposal doesn't have expected leader key for view {} \n Upgrade proposal is: {:?}", *view, proposal.data.clone()
                     )
                 );
+                ensure!(
+                    membership_reader.verify_leader_proof(
+                        view,
+                        self.cur_epoch,
+                        &view_leader_key,
+                        &proposal.leader_proof
+                    ),
+                    warn!(
+                        "Upgrade proposal for view {view:?} does not prove its leader was selected"
+                    )
+                );
+                drop(membership_reader);
 
                 // At this point, we've checked that:
                 //   * the proposal was expected,
@@ -207,6 +235,7 @@ impl<TYPES: NodeType, V: Versions> UpgradeTaskState<TYPES, V> {
                 let vote = UpgradeVote::create_signed_vote(
                     proposal.data.upgrade_proposal.clone(),
                     view,
+                    self.cur_epoch,
                     &self.public_key,
                     &self.private_key,
                     &self.upgrade_lock,
@@ -222,13 +251,13 @@ impl<TYPES: NodeType, V: Versions> UpgradeTaskState<TYPES, V> {
                 // Check if we are the leader.
                 {
                     let view = vote.view_number();
+                    let membership_reader = self.quorum_membership.read().await;
                     ensure!(
-                        self.quorum_membership.leader(view, self.cur_epoch)? == self.public_key,
+                        membership_reader.leader(view, self.cur_epoch)? == self.public_key,
                         debug!(
                             "We are not the leader for view {} are we leader for next view? {}",
                             *view,
-                            self.quorum_membership.leader(view + 1, self.cur_epoch)?
-                                == self.public_key
+                            membership_reader.leader(view + 1, self.cur_epoch)? == self.public_key
                         )
                     );
                 }
@@ -244,13 +273,13 @@ impl<TYPES: NodeType, V: Versions> UpgradeTaskState<TYPES, V> {
                     &tx,
                     &self.upgrade_lock,
                     true,
+                    &self.vote_metrics,
+                    self.late_vote_grace,
                 )
                 .await?;
             }
             HotShotEvent::ViewChange(new_view, epoch_number) => {
-                if *epoch_number > self.cur_epoch {
-                    self.cur_epoch = *epoch_number;
-                }
+                advance_epoch::<TYPES>(&mut self.cur_epoch, *epoch_number, self.max_epoch_skip);
                 ensure!(self.cur_view < *new_view || *self.cur_view == 0);
 
                 self.cur_view = *new_view;
@@ -270,7 +299,7 @@ impl<TYPES: NodeType, V: Versions> UpgradeTaskState<TYPES, V> {
                     && time >= self.start_proposing_time
                     && time < self.stop_proposing_time
                     && !self.upgraded().await
-                    && self.quorum_membership.leader(
+                    && self.quorum_membership.read().await.leader(
                         TYPES::View::new(view + UPGRADE_PROPOSE_OFFSET),
                         self.cur_epoch,
                     )? == self.public_key
@@ -297,9 +326,16 @@ impl<TYPES: NodeType, V: Versions> UpgradeTaskState<TYPES, V> {
 
                     tracing::warn!("Sending upgrade proposal:\n\n {:?}", upgrade_proposal);
 
+                    let leader_proof = self.quorum_membership.read().await.leader_proof(
+                        upgrade_proposal.view_number,
+                        self.cur_epoch,
+                        &self.private_key,
+                    )?;
+
                     let message = Proposal {
                         data: upgrade_proposal,
                         signature,
+                        leader_proof,
                         _pd: PhantomData,
                     };
 
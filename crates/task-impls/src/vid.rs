@@ -7,6 +7,7 @@
 use std::{marker::PhantomData, sync::Arc};
 
 use async_broadcast::{Receiver, Sender};
+use async_lock::RwLock;
 use async_trait::async_trait;
 use hotshot_task::task::TaskState;
 use hotshot_types::{
@@ -14,6 +15,7 @@ use hotshot_types::{
     data::{PackedBundle, VidDisperse, VidDisperseShare},
     message::Proposal,
     traits::{
+        election::Membership,
         node_implementation::{NodeImplementation, NodeType},
         signature_key::SignatureKey,
         BlockPayload,
@@ -24,7 +26,7 @@ use utils::anytrace::Result;
 
 use crate::{
     events::{HotShotEvent, HotShotTaskCompleted},
-    helpers::broadcast_event,
+    helpers::{advance_epoch, broadcast_event},
 };
 
 /// Tracks state of a VID task
@@ -42,7 +44,7 @@ pub struct VidTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>> {
     pub network: Arc<I::Network>,
 
     /// Membership for the quorum
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// This Nodes Public Key
     pub public_key: TYPES::SignatureKey,
@@ -52,6 +54,9 @@ pub struct VidTaskState<TYPES: NodeType, I: NodeImplementation<TYPES>> {
 
     /// This state's ID
     pub id: u64,
+
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
 }
 
 impl<TYPES: NodeType, I: NodeImplementation<TYPES>> VidTaskState<TYPES, I> {
@@ -78,7 +83,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> VidTaskState<TYPES, I> {
                 let builder_commitment = payload.builder_commitment(metadata);
                 let vid_disperse = VidDisperse::calculate_vid_disperse(
                     Arc::clone(encoded_transactions),
-                    &Arc::clone(&self.membership),
+                    &self.membership,
                     *view_number,
                     self.cur_epoch,
                     vid_precompute.clone(),
@@ -116,12 +121,21 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> VidTaskState<TYPES, I> {
                     error!("VID: failed to sign dispersal payload");
                     return None;
                 };
+                let Ok(leader_proof) = self.membership.read().await.leader_proof(
+                    view_number,
+                    self.cur_epoch,
+                    &self.private_key,
+                ) else {
+                    error!("VID: failed to prove we lead view {}", *view_number);
+                    return None;
+                };
                 debug!("publishing VID disperse for view {}", *view_number);
                 broadcast_event(
                     Arc::new(HotShotEvent::VidDisperseSend(
                         Proposal {
                             signature,
                             data: vid_disperse.clone(),
+                            leader_proof,
                             _pd: PhantomData,
                         },
                         self.public_key.clone(),
@@ -132,6 +146,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> VidTaskState<TYPES, I> {
             }
 
             HotShotEvent::ViewChange(view, epoch) => {
+                advance_epoch::<TYPES>(&mut self.cur_epoch, *epoch, self.max_epoch_skip);
                 let view = *view;
                 if (*view != 0 || *self.cur_view > 0) && *self.cur_view >= *view {
                     return None;
@@ -141,9 +156,6 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>> VidTaskState<TYPES, I> {
                     info!("View changed by more than 1 going to view {:?}", view);
                 }
                 self.cur_view = view;
-                if *epoch > self.cur_epoch {
-                    self.cur_epoch = *epoch;
-                }
 
                 return None;
             }
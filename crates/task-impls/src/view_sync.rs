@@ -30,6 +30,7 @@ use hotshot_types::{
         signature_key::SignatureKey,
     },
     vote::{Certificate, HasViewNumber, Vote},
+    vote_metrics::VoteMetrics,
 };
 use tokio::{spawn, task::JoinHandle, time::sleep};
 use tracing::instrument;
@@ -37,9 +38,10 @@ use utils::anytrace::*;
 
 use crate::{
     events::{HotShotEvent, HotShotTaskCompleted},
-    helpers::broadcast_event,
+    helpers::{advance_epoch, broadcast_event},
     vote_collection::{
-        create_vote_accumulator, AccumulatorInfo, HandleVoteEvent, VoteCollectionTaskState,
+        create_vote_accumulator, AccumulatorInfo, AggregatableVote, HandleVoteEvent,
+        VoteCollectionTaskState,
     },
 };
 #[derive(PartialEq, PartialOrd, Clone, Debug, Eq, Hash)]
@@ -73,7 +75,7 @@ pub struct ViewSyncTaskState<TYPES: NodeType, V: Versions> {
     pub cur_epoch: TYPES::Epoch,
 
     /// Membership for the quorum
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// This Nodes Public Key
     pub public_key: TYPES::SignatureKey,
@@ -112,6 +114,15 @@ pub struct ViewSyncTaskState<TYPES: NodeType, V: Versions> {
 
     /// Lock for a decided upgrade
     pub upgrade_lock: UpgradeLock<TYPES, V>,
+
+    /// How long votes are still recorded for participation after their certificate formed
+    pub late_vote_grace: Duration,
+
+    /// Metrics of the votes this node accumulates
+    pub vote_metrics: Arc<VoteMetrics>,
+
+    /// Maximum number of epochs a view change may advance by at once
+    pub max_epoch_skip: u64,
 }
 
 #[async_trait]
@@ -160,7 +171,7 @@ pub struct ViewSyncReplicaTaskState<TYPES: NodeType, V: Versions> {
     pub id: u64,
 
     /// Membership for the quorum
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// This Nodes Public Key
     pub public_key: TYPES::SignatureKey,
@@ -254,6 +265,83 @@ impl<TYPES: NodeType, V: Versions> ViewSyncTaskState<TYPES, V> {
         task_map.insert(view, replica_state);
     }
 
+    /// Pass a view sync vote to the accumulator for its view and relay in `relay_map`, starting
+    /// one if we are the leader for that relay.
+    ///
+    /// Each phase has its own relay map, and so its own accumulators, which means a certificate
+    /// is only ever assembled from votes of the matching phase.
+    async fn accumulate_view_sync_vote<VOTE, CERT>(
+        &self,
+        relay_map: &RwLock<RelayMap<TYPES, VOTE, CERT, V>>,
+        vote: &VOTE,
+        relay: u64,
+        phase: ViewSyncPhase,
+        event: Arc<HotShotEvent<TYPES>>,
+        event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
+    ) -> Result<()>
+    where
+        VOTE: Vote<TYPES> + AggregatableVote<TYPES, VOTE, CERT> + Send + Sync + 'static,
+        CERT: Certificate<TYPES, VOTE::Commitment, Voteable = VOTE::Commitment>
+            + Debug
+            + Send
+            + Sync
+            + 'static,
+        VoteCollectionTaskState<TYPES, VOTE, CERT, V>: HandleVoteEvent<TYPES, VOTE, CERT>,
+    {
+        let mut map = relay_map.write().await;
+        let vote_view = vote.view_number();
+        let phase_map = map.entry(vote_view).or_insert(BTreeMap::new());
+        if let Some(relay_task) = phase_map.get_mut(&relay) {
+            if relay_task.late_vote_grace_elapsed() {
+                map.remove(&vote_view);
+
+                return Ok(());
+            }
+
+            tracing::debug!("Forwarding {phase:?} vote for relay {relay}");
+
+            // Handle the vote and check if the accumulator has returned successfully, keeping
+            // the collector while it records late votes
+            if relay_task
+                .handle_vote_event(event, event_stream)
+                .await?
+                .is_some()
+                && self.late_vote_grace.is_zero()
+            {
+                map.remove(&vote_view);
+            }
+
+            return Ok(());
+        }
+
+        // We do not have a relay task already running, so start one
+        let leader = self
+            .membership
+            .read()
+            .await
+            .leader(vote_view + relay, self.cur_epoch)?;
+        ensure!(
+            leader == self.public_key,
+            debug!("View sync {phase:?} vote sent to wrong leader")
+        );
+
+        let info = AccumulatorInfo {
+            public_key: self.public_key.clone(),
+            membership: Arc::clone(&self.membership),
+            view: vote_view,
+            epoch: self.cur_epoch,
+            id: self.id,
+            metrics: Arc::clone(&self.vote_metrics),
+            late_vote_grace: self.late_vote_grace,
+        };
+        let vote_collector =
+            create_vote_accumulator(&info, event, event_stream, self.upgrade_lock.clone(), true)
+                .await?;
+        phase_map.insert(relay, vote_collector);
+
+        Ok(())
+    }
+
     #[instrument(skip_all, fields(id = self.id, view = *self.cur_view, epoch = *self.cur_epoch), name = "View Sync Main Task", level = "error")]
     #[allow(clippy::type_complexity)]
     /// Handles incoming events for the main view sync task
@@ -288,146 +376,44 @@ impl<TYPES: NodeType, V: Versions> ViewSyncTaskState<TYPES, V> {
                     .await;
             }
 
-            HotShotEvent::ViewSyncPreCommitVoteRecv(ref vote) => {
-                let mut map = self.pre_commit_relay_map.write().await;
-                let vote_view = vote.view_number();
-                let relay = vote.date().relay;
-                let relay_map = map.entry(vote_view).or_insert(BTreeMap::new());
-                if let Some(relay_task) = relay_map.get_mut(&relay) {
-                    tracing::debug!("Forwarding message");
-
-                    // Handle the vote and check if the accumulator has returned successfully
-                    if relay_task
-                        .handle_vote_event(Arc::clone(&event), &event_stream)
-                        .await?
-                        .is_some()
-                    {
-                        map.remove(&vote_view);
-                    }
-
-                    return Ok(());
-                }
-
-                // We do not have a relay task already running, so start one
-                ensure!(
-                    self.membership.leader(vote_view + relay, self.cur_epoch)? == self.public_key,
-                    "View sync vote sent to wrong leader"
-                );
-
-                let info = AccumulatorInfo {
-                    public_key: self.public_key.clone(),
-                    membership: Arc::clone(&self.membership),
-                    view: vote_view,
-                    epoch: self.cur_epoch,
-                    id: self.id,
-                };
-                let vote_collector = create_vote_accumulator(
-                    &info,
-                    event,
+            HotShotEvent::ViewSyncPreCommitVoteRecv(vote) => {
+                self.accumulate_view_sync_vote(
+                    &self.pre_commit_relay_map,
+                    vote,
+                    vote.date().relay,
+                    ViewSyncPhase::PreCommit,
+                    Arc::clone(&event),
                     &event_stream,
-                    self.upgrade_lock.clone(),
-                    true,
                 )
                 .await?;
-
-                relay_map.insert(relay, vote_collector);
             }
 
-            HotShotEvent::ViewSyncCommitVoteRecv(ref vote) => {
-                let mut map = self.commit_relay_map.write().await;
-                let vote_view = vote.view_number();
-                let relay = vote.date().relay;
-                let relay_map = map.entry(vote_view).or_insert(BTreeMap::new());
-                if let Some(relay_task) = relay_map.get_mut(&relay) {
-                    tracing::debug!("Forwarding message");
-
-                    // Handle the vote and check if the accumulator has returned successfully
-                    if relay_task
-                        .handle_vote_event(Arc::clone(&event), &event_stream)
-                        .await?
-                        .is_some()
-                    {
-                        map.remove(&vote_view);
-                    }
-
-                    return Ok(());
-                }
-
-                // We do not have a relay task already running, so start one
-                ensure!(
-                    self.membership.leader(vote_view + relay, self.cur_epoch)? == self.public_key,
-                    debug!("View sync vote sent to wrong leader")
-                );
-
-                let info = AccumulatorInfo {
-                    public_key: self.public_key.clone(),
-                    membership: Arc::clone(&self.membership),
-                    view: vote_view,
-                    epoch: self.cur_epoch,
-                    id: self.id,
-                };
-
-                let vote_collector = create_vote_accumulator(
-                    &info,
-                    event,
+            HotShotEvent::ViewSyncCommitVoteRecv(vote) => {
+                self.accumulate_view_sync_vote(
+                    &self.commit_relay_map,
+                    vote,
+                    vote.date().relay,
+                    ViewSyncPhase::Commit,
+                    Arc::clone(&event),
                     &event_stream,
-                    self.upgrade_lock.clone(),
-                    true,
                 )
                 .await?;
-                relay_map.insert(relay, vote_collector);
             }
 
             HotShotEvent::ViewSyncFinalizeVoteRecv(vote) => {
-                let mut map = self.finalize_relay_map.write().await;
-                let vote_view = vote.view_number();
-                let relay = vote.date().relay;
-                let relay_map = map.entry(vote_view).or_insert(BTreeMap::new());
-                if let Some(relay_task) = relay_map.get_mut(&relay) {
-                    tracing::debug!("Forwarding message");
-
-                    // Handle the vote and check if the accumulator has returned successfully
-                    if relay_task
-                        .handle_vote_event(Arc::clone(&event), &event_stream)
-                        .await?
-                        .is_some()
-                    {
-                        map.remove(&vote_view);
-                    }
-
-                    return Ok(());
-                }
-
-                // We do not have a relay task already running, so start one
-                ensure!(
-                    self.membership.leader(vote_view + relay, self.cur_epoch)? == self.public_key,
-                    debug!("View sync vote sent to wrong leader")
-                );
-
-                let info = AccumulatorInfo {
-                    public_key: self.public_key.clone(),
-                    membership: Arc::clone(&self.membership),
-                    view: vote_view,
-                    epoch: self.cur_epoch,
-                    id: self.id,
-                };
-                let vote_collector = create_vote_accumulator(
-                    &info,
-                    event,
+                self.accumulate_view_sync_vote(
+                    &self.finalize_relay_map,
+                    vote,
+                    vote.date().relay,
+                    ViewSyncPhase::Finalize,
+                    Arc::clone(&event),
                     &event_stream,
-                    self.upgrade_lock.clone(),
-                    true,
                 )
-                .await;
-                if let Ok(vote_task) = vote_collector {
-                    relay_map.insert(relay, vote_task);
-                }
+                .await?;
             }
 
             &HotShotEvent::ViewChange(new_view, epoch) => {
-                if epoch > self.cur_epoch {
-                    self.cur_epoch = epoch;
-                }
+                advance_epoch::<TYPES>(&mut self.cur_epoch, epoch, self.max_epoch_skip);
                 let new_view = TYPES::View::new(*new_view);
                 if self.cur_view < new_view {
                     tracing::debug!(
@@ -473,7 +459,11 @@ impl<TYPES: NodeType, V: Versions> ViewSyncTaskState<TYPES, V> {
                 );
 
                 self.num_timeouts_tracked += 1;
-                let leader = self.membership.leader(view_number, self.cur_epoch)?;
+                let leader = self
+                    .membership
+                    .read()
+                    .await
+                    .leader(view_number, self.cur_epoch)?;
                 tracing::warn!(
                     %leader,
                     leader_mnemonic = hotshot_types::utils::mnemonic(&leader),
@@ -513,6 +503,60 @@ impl<TYPES: NodeType, V: Versions> ViewSyncTaskState<TYPES, V> {
 }
 
 impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
+    /// The first relay from `relay` on which is not led by the leader of the view which failed,
+    /// i.e. the view before `next_view`.
+    ///
+    /// A relay led by the node which stalled the view would likely stall view sync as well.
+    /// Returns `relay` if skipping is disabled (see [`Membership::skip_failed_leader_relays`]), or
+    /// if every node would be skipped.
+    pub async fn relay_avoiding_failed_leader(&self, relay: u64) -> u64 {
+        let membership_reader = self.membership.read().await;
+        if !membership_reader.skip_failed_leader_relays() {
+            return relay;
+        }
+        let failed_view = TYPES::View::new(self.next_view.saturating_sub(1));
+        let Ok(failed_leader) = membership_reader.leader(failed_view, self.cur_epoch) else {
+            return relay;
+        };
+
+        let total_nodes = membership_reader.total_nodes(self.cur_epoch) as u64;
+        (relay..relay.saturating_add(total_nodes))
+            .find(|candidate| {
+                membership_reader
+                    .leader(self.next_view + *candidate, self.cur_epoch)
+                    .is_ok_and(|leader| leader != failed_leader)
+            })
+            .unwrap_or(relay)
+    }
+
+    /// Check that `relay` is a relay a replica could have voted for in `round`.
+    ///
+    /// The relay must lie within one rotation through the committee, so that `round + relay`
+    /// neither overflows nor wraps around to a leader which already had its turn, and, for the
+    /// round we are syncing to, must not be skipped for being led by the leader which failed.
+    ///
+    /// # Errors
+    /// If the relay is out of range or was not eligible
+    async fn check_relay(&self, round: TYPES::View, relay: u64) -> Result<()> {
+        let membership_reader = self.membership.read().await;
+        let total_nodes = membership_reader.total_nodes(self.cur_epoch) as u64;
+        ensure!(
+            relay < total_nodes,
+            warn!("Relay {relay} is out of range for a committee of {total_nodes} nodes")
+        );
+
+        let relay_leader = membership_reader.leader(round + relay, self.cur_epoch)?;
+        drop(membership_reader);
+        if round == self.next_view {
+            ensure!(
+                self.relay_avoiding_failed_leader(relay).await == relay,
+                warn!("Relay {relay} is led by {relay_leader}, who led the view which failed")
+            );
+        }
+
+        Ok(())
+    }
+
     #[instrument(skip_all, fields(id = self.id, view = *self.cur_view, epoch = *self.cur_epoch), name = "View Sync Replica Task", level = "error")]
     /// Handle incoming events for the view sync replica task
     pub async fn handle(
@@ -531,15 +575,23 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                     return None;
                 }
 
-                // If certificate is not valid, return current state
-                if !certificate
-                    .is_valid_cert(
-                        self.membership.stake_table(self.cur_epoch),
-                        self.membership.failure_threshold(self.cur_epoch),
-                        &self.upgrade_lock,
-                    )
+                // Check the relay before anything is derived from it
+                if let Err(e) = self
+                    .check_relay(certificate.view_number(), certificate.data().relay)
                     .await
                 {
+                    tracing::error!("Not valid view sync cert! {e}");
+
+                    return None;
+                }
+
+                // If certificate is not valid, return current state
+                let membership_reader = self.membership.read().await;
+                let certificate_valid = certificate
+                    .is_valid_cert(&membership_reader, self.cur_epoch, &self.upgrade_lock)
+                    .await;
+                drop(membership_reader);
+                if !certificate_valid {
                     tracing::error!("Not valid view sync cert! {:?}", certificate.data());
 
                     return None;
@@ -561,6 +613,7 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                         round: self.next_view,
                     },
                     self.next_view,
+                    self.cur_epoch,
                     &self.public_key,
                     &self.private_key,
                     &self.upgrade_lock,
@@ -617,15 +670,23 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                     return None;
                 }
 
-                // If certificate is not valid, return current state
-                if !certificate
-                    .is_valid_cert(
-                        self.membership.stake_table(self.cur_epoch),
-                        self.membership.success_threshold(self.cur_epoch),
-                        &self.upgrade_lock,
-                    )
+                // Check the relay before anything is derived from it
+                if let Err(e) = self
+                    .check_relay(certificate.view_number(), certificate.data().relay)
                     .await
                 {
+                    tracing::error!("Not valid view sync cert! {e}");
+
+                    return None;
+                }
+
+                // If certificate is not valid, return current state
+                let membership_reader = self.membership.read().await;
+                let certificate_valid = certificate
+                    .is_valid_cert(&membership_reader, self.cur_epoch, &self.upgrade_lock)
+                    .await;
+                drop(membership_reader);
+                if !certificate_valid {
                     tracing::error!("Not valid view sync cert! {:?}", certificate.data());
 
                     return None;
@@ -647,6 +708,7 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                         round: self.next_view,
                     },
                     self.next_view,
+                    self.cur_epoch,
                     &self.public_key,
                     &self.private_key,
                     &self.upgrade_lock,
@@ -714,15 +776,23 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                     return None;
                 }
 
-                // If certificate is not valid, return current state
-                if !certificate
-                    .is_valid_cert(
-                        self.membership.stake_table(self.cur_epoch),
-                        self.membership.success_threshold(self.cur_epoch),
-                        &self.upgrade_lock,
-                    )
+                // Check the relay before anything is derived from it
+                if let Err(e) = self
+                    .check_relay(certificate.view_number(), certificate.data().relay)
                     .await
                 {
+                    tracing::error!("Not valid view sync cert! {e}");
+
+                    return None;
+                }
+
+                // If certificate is not valid, return current state
+                let membership_reader = self.membership.read().await;
+                let certificate_valid = certificate
+                    .is_valid_cert(&membership_reader, self.cur_epoch, &self.upgrade_lock)
+                    .await;
+                drop(membership_reader);
+                if !certificate_valid {
                     tracing::error!("Not valid view sync cert! {:?}", certificate.data());
 
                     return None;
@@ -758,12 +828,14 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                     return None;
                 }
 
+                self.relay = self.relay_avoiding_failed_leader(0).await;
                 let Ok(vote) = ViewSyncPreCommitVote::<TYPES>::create_signed_vote(
                     ViewSyncPreCommitData {
-                        relay: 0,
+                        relay: self.relay,
                         round: view_number,
                     },
                     view_number,
+                    self.cur_epoch,
                     &self.public_key,
                     &self.private_key,
                     &self.upgrade_lock,
@@ -813,7 +885,7 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                     if let Some(timeout_task) = self.timeout_task.take() {
                         timeout_task.abort();
                     }
-                    self.relay += 1;
+                    self.relay = self.relay_avoiding_failed_leader(self.relay + 1).await;
                     match last_seen_certificate {
                         ViewSyncPhase::None | ViewSyncPhase::PreCommit | ViewSyncPhase::Commit => {
                             let Ok(vote) = ViewSyncPreCommitVote::<TYPES>::create_signed_vote(
@@ -822,6 +894,7 @@ impl<TYPES: NodeType, V: Versions> ViewSyncReplicaTaskState<TYPES, V> {
                                     round: self.next_view,
                                 },
                                 self.next_view,
+                                self.cur_epoch,
                                 &self.public_key,
                                 &self.private_key,
                                 &self.upgrade_lock,
@@ -5,13 +5,15 @@
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
 use std::{
-    collections::{btree_map::Entry, BTreeMap, HashMap},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet, HashMap},
     fmt::Debug,
     marker::PhantomData,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use async_broadcast::Sender;
+use async_lock::RwLock;
 use async_trait::async_trait;
 use either::Either::{self, Left, Right};
 use hotshot_types::{
@@ -29,7 +31,8 @@ use hotshot_types::{
         election::Membership,
         node_implementation::{NodeType, Versions},
     },
-    vote::{Certificate, HasViewNumber, Vote, VoteAccumulator},
+    vote::{is_valid_vote, AccumulatorDump, Certificate, HasViewNumber, Vote, VoteAccumulator},
+    vote_metrics::VoteMetrics,
 };
 use utils::anytrace::*;
 
@@ -50,7 +53,7 @@ pub struct VoteCollectionTaskState<
     pub public_key: TYPES::SignatureKey,
 
     /// Membership for voting
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
 
     /// accumulator handles aggregating the votes
     pub accumulator: Option<VoteAccumulator<TYPES, VOTE, CERT, V>>,
@@ -66,6 +69,18 @@ pub struct VoteCollectionTaskState<
 
     /// Whether we should check if we are the leader when handling a vote
     pub check_if_leader: bool,
+
+    /// When the collector received its first vote
+    pub started_at: Instant,
+
+    /// How long votes are still recorded for participation after the certificate formed
+    pub late_vote_grace: Duration,
+
+    /// When the certificate formed, if it has
+    pub certificate_formed_at: Option<Instant>,
+
+    /// Valid votes received after the certificate formed, within the late vote grace period
+    pub late_voters: BTreeSet<TYPES::SignatureKey>,
 }
 
 /// Describes the functions a vote must implement for it to be aggregatable by the generic vote collection task
@@ -108,8 +123,9 @@ impl<
         event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
     ) -> Result<Option<CERT>> {
         if self.check_if_leader {
+            let leader = vote.leader(&*self.membership.read().await, self.epoch)?;
             ensure!(
-                vote.leader(&self.membership, self.epoch)? == self.public_key,
+                leader == self.public_key,
                 info!("Received vote for a view in which we were not the leader.")
             );
         }
@@ -122,14 +138,30 @@ impl<
             )
         );
 
+        if let Some(formed_at) = self.certificate_formed_at {
+            ensure!(
+                formed_at.elapsed() <= self.late_vote_grace,
+                info!(
+                    "Dropping vote for view {} received after the late vote grace period",
+                    *self.view
+                )
+            );
+            self.record_late_vote(vote).await?;
+
+            return Ok(None);
+        }
+
         let accumulator = self.accumulator.as_mut().context(warn!(
             "No accumulator to handle vote with. This shouldn't happen."
         ))?;
 
-        match accumulator
-            .accumulate(vote, &self.membership, self.epoch)
-            .await
-        {
+        let membership_reader = self.membership.read().await;
+        let outcome = accumulator
+            .accumulate(vote, self.view, &membership_reader, self.epoch)
+            .await;
+        drop(membership_reader);
+
+        match outcome {
             Either::Left(()) => Ok(None),
             Either::Right(cert) => {
                 tracing::debug!("Certificate Formed! {:?}", cert);
@@ -139,12 +171,88 @@ impl<
                     event_stream,
                 )
                 .await;
-                self.accumulator = None;
+                if self.late_vote_grace.is_zero() {
+                    self.accumulator = None;
+                } else {
+                    self.certificate_formed_at = Some(Instant::now());
+                }
 
                 Ok(Some(cert))
             }
         }
     }
+
+    /// Record a vote received after the certificate formed. It counts towards participation
+    /// only, the certificate is not formed again.
+    ///
+    /// # Errors
+    /// If the vote is invalid or its signer is not in the stake table
+    async fn record_late_vote(&mut self, vote: &VOTE) -> Result<()> {
+        let accumulator = self.accumulator.as_ref().context(warn!(
+            "No accumulator to validate late vote with. This shouldn't happen."
+        ))?;
+        ensure!(
+            is_valid_vote(vote, self.epoch, &accumulator.upgrade_lock).await,
+            warn!("Invalid late vote for view {}", *self.view)
+        );
+        let stake_table_entry = CERT::stake_table_entry(
+            &*self.membership.read().await,
+            &vote.signing_key(),
+            self.epoch,
+        );
+        ensure!(
+            stake_table_entry.is_some(),
+            info!(
+                "Late vote for view {} from a node without stake",
+                *self.view
+            )
+        );
+        self.late_voters.insert(vote.signing_key());
+
+        Ok(())
+    }
+
+    /// Whether the certificate formed longer than the late vote grace period ago, after which
+    /// the collector can be dropped
+    #[must_use]
+    pub fn late_vote_grace_elapsed(&self) -> bool {
+        self.certificate_formed_at
+            .is_some_and(|formed_at| formed_at.elapsed() > self.late_vote_grace)
+    }
+
+    /// The nodes which cast a valid vote for this view, including late votes
+    #[must_use]
+    pub fn participants(&self) -> BTreeSet<TYPES::SignatureKey> {
+        let mut participants = self.late_voters.clone();
+        if let Some(accumulator) = &self.accumulator {
+            for (_, votes) in accumulator.vote_outcomes.values() {
+                participants.extend(votes.keys().cloned());
+            }
+        }
+        participants
+    }
+}
+
+/// Snapshot the votes collected for `view`, for diagnosing a view which fails to form a
+/// certificate.
+///
+/// Returns `None` if there is no collector for `view`, or it no longer holds its votes.
+pub async fn dump_collector<TYPES, VOTE, CERT, V>(
+    collectors: &VoteCollectorsMap<TYPES, VOTE, CERT, V>,
+    view: TYPES::View,
+) -> Option<AccumulatorDump<TYPES>>
+where
+    TYPES: NodeType,
+    VOTE: Vote<TYPES>,
+    CERT: Certificate<TYPES, VOTE::Commitment, Voteable = VOTE::Commitment> + Debug,
+    V: Versions,
+{
+    let collector = collectors.get(&view)?;
+    let accumulator = collector.accumulator.as_ref()?;
+
+    let membership_reader = collector.membership.read().await;
+
+    Some(accumulator.dump(&membership_reader, view, collector.epoch))
 }
 
 /// Trait for types which will handle a vote event.
@@ -174,13 +282,17 @@ pub struct AccumulatorInfo<TYPES: NodeType> {
     /// This nodes Pub Key
     pub public_key: TYPES::SignatureKey,
     /// Membership we are accumulation votes for
-    pub membership: Arc<TYPES::Membership>,
+    pub membership: Arc<RwLock<TYPES::Membership>>,
     /// View of the votes we are collecting
     pub view: TYPES::View,
     /// Epoch of the votes we are collecting
     pub epoch: TYPES::Epoch,
     /// This nodes id
     pub id: u64,
+    /// Metrics of the votes this node accumulates
+    pub metrics: Arc<VoteMetrics>,
+    /// How long votes are still recorded for participation after the certificate formed
+    pub late_vote_grace: Duration,
 }
 
 /// Generic function for spawning a vote task.  Returns the event stream id of the spawned task if created
@@ -211,15 +323,34 @@ where
         + 'static,
     V: Versions,
     VoteCollectionTaskState<TYPES, VOTE, CERT, V>: HandleVoteEvent<TYPES, VOTE, CERT>,
+{
+    let mut state = new_vote_collector(info, upgrade_lock, check_if_leader);
+
+    state.handle_vote_event(Arc::clone(&event), sender).await?;
+
+    Ok(state)
+}
+
+/// Create a vote collector for the view of `info` which has not handled any vote yet
+fn new_vote_collector<TYPES: NodeType, VOTE, CERT, V: Versions>(
+    info: &AccumulatorInfo<TYPES>,
+    upgrade_lock: UpgradeLock<TYPES, V>,
+    check_if_leader: bool,
+) -> VoteCollectionTaskState<TYPES, VOTE, CERT, V>
+where
+    VOTE: Vote<TYPES>,
+    CERT: Certificate<TYPES, VOTE::Commitment, Voteable = VOTE::Commitment> + Debug,
 {
     let new_accumulator = VoteAccumulator {
         vote_outcomes: HashMap::new(),
         signers: HashMap::new(),
         phantom: PhantomData,
         upgrade_lock,
+        metrics: Arc::clone(&info.metrics),
+        on_certificate: None,
     };
 
-    let mut state = VoteCollectionTaskState::<TYPES, VOTE, CERT, V> {
+    VoteCollectionTaskState::<TYPES, VOTE, CERT, V> {
         membership: Arc::clone(&info.membership),
         public_key: info.public_key.clone(),
         accumulator: Some(new_accumulator),
@@ -227,14 +358,57 @@ where
         epoch: info.epoch,
         id: info.id,
         check_if_leader,
-    };
+        started_at: Instant::now(),
+        late_vote_grace: info.late_vote_grace,
+        certificate_formed_at: None,
+        late_voters: BTreeSet::new(),
+    }
+}
 
-    state.handle_vote_event(Arc::clone(&event), sender).await?;
+/// Remove the collectors for views up to and including `decided_view`, whose certificates are no
+/// longer needed. A collector still recording late votes is kept until its grace period elapses,
+/// when [`handle_vote`] removes it.
+pub fn remove_decided_collectors<TYPES, VOTE, CERT, V>(
+    collectors: &mut VoteCollectorsMap<TYPES, VOTE, CERT, V>,
+    decided_view: TYPES::View,
+) where
+    TYPES: NodeType,
+    VOTE: Vote<TYPES>,
+    CERT: Certificate<TYPES, VOTE::Commitment, Voteable = VOTE::Commitment> + Debug,
+    V: Versions,
+{
+    collectors.retain(|view, collector| {
+        *view > decided_view
+            || (collector.certificate_formed_at.is_some() && !collector.late_vote_grace_elapsed())
+    });
+}
 
-    Ok(state)
+/// Check that a vote for `view` is still of use after `decided_view` decided, which it only is
+/// while the collector for `view` records late votes.
+///
+/// # Errors
+/// If `view` has decided and has no collector left
+pub fn ensure_undecided<TYPES, VOTE, CERT, V>(
+    collectors: &VoteCollectorsMap<TYPES, VOTE, CERT, V>,
+    view: TYPES::View,
+    decided_view: TYPES::View,
+) -> Result<()>
+where
+    TYPES: NodeType,
+    VOTE: Vote<TYPES>,
+    CERT: Certificate<TYPES, VOTE::Commitment, Voteable = VOTE::Commitment> + Debug,
+    V: Versions,
+{
+    ensure!(
+        view > decided_view || collectors.contains_key(&view),
+        debug!("Ignoring vote for view {view:?}, which has already decided")
+    );
+
+    Ok(())
 }
 
 /// A helper function that handles a vote regardless whether it's the first vote in the view or not.
+/// Returns the certificate if this vote formed it, whether or not its collector is kept.
 ///
 /// # Errors
 /// If we fail to handle the vote
@@ -252,14 +426,16 @@ pub async fn handle_vote<
     collectors: &mut VoteCollectorsMap<TYPES, VOTE, CERT, V>,
     vote: &VOTE,
     public_key: TYPES::SignatureKey,
-    membership: &Arc<TYPES::Membership>,
+    membership: &Arc<RwLock<TYPES::Membership>>,
     epoch: TYPES::Epoch,
     id: u64,
     event: &Arc<HotShotEvent<TYPES>>,
     event_stream: &Sender<Arc<HotShotEvent<TYPES>>>,
     upgrade_lock: &UpgradeLock<TYPES, V>,
     check_if_leader: bool,
-) -> Result<()>
+    metrics: &Arc<VoteMetrics>,
+    late_vote_grace: Duration,
+) -> Result<Option<CERT>>
 where
     VoteCollectionTaskState<TYPES, VOTE, CERT, V>: HandleVoteEvent<TYPES, VOTE, CERT>,
 {
@@ -272,33 +448,40 @@ where
                 view: vote.view_number(),
                 epoch,
                 id,
+                metrics: Arc::clone(metrics),
+                late_vote_grace,
             };
-            let collector = create_vote_accumulator(
-                &info,
-                Arc::clone(event),
-                event_stream,
-                upgrade_lock.clone(),
-                check_if_leader,
-            )
-            .await?;
+            let mut collector = new_vote_collector(&info, upgrade_lock.clone(), check_if_leader);
+            let certificate = collector
+                .handle_vote_event(Arc::clone(event), event_stream)
+                .await?;
 
             entry.insert(collector);
 
-            Ok(())
+            Ok(certificate)
         }
         Entry::Occupied(mut entry) => {
+            // the certificate for this view formed and its late votes are no longer recorded
+            if entry.get().late_vote_grace_elapsed() {
+                entry.remove();
+
+                return Ok(None);
+            }
+
             // handle the vote, and garbage collect if the vote collector is finished
-            if entry
+            let certificate = entry
                 .get_mut()
                 .handle_vote_event(Arc::clone(event), event_stream)
-                .await?
-                .is_some()
-            {
-                entry.remove();
+                .await?;
+            if certificate.is_some() {
+                // keep the collector while it records late votes
+                if late_vote_grace.is_zero() {
+                    entry.remove();
+                }
                 *collectors = collectors.split_off(&vote.view_number());
             }
 
-            Ok(())
+            Ok(certificate)
         }
     }
 }
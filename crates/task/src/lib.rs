@@ -10,5 +10,9 @@
 pub mod dependency;
 /// Task which can uses dependencies
 pub mod dependency_task;
+/// Bounded queues in front of tasks
+pub mod queue;
 /// Basic task types
 pub mod task;
+/// Watchdog for tasks which stop making progress
+pub mod watchdog;
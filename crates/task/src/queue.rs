@@ -0,0 +1,230 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    Arc,
+};
+
+use async_broadcast::{broadcast, Receiver, RecvError, Sender};
+use tokio::task::{spawn, JoinHandle};
+
+use crate::task::TaskEvent;
+
+/// How far behind the task fed by an [`EventQueue`] is, shared between the queue and whoever
+/// monitors it
+#[derive(Debug)]
+pub struct QueueDepth {
+    /// Identifier of the task the queue feeds, used when warning about it
+    task_id: String,
+    /// The depth at which the task is considered to be falling behind
+    high_watermark: usize,
+    /// The number of events queued when the last event arrived
+    depth: AtomicUsize,
+    /// The most events ever queued
+    max_depth: AtomicUsize,
+    /// Whether the queue has reached the high watermark without draining below it since
+    above_watermark: AtomicBool,
+    /// The number of times the queue has reached the high watermark
+    watermark_warnings: AtomicU64,
+}
+
+impl QueueDepth {
+    /// Create the depth tracker for the queue feeding the task `task_id`
+    #[must_use]
+    pub fn new(task_id: impl Into<String>, high_watermark: usize) -> Self {
+        Self {
+            task_id: task_id.into(),
+            high_watermark,
+            depth: AtomicUsize::new(0),
+            max_depth: AtomicUsize::new(0),
+            above_watermark: AtomicBool::new(false),
+            watermark_warnings: AtomicU64::new(0),
+        }
+    }
+
+    /// The number of events queued when the last event arrived
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Acquire)
+    }
+
+    /// The most events ever queued
+    #[must_use]
+    pub fn max_depth(&self) -> usize {
+        self.max_depth.load(Ordering::Acquire)
+    }
+
+    /// The number of times the queue has reached the high watermark
+    #[must_use]
+    pub fn watermark_warnings(&self) -> u64 {
+        self.watermark_warnings.load(Ordering::Acquire)
+    }
+
+    /// Record the depth an event arrived at, warning once each time it reaches the high watermark
+    fn record(&self, depth: usize) {
+        self.depth.store(depth, Ordering::Release);
+        self.max_depth.fetch_max(depth, Ordering::AcqRel);
+
+        if depth < self.high_watermark {
+            self.above_watermark.store(false, Ordering::Release);
+        } else if !self.above_watermark.swap(true, Ordering::AcqRel) {
+            self.watermark_warnings.fetch_add(1, Ordering::AcqRel);
+            tracing::warn!(
+                "Task {} is falling behind, {depth} events are queued for it",
+                self.task_id
+            );
+        }
+    }
+}
+
+/// A bounded queue between an event stream and the task handling its events.
+///
+/// Events are forwarded in order, waiting for room when the queue is full, so a task which can't
+/// keep up shows as the depth of its queue rather than as a lagging event stream.
+pub struct EventQueue<E> {
+    /// The event stream the events come from
+    source: Receiver<Arc<E>>,
+    /// The sending end of the queue
+    queue: Sender<Arc<E>>,
+    /// How far behind the task is
+    depth: Arc<QueueDepth>,
+}
+
+impl<E: TaskEvent + Clone + Send + Sync + 'static> EventQueue<E> {
+    /// Create a queue of at most `capacity` events from `source`, returning it along with the
+    /// receiving end to hand to the task
+    #[must_use]
+    pub fn new(
+        source: Receiver<Arc<E>>,
+        capacity: usize,
+        depth: Arc<QueueDepth>,
+    ) -> (Self, Receiver<Arc<E>>) {
+        let (queue, receiver) = broadcast(capacity);
+
+        (
+            Self {
+                source,
+                queue,
+                depth,
+            },
+            receiver,
+        )
+    }
+
+    /// Spawn the loop forwarding events into the queue until shutdown, passing the depth each
+    /// event arrives at to `report`
+    pub fn run(mut self, report: impl Fn(usize) + Send + 'static) -> JoinHandle<()> {
+        spawn(async move {
+            loop {
+                match self.source.recv_direct().await {
+                    Ok(event) => {
+                        let shutdown = *event == E::shutdown_event();
+                        let depth = self.queue.len();
+                        self.depth.record(depth);
+                        report(depth);
+
+                        if self.queue.broadcast_direct(event).await.is_err() || shutdown {
+                            return;
+                        }
+                    }
+                    Err(RecvError::Closed) => return,
+                    Err(e) => {
+                        tracing::error!("Failed to receive from event stream Error: {}", e);
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use tokio::time::sleep;
+    use utils::anytrace::Result;
+
+    use super::*;
+    use crate::task::{Task, TaskState};
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    enum TestEvent {
+        Shutdown,
+        Work,
+        TaskStalled(String),
+    }
+
+    impl TaskEvent for TestEvent {
+        fn shutdown_event() -> Self {
+            TestEvent::Shutdown
+        }
+
+        fn task_stalled_event(task_id: &str) -> Self {
+            TestEvent::TaskStalled(task_id.to_string())
+        }
+    }
+
+    /// Takes a while over every event
+    struct SlowState;
+
+    #[async_trait]
+    impl TaskState for SlowState {
+        type Event = TestEvent;
+
+        fn cancel_subtasks(&mut self) {}
+
+        async fn handle_event(
+            &mut self,
+            _event: Arc<TestEvent>,
+            _sender: &Sender<Arc<TestEvent>>,
+            _receiver: &Receiver<Arc<TestEvent>>,
+        ) -> Result<()> {
+            sleep(Duration::from_millis(20)).await;
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn flooded_queue_reports_depth_and_warns_at_watermark() {
+        let (tx, rx) = broadcast(256);
+        let depth = Arc::new(QueueDepth::new("SlowState", 8));
+        let (queue, queue_rx) = EventQueue::new(rx, 16, Arc::clone(&depth));
+        let reported = Arc::new(AtomicUsize::new(0));
+        let _queue = queue.run({
+            let reported = Arc::clone(&reported);
+            move |queued| reported.store(queued, Ordering::Release)
+        });
+        let handle = Task::new(SlowState, tx.clone(), queue_rx).run();
+
+        // Events arrive much faster than they are handled
+        for _ in 0..64 {
+            tx.broadcast(Arc::new(TestEvent::Work)).await.unwrap();
+        }
+        sleep(Duration::from_millis(100)).await;
+
+        assert!(depth.max_depth() >= 8, "max depth {}", depth.max_depth());
+        assert!(reported.load(Ordering::Acquire) >= 8);
+        assert!(depth.depth() <= 16);
+        assert_eq!(depth.watermark_warnings(), 1);
+
+        // Once the task catches up, falling behind again warns again
+        while depth.depth() > 0 {
+            tx.broadcast(Arc::new(TestEvent::Work)).await.unwrap();
+            sleep(Duration::from_millis(40)).await;
+        }
+        for _ in 0..64 {
+            tx.broadcast(Arc::new(TestEvent::Work)).await.unwrap();
+        }
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(depth.watermark_warnings(), 2);
+
+        tx.broadcast(Arc::new(TestEvent::Shutdown)).await.unwrap();
+        handle.await.unwrap();
+    }
+}
@@ -12,6 +12,8 @@ use futures::future::try_join_all;
 use tokio::task::{spawn, JoinHandle};
 use utils::anytrace::Result;
 
+use crate::watchdog::TaskProgress;
+
 /// Trait for events that long-running tasks handle
 pub trait TaskEvent: PartialEq {
     /// The shutdown signal for this event type
@@ -19,6 +21,9 @@ pub trait TaskEvent: PartialEq {
     /// Note that this is necessarily uniform across all tasks.
     /// Exiting the task loop is handled by the task spawner, rather than the task individually.
     fn shutdown_event() -> Self;
+
+    /// The event reporting that the task `task_id` has stalled
+    fn task_stalled_event(task_id: &str) -> Self;
 }
 
 #[async_trait]
@@ -52,6 +57,8 @@ pub struct Task<S: TaskState> {
     sender: Sender<Arc<S::Event>>,
     /// Receives events that are broadcast from any task, including itself
     receiver: Receiver<Arc<S::Event>>,
+    /// Progress of the task loop, monitored by the watchdog
+    progress: Arc<TaskProgress>,
 }
 
 impl<S: TaskState + Send + 'static> Task<S> {
@@ -61,9 +68,16 @@ impl<S: TaskState + Send + 'static> Task<S> {
             state,
             sender,
             receiver,
+            progress: Arc::new(TaskProgress::new(std::any::type_name::<S>())),
         }
     }
 
+    /// The progress of the task loop, for monitoring by a [`Watchdog`](crate::watchdog::Watchdog)
+    #[must_use]
+    pub fn progress(&self) -> Arc<TaskProgress> {
+        Arc::clone(&self.progress)
+    }
+
     /// The state of the task, as a boxed dynamic trait object.
     fn boxed_state(self) -> Box<dyn TaskState<Event = S::Event>> {
         Box::new(self.state) as Box<dyn TaskState<Event = S::Event>>
@@ -82,10 +96,21 @@ impl<S: TaskState + Send + 'static> Task<S> {
                             break self.boxed_state();
                         }
 
-                        let _ =
-                            S::handle_event(&mut self.state, input, &self.sender, &self.receiver)
-                                .await
-                                .inspect_err(|e| tracing::debug!("{e}"));
+                        self.progress.start_event();
+                        let handled =
+                            S::handle_event(&mut self.state, input, &self.sender, &self.receiver);
+                        tokio::select! {
+                            result = handled => {
+                                let _ = result.inspect_err(|e| tracing::debug!("{e}"));
+                            }
+                            () = self.progress.restarted() => {
+                                tracing::warn!(
+                                    "Task {} restarted, abandoning its current event",
+                                    self.progress.task_id()
+                                );
+                            }
+                        }
+                        self.progress.finish_event();
                     }
                     Err(RecvError::Closed) => {
                         break self.boxed_state();
@@ -104,6 +129,8 @@ impl<S: TaskState + Send + 'static> Task<S> {
 pub struct ConsensusTaskRegistry<EVENT> {
     /// Tasks this registry controls
     task_handles: Vec<JoinHandle<Box<dyn TaskState<Event = EVENT>>>>,
+    /// Progress of the tasks this registry controls
+    task_progress: Vec<Arc<TaskProgress>>,
 }
 
 impl<EVENT: Send + Sync + Clone + TaskEvent> ConsensusTaskRegistry<EVENT> {
@@ -112,6 +139,7 @@ impl<EVENT: Send + Sync + Clone + TaskEvent> ConsensusTaskRegistry<EVENT> {
     pub fn new() -> Self {
         ConsensusTaskRegistry {
             task_handles: vec![],
+            task_progress: vec![],
         }
     }
     /// Add a task to the registry
@@ -137,9 +165,16 @@ impl<EVENT: Send + Sync + Clone + TaskEvent> ConsensusTaskRegistry<EVENT> {
     where
         S: TaskState<Event = EVENT> + Send + 'static,
     {
+        self.task_progress.push(task.progress());
         self.register(task.run());
     }
 
+    /// Progress of every task run through this registry
+    #[must_use]
+    pub fn task_progress(&self) -> Vec<Arc<TaskProgress>> {
+        self.task_progress.clone()
+    }
+
     /// Wait for the results of all the tasks registered
     /// # Panics
     /// Panics if one of the tasks panicked
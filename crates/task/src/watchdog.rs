@@ -0,0 +1,306 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use async_broadcast::Sender;
+use tokio::sync::Notify;
+
+use crate::task::TaskEvent;
+
+/// Progress of a single task's event loop, shared between the task and the [`Watchdog`]
+#[derive(Debug)]
+pub struct TaskProgress {
+    /// Identifier of the task, used when reporting a stall
+    task_id: String,
+    /// When the task last started or finished handling an event
+    last_processed: Mutex<Instant>,
+    /// Whether the task is currently handling an event
+    in_flight: AtomicBool,
+    /// Whether the watchdog has already reported the current stall
+    stall_reported: AtomicBool,
+    /// Signals the task to abandon the event it is currently handling
+    restart: Notify,
+}
+
+impl TaskProgress {
+    /// Create the progress tracker for the task `task_id`
+    #[must_use]
+    pub fn new(task_id: impl Into<String>) -> Self {
+        Self {
+            task_id: task_id.into(),
+            last_processed: Mutex::new(Instant::now()),
+            in_flight: AtomicBool::new(false),
+            stall_reported: AtomicBool::new(false),
+            restart: Notify::new(),
+        }
+    }
+
+    /// Identifier of the task
+    #[must_use]
+    pub fn task_id(&self) -> &str {
+        &self.task_id
+    }
+
+    /// Record that the task has started handling an event
+    pub fn start_event(&self) {
+        *self
+            .last_processed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Instant::now();
+        self.in_flight.store(true, Ordering::Release);
+    }
+
+    /// Record that the task has finished (or abandoned) handling an event
+    pub fn finish_event(&self) {
+        *self
+            .last_processed
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Instant::now();
+        self.in_flight.store(false, Ordering::Release);
+        self.stall_reported.store(false, Ordering::Release);
+    }
+
+    /// How long the task has been handling its current event, or `None` if it is waiting for one
+    #[must_use]
+    pub fn busy_for(&self) -> Option<Duration> {
+        self.in_flight.load(Ordering::Acquire).then(|| {
+            self.last_processed
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .elapsed()
+        })
+    }
+
+    /// Ask the task to abandon the event it is currently handling and move on to the next one
+    pub fn restart(&self) {
+        // `notify_waiters` does not store a permit, so a task which has just finished its event
+        // cannot have its next event abandoned by a stale restart.
+        self.restart.notify_waiters();
+    }
+
+    /// Resolves when the watchdog restarts the task
+    pub async fn restarted(&self) {
+        self.restart.notified().await;
+    }
+}
+
+/// Monitors the progress of a set of tasks sharing an event stream.
+///
+/// A task is stalled if it has been handling a single event for longer than the stall timeout
+/// while further events are queued behind it. Each stall is logged and reported once, by
+/// broadcasting [`TaskEvent::task_stalled_event`], and the task is optionally restarted.
+pub struct Watchdog<EVENT> {
+    /// Progress of the monitored tasks
+    tasks: Vec<Arc<TaskProgress>>,
+    /// The event stream the monitored tasks receive from
+    sender: Sender<Arc<EVENT>>,
+    /// How long a task may handle a single event while others are queued
+    stall_timeout: Duration,
+    /// Whether to restart stalled tasks
+    restart_stalled: bool,
+}
+
+impl<EVENT: TaskEvent> Watchdog<EVENT> {
+    /// Create a watchdog for `tasks`, which receive events broadcast on `sender`
+    #[must_use]
+    pub fn new(
+        tasks: Vec<Arc<TaskProgress>>,
+        sender: Sender<Arc<EVENT>>,
+        stall_timeout: Duration,
+        restart_stalled: bool,
+    ) -> Self {
+        Self {
+            tasks,
+            sender,
+            stall_timeout,
+            restart_stalled,
+        }
+    }
+
+    /// How often the watchdog should be checked to detect stalls close to the timeout
+    #[must_use]
+    pub fn check_interval(&self) -> Duration {
+        (self.stall_timeout / 4).max(Duration::from_millis(10))
+    }
+
+    /// Check every task once, reporting any newly stalled tasks.
+    ///
+    /// Returns the ids of the tasks reported by this check.
+    pub fn check(&self) -> Vec<String> {
+        let queued = self.sender.len();
+        if queued == 0 {
+            return Vec::new();
+        }
+
+        let mut stalled = Vec::new();
+        for progress in &self.tasks {
+            let Some(busy_for) = progress.busy_for() else {
+                continue;
+            };
+            if busy_for < self.stall_timeout || progress.stall_reported.swap(true, Ordering::AcqRel)
+            {
+                continue;
+            }
+
+            tracing::warn!(
+                "Task {} has been handling a single event for {:?} with {} events queued{}",
+                progress.task_id(),
+                busy_for,
+                queued,
+                if self.restart_stalled {
+                    "; restarting it"
+                } else {
+                    ""
+                }
+            );
+
+            // Never wait on the event stream here: it is full precisely when a task is stuck.
+            let _ = self
+                .sender
+                .try_broadcast(Arc::new(EVENT::task_stalled_event(progress.task_id())))
+                .inspect_err(|e| tracing::error!("Failed to report stalled task: {e}"));
+
+            if self.restart_stalled {
+                progress.restart();
+            }
+            stalled.push(progress.task_id().to_string());
+        }
+
+        stalled
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_broadcast::{broadcast, Receiver};
+    use async_trait::async_trait;
+    use tokio::time::sleep;
+    use utils::anytrace::Result;
+
+    use super::*;
+    use crate::task::{Task, TaskState};
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    enum TestEvent {
+        Shutdown,
+        Block,
+        Work,
+        TaskStalled(String),
+    }
+
+    impl TaskEvent for TestEvent {
+        fn shutdown_event() -> Self {
+            TestEvent::Shutdown
+        }
+
+        fn task_stalled_event(task_id: &str) -> Self {
+            TestEvent::TaskStalled(task_id.to_string())
+        }
+    }
+
+    /// Never finishes handling a `Block` event
+    struct BlockingState;
+
+    #[async_trait]
+    impl TaskState for BlockingState {
+        type Event = TestEvent;
+
+        fn cancel_subtasks(&mut self) {}
+
+        async fn handle_event(
+            &mut self,
+            event: Arc<TestEvent>,
+            _sender: &Sender<Arc<TestEvent>>,
+            _receiver: &Receiver<Arc<TestEvent>>,
+        ) -> Result<()> {
+            if *event == TestEvent::Block {
+                futures::future::pending::<()>().await;
+            }
+
+            Ok(())
+        }
+    }
+
+    async fn next_stalled(rx: &mut Receiver<Arc<TestEvent>>) -> String {
+        loop {
+            if let TestEvent::TaskStalled(id) = rx.recv().await.unwrap().as_ref() {
+                return id.clone();
+            }
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn watchdog_reports_blocked_task_after_timeout() {
+        let timeout = Duration::from_millis(200);
+        let (tx, rx) = broadcast(16);
+        let mut observer = rx.clone();
+        let task = Task::new(BlockingState, tx.clone(), rx);
+        let progress = task.progress();
+        let watchdog = Watchdog::new(vec![Arc::clone(&progress)], tx.clone(), timeout, false);
+        let _handle = task.run();
+
+        tx.broadcast(Arc::new(TestEvent::Block)).await.unwrap();
+        while progress.busy_for().is_none() {
+            sleep(Duration::from_millis(10)).await;
+        }
+
+        // Blocked, but with nothing queued behind it the task is not (yet) holding anything up.
+        sleep(timeout * 2).await;
+        while observer.try_recv().is_ok() {}
+        assert!(watchdog.check().is_empty());
+
+        tx.broadcast(Arc::new(TestEvent::Work)).await.unwrap();
+        assert_eq!(watchdog.check(), vec![progress.task_id().to_string()]);
+        assert_eq!(next_stalled(&mut observer).await, progress.task_id());
+
+        // The stall is only reported once.
+        assert!(watchdog.check().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn watchdog_does_not_fire_before_timeout() {
+        let (tx, rx) = broadcast(16);
+        let task = Task::new(BlockingState, tx.clone(), rx);
+        let progress = task.progress();
+        let watchdog = Watchdog::new(vec![progress], tx.clone(), Duration::from_secs(60), false);
+        let _handle = task.run();
+
+        tx.broadcast(Arc::new(TestEvent::Block)).await.unwrap();
+        tx.broadcast(Arc::new(TestEvent::Work)).await.unwrap();
+        sleep(Duration::from_millis(50)).await;
+
+        assert!(watchdog.check().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn watchdog_restarts_blocked_task() {
+        let timeout = Duration::from_millis(100);
+        let (tx, rx) = broadcast(16);
+        let task = Task::new(BlockingState, tx.clone(), rx);
+        let progress = task.progress();
+        let watchdog = Watchdog::new(vec![Arc::clone(&progress)], tx.clone(), timeout, true);
+        let handle = task.run();
+
+        tx.broadcast(Arc::new(TestEvent::Block)).await.unwrap();
+        tx.broadcast(Arc::new(TestEvent::Work)).await.unwrap();
+        sleep(timeout * 2).await;
+        assert_eq!(watchdog.check().len(), 1);
+
+        // Once restarted, the task moves on to the queued events and can be shut down.
+        tx.broadcast(Arc::new(TestEvent::Shutdown)).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -315,13 +315,14 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + std::fmt::Debug, V: Version
         public_key: &TYPES::SignatureKey,
         private_key: &<TYPES::SignatureKey as SignatureKey>::PrivateKey,
         upgrade_lock: &UpgradeLock<TYPES, V>,
-        _consensus: Arc<RwLock<Consensus<TYPES>>>,
+        consensus: Arc<RwLock<Consensus<TYPES>>>,
     ) -> Vec<HotShotEvent<TYPES>> {
         if let HotShotEvent::QuorumVoteSend(vote) = event {
             let new_view = vote.view_number + self.view_increment;
             let spoofed_vote = QuorumVote2::<TYPES>::create_signed_vote(
                 vote.data.clone(),
                 new_view,
+                consensus.read().await.cur_epoch(),
                 public_key,
                 private_key,
                 upgrade_lock,
@@ -339,17 +340,22 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + std::fmt::Debug, V: Version
         &self,
         handle: &mut SystemContextHandle<TYPES, I, V>,
         network: Arc<<I as NodeImplementation<TYPES>>::Network>,
-        membership: TYPES::Membership,
+        membership: Arc<RwLock<TYPES::Membership>>,
     ) {
         let network_state: NetworkEventTaskState<_, V, _, _> = NetworkEventTaskState {
             network,
             view: TYPES::View::genesis(),
             epoch: TYPES::Epoch::genesis(),
+            max_epoch_skip: handle.hotshot.config.max_epoch_skip,
             membership,
             storage: Arc::clone(&handle.storage()),
             consensus: OuterConsensus::new(handle.consensus()),
             upgrade_lock: handle.hotshot.upgrade_lock.clone(),
+            network_id: handle.hotshot.config.network_id,
             transmit_tasks: BTreeMap::new(),
+            participation: Arc::clone(&handle.hotshot.participation),
+            batch_justify_qcs: handle.hotshot.config.batch_justify_qcs,
+            last_justify_qc: None,
         };
         let modified_network_state = NetworkEventTaskStateModifier {
             network_event_task_state: network_state,
@@ -390,7 +396,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + std::fmt::Debug, V: Version
         public_key: &TYPES::SignatureKey,
         private_key: &<TYPES::SignatureKey as SignatureKey>::PrivateKey,
         upgrade_lock: &UpgradeLock<TYPES, V>,
-        _consensus: Arc<RwLock<Consensus<TYPES>>>,
+        consensus: Arc<RwLock<Consensus<TYPES>>>,
     ) -> Vec<HotShotEvent<TYPES>> {
         match event {
             HotShotEvent::QuorumProposalRecv(proposal, _sender) => {
@@ -403,6 +409,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES> + std::fmt::Debug, V: Version
                     let vote = QuorumVote2::<TYPES>::create_signed_vote(
                         self.votes_sent.last().unwrap().data.clone(),
                         event.view_number().unwrap(),
+                        consensus.read().await.cur_epoch(),
                         public_key,
                         private_key,
                         upgrade_lock,
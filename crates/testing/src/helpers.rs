@@ -159,6 +159,7 @@ pub async fn build_cert<
     let vote = SimpleVote::<TYPES, DATAType>::create_signed_vote(
         data,
         view,
+        epoch,
         public_key,
         private_key,
         upgrade_lock,
@@ -167,7 +168,7 @@ pub async fn build_cert<
     .expect("Failed to sign data!");
 
     let vote_commitment =
-        VersionedVoteData::new(vote.date().clone(), vote.view_number(), upgrade_lock)
+        VersionedVoteData::new(vote.date().clone(), vote.view_number(), epoch, upgrade_lock)
             .await
             .expect("Failed to create VersionedVoteData!")
             .commit();
@@ -177,6 +178,7 @@ pub async fn build_cert<
         vote.date().clone(),
         real_qc_sig,
         vote.view_number(),
+        epoch,
     );
     cert
 }
@@ -227,6 +229,7 @@ pub async fn build_assembled_sig<
         let vote: SimpleVote<TYPES, DATAType> = SimpleVote::<TYPES, DATAType>::create_signed_vote(
             data.clone(),
             view,
+            epoch,
             &public_key_i,
             &private_key_i,
             upgrade_lock,
@@ -339,6 +342,9 @@ pub fn build_vid_proposal<TYPES: NodeType>(
     let vid_disperse_proposal = Proposal {
         data: vid_disperse.clone(),
         signature,
+        leader_proof: quorum_membership
+            .leader_proof(view_number, epoch_number, private_key)
+            .expect("Failed to prove the leader"),
         _pd: PhantomData,
     };
 
@@ -398,6 +404,7 @@ pub async fn build_vote<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versio
             leaf_commit: leaf.commit(&handle.hotshot.upgrade_lock).await,
         },
         view,
+        handle.cur_epoch().await,
         &handle.public_key(),
         handle.private_key(),
         &handle.hotshot.upgrade_lock,
@@ -24,6 +24,9 @@ pub mod overall_safety_task;
 /// task that checks leaves received across all nodes from decide events for consistency
 pub mod consistency_task;
 
+/// offline safety checks over recorded consensus traces
+pub mod safety_checker;
+
 /// task that's submitting transactions to the stream
 pub mod txn_task;
 
@@ -33,6 +36,9 @@ pub mod completion_task;
 /// task to spin nodes up and down
 pub mod spinning_task;
 
+/// view-by-view scripted scenarios for the spinning task
+pub mod scenario;
+
 /// the `TestTask` struct and associated trait/functions
 pub mod test_task;
 
@@ -5,14 +5,16 @@
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
 use std::{
-    collections::{hash_map::Entry, HashMap, HashSet},
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
 use async_broadcast::Sender;
 use async_lock::RwLock;
 use async_trait::async_trait;
+use committable::Committable;
 use hotshot::{traits::TestableNodeImplementation, HotShotError};
 use hotshot_types::{
     data::Leaf2,
@@ -20,8 +22,10 @@ use hotshot_types::{
     event::{Event, EventType, LeafChain},
     simple_certificate::QuorumCertificate2,
     traits::node_implementation::{ConsensusTime, NodeType, Versions},
+    utils::CommitmentHex,
     vid::VidCommitment,
 };
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::error;
 
@@ -329,6 +333,12 @@ pub struct RoundResult<TYPES: NodeType> {
 
     /// number of transactions -> number of nodes reporting that number
     pub num_txns_map: HashMap<u64, usize>,
+
+    /// when the first event of this round was recorded
+    pub started: Instant,
+
+    /// how long after `started` the round succeeded, once it has
+    pub duration: Option<Duration>,
 }
 
 impl<TYPES: NodeType> Default for RoundResult<TYPES> {
@@ -340,10 +350,41 @@ impl<TYPES: NodeType> Default for RoundResult<TYPES> {
             block_map: HashMap::default(),
             num_txns_map: HashMap::default(),
             status: ViewStatus::InProgress,
+            started: Instant::now(),
+            duration: None,
         }
     }
 }
 
+/// Outcome of a round, as recorded in a [`RoundSummary`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundOutcome {
+    /// the round succeeded
+    Ok,
+    /// the round failed
+    Failed,
+    /// the round violated a safety property
+    Err,
+    /// the round is still in progress
+    InProgress,
+}
+
+/// Summary of a round, stable across runs so that a run can be diffed against a stored baseline
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoundSummary {
+    /// outcome of the round
+    pub outcome: RoundOutcome,
+    /// hex commitment of the leaf decided by the most nodes, if any node decided
+    pub committed_leaf: Option<String>,
+    /// ids of the nodes which decided in this round, ascending
+    pub participating_nodes: Vec<u64>,
+    /// ids of the nodes which reported a failure in this round, ascending
+    pub failed_nodes: Vec<u64>,
+    /// how long the round took to succeed, in milliseconds, if it has
+    pub duration_ms: Option<u64>,
+}
+
 /// smh my head I shouldn't need to implement this
 /// Rust doesn't realize I doesn't need to implement default
 impl<TYPES: NodeType> Default for RoundCtx<TYPES> {
@@ -372,6 +413,21 @@ pub struct RoundCtx<TYPES: NodeType> {
 }
 
 impl<TYPES: NodeType> RoundCtx<TYPES> {
+    /// Summarize every round by view number, as JSON which is stable across runs so that a run
+    /// can be diffed against a stored baseline.
+    ///
+    /// # Errors
+    /// If the summary cannot be serialized
+    pub fn to_summary(&self) -> serde_json::Result<String> {
+        let summaries: BTreeMap<u64, RoundSummary> = self
+            .round_results
+            .iter()
+            .map(|(view, result)| (**view, result.to_summary()))
+            .collect();
+
+        serde_json::to_string_pretty(&summaries)
+    }
+
     /// inserts an error into the context
     pub fn insert_error_to_context(
         &mut self,
@@ -518,6 +574,7 @@ impl<TYPES: NodeType> RoundResult<TYPES> {
                 && *self.leaf_map.get(key).unwrap() == threshold
             {
                 self.status = ViewStatus::Ok;
+                self.duration.get_or_insert_with(|| self.started.elapsed());
                 return;
             }
         }
@@ -528,6 +585,38 @@ impl<TYPES: NodeType> RoundResult<TYPES> {
         }
     }
 
+    /// Summarize the round for comparison across runs.
+    #[must_use]
+    pub fn to_summary(&self) -> RoundSummary {
+        let outcome = match self.status {
+            ViewStatus::Ok => RoundOutcome::Ok,
+            ViewStatus::Failed => RoundOutcome::Failed,
+            ViewStatus::Err(_) => RoundOutcome::Err,
+            ViewStatus::InProgress => RoundOutcome::InProgress,
+        };
+        // break ties between equally popular leaves by commitment, so the summary is stable
+        let committed_leaf = self
+            .leaf_map
+            .iter()
+            .map(|(leaf, count)| (*count, leaf.commit().to_hex()))
+            .max()
+            .map(|(_, commitment)| commitment);
+        let mut participating_nodes: Vec<_> = self.success_nodes.keys().copied().collect();
+        participating_nodes.sort_unstable();
+        let mut failed_nodes: Vec<_> = self.failed_nodes.keys().copied().collect();
+        failed_nodes.sort_unstable();
+
+        RoundSummary {
+            outcome,
+            committed_leaf,
+            participating_nodes,
+            failed_nodes,
+            duration_ms: self
+                .duration
+                .map(|duration| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)),
+        }
+    }
+
     /// generate leaves
     #[must_use]
     pub fn gen_leaves(&self) -> HashMap<Leaf2<TYPES>, usize> {
@@ -0,0 +1,251 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+//! Offline safety checks over a recorded trace of consensus events.
+//!
+//! Unlike the [`OverallSafetyTask`](crate::overall_safety_task::OverallSafetyTask), which checks
+//! properties while a test is running, [`check_safety`] replays a [`Trace`] after the fact, so it
+//! can be run against traces recorded anywhere.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use committable::{Commitment, Committable};
+use hotshot_types::{
+    data::Leaf2,
+    event::{Event, EventType},
+    traits::node_implementation::{ConsensusTime, NodeType},
+};
+
+/// A single consensus event observed on one node
+#[derive(Clone, Debug)]
+pub enum TraceEvent<TYPES: NodeType> {
+    /// The node finished a view
+    ViewFinished {
+        /// The node which finished the view
+        node: usize,
+        /// The view which was finished
+        view: TYPES::View,
+    },
+    /// The node committed a leaf
+    Commit {
+        /// The node which committed the leaf
+        node: usize,
+        /// The committed leaf
+        leaf: Leaf2<TYPES>,
+    },
+}
+
+/// A recorded trace of consensus events across all nodes, in the order they were observed
+#[derive(Clone, Debug)]
+pub struct Trace<TYPES: NodeType> {
+    /// The recorded events
+    pub events: Vec<TraceEvent<TYPES>>,
+}
+
+impl<TYPES: NodeType> Default for Trace<TYPES> {
+    fn default() -> Self {
+        Self { events: Vec::new() }
+    }
+}
+
+impl<TYPES: NodeType> Trace<TYPES> {
+    /// Create an empty trace
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the parts of an external event from `node` which are relevant to safety
+    pub fn record(&mut self, node: usize, event: &Event<TYPES>) {
+        match &event.event {
+            EventType::ViewFinished { view_number } => {
+                self.events.push(TraceEvent::ViewFinished {
+                    node,
+                    view: *view_number,
+                });
+            }
+            EventType::Decide { leaf_chain, .. } => {
+                // The leaf chain is sorted newest first, but commits happen oldest first.
+                for leaf_info in leaf_chain.iter().rev() {
+                    self.events.push(TraceEvent::Commit {
+                        node,
+                        leaf: leaf_info.leaf.clone(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A single violated safety invariant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SafetyViolation<TYPES: NodeType> {
+    /// Two nodes committed different leaves for the same view
+    ConflictingCommit {
+        /// The view in which the leaves were committed
+        view: TYPES::View,
+        /// The first node to commit a leaf for this view, and the leaf it committed
+        first: (usize, Commitment<Leaf2<TYPES>>),
+        /// The node that committed a conflicting leaf, and the leaf it committed
+        second: (usize, Commitment<Leaf2<TYPES>>),
+    },
+    /// A committed leaf has a QC which does not reference a valid parent
+    InvalidParent {
+        /// The node which committed the leaf
+        node: usize,
+        /// The view of the committed leaf
+        view: TYPES::View,
+        /// The view referenced by the leaf's QC
+        parent_view: TYPES::View,
+        /// The leaf committed for `parent_view`, if any, which the QC should have referenced
+        expected: Option<Commitment<Leaf2<TYPES>>>,
+        /// The leaf the QC actually references
+        actual: Commitment<Leaf2<TYPES>>,
+    },
+    /// A node's views did not strictly increase
+    NonMonotonicView {
+        /// The offending node
+        node: usize,
+        /// The last view the node finished
+        previous: TYPES::View,
+        /// The view the node finished next
+        next: TYPES::View,
+    },
+}
+
+impl<TYPES: NodeType> fmt::Display for SafetyViolation<TYPES> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConflictingCommit {
+                view,
+                first,
+                second,
+            } => write!(
+                f,
+                "conflicting commits for view {view:?}: node {} committed {}, node {} committed {}",
+                first.0, first.1, second.0, second.1
+            ),
+            Self::InvalidParent {
+                node,
+                view,
+                parent_view,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "node {node} committed a leaf for view {view:?} whose QC references {actual} \
+                 in view {parent_view:?}, but the committed leaf for that view is {expected:?}"
+            ),
+            Self::NonMonotonicView {
+                node,
+                previous,
+                next,
+            } => write!(
+                f,
+                "node {node} finished view {next:?} after already finishing view {previous:?}"
+            ),
+        }
+    }
+}
+
+/// The violations found by [`check_safety`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SafetyReport<TYPES: NodeType> {
+    /// Every violation found, in trace order
+    pub violations: Vec<SafetyViolation<TYPES>>,
+}
+
+impl<TYPES: NodeType> fmt::Display for SafetyReport<TYPES> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} safety violation(s) found:", self.violations.len())?;
+        for violation in &self.violations {
+            write!(f, "\n  - {violation}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<TYPES: NodeType> std::error::Error for SafetyReport<TYPES> {}
+
+/// Replay `trace` and check the core consensus safety invariants:
+///
+/// - no two different leaves are committed for the same view,
+/// - a committed leaf's QC references the leaf committed for the QC's view, if there is one, and
+///   never a view at or after its own,
+/// - the views each node finishes strictly increase.
+///
+/// # Errors
+///
+/// Returns a [`SafetyReport`] listing every violation if any invariant does not hold.
+pub fn check_safety<TYPES: NodeType>(trace: &Trace<TYPES>) -> Result<(), SafetyReport<TYPES>> {
+    let mut violations = Vec::new();
+    let mut committed: BTreeMap<TYPES::View, (usize, Commitment<Leaf2<TYPES>>)> = BTreeMap::new();
+    let mut last_views: HashMap<usize, TYPES::View> = HashMap::new();
+
+    for event in &trace.events {
+        match event {
+            TraceEvent::ViewFinished { node, view } => {
+                if let Some(previous) = last_views.insert(*node, *view) {
+                    if *view <= previous {
+                        violations.push(SafetyViolation::NonMonotonicView {
+                            node: *node,
+                            previous,
+                            next: *view,
+                        });
+                    }
+                }
+            }
+            TraceEvent::Commit { node, leaf } => {
+                let view = leaf.view_number();
+                let commitment = leaf.commit();
+
+                match committed.get(&view) {
+                    Some(first) if first.1 != commitment => {
+                        violations.push(SafetyViolation::ConflictingCommit {
+                            view,
+                            first: *first,
+                            second: (*node, commitment),
+                        });
+                    }
+                    Some(_) => {}
+                    None => {
+                        committed.insert(view, (*node, commitment));
+                    }
+                }
+
+                let qc = leaf.justify_qc();
+                let parent_view = qc.view_number;
+                let actual = qc.data.leaf_commit;
+                let expected = committed
+                    .get(&parent_view)
+                    .map(|(_, commitment)| *commitment);
+                let is_genesis = view == TYPES::View::genesis();
+                if (parent_view >= view && !is_genesis)
+                    || expected.is_some_and(|expected| expected != actual)
+                {
+                    violations.push(SafetyViolation::InvalidParent {
+                        node: *node,
+                        view,
+                        parent_view,
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(SafetyReport { violations })
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::collections::BTreeMap;
+
+use crate::spinning_task::{ChangeNode, NodeAction, SpinningTaskDescription};
+
+/// A script of actions to take at given views of a test, run by the spinning task.
+///
+/// Actions are scripted in view order, and the ones for the same view are taken in the order they
+/// were scripted. The script involves no randomness, so every run of a test takes the same
+/// actions at the same views.
+#[derive(Clone, Debug, Default)]
+pub struct Scenario {
+    /// The node changes to make, by view
+    changes: BTreeMap<u64, Vec<ChangeNode>>,
+    /// The nodes cut off by the current partition
+    partitioned: Vec<usize>,
+    /// The view of the last scripted action
+    last_view: u64,
+}
+
+impl Scenario {
+    /// An empty scenario
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take `action` on each of `nodes` at `view`
+    fn act(&mut self, view: u64, nodes: &[usize], action: &NodeAction) {
+        assert!(
+            view >= self.last_view,
+            "Scenario actions must be scripted in view order, got view {view} after {}",
+            self.last_view
+        );
+        self.last_view = view;
+
+        self.changes
+            .entry(view)
+            .or_default()
+            .extend(nodes.iter().map(|&idx| ChangeNode {
+                idx,
+                updown: action.clone(),
+            }));
+    }
+
+    /// At `view`, partition the nodes `side_a` from the nodes `side_b`, healing any earlier
+    /// partition first.
+    ///
+    /// Only the side with a quorum can make progress, so the smaller side is cut off from the
+    /// network entirely, which consensus can't tell apart from the partition. The nodes of
+    /// `side_b` are cut off on a tie.
+    ///
+    /// # Panics
+    /// If the sides share a node
+    #[must_use]
+    pub fn partition(mut self, view: u64, side_a: &[usize], side_b: &[usize]) -> Self {
+        assert!(
+            side_a.iter().all(|node| !side_b.contains(node)),
+            "Partition sides {side_a:?} and {side_b:?} overlap"
+        );
+
+        self = self.heal(view);
+        let cut_off = if side_a.len() < side_b.len() {
+            side_a
+        } else {
+            side_b
+        };
+        self.act(view, cut_off, &NodeAction::NetworkDown);
+        self.partitioned = cut_off.to_vec();
+
+        self
+    }
+
+    /// At `view`, heal the current partition, if any
+    #[must_use]
+    pub fn heal(mut self, view: u64) -> Self {
+        let partitioned = std::mem::take(&mut self.partitioned);
+        self.act(view, &partitioned, &NodeAction::NetworkUp);
+
+        self
+    }
+
+    /// At `view`, crash `node` for the rest of the test
+    #[must_use]
+    pub fn crash(mut self, view: u64, node: usize) -> Self {
+        self.act(view, &[node], &NodeAction::Down);
+
+        self
+    }
+
+    /// At `view`, take `node` down and start it again `down_views` views later
+    #[must_use]
+    pub fn restart(mut self, view: u64, node: usize, down_views: u64) -> Self {
+        self.act(view, &[node], &NodeAction::RestartDown(down_views));
+
+        self
+    }
+
+    /// At `view`, pause the consensus participation of `node`, which keeps following the chain
+    #[must_use]
+    pub fn pause_participation(mut self, view: u64, node: usize) -> Self {
+        self.act(view, &[node], &NodeAction::PauseParticipation);
+
+        self
+    }
+
+    /// At `view`, resume the consensus participation of `node`
+    #[must_use]
+    pub fn resume_participation(mut self, view: u64, node: usize) -> Self {
+        self.act(view, &[node], &NodeAction::ResumeParticipation);
+
+        self
+    }
+
+    /// At `view`, start `node`, which must not be among the nodes started with the test
+    #[must_use]
+    pub fn start_late(mut self, view: u64, node: usize) -> Self {
+        self.act(view, &[node], &NodeAction::Up);
+
+        self
+    }
+}
+
+impl From<Scenario> for SpinningTaskDescription {
+    fn from(scenario: Scenario) -> Self {
+        Self {
+            node_changes: scenario
+                .changes
+                .into_iter()
+                .filter(|(_, changes)| !changes.is_empty())
+                .collect(),
+        }
+    }
+}
@@ -267,7 +267,7 @@ where
                                     TestRunner::<TYPES, I, V, N>::add_node_with_config_and_channels(
                                         node_id,
                                         generated_network.clone(),
-                                        (*memberships).clone(),
+                                        memberships.read().await.clone(),
                                         initializer,
                                         config,
                                         validator_config,
@@ -316,6 +316,18 @@ where
                                 handle.network.pause();
                             }
                         }
+                        NodeAction::PauseParticipation => {
+                            if let Some(node) = self.handles.read().await.get(idx) {
+                                tracing::error!("Node {} pausing participation", idx);
+                                node.handle.pause_participation();
+                            }
+                        }
+                        NodeAction::ResumeParticipation => {
+                            if let Some(node) = self.handles.read().await.get(idx) {
+                                tracing::error!("Node {} resuming participation", idx);
+                                node.handle.resume_participation().await;
+                            }
+                        }
                     }
                 }
             }
@@ -378,7 +390,7 @@ pub(crate) struct RestartContext<
 }
 
 /// Spin the node up or down
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum NodeAction {
     /// spin the node up
     Up,
@@ -393,10 +405,14 @@ pub enum NodeAction {
     /// Start a node up again after it's been shutdown for restart.  This
     /// should only be created following a `RestartDown`
     RestartUp,
+    /// Pause the node's consensus participation, leaving it running
+    PauseParticipation,
+    /// Resume the node's consensus participation
+    ResumeParticipation,
 }
 
 /// denotes a change in node state
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ChangeNode {
     /// the index of the node
     pub idx: usize,
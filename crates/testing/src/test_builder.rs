@@ -18,8 +18,15 @@ use hotshot_example_types::{
     storage_types::TestStorage, testable_delay::DelayConfig,
 };
 use hotshot_types::{
-    consensus::ConsensusMetricsValue,
-    traits::node_implementation::{NodeType, Versions},
+    consensus::{ConsensusMetricsValue, EarlyVotePolicy, EmptyBlockPolicy, ParticipationMode},
+    constants::{
+        COMMITMENT_CACHE_CAPACITY, DA_VOTE_TIMEOUT, MAX_CLOCK_SKEW, MAX_DA_PAYLOAD_BYTES,
+        SIGNATURE_CACHE_CAPACITY, TARGET_BLOCK_BYTES,
+    },
+    traits::{
+        node_implementation::{NodeType, Versions},
+        storage::CheckpointPolicy,
+    },
     HotShotConfig, ValidatorConfig,
 };
 use tide_disco::Url;
@@ -100,6 +107,9 @@ pub struct TestDescription<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Ver
     pub validate_transactions: TransactionValidator,
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+    /// Whether to run each node started with the test on its own runtime, rather than on the
+    /// harness runtime shared by every node
+    pub isolated_runtimes: bool,
 }
 
 pub fn nonempty_block_threshold(threshold: (u64, u64)) -> TransactionValidator {
@@ -418,6 +428,7 @@ impl<TYPES: NodeType, I: NodeImplementation<TYPES>, V: Versions> Default
             start_solver: true,
             validate_transactions: Arc::new(|_| Ok(())),
             epoch_height: 0,
+            isolated_runtimes: false,
         }
     }
 }
@@ -513,6 +524,31 @@ where
             start_voting_time: u64::MAX,
             stop_voting_time: 0,
             epoch_height,
+            da_storage_retries: 2,
+            da_storage_retry_backoff: Duration::from_millis(1),
+            max_epoch_skip: 1,
+            target_block_bytes: TARGET_BLOCK_BYTES,
+            checkpoint_policy: CheckpointPolicy::default(),
+            task_stall_timeout: Duration::from_secs(60),
+            restart_stalled_tasks: false,
+            warmup_views: 0,
+            network_id: 0,
+            max_message_age_views: 100,
+            max_future_buffer: 8,
+            late_vote_grace: Duration::ZERO,
+            quorum_formation_timeout: None,
+            early_vote_policy: EarlyVotePolicy::default(),
+            empty_block_policy: EmptyBlockPolicy::default(),
+            vid_fault_fraction: 0.0,
+            max_undecided_views: 1000,
+            batch_justify_qcs: false,
+            vote_transition_views: 5,
+            max_da_payload_bytes: MAX_DA_PAYLOAD_BYTES,
+            da_vote_timeout: DA_VOTE_TIMEOUT,
+            max_clock_skew: MAX_CLOCK_SKEW,
+            signature_cache_capacity: SIGNATURE_CACHE_CAPACITY,
+            commitment_cache_capacity: COMMITMENT_CACHE_CAPACITY,
+            participation_mode: ParticipationMode::default(),
         };
         let TimingData {
             next_view_timeout,
@@ -547,6 +583,7 @@ where
                     storage
                 }),
                 config,
+                per_node_config: None,
                 validator_config,
                 marketplace_config: Box::new(|_| MarketplaceConfig::<TYPES, I> {
                     auction_results_provider: TestAuctionResultsProvider::<TYPES>::default().into(),
@@ -36,6 +36,8 @@ pub struct ResourceGenerators<TYPES: NodeType, I: TestableNodeImplementation<TYP
     pub storage: Generator<TestStorage<TYPES>>,
     /// configuration used to generate each hotshot node
     pub config: HotShotConfig<TYPES::SignatureKey>,
+    /// optional per-node configuration, used instead of `config` when set
+    pub per_node_config: Option<Generator<HotShotConfig<TYPES::SignatureKey>>>,
     /// config that contains the signature keys
     pub validator_config: ValidatorConfig<TYPES::SignatureKey>,
     /// generate a new marketplace config for each node
@@ -61,6 +63,7 @@ impl<TYPES: NodeType, I: TestableNodeImplementation<TYPES>, V: Versions> TestLau
             nodes: Vec::new(),
             solver_server: None,
             late_start: HashMap::new(),
+            node_runtimes: HashMap::new(),
             next_node_id: 0,
             _pd: PhantomData,
         }
@@ -74,4 +77,14 @@ impl<TYPES: NodeType, I: TestableNodeImplementation<TYPES>, V: Versions> TestLau
         f(&mut self.resource_generator.config);
         self
     }
+    /// Overrides the config used when generating each node with the output of `f`, allowing
+    /// nodes within a test to be configured differently
+    #[must_use]
+    pub fn with_per_node_config(
+        mut self,
+        f: impl Fn(u64) -> HotShotConfig<TYPES::SignatureKey> + 'static,
+    ) -> Self {
+        self.resource_generator.per_node_config = Some(Box::new(f));
+        self
+    }
 }
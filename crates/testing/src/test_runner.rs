@@ -7,7 +7,9 @@
 #![allow(clippy::panic)]
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
+    future::{poll_fn, Future},
     marker::PhantomData,
+    pin::pin,
     sync::Arc,
 };
 
@@ -40,7 +42,11 @@ use hotshot_types::{
     HotShotConfig, ValidatorConfig,
 };
 use tide_disco::Url;
-use tokio::{spawn, task::JoinHandle};
+use tokio::{
+    runtime::{Builder, Handle, Runtime},
+    spawn,
+    task::JoinHandle,
+};
 #[allow(deprecated)]
 use tracing::info;
 
@@ -61,6 +67,12 @@ use crate::{
     view_sync_task::ViewSyncTask,
 };
 
+/// Number of worker threads in the runtime of each node, when nodes run on isolated runtimes
+pub const ISOLATED_RUNTIME_WORKER_THREADS: usize = 2;
+
+/// Name of the worker threads of each node's runtime, when nodes run on isolated runtimes
+pub const NODE_RUNTIME_THREAD_NAME: &str = "hotshot-node-runtime";
+
 pub trait TaskErr: std::error::Error + Sync + Send + 'static {}
 impl<T: std::error::Error + Sync + Send + 'static> TaskErr for T {}
 
@@ -133,6 +145,7 @@ where
             nodes,
             solver_server,
             late_start,
+            node_runtimes,
             next_node_id: _,
             _pd: _,
         } = self;
@@ -252,7 +265,11 @@ where
         // Start hotshot
         for node in &*nodes {
             if !late_start_nodes.contains(&node.node_id) {
-                node.handle.hotshot.start_consensus().await;
+                on_node_runtime(
+                    node_runtimes.get(&node.node_id),
+                    node.handle.hotshot.start_consensus(),
+                )
+                .await;
             }
         }
 
@@ -311,6 +328,7 @@ where
         tracing::info!("Nodes shutdown");
 
         completion_handle.abort();
+        drop(node_runtimes);
 
         assert!(
             error_list.is_empty(),
@@ -411,12 +429,15 @@ where
         let mut networks_ready = Vec::new();
 
         for i in 0..total {
-            let mut config = config.clone();
+            let node_id = self.next_node_id;
+            self.next_node_id += 1;
+            let mut config = match &self.launcher.resource_generator.per_node_config {
+                Some(per_node_config) => per_node_config(node_id),
+                None => config.clone(),
+            };
             if let Some(upgrade_view) = self.launcher.metadata.upgrade_view {
                 config.set_view_upgrade(upgrade_view);
             }
-            let node_id = self.next_node_id;
-            self.next_node_id += 1;
             tracing::debug!("launch node {}", i);
 
             let memberships = <TYPES as NodeType>::Membership::new(
@@ -534,16 +555,28 @@ where
         for (node_id, network, memberships, config, storage, marketplace_config) in
             uninitialized_nodes
         {
-            let handle = create_test_handle(
-                self.launcher.metadata.clone(),
-                node_id,
-                network.clone(),
-                memberships,
-                config.clone(),
-                storage,
-                marketplace_config,
+            let runtime = self
+                .launcher
+                .metadata
+                .isolated_runtimes
+                .then(NodeRuntime::new);
+            // Everything the node spawns while starting up runs on its own runtime.
+            let handle = on_node_runtime(
+                runtime.as_ref(),
+                create_test_handle(
+                    self.launcher.metadata.clone(),
+                    node_id,
+                    network.clone(),
+                    memberships,
+                    config.clone(),
+                    storage,
+                    marketplace_config,
+                ),
             )
             .await;
+            if let Some(runtime) = runtime {
+                self.node_runtimes.insert(node_id, runtime);
+            }
 
             match node_id.cmp(&(config.da_staked_committee_size as u64 - 1)) {
                 std::cmp::Ordering::Less => {
@@ -643,6 +676,78 @@ where
     }
 }
 
+/// A runtime dedicated to a single node, modelling the node running on its own machine.
+///
+/// The runtime is shut down without waiting for its tasks when dropped, so it may be dropped from
+/// within an async context.
+pub struct NodeRuntime {
+    /// The runtime, only taken when it is dropped
+    runtime: Option<Runtime>,
+    /// A handle to the runtime
+    handle: Handle,
+}
+
+impl NodeRuntime {
+    /// Create a runtime with [`ISOLATED_RUNTIME_WORKER_THREADS`] worker threads
+    /// # Panics
+    /// if the runtime cannot be created
+    #[must_use]
+    pub fn new() -> Self {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(ISOLATED_RUNTIME_WORKER_THREADS)
+            .thread_name(NODE_RUNTIME_THREAD_NAME)
+            .enable_all()
+            .build()
+            .expect("Failed to build node runtime");
+        let handle = runtime.handle().clone();
+
+        Self {
+            runtime: Some(runtime),
+            handle,
+        }
+    }
+
+    /// Get a handle to the runtime, which can be used to spawn tasks onto it
+    #[must_use]
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Drive `future` to completion with this runtime entered, so that the tasks it spawns run on
+    /// this runtime. The runtime is only entered for the duration of each poll, so no guard is held
+    /// across an `.await`.
+    pub async fn run<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        poll_fn(|cx| {
+            let _runtime = self.handle.enter();
+            future.as_mut().poll(cx)
+        })
+        .await
+    }
+}
+
+/// Drive `future` on the node's runtime if it has one, and on the current runtime otherwise
+async fn on_node_runtime<F: Future>(runtime: Option<&NodeRuntime>, future: F) -> F::Output {
+    match runtime {
+        Some(runtime) => runtime.run(future).await,
+        None => future.await,
+    }
+}
+
+impl Default for NodeRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for NodeRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
 /// a node participating in a test
 pub struct Node<TYPES: NodeType, I: TestableNodeImplementation<TYPES>, V: Versions> {
     /// The node's unique identifier
@@ -708,6 +813,8 @@ pub struct TestRunner<
     pub(crate) solver_server: Option<(Url, JoinHandle<()>)>,
     /// nodes with a late start
     pub(crate) late_start: HashMap<u64, LateStartNode<TYPES, I, V>>,
+    /// the runtimes of nodes running on isolated runtimes
+    pub(crate) node_runtimes: HashMap<u64, NodeRuntime>,
     /// the next node unique identifier
     pub(crate) next_node_id: u64,
     /// Phantom for N
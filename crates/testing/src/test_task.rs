@@ -15,10 +15,16 @@ use hotshot::{
     traits::TestableNodeImplementation,
     types::{Event, Message},
 };
-use hotshot_task_impls::{events::HotShotEvent, network::NetworkMessageTaskState};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    network::{strip_network_id, NetworkMessageTaskState, ReceivedJustifyQcs},
+};
 use hotshot_types::{
+    consensus::{ConsensusMetricsValue, OuterConsensus},
+    constants::MAX_MESSAGE_AGE_VIEWS,
     message::UpgradeLock,
     traits::{
+        block_contents::AcceptAllTxnValidator,
         network::ConnectedNetwork,
         node_implementation::{NodeType, Versions},
     },
@@ -160,8 +166,10 @@ pub async fn add_network_message_test_task<
     internal_event_stream: Sender<Arc<HotShotEvent<TYPES>>>,
     external_event_stream: Sender<Event<TYPES>>,
     upgrade_lock: UpgradeLock<TYPES, V>,
+    network_id: u64,
     channel: Arc<NET>,
     public_key: TYPES::SignatureKey,
+    consensus: OuterConsensus<TYPES>,
 ) -> JoinHandle<()> {
     let net = Arc::clone(&channel);
     let network_state: NetworkMessageTaskState<_> = NetworkMessageTaskState {
@@ -169,6 +177,11 @@ pub async fn add_network_message_test_task<
         external_event_stream: external_event_stream.clone(),
         public_key,
         transactions_cache: lru::LruCache::new(NonZeroUsize::new(100_000).unwrap()),
+        transaction_validator: Arc::new(AcceptAllTxnValidator),
+        metrics: Arc::new(ConsensusMetricsValue::default()),
+        consensus,
+        max_message_age_views: MAX_MESSAGE_AGE_VIEWS,
+        received_justify_qcs: ReceivedJustifyQcs::default(),
     };
 
     let network = Arc::clone(&net);
@@ -185,15 +198,24 @@ pub async fn add_network_message_test_task<
                 }
             };
 
+            // Drop messages from other networks before decoding them
+            let message = match strip_network_id(network_id, &message) {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::debug!("{e}");
+                    continue;
+                }
+            };
+
             // Deserialize the message
-            let deserialized_message: Message<TYPES> =
-                match upgrade_lock.deserialize(&message).await {
-                    Ok(message) => message,
-                    Err(e) => {
-                        tracing::error!("Failed to deserialize message: {:?}", e);
-                        continue;
-                    }
-                };
+            let deserialized_message: Message<TYPES> = match upgrade_lock.deserialize(message).await
+            {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::error!("Failed to deserialize message: {:?}", e);
+                    continue;
+                }
+            };
 
             // Handle the message
             state.handle_message(deserialized_message).await;
@@ -26,7 +26,7 @@ use hotshot_types::{
         ViewChangeEvidence, ViewNumber,
     },
     drb::{INITIAL_DRB_RESULT, INITIAL_DRB_SEED_INPUT},
-    message::{Proposal, UpgradeLock},
+    message::{quorum_proposal_signing_bytes, Proposal, UpgradeLock},
     simple_certificate::{
         DaCertificate, QuorumCertificate, QuorumCertificate2, TimeoutCertificate,
         UpgradeCertificate, ViewSyncFinalizeCertificate2,
@@ -37,6 +37,7 @@ use hotshot_types::{
     },
     traits::{
         consensus_api::ConsensusApi,
+        election::Membership,
         node_implementation::{ConsensusTime, NodeType},
         BlockPayload,
     },
@@ -158,9 +159,14 @@ impl TestView {
             view_number: genesis_view,
         };
 
+        let leader_proof = membership
+            .leader_proof(genesis_view, genesis_epoch, &private_key)
+            .expect("Failed to prove the genesis leader");
+
         let da_proposal = Proposal {
             data: da_proposal_inner,
             signature: block_payload_signature,
+            leader_proof: leader_proof.clone(),
             _pd: PhantomData,
         };
 
@@ -169,12 +175,19 @@ impl TestView {
             transactions: transactions.clone(),
         });
 
-        let signature = <BLSPubKey as SignatureKey>::sign(&private_key, leaf.commit().as_ref())
-            .expect("Failed to sign leaf commitment!");
+        let signature = <BLSPubKey as SignatureKey>::sign(
+            &private_key,
+            &quorum_proposal_signing_bytes::<TestTypes, TestVersions>(
+                &quorum_proposal_inner,
+                upgrade_lock.version_infallible(genesis_view).await,
+            ),
+        )
+        .expect("Failed to sign leaf commitment!");
 
         let quorum_proposal = Proposal {
             data: quorum_proposal_inner,
             signature,
+            leader_proof,
             _pd: PhantomData,
         };
 
@@ -378,12 +391,22 @@ impl TestView {
             transactions: transactions.clone(),
         });
 
-        let signature = <BLSPubKey as SignatureKey>::sign(&private_key, leaf.commit().as_ref())
-            .expect("Failed to sign leaf commitment.");
+        let signature = <BLSPubKey as SignatureKey>::sign(
+            &private_key,
+            &quorum_proposal_signing_bytes::<TestTypes, TestVersions>(
+                &proposal,
+                self.upgrade_lock.version_infallible(next_view).await,
+            ),
+        )
+        .expect("Failed to sign leaf commitment.");
+        let leader_proof = membership
+            .leader_proof(next_view, self.epoch_number, &private_key)
+            .expect("Failed to prove the leader");
 
         let quorum_proposal = Proposal {
             data: proposal,
             signature,
+            leader_proof: leader_proof.clone(),
             _pd: PhantomData,
         };
 
@@ -402,6 +425,7 @@ impl TestView {
         let da_proposal = Proposal {
             data: da_proposal_inner,
             signature: block_payload_signature,
+            leader_proof,
             _pd: PhantomData,
         };
 
@@ -444,6 +468,7 @@ impl TestView {
                 leaf_commit: self.leaf.commit(),
             },
             self.view_number,
+            self.epoch_number,
             &handle.public_key(),
             handle.private_key(),
             &handle.hotshot.upgrade_lock,
@@ -460,6 +485,7 @@ impl TestView {
         UpgradeVote::<TestTypes>::create_signed_vote(
             data,
             self.view_number,
+            self.epoch_number,
             &handle.public_key(),
             handle.private_key(),
             &handle.hotshot.upgrade_lock,
@@ -476,6 +502,7 @@ impl TestView {
         DaVote::create_signed_vote(
             data,
             self.view_number,
+            self.epoch_number,
             &handle.public_key(),
             handle.private_key(),
             &handle.hotshot.upgrade_lock,
@@ -0,0 +1,120 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use either::Either;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::VoteAccumulator,
+    vote_metrics::VoteRejection,
+    ValidatorConfig,
+};
+
+/// The accumulator type under test
+type DaAccumulator =
+    VoteAccumulator<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions>;
+
+/// An empty accumulator
+fn accumulator(upgrade_lock: &UpgradeLock<TestTypes, TestVersions>) -> DaAccumulator {
+    VoteAccumulator {
+        vote_outcomes: HashMap::new(),
+        signers: HashMap::new(),
+        phantom: PhantomData,
+        upgrade_lock: upgrade_lock.clone(),
+        metrics: Arc::default(),
+        on_certificate: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_batch_forms_the_same_certificate_as_single_votes() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..10)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+
+    let mut votes = Vec::new();
+    for validator in &validators {
+        let vote = DaVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        votes.push(vote);
+    }
+
+    // A vote for another view, a vote whose signature is not its signer's, and a duplicate go
+    // ahead of the valid votes
+    let mut wrong_view = votes[0].clone();
+    wrong_view.view_number = ViewNumber::new(2);
+    let mut forged = votes[1].clone();
+    forged.signature.1 = votes[2].signature.1.clone();
+    let batch: Vec<_> = [wrong_view, forged, votes[3].clone()]
+        .into_iter()
+        .chain(votes.iter().cloned())
+        .collect();
+
+    let mut single = accumulator(&upgrade_lock);
+    let mut single_cert = None;
+    for vote in &batch {
+        if let Either::Right(cert) = single.accumulate(vote, view, &membership, epoch).await {
+            single_cert = Some(cert);
+            break;
+        }
+    }
+    let single_cert = single_cert.unwrap();
+
+    let mut batched = accumulator(&upgrade_lock);
+    let batch_cert = batched
+        .accumulate_batch(batch, view, &membership, epoch)
+        .await
+        .unwrap_right();
+    assert_eq!(batch_cert, single_cert);
+
+    // Both rejected the same votes for the same reasons
+    let (single, batched) = (single.metrics.export(), batched.metrics.export());
+    assert_eq!(batched.rejections, single.rejections);
+    assert_eq!(batched.votes_verified, single.votes_verified);
+    assert_eq!(batched.rejected(VoteRejection::WrongView), 1);
+    assert_eq!(batched.rejected(VoteRejection::InvalidSignature), 1);
+    assert_eq!(batched.rejected(VoteRejection::Duplicate), 1);
+    assert_eq!(batched.certificates_formed, 1);
+
+    // Too few votes leave the accumulator waiting for more
+    let mut short = accumulator(&upgrade_lock);
+    assert!(short
+        .accumulate_batch(votes[..2].to_vec(), view, &membership, epoch)
+        .await
+        .is_left());
+}
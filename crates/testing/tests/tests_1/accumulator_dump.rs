@@ -0,0 +1,90 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{Certificate, VoteAccumulator},
+    ValidatorConfig,
+};
+use primitive_types::U256;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dump_lists_signers_and_remaining_stake() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..5)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let mut accumulator =
+        VoteAccumulator::<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions> {
+            vote_outcomes: HashMap::new(),
+            signers: HashMap::new(),
+            phantom: PhantomData,
+            upgrade_lock: upgrade_lock.clone(),
+            metrics: Arc::default(),
+            on_certificate: None,
+        };
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+
+    let voters = [3, 1];
+    for voter in voters {
+        let vote = DaVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validators[voter].public_key,
+            &validators[voter].private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        assert!(accumulator
+            .accumulate(&vote, view, &membership, epoch)
+            .await
+            .is_left());
+    }
+
+    let dump = accumulator.dump(&membership, view, epoch);
+    let threshold = U256::from(DaCertificate::<TestTypes>::threshold(&membership, epoch));
+    assert_eq!(dump.threshold, threshold);
+    assert_eq!(dump.tallies.len(), 1);
+
+    let tally = &dump.tallies[0];
+    let mut signers: Vec<_> = voters
+        .iter()
+        .map(|&voter| validators[voter].public_key)
+        .collect();
+    signers.sort();
+    assert_eq!(tally.signers, signers);
+    assert_eq!(tally.stake, U256::from(voters.len()));
+    assert_eq!(tally.remaining_stake, threshold - U256::from(voters.len()));
+
+    // Dumping again sees the same votes, so the first dump changed nothing.
+    assert_eq!(accumulator.dump(&membership, view, epoch), dump);
+    assert_eq!(accumulator.vote_outcomes.len(), 1);
+}
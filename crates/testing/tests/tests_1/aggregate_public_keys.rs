@@ -0,0 +1,61 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use bitvec::bitvec;
+use hotshot::types::{BLSPrivKey, BLSPubKey, SignatureKey};
+use hotshot_types::signature_key::aggregate_public_keys;
+use primitive_types::U256;
+
+/// The keys of the nodes `ids`
+fn keys(ids: std::ops::Range<u64>) -> Vec<(BLSPubKey, BLSPrivKey)> {
+    ids.map(|id| BLSPubKey::generated_from_seed_indexed([0u8; 32], id))
+        .collect()
+}
+
+/// `message` signed by every key of `keys`, with the signatures aggregated
+fn sign_by_all(
+    keys: &[(BLSPubKey, BLSPrivKey)],
+    message: &[u8],
+) -> <BLSPubKey as SignatureKey>::PureAssembledSignatureType {
+    let entries = keys
+        .iter()
+        .map(|(key, _)| key.stake_table_entry(1))
+        .collect();
+    let params = BLSPubKey::public_parameter(entries, U256::from(keys.len()));
+    let signatures: Vec<_> = keys
+        .iter()
+        .map(|(_, private_key)| BLSPubKey::sign(private_key, message).unwrap())
+        .collect();
+
+    BLSPubKey::assemble(&params, &bitvec![1; keys.len()], &signatures).0
+}
+
+#[test]
+fn test_aggregate_key_verifies_signatures_of_its_set() {
+    let message = b"committee for epoch 7";
+    let committee = keys(0..4);
+    let others = keys(4..8);
+
+    let aggregate =
+        aggregate_public_keys(&committee.iter().map(|(key, _)| *key).collect::<Vec<_>>()).unwrap();
+
+    // A message signed by the whole set verifies against the aggregate
+    assert!(aggregate.verify(message, &sign_by_all(&committee, message)));
+
+    // One signed by a different set, or by only part of the set, does not
+    assert!(!aggregate.verify(message, &sign_by_all(&others, message)));
+    assert!(!aggregate.verify(message, &sign_by_all(&committee[..3], message)));
+
+    // Neither does the set's signature on another message
+    assert!(!aggregate.verify(b"committee for epoch 8", &sign_by_all(&committee, message)));
+
+    // The aggregate of a single key is that key, and there is no aggregate of no keys
+    assert_eq!(
+        aggregate_public_keys(&[committee[0].0]).unwrap().key(),
+        committee[0].0
+    );
+    assert!(aggregate_public_keys(&[]).is_err());
+}
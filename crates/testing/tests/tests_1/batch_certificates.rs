@@ -0,0 +1,88 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use futures::StreamExt;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    data::{Leaf2, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::QuorumCertificate2,
+    traits::node_implementation::{ConsensusTime, NodeType},
+};
+
+/// Generate a chain of five leaves, each with the QC which certifies it
+async fn certified_chain() -> (
+    <TestTypes as NodeType>::Membership,
+    Vec<QuorumCertificate2<TestTypes>>,
+    Vec<Leaf2<TestTypes>>,
+) {
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let views = TestViewGenerator::generate(membership.clone())
+        .take(6)
+        .collect::<Vec<_>>()
+        .await;
+    // The QC for each view's leaf justifies the proposal for the following view.
+    let certs = views[1..]
+        .iter()
+        .map(|view| view.quorum_proposal.data.justify_qc.clone())
+        .collect();
+    let leaves = views[..5].iter().map(|view| view.leaf.clone()).collect();
+
+    (membership, certs, leaves)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_valid_certificate_chain_is_accepted() {
+    hotshot::helpers::initialize_logging();
+
+    let (membership, certs, leaves) = certified_chain().await;
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+
+    assert_eq!(
+        QuorumCertificate2::verify_batch_certificates(&certs, &leaves, &membership, &upgrade_lock)
+            .await,
+        Ok(())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_broken_link_reports_its_index() {
+    hotshot::helpers::initialize_logging();
+
+    let (membership, mut certs, mut leaves) = certified_chain().await;
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+
+    // Dropping the third leaf leaves a correctly certified leaf at index 2 whose parent is
+    // missing from the chain.
+    certs.remove(2);
+    leaves.remove(2);
+    assert_eq!(
+        QuorumCertificate2::verify_batch_certificates(&certs, &leaves, &membership, &upgrade_lock)
+            .await,
+        Err(2)
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_invalid_signature_reports_its_index() {
+    hotshot::helpers::initialize_logging();
+
+    let (membership, mut certs, leaves) = certified_chain().await;
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+
+    // The signature no longer covers the certificate once its view is changed.
+    certs[3].view_number = ViewNumber::new(100);
+    assert_eq!(
+        QuorumCertificate2::verify_batch_certificates(&certs, &leaves, &membership, &upgrade_lock)
+            .await,
+        Err(3)
+    );
+}
@@ -98,9 +98,7 @@ async fn test_random_block_builder() {
             &TestBlockPayload {
                 transactions: vec![TestTransaction::new(vec![0; 1])],
             },
-            &TestMetadata {
-                num_transactions: 1,
-            },
+            &TestMetadata::new(&[TestTransaction::new(vec![0; 1])]),
         );
 
     let result = client
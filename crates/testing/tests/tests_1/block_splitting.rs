@@ -0,0 +1,105 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    block_types::TestTransaction,
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    transactions::{BlockContents, ProposalSource, TransactionTaskState},
+};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    data::ViewNumber,
+    traits::{
+        block_contents::Transaction, election::Membership, node_implementation::ConsensusTime,
+    },
+};
+
+/// Mempool which hands out its oldest transactions, up to the size asked for, and keeps the rest
+struct Mempool(Mutex<VecDeque<TestTransaction>>);
+
+#[async_trait]
+impl ProposalSource<TestTypes> for Mempool {
+    async fn next_block(&self, _view: ViewNumber, max_bytes: u64) -> BlockContents<TestTypes> {
+        let mut mempool = self.0.lock().unwrap();
+        let mut block_bytes = 0;
+        let mut transactions = Vec::new();
+        while let Some(transaction) = mempool.front() {
+            block_bytes += transaction.minimum_block_size();
+            if block_bytes > max_bytes {
+                break;
+            }
+            transactions.extend(mempool.pop_front());
+        }
+
+        BlockContents { transactions }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_oversized_transaction_set_spans_three_proposals() {
+    hotshot::helpers::initialize_logging();
+
+    const TXN_BYTES: usize = 100;
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    let mut state =
+        TransactionTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let epoch = state.cur_epoch;
+
+    // 2.5x the target, with every transaction distinct so loss or duplication is detectable.
+    let submitted: Vec<_> = (0..10u8)
+        .map(|i| TestTransaction::new(vec![i; TXN_BYTES]))
+        .collect();
+    let target_block_bytes = submitted[..4]
+        .iter()
+        .map(Transaction::minimum_block_size)
+        .sum();
+    state.target_block_bytes = target_block_bytes;
+    state.proposal_source = Some(Arc::new(Mempool(Mutex::new(submitted.clone().into()))));
+
+    let membership_reader = state.membership.read().await;
+    let led_views: Vec<_> = (2..)
+        .map(ViewNumber::new)
+        .filter(|view| membership_reader.leader(*view, epoch).unwrap() == handle.public_key())
+        .take(3)
+        .collect();
+    drop(membership_reader);
+    let mut proposed = Vec::new();
+    for view in led_views {
+        state
+            .handle(Arc::new(HotShotEvent::ViewChange(view, epoch)), tx.clone())
+            .await
+            .unwrap();
+        let event = rx.try_recv().unwrap();
+        let HotShotEvent::BlockRecv(bundle) = event.as_ref() else {
+            panic!("Expected a block, got {event:?}");
+        };
+        assert_eq!(bundle.view_number, view);
+        proposed.push(bundle.encoded_transactions.clone());
+    }
+
+    // Each proposal takes up where the last one stopped, so every transaction is proposed once.
+    assert_eq!(
+        proposed,
+        submitted
+            .chunks(4)
+            .map(|block| Arc::from(TestTransaction::encode(block)))
+            .collect::<Vec<Arc<[u8]>>>()
+    );
+}
@@ -0,0 +1,88 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{HasViewNumber, VoteAccumulator},
+    ValidatorConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_certificate_callback_fires_once_when_threshold_is_crossed() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let mut accumulator =
+        VoteAccumulator::<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions> {
+            vote_outcomes: HashMap::new(),
+            signers: HashMap::new(),
+            phantom: PhantomData,
+            upgrade_lock: upgrade_lock.clone(),
+            metrics: Arc::default(),
+            on_certificate: None,
+        };
+
+    let formed = Arc::new(Mutex::new(Vec::new()));
+    accumulator.on_certificate({
+        let formed = Arc::clone(&formed);
+        move |cert: &DaCertificate<TestTypes>| formed.lock().unwrap().push(cert.view_number())
+    });
+
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+    let threshold = membership.da_success_threshold(epoch).get();
+    for (count, validator) in (1..).zip(&validators) {
+        let vote = DaVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        let result = accumulator
+            .accumulate(&vote, view, &membership, epoch)
+            .await;
+
+        // The callback fires with the certificate as it is returned, and only then
+        assert_eq!(result.is_right(), count == threshold);
+        let expected = if count < threshold {
+            vec![]
+        } else {
+            vec![view]
+        };
+        assert_eq!(*formed.lock().unwrap(), expected);
+    }
+}
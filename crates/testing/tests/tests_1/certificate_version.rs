@@ -0,0 +1,79 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_testing::helpers::build_cert;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::ViewSyncPreCommitCertificate2,
+    simple_vote::{ViewSyncPreCommitData, ViewSyncPreCommitVote},
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType, Versions},
+    },
+    ValidatorConfig,
+};
+use vbs::{version::StaticVersion, BinarySerializer, Serializer};
+
+/// A pre-commit certificate for view 5, signed by a committee of four
+async fn certificate(
+    upgrade_lock: &UpgradeLock<TestTypes, TestVersions>,
+) -> ViewSyncPreCommitCertificate2<TestTypes> {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let round = ViewNumber::new(5);
+
+    build_cert::<
+        TestTypes,
+        TestVersions,
+        ViewSyncPreCommitData<TestTypes>,
+        ViewSyncPreCommitVote<TestTypes>,
+        ViewSyncPreCommitCertificate2<TestTypes>,
+    >(
+        ViewSyncPreCommitData { relay: 0, round },
+        &membership,
+        round,
+        EpochNumber::new(0),
+        &validators[0].public_key,
+        &validators[0].private_key,
+        upgrade_lock,
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_certificate_with_unexpected_version_is_rejected() {
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let certificate = certificate(&upgrade_lock).await;
+    let bytes = upgrade_lock.serialize(&certificate).await.unwrap();
+
+    let decoded: ViewSyncPreCommitCertificate2<TestTypes> =
+        upgrade_lock.deserialize(&bytes).await.unwrap();
+    assert_eq!(decoded, certificate);
+
+    // A version this node does not know at all
+    let unknown = Serializer::<StaticVersion<{ u16::MAX }, 0>>::serialize(&certificate).unwrap();
+    assert!(upgrade_lock
+        .deserialize::<ViewSyncPreCommitCertificate2<TestTypes>>(&unknown)
+        .await
+        .is_err());
+
+    // A version this node knows, but which is not in effect yet since no upgrade was decided
+    let upgraded =
+        Serializer::<<TestVersions as Versions>::Upgrade>::serialize(&certificate).unwrap();
+    assert!(upgrade_lock
+        .deserialize::<ViewSyncPreCommitCertificate2<TestTypes>>(&upgraded)
+        .await
+        .is_err());
+}
@@ -0,0 +1,80 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::atomic::Ordering;
+
+use async_lock::RwLock;
+use hotshot_example_types::{
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    storage_types::TestStorage,
+};
+use hotshot_task_impls::helpers::checkpoint_undecided_state;
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{consensus::OuterConsensus, traits::storage::CheckpointPolicy};
+
+/// Count the writes `TestStorage` receives over views `1..=20` under `policy`, where every other
+/// view decides a new leaf
+async fn storage_writes_over_20_views(policy: CheckpointPolicy) -> usize {
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    let consensus = OuterConsensus::new(handle.hotshot.consensus());
+    let storage = RwLock::new(TestStorage::<TestTypes>::default());
+
+    for view in 1..=20u64 {
+        if policy.checkpoint_on_view(view) {
+            checkpoint_undecided_state(&consensus, &storage)
+                .await
+                .unwrap();
+        }
+
+        let decided = view % 2 == 0;
+        if decided && policy.checkpoint_on_decide() {
+            checkpoint_undecided_state(&consensus, &storage)
+                .await
+                .unwrap();
+        }
+    }
+
+    storage
+        .read()
+        .await
+        .undecided_state_writes
+        .load(Ordering::SeqCst)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_checkpoint_policy_write_frequency() {
+    hotshot::helpers::initialize_logging();
+
+    assert_eq!(
+        storage_writes_over_20_views(CheckpointPolicy::EveryView).await,
+        20
+    );
+    assert_eq!(
+        storage_writes_over_20_views(CheckpointPolicy::EveryNViews(5)).await,
+        4
+    );
+    assert_eq!(
+        storage_writes_over_20_views(CheckpointPolicy::EveryNViews(1)).await,
+        20
+    );
+    assert_eq!(
+        storage_writes_over_20_views(CheckpointPolicy::OnDecideOnly).await,
+        10
+    );
+
+    // A zero interval is treated as checkpointing every view, rather than dividing by zero.
+    assert_eq!(
+        storage_writes_over_20_views(CheckpointPolicy::EveryNViews(0)).await,
+        20
+    );
+}
+
+#[test]
+fn test_checkpoint_policy_defaults_to_every_view() {
+    assert_eq!(CheckpointPolicy::default(), CheckpointPolicy::EveryView);
+}
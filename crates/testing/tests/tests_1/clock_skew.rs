@@ -0,0 +1,197 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    state_types::TestValidatedState,
+};
+use hotshot_macros::{run_test, test_scripts};
+use hotshot_task_impls::{
+    events::HotShotEvent::*, quorum_proposal_recv::QuorumProposalRecvTaskState,
+};
+use hotshot_testing::{
+    helpers::build_system_handle,
+    predicates::event::exact,
+    script::{Expectations, InputOrder, TaskScript},
+    serial,
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::{
+    consensus::ConsensusMetricsValue,
+    constants::MAX_CLOCK_SKEW,
+    data::{EpochNumber, Leaf2, ViewNumber},
+    traits::{
+        metrics::{
+            Counter, CounterFamily, Gauge, GaugeFamily, Histogram, HistogramFamily, Metrics,
+            NoMetrics, TextFamily,
+        },
+        node_implementation::ConsensusTime,
+    },
+};
+
+/// How often each counter was incremented, by name
+type Counts = Arc<Mutex<HashMap<String, usize>>>;
+
+/// Metrics which record how often each counter is incremented, and discard everything else
+#[derive(Clone, Debug, Default)]
+struct CountingMetrics {
+    /// The counts of every counter created so far
+    counts: Counts,
+}
+
+impl CountingMetrics {
+    /// How often the counter `name` was incremented
+    fn count(&self, name: &str) -> usize {
+        self.counts
+            .lock()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// A counter recording into the counts of [`CountingMetrics`]
+#[derive(Clone, Debug)]
+struct CountingCounter {
+    /// Name of the counter
+    name: String,
+    /// Where increments are recorded
+    counts: Counts,
+}
+
+impl Counter for CountingCounter {
+    fn add(&self, amount: usize) {
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry(self.name.clone())
+            .or_default() += amount;
+    }
+}
+
+impl Metrics for CountingMetrics {
+    fn create_counter(&self, name: String, _: Option<String>) -> Box<dyn Counter> {
+        Box::new(CountingCounter {
+            name,
+            counts: Arc::clone(&self.counts),
+        })
+    }
+
+    fn create_gauge(&self, name: String, unit_label: Option<String>) -> Box<dyn Gauge> {
+        NoMetrics.create_gauge(name, unit_label)
+    }
+
+    fn create_histogram(&self, name: String, unit_label: Option<String>) -> Box<dyn Histogram> {
+        NoMetrics.create_histogram(name, unit_label)
+    }
+
+    fn create_text(&self, name: String) {
+        NoMetrics.create_text(name);
+    }
+
+    fn counter_family(&self, name: String, labels: Vec<String>) -> Box<dyn CounterFamily> {
+        NoMetrics.counter_family(name, labels)
+    }
+
+    fn gauge_family(&self, name: String, labels: Vec<String>) -> Box<dyn GaugeFamily> {
+        NoMetrics.gauge_family(name, labels)
+    }
+
+    fn histogram_family(&self, name: String, labels: Vec<String>) -> Box<dyn HistogramFamily> {
+        NoMetrics.histogram_family(name, labels)
+    }
+
+    fn text_family(&self, name: String, labels: Vec<String>) -> Box<dyn TextFamily> {
+        NoMetrics.text_family(name, labels)
+    }
+
+    fn subgroup(&self, _: String) -> Box<dyn Metrics> {
+        Box::new(self.clone())
+    }
+}
+
+/// Feed the proposal for view 2 to the quorum proposal recv task, allowing `max_clock_skew`, and
+/// return how many proposals were flagged for their timestamp. The generated header is stamped
+/// with its view number as the time, i.e. decades in the past.
+async fn flagged_proposals(max_clock_skew: Duration) -> usize {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let metrics = CountingMetrics::default();
+    let consensus = handle.hotshot.consensus();
+    let mut consensus_writer = consensus.write().await;
+    consensus_writer.metrics = Arc::new(ConsensusMetricsValue::new(&metrics));
+
+    let mut generator = TestViewGenerator::generate(membership);
+    let mut proposals = Vec::new();
+    let mut leaders = Vec::new();
+    let mut leaves = Vec::new();
+    for view in (&mut generator).take(2).collect::<Vec<_>>().await {
+        proposals.push(view.quorum_proposal.clone());
+        leaders.push(view.leader_public_key);
+        leaves.push(view.leaf.clone());
+
+        consensus_writer
+            .update_leaf(
+                Leaf2::from_quorum_proposal(&view.quorum_proposal.data),
+                Arc::new(TestValidatedState::default()),
+                None,
+            )
+            .unwrap();
+    }
+    drop(consensus_writer);
+
+    let inputs = vec![serial![QuorumProposalRecv(
+        proposals[1].clone(),
+        leaders[1]
+    )]];
+
+    // The proposal is processed on its view regardless of its timestamp
+    let expectations = vec![Expectations::from_outputs(vec![
+        exact(QuorumProposalPreliminarilyValidated(proposals[1].clone())),
+        exact(QuorumProposalValidated(
+            proposals[1].clone(),
+            leaves[0].clone(),
+        )),
+        exact(ViewChange(ViewNumber::new(2), EpochNumber::new(0))),
+    ])];
+
+    let mut state =
+        QuorumProposalRecvTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle)
+            .await;
+    state.max_clock_skew = max_clock_skew;
+    let mut script = TaskScript {
+        timeout: Duration::from_millis(35),
+        state,
+        expectations,
+    };
+    run_test![inputs, script].await;
+
+    metrics.count("clock_skewed_proposals")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_skewed_proposal_is_flagged_and_still_processed() {
+    assert_eq!(flagged_proposals(MAX_CLOCK_SKEW).await, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_proposal_within_allowed_skew_is_not_flagged() {
+    assert_eq!(flagged_proposals(Duration::MAX).await, 0);
+}
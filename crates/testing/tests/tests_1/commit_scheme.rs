@@ -0,0 +1,65 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use committable::Committable;
+use futures::StreamExt;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    commit_scheme::{CommitScheme, DefaultCommitScheme, RescueCommitScheme},
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_vote::{QuorumData2, VersionedVoteData},
+    traits::node_implementation::ConsensusTime,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_commit_schemes_are_distinct_and_self_consistent() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let mut generator = TestViewGenerator::generate(membership);
+    let leaf = generator.next().await.unwrap().leaf;
+    let other_leaf = generator.next().await.unwrap().leaf;
+
+    // The test types keep the default scheme
+    let default = DefaultCommitScheme::commit(&leaf);
+    let rescue = RescueCommitScheme::commit(&leaf);
+    assert_eq!(leaf.commit(), default);
+    assert_ne!(default, rescue);
+
+    assert_eq!(RescueCommitScheme::commit(&leaf), rescue);
+    assert!(DefaultCommitScheme::verify(&leaf, &default));
+    assert!(RescueCommitScheme::verify(&leaf, &rescue));
+    assert!(!DefaultCommitScheme::verify(&leaf, &rescue));
+    assert!(!RescueCommitScheme::verify(&leaf, &default));
+    assert!(!RescueCommitScheme::verify(&other_leaf, &rescue));
+
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let vote_data = |epoch: u64| {
+        VersionedVoteData::new_infallible(
+            QuorumData2 {
+                leaf_commit: leaf.commit(),
+            },
+            ViewNumber::new(1),
+            EpochNumber::new(epoch),
+            &upgrade_lock,
+        )
+    };
+    let vote_data_1 = vote_data(1).await;
+    let vote_data_2 = vote_data(2).await;
+
+    let default = DefaultCommitScheme::commit(&vote_data_1);
+    let rescue = RescueCommitScheme::commit(&vote_data_1);
+    assert_eq!(vote_data_1.commit(), default);
+    assert_ne!(default, rescue);
+    assert!(RescueCommitScheme::verify(&vote_data_1, &rescue));
+    assert!(!RescueCommitScheme::verify(&vote_data_2, &rescue));
+    assert!(!DefaultCommitScheme::verify(&vote_data_2, &default));
+}
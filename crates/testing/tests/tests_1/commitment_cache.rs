@@ -0,0 +1,136 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, num::NonZeroUsize, sync::Arc};
+
+use committable::Committable;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    commitment_cache::{commit_with_cache, CommitmentCache},
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote, TimeoutData},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::VoteAccumulator,
+    ValidatorConfig,
+};
+
+#[test]
+fn test_cached_commitments_equal_fresh_ones() {
+    let cache = CommitmentCache::new(NonZeroUsize::new(2).unwrap());
+    let data = DaData {
+        payload_commit: vid_commitment(&[1, 2, 3], 4),
+    };
+    let other = DaData {
+        payload_commit: vid_commitment(&[4, 5, 6], 4),
+    };
+
+    assert_eq!(cache.commit(&data), data.commit());
+    // An equal value with a different identity is served from the cache.
+    assert_eq!(cache.commit(&data.clone()), data.commit());
+    assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+    // A different value gets its own commitment.
+    assert_eq!(cache.commit(&other), other.commit());
+    assert_ne!(cache.commit(&other), cache.commit(&data));
+
+    // Values of another type are cached separately, under their own commitment.
+    let timeout = TimeoutData::<TestTypes> {
+        view: ViewNumber::new(1),
+    };
+    assert_eq!(cache.commit(&timeout), timeout.commit());
+    assert!(cache.contains(&timeout) && cache.contains(&data));
+
+    assert_eq!(commit_with_cache(None, &other), other.commit());
+}
+
+/// Count the commitments to the vote data computed during a DA vote round of `num_nodes` nodes.
+///
+/// Every vote is signed, then verified and counted by the leader's accumulator, so without the
+/// cache the vote data is hashed twice per node.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vote_round_hashes_vote_data_once() {
+    let num_nodes: u64 = 10;
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..num_nodes)
+        .map(|id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new().with_commitment_cache(16);
+    let view = ViewNumber::new(1);
+    let epoch = EpochNumber::new(1);
+    let data = DaData {
+        payload_commit: vid_commitment(&[], 10),
+    };
+
+    let mut accumulator =
+        VoteAccumulator::<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions> {
+            vote_outcomes: HashMap::new(),
+            signers: HashMap::new(),
+            phantom: PhantomData,
+            upgrade_lock: upgrade_lock.clone(),
+            metrics: Arc::default(),
+            on_certificate: None,
+        };
+    let mut certificate = None;
+    for validator in &validators {
+        let vote = DaVote::<TestTypes>::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        if let Some(cert) = accumulator
+            .accumulate(&vote, view, &membership, epoch)
+            .await
+            .right()
+        {
+            certificate.get_or_insert(cert);
+        }
+    }
+
+    // Only the first signature hashed the vote data, every later use was a cache hit.
+    let cache = upgrade_lock.commitment_cache.unwrap();
+    assert!(cache.contains(&data));
+    assert_eq!(cache.misses(), 1);
+    assert!(cache.hits() >= 2 * num_nodes - 1);
+    assert_eq!(certificate.unwrap().data, data);
+}
+
+#[test]
+fn test_each_upgrade_lock_owns_its_commitment_cache() {
+    let data = DaData {
+        payload_commit: vid_commitment(&[], 1),
+    };
+
+    let first = UpgradeLock::<TestTypes, TestVersions>::new().with_commitment_cache(16);
+    let second = UpgradeLock::<TestTypes, TestVersions>::new().with_commitment_cache(16);
+    let first = first.commitment_cache.unwrap();
+    let second = second.commitment_cache.unwrap();
+    first.commit(&data);
+    assert!(first.contains(&data));
+    assert!(!second.contains(&data));
+
+    // A capacity of zero disables caching, and a new lock caches nothing
+    let uncached = UpgradeLock::<TestTypes, TestVersions>::new().with_commitment_cache(0);
+    assert!(uncached.commitment_cache.is_none());
+    assert!(UpgradeLock::<TestTypes, TestVersions>::new()
+        .commitment_cache
+        .is_none());
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use committable::{Commitment, Committable};
+use hotshot_types::{
+    data::ViewNumber,
+    traits::{block_contents::vid_commitment, node_implementation::ConsensusTime},
+    utils::{commitment_from_hex, CommitmentHex},
+};
+
+#[test]
+fn test_commitment_hex_round_trips() {
+    let commitment = ViewNumber::new(42).commit();
+    let hex = commitment.to_hex();
+
+    assert_eq!(hex.len(), 64);
+    assert!(hex
+        .chars()
+        .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
+    assert_eq!(hex, ViewNumber::new(42).commit().to_hex());
+    assert_ne!(hex, ViewNumber::new(43).commit().to_hex());
+    assert_eq!(commitment_from_hex::<ViewNumber>(&hex), Some(commitment));
+
+    let raw = Commitment::<ViewNumber>::from_raw([0xab; 32]);
+    assert_eq!(raw.to_hex(), "ab".repeat(32));
+}
+
+#[test]
+fn test_commitment_hex_rejects_malformed_input() {
+    let hex = ViewNumber::new(42).commit().to_hex();
+
+    assert_eq!(commitment_from_hex::<ViewNumber>(&hex[2..]), None);
+    assert_eq!(commitment_from_hex::<ViewNumber>(&format!("{hex}00")), None);
+    assert_eq!(
+        commitment_from_hex::<ViewNumber>(&format!("+f{}", &hex[2..])),
+        None
+    );
+    assert_eq!(commitment_from_hex::<ViewNumber>(&"zz".repeat(32)), None);
+}
+
+#[test]
+fn test_vid_commitment_hex_is_deterministic() {
+    let hex = vid_commitment(&[1, 2, 3], 4).to_hex();
+
+    assert_eq!(hex.len(), 64);
+    assert_eq!(hex, vid_commitment(&[1, 2, 3], 4).to_hex());
+    assert_ne!(hex, vid_commitment(&[1, 2, 4], 4).to_hex());
+}
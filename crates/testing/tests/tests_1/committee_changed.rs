@@ -0,0 +1,84 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use hotshot::{
+    tasks::task_state::CreateTaskState, traits::election::helpers::StableQuorumFilterConfig,
+};
+use hotshot_example_types::node_types::{
+    MemoryImpl, TestTypesRandomizedCommitteeMembers, TestVersions,
+};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    data::ViewNumber,
+    event::EventType,
+    traits::{
+        election::{CommitteeDiff, Membership},
+        node_implementation::ConsensusTime,
+    },
+};
+
+/// Types whose committee rotates every epoch, keeping two members of the previous one
+type RotatingTypes = TestTypesRandomizedCommitteeMembers<StableQuorumFilterConfig<123, 2>>;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_committee_change_is_reported_to_the_application() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<RotatingTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let mut da_state =
+        DaTaskState::<RotatingTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let (output_tx, mut output_rx) = async_broadcast::broadcast(16);
+    da_state.output_event_stream = output_tx;
+    let (internal_tx, _internal_rx) = async_broadcast::broadcast(16);
+
+    let view = ViewNumber::new(*da_state.cur_view + 1);
+    let prev_epoch = da_state.cur_epoch;
+    let next_epoch = prev_epoch + 1;
+    let membership_reader = da_state.membership.read().await;
+    let expected = CommitteeDiff::<RotatingTypes>::between(
+        &membership_reader.committee_members(view, prev_epoch),
+        &membership_reader.committee_members(view, next_epoch),
+    );
+    drop(membership_reader);
+    assert!(!expected.added.is_empty() && !expected.removed.is_empty());
+
+    // A view change within the epoch does not notify the application
+    da_state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(view, prev_epoch)),
+            internal_tx.clone(),
+        )
+        .await
+        .unwrap();
+    assert!(output_rx.try_recv().is_err());
+
+    // Entering the next epoch does
+    da_state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(view + 1, next_epoch)),
+            internal_tx,
+        )
+        .await
+        .unwrap();
+    let event = output_rx.try_recv().unwrap();
+    let EventType::CommitteeChanged {
+        epoch,
+        added,
+        removed,
+    } = event.event
+    else {
+        panic!("Expected a committee change, got {:?}", event.event);
+    };
+    assert_eq!(epoch, next_epoch);
+    assert_eq!(added, expected.added);
+    assert_eq!(removed, expected.removed);
+    assert!(output_rx.try_recv().is_err());
+}
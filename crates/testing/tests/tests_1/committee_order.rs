@@ -0,0 +1,75 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::collections::BTreeSet;
+
+use hotshot::types::{BLSPubKey, SignatureKey};
+use hotshot_example_types::node_types::{
+    TestConsecutiveLeaderTypes, TestTypes, TestTypesRandomizedLeader,
+};
+use hotshot_types::{
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    PeerConfig, ValidatorConfig,
+};
+
+/// The keys of `peers`, in order
+fn keys(peers: &[PeerConfig<BLSPubKey>]) -> Vec<BLSPubKey> {
+    peers
+        .iter()
+        .map(|peer| BLSPubKey::public_key(&peer.stake_table_entry))
+        .collect()
+}
+
+/// Check that a membership built from deliberately unsorted peers keeps its stake tables, which
+/// signer bit vectors index into, in the configured order, and that the committees hold exactly
+/// the keys of the stake tables.
+fn assert_stake_table_keeps_configured_order<TYPES: NodeType<SignatureKey = BLSPubKey>>() {
+    let mut peers: Vec<_> = (0..10)
+        .map(|node_id| {
+            ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], node_id, 1, true)
+                .public_config()
+        })
+        .collect();
+    peers.reverse();
+    peers.swap(2, 7);
+    let da_peers = peers[..4].to_vec();
+
+    let epoch = TYPES::Epoch::new(0);
+    let view = TYPES::View::new(0);
+    let membership = TYPES::Membership::new(peers.clone(), da_peers.clone());
+
+    let stake_table_keys: Vec<_> = membership
+        .stake_table(epoch)
+        .iter()
+        .map(BLSPubKey::public_key)
+        .collect();
+    assert_eq!(stake_table_keys, keys(&peers));
+    assert_eq!(
+        membership.committee_members(view, epoch),
+        stake_table_keys.into_iter().collect::<BTreeSet<_>>()
+    );
+
+    let da_stake_table_keys: Vec<_> = membership
+        .da_stake_table(epoch)
+        .iter()
+        .map(BLSPubKey::public_key)
+        .collect();
+    assert_eq!(da_stake_table_keys, keys(&da_peers));
+    assert_eq!(
+        membership.da_committee_members(view, epoch),
+        da_stake_table_keys.into_iter().collect::<BTreeSet<_>>()
+    );
+}
+
+#[test]
+fn test_stake_table_keeps_configured_order() {
+    assert_stake_table_keeps_configured_order::<TestTypes>();
+    assert_stake_table_keeps_configured_order::<TestTypesRandomizedLeader>();
+    assert_stake_table_keeps_configured_order::<TestConsecutiveLeaderTypes>();
+}
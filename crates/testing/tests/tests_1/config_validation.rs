@@ -0,0 +1,60 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::test_builder::TestDescription;
+use hotshot_types::error::ConfigDiagnostic;
+
+#[test]
+fn test_config_validation_reports_every_problem() {
+    let config = TestDescription::<TestTypes, MemoryImpl, TestVersions>::default()
+        .gen_launcher(0)
+        .resource_generator
+        .config;
+    assert_eq!(config.validate(), Ok(()));
+    let num_nodes_with_stake = config.num_nodes_with_stake.get();
+
+    let mut invalid = config.clone();
+    invalid.known_nodes_with_stake.pop();
+    invalid
+        .known_da_nodes
+        .push(invalid.known_da_nodes[0].clone());
+    invalid.fixed_leader_for_gpuvid = num_nodes_with_stake + 1;
+    invalid.start_threshold = (2, 1);
+    invalid.next_view_timeout = 0;
+
+    assert_eq!(
+        invalid.validate(),
+        Err(vec![
+            ConfigDiagnostic::NodeCountMismatch {
+                num_nodes_with_stake,
+                known_nodes: num_nodes_with_stake - 1,
+            },
+            ConfigDiagnostic::DaNodeCountMismatch {
+                da_staked_committee_size: num_nodes_with_stake,
+                known_da_nodes: num_nodes_with_stake + 1,
+            },
+            ConfigDiagnostic::DuplicateDaNode {
+                index: num_nodes_with_stake,
+            },
+            ConfigDiagnostic::InvalidStartThreshold {
+                numerator: 2,
+                denominator: 1,
+            },
+            ConfigDiagnostic::TooManyFixedLeaders {
+                fixed_leader_for_gpuvid: num_nodes_with_stake + 1,
+                num_nodes_with_stake,
+            },
+            ConfigDiagnostic::ZeroNextViewTimeout,
+        ])
+    );
+
+    // A committee without stake can never certify anything.
+    let mut unstaked = config;
+    unstaked.known_da_nodes.clear();
+    unstaked.da_staked_committee_size = 0;
+    assert_eq!(unstaked.validate(), Err(vec![ConfigDiagnostic::NoDaStake]));
+}
@@ -0,0 +1,82 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+use primitive_types::U256;
+
+#[test]
+fn test_could_form_certificate_checks_signer_stake() {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..5)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let keys: Vec<BLSPubKey> = validators.iter().map(|v| v.public_key).collect();
+    // The last validator is not on the committee
+    let peers: Vec<_> = validators[..4]
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let committee = membership.snapshot_for_epoch(EpochNumber::new(0));
+    let view = ViewNumber::new(1);
+    let threshold = usize::try_from(committee.success_threshold().get()).unwrap();
+
+    // Enough distinct members reach the threshold
+    assert!(committee.could_form_certificate(&keys[..threshold], view));
+    assert!(committee.could_form_certificate(&keys[..4], view));
+
+    // One signer short of the threshold does not
+    let short = &keys[..threshold - 1];
+    assert!(!committee.could_form_certificate(short, view));
+
+    // Neither does repeating a signer, or adding one who is not on the committee
+    let mut repeated = short.to_vec();
+    repeated.push(keys[0]);
+    assert!(!committee.could_form_certificate(&repeated, view));
+
+    let mut outsider = short.to_vec();
+    outsider.push(keys[4]);
+    assert!(!committee.could_form_certificate(&outsider, view));
+}
+
+#[test]
+fn test_could_form_certificate_weighs_effective_stake() {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let keys: Vec<BLSPubKey> = validators.iter().map(|v| v.public_key).collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+
+    // Half of the committee unbonds from view 10
+    let epoch = EpochNumber::new(0);
+    let unbonding_view = ViewNumber::new(10);
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    for key in &keys[..2] {
+        membership.schedule_stake_change(*key, epoch, unbonding_view, U256::zero());
+    }
+    let committee = membership.snapshot_for_epoch(epoch);
+
+    // Before then the bonded half is short of the threshold, from then on it is all the stake
+    // left, as the accumulator would count it
+    assert!(!committee.could_form_certificate(&keys[2..], ViewNumber::new(9)));
+    assert!(committee.could_form_certificate(&keys[2..], unbonding_view));
+
+    // Signers whose stake has unbonded count for nothing
+    assert!(committee.could_form_certificate(&keys[..3], ViewNumber::new(9)));
+    assert!(!committee.could_form_certificate(&keys[..3], unbonding_view));
+}
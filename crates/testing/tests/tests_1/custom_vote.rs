@@ -0,0 +1,150 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use committable::{Commitment, Committable, RawCommitmentBuilder};
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::CustomCertificate,
+    simple_vote::{CustomData, CustomVote, DaData},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{Certificate, VoteAccumulator},
+    ValidatorConfig,
+};
+use serde::{Deserialize, Serialize};
+
+/// An application-defined attestation to the chain at a height
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Checkpoint {
+    /// The height attested to
+    height: u64,
+}
+
+impl Committable for Checkpoint {
+    fn commit(&self) -> Commitment<Self> {
+        RawCommitmentBuilder::new("Checkpoint")
+            .u64(self.height)
+            .finalize()
+    }
+}
+
+/// Custom data of kind `tag` over the checkpoint at `height`
+fn custom_data(tag: &str, height: u64) -> CustomData<Checkpoint> {
+    CustomData {
+        tag: tag.to_string(),
+        commitment: Checkpoint { height }.commit(),
+    }
+}
+
+#[test]
+fn test_custom_data_round_trips_and_separates_tags() {
+    let data = custom_data("checkpoint", 7);
+    let json = serde_json::to_string(&data).unwrap();
+    let decoded: CustomData<Checkpoint> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, data);
+    assert_eq!(decoded.commit(), data.commit());
+
+    // The tag and the data are both committed to
+    assert_ne!(custom_data("checkpoint-v2", 7).commit(), data.commit());
+    assert_ne!(custom_data("checkpoint", 8).commit(), data.commit());
+
+    // No tag reproduces the commitment of the built-in vote data
+    let da = DaData {
+        payload_commit: vid_commitment(&[], 4),
+    };
+    let disguised = CustomData::<Checkpoint> {
+        tag: "DA data".to_string(),
+        commitment: Commitment::from_raw(da.payload_commit.as_ref().try_into().unwrap()),
+    };
+    assert_ne!(
+        <[u8; 32]>::from(disguised.commit()),
+        <[u8; 32]>::from(da.commit())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_custom_votes_form_a_certificate() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let mut accumulator = VoteAccumulator::<
+        TestTypes,
+        CustomVote<TestTypes, Checkpoint>,
+        CustomCertificate<TestTypes, Checkpoint>,
+        TestVersions,
+    > {
+        vote_outcomes: HashMap::new(),
+        signers: HashMap::new(),
+        phantom: PhantomData,
+        upgrade_lock: upgrade_lock.clone(),
+        metrics: Arc::default(),
+        on_certificate: None,
+    };
+
+    // Votes of another kind are tallied separately, so they don't help form the certificate
+    let data = custom_data("checkpoint", 7);
+    for (i, validator) in validators[..3].iter().enumerate() {
+        let data = if i != 1 {
+            data.clone()
+        } else {
+            custom_data("other", 7)
+        };
+        let vote = CustomVote::<TestTypes, Checkpoint>::create_signed_vote(
+            data,
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        assert!(accumulator
+            .accumulate(&vote, view, &membership, epoch)
+            .await
+            .is_left());
+    }
+
+    // A third vote of the same kind crosses the threshold
+    let vote = CustomVote::<TestTypes, Checkpoint>::create_signed_vote(
+        data.clone(),
+        view,
+        epoch,
+        &validators[3].public_key,
+        &validators[3].private_key,
+        &upgrade_lock,
+    )
+    .await
+    .unwrap();
+    let certificate = accumulator
+        .accumulate(&vote, view, &membership, epoch)
+        .await
+        .unwrap_right();
+    assert_eq!(certificate.data, data);
+    assert!(
+        certificate
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+}
@@ -0,0 +1,55 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    data::ViewNumber,
+    traits::{block_contents::vid_commitment, node_implementation::ConsensusTime},
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_da_view_and_payload_update_together() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let consensus = handle.consensus();
+    let payload: Arc<[u8]> = vec![1, 2, 3].into();
+    let payload_commitment = vid_commitment(&payload, 10);
+    let mut consensus_writer = consensus.write().await;
+
+    // The view and the payload are both recorded.
+    let view = ViewNumber::new(5);
+    consensus_writer
+        .update_da_view_and_payload(view, payload_commitment, Arc::clone(&payload))
+        .unwrap();
+    assert_eq!(consensus_writer.saved_payloads().get(&view), Some(&payload));
+    assert!(consensus_writer.validated_state_map().contains_key(&view));
+
+    // A view whose payload was saved already is still tracked, and keeps the payload it has.
+    let other_view = ViewNumber::new(6);
+    consensus_writer
+        .update_saved_payloads(other_view, vec![4].into())
+        .unwrap();
+    assert!(consensus_writer
+        .update_da_view_and_payload(other_view, payload_commitment, Arc::clone(&payload))
+        .is_err());
+    assert_eq!(
+        consensus_writer
+            .saved_payloads()
+            .get(&other_view)
+            .unwrap()
+            .as_ref(),
+        &[4]
+    );
+    assert!(consensus_writer
+        .validated_state_map()
+        .contains_key(&other_view));
+}
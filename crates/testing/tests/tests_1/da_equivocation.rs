@@ -0,0 +1,114 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use hotshot::{tasks::task_state::CreateTaskState, types::SignatureKey};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::{
+    helpers::{build_system_handle, key_pair_for_id},
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::{
+    data::{DaProposal, EpochNumber},
+    message::Proposal,
+    traits::node_implementation::{ConsensusTime, NodeType},
+    vote::HasViewNumber,
+};
+use sha2::{Digest, Sha256};
+
+/// `proposal` with its payload replaced by `payload`, signed by the leader of view `signer`
+fn with_payload(
+    proposal: &Proposal<TestTypes, DaProposal<TestTypes>>,
+    payload: Vec<u8>,
+    signer: u64,
+) -> Proposal<TestTypes, DaProposal<TestTypes>> {
+    let (private_key, _) = key_pair_for_id::<TestTypes>(signer);
+    let mut conflicting = proposal.clone();
+    conflicting.data.encoded_transactions = Arc::from(payload);
+    conflicting.signature = <TestTypes as NodeType>::SignatureKey::sign(
+        &private_key,
+        &Sha256::digest(&conflicting.data.encoded_transactions),
+    )
+    .unwrap();
+
+    conflicting
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_conflicting_da_proposals_from_leader_are_reported() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let view = TestViewGenerator::generate(membership)
+        .next()
+        .await
+        .unwrap();
+    let proposal = view.da_proposal.clone();
+    let view_number = proposal.data.view_number();
+    let leader = view.leader_public_key;
+
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let recv = |proposal| Arc::new(HotShotEvent::DaProposalRecv(proposal, leader.clone()));
+
+    // Receiving the same proposal twice is not an equivocation
+    for _ in 0..2 {
+        state
+            .handle(recv(proposal.clone()), tx.clone())
+            .await
+            .unwrap();
+        let event = rx.try_recv().unwrap();
+        assert!(matches!(
+            event.as_ref(),
+            HotShotEvent::DaProposalValidated(..)
+        ));
+    }
+    assert!(rx.try_recv().is_err());
+
+    // A conflicting payload which the leader did not sign proves nothing
+    let forged = with_payload(&proposal, vec![1, 2, 3], *view_number + 1);
+    assert!(state.handle(recv(forged), tx.clone()).await.is_err());
+    assert!(rx.try_recv().is_err());
+
+    // One it did sign is reported with both hashes
+    let conflicting = with_payload(&proposal, vec![1, 2, 3], *view_number);
+    state.handle(recv(conflicting), tx.clone()).await.unwrap();
+    let event = rx.try_recv().unwrap();
+    let HotShotEvent::DaProposalEquivocation {
+        view,
+        leader: equivocator,
+        first_hash,
+        second_hash,
+    } = event.as_ref()
+    else {
+        panic!("Expected an equivocation, got {event:?}");
+    };
+    assert_eq!(*view, view_number);
+    assert_eq!(*equivocator, leader);
+    assert_eq!(
+        *first_hash,
+        <[u8; 32]>::from(Sha256::digest(&proposal.data.encoded_transactions))
+    );
+    assert_eq!(*second_hash, <[u8; 32]>::from(Sha256::digest([1, 2, 3])));
+
+    // The record of the view is dropped once it is too old for its proposals to be accepted
+    assert!(state.proposal_hashes.contains_key(&view_number));
+    let epoch = EpochNumber::new(0);
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(view_number + 2, epoch)),
+            tx,
+        )
+        .await
+        .unwrap();
+    assert!(state.proposal_hashes.is_empty());
+}
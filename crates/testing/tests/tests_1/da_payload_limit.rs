@@ -0,0 +1,95 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    block_types::TestTransaction,
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::node_implementation::ConsensusTime,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_oversized_da_proposal_is_dropped_before_hashing() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let mut generator = TestViewGenerator::generate(membership);
+    generator.next().await.unwrap();
+    generator.add_transactions(vec![TestTransaction::new(vec![0; 64])]);
+    let views: Vec<_> = (&mut generator).take(3).collect().await;
+    let proposal = views[0].da_proposal.clone();
+    let leader = views[0].leader_public_key;
+    let payload_bytes = proposal.data.encoded_transactions.len();
+
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(
+                ViewNumber::new(1),
+                EpochNumber::new(0),
+            )),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+    state.max_da_payload_bytes = payload_bytes - 1;
+
+    // A validly signed proposal from the leader is still dropped if its payload is too large,
+    // before its hash is taken and recorded
+    let err = state
+        .handle(
+            Arc::new(HotShotEvent::DaProposalRecv(proposal.clone(), leader)),
+            tx.clone(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("over the limit"), "{err}");
+    assert!(rx.try_recv().is_err());
+    assert!(state.proposal_hashes.is_empty());
+
+    // Nor is an oversized proposal for a future view buffered
+    let future = views[2].da_proposal.clone();
+    state.max_da_payload_bytes = future.data.encoded_transactions.len() - 1;
+    assert!(state
+        .handle(
+            Arc::new(HotShotEvent::DaProposalRecv(
+                future,
+                views[2].leader_public_key
+            )),
+            tx.clone(),
+        )
+        .await
+        .is_err());
+    assert!(state.future_proposal_buffer.is_empty());
+
+    // A payload exactly at the limit is accepted
+    state.max_da_payload_bytes = payload_bytes;
+    state
+        .handle(
+            Arc::new(HotShotEvent::DaProposalRecv(proposal.clone(), leader)),
+            tx,
+        )
+        .await
+        .unwrap();
+    let event = rx.try_recv().unwrap();
+    assert!(matches!(
+        event.as_ref(),
+        HotShotEvent::DaProposalValidated(..)
+    ));
+    assert!(state.proposal_hashes.contains_key(&views[0].view_number));
+}
@@ -4,10 +4,16 @@
 // You should have received a copy of the MIT License
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
 
 use futures::StreamExt;
-use hotshot::tasks::task_state::CreateTaskState;
+use hotshot::{
+    tasks::task_state::CreateTaskState,
+    types::{BLSPubKey, SystemContextHandle},
+};
 use hotshot_example_types::{
     block_types::{TestMetadata, TestTransaction},
     node_types::{MemoryImpl, TestTypes, TestVersions},
@@ -22,13 +28,15 @@ use hotshot_testing::{
     view_generator::TestViewGenerator,
 };
 use hotshot_types::{
-    data::{null_block, EpochNumber, PackedBundle, ViewNumber},
-    simple_vote::DaData,
+    data::{null_block, DaProposal, EpochNumber, PackedBundle, ViewNumber},
+    message::Proposal,
+    simple_vote::{DaData, DaVote},
     traits::{
         block_contents::precompute_vid_commitment,
         election::Membership,
         node_implementation::{ConsensusTime, Versions},
     },
+    vid::VidCommitment,
 };
 use vbs::version::StaticVersionType;
 
@@ -40,7 +48,7 @@ async fn test_da_task() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     // Make some empty encoded transactions, we just care about having a commitment handy for the
     // later calls. We need the VID commitment to be able to propose later.
@@ -48,7 +56,12 @@ async fn test_da_task() {
     let encoded_transactions = Arc::from(TestTransaction::encode(&transactions));
     let (payload_commit, precompute) = precompute_vid_commitment(
         &encoded_transactions,
-        handle.hotshot.memberships.total_nodes(EpochNumber::new(0)),
+        handle
+            .hotshot
+            .memberships
+            .read()
+            .await
+            .total_nodes(EpochNumber::new(0)),
     );
 
     let mut generator = TestViewGenerator::generate(membership.clone());
@@ -89,9 +102,7 @@ async fn test_da_task() {
             ViewChange(ViewNumber::new(2), EpochNumber::new(1)),
             BlockRecv(PackedBundle::new(
                 encoded_transactions.clone(),
-                TestMetadata {
-                    num_transactions: transactions.len() as u64
-                },
+                TestMetadata::new(&transactions),
                 ViewNumber::new(2),
                 vec1::vec1![null_block::builder_fee::<TestTypes, TestVersions>(
                     membership.total_nodes(EpochNumber::new(0)),
@@ -135,7 +146,7 @@ async fn test_da_task_storage_failure() {
 
     // Set the error flag here for the system handle. This causes it to emit an error on append.
     handle.storage().write().await.should_return_err = true;
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     // Make some empty encoded transactions, we just care about having a commitment handy for the
     // later calls. We need the VID commitment to be able to propose later.
@@ -143,7 +154,12 @@ async fn test_da_task_storage_failure() {
     let encoded_transactions = Arc::from(TestTransaction::encode(&transactions));
     let (payload_commit, precompute) = precompute_vid_commitment(
         &encoded_transactions,
-        handle.hotshot.memberships.total_nodes(EpochNumber::new(0)),
+        handle
+            .hotshot
+            .memberships
+            .read()
+            .await
+            .total_nodes(EpochNumber::new(0)),
     );
 
     let mut generator = TestViewGenerator::generate(membership.clone());
@@ -184,9 +200,7 @@ async fn test_da_task_storage_failure() {
             ViewChange(ViewNumber::new(2), EpochNumber::new(1)),
             BlockRecv(PackedBundle::new(
                 encoded_transactions.clone(),
-                TestMetadata {
-                    num_transactions: transactions.len() as u64
-                },
+                TestMetadata::new(&transactions),
                 ViewNumber::new(2),
                 vec1::vec1![null_block::builder_fee::<TestTypes, TestVersions>(
                     membership.total_nodes(EpochNumber::new(0)),
@@ -222,3 +236,152 @@ async fn test_da_task_storage_failure() {
 
     run_test![inputs, da_script].await;
 }
+
+/// What a DA task test drives node 2's DA task with in view 2
+struct DaFixture {
+    /// Node 2's handle
+    handle: SystemContextHandle<TestTypes, MemoryImpl, TestVersions>,
+    /// The block node 2 proposes for view 2
+    bundle: PackedBundle<TestTypes>,
+    /// The DA proposal for view 2
+    proposal: Proposal<TestTypes, DaProposal<TestTypes>>,
+    /// The leader of view 2
+    leader: BLSPubKey,
+    /// Node 2's vote on the proposal
+    vote: DaVote<TestTypes>,
+}
+
+/// Build node 2 and the DA proposal for view 2, carrying a single transaction
+async fn da_fixture() -> DaFixture {
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let num_nodes = membership.total_nodes(EpochNumber::new(0));
+
+    let transactions = vec![TestTransaction::new(vec![0])];
+    let encoded_transactions = Arc::from(TestTransaction::encode(&transactions));
+    let (payload_commit, precompute) = precompute_vid_commitment(&encoded_transactions, num_nodes);
+
+    let mut generator = TestViewGenerator::generate(membership);
+    generator.next().await.unwrap();
+    generator.add_transactions(transactions.clone());
+    let view = generator.next().await.unwrap();
+    let vote = view
+        .create_da_vote(DaData { payload_commit }, &handle)
+        .await;
+
+    let bundle = PackedBundle::new(
+        encoded_transactions,
+        TestMetadata::new(&transactions),
+        ViewNumber::new(2),
+        vec1::vec1![null_block::builder_fee::<TestTypes, TestVersions>(
+            num_nodes,
+            <TestVersions as Versions>::Base::VERSION,
+            *ViewNumber::new(2),
+        )
+        .unwrap()],
+        Some(precompute),
+        None,
+    );
+
+    DaFixture {
+        handle,
+        bundle,
+        proposal: view.da_proposal,
+        leader: view.leader_public_key,
+        vote,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_da_task_transient_storage_failure() {
+    hotshot::helpers::initialize_logging();
+
+    let DaFixture {
+        handle,
+        bundle,
+        proposal,
+        leader,
+        vote,
+    } = da_fixture().await;
+
+    // Fail the first two appends. The task should retry and still vote on the proposal.
+    handle
+        .storage()
+        .read()
+        .await
+        .transient_append_da_failures
+        .store(2, Ordering::SeqCst);
+
+    let inputs = vec![
+        serial![
+            ViewChange(ViewNumber::new(1), EpochNumber::new(1)),
+            ViewChange(ViewNumber::new(2), EpochNumber::new(1)),
+            BlockRecv(bundle),
+        ],
+        serial![DaProposalRecv(proposal.clone(), leader)],
+    ];
+
+    let da_state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let mut da_script = TaskScript {
+        timeout: Duration::from_millis(35),
+        state: da_state,
+        expectations: vec![
+            Expectations::from_outputs(vec![exact(DaProposalSend(proposal.clone(), leader))]),
+            Expectations::from_outputs(vec![
+                exact(DaProposalValidated(proposal, leader)),
+                exact(DaVoteSend(vote)),
+            ]),
+        ],
+    };
+
+    run_test![inputs, da_script].await;
+}
+
+/// A VID commitment function which always panics, as it might on degenerate parameters
+#[allow(clippy::panic)]
+fn panicking_vid_commitment(_: &[u8], _: usize) -> VidCommitment {
+    panic!("Degenerate VID parameters");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_da_task_vid_commitment_panic() {
+    hotshot::helpers::initialize_logging();
+
+    let DaFixture {
+        handle,
+        bundle,
+        proposal,
+        leader,
+        ..
+    } = da_fixture().await;
+
+    let inputs = vec![
+        serial![
+            ViewChange(ViewNumber::new(1), EpochNumber::new(1)),
+            ViewChange(ViewNumber::new(2), EpochNumber::new(1)),
+        ],
+        serial![DaProposalRecv(proposal.clone(), leader)],
+        // The task should keep handling events after the failed VID computation.
+        serial![BlockRecv(bundle)],
+    ];
+
+    let mut da_state =
+        DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    da_state.vid_commitment_fn = panicking_vid_commitment;
+    let mut da_script = TaskScript {
+        timeout: Duration::from_millis(35),
+        state: da_state,
+        expectations: vec![
+            Expectations::from_outputs(vec![]),
+            Expectations::from_outputs(vec![
+                exact(DaProposalValidated(proposal.clone(), leader)),
+                exact(DaProposalRejected(proposal.clone(), leader)),
+            ]),
+            Expectations::from_outputs(vec![exact(DaProposalSend(proposal, leader))]),
+        ],
+    };
+
+    run_test![inputs, da_script].await;
+}
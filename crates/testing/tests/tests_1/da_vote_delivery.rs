@@ -0,0 +1,102 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use async_lock::RwLock;
+use hotshot::{tasks::task_state::CreateTaskState, traits::implementations::MemoryNetwork};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent, network::NetworkEventTaskState};
+use hotshot_testing::{
+    helpers::{build_system_handle, key_pair_for_id},
+    test_builder::TestDescription,
+};
+use hotshot_types::{
+    consensus::OuterConsensus,
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        network::NetworkError,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+};
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_undelivered_da_vote_is_reported_to_the_da_task() {
+    hotshot::helpers::initialize_logging();
+
+    let builder: TestDescription<TestTypes, MemoryImpl, TestVersions> =
+        TestDescription::default_multiple_rounds();
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let node_id = 1;
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(node_id)
+        .await
+        .0;
+    let launcher = builder.gen_launcher(node_id);
+    let config = launcher.resource_generator.config.clone();
+    let all_nodes = config.known_nodes_with_stake.clone();
+    let membership = <TestTypes as NodeType>::Membership::new(all_nodes.clone(), all_nodes);
+
+    // Only our own node is on the network, so every other DA leader is unreachable
+    let mut network_state: NetworkEventTaskState<TestTypes, TestVersions, MemoryNetwork<_>, _> =
+        NetworkEventTaskState {
+            network: (launcher.resource_generator.channel_generator)(node_id).await,
+            view: ViewNumber::new(0),
+            epoch: EpochNumber::new(0),
+            max_epoch_skip: config.max_epoch_skip,
+            membership: Arc::new(RwLock::new(membership.clone())),
+            upgrade_lock: upgrade_lock.clone(),
+            network_id: config.network_id,
+            storage: Arc::new(RwLock::new((launcher.resource_generator.storage)(node_id))),
+            consensus: OuterConsensus::new(handle.hotshot.consensus()),
+            transmit_tasks: BTreeMap::new(),
+            participation: Arc::default(),
+            batch_justify_qcs: false,
+            last_justify_qc: None,
+        };
+    let (tx, mut rx) = async_broadcast::broadcast(10);
+
+    let (private_key, public_key) = key_pair_for_id::<TestTypes>(node_id);
+    let epoch = EpochNumber::new(0);
+    let view = (1..)
+        .map(ViewNumber::new)
+        .find(|view| membership.leader(*view, epoch).unwrap() != public_key)
+        .unwrap();
+    let vote = DaVote::create_signed_vote(
+        DaData {
+            payload_commit: vid_commitment(&[], 1),
+        },
+        view,
+        epoch,
+        &public_key,
+        &private_key,
+        &upgrade_lock,
+    )
+    .await
+    .unwrap();
+
+    network_state
+        .handle(Arc::new(HotShotEvent::DaVoteSend(vote.clone())), &tx)
+        .await;
+    let event = timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("timed out waiting for the failed send")
+        .unwrap();
+    assert!(matches!(
+        event.as_ref(),
+        HotShotEvent::DaVoteSendFailed(failed, NetworkError::MessageSendError(_))
+            if *failed == vote
+    ));
+
+    // The DA task takes the report without failing
+    let mut da_state =
+        DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    da_state.handle(event, tx).await.unwrap();
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{sync::Arc, time::Duration};
+
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::helpers::{build_system_handle, key_pair_for_id};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment, election::Membership, node_implementation::ConsensusTime,
+    },
+};
+use tokio::time::timeout;
+
+/// A DA vote for `view` from node `node_id`
+async fn da_vote(view: ViewNumber, node_id: u64, epoch: EpochNumber) -> DaVote<TestTypes> {
+    let (private_key, public_key) = key_pair_for_id::<TestTypes>(node_id);
+    DaVote::create_signed_vote(
+        DaData {
+            payload_commit: vid_commitment(&[], 1),
+        },
+        view,
+        epoch,
+        &public_key,
+        &private_key,
+        &UpgradeLock::<TestTypes, TestVersions>::new(),
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_da_vote_timeout_fires_unless_cancelled() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let (tx, mut rx) = async_broadcast::broadcast(64);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let epoch = state.cur_epoch;
+
+    // The views we lead, and the DA committee members who vote in them
+    let led_views: Vec<_> = (2..)
+        .map(ViewNumber::new)
+        .filter(|view| membership.leader(*view, epoch).unwrap() == handle.public_key())
+        .take(3)
+        .collect();
+    let voters: Vec<_> = (0..membership.total_nodes(epoch) as u64)
+        .filter(|node_id| membership.has_da_stake(&key_pair_for_id::<TestTypes>(*node_id).1, epoch))
+        .collect();
+
+    // A view whose certificate never forms times out
+    state.da_vote_timeout = Duration::from_millis(50);
+    let vote = da_vote(led_views[0], voters[0], epoch).await;
+    state
+        .handle(Arc::new(HotShotEvent::DaVoteRecv(vote)), tx.clone())
+        .await
+        .unwrap();
+    assert!(state.da_vote_timers.contains_key(&led_views[0]));
+    let event = timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(event.as_ref(), HotShotEvent::DaVoteTimeout(view) if *view == led_views[0]));
+
+    // The timer is cancelled once the certificate forms, and votes arriving after it don't start
+    // it again, even though the collector is dropped as soon as the certificate forms
+    assert!(state.late_vote_grace.is_zero());
+    state.da_vote_timeout = Duration::from_millis(500);
+    let mut votes = Vec::new();
+    for node_id in &voters {
+        votes.push(da_vote(led_views[1], *node_id, epoch).await);
+    }
+    for vote in votes {
+        let _ = state
+            .handle(Arc::new(HotShotEvent::DaVoteRecv(vote)), tx.clone())
+            .await;
+    }
+    assert!(state.da_certificate_views.contains(&led_views[1]));
+    assert!(!state.da_vote_timers.contains_key(&led_views[1]));
+    while let Ok(event) = timeout(Duration::from_secs(1), rx.recv()).await {
+        assert!(!matches!(
+            event.unwrap().as_ref(),
+            HotShotEvent::DaVoteTimeout(view) if *view == led_views[1]
+        ));
+    }
+
+    // or once we move past its view
+    state.da_vote_timeout = Duration::from_secs(3600);
+    let vote = da_vote(led_views[2], voters[0], epoch).await;
+    state
+        .handle(Arc::new(HotShotEvent::DaVoteRecv(vote)), tx.clone())
+        .await
+        .unwrap();
+    assert!(state.da_vote_timers.contains_key(&led_views[2]));
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(led_views[2] + 1, epoch)),
+            tx,
+        )
+        .await
+        .unwrap();
+    assert!(state.da_vote_timers.is_empty());
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use hotshot_example_types::{
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    state_types::TestValidatedState,
+};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    data::ViewNumber,
+    event::{DecidedLeafSummary, LeafChain, LeafInfo},
+    traits::{block_contents::BlockHeader, node_implementation::ConsensusTime},
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_decide_summary_covers_each_leaf_once_in_order() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let leaves: Vec<_> = TestViewGenerator::generate(membership)
+        .take(4)
+        .map(|view| view.leaf)
+        .collect()
+        .await;
+
+    // A single QC finalizes the leaves of views 1, 2 and 3; the decide chain lists them newest
+    // first.
+    let leaf_chain: LeafChain<TestTypes> = leaves[..3]
+        .iter()
+        .rev()
+        .map(|leaf| {
+            LeafInfo::new(
+                leaf.clone(),
+                Arc::new(TestValidatedState::default()),
+                None,
+                None,
+            )
+        })
+        .collect();
+
+    let summary = DecidedLeafSummary::newly_decided(&leaf_chain, ViewNumber::genesis());
+    assert_eq!(
+        summary
+            .iter()
+            .map(|decided| decided.leaf.clone())
+            .collect::<Vec<_>>(),
+        leaves[..3]
+    );
+    for decided in &summary {
+        assert_eq!(
+            decided.payload_commitment,
+            decided.leaf.block_header().payload_commitment()
+        );
+    }
+
+    // The next decide's chain overlaps the previous one, but only the new leaf is reported.
+    let next_chain: LeafChain<TestTypes> = leaves[2..]
+        .iter()
+        .rev()
+        .map(|leaf| {
+            LeafInfo::new(
+                leaf.clone(),
+                Arc::new(TestValidatedState::default()),
+                None,
+                None,
+            )
+        })
+        .collect();
+    let last_decided_view = summary.last().unwrap().leaf.view_number();
+    let next_summary = DecidedLeafSummary::newly_decided(&next_chain, last_decided_view);
+    assert_eq!(next_summary.len(), 1);
+    assert_eq!(next_summary[0].leaf, leaves[3]);
+}
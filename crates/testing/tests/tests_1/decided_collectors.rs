@@ -0,0 +1,141 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use async_broadcast::broadcast;
+use async_lock::RwLock;
+use futures::StreamExt;
+use hotshot::{tasks::task_state::CreateTaskState, types::BLSPubKey};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{
+    da::DaTaskState,
+    events::HotShotEvent,
+    vote_collection::{handle_vote, remove_decided_collectors},
+};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote_metrics::VoteMetrics,
+    ValidatorConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collector_is_removed_once_its_view_decides() {
+    hotshot::helpers::initialize_logging();
+
+    // Node 2 leads view 2.
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let payload_commit = vid_commitment(&[], membership.total_nodes(EpochNumber::new(0)));
+    let mut generator = TestViewGenerator::generate(membership);
+    generator.next().await;
+    let view = generator.next().await.unwrap();
+    let vote = view
+        .create_da_vote(DaData { payload_commit }, &handle)
+        .await;
+    let recv = Arc::new(HotShotEvent::DaVoteRecv(vote));
+
+    let (tx, _rx) = broadcast(16);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    state.handle(Arc::clone(&recv), tx.clone()).await.unwrap();
+    assert!(state.vote_collectors.contains_key(&view.view_number));
+
+    handle
+        .hotshot
+        .consensus()
+        .write()
+        .await
+        .update_last_decided_view(view.view_number)
+        .unwrap();
+    let epoch = state.cur_epoch;
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(3), epoch)),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+    assert!(!state.vote_collectors.contains_key(&view.view_number));
+
+    // A late vote for the decided view does not start a new collector.
+    assert!(state.handle(recv, tx).await.is_err());
+    assert!(state.vote_collectors.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_collector_in_late_vote_grace_outlives_decide() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = Arc::new(RwLock::new(<TestTypes as NodeType>::Membership::new(
+        peers.clone(),
+        peers,
+    )));
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let metrics = Arc::new(VoteMetrics::default());
+    let (tx, _rx) = broadcast(16);
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+
+    // The certificate forms at the third vote, so the collector is kept for late votes.
+    let mut collectors = BTreeMap::new();
+    for validator in &validators[..3] {
+        let vote = DaVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        handle_vote(
+            &mut collectors,
+            &vote,
+            validators[0].public_key,
+            &membership,
+            epoch,
+            0,
+            &Arc::new(HotShotEvent::DaVoteRecv(vote.clone())),
+            &tx,
+            &upgrade_lock,
+            false,
+            &metrics,
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+    }
+
+    remove_decided_collectors(&mut collectors, view);
+    assert!(collectors[&view].certificate_formed_at.is_some());
+
+    // Once the grace period no longer applies, deciding removes the collector.
+    collectors.get_mut(&view).unwrap().late_vote_grace = Duration::ZERO;
+    std::thread::sleep(Duration::from_millis(1));
+    remove_decided_collectors(&mut collectors, view);
+    assert!(collectors.is_empty());
+}
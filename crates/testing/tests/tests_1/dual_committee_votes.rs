@@ -0,0 +1,164 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use committable::Commitment;
+use either::Either;
+use hotshot::types::{BLSPubKey, SignatureKey};
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::{DaCertificate, QuorumCertificate2},
+    simple_vote::{DaData, DaVote, QuorumData2, QuorumVote2},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{Certificate, VoteAccumulator},
+    ValidatorConfig,
+};
+
+/// The signer bit positions set in a certificate's signatures
+fn signer_positions(signatures: &Option<<BLSPubKey as SignatureKey>::QcType>) -> Vec<usize> {
+    signatures.as_ref().unwrap().1.iter_ones().collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_dual_committee_node_votes_in_independent_accumulators() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+
+    // Put the last node of the quorum stake table in a two node DA committee, where it cannot
+    // hold the same position.
+    let quorum_keys: Vec<_> = <TestTypes as NodeType>::Membership::new(peers.clone(), vec![])
+        .stake_table(epoch)
+        .iter()
+        .map(BLSPubKey::public_key)
+        .collect();
+    let dual_key = quorum_keys[3];
+    let da_peers: Vec<_> = validators
+        .iter()
+        .filter(|validator| [quorum_keys[0], dual_key].contains(&validator.public_key))
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), da_peers);
+    let da_keys: Vec<_> = membership
+        .da_stake_table(epoch)
+        .iter()
+        .map(BLSPubKey::public_key)
+        .collect();
+    let da_position = da_keys.iter().position(|key| *key == dual_key).unwrap();
+    let quorum_position = quorum_keys.iter().position(|key| *key == dual_key).unwrap();
+    assert_ne!(da_position, quorum_position);
+
+    let mut da_accumulator =
+        VoteAccumulator::<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions> {
+            vote_outcomes: HashMap::new(),
+            signers: HashMap::new(),
+            phantom: PhantomData,
+            upgrade_lock: upgrade_lock.clone(),
+            metrics: Arc::default(),
+            on_certificate: None,
+        };
+    let mut quorum_accumulator = VoteAccumulator::<
+        TestTypes,
+        QuorumVote2<TestTypes>,
+        QuorumCertificate2<TestTypes>,
+        TestVersions,
+    > {
+        vote_outcomes: HashMap::new(),
+        signers: HashMap::new(),
+        phantom: PhantomData,
+        upgrade_lock: upgrade_lock.clone(),
+        metrics: Arc::default(),
+        on_certificate: None,
+    };
+
+    let da_data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+    let quorum_data = QuorumData2 {
+        leaf_commit: Commitment::from_raw([7; 32]),
+    };
+
+    // Every node votes in reverse quorum order, so the dual node votes first, and the DA members
+    // also cast a DA vote for the same view right after their quorum vote.
+    let mut da_cert = None;
+    let mut quorum_cert = None;
+    for key in quorum_keys.iter().rev() {
+        let validator = validators
+            .iter()
+            .find(|validator| validator.public_key == *key)
+            .unwrap();
+        let quorum_vote = QuorumVote2::create_signed_vote(
+            quorum_data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        if let Either::Right(cert) = quorum_accumulator
+            .accumulate(&quorum_vote, view, &membership, epoch)
+            .await
+        {
+            quorum_cert.get_or_insert(cert);
+        }
+
+        if da_keys.contains(key) {
+            let da_vote = DaVote::create_signed_vote(
+                da_data.clone(),
+                view,
+                epoch,
+                &validator.public_key,
+                &validator.private_key,
+                &upgrade_lock,
+            )
+            .await
+            .unwrap();
+            if let Either::Right(cert) = da_accumulator
+                .accumulate(&da_vote, view, &membership, epoch)
+                .await
+            {
+                da_cert.get_or_insert(cert);
+            }
+        }
+    }
+
+    // Each certificate sets the dual node's bit at its position in that committee only.
+    let quorum_cert = quorum_cert.expect("quorum certificate should form");
+    let da_cert = da_cert.expect("DA certificate should form");
+    assert_eq!(signer_positions(&quorum_cert.signatures), vec![1, 2, 3]);
+    assert_eq!(signer_positions(&da_cert.signatures), vec![0, 1]);
+    assert!(signer_positions(&quorum_cert.signatures).contains(&quorum_position));
+    assert!(signer_positions(&da_cert.signatures).contains(&da_position));
+
+    assert!(
+        da_cert
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+    assert!(
+        quorum_cert
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::vote::HasViewNumber;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_saved_payload_is_never_replaced() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let view = TestViewGenerator::generate(membership)
+        .next()
+        .await
+        .unwrap();
+    let view_number = view.da_proposal.data.view_number();
+
+    let consensus = handle.hotshot.consensus();
+    let saved: Arc<[u8]> = Arc::from(vec![0xde, 0xad]);
+    consensus
+        .write()
+        .await
+        .update_saved_payloads(view_number, Arc::clone(&saved))
+        .unwrap();
+
+    // A second payload for the view is thrown away rather than voted on again
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let recv = Arc::new(HotShotEvent::DaProposalRecv(
+        view.da_proposal.clone(),
+        view.leader_public_key,
+    ));
+    assert!(state.handle(recv, tx).await.is_err());
+    assert!(rx.try_recv().is_err());
+    assert_eq!(
+        consensus.read().await.saved_payloads().get(&view_number),
+        Some(&saved)
+    );
+}
@@ -0,0 +1,64 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::test_builder::TestDescription;
+use hotshot_types::{
+    error::ConfigDiagnostic,
+    traits::{election::Membership, node_implementation::NodeType},
+    ValidatorConfig,
+};
+
+#[test]
+fn test_stake_table_with_duplicated_key_is_rejected() {
+    let peers: Vec<_> = (0..3)
+        .map(|id| {
+            ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], id, 1, true)
+                .public_config()
+        })
+        .collect();
+    assert!(<TestTypes as NodeType>::Membership::try_new(peers.clone(), peers.clone()).is_ok());
+
+    let mut duplicated = peers.clone();
+    duplicated.push(peers[1].clone());
+
+    let err = <TestTypes as NodeType>::Membership::try_new(duplicated.clone(), peers.clone())
+        .unwrap_err();
+    assert!(format!("{err}").contains(&peers[1].stake_table_entry.stake_key.to_string()));
+    assert!(<TestTypes as NodeType>::Membership::try_new(peers, duplicated).is_err());
+}
+
+#[test]
+fn test_config_with_duplicated_key_is_rejected() {
+    let mut config = TestDescription::<TestTypes, MemoryImpl, TestVersions>::default()
+        .gen_launcher(0)
+        .resource_generator
+        .config;
+    let duplicated = config.known_nodes_with_stake[0].clone();
+    let index = config.known_nodes_with_stake.len() - 1;
+    config.known_nodes_with_stake[index] = duplicated.clone();
+    config.known_da_nodes[index] = duplicated;
+
+    assert_eq!(
+        config.validate(),
+        Err(vec![
+            ConfigDiagnostic::DuplicateKnownNode { index },
+            ConfigDiagnostic::DuplicateDaNode { index },
+        ])
+    );
+
+    // Building the committee itself does not panic, the duplicate is reported as an error instead
+    let _ = <TestTypes as NodeType>::Membership::new(
+        config.known_nodes_with_stake.clone(),
+        config.known_da_nodes.clone(),
+    );
+    assert!(<TestTypes as NodeType>::Membership::try_new(
+        config.known_nodes_with_stake,
+        config.known_da_nodes
+    )
+    .is_err());
+}
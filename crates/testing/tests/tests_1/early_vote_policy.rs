@@ -0,0 +1,115 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    block_types::{TestMetadata, TestTransaction},
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    consensus::EarlyVotePolicy,
+    data::{null_block, EpochNumber, PackedBundle, ViewNumber},
+    simple_vote::DaData,
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, Versions},
+    },
+};
+use vbs::version::StaticVersionType;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_early_da_vote_is_handled_once_proposal_is_sent() {
+    hotshot::helpers::initialize_logging();
+
+    // Node 2 leads view 2.
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let num_nodes = membership.total_nodes(EpochNumber::new(0));
+
+    let transactions = vec![TestTransaction::new(vec![0])];
+    let encoded_transactions: Arc<[u8]> = Arc::from(TestTransaction::encode(&transactions));
+    let payload_commit = vid_commitment(&encoded_transactions, num_nodes);
+
+    let mut generator = TestViewGenerator::generate(membership);
+    generator.next().await;
+    let view = generator.next().await.unwrap();
+    assert_eq!(view.view_number, ViewNumber::new(2));
+    let vote = view
+        .create_da_vote(DaData { payload_commit }, &handle)
+        .await;
+
+    let (tx, _rx) = async_broadcast::broadcast(16);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    state.early_vote_policy = EarlyVotePolicy::Buffer {
+        max_votes: 4,
+        ttl: Duration::from_secs(60),
+    };
+
+    // The vote arrives before the proposal for its view, so it is held.
+    state
+        .handle(Arc::new(HotShotEvent::DaVoteRecv(vote)), tx.clone())
+        .await
+        .unwrap();
+    assert!(state.vote_collectors.is_empty());
+    assert_eq!(state.early_votes[&view.view_number].1.len(), 1);
+
+    // Sending the proposal confirms we lead the view and releases the vote.
+    let bundle = PackedBundle::new(
+        encoded_transactions,
+        TestMetadata::new(&transactions),
+        view.view_number,
+        vec1::vec1![null_block::builder_fee::<TestTypes, TestVersions>(
+            num_nodes,
+            <TestVersions as Versions>::Base::VERSION,
+            *view.view_number,
+        )
+        .unwrap()],
+        None,
+        None,
+    );
+    state
+        .handle(Arc::new(HotShotEvent::BlockRecv(bundle)), tx)
+        .await
+        .unwrap();
+    assert!(state.early_votes.is_empty());
+    assert!(state.vote_collectors.contains_key(&view.view_number));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_early_da_vote_is_handled_immediately_by_default() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let payload_commit = vid_commitment(&[], membership.total_nodes(EpochNumber::new(0)));
+    let mut generator = TestViewGenerator::generate(membership);
+    generator.next().await;
+    let view = generator.next().await.unwrap();
+    let vote = view
+        .create_da_vote(DaData { payload_commit }, &handle)
+        .await;
+
+    let (tx, _rx) = async_broadcast::broadcast(16);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    assert_eq!(state.early_vote_policy, EarlyVotePolicy::Process);
+
+    state
+        .handle(Arc::new(HotShotEvent::DaVoteRecv(vote)), tx)
+        .await
+        .unwrap();
+    assert!(state.early_votes.is_empty());
+    assert!(state.vote_collectors.contains_key(&view.view_number));
+}
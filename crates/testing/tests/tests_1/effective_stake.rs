@@ -0,0 +1,205 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use bitvec::bitvec;
+use committable::Committable;
+use either::Either;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::TimeoutCertificate,
+    simple_vote::{TimeoutData, TimeoutVote, VersionedVoteData},
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+        signature_key::SignatureKey,
+    },
+    vote::{Certificate, Vote, VoteAccumulator},
+    ValidatorConfig,
+};
+use primitive_types::U256;
+
+/// Collect votes on a timeout at `view` from `voters`, returning the certificate if one forms
+async fn timeout_certificate(
+    membership: &<TestTypes as NodeType>::Membership,
+    voters: &[ValidatorConfig<BLSPubKey>],
+    view: ViewNumber,
+    epoch: EpochNumber,
+    upgrade_lock: &UpgradeLock<TestTypes, TestVersions>,
+) -> Option<TimeoutCertificate<TestTypes>> {
+    let mut accumulator = VoteAccumulator::<
+        TestTypes,
+        TimeoutVote<TestTypes>,
+        TimeoutCertificate<TestTypes>,
+        TestVersions,
+    > {
+        vote_outcomes: HashMap::new(),
+        signers: HashMap::new(),
+        phantom: PhantomData,
+        upgrade_lock: upgrade_lock.clone(),
+        metrics: Arc::default(),
+        on_certificate: None,
+    };
+
+    let mut certificate = None;
+    for validator in voters {
+        let vote = TimeoutVote::create_signed_vote(
+            TimeoutData { view },
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            upgrade_lock,
+        )
+        .await
+        .unwrap();
+
+        if let Either::Right(cert) = accumulator.accumulate(&vote, view, membership, epoch).await {
+            certificate = Some(cert);
+        }
+    }
+
+    certificate
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_bonded_stake_forms_certificate_after_unbonding_view() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+
+    let epoch = EpochNumber::new(0);
+    let unbonding_view = ViewNumber::new(100);
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers.clone());
+    for validator in &validators[..2] {
+        membership.schedule_stake_change(
+            validator.public_key.clone(),
+            epoch,
+            unbonding_view,
+            U256::zero(),
+        );
+    }
+
+    assert_eq!(
+        membership.effective_stake_at_view(&validators[0].public_key, ViewNumber::new(99), epoch),
+        U256::from(1)
+    );
+    assert_eq!(
+        membership.effective_stake_at_view(&validators[0].public_key, unbonding_view, epoch),
+        U256::zero()
+    );
+
+    // Before the unbonding view three of the four validators are needed, and enough.
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let view = ViewNumber::new(99);
+    assert!(
+        timeout_certificate(&membership, &validators[2..], view, epoch, &upgrade_lock)
+            .await
+            .is_none()
+    );
+    let certificate =
+        timeout_certificate(&membership, &validators[..3], view, epoch, &upgrade_lock)
+            .await
+            .expect("three of four bonded validators form a certificate");
+    assert!(
+        certificate
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+
+    // From the unbonding view the threshold follows the stake which is still bonded, so the two
+    // remaining validators form a certificate on their own ...
+    let view = unbonding_view;
+    let certificate =
+        timeout_certificate(&membership, &validators[2..], view, epoch, &upgrade_lock)
+            .await
+            .expect("the bonded validators form a certificate");
+    assert!(
+        certificate
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+
+    // ... while the unbonded validators count for nothing.
+    assert!(
+        timeout_certificate(&membership, &validators[..3], view, epoch, &upgrade_lock)
+            .await
+            .is_none()
+    );
+
+    // A reconfiguration does not inherit the stake changes of the committee it replaces.
+    let next_epoch = EpochNumber::new(1);
+    membership
+        .reconfigure(next_epoch, peers.clone(), peers)
+        .unwrap();
+    assert_eq!(
+        membership.effective_stake_at_view(&validators[0].public_key, view, next_epoch),
+        U256::from(1)
+    );
+    assert_eq!(
+        membership.effective_stake_at_view(&validators[0].public_key, view, epoch),
+        U256::zero()
+    );
+
+    // A leader still counting the unbonded stake would assemble three votes into a certificate
+    // against the full threshold, which replicas reject, as they weigh the signers by the same
+    // effective stake.
+    let data = TimeoutData { view };
+    let stake_table = membership.stake_table(epoch);
+    let mut signers = bitvec![0; stake_table.len()];
+    let mut signatures = Vec::new();
+    for validator in &validators[..3] {
+        let vote = TimeoutVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        let node_id = stake_table
+            .iter()
+            .position(|entry| BLSPubKey::public_key(entry) == validator.public_key)
+            .unwrap();
+        signers.set(node_id, true);
+        signatures.push(vote.signature());
+    }
+    let threshold = TimeoutCertificate::<TestTypes>::threshold(&membership, epoch);
+    let qc_params = BLSPubKey::public_parameter(stake_table, U256::from(threshold));
+    let vote_commitment = VersionedVoteData::<TestTypes, _, TestVersions>::new(
+        data.clone(),
+        view,
+        epoch,
+        &upgrade_lock,
+    )
+    .await
+    .unwrap()
+    .commit();
+    let certificate = TimeoutCertificate::<TestTypes>::create_signed_certificate(
+        vote_commitment,
+        data,
+        BLSPubKey::assemble(&qc_params, signers.as_bitslice(), &signatures),
+        view,
+        epoch,
+    );
+    assert!(
+        !certificate
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+}
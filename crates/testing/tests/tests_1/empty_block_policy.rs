@@ -0,0 +1,168 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use hotshot::{tasks::task_state::CreateTaskState, types::SystemContextHandle};
+use hotshot_example_types::{
+    block_types::TestTransaction,
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    transactions::{BlockContents, ProposalSource, TransactionTaskState},
+};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    consensus::EmptyBlockPolicy, data::ViewNumber, traits::node_implementation::ConsensusTime,
+};
+
+/// A mempool which is empty for the first `empty_calls` requests, and holds one transaction after
+/// that
+struct Mempool {
+    /// Number of requests answered with no transactions
+    empty_calls: usize,
+    /// Number of requests so far
+    calls: AtomicUsize,
+}
+
+impl Mempool {
+    /// A mempool which is empty for the first `empty_calls` requests
+    fn new(empty_calls: usize) -> Arc<Self> {
+        Arc::new(Self {
+            empty_calls,
+            calls: AtomicUsize::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl ProposalSource<TestTypes> for Mempool {
+    async fn next_block(&self, _view: ViewNumber, _max_bytes: u64) -> BlockContents<TestTypes> {
+        let transactions = if self.calls.fetch_add(1, Ordering::SeqCst) < self.empty_calls {
+            Vec::new()
+        } else {
+            vec![TestTransaction::new(vec![1])]
+        };
+
+        BlockContents { transactions }
+    }
+}
+
+/// Have node 2, the leader of view 2, reach view 2 with an empty mempool under `policy`.
+///
+/// Returns the number of transactions it proposed, if it proposed at all.
+async fn propose_for_view_two(policy: EmptyBlockPolicy, mempool: Arc<Mempool>) -> Option<u64> {
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    propose_with_handle(&handle, policy, mempool).await
+}
+
+/// Have the leader of view 2 behind `handle` reach view 2 under `policy`
+async fn propose_with_handle(
+    handle: &SystemContextHandle<TestTypes, MemoryImpl, TestVersions>,
+    policy: EmptyBlockPolicy,
+    mempool: Arc<Mempool>,
+) -> Option<u64> {
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    let mut state =
+        TransactionTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(handle).await;
+    state.proposal_source = Some(mempool);
+    state.empty_block_policy = policy;
+    let epoch = state.cur_epoch;
+
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(2), epoch)),
+            tx,
+        )
+        .await
+        .unwrap();
+
+    let event = rx.try_recv().ok()?;
+    let HotShotEvent::BlockRecv(bundle) = event.as_ref() else {
+        panic!("Expected a block, got {event:?}");
+    };
+    assert_eq!(bundle.view_number, ViewNumber::new(2));
+    Some(bundle.metadata.num_transactions)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_propose_empty_proposes_immediately() {
+    hotshot::helpers::initialize_logging();
+
+    let mempool = Mempool::new(usize::MAX);
+    let proposed = propose_for_view_two(EmptyBlockPolicy::ProposeEmpty, Arc::clone(&mempool)).await;
+
+    assert_eq!(proposed, Some(0));
+    assert_eq!(mempool.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_skip_view_proposes_nothing() {
+    hotshot::helpers::initialize_logging();
+
+    let proposed = propose_for_view_two(EmptyBlockPolicy::SkipView, Mempool::new(usize::MAX)).await;
+
+    assert_eq!(proposed, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_skip_view_still_proposes_to_decide_earlier_blocks() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+
+    // View 1 is certified but not decided, so view 2 must extend it.
+    let mut generator =
+        TestViewGenerator::generate(handle.hotshot.memberships.read().await.clone());
+    generator.next().await;
+    let view_two = generator.next().await.unwrap();
+    handle
+        .hotshot
+        .consensus()
+        .write()
+        .await
+        .update_high_qc(view_two.quorum_proposal.data.justify_qc.clone())
+        .unwrap();
+
+    let proposed = propose_with_handle(
+        &handle,
+        EmptyBlockPolicy::SkipView,
+        Mempool::new(usize::MAX),
+    )
+    .await;
+
+    assert_eq!(proposed, Some(0));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_propose_after_delay_waits_for_transactions() {
+    hotshot::helpers::initialize_logging();
+
+    let delay = Duration::from_millis(200);
+    let policy = EmptyBlockPolicy::ProposeAfterDelay(delay);
+
+    // Transactions arrive during the delay
+    let start = Instant::now();
+    let proposed = propose_for_view_two(policy, Mempool::new(1)).await;
+    assert!(start.elapsed() >= delay);
+    assert_eq!(proposed, Some(1));
+
+    // Or the block is empty after all
+    let proposed = propose_for_view_two(policy, Mempool::new(usize::MAX)).await;
+    assert_eq!(proposed, Some(0));
+}
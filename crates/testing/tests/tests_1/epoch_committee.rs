@@ -0,0 +1,69 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+use primitive_types::U256;
+
+#[test]
+fn test_epoch_committee_snapshot_is_stable() {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(10);
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let snapshot = membership.snapshot_for_epoch(epoch);
+    let shared = snapshot.clone();
+
+    let key = &validators[0].public_key;
+    let leader = membership.leader(view, epoch).unwrap();
+    assert_eq!(snapshot.epoch(), epoch);
+    assert_eq!(
+        snapshot.stake_table(),
+        membership.stake_table(epoch).as_slice()
+    );
+    assert_eq!(
+        snapshot.committee_members(),
+        membership.committee_members(view, epoch)
+    );
+    assert_eq!(
+        snapshot.success_threshold(),
+        membership.success_threshold(epoch)
+    );
+    assert_eq!(snapshot.leader(view).unwrap(), leader);
+    assert_eq!(snapshot.effective_stake_at_view(key, view), U256::from(1));
+
+    // Change the live membership after taking the snapshot.
+    membership.schedule_stake_change(key.clone(), epoch, ViewNumber::new(0), U256::zero());
+    assert_eq!(
+        membership.effective_stake_at_view(key, view, epoch),
+        U256::zero()
+    );
+
+    // The snapshot, including a copy handed to another thread, still sees the old committee.
+    let thread_key = key.clone();
+    let on_other_thread =
+        std::thread::spawn(move || shared.effective_stake_at_view(&thread_key, view))
+            .join()
+            .unwrap();
+    assert_eq!(on_other_thread, U256::from(1));
+    assert_eq!(snapshot.effective_stake_at_view(key, view), U256::from(1));
+    assert_eq!(snapshot.leader(view).unwrap(), leader);
+}
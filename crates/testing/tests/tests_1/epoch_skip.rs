@@ -0,0 +1,64 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::node_implementation::ConsensusTime,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_view_change_clamps_epoch_jump() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let (tx, _rx) = async_broadcast::broadcast(10);
+    let start_epoch = state.cur_epoch;
+    let max_epoch_skip = handle.hotshot.config.max_epoch_skip;
+
+    // A view change which jumps past the allowed number of epochs still advances the view, but
+    // only advances the epoch by the allowed number.
+    let jump = EpochNumber::new(*start_epoch + max_epoch_skip + 1);
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(1), jump)),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+    let clamped = EpochNumber::new(*start_epoch + max_epoch_skip);
+    assert_eq!(state.cur_epoch, clamped);
+    assert_eq!(state.cur_view, ViewNumber::new(1));
+
+    // So a node catching up reaches the epoch over the next view changes.
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(2), jump)),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(state.cur_epoch, jump);
+    assert_eq!(state.cur_view, ViewNumber::new(2));
+
+    // The epoch never moves backwards.
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(3), start_epoch)),
+            tx,
+        )
+        .await
+        .unwrap();
+    assert_eq!(state.cur_epoch, jump);
+}
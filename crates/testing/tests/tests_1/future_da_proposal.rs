@@ -0,0 +1,120 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{da::DaTaskState, events::HotShotEvent};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::node_implementation::ConsensusTime,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_future_da_proposal_is_applied_on_reaching_its_view() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let views: Vec<_> = TestViewGenerator::generate(membership)
+        .take(5)
+        .collect()
+        .await;
+
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    let mut state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let epoch = EpochNumber::new(0);
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(1), epoch)),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+
+    // A proposal two views ahead is held back
+    let proposal = views[2].da_proposal.clone();
+    let leader = views[2].leader_public_key;
+    state
+        .handle(
+            Arc::new(HotShotEvent::DaProposalRecv(proposal.clone(), leader)),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+    assert!(rx.try_recv().is_err());
+    assert!(state
+        .future_proposal_buffer
+        .contains_key(&ViewNumber::new(3)));
+
+    // and validated once the view before it is reached
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(2), epoch)),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+    let event = rx.try_recv().unwrap();
+    let HotShotEvent::DaProposalValidated(validated, sender) = event.as_ref() else {
+        panic!("Expected the buffered proposal to be validated, got {event:?}");
+    };
+    assert_eq!(*validated, proposal);
+    assert_eq!(*sender, leader);
+    assert!(state.future_proposal_buffer.is_empty());
+
+    // A proposal which is not signed by the view's leader is not buffered, so it can't take the
+    // place of the real one
+    let mut forged = views[3].da_proposal.clone();
+    forged.signature = views[2].da_proposal.signature.clone();
+    assert!(state
+        .handle(
+            Arc::new(HotShotEvent::DaProposalRecv(
+                forged,
+                views[3].leader_public_key
+            )),
+            tx.clone(),
+        )
+        .await
+        .is_err());
+    assert!(state.future_proposal_buffer.is_empty());
+
+    // Proposals are only buffered for views within `max_future_buffer` of the next one
+    state.max_future_buffer = 1;
+    assert!(state
+        .handle(
+            Arc::new(HotShotEvent::DaProposalRecv(
+                views[4].da_proposal.clone(),
+                views[4].leader_public_key,
+            )),
+            tx.clone(),
+        )
+        .await
+        .is_err());
+    state
+        .handle(
+            Arc::new(HotShotEvent::DaProposalRecv(
+                views[3].da_proposal.clone(),
+                views[3].leader_public_key,
+            )),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        state.future_proposal_buffer.keys().collect::<Vec<_>>(),
+        vec![&ViewNumber::new(4)]
+    );
+    assert_eq!(
+        state.future_proposal_buffer[&ViewNumber::new(4)].0,
+        views[3].da_proposal
+    );
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::{
+    block_types::{TestMetadata, TestTransaction},
+    node_types::TestTypes,
+};
+use hotshot_types::{
+    data::{DaProposal, ViewNumber},
+    traits::{block_contents::vid_commitment, node_implementation::ConsensusTime},
+    vid::verify_inclusion,
+};
+
+const NUM_STORAGE_NODES: usize = 4;
+
+/// A DA proposal for three distinct transactions, along with those transactions
+fn da_proposal() -> (DaProposal<TestTypes>, Vec<TestTransaction>) {
+    let transactions = vec![
+        TestTransaction::new(vec![1; 3]),
+        TestTransaction::new(vec![2; 10]),
+        TestTransaction::new(vec![3; 5]),
+    ];
+    let proposal = DaProposal {
+        encoded_transactions: TestTransaction::encode(&transactions).into(),
+        metadata: TestMetadata::new(&transactions),
+        view_number: ViewNumber::new(1),
+    };
+
+    (proposal, transactions)
+}
+
+#[test]
+fn test_inclusion_proof_verifies_against_payload_commitment() {
+    let (proposal, transactions) = da_proposal();
+    let commitment = vid_commitment(&proposal.encoded_transactions, NUM_STORAGE_NODES);
+
+    for (index, txn) in transactions.iter().enumerate() {
+        let proof = proposal.inclusion_proof(index, NUM_STORAGE_NODES).unwrap();
+        assert_eq!(
+            &proposal.encoded_transactions[proof.range.clone()],
+            txn.bytes().as_slice()
+        );
+        assert!(verify_inclusion(&commitment, &proof, txn.bytes()));
+    }
+}
+
+#[test]
+fn test_inclusion_proof_out_of_range_index() {
+    let (proposal, transactions) = da_proposal();
+
+    assert!(proposal
+        .inclusion_proof(transactions.len(), NUM_STORAGE_NODES)
+        .is_err());
+}
+
+#[test]
+fn test_inclusion_proof_rejects_tampered_transaction() {
+    let (proposal, transactions) = da_proposal();
+    let commitment = vid_commitment(&proposal.encoded_transactions, NUM_STORAGE_NODES);
+    let proof = proposal.inclusion_proof(1, NUM_STORAGE_NODES).unwrap();
+
+    let mut tampered = transactions[1].bytes().clone();
+    tampered[0] ^= 1;
+    assert!(!verify_inclusion(&commitment, &proof, &tampered));
+
+    // The proof is also bound to its transaction's position in the payload.
+    assert!(!verify_inclusion(
+        &commitment,
+        &proof,
+        transactions[0].bytes()
+    ));
+
+    // And to the payload it was computed for.
+    let other_commitment = vid_commitment(&[0; 32], NUM_STORAGE_NODES);
+    assert!(!verify_inclusion(
+        &other_commitment,
+        &proof,
+        transactions[1].bytes()
+    ));
+}
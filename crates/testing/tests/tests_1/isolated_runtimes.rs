@@ -0,0 +1,54 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{
+    block_builder::SimpleBuilderImplementation,
+    test_builder::TestDescription,
+    test_runner::{NodeRuntime, NODE_RUNTIME_THREAD_NAME},
+};
+
+/// Name of the thread the calling task is running on
+fn thread_name() -> Option<String> {
+    std::thread::current().name().map(str::to_owned)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_node_runtime_runs_spawned_tasks() {
+    let runtime = NodeRuntime::new();
+
+    // Tasks spawned while the node starts up run on the node's runtime, even when they are
+    // spawned after the startup future has yielded.
+    let spawned_on = runtime
+        .run(async {
+            tokio::task::yield_now().await;
+            tokio::spawn(async { thread_name() })
+        })
+        .await
+        .await
+        .unwrap();
+    assert_eq!(spawned_on.as_deref(), Some(NODE_RUNTIME_THREAD_NAME));
+
+    // Once startup is done, the harness spawns onto its own runtime again.
+    let spawned_on = tokio::spawn(async { thread_name() }).await.unwrap();
+    assert_ne!(spawned_on.as_deref(), Some(NODE_RUNTIME_THREAD_NAME));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_success_with_isolated_runtimes() {
+    hotshot::helpers::initialize_logging();
+
+    let metadata: TestDescription<TestTypes, MemoryImpl, TestVersions> = TestDescription {
+        isolated_runtimes: true,
+        ..TestDescription::default()
+    };
+
+    metadata
+        .gen_launcher(0)
+        .launch()
+        .run_test::<SimpleBuilderImplementation>()
+        .await;
+}
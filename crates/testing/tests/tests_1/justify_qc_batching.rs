@@ -0,0 +1,194 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::BTreeMap, num::NonZeroUsize, sync::Arc, time::Duration};
+
+use async_lock::RwLock;
+use futures::StreamExt;
+use hotshot::{tasks::task_state::CreateTaskState, traits::implementations::MemoryNetwork};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    network::{
+        strip_network_id, NetworkEventTaskState, NetworkMessageTaskState, ReceivedJustifyQcs,
+    },
+    quorum_proposal_recv::QuorumProposalRecvTaskState,
+};
+use hotshot_testing::{
+    helpers::build_system_handle, test_builder::TestDescription, view_generator::TestViewGenerator,
+};
+use hotshot_types::{
+    consensus::{ConsensusMetricsValue, OuterConsensus},
+    data::{EpochNumber, ViewNumber},
+    message::{GeneralConsensusMessage, Message, MessageKind, SequencingMessage, UpgradeLock},
+    traits::{
+        block_contents::AcceptAllTxnValidator,
+        election::Membership,
+        network::ConnectedNetwork,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+};
+use tokio::time::timeout;
+
+/// Whether `message` is a proposal carrying its justify QC, or `None` if it is no proposal
+fn carries_justify_qc(message: &Message<TestTypes>) -> Option<bool> {
+    match &message.kind {
+        MessageKind::Consensus(SequencingMessage::General(general_message)) => {
+            match general_message {
+                GeneralConsensusMessage::Proposal(_) => Some(true),
+                GeneralConsensusMessage::ProposalWithQcRef(_) => Some(false),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_proposals_sharing_a_justify_qc_send_it_once() {
+    hotshot::helpers::initialize_logging();
+
+    let builder: TestDescription<TestTypes, MemoryImpl, TestVersions> =
+        TestDescription::default_multiple_rounds();
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let node_id = 1;
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(node_id)
+        .await
+        .0;
+    let launcher = builder.gen_launcher(node_id);
+    let network = (launcher.resource_generator.channel_generator)(node_id).await;
+    let config = launcher.resource_generator.config.clone();
+    let consensus = OuterConsensus::new(handle.hotshot.consensus());
+    let all_nodes = config.known_nodes_with_stake.clone();
+    let membership = <TestTypes as NodeType>::Membership::new(all_nodes.clone(), all_nodes);
+
+    let mut network_state: NetworkEventTaskState<TestTypes, TestVersions, MemoryNetwork<_>, _> =
+        NetworkEventTaskState {
+            network: Arc::clone(&network),
+            view: ViewNumber::new(0),
+            epoch: EpochNumber::new(0),
+            max_epoch_skip: config.max_epoch_skip,
+            membership: Arc::new(RwLock::new(membership.clone())),
+            upgrade_lock: upgrade_lock.clone(),
+            network_id: config.network_id,
+            storage: Arc::new(RwLock::new((launcher.resource_generator.storage)(node_id))),
+            consensus: consensus.clone(),
+            transmit_tasks: BTreeMap::new(),
+            participation: Arc::default(),
+            batch_justify_qcs: true,
+            last_justify_qc: None,
+        };
+
+    // Views 2, 3 and 4 all extend view 1, so share its QC as their justify QC
+    let mut generator = TestViewGenerator::generate(membership.clone());
+    let view_one = generator.next().await.unwrap();
+    let mut batch = vec![generator.next().await.unwrap()];
+    for _ in 0..2 {
+        generator.next_from_ancestor_view(view_one.clone()).await;
+        batch.push(generator.current_view.clone().unwrap());
+    }
+    for view in &batch {
+        assert_eq!(
+            view.quorum_proposal.data.justify_qc,
+            batch[0].quorum_proposal.data.justify_qc
+        );
+    }
+
+    let (tx, _rx) = async_broadcast::broadcast(10);
+    for view in &batch {
+        network_state
+            .handle(
+                Arc::new(HotShotEvent::QuorumProposalSend(
+                    view.quorum_proposal.clone(),
+                    handle.public_key(),
+                )),
+                &tx,
+            )
+            .await;
+    }
+
+    // The QC goes over the wire with only one of the proposals
+    let mut messages = Vec::new();
+    while messages.len() < batch.len() {
+        let message = timeout(Duration::from_secs(1), network.recv_message())
+            .await
+            .expect("timed out waiting for the proposals")
+            .unwrap();
+        let message = strip_network_id(config.network_id, &message).unwrap();
+        let message: Message<TestTypes> = upgrade_lock.deserialize(message).await.unwrap();
+        if carries_justify_qc(&message).is_some() {
+            messages.push(message);
+        }
+    }
+    let with_qc = messages
+        .iter()
+        .filter(|message| carries_justify_qc(message) == Some(true))
+        .count();
+    assert_eq!(with_qc, 1);
+
+    // A replica receiving the proposals referencing the QC first asks for it
+    messages.sort_by_key(carries_justify_qc);
+    let (internal_tx, mut internal_rx) = async_broadcast::broadcast(10);
+    let (external_tx, _external_rx) = async_broadcast::broadcast(10);
+    let mut replica = NetworkMessageTaskState::<TestTypes> {
+        internal_event_stream: internal_tx,
+        external_event_stream: external_tx,
+        public_key: handle.public_key(),
+        transactions_cache: lru::LruCache::new(NonZeroUsize::new(100).unwrap()),
+        transaction_validator: Arc::new(AcceptAllTxnValidator),
+        metrics: Arc::new(ConsensusMetricsValue::default()),
+        consensus,
+        max_message_age_views: 10,
+        received_justify_qcs: ReceivedJustifyQcs::default(),
+    };
+    for message in messages {
+        replica.handle_message(message).await;
+    }
+
+    let mut missing = Vec::new();
+    let mut restored = Vec::new();
+    while let Ok(event) = internal_rx.try_recv() {
+        match event.as_ref() {
+            HotShotEvent::JustifyQcMissing(view) => missing.push(*view),
+            HotShotEvent::QuorumProposalRecv(proposal, _) => restored.push(proposal.clone()),
+            _ => {}
+        }
+    }
+    assert_eq!(missing.len(), batch.len() - 1);
+
+    // Once the QC arrives every proposal of the batch is restored, and validates
+    restored.sort_by_key(|proposal| proposal.data.view_number);
+    assert_eq!(restored.len(), batch.len());
+    for (proposal, view) in restored.iter().zip(&batch) {
+        assert_eq!(proposal.data.view_number, view.view_number);
+        assert_eq!(
+            proposal.data.justify_qc,
+            view.quorum_proposal.data.justify_qc
+        );
+        proposal
+            .validate_signature(&membership, view.epoch_number, &handle.hotshot.upgrade_lock)
+            .await
+            .unwrap();
+    }
+
+    // The missing QC is requested through the proposal request-response flow
+    let (event_tx, mut event_rx) = async_broadcast::broadcast(10);
+    let mut recv_state =
+        QuorumProposalRecvTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle)
+            .await;
+    recv_state
+        .handle(
+            Arc::new(HotShotEvent::JustifyQcMissing(missing[0])),
+            event_tx,
+            event_rx.clone(),
+        )
+        .await;
+    let request = event_rx.try_recv().unwrap();
+    assert!(matches!(
+        request.as_ref(),
+        HotShotEvent::QuorumProposalRequestSend(req, _) if req.view_number == missing[0]
+    ));
+}
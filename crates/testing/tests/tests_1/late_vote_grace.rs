@@ -0,0 +1,132 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use async_broadcast::broadcast;
+use async_lock::RwLock;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    vote_collection::{handle_vote, VoteCollectorsMap},
+};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote_metrics::VoteMetrics,
+    ValidatorConfig,
+};
+
+/// Collect DA votes from every validator in turn, returning the collectors and the number of
+/// certificates formed
+async fn collect_all_votes(
+    validators: &[ValidatorConfig<BLSPubKey>],
+    late_vote_grace: Duration,
+) -> (
+    VoteCollectorsMap<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions>,
+    usize,
+) {
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = Arc::new(RwLock::new(<TestTypes as NodeType>::Membership::new(
+        peers.clone(),
+        peers,
+    )));
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let metrics = Arc::new(VoteMetrics::default());
+    let (tx, mut rx) = broadcast(16);
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+
+    let mut collectors = BTreeMap::new();
+    for validator in validators {
+        let vote = DaVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        handle_vote(
+            &mut collectors,
+            &vote,
+            validators[0].public_key,
+            &membership,
+            epoch,
+            0,
+            &Arc::new(HotShotEvent::DaVoteRecv(vote.clone())),
+            &tx,
+            &upgrade_lock,
+            false,
+            &metrics,
+            late_vote_grace,
+        )
+        .await
+        .unwrap();
+    }
+
+    let mut certificates = 0;
+    while let Ok(event) = rx.try_recv() {
+        if matches!(event.as_ref(), HotShotEvent::DacSend(..)) {
+            certificates += 1;
+        }
+    }
+
+    (collectors, certificates)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_late_vote_is_recorded_within_grace_period() {
+    hotshot::helpers::initialize_logging();
+
+    // The threshold is 3, so the last vote arrives after the certificate formed.
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let view = ViewNumber::new(1);
+
+    let (collectors, certificates) = collect_all_votes(&validators, Duration::from_secs(60)).await;
+    assert_eq!(certificates, 1);
+    let collector = &collectors[&view];
+    assert!(collector.certificate_formed_at.is_some());
+    assert!(!collector.late_vote_grace_elapsed());
+    assert_eq!(
+        collector.late_voters.iter().collect::<Vec<_>>(),
+        vec![&validators[3].public_key]
+    );
+    let participants = collector.participants();
+    assert!(validators
+        .iter()
+        .all(|validator| participants.contains(&validator.public_key)));
+
+    // Without a grace period the collector is dropped as soon as the certificate forms, and the
+    // late vote starts a collector of its own.
+    let (collectors, certificates) = collect_all_votes(&validators, Duration::ZERO).await;
+    assert_eq!(certificates, 1);
+    let collector = &collectors[&view];
+    assert!(collector.certificate_formed_at.is_none());
+    assert!(collector.late_voters.is_empty());
+    assert_eq!(
+        collector.participants().into_iter().collect::<Vec<_>>(),
+        vec![validators[3].public_key]
+    );
+}
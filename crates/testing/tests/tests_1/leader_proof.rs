@@ -0,0 +1,164 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::marker::PhantomData;
+
+use hotshot::types::{BLSPubKey, SignatureKey};
+use hotshot_example_types::{
+    node_types::{TestTypes, TestTypesRandomizedLeader, TestVersions},
+    state_types::{TestInstanceState, TestValidatedState},
+};
+use hotshot_types::{
+    data::{EpochNumber, Leaf, QuorumProposal2, ViewNumber},
+    drb::{INITIAL_DRB_RESULT, INITIAL_DRB_SEED_INPUT},
+    message::{quorum_proposal_signing_bytes, Proposal, UpgradeLock},
+    simple_certificate::QuorumCertificate,
+    traits::{
+        election::{LeaderProof, Membership},
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+
+/// The validators of a committee of five nodes
+fn validators() -> Vec<ValidatorConfig<BLSPubKey>> {
+    (0..5)
+        .map(|id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], id, 1, true))
+        .collect()
+}
+
+#[test]
+fn test_deterministic_leader_proof_checks_the_leader() {
+    let validators = validators();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(3);
+
+    let leader = membership.leader(view, epoch).unwrap();
+    let leader_config = validators.iter().find(|v| v.public_key == leader).unwrap();
+    let proof = membership
+        .leader_proof(view, epoch, &leader_config.private_key)
+        .unwrap();
+    assert_eq!(proof, LeaderProof::Deterministic);
+    assert!(membership.verify_leader_proof(view, epoch, &leader, &proof));
+
+    // Any other node claiming the view is rejected
+    let impostor = validators.iter().find(|v| v.public_key != leader).unwrap();
+    let impostor_proof = membership
+        .leader_proof(view, epoch, &impostor.private_key)
+        .unwrap();
+    assert!(!membership.verify_leader_proof(view, epoch, &impostor.public_key, &impostor_proof));
+}
+
+#[test]
+fn test_randomized_leader_proof_is_signed_by_the_leader() {
+    let validators = validators();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypesRandomizedLeader as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(3);
+
+    let leader = membership.leader(view, epoch).unwrap();
+    let leader_config = validators.iter().find(|v| v.public_key == leader).unwrap();
+    let proof = membership
+        .leader_proof(view, epoch, &leader_config.private_key)
+        .unwrap();
+    assert!(matches!(proof, LeaderProof::Signed(_)));
+    assert!(membership.verify_leader_proof(view, epoch, &leader, &proof));
+
+    // The proof is bound to its view
+    assert!(!membership.verify_leader_proof(view + 1, epoch, &leader, &proof));
+
+    // A node claiming the view with its own signature is rejected
+    let impostor = validators.iter().find(|v| v.public_key != leader).unwrap();
+    let impostor_proof = membership
+        .leader_proof(view, epoch, &impostor.private_key)
+        .unwrap();
+    assert!(!membership.verify_leader_proof(view, epoch, &impostor.public_key, &impostor_proof));
+
+    // As is one presenting the leader's key without the leader's signature
+    assert!(!membership.verify_leader_proof(view, epoch, &leader, &impostor_proof));
+    assert!(!membership.verify_leader_proof(view, epoch, &leader, &LeaderProof::Deterministic));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_proposal_with_bad_leader_proof_is_rejected() {
+    let validators = validators();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypesRandomizedLeader as NodeType>::Membership::new(peers.clone(), peers);
+    let upgrade_lock = UpgradeLock::<TestTypesRandomizedLeader, TestVersions>::new();
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(3);
+
+    let genesis = Leaf::<TestTypesRandomizedLeader>::genesis(
+        &TestValidatedState::default(),
+        &TestInstanceState::default(),
+    )
+    .await;
+    let data = QuorumProposal2::<TestTypesRandomizedLeader> {
+        block_header: genesis.block_header().clone(),
+        view_number: view,
+        justify_qc: QuorumCertificate::genesis::<TestVersions>(
+            &TestValidatedState::default(),
+            &TestInstanceState::default(),
+        )
+        .await
+        .to_qc2(),
+        upgrade_certificate: None,
+        view_change_evidence: None,
+        drb_result: INITIAL_DRB_RESULT,
+        drb_seed: INITIAL_DRB_SEED_INPUT,
+    };
+
+    // The proposal is signed by the genuine leader of the view
+    let leader = membership.leader(view, epoch).unwrap();
+    let leader_config = validators.iter().find(|v| v.public_key == leader).unwrap();
+    let signed_bytes = quorum_proposal_signing_bytes::<TestTypesRandomizedLeader, TestVersions>(
+        &data,
+        upgrade_lock.version_infallible(view).await,
+    );
+    let signature = BLSPubKey::sign(&leader_config.private_key, &signed_bytes).unwrap();
+    let mut proposal = Proposal {
+        data,
+        signature,
+        leader_proof: membership
+            .leader_proof(view, epoch, &leader_config.private_key)
+            .unwrap(),
+        _pd: PhantomData,
+    };
+    assert!(proposal
+        .validate_signature(&membership, epoch, &upgrade_lock)
+        .await
+        .is_ok());
+
+    // Without a proof of the leader's selection the same proposal is rejected
+    let impostor = validators.iter().find(|v| v.public_key != leader).unwrap();
+    for bad_proof in [
+        membership
+            .leader_proof(view, epoch, &impostor.private_key)
+            .unwrap(),
+        membership
+            .leader_proof(view + 1, epoch, &leader_config.private_key)
+            .unwrap(),
+        LeaderProof::Deterministic,
+    ] {
+        proposal.leader_proof = bad_proof;
+        assert!(proposal
+            .validate_signature(&membership, epoch, &upgrade_lock)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,170 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::{BLSPubKey, SignatureKey};
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::{
+        election::{Membership, MembershipChange},
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    PeerConfig, ValidatorConfig,
+};
+
+/// The public config of node `id`, holding `stake`
+fn peer(id: u64, stake: u64) -> PeerConfig<BLSPubKey> {
+    ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], id, stake, true)
+        .public_config()
+}
+
+#[test]
+fn test_incremental_changes_match_rebuilt_committee() {
+    let peers: Vec<_> = (0..5).map(|id| peer(id, 1)).collect();
+    let mut membership =
+        <TestTypes as NodeType>::Membership::new(peers.clone(), peers[..3].to_vec());
+
+    let key = |id: usize| peers[id].stake_table_entry.stake_key;
+    let changes = [
+        MembershipChange::Add {
+            peer: peer(5, 2),
+            da: true,
+        },
+        MembershipChange::Remove(key(1)),
+        MembershipChange::UpdateStake {
+            key: key(0),
+            stake: 3,
+        },
+        MembershipChange::Add {
+            peer: peer(6, 1),
+            da: false,
+        },
+        MembershipChange::UpdateStake {
+            key: key(3),
+            stake: 0,
+        },
+        MembershipChange::Remove(key(2)),
+    ];
+    for change in changes {
+        membership.apply_change(change).unwrap();
+    }
+
+    // The final member lists, with added nodes appended
+    let members = vec![peer(0, 3), peer(3, 0), peer(4, 1), peer(5, 2), peer(6, 1)];
+    let da_members = vec![peer(0, 3), peer(5, 2)];
+    assert_eq!(
+        membership,
+        <TestTypes as NodeType>::Membership::new(members, da_members)
+    );
+}
+
+#[test]
+fn test_invalid_membership_changes_are_rejected() {
+    let peers: Vec<_> = (0..3).map(|id| peer(id, 1)).collect();
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers.clone());
+    let unchanged = membership.clone();
+
+    assert!(membership
+        .apply_change(MembershipChange::Add {
+            peer: peers[0].clone(),
+            da: false,
+        })
+        .is_err());
+
+    let (outsider, _) = BLSPubKey::generated_from_seed_indexed([1u8; 32], 0);
+    assert!(membership
+        .apply_change(MembershipChange::Remove(outsider))
+        .is_err());
+    assert!(membership
+        .apply_change(MembershipChange::UpdateStake {
+            key: outsider,
+            stake: 2,
+        })
+        .is_err());
+
+    assert_eq!(membership, unchanged);
+}
+
+#[test]
+fn test_rotated_key_keeps_stake_and_position() {
+    let peers: Vec<_> = (0..4).map(|id| peer(id, id + 1)).collect();
+    let mut membership =
+        <TestTypes as NodeType>::Membership::new(peers.clone(), peers[..2].to_vec());
+    let rotated = peer(9, 2);
+
+    membership
+        .apply_change(MembershipChange::RotateKey {
+            key: peers[1].stake_table_entry.stake_key,
+            new_key: rotated.stake_table_entry.stake_key,
+        })
+        .unwrap();
+
+    // The same committee as if the node had joined with its new key
+    let mut members = peers.clone();
+    members[1] = rotated;
+    let da_members = members[..2].to_vec();
+    assert_eq!(
+        membership,
+        <TestTypes as NodeType>::Membership::new(members, da_members)
+    );
+}
+
+#[test]
+fn test_invalid_key_rotations_are_rejected() {
+    let peers: Vec<_> = (0..3).map(|id| peer(id, 1)).collect();
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers.clone());
+    let unchanged = membership.clone();
+
+    let key = |id: usize| peers[id].stake_table_entry.stake_key;
+    let (outsider, _) = BLSPubKey::generated_from_seed_indexed([1u8; 32], 0);
+    assert!(membership
+        .apply_change(MembershipChange::RotateKey {
+            key: outsider,
+            new_key: outsider,
+        })
+        .is_err());
+    assert!(membership
+        .apply_change(MembershipChange::RotateKey {
+            key: key(0),
+            new_key: key(1),
+        })
+        .is_err());
+
+    assert_eq!(membership, unchanged);
+}
+
+#[test]
+fn test_key_rotation_scheduled_for_a_later_epoch() {
+    let peers: Vec<_> = (0..4).map(|id| peer(id, 1)).collect();
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers.clone());
+    let (current, next) = (EpochNumber::new(1), EpochNumber::new(2));
+    let key = peers[0].stake_table_entry.stake_key;
+    let (new_key, _) = BLSPubKey::generated_from_seed_indexed([1u8; 32], 0);
+
+    // The committee acknowledges the rotation from the next epoch on
+    membership
+        .reconfigure(next, peers.clone(), peers.clone())
+        .unwrap();
+    membership
+        .apply_change(MembershipChange::RotateKey { key, new_key })
+        .unwrap();
+
+    // Until then, the old key is the one which signs and leads
+    assert!(membership.has_stake(&key, current) && membership.has_da_stake(&key, current));
+    assert!(!membership.has_stake(&new_key, current));
+    assert_eq!(membership.leader(ViewNumber::new(0), current).unwrap(), key);
+
+    assert!(membership.has_stake(&new_key, next) && membership.has_da_stake(&new_key, next));
+    assert!(!membership.has_stake(&key, next));
+    assert_eq!(
+        membership.leader(ViewNumber::new(0), next).unwrap(),
+        new_key
+    );
+    assert_eq!(
+        membership.total_nodes(next),
+        membership.total_nodes(current)
+    );
+}
@@ -0,0 +1,61 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::{
+    traits::election::{
+        randomized_committee::RandomizedCommittee, static_committee::StaticCommittee,
+    },
+    types::BLSPubKey,
+};
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+use primitive_types::U256;
+
+/// Generic code which only knows the membership through the trait
+fn scheduled_changes<TYPES: NodeType>(
+    membership: &TYPES::Membership,
+    key: &TYPES::SignatureKey,
+    epoch: TYPES::Epoch,
+) -> Option<Vec<(TYPES::View, U256)>> {
+    membership
+        .as_any()
+        .downcast_ref::<StaticCommittee<TYPES>>()
+        .map(|committee| committee.scheduled_stake_changes(key, epoch))
+}
+
+#[test]
+fn test_membership_downcasts_to_its_implementation() {
+    let peers: Vec<_> = (0..4)
+        .map(|id| {
+            ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], id, 1, true)
+                .public_config()
+        })
+        .collect();
+    let key = peers[1].stake_table_entry.stake_key;
+
+    let mut membership: <TestTypes as NodeType>::Membership =
+        StaticCommittee::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    membership.schedule_stake_change(key, epoch, ViewNumber::new(10), U256::zero());
+
+    assert_eq!(
+        scheduled_changes::<TestTypes>(&membership, &key, epoch),
+        Some(vec![(ViewNumber::new(10), U256::zero())])
+    );
+
+    // Downcasting to another implementation fails
+    assert!(membership
+        .as_any()
+        .downcast_ref::<RandomizedCommittee<TestTypes>>()
+        .is_none());
+}
@@ -0,0 +1,83 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use hotshot::{tasks::task_state::CreateTaskState, types::BLSPubKey};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{da::DaTaskState, view_sync::ViewSyncTaskState};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::{
+        election::{Membership, MembershipChange},
+        node_implementation::ConsensusTime,
+    },
+    ValidatorConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_membership_mut_changes_are_seen_by_running_tasks() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let da_state = DaTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let view_sync_state = ViewSyncTaskState::<TestTypes, TestVersions>::create_from(&handle).await;
+    let epoch = EpochNumber::new(0);
+
+    // Every task shares the membership of the node, rather than holding a copy of it
+    assert!(Arc::ptr_eq(
+        &da_state.membership,
+        &handle.hotshot.memberships
+    ));
+    assert!(Arc::ptr_eq(
+        &view_sync_state.membership,
+        &handle.hotshot.memberships
+    ));
+
+    let total_nodes = handle.hotshot.memberships.read().await.total_nodes(epoch);
+    let newcomer = ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([1u8; 32], 0, 1, true)
+        .public_config();
+    let newcomer_key = newcomer.stake_table_entry.stake_key;
+    assert!(!da_state
+        .membership
+        .read()
+        .await
+        .has_da_stake(&newcomer_key, epoch));
+
+    // Add a node, and remove this one, while the tasks hold on to the membership
+    let mut membership = handle.membership_mut().await;
+    membership
+        .apply_change(MembershipChange::Add {
+            peer: newcomer,
+            da: true,
+        })
+        .unwrap();
+    membership
+        .apply_change(MembershipChange::Remove(handle.public_key()))
+        .unwrap();
+    drop(membership);
+
+    let membership_reader = da_state.membership.read().await;
+    assert!(membership_reader.has_da_stake(&newcomer_key, epoch));
+    assert!(!membership_reader.has_stake(&handle.public_key(), epoch));
+    assert_eq!(membership_reader.total_nodes(epoch), total_nodes);
+    drop(membership_reader);
+
+    assert!(view_sync_state
+        .membership
+        .read()
+        .await
+        .has_stake(&newcomer_key, epoch));
+
+    // The removed node is no longer picked to lead
+    for view in 0..total_nodes as u64 {
+        let leader = handle.leader(ViewNumber::new(view), epoch).await.unwrap();
+        assert_ne!(leader, handle.public_key());
+    }
+}
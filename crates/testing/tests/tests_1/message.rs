@@ -8,12 +8,18 @@
 use std::marker::PhantomData;
 
 use committable::Committable;
-use hotshot_example_types::node_types::TestTypes;
+use futures::StreamExt;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
 use hotshot_types::{
-    message::{GeneralConsensusMessage, Message, MessageKind, SequencingMessage},
+    message::{
+        convert_proposal, DaConsensusMessage, GeneralConsensusMessage, Message, MessageClass,
+        MessageKind, SequencingMessage,
+    },
+    request_response::ProposalRequestPayload,
     signature_key::BLSPubKey,
     simple_certificate::SimpleCertificate,
-    simple_vote::ViewSyncCommitData,
+    simple_vote::{DaData, ViewSyncCommitData},
     traits::{node_implementation::ConsensusTime, signature_key::SignatureKey},
 };
 use vbs::{
@@ -41,8 +47,14 @@ fn version_number_at_start_of_serialization() {
         relay: 37,
         round: view_number,
     };
-    let simple_certificate =
-        SimpleCertificate::new(data.clone(), data.commit(), view_number, None, PhantomData);
+    let simple_certificate = SimpleCertificate::new(
+        data.clone(),
+        data.commit(),
+        view_number,
+        ConsensusTime::genesis(),
+        None,
+        PhantomData,
+    );
     let message = Message {
         sender,
         kind: MessageKind::Consensus(SequencingMessage::General(
@@ -76,7 +88,7 @@ async fn test_certificate2_validity() {
     let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(node_id)
         .await
         .0;
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership.clone());
 
@@ -103,8 +115,8 @@ async fn test_certificate2_validity() {
 
     assert!(
         qc.is_valid_cert(
-            membership.stake_table(EpochNumber::new(0)),
-            membership.success_threshold(EpochNumber::new(0)),
+            &membership,
+            EpochNumber::new(0),
             &handle.hotshot.upgrade_lock
         )
         .await
@@ -112,8 +124,8 @@ async fn test_certificate2_validity() {
 
     assert!(
         qc2.is_valid_cert(
-            membership.stake_table(EpochNumber::new(0)),
-            membership.success_threshold(EpochNumber::new(0)),
+            &membership,
+            EpochNumber::new(0),
             &handle.hotshot.upgrade_lock
         )
         .await
@@ -130,3 +142,115 @@ async fn test_certificate2_validity() {
 
     assert!(leaf2.parent_commitment() == parent_leaf2.commit());
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_message_classification_and_routing_key() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let mut generator = TestViewGenerator::generate(membership);
+    let view = (&mut generator).take(2).collect::<Vec<_>>().await[1].clone();
+    let view_number = view.view_number;
+
+    let sync_data: ViewSyncCommitData<TestTypes> = ViewSyncCommitData {
+        relay: 0,
+        round: view_number,
+    };
+    let sync_certificate = SimpleCertificate::new(
+        sync_data.clone(),
+        sync_data.commit(),
+        view_number,
+        view.epoch_number,
+        None,
+        PhantomData,
+    );
+    let da_data = DaData {
+        payload_commit: view.da_certificate.data.payload_commit,
+    };
+
+    let messages = vec![
+        (
+            SequencingMessage::General(GeneralConsensusMessage::Proposal(convert_proposal(
+                view.quorum_proposal.clone(),
+            ))),
+            MessageClass::Proposal,
+        ),
+        (
+            SequencingMessage::General(GeneralConsensusMessage::ProposalResponse(
+                convert_proposal(view.quorum_proposal.clone()),
+            )),
+            MessageClass::Proposal,
+        ),
+        (
+            SequencingMessage::General(GeneralConsensusMessage::Vote(
+                view.create_quorum_vote(&handle).await.to_vote(),
+            )),
+            MessageClass::Vote,
+        ),
+        (
+            SequencingMessage::General(GeneralConsensusMessage::ViewSyncCommitCertificate(
+                sync_certificate,
+            )),
+            MessageClass::Certificate,
+        ),
+        (
+            SequencingMessage::General(GeneralConsensusMessage::HighQc(
+                view.quorum_proposal.data.justify_qc.clone(),
+            )),
+            MessageClass::Certificate,
+        ),
+        (
+            SequencingMessage::General(GeneralConsensusMessage::ProposalRequested(
+                ProposalRequestPayload {
+                    view_number,
+                    key: handle.public_key(),
+                },
+                view.quorum_proposal.signature.clone(),
+            )),
+            MessageClass::InternalTrigger,
+        ),
+        (
+            SequencingMessage::Da(DaConsensusMessage::DaProposal(view.da_proposal.clone())),
+            MessageClass::Proposal,
+        ),
+        (
+            SequencingMessage::Da(DaConsensusMessage::DaVote(
+                view.create_da_vote(da_data, &handle).await,
+            )),
+            MessageClass::Vote,
+        ),
+        (
+            SequencingMessage::Da(DaConsensusMessage::DaCertificate(
+                view.da_certificate.clone(),
+            )),
+            MessageClass::Certificate,
+        ),
+        (
+            SequencingMessage::Da(DaConsensusMessage::VidDisperseMsg(
+                view.vid_proposal.0[0].clone(),
+            )),
+            MessageClass::Proposal,
+        ),
+    ];
+
+    for (message, class) in messages {
+        assert_eq!(message.class(), class, "{message:?}");
+        assert_eq!(message.is_proposal(), class == MessageClass::Proposal);
+        assert_eq!(message.is_vote(), class == MessageClass::Vote);
+        assert_eq!(
+            message.is_internal_trigger(),
+            class == MessageClass::InternalTrigger
+        );
+
+        // The high QC is for the parent view, everything else is for the view itself
+        let expected_view = match &message {
+            SequencingMessage::General(GeneralConsensusMessage::HighQc(qc)) => qc.view_number,
+            _ => view_number,
+        };
+        assert_eq!(message.routing_key(), (expected_view, class));
+    }
+}
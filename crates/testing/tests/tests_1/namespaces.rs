@@ -0,0 +1,100 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::{
+    block_types::{TestBlockPayload, TestMetadata, TestTransaction},
+    node_types::TestTypes,
+    state_types::{TestInstanceState, TestValidatedState},
+};
+use hotshot_types::{
+    data::{DaProposal, ViewNumber},
+    traits::{
+        block_contents::{EncodeBytes, NamespaceTable, NamespacedTransaction},
+        node_implementation::ConsensusTime,
+        BlockPayload,
+    },
+};
+
+/// A DA proposal for a block with transactions from three namespaces, submitted interleaved
+async fn multi_namespace_proposal() -> (DaProposal<TestTypes>, Vec<TestTransaction>) {
+    let transactions = vec![
+        TestTransaction::new_in_namespace(7, vec![7; 2]),
+        TestTransaction::new_in_namespace(2, vec![2; 1]),
+        TestTransaction::new_in_namespace(7, vec![7; 3]),
+        TestTransaction::new_in_namespace(5, vec![5; 4]),
+        TestTransaction::new_in_namespace(2, vec![2; 5]),
+    ];
+    let (payload, metadata) = <TestBlockPayload as BlockPayload<TestTypes>>::from_transactions(
+        transactions.clone(),
+        &TestValidatedState::default(),
+        &TestInstanceState::default(),
+    )
+    .await
+    .unwrap();
+    let proposal = DaProposal {
+        encoded_transactions: payload.encode(),
+        metadata,
+        view_number: ViewNumber::new(1),
+    };
+
+    (proposal, transactions)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_namespace_ranges_of_a_multi_namespace_block() {
+    let (proposal, transactions) = multi_namespace_proposal().await;
+
+    for ns in [2, 5, 7] {
+        let in_namespace: Vec<_> = transactions
+            .iter()
+            .filter(|txn| txn.namespace() == ns)
+            .cloned()
+            .collect();
+        let range = proposal.namespace_range(ns).unwrap().unwrap();
+        assert_eq!(
+            proposal.encoded_transactions[range],
+            TestTransaction::encode(&in_namespace)
+        );
+    }
+    assert_eq!(proposal.namespace_range(3).unwrap(), None);
+
+    // Every transaction comes back out of the payload in its own namespace
+    let decoded = <TestBlockPayload as BlockPayload<TestTypes>>::from_bytes(
+        &proposal.encoded_transactions,
+        &proposal.metadata,
+    );
+    let mut by_namespace = transactions;
+    by_namespace.sort_by_key(NamespacedTransaction::namespace);
+    assert_eq!(decoded.transactions, by_namespace);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_malformed_namespace_table_is_an_error() {
+    let (mut proposal, _) = multi_namespace_proposal().await;
+    let payload_len = proposal.encoded_transactions.len();
+
+    // A table whose second namespace ends before the first one does
+    let decreasing: NamespaceTable =
+        serde_json::from_str(&format!(r#"{{"entries":[[2,{payload_len}],[5,1]]}}"#)).unwrap();
+    proposal.metadata = TestMetadata {
+        num_transactions: 2,
+        namespaces: decreasing,
+    };
+    assert!(proposal.namespace_range(5).is_err());
+    assert!(
+        <TestBlockPayload as BlockPayload<TestTypes>>::validate_metadata(
+            &proposal.metadata,
+            payload_len
+        )
+        .is_err()
+    );
+
+    // A table which runs past the end of the payload
+    let (intact, _) = multi_namespace_proposal().await;
+    proposal.encoded_transactions = intact.encoded_transactions[..payload_len - 1].into();
+    proposal.metadata = intact.metadata;
+    assert!(proposal.namespace_range(2).is_err());
+}
@@ -0,0 +1,54 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::{BLSPubKey, SignatureKey};
+use hotshot_example_types::{
+    block_types::TestTransaction,
+    node_types::{TestTypes, TestVersions},
+};
+use hotshot_task_impls::network::{strip_network_id, tag_network_id, NETWORK_ID_TAG_LEN};
+use hotshot_types::{
+    data::ViewNumber,
+    message::{DataMessage, Message, MessageKind, UpgradeLock},
+    traits::node_implementation::ConsensusTime,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_message_from_foreign_network_is_rejected_before_decode() {
+    let testnet_id = 1;
+    let mainnet_id = 2;
+
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let message: Message<TestTypes> = Message {
+        sender: BLSPubKey::generated_from_seed_indexed([0u8; 32], 0).0,
+        kind: MessageKind::Data(DataMessage::SubmitTransaction(
+            TestTransaction::new(vec![1, 2, 3]),
+            ViewNumber::new(0),
+        )),
+    };
+    let serialized = upgrade_lock.serialize(&message).await.unwrap();
+    let tagged = tag_network_id(testnet_id, serialized.clone());
+    assert_eq!(tagged.len(), NETWORK_ID_TAG_LEN + serialized.len());
+
+    // The payload is a perfectly decodable message, but it is dropped before it is decoded.
+    assert!(strip_network_id(mainnet_id, &tagged).is_err());
+
+    // A node on the same network gets the original message back.
+    let payload = strip_network_id(testnet_id, &tagged).unwrap();
+    assert_eq!(payload, serialized.as_slice());
+    let decoded: Message<TestTypes> = upgrade_lock.deserialize(payload).await.unwrap();
+    assert_eq!(decoded.sender, message.sender);
+
+    // Frames too short to carry a tag are dropped as well.
+    assert!(strip_network_id(testnet_id, &[0u8; NETWORK_ID_TAG_LEN - 1]).is_err());
+
+    // The default network id leaves messages untagged, as they were before network ids.
+    assert_eq!(tag_network_id(0, serialized.clone()), serialized);
+    assert_eq!(
+        strip_network_id(0, &serialized).unwrap(),
+        serialized.as_slice()
+    );
+}
@@ -64,11 +64,16 @@ async fn test_network_task() {
             network: network.clone(),
             view: ViewNumber::new(0),
             epoch: EpochNumber::new(0),
-            membership: membership.clone(),
+            max_epoch_skip: config.max_epoch_skip,
+            membership: Arc::new(RwLock::new(membership.clone())),
             upgrade_lock: upgrade_lock.clone(),
+            network_id: config.network_id,
             storage,
-            consensus,
+            consensus: consensus.clone(),
             transmit_tasks: BTreeMap::new(),
+            participation: Arc::default(),
+            batch_justify_qcs: false,
+            last_justify_qc: None,
         };
     let (tx, rx) = async_broadcast::broadcast(10);
     let mut task_reg = ConsensusTaskRegistry::new();
@@ -85,8 +90,10 @@ async fn test_network_task() {
         out_tx_internal.clone(),
         out_tx_external.clone(),
         upgrade_lock,
+        config.network_id,
         network.clone(),
         public_key,
+        consensus,
     )
     .await;
 
@@ -233,11 +240,16 @@ async fn test_network_storage_fail() {
             network: network.clone(),
             view: ViewNumber::new(0),
             epoch: EpochNumber::new(0),
-            membership: membership.clone(),
+            max_epoch_skip: config.max_epoch_skip,
+            membership: Arc::new(RwLock::new(membership.clone())),
             upgrade_lock: upgrade_lock.clone(),
+            network_id: config.network_id,
             storage,
-            consensus,
+            consensus: consensus.clone(),
             transmit_tasks: BTreeMap::new(),
+            participation: Arc::default(),
+            batch_justify_qcs: false,
+            last_justify_qc: None,
         };
     let (tx, rx) = async_broadcast::broadcast(10);
     let mut task_reg = ConsensusTaskRegistry::new();
@@ -255,8 +267,10 @@ async fn test_network_storage_fail() {
         out_tx_internal.clone(),
         out_tx_external.clone(),
         upgrade_lock,
+        config.network_id,
         network.clone(),
         public_key,
+        consensus,
     )
     .await;
 
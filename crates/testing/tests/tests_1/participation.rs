@@ -0,0 +1,148 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+use async_broadcast::{Receiver, Sender};
+use async_lock::RwLock;
+use hotshot::traits::implementations::MemoryNetwork;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task::task::{ConsensusTaskRegistry, Task};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    network::{NetworkEventTaskState, Participation},
+};
+use hotshot_testing::{
+    block_builder::SimpleBuilderImplementation, helpers::build_system_handle, scenario::Scenario,
+    test_builder::TestDescription, test_task::add_network_message_test_task,
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::{
+    consensus::OuterConsensus,
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+};
+use tokio::time::timeout;
+
+/// Whether the proposal sent through the network task reaches the network
+async fn proposal_is_sent(
+    tx: &Sender<Arc<HotShotEvent<TestTypes>>>,
+    rx: &mut Receiver<Arc<HotShotEvent<TestTypes>>>,
+    event: HotShotEvent<TestTypes>,
+) -> bool {
+    tx.broadcast_direct(Arc::new(event)).await.unwrap();
+    match timeout(Duration::from_millis(100), rx.recv_direct()).await {
+        Ok(received) => {
+            assert!(matches!(
+                received.unwrap().as_ref(),
+                HotShotEvent::QuorumProposalRecv(..)
+            ));
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_paused_node_sends_no_proposals_until_the_view_after_resuming() {
+    hotshot::helpers::initialize_logging();
+
+    let builder: TestDescription<TestTypes, MemoryImpl, TestVersions> =
+        TestDescription::default_multiple_rounds();
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let node_id = 1;
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(node_id)
+        .await
+        .0;
+    let launcher = builder.gen_launcher(node_id);
+    let network = (launcher.resource_generator.channel_generator)(node_id).await;
+    let config = launcher.resource_generator.config.clone();
+    let public_key = launcher.resource_generator.validator_config.public_key;
+    let consensus = OuterConsensus::new(handle.hotshot.consensus());
+    let all_nodes = config.known_nodes_with_stake.clone();
+    let membership = <TestTypes as NodeType>::Membership::new(all_nodes.clone(), all_nodes);
+
+    let participation = Arc::new(Participation::default());
+    let network_state: NetworkEventTaskState<TestTypes, TestVersions, MemoryNetwork<_>, _> =
+        NetworkEventTaskState {
+            network: network.clone(),
+            view: ViewNumber::new(0),
+            epoch: EpochNumber::new(0),
+            max_epoch_skip: config.max_epoch_skip,
+            membership: Arc::new(RwLock::new(membership.clone())),
+            upgrade_lock: upgrade_lock.clone(),
+            network_id: config.network_id,
+            storage: Arc::new(RwLock::new((launcher.resource_generator.storage)(node_id))),
+            consensus: consensus.clone(),
+            transmit_tasks: BTreeMap::new(),
+            participation: Arc::clone(&participation),
+            batch_justify_qcs: false,
+            last_justify_qc: None,
+        };
+    let (tx, rx) = async_broadcast::broadcast(10);
+    let mut task_reg = ConsensusTaskRegistry::new();
+    task_reg.run_task(Task::new(network_state, tx.clone(), rx));
+
+    let (out_tx_internal, mut out_rx_internal) = async_broadcast::broadcast(10);
+    let (out_tx_external, _) = async_broadcast::broadcast(10);
+    add_network_message_test_task(
+        out_tx_internal,
+        out_tx_external,
+        upgrade_lock,
+        config.network_id,
+        network,
+        public_key,
+        consensus,
+    )
+    .await;
+
+    let mut generator = TestViewGenerator::generate(membership);
+    let view_one = generator.next().await.unwrap().quorum_proposal;
+    let view_two = generator.next().await.unwrap().quorum_proposal;
+    let send = |proposal| HotShotEvent::QuorumProposalSend(proposal, public_key);
+
+    participation.pause();
+    assert!(participation.is_paused());
+    assert!(!proposal_is_sent(&tx, &mut out_rx_internal, send(view_one.clone())).await);
+
+    // Resuming in view 1 re-engages from view 2
+    participation.resume(1);
+    assert!(!participation.is_paused());
+    assert!(!proposal_is_sent(&tx, &mut out_rx_internal, send(view_one)).await);
+    assert!(proposal_is_sent(&tx, &mut out_rx_internal, send(view_two)).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_cluster_continues_while_a_node_is_paused() {
+    hotshot::helpers::initialize_logging();
+
+    let mut metadata: TestDescription<TestTypes, MemoryImpl, TestVersions> =
+        TestDescription::default_multiple_rounds();
+
+    // Node 3 sits out its leadership of view 13, and must lead view 23 again after resuming
+    metadata.spinning_properties = Scenario::new()
+        .pause_participation(4, 3)
+        .resume_participation(16, 3)
+        .into();
+    metadata.overall_safety_properties.num_successful_views = 25;
+    metadata.overall_safety_properties.num_failed_views = 1;
+    metadata.overall_safety_properties.expected_views_to_fail =
+        HashMap::from([(ViewNumber::new(13), false)]);
+
+    metadata
+        .gen_launcher(0)
+        .launch()
+        .run_test::<SimpleBuilderImplementation>()
+        .await;
+}
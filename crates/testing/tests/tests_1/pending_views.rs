@@ -0,0 +1,63 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use hotshot_example_types::{
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    state_types::TestValidatedState,
+};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    consensus::PendingViewInfo,
+    data::{Leaf2, ViewNumber},
+    traits::node_implementation::ConsensusTime,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_pending_views_reports_view_without_qc() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let mut generator = TestViewGenerator::generate(membership);
+    let view = generator.next().await.unwrap();
+
+    // We received the proposal for view 1, but no QC for it ever formed
+    let consensus = handle.hotshot.consensus();
+    let mut consensus_writer = consensus.write().await;
+    consensus_writer
+        .update_leaf(
+            Leaf2::from_quorum_proposal(&view.quorum_proposal.data),
+            Arc::new(TestValidatedState::default()),
+            None,
+        )
+        .unwrap();
+    consensus_writer.update_view(ViewNumber::new(2)).unwrap();
+    drop(consensus_writer);
+
+    let status =
+        |view: u64, proposal_received: bool, qc_formed: bool, decided: bool| PendingViewInfo::<
+            TestTypes,
+        > {
+            view: ViewNumber::new(view),
+            proposal_received,
+            da_certificate_formed: false,
+            qc_formed,
+            decided,
+        };
+    assert_eq!(
+        consensus.read().await.pending_views(),
+        vec![
+            status(0, true, true, true),
+            status(1, true, false, false),
+            status(2, false, false, false),
+        ]
+    );
+}
@@ -0,0 +1,99 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use async_lock::RwLock;
+use async_trait::async_trait;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{
+    block_builder::SimpleBuilderImplementation,
+    completion_task::{CompletionTaskDescription, TimeBasedCompletionTaskDescription},
+    test_builder::TestDescription,
+    test_runner::Node,
+    test_task::{AnyTestTaskState, TestResult, TestTaskState, TestTaskStateSeed},
+};
+use hotshot_types::event::Event;
+
+/// The `next_view_timeout` given to node 0 only
+const NODE_0_TIMEOUT: u64 = 4321;
+
+/// Checks that only node 0 was started with `NODE_0_TIMEOUT`
+struct ConfigCheck {
+    /// The `next_view_timeout` of each node, by node id
+    timeouts: Vec<(u64, u64)>,
+}
+
+#[async_trait]
+impl TestTaskState for ConfigCheck {
+    type Event = Event<TestTypes>;
+
+    async fn handle_event(&mut self, _: (Self::Event, usize)) -> Result<()> {
+        Ok(())
+    }
+
+    async fn check(&self) -> TestResult {
+        for (node_id, timeout) in &self.timeouts {
+            if (*node_id == 0) != (*timeout == NODE_0_TIMEOUT) {
+                return TestResult::Fail(Box::new(format!(
+                    "Node {node_id} was configured with an unexpected timeout of {timeout}"
+                )));
+            }
+        }
+
+        TestResult::Pass
+    }
+}
+
+/// Seed for [`ConfigCheck`]
+struct ConfigCheckSeed;
+
+#[async_trait]
+impl TestTaskStateSeed<TestTypes, MemoryImpl, TestVersions> for ConfigCheckSeed {
+    async fn into_state(
+        self: Box<Self>,
+        handles: Arc<RwLock<Vec<Node<TestTypes, MemoryImpl, TestVersions>>>>,
+    ) -> AnyTestTaskState<TestTypes> {
+        let timeouts = handles
+            .read()
+            .await
+            .iter()
+            .map(|node| (node.node_id, node.handle.hotshot.config.next_view_timeout))
+            .collect();
+
+        Box::new(ConfigCheck { timeouts })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_per_node_config_override() {
+    hotshot::helpers::initialize_logging();
+
+    let metadata = TestDescription::<TestTypes, MemoryImpl, TestVersions> {
+        completion_task_description: CompletionTaskDescription::TimeBasedCompletionTaskBuilder(
+            TimeBasedCompletionTaskDescription {
+                duration: Duration::from_secs(10),
+            },
+        ),
+        ..TestDescription::default()
+    };
+
+    let launcher = metadata.gen_launcher_with_tasks(0, vec![Box::new(ConfigCheckSeed)]);
+    let default_config = launcher.resource_generator.config.clone();
+
+    launcher
+        .with_per_node_config(move |node_id| {
+            let mut config = default_config.clone();
+            if node_id == 0 {
+                config.next_view_timeout = NODE_0_TIMEOUT;
+            }
+            config
+        })
+        .launch()
+        .run_test::<SimpleBuilderImplementation>()
+        .await;
+}
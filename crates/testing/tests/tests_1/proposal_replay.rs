@@ -0,0 +1,84 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use futures::StreamExt;
+use hotshot_example_types::node_types::{EpochsTestVersions, MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{
+    helpers::{build_system_handle, key_pair_for_id},
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::{
+    message::{quorum_proposal_signing_bytes, UpgradeLock},
+    traits::{
+        node_implementation::{NodeType, Versions},
+        signature_key::SignatureKey,
+    },
+};
+use vbs::version::StaticVersionType;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_proposal_signature_is_bound_to_its_epoch() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let view = TestViewGenerator::generate(membership.clone())
+        .next()
+        .await
+        .unwrap();
+    let (private_key, _) = key_pair_for_id::<TestTypes>(*view.view_number);
+    let legacy_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let epochs_lock = UpgradeLock::<TestTypes, EpochsTestVersions>::new();
+    let epochs_version = <EpochsTestVersions as Versions>::Epochs::VERSION;
+
+    // Before the epochs version the leaf commitment alone is signed, as it always was.
+    let legacy = view.quorum_proposal;
+    legacy
+        .validate_signature(&membership, view.epoch_number, &legacy_lock)
+        .await
+        .unwrap();
+    assert!(legacy
+        .validate_signature(&membership, view.epoch_number, &epochs_lock)
+        .await
+        .is_err());
+
+    // From the epochs version the epoch is read from the proposal itself, so the signature
+    // verifies whichever epoch the replica is in when the proposal arrives.
+    let mut bound = legacy.clone();
+    bound.signature = <TestTypes as NodeType>::SignatureKey::sign(
+        &private_key,
+        &quorum_proposal_signing_bytes::<TestTypes, EpochsTestVersions>(
+            &bound.data,
+            epochs_version,
+        ),
+    )
+    .unwrap();
+    for epoch in [view.epoch_number, view.epoch_number + 1] {
+        bound
+            .validate_signature(&membership, epoch, &epochs_lock)
+            .await
+            .unwrap();
+    }
+
+    // A signature made in another epoch does not verify.
+    let mut other_epoch = bound.data.clone();
+    other_epoch.justify_qc.epoch += 1;
+    let mut replayed = bound.clone();
+    replayed.signature = <TestTypes as NodeType>::SignatureKey::sign(
+        &private_key,
+        &quorum_proposal_signing_bytes::<TestTypes, EpochsTestVersions>(
+            &other_epoch,
+            epochs_version,
+        ),
+    )
+    .unwrap();
+    assert!(replayed
+        .validate_signature(&membership, view.epoch_number, &epochs_lock)
+        .await
+        .is_err());
+}
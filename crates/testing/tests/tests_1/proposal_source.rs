@@ -0,0 +1,71 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    block_types::{TestMetadata, TestTransaction},
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    transactions::{BlockContents, ProposalSource, TransactionTaskState},
+};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{data::ViewNumber, traits::node_implementation::ConsensusTime};
+
+/// Proposal source which always provides the same transactions
+struct FixedSource(Vec<TestTransaction>);
+
+#[async_trait]
+impl ProposalSource<TestTypes> for FixedSource {
+    async fn next_block(&self, _view: ViewNumber, _max_bytes: u64) -> BlockContents<TestTypes> {
+        BlockContents {
+            transactions: self.0.clone(),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_leader_proposes_block_from_custom_source() {
+    hotshot::helpers::initialize_logging();
+
+    // Node 2 leads view 2.
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let transactions = vec![
+        TestTransaction::new(vec![1]),
+        TestTransaction::new(vec![2, 3]),
+    ];
+
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    let mut state =
+        TransactionTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    state.proposal_source = Some(Arc::new(FixedSource(transactions.clone())));
+    let epoch = state.cur_epoch;
+
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(ViewNumber::new(2), epoch)),
+            tx,
+        )
+        .await
+        .unwrap();
+
+    let event = rx.try_recv().unwrap();
+    let HotShotEvent::BlockRecv(bundle) = event.as_ref() else {
+        panic!("Expected a block, got {event:?}");
+    };
+    assert_eq!(bundle.view_number, ViewNumber::new(2));
+    assert_eq!(
+        bundle.encoded_transactions,
+        Arc::from(TestTransaction::encode(&transactions))
+    );
+    assert_eq!(bundle.metadata, TestMetadata::new(&transactions));
+}
@@ -0,0 +1,115 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{sync::Arc, time::Duration};
+
+use async_broadcast::{Receiver, Sender};
+use committable::Commitment;
+use hotshot::{tasks::task_state::CreateTaskState, types::BLSPubKey};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{consensus::ConsensusTaskState, events::HotShotEvent};
+use hotshot_testing::{helpers::build_system_handle, test_builder::TestDescription};
+use hotshot_types::{
+    data::ViewNumber,
+    error::ConfigDiagnostic,
+    simple_vote::{QuorumData2, QuorumVote2},
+    traits::node_implementation::ConsensusTime,
+    ValidatorConfig,
+};
+
+/// The quorum formation timeout of the leader under test
+const QUORUM_FORMATION_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Send a quorum vote for `view` from each of `validators` to the consensus task
+async fn send_votes(
+    state: &mut ConsensusTaskState<TestTypes, MemoryImpl, TestVersions>,
+    validators: &[ValidatorConfig<BLSPubKey>],
+    view: ViewNumber,
+    tx: &Sender<Arc<HotShotEvent<TestTypes>>>,
+) {
+    for validator in validators {
+        let vote = QuorumVote2::create_signed_vote(
+            QuorumData2 {
+                leaf_commit: Commitment::from_raw([7; 32]),
+            },
+            view,
+            state.cur_epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &state.upgrade_lock,
+        )
+        .await
+        .unwrap();
+        state
+            .handle(Arc::new(HotShotEvent::QuorumVoteRecv(vote)), tx.clone())
+            .await
+            .unwrap();
+    }
+}
+
+/// The views of the QCs formed so far
+fn formed_qcs(rx: &mut Receiver<Arc<HotShotEvent<TestTypes>>>) -> Vec<ViewNumber> {
+    let mut views = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        if let HotShotEvent::Qc2Formed(either::Left(qc)) = event.as_ref() {
+            views.push(qc.view_number);
+        }
+    }
+    views
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_qc_is_abandoned_after_quorum_formation_timeout() {
+    hotshot::helpers::initialize_logging();
+
+    // Node 2 leads views 2 and 12, so it collects the votes for views 1 and 11.
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let mut state =
+        ConsensusTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    state.quorum_formation_timeout = Some(QUORUM_FORMATION_TIMEOUT);
+    assert!(QUORUM_FORMATION_TIMEOUT < Duration::from_millis(state.timeout));
+    let (tx, mut rx) = async_broadcast::broadcast(64);
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..10)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+
+    // Votes arriving within the timeout form a QC.
+    send_votes(&mut state, &validators, ViewNumber::new(11), &tx).await;
+    assert_eq!(formed_qcs(&mut rx), vec![ViewNumber::new(11)]);
+
+    // Votes arriving after the timeout, but well within the view timeout, do not.
+    let view = ViewNumber::new(1);
+    send_votes(&mut state, &validators[..3], view, &tx).await;
+    tokio::time::sleep(QUORUM_FORMATION_TIMEOUT * 2).await;
+    send_votes(&mut state, &validators[3..], view, &tx).await;
+
+    assert!(formed_qcs(&mut rx).is_empty());
+    assert!(state.abandoned_quorum_views.contains(&view));
+    assert!(!state.vote_collectors.contains_key(&view));
+}
+
+#[test]
+fn test_quorum_formation_timeout_is_bounded_by_view_timeout() {
+    let mut config = TestDescription::<TestTypes, MemoryImpl, TestVersions>::default()
+        .gen_launcher(0)
+        .resource_generator
+        .config;
+    let view_timeout = Duration::from_millis(config.next_view_timeout);
+
+    config.quorum_formation_timeout = Some(view_timeout);
+    assert_eq!(config.validate(), Ok(()));
+
+    config.quorum_formation_timeout = Some(view_timeout + Duration::from_millis(1));
+    assert_eq!(
+        config.validate(),
+        Err(vec![ConfigDiagnostic::QuorumTimeoutExceedsViewTimeout {
+            quorum_formation_timeout: view_timeout + Duration::from_millis(1),
+            next_view_timeout: config.next_view_timeout,
+        }])
+    );
+}
@@ -54,7 +54,7 @@ async fn test_quorum_proposal_recv_task() {
     let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
         .await
         .0;
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
     let consensus = handle.hotshot.consensus();
     let mut consensus_writer = consensus.write().await;
 
@@ -129,7 +129,7 @@ async fn test_quorum_proposal_recv_task_liveness_check() {
     let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(4)
         .await
         .0;
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
     let consensus = handle.hotshot.consensus();
     let mut consensus_writer = consensus.write().await;
 
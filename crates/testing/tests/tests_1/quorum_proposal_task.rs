@@ -51,7 +51,7 @@ async fn test_quorum_proposal_task_quorum_proposal_view_1() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let payload_commitment = build_payload_commitment::<TestTypes>(
         &membership,
@@ -107,9 +107,7 @@ async fn test_quorum_proposal_task_quorum_proposal_view_1() {
             SendPayloadCommitmentAndMetadata(
                 payload_commitment,
                 builder_commitment,
-                TestMetadata {
-                    num_transactions: 0
-                },
+                TestMetadata::default(),
                 ViewNumber::new(1),
                 vec1![builder_fee.clone()],
                 None,
@@ -145,7 +143,7 @@ async fn test_quorum_proposal_task_quorum_proposal_view_gt_1() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership.clone());
 
@@ -198,9 +196,7 @@ async fn test_quorum_proposal_task_quorum_proposal_view_gt_1() {
                     EpochNumber::new(1)
                 ),
                 builder_commitment.clone(),
-                TestMetadata {
-                    num_transactions: 0
-                },
+                TestMetadata::default(),
                 ViewNumber::new(1),
                 vec1![builder_fee.clone()],
                 None,
@@ -308,7 +304,7 @@ async fn test_quorum_proposal_task_qc_timeout() {
     let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(node_id)
         .await
         .0;
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let payload_commitment = build_payload_commitment::<TestTypes>(
         &membership,
@@ -354,9 +350,7 @@ async fn test_quorum_proposal_task_qc_timeout() {
         SendPayloadCommitmentAndMetadata(
             payload_commitment,
             builder_commitment,
-            TestMetadata {
-                num_transactions: 0
-            },
+            TestMetadata::default(),
             ViewNumber::new(3),
             vec1![null_block::builder_fee::<TestTypes, TestVersions>(
                 membership.total_nodes(EpochNumber::new(1)),
@@ -396,7 +390,7 @@ async fn test_quorum_proposal_task_view_sync() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let payload_commitment = build_payload_commitment::<TestTypes>(
         &membership,
@@ -444,9 +438,7 @@ async fn test_quorum_proposal_task_view_sync() {
         SendPayloadCommitmentAndMetadata(
             payload_commitment,
             builder_commitment,
-            TestMetadata {
-                num_transactions: 0
-            },
+            TestMetadata::default(),
             ViewNumber::new(2),
             vec1![null_block::builder_fee::<TestTypes, TestVersions>(
                 membership.total_nodes(EpochNumber::new(1)),
@@ -484,7 +476,7 @@ async fn test_quorum_proposal_task_liveness_check() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership.clone());
 
@@ -536,9 +528,7 @@ async fn test_quorum_proposal_task_liveness_check() {
                     EpochNumber::new(1)
                 ),
                 builder_commitment.clone(),
-                TestMetadata {
-                    num_transactions: 0
-                },
+                TestMetadata::default(),
                 ViewNumber::new(1),
                 vec1![builder_fee.clone()],
                 None,
@@ -642,7 +632,7 @@ async fn test_quorum_proposal_task_with_incomplete_events() {
     let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
         .await
         .0;
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership);
 
@@ -45,7 +45,7 @@ async fn test_quorum_vote_task_success() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership.clone());
 
@@ -112,7 +112,7 @@ async fn test_quorum_vote_task_miss_dependency() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership.clone());
 
@@ -196,7 +196,7 @@ async fn test_quorum_vote_task_incorrect_dependency() {
         .await
         .0;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership);
 
@@ -0,0 +1,158 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_lock::RwLock;
+use async_trait::async_trait;
+use hotshot::types::{BLSPubKey, EventType, SignatureKey};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::network::Participation;
+use hotshot_testing::{
+    block_builder::SimpleBuilderImplementation,
+    test_builder::TestDescription,
+    test_runner::Node,
+    test_task::{AnyTestTaskState, TestResult, TestTaskState, TestTaskStateSeed},
+};
+use hotshot_types::{
+    consensus::ParticipationMode,
+    data::EpochNumber,
+    event::Event,
+    traits::{
+        election::Membership, node_implementation::ConsensusTime,
+        signature_key::StakeTableEntryType,
+    },
+    PeerConfig, ValidatorConfig,
+};
+
+/// The node following consensus as a read replica
+const REPLICA: u64 = 9;
+
+/// The public key of the read replica
+fn replica_key() -> BLSPubKey {
+    ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], REPLICA, 1, false)
+        .public_key
+}
+
+#[test]
+fn test_read_replica_never_takes_part() {
+    let participation = Participation::new(ParticipationMode::ReadReplica);
+    assert!(participation.is_read_replica());
+    assert!(participation.is_paused());
+
+    // Resuming does not turn a read replica into a validator
+    participation.resume(1);
+    assert!(participation.is_paused());
+    assert!(!participation.is_engaged(2));
+
+    assert!(!Participation::new(ParticipationMode::Validator).is_paused());
+}
+
+/// Checks that the read replica decides the chain without ever signing a QC
+struct ReplicaCheck {
+    /// The stake table the QCs are signed over
+    stake_table: Vec<<BLSPubKey as SignatureKey>::StakeTableEntry>,
+    /// Leaves decided by the read replica
+    replica_decided: usize,
+    /// QCs the read replica is among the signers of
+    signed_by_replica: usize,
+}
+
+#[async_trait]
+impl TestTaskState for ReplicaCheck {
+    type Event = Event<TestTypes>;
+
+    async fn handle_event(&mut self, (event, id): (Self::Event, usize)) -> Result<()> {
+        let EventType::Decide { leaf_chain, qc, .. } = event.event else {
+            return Ok(());
+        };
+        if id as u64 == REPLICA {
+            self.replica_decided += leaf_chain.len();
+        }
+        if let Some(signatures) = &qc.signatures {
+            let (_, signers) = BLSPubKey::sig_proof(signatures);
+            let replica_signed = self
+                .stake_table
+                .iter()
+                .zip(signers.iter())
+                .any(|(entry, signed)| *signed && entry.public_key() == replica_key());
+            self.signed_by_replica += usize::from(replica_signed);
+        }
+
+        Ok(())
+    }
+
+    async fn check(&self) -> TestResult {
+        if self.replica_decided == 0 {
+            return TestResult::Fail(Box::new("The read replica decided nothing"));
+        }
+        if self.signed_by_replica > 0 {
+            return TestResult::Fail(Box::new(format!(
+                "The read replica signed {} QCs",
+                self.signed_by_replica
+            )));
+        }
+
+        TestResult::Pass
+    }
+}
+
+/// Seed for [`ReplicaCheck`]
+struct ReplicaCheckSeed;
+
+#[async_trait]
+impl TestTaskStateSeed<TestTypes, MemoryImpl, TestVersions> for ReplicaCheckSeed {
+    async fn into_state(
+        self: Box<Self>,
+        handles: Arc<RwLock<Vec<Node<TestTypes, MemoryImpl, TestVersions>>>>,
+    ) -> AnyTestTaskState<TestTypes> {
+        let stake_table = handles.read().await[0]
+            .handle
+            .hotshot
+            .memberships
+            .read()
+            .await
+            .stake_table(EpochNumber::new(0));
+
+        Box::new(ReplicaCheck {
+            stake_table,
+            replica_decided: 0,
+            signed_by_replica: 0,
+        })
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_read_replica_follows_cluster_without_signing() {
+    hotshot::helpers::initialize_logging();
+
+    let metadata: TestDescription<TestTypes, MemoryImpl, TestVersions> =
+        TestDescription::default_multiple_rounds();
+    let launcher = metadata.gen_launcher_with_tasks(0, vec![Box::new(ReplicaCheckSeed)]);
+    let default_config = launcher.resource_generator.config.clone();
+
+    // The read replica holds no stake, so it is left out of every node's stake table
+    launcher
+        .with_per_node_config(move |node_id| {
+            let mut config = default_config.clone();
+            let is_replica =
+                |peer: &PeerConfig<BLSPubKey>| peer.stake_table_entry.public_key() == replica_key();
+            config
+                .known_nodes_with_stake
+                .retain(|peer| !is_replica(peer));
+            config.known_da_nodes.retain(|peer| !is_replica(peer));
+            config.num_nodes_with_stake = config.known_nodes_with_stake.len().try_into().unwrap();
+            config.da_staked_committee_size = config.known_da_nodes.len();
+            if node_id == REPLICA {
+                config.participation_mode = ParticipationMode::ReadReplica;
+            }
+            config
+        })
+        .launch()
+        .run_test::<SimpleBuilderImplementation>()
+        .await;
+}
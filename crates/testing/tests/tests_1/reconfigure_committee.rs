@@ -0,0 +1,154 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use either::Either;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{Certificate, VoteAccumulator},
+    PeerConfig, ValidatorConfig,
+};
+
+/// The epoch from which the new committee is in effect
+const RECONFIGURATION_EPOCH: u64 = 2;
+
+/// Validators with the node ids in `node_ids`
+fn validators(node_ids: std::ops::Range<u64>) -> Vec<ValidatorConfig<BLSPubKey>> {
+    node_ids
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect()
+}
+
+/// The public configs of `validators`
+fn peers(validators: &[ValidatorConfig<BLSPubKey>]) -> Vec<PeerConfig<BLSPubKey>> {
+    validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect()
+}
+
+/// A DA certificate signed by all of `validators` for `view` in `epoch`
+async fn certificate(
+    validators: &[ValidatorConfig<BLSPubKey>],
+    membership: &<TestTypes as NodeType>::Membership,
+    view: ViewNumber,
+    epoch: EpochNumber,
+) -> DaCertificate<TestTypes> {
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let mut accumulator =
+        VoteAccumulator::<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions> {
+            vote_outcomes: HashMap::new(),
+            signers: HashMap::new(),
+            phantom: PhantomData,
+            upgrade_lock: upgrade_lock.clone(),
+            metrics: Arc::default(),
+            on_certificate: None,
+        };
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+
+    for validator in validators {
+        let vote = DaVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validator.public_key,
+            &validator.private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        if let Either::Right(cert) = accumulator.accumulate(&vote, view, membership, epoch).await {
+            return cert;
+        }
+    }
+
+    panic!("The votes of the whole committee did not form a certificate");
+}
+
+/// Whether `cert` is valid against the committee of the epoch it claims
+async fn is_valid(
+    cert: &DaCertificate<TestTypes>,
+    membership: &<TestTypes as NodeType>::Membership,
+) -> bool {
+    cert.is_valid_cert(
+        membership,
+        cert.epoch,
+        &UpgradeLock::<TestTypes, TestVersions>::new(),
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_reconfiguration_keeps_earlier_certificates_valid() {
+    hotshot::helpers::initialize_logging();
+
+    let old_committee = validators(0..4);
+    let new_committee = validators(4..10);
+    let mut membership =
+        <TestTypes as NodeType>::Membership::new(peers(&old_committee), peers(&old_committee));
+    let before = EpochNumber::new(RECONFIGURATION_EPOCH - 1);
+    let after = EpochNumber::new(RECONFIGURATION_EPOCH);
+
+    // The last view of the old committee, certified before the reconfiguration is known
+    let old_cert = certificate(&old_committee, &membership, ViewNumber::new(9), before).await;
+    assert!(is_valid(&old_cert, &membership).await);
+
+    membership
+        .reconfigure(after, peers(&new_committee), peers(&new_committee))
+        .unwrap();
+
+    // Each epoch is served by its own committee
+    let old_keys = membership.committee_members(ViewNumber::new(9), before);
+    let new_keys = membership.committee_members(ViewNumber::new(10), after);
+    assert_eq!(old_keys.len(), old_committee.len());
+    assert_eq!(new_keys.len(), new_committee.len());
+    assert!(old_keys.is_disjoint(&new_keys));
+    assert_eq!(membership.total_nodes(before), old_committee.len());
+    assert_eq!(
+        membership.total_nodes(EpochNumber::new(7)),
+        new_committee.len()
+    );
+    assert!(new_keys.contains(&membership.leader(ViewNumber::new(10), after).unwrap()));
+    let upcoming: Vec<_> = membership
+        .upcoming_committee(before, 2)
+        .into_iter()
+        .map(|(epoch, members)| (epoch, members == new_keys))
+        .collect();
+    assert_eq!(upcoming, vec![(after, true), (EpochNumber::new(3), true)]);
+
+    // The certificate from before the boundary still validates against its own epoch
+    assert!(is_valid(&old_cert, &membership).await);
+    let new_cert = certificate(&new_committee, &membership, ViewNumber::new(10), after).await;
+    assert!(is_valid(&new_cert, &membership).await);
+
+    // A certificate spanning the boundary is checked against the epoch it claims, so the old
+    // committee's signatures don't carry over into the new epoch
+    let mut moved = old_cert.clone();
+    moved.epoch = after;
+    assert!(!is_valid(&moved, &membership).await);
+
+    // Reconfiguring an earlier epoch replaces the committees scheduled after it
+    membership
+        .reconfigure(before, peers(&old_committee), peers(&old_committee))
+        .unwrap();
+    assert_eq!(membership.total_nodes(after), old_committee.len());
+    assert!(membership
+        .reconfigure(after, Vec::new(), Vec::new())
+        .is_err());
+}
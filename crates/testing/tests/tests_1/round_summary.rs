@@ -0,0 +1,65 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use committable::Committable;
+use futures::StreamExt;
+use hotshot_example_types::{
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    state_types::TestValidatedState,
+};
+use hotshot_testing::{
+    helpers::build_system_handle,
+    overall_safety_task::{RoundCtx, RoundOutcome, RoundResult},
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::{event::LeafInfo, utils::CommitmentHex};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_round_summary_fields() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let mut generator = TestViewGenerator::generate(membership);
+    let view = generator.next().await.unwrap();
+
+    let decided = (
+        vec![LeafInfo::new(
+            view.leaf.clone(),
+            Arc::new(TestValidatedState::default()),
+            None,
+            None,
+        )],
+        view.quorum_proposal.data.justify_qc.clone(),
+    );
+
+    // Nodes report out of order; the summary lists them sorted.
+    let mut result = RoundResult::<TestTypes>::default();
+    result.insert_into_result(2, decided.clone(), None);
+    result.insert_into_result(0, decided, None);
+    result.update_status(2, 3, &view.leaf, true, true, 0);
+
+    let summary = result.to_summary();
+    assert_eq!(summary.outcome, RoundOutcome::Ok);
+    assert_eq!(summary.committed_leaf, Some(view.leaf.commit().to_hex()));
+    assert_eq!(summary.participating_nodes, vec![0, 2]);
+    assert!(summary.failed_nodes.is_empty());
+    assert!(summary.duration_ms.is_some());
+
+    let mut ctx = RoundCtx::<TestTypes>::default();
+    ctx.round_results.insert(view.view_number, result);
+    let json: serde_json::Value = serde_json::from_str(&ctx.to_summary().unwrap()).unwrap();
+
+    let round = &json[(*view.view_number).to_string()];
+    assert_eq!(round["outcome"], "ok");
+    assert_eq!(round["committed_leaf"], view.leaf.commit().to_hex());
+    assert_eq!(round["participating_nodes"], serde_json::json!([0, 2]));
+    assert!(round["duration_ms"].is_u64());
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use committable::Committable;
+use futures::StreamExt;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{
+    helpers::build_system_handle,
+    safety_checker::{check_safety, SafetyViolation, Trace, TraceEvent},
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::data::Leaf2;
+
+/// Generate a chain of four leaves, each extending the previous one
+async fn leaf_chain() -> Vec<Leaf2<TestTypes>> {
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let mut generator = TestViewGenerator::generate(membership);
+    (&mut generator)
+        .take(4)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(|view| view.leaf)
+        .collect()
+}
+
+/// Build a trace in which every node finishes each view and commits the leaf for it
+fn trace_for(nodes: &[usize], leaves: &[Leaf2<TestTypes>]) -> Trace<TestTypes> {
+    let mut trace = Trace::new();
+    for leaf in leaves {
+        for &node in nodes {
+            trace.events.push(TraceEvent::ViewFinished {
+                node,
+                view: leaf.view_number(),
+            });
+            trace.events.push(TraceEvent::Commit {
+                node,
+                leaf: leaf.clone(),
+            });
+        }
+    }
+
+    trace
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_safety_checker_accepts_good_trace() {
+    hotshot::helpers::initialize_logging();
+
+    let leaves = leaf_chain().await;
+
+    if let Err(report) = check_safety(&trace_for(&[0, 1, 2], &leaves)) {
+        panic!("{report}");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_safety_checker_reports_conflicting_commit() {
+    hotshot::helpers::initialize_logging();
+
+    let leaves = leaf_chain().await;
+    let mut trace = trace_for(&[0], &leaves);
+
+    // Node 1 commits a different leaf for the last view.
+    let mut conflicting = leaves[3].clone();
+    conflicting.block_header_mut().block_number += 1;
+    trace.events.push(TraceEvent::Commit {
+        node: 1,
+        leaf: conflicting.clone(),
+    });
+
+    let report = check_safety(&trace).expect_err("conflicting commits should be reported");
+    assert_eq!(
+        report.violations,
+        vec![SafetyViolation::ConflictingCommit {
+            view: leaves[3].view_number(),
+            first: (0, leaves[3].commit()),
+            second: (1, conflicting.commit()),
+        }]
+    );
+    assert!(report.to_string().contains("conflicting commits"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_safety_checker_reports_invalid_parent_and_view_regression() {
+    hotshot::helpers::initialize_logging();
+
+    let leaves = leaf_chain().await;
+
+    // Node 0 commits a leaf whose QC does not point to the leaf it committed for the previous
+    // view, then goes back to an earlier view.
+    let mut wrong_parent = leaves[0].clone();
+    wrong_parent.block_header_mut().block_number += 1;
+    let trace = Trace {
+        events: vec![
+            TraceEvent::ViewFinished {
+                node: 0,
+                view: leaves[1].view_number(),
+            },
+            TraceEvent::Commit {
+                node: 0,
+                leaf: wrong_parent.clone(),
+            },
+            TraceEvent::Commit {
+                node: 0,
+                leaf: leaves[1].clone(),
+            },
+            TraceEvent::ViewFinished {
+                node: 0,
+                view: leaves[0].view_number(),
+            },
+        ],
+    };
+
+    let report = check_safety(&trace).expect_err("invalid history should be reported");
+    assert_eq!(
+        report.violations,
+        vec![
+            SafetyViolation::InvalidParent {
+                node: 0,
+                view: leaves[1].view_number(),
+                parent_view: leaves[0].view_number(),
+                expected: Some(wrong_parent.commit()),
+                actual: leaves[0].commit(),
+            },
+            SafetyViolation::NonMonotonicView {
+                node: 0,
+                previous: leaves[1].view_number(),
+                next: leaves[0].view_number(),
+            },
+        ]
+    );
+}
@@ -0,0 +1,129 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use async_broadcast::Receiver;
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    block_types::TestTransaction,
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    state_types::TestValidatedState,
+};
+use hotshot_task_impls::{events::HotShotEvent, quorum_proposal_recv::QuorumProposalRecvTaskState};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{
+    data::{Leaf2, ViewNumber},
+    traits::node_implementation::ConsensusTime,
+};
+
+/// Drain the events broadcast so far
+fn drain(rx: &mut Receiver<Arc<HotShotEvent<TestTypes>>>) -> Vec<HotShotEvent<TestTypes>> {
+    std::iter::from_fn(|| rx.try_recv().ok())
+        .map(|event| (*event).clone())
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_conflicting_qcs_halt_the_node() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    // Two chains which agree on view 1, but certify different leaves for view 2
+    let mut generator = TestViewGenerator::generate(membership.clone());
+    let views = (&mut generator).take(3).collect::<Vec<_>>().await;
+    let mut forked_generator = TestViewGenerator::generate(membership);
+    forked_generator.next().await;
+    forked_generator.add_transactions(vec![TestTransaction::new(vec![1])]);
+    let forked_views = (&mut forked_generator).take(2).collect::<Vec<_>>().await;
+
+    let proposal = views[2].quorum_proposal.clone();
+    let forked_proposal = forked_views[1].quorum_proposal.clone();
+    let (qc_a, qc_b) = (
+        proposal.data.justify_qc.clone(),
+        forked_proposal.data.justify_qc.clone(),
+    );
+    assert_eq!(qc_a.view_number, qc_b.view_number);
+    assert_ne!(qc_a.data.leaf_commit, qc_b.data.leaf_commit);
+
+    let consensus = handle.hotshot.consensus();
+    for view in &views[..2] {
+        consensus
+            .write()
+            .await
+            .update_leaf(
+                Leaf2::from_quorum_proposal(&view.quorum_proposal.data),
+                Arc::new(TestValidatedState::default()),
+                None,
+            )
+            .unwrap();
+    }
+
+    let (tx, mut rx) = async_broadcast::broadcast(64);
+    let mut state =
+        QuorumProposalRecvTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle)
+            .await;
+    state.cur_epoch = generator.current_view.as_ref().unwrap().epoch_number;
+
+    // The first proposal is followed as usual
+    state
+        .handle(
+            Arc::new(HotShotEvent::QuorumProposalRecv(
+                proposal.clone(),
+                views[2].leader_public_key,
+            )),
+            tx.clone(),
+            rx.clone(),
+        )
+        .await;
+    assert!(drain(&mut rx)
+        .iter()
+        .any(|event| matches!(event, HotShotEvent::QuorumProposalValidated(..))));
+    assert_eq!(consensus.read().await.safety_fork(), None);
+
+    // The conflicting one is reported with both QCs as evidence
+    state
+        .handle(
+            Arc::new(HotShotEvent::QuorumProposalRecv(
+                forked_proposal,
+                forked_views[1].leader_public_key,
+            )),
+            tx.clone(),
+            rx.clone(),
+        )
+        .await;
+    let events = drain(&mut rx);
+    assert_eq!(
+        events,
+        vec![HotShotEvent::SafetyFork {
+            view: ViewNumber::new(2),
+            qc_a,
+            qc_b,
+        }]
+    );
+    assert_eq!(
+        consensus.read().await.safety_fork(),
+        Some(ViewNumber::new(2))
+    );
+
+    // And the node follows neither chain from now on
+    state
+        .handle(
+            Arc::new(HotShotEvent::QuorumProposalRecv(
+                proposal,
+                views[2].leader_public_key,
+            )),
+            tx,
+            rx.clone(),
+        )
+        .await;
+    assert!(drain(&mut rx).is_empty());
+}
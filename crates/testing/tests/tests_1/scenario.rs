@@ -0,0 +1,75 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{
+    block_builder::SimpleBuilderImplementation,
+    scenario::Scenario,
+    spinning_task::{ChangeNode, NodeAction, SpinningTaskDescription},
+    test_builder::TestDescription,
+    view_sync_task::ViewSyncTaskDescription,
+};
+
+#[test]
+fn test_scenario_compiles_to_node_changes() {
+    let description: SpinningTaskDescription = Scenario::new()
+        .partition(5, &[0, 1], &[2, 3, 4])
+        .crash(8, 2)
+        .heal(10)
+        .into();
+
+    let change = |idx, updown| ChangeNode { idx, updown };
+
+    assert_eq!(
+        description.node_changes,
+        vec![
+            (
+                5,
+                vec![
+                    change(0, NodeAction::NetworkDown),
+                    change(1, NodeAction::NetworkDown)
+                ]
+            ),
+            (8, vec![change(2, NodeAction::Down)]),
+            (
+                10,
+                vec![
+                    change(0, NodeAction::NetworkUp),
+                    change(1, NodeAction::NetworkUp)
+                ]
+            ),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scripted_partition_crash_and_heal() {
+    hotshot::helpers::initialize_logging();
+
+    let mut metadata: TestDescription<TestTypes, MemoryImpl, TestVersions> =
+        TestDescription::default_more_nodes();
+    metadata.num_nodes_with_stake = 12;
+    metadata.da_staked_committee_size = 12;
+    metadata.start_nodes = 12;
+
+    // The majority side keeps a quorum of 9 throughout, and the cut off nodes rejoin it
+    metadata.spinning_properties = Scenario::new()
+        .partition(5, &[0, 1], &(2..12).collect::<Vec<_>>())
+        .crash(8, 2)
+        .heal(10)
+        .into();
+
+    // Views led by the cut off or crashed nodes may fail, but the chain keeps committing
+    metadata.overall_safety_properties.num_failed_views = 6;
+    metadata.overall_safety_properties.num_successful_views = 15;
+    metadata.view_sync_properties = ViewSyncTaskDescription::Threshold(0, 12);
+
+    metadata
+        .gen_launcher(0)
+        .launch()
+        .run_test::<SimpleBuilderImplementation>()
+        .await;
+}
@@ -0,0 +1,91 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::{block_types::TestTransaction, node_types::TestTypes};
+use hotshot_task_impls::transactions::{FifoSelection, SelectionPolicy};
+use hotshot_types::traits::block_contents::Transaction;
+
+/// Selects the transactions paying the highest fee first, taken to be their first byte, skipping
+/// any which don't fit in what is left of the budget
+struct FeePriority;
+
+impl SelectionPolicy<TestTypes> for FeePriority {
+    fn select(&self, candidates: &[TestTransaction], max_bytes: usize) -> Vec<TestTransaction> {
+        let mut by_fee = candidates.to_vec();
+        by_fee.sort_by_key(|transaction| std::cmp::Reverse(transaction.bytes()[0]));
+
+        let mut remaining = max_bytes as u64;
+        by_fee
+            .into_iter()
+            .filter(|transaction| {
+                let fits = transaction.minimum_block_size() <= remaining;
+                if fits {
+                    remaining -= transaction.minimum_block_size();
+                }
+                fits
+            })
+            .collect()
+    }
+}
+
+/// A transaction of `size` bytes paying `fee`
+fn transaction(fee: u8, size: usize) -> TestTransaction {
+    let mut bytes = vec![0; size];
+    bytes[0] = fee;
+    TestTransaction::new(bytes)
+}
+
+/// Total size of `transactions` in bytes
+fn block_bytes(transactions: &[TestTransaction]) -> u64 {
+    transactions
+        .iter()
+        .map(Transaction::minimum_block_size)
+        .sum()
+}
+
+#[test]
+fn test_fifo_selects_in_arrival_order() {
+    let candidates: Vec<_> = (0..5).map(|i| transaction(i, 10)).collect();
+
+    let selected = SelectionPolicy::<TestTypes>::select(&FifoSelection, &candidates, 35);
+
+    assert_eq!(selected, candidates[..3]);
+}
+
+#[test]
+fn test_fee_priority_selects_highest_fees_first() {
+    let candidates = vec![
+        transaction(1, 10),
+        transaction(7, 10),
+        transaction(3, 10),
+        transaction(9, 10),
+    ];
+
+    let selected = FeePriority.select(&candidates, 20);
+
+    assert_eq!(selected, vec![transaction(9, 10), transaction(7, 10)]);
+}
+
+#[test]
+fn test_selection_fills_budget_exactly_without_exceeding_it() {
+    let candidates = vec![
+        transaction(5, 6),
+        transaction(4, 8),
+        transaction(3, 4),
+        transaction(2, 1),
+    ];
+    let max_bytes = 18;
+
+    // The first three fill the budget exactly, so the fourth no longer fits.
+    let fifo = SelectionPolicy::<TestTypes>::select(&FifoSelection, &candidates, max_bytes);
+    assert_eq!(fifo, candidates[..3]);
+    assert_eq!(block_bytes(&fifo), max_bytes as u64);
+
+    // Skipping the transaction which doesn't fit still leaves room for a cheaper one.
+    let by_fee = FeePriority.select(&candidates, 10);
+    assert_eq!(by_fee, vec![transaction(5, 6), transaction(3, 4)]);
+    assert_eq!(block_bytes(&by_fee), 10);
+}
@@ -0,0 +1,103 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::num::NonZeroUsize;
+
+use hotshot::types::{BLSPubKey, SignatureKey};
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_testing::helpers::key_pair_for_id;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    signature_cache::SignatureCache,
+    simple_vote::{DaData, DaVote},
+    traits::{block_contents::vid_commitment, node_implementation::ConsensusTime},
+    vote::is_valid_vote,
+};
+
+/// A signature under a `BLSPubKey`
+type Signature = <BLSPubKey as SignatureKey>::PureAssembledSignatureType;
+
+/// Sign `data` with the key of node `id`
+fn signed(id: u64, data: &[u8]) -> (BLSPubKey, Signature) {
+    let (key, private_key) = BLSPubKey::generated_from_seed_indexed([0u8; 32], id);
+    (key, BLSPubKey::sign(&private_key, data).unwrap())
+}
+
+#[test]
+fn test_cached_verifications_match_fresh_ones() {
+    let cache = SignatureCache::<BLSPubKey>::new(NonZeroUsize::new(16).unwrap());
+    let (key, signature) = signed(0, b"vote");
+    let (other_key, other_signature) = signed(1, b"vote");
+
+    // Each input changes the outcome, so a result is never reused for the wrong one
+    let verifications = [
+        (key, signature.clone(), b"vote".as_slice()),
+        (key, signature.clone(), b"other vote".as_slice()),
+        (other_key, signature, b"vote".as_slice()),
+        (key, other_signature, b"vote".as_slice()),
+    ];
+    for _ in 0..2 {
+        for (key, signature, data) in &verifications {
+            assert_eq!(
+                cache.validate(key, signature, data),
+                key.validate(signature, data)
+            );
+        }
+    }
+
+    assert!(cache.validate(&verifications[0].0, &verifications[0].1, b"vote"));
+    assert_eq!((cache.hits(), cache.misses()), (5, 4));
+}
+
+#[test]
+fn test_duplicate_deliveries_are_verified_once() {
+    const DELIVERIES: u64 = 50;
+    let cache = SignatureCache::<BLSPubKey>::new(NonZeroUsize::new(16).unwrap());
+    let (key, signature) = signed(0, b"vote");
+
+    for _ in 0..DELIVERIES {
+        assert!(cache.validate(&key, &signature, b"vote"));
+    }
+
+    assert_eq!((cache.hits(), cache.misses()), (DELIVERIES - 1, 1));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_each_upgrade_lock_owns_its_cache() {
+    let (private_key, public_key) = key_pair_for_id::<TestTypes>(0);
+    let epoch = EpochNumber::new(0);
+    let vote = DaVote::create_signed_vote(
+        DaData {
+            payload_commit: vid_commitment(&[], 1),
+        },
+        ViewNumber::new(1),
+        epoch,
+        &public_key,
+        &private_key,
+        &UpgradeLock::<TestTypes, TestVersions>::new(),
+    )
+    .await
+    .unwrap();
+
+    let first = UpgradeLock::<TestTypes, TestVersions>::new().with_signature_cache(16);
+    let second = UpgradeLock::<TestTypes, TestVersions>::new().with_signature_cache(16);
+    for _ in 0..2 {
+        assert!(is_valid_vote(&vote, epoch, &first).await);
+    }
+    assert!(is_valid_vote(&vote, epoch, &second).await);
+
+    // The second node verified the vote itself, rather than reusing the first node's result
+    let first = first.signature_cache.unwrap();
+    let second = second.signature_cache.unwrap();
+    assert_eq!((first.hits(), first.misses()), (1, 1));
+    assert_eq!((second.hits(), second.misses()), (0, 1));
+
+    // A capacity of zero disables caching
+    let uncached = UpgradeLock::<TestTypes, TestVersions>::new().with_signature_cache(0);
+    assert!(uncached.signature_cache.is_none());
+    assert!(is_valid_vote(&vote, epoch, &uncached).await);
+}
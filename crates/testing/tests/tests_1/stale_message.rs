@@ -0,0 +1,154 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{num::NonZeroUsize, sync::Arc};
+
+use committable::Committable;
+use hotshot::types::{BLSPubKey, Message, SignatureKey};
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{
+    events::HotShotEvent,
+    network::{NetworkMessageTaskState, ReceivedJustifyQcs},
+};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    consensus::{ConsensusMetricsValue, OuterConsensus},
+    data::{EpochNumber, ViewNumber},
+    message::{GeneralConsensusMessage, MessageKind, SequencingMessage, UpgradeLock},
+    request_response::ProposalRequestPayload,
+    simple_vote::{TimeoutData, TimeoutVote},
+    traits::{block_contents::AcceptAllTxnValidator, node_implementation::ConsensusTime},
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_stale_vote_is_dropped_on_arrival() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    let consensus = OuterConsensus::new(handle.hotshot.consensus());
+    consensus
+        .write()
+        .await
+        .update_view(ViewNumber::new(50))
+        .unwrap();
+
+    let (internal_tx, mut internal_rx) = async_broadcast::broadcast(10);
+    let (external_tx, _external_rx) = async_broadcast::broadcast(10);
+    let mut state = NetworkMessageTaskState::<TestTypes> {
+        internal_event_stream: internal_tx,
+        external_event_stream: external_tx,
+        public_key: handle.public_key(),
+        transactions_cache: lru::LruCache::new(NonZeroUsize::new(100).unwrap()),
+        transaction_validator: Arc::new(AcceptAllTxnValidator),
+        metrics: Arc::new(ConsensusMetricsValue::default()),
+        consensus,
+        max_message_age_views: 10,
+        received_justify_qcs: ReceivedJustifyQcs::default(),
+    };
+
+    let (sender, private_key) = BLSPubKey::generated_from_seed_indexed([0u8; 32], 1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let timeout_vote = |view: u64| {
+        let view = ViewNumber::new(view);
+        TimeoutVote::create_signed_vote(
+            TimeoutData { view },
+            view,
+            EpochNumber::new(0),
+            &sender,
+            &private_key,
+            &upgrade_lock,
+        )
+    };
+    let message = |vote| Message {
+        sender,
+        kind: MessageKind::Consensus(SequencingMessage::General(
+            GeneralConsensusMessage::TimeoutVote(vote),
+        )),
+    };
+
+    // A vote from 20 views ago never reaches the tasks
+    state
+        .handle_message(message(timeout_vote(30).await.unwrap()))
+        .await;
+    assert!(internal_rx.try_recv().is_err());
+
+    // A vote within the allowed age is forwarded
+    state
+        .handle_message(message(timeout_vote(45).await.unwrap()))
+        .await;
+    let event = internal_rx.try_recv().unwrap();
+    assert!(matches!(
+        event.as_ref(),
+        HotShotEvent::TimeoutVoteRecv(vote) if *vote.view_number == 45
+    ));
+
+    // A proposal request is for a past view by design, however old, so is always forwarded
+    let request = ProposalRequestPayload {
+        view_number: ViewNumber::new(1),
+        key: sender,
+    };
+    let signature = BLSPubKey::sign(&private_key, request.commit().as_ref()).unwrap();
+    state
+        .handle_message(Message {
+            sender,
+            kind: MessageKind::Consensus(SequencingMessage::General(
+                GeneralConsensusMessage::ProposalRequested(request, signature),
+            )),
+        })
+        .await;
+    let event = internal_rx.try_recv().unwrap();
+    assert!(matches!(
+        event.as_ref(),
+        HotShotEvent::QuorumProposalRequestRecv(req, _) if *req.view_number == 1
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_message_age_limit_does_not_overflow() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    let (internal_tx, mut internal_rx) = async_broadcast::broadcast(10);
+    let (external_tx, _external_rx) = async_broadcast::broadcast(10);
+    let mut state = NetworkMessageTaskState::<TestTypes> {
+        internal_event_stream: internal_tx,
+        external_event_stream: external_tx,
+        public_key: handle.public_key(),
+        transactions_cache: lru::LruCache::new(NonZeroUsize::new(100).unwrap()),
+        transaction_validator: Arc::new(AcceptAllTxnValidator),
+        metrics: Arc::new(ConsensusMetricsValue::default()),
+        consensus: OuterConsensus::new(handle.hotshot.consensus()),
+        max_message_age_views: u64::MAX,
+        received_justify_qcs: ReceivedJustifyQcs::default(),
+    };
+
+    // An unlimited age accepts a message for any view
+    let (sender, private_key) = BLSPubKey::generated_from_seed_indexed([0u8; 32], 1);
+    let view = ViewNumber::new(5);
+    let vote = TimeoutVote::create_signed_vote(
+        TimeoutData { view },
+        view,
+        EpochNumber::new(0),
+        &sender,
+        &private_key,
+        &UpgradeLock::<TestTypes, TestVersions>::new(),
+    )
+    .await
+    .unwrap();
+    state
+        .handle_message(Message {
+            sender,
+            kind: MessageKind::Consensus(SequencingMessage::General(
+                GeneralConsensusMessage::TimeoutVote(vote),
+            )),
+        })
+        .await;
+    assert!(internal_rx.try_recv().is_ok());
+}
@@ -0,0 +1,46 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::EventType;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::events::HotShotEvent;
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{data::ViewNumber, traits::node_implementation::ConsensusTime};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_starting_consensus_twice_starts_it_once() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    let mut internal_events = handle.internal_event_stream_receiver_known_impl();
+    let mut external_events = handle.event_stream_known_impl();
+
+    handle.hotshot.start_consensus().await;
+    handle.hotshot.start_consensus().await;
+
+    // Genesis is only entered once
+    let mut view_changes = 0;
+    while let Ok(event) = internal_events.try_recv() {
+        if matches!(
+            event.as_ref(),
+            HotShotEvent::ViewChange(view, _) if *view == ViewNumber::genesis()
+        ) {
+            view_changes += 1;
+        }
+    }
+    assert_eq!(view_changes, 1);
+
+    // And the genesis leaf only decided once
+    let mut decides = 0;
+    while let Ok(event) = external_events.try_recv() {
+        if matches!(event.event, EventType::Decide { .. }) {
+            decides += 1;
+        }
+    }
+    assert_eq!(decides, 1);
+}
@@ -0,0 +1,73 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::num::NonZeroU64;
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::EpochNumber,
+    traits::{
+        election::{threshold_above, threshold_at_least, Membership, ThresholdError},
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+
+#[test]
+fn test_one_node_committee_thresholds() {
+    // Every certificate of a one-node committee needs that node's vote
+    assert_eq!(threshold_above(1, 2, 3), Ok(NonZeroU64::MIN));
+    assert_eq!(threshold_above(1, 1, 3), Ok(NonZeroU64::MIN));
+
+    // But 9/10 of one node rounds down to nothing
+    let err = threshold_at_least(1, 9, 10).unwrap_err();
+    assert_eq!(
+        err,
+        ThresholdError::Zero {
+            numerator: 9,
+            denominator: 10,
+            total_nodes: 1
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "A threshold of 9/10 of a committee of 1 nodes rounds down to zero"
+    );
+
+    // The membership still derives usable thresholds for such a committee
+    let peers =
+        vec![
+            ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], 0, 1, true)
+                .public_config(),
+        ];
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+    assert_eq!(membership.success_threshold(epoch), NonZeroU64::MIN);
+    assert_eq!(membership.failure_threshold(epoch), NonZeroU64::MIN);
+    assert_eq!(membership.upgrade_threshold(epoch), NonZeroU64::MIN);
+}
+
+#[test]
+fn test_percentage_threshold_rounding_to_zero_is_an_error() {
+    assert_eq!(threshold_at_least(100, 1, 100), Ok(NonZeroU64::MIN));
+    assert!(matches!(
+        threshold_at_least(50, 1, 100),
+        Err(ThresholdError::Zero {
+            total_nodes: 50,
+            ..
+        })
+    ));
+    assert_eq!(
+        threshold_at_least(10, 9, 10),
+        Ok(NonZeroU64::new(9).unwrap())
+    );
+
+    // Fractions outside of zero to one are rejected rather than computed
+    let err = threshold_above(10, 3, 2).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid threshold fraction 3/2");
+    assert!(threshold_at_least(10, 1, 0).is_err());
+}
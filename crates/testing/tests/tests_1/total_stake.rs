@@ -0,0 +1,61 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::EpochNumber,
+    traits::{
+        election::{Membership, MembershipChange},
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+use primitive_types::U256;
+
+/// The stakes of the nodes in the committee
+const STAKES: [u64; 4] = [1, 2, 3, 4];
+
+#[test]
+fn test_total_stake_and_stake_of_follow_the_stake_table() {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..)
+        .zip(STAKES)
+        .map(|(i, stake)| ValidatorConfig::generated_from_seed_indexed([0u8; 32], i, stake, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+
+    assert_eq!(
+        membership.total_stake(epoch),
+        U256::from(STAKES.iter().sum::<u64>())
+    );
+    for (validator, stake) in validators.iter().zip(STAKES) {
+        assert_eq!(
+            membership.stake_of(&validator.public_key, epoch),
+            Some(U256::from(stake))
+        );
+    }
+
+    let outsider = ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], 9, 1, true);
+    assert_eq!(membership.stake_of(&outsider.public_key, epoch), None);
+
+    // Both follow changes to the committee
+    membership
+        .apply_change(MembershipChange::UpdateStake {
+            key: validators[3].public_key,
+            stake: 0,
+        })
+        .unwrap();
+    assert_eq!(
+        membership.total_stake(epoch),
+        U256::from(STAKES[..3].iter().sum::<u64>())
+    );
+    assert_eq!(membership.stake_of(&validators[3].public_key, epoch), None);
+}
@@ -42,19 +42,27 @@ async fn test_transaction_task_leader_two_views_in_a_row() {
 
     let (_, precompute_data) = precompute_vid_commitment(
         &[],
-        handle.hotshot.memberships.total_nodes(EpochNumber::new(0)),
+        handle
+            .hotshot
+            .memberships
+            .read()
+            .await
+            .total_nodes(EpochNumber::new(0)),
     );
 
     // current view
     let mut exp_packed_bundle = PackedBundle::new(
         vec![].into(),
-        TestMetadata {
-            num_transactions: 0,
-        },
+        TestMetadata::default(),
         current_view,
         vec1::vec1![
             null_block::builder_fee::<TestConsecutiveLeaderTypes, TestVersions>(
-                handle.hotshot.memberships.total_nodes(EpochNumber::new(0)),
+                handle
+                    .hotshot
+                    .memberships
+                    .read()
+                    .await
+                    .total_nodes(EpochNumber::new(0)),
                 <TestVersions as Versions>::Base::VERSION,
                 *ViewNumber::new(4),
             )
@@ -0,0 +1,83 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
+
+use hotshot_example_types::{block_types::TestTransaction, node_types::TestTypes};
+use hotshot_task_impls::{events::HotShotEvent, network::NetworkMessageTaskState};
+use hotshot_types::{
+    consensus::ConsensusMetricsValue,
+    data::ViewNumber,
+    message::{DataMessage, Message, MessageKind},
+    signature_key::BLSPubKey,
+    traits::{
+        block_contents::{Transaction, TxnError, TxnValidator},
+        node_implementation::{ConsensusTime, NodeType},
+        signature_key::SignatureKey,
+    },
+};
+use tokio::time::timeout;
+
+/// The largest transaction admitted by [`MaxSizeValidator`]
+const MAX_TXN_BYTES: u64 = 16;
+
+/// Rejects transactions larger than `MAX_TXN_BYTES`
+struct MaxSizeValidator;
+
+impl TxnValidator<TestTypes> for MaxSizeValidator {
+    fn validate(&self, txn: &<TestTypes as NodeType>::Transaction) -> Result<(), TxnError> {
+        let size = txn.minimum_block_size();
+        if size > MAX_TXN_BYTES {
+            return Err(TxnError::TooLarge {
+                size,
+                max: MAX_TXN_BYTES,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_oversized_transactions_are_rejected() {
+    hotshot::helpers::initialize_logging();
+
+    let (public_key, _) = BLSPubKey::generated_from_seed_indexed([0u8; 32], 0);
+    let (internal_tx, mut internal_rx) = async_broadcast::broadcast(10);
+    let (external_tx, _external_rx) = async_broadcast::broadcast(10);
+
+    let mut state = NetworkMessageTaskState::<TestTypes> {
+        internal_event_stream: internal_tx,
+        external_event_stream: external_tx,
+        public_key: public_key.clone(),
+        transactions_cache: lru::LruCache::new(NonZeroUsize::new(100).unwrap()),
+        transaction_validator: Arc::new(MaxSizeValidator),
+        metrics: Arc::new(ConsensusMetricsValue::default()),
+    };
+
+    let oversized = TestTransaction::new(vec![1; MAX_TXN_BYTES as usize + 1]);
+    let valid = TestTransaction::new(vec![2; MAX_TXN_BYTES as usize]);
+
+    for transaction in [oversized, valid.clone()] {
+        state
+            .handle_message(Message {
+                sender: public_key.clone(),
+                kind: MessageKind::Data(DataMessage::SubmitTransaction(
+                    transaction,
+                    ViewNumber::new(1),
+                )),
+            })
+            .await;
+    }
+
+    // Only the valid transaction is forwarded to the mempool.
+    let event = timeout(Duration::from_millis(100), internal_rx.recv_direct())
+        .await
+        .expect("timed out waiting for the valid transaction")
+        .expect("channel closed");
+    assert_eq!(event.as_ref(), &HotShotEvent::TransactionsRecv(vec![valid]));
+    assert!(internal_rx.is_empty());
+}
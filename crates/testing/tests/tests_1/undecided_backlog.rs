@@ -0,0 +1,109 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    state_types::TestValidatedState,
+};
+use hotshot_macros::{run_test, test_scripts};
+use hotshot_task_impls::{events::HotShotEvent::*, quorum_vote::QuorumVoteTaskState};
+use hotshot_testing::{
+    all_predicates,
+    helpers::build_system_handle,
+    predicates::event::{exact, quorum_vote_send, EventPredicate},
+    random,
+    script::{Expectations, InputOrder, TaskScript},
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::{
+    data::{EpochNumber, Leaf2, ViewNumber},
+    traits::node_implementation::ConsensusTime,
+};
+
+const TIMEOUT: Duration = Duration::from_millis(35);
+
+/// Have node 2 vote in view 2 with a QC for view 1 formed but nothing decided, so one view is
+/// undecided, and a limit of `max_undecided_views`.
+///
+/// `outcome` is the event expected after the view changes.
+async fn vote_with_one_undecided_view(
+    max_undecided_views: u64,
+    outcome: Box<EventPredicate<TestTypes>>,
+) {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let mut generator = TestViewGenerator::generate(membership);
+
+    let views = (&mut generator).take(2).collect::<Vec<_>>().await;
+    let consensus = handle.hotshot.consensus();
+    let mut consensus_writer = consensus.write().await;
+    for view in &views {
+        consensus_writer
+            .update_leaf(
+                Leaf2::from_quorum_proposal(&view.quorum_proposal.data),
+                Arc::new(TestValidatedState::default()),
+                None,
+            )
+            .unwrap();
+    }
+    consensus_writer
+        .update_high_qc(views[1].quorum_proposal.data.justify_qc.clone())
+        .unwrap();
+    assert_eq!(consensus_writer.undecided_views(), 1);
+    drop(consensus_writer);
+
+    let inputs = vec![random![
+        QuorumProposalValidated(views[1].quorum_proposal.clone(), views[0].leaf.clone()),
+        DaCertificateRecv(views[1].da_certificate.clone()),
+        VidShareRecv(
+            views[1].leader_public_key,
+            views[1].vid_proposal.0[0].clone()
+        ),
+    ]];
+
+    let expectations = vec![Expectations::from_outputs(all_predicates![
+        exact(DaCertificateValidated(views[1].da_certificate.clone())),
+        exact(VidShareValidated(views[1].vid_proposal.0[0].clone())),
+        exact(ViewChange(ViewNumber::new(3), EpochNumber::new(0))),
+        outcome,
+    ])];
+
+    let mut state =
+        QuorumVoteTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    state.max_undecided_views = max_undecided_views;
+
+    let mut script = TaskScript {
+        timeout: TIMEOUT,
+        state,
+        expectations,
+    };
+    run_test![inputs, script].await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vote_within_undecided_limit() {
+    vote_with_one_undecided_view(1, quorum_vote_send()).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_backpressure_beyond_undecided_limit() {
+    vote_with_one_undecided_view(
+        0,
+        exact(UndecidedBacklogFull {
+            view: ViewNumber::new(2),
+            undecided_views: 1,
+        }),
+    )
+    .await;
+}
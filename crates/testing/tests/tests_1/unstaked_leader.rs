@@ -0,0 +1,59 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use futures::StreamExt;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::traits::election::{Membership, MembershipChange};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_proposal_from_unstaked_leader_is_rejected() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let mut generator = TestViewGenerator::generate(membership.clone());
+    generator.next().await;
+    let view = generator.next().await.unwrap();
+    let proposal = &view.quorum_proposal;
+    let leader = view.leader_public_key;
+    assert_eq!(
+        membership
+            .leader(view.view_number, view.epoch_number)
+            .unwrap(),
+        leader
+    );
+
+    let upgrade_lock = &handle.hotshot.upgrade_lock;
+    proposal
+        .validate_leader_stake(&membership, view.epoch_number, &leader, upgrade_lock)
+        .await
+        .unwrap();
+
+    // The leader's stake is removed before its proposal is processed.
+    let mut unstaked = membership.clone();
+    unstaked
+        .apply_change(MembershipChange::UpdateStake {
+            key: leader,
+            stake: 0,
+        })
+        .unwrap();
+
+    let err = proposal
+        .validate_leader_stake(&unstaked, view.epoch_number, &leader, upgrade_lock)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("holds no stake"), "{err}");
+
+    // The view now has another leader, so the signature alone fails less clearly.
+    assert!(proposal
+        .validate_signature(&unstaked, view.epoch_number, upgrade_lock)
+        .await
+        .is_err());
+}
@@ -0,0 +1,187 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex},
+};
+
+use async_lock::RwLock;
+use async_trait::async_trait;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::{
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+    storage_types::TestStorage,
+};
+use hotshot_task_impls::{events::HotShotEvent, network::NetworkEventTaskState};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    boxed_sync,
+    consensus::OuterConsensus,
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    traits::{
+        election::Membership,
+        network::{BroadcastDelay, ConnectedNetwork, NetworkError, Topic},
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    BoxSyncFuture, ValidatorConfig,
+};
+use tokio::sync::mpsc::error::TrySendError;
+
+/// A network which sends nothing, and records which nodes it has been asked to look up
+#[derive(Clone, Default)]
+struct PreDialRecorder {
+    /// The nodes looked up so far, with the view they were looked up in
+    lookups: Arc<Mutex<Vec<(ViewNumber, BLSPubKey)>>>,
+}
+
+#[async_trait]
+impl ConnectedNetwork<BLSPubKey> for PreDialRecorder {
+    fn pause(&self) {}
+
+    fn resume(&self) {}
+
+    async fn wait_for_ready(&self) {}
+
+    fn shut_down<'a, 'b>(&'a self) -> BoxSyncFuture<'b, ()>
+    where
+        'a: 'b,
+        Self: 'b,
+    {
+        boxed_sync(async {})
+    }
+
+    async fn broadcast_message(
+        &self,
+        _message: Vec<u8>,
+        _topic: Topic,
+        _broadcast_delay: BroadcastDelay,
+    ) -> Result<(), NetworkError> {
+        Ok(())
+    }
+
+    async fn da_broadcast_message(
+        &self,
+        _message: Vec<u8>,
+        _recipients: Vec<BLSPubKey>,
+        _broadcast_delay: BroadcastDelay,
+    ) -> Result<(), NetworkError> {
+        Ok(())
+    }
+
+    async fn direct_message(
+        &self,
+        _message: Vec<u8>,
+        _recipient: BLSPubKey,
+    ) -> Result<(), NetworkError> {
+        Ok(())
+    }
+
+    async fn recv_message(&self) -> Result<Vec<u8>, NetworkError> {
+        futures::future::pending().await
+    }
+
+    fn queue_node_lookup(
+        &self,
+        view_number: ViewNumber,
+        pk: BLSPubKey,
+    ) -> Result<(), TrySendError<Option<(ViewNumber, BLSPubKey)>>> {
+        self.lookups.lock().unwrap().push((view_number, pk));
+        Ok(())
+    }
+}
+
+fn test_membership() -> (<TestTypes as NodeType>::Membership, BTreeSet<BLSPubKey>) {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let members = validators.iter().map(|v| v.public_key).collect();
+
+    (
+        <TestTypes as NodeType>::Membership::new(peers.clone(), peers),
+        members,
+    )
+}
+
+#[test]
+fn test_upcoming_committee_lookahead() {
+    let (membership, members) = test_membership();
+
+    assert_eq!(
+        membership.upcoming_committee(EpochNumber::new(1), 2),
+        vec![
+            (EpochNumber::new(2), members.clone()),
+            (EpochNumber::new(3), members),
+        ]
+    );
+    assert!(membership
+        .upcoming_committee(EpochNumber::new(1), 0)
+        .is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_next_committee_is_pre_dialed_before_epoch_change() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    let (membership, members) = test_membership();
+    let network = PreDialRecorder::default();
+    let mut state: NetworkEventTaskState<TestTypes, TestVersions, _, _> = NetworkEventTaskState {
+        network: Arc::new(network.clone()),
+        view: ViewNumber::new(0),
+        epoch: EpochNumber::new(1),
+        max_epoch_skip: 1,
+        membership: Arc::new(RwLock::new(membership)),
+        storage: Arc::new(RwLock::new(TestStorage::<TestTypes>::default())),
+        consensus: OuterConsensus::new(handle.hotshot.consensus()),
+        upgrade_lock: UpgradeLock::new(),
+        network_id: 0,
+        transmit_tasks: BTreeMap::new(),
+        participation: Arc::default(),
+        batch_justify_qcs: false,
+        last_justify_qc: None,
+    };
+    let (tx, _rx) = async_broadcast::broadcast(10);
+
+    // A view change within the current epoch does not dial anyone.
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(
+                ViewNumber::new(5),
+                EpochNumber::new(1),
+            )),
+            &tx,
+        )
+        .await;
+    assert!(network.lookups.lock().unwrap().is_empty());
+
+    // Entering epoch 2 dials the committee of epoch 3, a whole epoch before it starts.
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewChange(
+                ViewNumber::new(10),
+                EpochNumber::new(2),
+            )),
+            &tx,
+        )
+        .await;
+    let lookups = network.lookups.lock().unwrap().clone();
+    assert!(lookups.iter().all(|(view, _)| *view == ViewNumber::new(10)));
+    assert_eq!(
+        lookups
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect::<BTreeSet<_>>(),
+        members
+    );
+}
@@ -83,7 +83,7 @@ async fn test_upgrade_task_with_proposal() {
     let consensus = handle.hotshot.consensus();
     let mut consensus_writer = consensus.write().await;
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership.clone());
 
@@ -144,7 +144,9 @@ async fn test_upgrade_task_with_proposal() {
 
     let proposal_state =
         QuorumProposalTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
-    let upgrade_state = UpgradeTaskState::<TestTypes, TestVersions>::create_from(&handle).await;
+    let mut upgrade_state = UpgradeTaskState::<TestTypes, TestVersions>::create_from(&handle).await;
+    // The upgrade votes are cast in the epoch of the generated views.
+    upgrade_state.cur_epoch = views[2].epoch_number;
 
     let upgrade_vote_recvs: Vec<_> = upgrade_votes.into_iter().map(UpgradeVoteRecv).collect();
 
@@ -158,9 +160,7 @@ async fn test_upgrade_task_with_proposal() {
                     EpochNumber::new(1)
                 ),
                 builder_commitment.clone(),
-                TestMetadata {
-                    num_transactions: 0
-                },
+                TestMetadata::default(),
                 ViewNumber::new(1),
                 vec1![builder_fee.clone()],
                 None,
@@ -0,0 +1,67 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::test_builder::TestDescription;
+use hotshot_types::{
+    error::ConfigDiagnostic,
+    vid::{auto_vid_rate, vid_scheme_with_fault_fraction},
+};
+use jf_vid::VidScheme;
+
+#[test]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn test_auto_vid_rate_recovers_from_honest_shares() {
+    let payload: Vec<u8> = (0..=255).collect();
+
+    for committee_size in [2, 4, 5, 7, 10, 16] {
+        for fault_fraction in [0.0, 0.2, 1.0 / 3.0] {
+            let max_faulty = (committee_size as f64 * fault_fraction).floor() as usize;
+            let recovery_threshold = auto_vid_rate(committee_size, fault_fraction);
+            assert!(recovery_threshold.is_power_of_two());
+            assert!(recovery_threshold <= committee_size - max_faulty);
+
+            // Recover from the shares of the honest nodes only.
+            let mut vid = vid_scheme_with_fault_fraction(committee_size, fault_fraction);
+            let disperse = vid.disperse(payload.clone()).unwrap();
+            let honest_shares = &disperse.shares[max_faulty..];
+            assert_eq!(
+                vid.recover_payload(honest_shares, &disperse.common)
+                    .unwrap(),
+                payload,
+                "committee of {committee_size} with fault fraction {fault_fraction}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_auto_vid_rate_gives_small_committees_more_redundancy() {
+    let fault_fraction = 1.0 / 3.0;
+
+    assert_eq!(auto_vid_rate(4, fault_fraction), 2);
+    assert_eq!(auto_vid_rate(100, fault_fraction), 64);
+    // Assuming no faults keeps the largest power of two within the committee.
+    assert_eq!(auto_vid_rate(10, 0.0), 8);
+}
+
+#[test]
+fn test_vid_fault_fraction_is_validated() {
+    let mut config = TestDescription::<TestTypes, MemoryImpl, TestVersions>::default()
+        .gen_launcher(0)
+        .resource_generator
+        .config;
+
+    config.vid_fault_fraction = 1.0;
+    assert_eq!(
+        config.validate(),
+        Err(vec![ConfigDiagnostic::InvalidVidFaultFraction])
+    );
+}
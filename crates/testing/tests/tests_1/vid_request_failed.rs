@@ -0,0 +1,82 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::BTreeSet, sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task::task::TaskState;
+use hotshot_task_impls::{events::HotShotEvent, request::NetworkRequestState};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::traits::election::Membership;
+use tokio::time::timeout;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vid_request_fails_once_every_da_member_was_asked() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let mut generator = TestViewGenerator::generate(membership.clone());
+    let view = (&mut generator)
+        .take(2)
+        .collect::<Vec<_>>()
+        .await
+        .pop()
+        .unwrap();
+    let view_number = view.view_number;
+
+    let mut state = <NetworkRequestState<TestTypes, MemoryImpl> as CreateTaskState<
+        TestTypes,
+        MemoryImpl,
+        TestVersions,
+    >>::create_from(&handle)
+    .await;
+    state.delay = Duration::ZERO;
+    let (tx, mut rx) = async_broadcast::broadcast(1024);
+
+    // We never got our share for the view, so the request task starts asking for it
+    state
+        .handle_event(
+            Arc::new(HotShotEvent::QuorumProposalValidated(
+                view.quorum_proposal.clone(),
+                view.leaf.clone(),
+            )),
+            &tx,
+            &rx.clone(),
+        )
+        .await
+        .unwrap();
+
+    // Nobody answers, so every other DA member is asked exactly once before the task gives up
+    let mut asked = Vec::new();
+    loop {
+        let event = timeout(Duration::from_secs(30), rx.recv())
+            .await
+            .expect("timed out waiting for the VID request to fail")
+            .unwrap();
+        match event.as_ref() {
+            HotShotEvent::VidRequestSend(request, _, recipient) => {
+                assert_eq!(request.view, view_number);
+                asked.push(*recipient);
+            }
+            HotShotEvent::VidRequestFailed(failed_view) => {
+                assert_eq!(*failed_view, view_number);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let mut da_members = membership.da_committee_members(view_number, view.epoch_number);
+    da_members.remove(&handle.public_key());
+    assert_eq!(asked.len(), da_members.len());
+    assert_eq!(asked.into_iter().collect::<BTreeSet<_>>(), da_members);
+}
@@ -0,0 +1,104 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use futures::StreamExt;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::{helpers::build_system_handle, view_generator::TestViewGenerator};
+use hotshot_types::{data::ViewNumber, traits::node_implementation::ConsensusTime};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vid_share_cache_reads_through_and_prunes() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let consensus = handle.hotshot.consensus();
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let mut generator = TestViewGenerator::generate(membership);
+    let mut shares = Vec::new();
+    for view in (&mut generator).take(3).collect::<Vec<_>>().await {
+        let share = view.vid_proposal.0[0].clone();
+        consensus
+            .write()
+            .await
+            .update_vid_shares(view.view_number, share.clone());
+        shares.push(share);
+    }
+
+    let cache = consensus.read().await.vid_share_cache();
+    for share in &shares {
+        let view = share.data.view_number;
+        let key = &share.data.recipient_key;
+        let direct = consensus
+            .read()
+            .await
+            .vid_shares()
+            .get(&view)
+            .and_then(|shares| shares.get(key))
+            .cloned();
+
+        // The first read goes through to consensus, the second is served from the cache.
+        assert_eq!(cache.get(&consensus, view, key).await, direct);
+        assert_eq!(cache.get(&consensus, view, key).await, direct);
+    }
+
+    // Garbage collect everything before view 2, which should also evict it from the cache.
+    consensus
+        .write()
+        .await
+        .collect_garbage(ViewNumber::genesis(), ViewNumber::new(3));
+
+    let pruned = &shares[0].data;
+    assert!(cache
+        .get(&consensus, pruned.view_number, &pruned.recipient_key)
+        .await
+        .is_none());
+
+    let retained = &shares[2].data;
+    assert_eq!(
+        cache
+            .get(&consensus, retained.view_number, &retained.recipient_key)
+            .await,
+        Some(shares[2].clone())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vid_share_cache_serves_replaced_shares() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let consensus = handle.hotshot.consensus();
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let views: Vec<_> = TestViewGenerator::generate(membership)
+        .take(2)
+        .collect()
+        .await;
+    let share = views[0].vid_proposal.0[0].clone();
+    let view = share.data.view_number;
+    let key = share.data.recipient_key;
+    consensus
+        .write()
+        .await
+        .update_vid_shares(view, share.clone());
+
+    let cache = consensus.read().await.vid_share_cache();
+    assert_eq!(cache.get(&consensus, view, &key).await, Some(share.clone()));
+
+    // Once consensus holds a different share for the view, the cached one is not served.
+    let mut replacement = share;
+    replacement.signature = views[1].vid_proposal.0[0].signature.clone();
+    consensus
+        .write()
+        .await
+        .update_vid_shares(view, replacement.clone());
+    assert_eq!(cache.get(&consensus, view, &key).await, Some(replacement));
+}
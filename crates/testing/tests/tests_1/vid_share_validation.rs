@@ -0,0 +1,103 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::sync::Arc;
+
+use futures::StreamExt;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::{
+    block_types::TestTransaction,
+    node_types::{MemoryImpl, TestTypes, TestVersions},
+};
+use hotshot_task_impls::{events::HotShotEvent, quorum_vote::QuorumVoteTaskState};
+use hotshot_testing::{
+    helpers::{build_system_handle, build_vid_proposal, key_pair_for_id, vid_share},
+    view_generator::TestViewGenerator,
+};
+use hotshot_types::traits::election::Membership;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vid_share_must_match_view_payload_commitment() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let membership = handle.hotshot.memberships.read().await.clone();
+
+    let mut generator = TestViewGenerator::generate(membership.clone());
+    let view = (&mut generator)
+        .take(2)
+        .collect::<Vec<_>>()
+        .await
+        .pop()
+        .unwrap();
+    let view_number = view.view_number;
+    let epoch = view.epoch_number;
+
+    let mut state =
+        QuorumVoteTaskState::<TestTypes, MemoryImpl, TestVersions>::create_from(&handle).await;
+    let (tx, mut rx) = async_broadcast::broadcast(1024);
+    rx.set_overflow(true);
+    let receiver = rx.clone();
+
+    // The DA certificate fixes the payload commitment for the view.
+    state
+        .handle(
+            Arc::new(HotShotEvent::DaCertificateRecv(view.da_certificate.clone())),
+            receiver.clone(),
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+
+    // A share for a different payload is internally consistent and correctly signed by the leader,
+    // but does not match the certified payload, so it is rejected.
+    let leader = view.leader_public_key;
+    let (leader_private_key, _) = (0..membership.total_nodes(epoch) as u64)
+        .map(key_pair_for_id::<TestTypes>)
+        .find(|(_, public_key)| *public_key == leader)
+        .unwrap();
+    let (_, other_shares) = build_vid_proposal(
+        &membership,
+        view_number,
+        epoch,
+        vec![TestTransaction::new(vec![1, 2, 3])],
+        &leader_private_key,
+    );
+    let mismatched = vid_share(&other_shares, handle.public_key());
+    assert_ne!(
+        mismatched.data.payload_commitment,
+        view.da_certificate.data.payload_commit
+    );
+    assert!(state
+        .handle(
+            Arc::new(HotShotEvent::VidShareRecv(leader, mismatched)),
+            receiver.clone(),
+            tx.clone(),
+        )
+        .await
+        .is_err());
+
+    // The share for the certified payload is accepted.
+    let valid = vid_share(&view.vid_proposal.0, handle.public_key());
+    state
+        .handle(
+            Arc::new(HotShotEvent::VidShareRecv(leader, valid.clone())),
+            receiver,
+            tx.clone(),
+        )
+        .await
+        .unwrap();
+
+    let mut validated = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        if let HotShotEvent::VidShareValidated(share) = event.as_ref() {
+            validated.push(share.clone());
+        }
+    }
+    assert_eq!(validated, vec![valid]);
+}
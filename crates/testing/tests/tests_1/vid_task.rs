@@ -45,7 +45,7 @@ async fn test_vid_task() {
         .0;
     let pub_key = handle.public_key();
 
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut vid = vid_scheme_from_view_number::<TestTypes>(
         &membership,
@@ -75,14 +75,20 @@ async fn test_vid_task() {
     .expect("Failed to sign block payload!");
     let proposal: DaProposal<TestTypes> = DaProposal {
         encoded_transactions: encoded_transactions.clone(),
-        metadata: TestMetadata {
-            num_transactions: encoded_transactions.len() as u64,
-        },
+        metadata: TestMetadata::new(&transactions),
         view_number: ViewNumber::new(2),
     };
+    let leader_proof = membership
+        .leader_proof(
+            ViewNumber::new(2),
+            EpochNumber::new(1),
+            handle.private_key(),
+        )
+        .unwrap();
     let message = Proposal {
         data: proposal.clone(),
         signature,
+        leader_proof: leader_proof.clone(),
         _pd: PhantomData,
     };
 
@@ -96,6 +102,7 @@ async fn test_vid_task() {
     let vid_proposal = Proposal {
         data: vid_disperse.clone(),
         signature: message.signature.clone(),
+        leader_proof,
         _pd: PhantomData,
     };
     let inputs = vec![
@@ -104,9 +111,7 @@ async fn test_vid_task() {
             ViewChange(ViewNumber::new(2), EpochNumber::new(1)),
             BlockRecv(PackedBundle::new(
                 encoded_transactions.clone(),
-                TestMetadata {
-                    num_transactions: transactions.len() as u64
-                },
+                TestMetadata::new(&transactions),
                 ViewNumber::new(2),
                 vec1::vec1![null_block::builder_fee::<TestTypes, TestVersions>(
                     membership.total_nodes(EpochNumber::new(0)),
@@ -126,9 +131,7 @@ async fn test_vid_task() {
             exact(SendPayloadCommitmentAndMetadata(
                 payload_commitment,
                 builder_commitment,
-                TestMetadata {
-                    num_transactions: transactions.len() as u64,
-                },
+                TestMetadata::new(&transactions),
                 ViewNumber::new(2),
                 vec1![null_block::builder_fee::<TestTypes, TestVersions>(
                     membership.total_nodes(EpochNumber::new(0)),
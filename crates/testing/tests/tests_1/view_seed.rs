@@ -0,0 +1,46 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+
+#[test]
+fn test_view_seed_is_agreed_by_identical_committees() {
+    let peers: Vec<_> = (0..5)
+        .map(|node_id| {
+            ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], node_id, 1, true)
+                .public_config()
+        })
+        .collect();
+    let mut reversed_peers = peers.clone();
+    reversed_peers.reverse();
+
+    // Two nodes which learned the committee in a different order.
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers.clone());
+    let other_membership =
+        <TestTypes as NodeType>::Membership::new(reversed_peers.clone(), reversed_peers);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(7);
+
+    let seed = membership.view_seed(view, epoch);
+    assert_eq!(seed, other_membership.view_seed(view, epoch));
+    assert_eq!(seed, membership.view_seed(view, epoch));
+    assert_ne!(seed, membership.view_seed(view + 1, epoch));
+    assert_ne!(seed, membership.view_seed(view, EpochNumber::new(1)));
+
+    // A different committee derives a different seed.
+    let smaller_membership =
+        <TestTypes as NodeType>::Membership::new(peers[1..].to_vec(), peers[1..].to_vec());
+    assert_ne!(seed, smaller_membership.view_seed(view, epoch));
+}
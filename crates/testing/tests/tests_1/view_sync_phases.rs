@@ -0,0 +1,166 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{marker::PhantomData, sync::Arc};
+
+use committable::Committable;
+use hotshot::tasks::task_state::CreateTaskState;
+use hotshot_example_types::node_types::{MemoryImpl, TestTypes, TestVersions};
+use hotshot_task_impls::{events::HotShotEvent, view_sync::ViewSyncTaskState};
+use hotshot_testing::helpers::{build_system_handle, key_pair_for_id};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    simple_certificate::{
+        SimpleCertificate, ViewSyncCommitCertificate2, ViewSyncPreCommitCertificate2,
+    },
+    simple_vote::{
+        ViewSyncCommitData, ViewSyncCommitVote, ViewSyncPreCommitData, ViewSyncPreCommitVote,
+    },
+    traits::{election::Membership, node_implementation::ConsensusTime},
+    vote::Certificate,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::too_many_lines)]
+async fn test_view_sync_phases_accumulate_independently() {
+    hotshot::helpers::initialize_logging();
+
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(2)
+        .await
+        .0;
+    let upgrade_lock = handle.hotshot.upgrade_lock.clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
+    let epoch = EpochNumber::new(0);
+    let round = ViewNumber::new(4);
+    let num_nodes = membership.total_nodes(epoch) as u64;
+
+    // Pick a relay which this node leads, so it will accumulate the votes.
+    let relay = (0..num_nodes)
+        .find(|relay| membership.leader(round + *relay, epoch).unwrap() == handle.public_key())
+        .expect("this node should lead some relay");
+
+    let pre_commit_threshold =
+        ViewSyncPreCommitCertificate2::<TestTypes>::threshold(&membership, epoch);
+    let commit_threshold = ViewSyncCommitCertificate2::<TestTypes>::threshold(&membership, epoch);
+
+    let mut state = ViewSyncTaskState::<TestTypes, TestVersions>::create_from(&handle).await;
+    let (tx, mut rx) = async_broadcast::broadcast(1024);
+    rx.set_overflow(true);
+
+    let pre_commit_data = ViewSyncPreCommitData { relay, round };
+    let commit_data = ViewSyncCommitData { relay, round };
+
+    // One short of a pre-commit certificate, interleaved with enough commit votes for a commit
+    // certificate.
+    for node_id in 0..commit_threshold {
+        let (private_key, public_key) = key_pair_for_id::<TestTypes>(node_id);
+
+        if node_id + 1 < pre_commit_threshold {
+            let vote = ViewSyncPreCommitVote::<TestTypes>::create_signed_vote(
+                pre_commit_data.clone(),
+                round,
+                epoch,
+                &public_key,
+                &private_key,
+                &upgrade_lock,
+            )
+            .await
+            .unwrap();
+            let _ = state
+                .handle(
+                    Arc::new(HotShotEvent::ViewSyncPreCommitVoteRecv(vote)),
+                    tx.clone(),
+                )
+                .await;
+        }
+
+        let vote = ViewSyncCommitVote::<TestTypes>::create_signed_vote(
+            commit_data.clone(),
+            round,
+            epoch,
+            &public_key,
+            &private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        let _ = state
+            .handle(
+                Arc::new(HotShotEvent::ViewSyncCommitVoteRecv(vote)),
+                tx.clone(),
+            )
+            .await;
+    }
+
+    let mut commit_certificate = None;
+    while let Ok(event) = rx.try_recv() {
+        match event.as_ref() {
+            HotShotEvent::ViewSyncCommitCertificate2Send(certificate, _) => {
+                commit_certificate = Some(certificate.clone());
+            }
+            HotShotEvent::ViewSyncPreCommitCertificate2Send(..) => {
+                panic!("Commit votes were counted towards a pre-commit certificate");
+            }
+            _ => {}
+        }
+    }
+    let commit_certificate = commit_certificate.expect("no commit certificate was formed");
+
+    // The last pre-commit vote completes the pre-commit certificate.
+    let (private_key, public_key) = key_pair_for_id::<TestTypes>(pre_commit_threshold - 1);
+    let vote = ViewSyncPreCommitVote::<TestTypes>::create_signed_vote(
+        pre_commit_data.clone(),
+        round,
+        epoch,
+        &public_key,
+        &private_key,
+        &upgrade_lock,
+    )
+    .await
+    .unwrap();
+    let _ = state
+        .handle(
+            Arc::new(HotShotEvent::ViewSyncPreCommitVoteRecv(vote)),
+            tx.clone(),
+        )
+        .await;
+
+    let mut pre_commit_certificate = None;
+    while let Ok(event) = rx.try_recv() {
+        if let HotShotEvent::ViewSyncPreCommitCertificate2Send(certificate, _) = event.as_ref() {
+            pre_commit_certificate = Some(certificate.clone());
+        }
+    }
+    let pre_commit_certificate =
+        pre_commit_certificate.expect("no pre-commit certificate was formed");
+
+    // Each certificate validates on its own.
+    assert!(
+        pre_commit_certificate
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+    assert!(
+        commit_certificate
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+
+    // Commit signatures can't be passed off as a pre-commit certificate.
+    let forged: ViewSyncPreCommitCertificate2<TestTypes> = SimpleCertificate::new(
+        pre_commit_data.clone(),
+        pre_commit_data.commit(),
+        round,
+        epoch,
+        commit_certificate.signatures.clone(),
+        PhantomData,
+    );
+    assert!(
+        !forged
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+}
@@ -0,0 +1,111 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{sync::Arc, time::Duration};
+
+use async_lock::RwLock;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_task_impls::{events::HotShotEvent, view_sync::ViewSyncReplicaTaskState};
+use hotshot_testing::helpers::build_cert;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::ViewSyncPreCommitCertificate2,
+    simple_vote::{ViewSyncPreCommitData, ViewSyncPreCommitVote},
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+
+/// Feed a pre-commit certificate for `relay` in round 5 to a fresh replica, returning whether
+/// the replica voted to commit it
+async fn replica_accepts_relay(relay: u64) -> bool {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    membership.set_skip_failed_leader_relays(true);
+    let round = ViewNumber::new(5);
+    let epoch = EpochNumber::new(0);
+
+    let mut state = ViewSyncReplicaTaskState::<TestTypes, TestVersions> {
+        view_sync_timeout: Duration::from_secs(10),
+        cur_view: round,
+        next_view: round,
+        cur_epoch: epoch,
+        relay: 0,
+        finalized: false,
+        sent_view_change_event: false,
+        timeout_task: None,
+        id: 0,
+        membership: Arc::new(RwLock::new(membership.clone())),
+        public_key: validators[0].public_key,
+        private_key: validators[0].private_key.clone(),
+        upgrade_lock: UpgradeLock::new(),
+    };
+
+    let certificate = build_cert::<
+        TestTypes,
+        TestVersions,
+        ViewSyncPreCommitData<TestTypes>,
+        ViewSyncPreCommitVote<TestTypes>,
+        ViewSyncPreCommitCertificate2<TestTypes>,
+    >(
+        ViewSyncPreCommitData { relay, round },
+        &membership,
+        round,
+        epoch,
+        &validators[0].public_key,
+        &validators[0].private_key,
+        &state.upgrade_lock,
+    )
+    .await;
+
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewSyncPreCommitCertificate2Recv(certificate)),
+            tx,
+        )
+        .await;
+    if let Some(timeout_task) = state.timeout_task.take() {
+        timeout_task.abort();
+    }
+
+    match rx.try_recv() {
+        Ok(event) => {
+            let HotShotEvent::ViewSyncCommitVoteSend(vote) = event.as_ref() else {
+                panic!("Expected a commit vote, got {event:?}");
+            };
+            assert_eq!(vote.data.relay, relay);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_view_sync_cert_relay_is_checked() {
+    hotshot::helpers::initialize_logging();
+
+    // Relays within the committee are accepted
+    assert!(replica_accepts_relay(0).await);
+    assert!(replica_accepts_relay(2).await);
+
+    // Relay 3 is led by the leader of the failed view 4, so no honest replica voted for it
+    assert!(!replica_accepts_relay(3).await);
+
+    // Relays beyond one rotation through the committee, or overflowing the view, are rejected
+    assert!(!replica_accepts_relay(4).await);
+    assert!(!replica_accepts_relay(u64::MAX).await);
+}
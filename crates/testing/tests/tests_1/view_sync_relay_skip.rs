@@ -0,0 +1,101 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{sync::Arc, time::Duration};
+
+use async_lock::RwLock;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestConsecutiveLeaderTypes, TestVersions};
+use hotshot_task_impls::{events::HotShotEvent, view_sync::ViewSyncReplicaTaskState};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    traits::{
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+
+/// A replica for round `round`, in a committee where each leader leads two consecutive views and
+/// which skips relays led by the failed leader if `skip` is set
+fn replica(
+    round: u64,
+    skip: bool,
+) -> ViewSyncReplicaTaskState<TestConsecutiveLeaderTypes, TestVersions> {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let mut membership =
+        <TestConsecutiveLeaderTypes as NodeType>::Membership::new(peers.clone(), peers);
+    membership.set_skip_failed_leader_relays(skip);
+
+    ViewSyncReplicaTaskState {
+        view_sync_timeout: Duration::from_secs(10),
+        cur_view: ViewNumber::new(round),
+        next_view: ViewNumber::new(round),
+        cur_epoch: EpochNumber::new(0),
+        relay: 0,
+        finalized: false,
+        sent_view_change_event: false,
+        timeout_task: None,
+        id: 0,
+        membership: Arc::new(RwLock::new(membership)),
+        public_key: validators[0].public_key,
+        private_key: validators[0].private_key.clone(),
+        upgrade_lock: UpgradeLock::new(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_view_sync_relay_skips_failed_leader() {
+    hotshot::helpers::initialize_logging();
+
+    // View 4 failed, and its leader also leads round 5, which is the natural relay.
+    let mut state = replica(5, true);
+    let epoch = state.cur_epoch;
+    let membership_reader = state.membership.read().await;
+    let failed_leader = membership_reader.leader(ViewNumber::new(4), epoch).unwrap();
+    assert_eq!(
+        membership_reader.leader(ViewNumber::new(5), epoch).unwrap(),
+        failed_leader
+    );
+    assert_ne!(
+        membership_reader.leader(ViewNumber::new(6), epoch).unwrap(),
+        failed_leader
+    );
+    drop(membership_reader);
+
+    // The relay offset advances to the next node.
+    assert_eq!(state.relay_avoiding_failed_leader(0).await, 1);
+
+    let (tx, mut rx) = async_broadcast::broadcast(16);
+    state
+        .handle(
+            Arc::new(HotShotEvent::ViewSyncTrigger(ViewNumber::new(5))),
+            tx,
+        )
+        .await;
+    let HotShotEvent::ViewSyncPreCommitVoteSend(vote) = rx.recv().await.unwrap().as_ref().clone()
+    else {
+        panic!("Expected a pre-commit vote");
+    };
+    assert_eq!(vote.data.relay, 1);
+    assert_eq!(state.relay, 1);
+    if let Some(timeout_task) = state.timeout_task.take() {
+        timeout_task.abort();
+    }
+
+    // Without skipping, the natural relay is kept.
+    assert_eq!(replica(5, false).relay_avoiding_failed_leader(0).await, 0);
+
+    // A natural relay led by another node is kept as well.
+    assert_eq!(replica(4, true).relay_avoiding_failed_leader(0).await, 0);
+}
@@ -33,6 +33,7 @@ async fn test_view_sync_task() {
     let vote = hotshot_types::simple_vote::ViewSyncPreCommitVote::<TestTypes>::create_signed_vote(
         vote_data,
         <TestTypes as hotshot_types::traits::node_implementation::NodeType>::View::new(4),
+        EpochNumber::new(0),
         hotshot_types::traits::consensus_api::ConsensusApi::public_key(&handle),
         hotshot_types::traits::consensus_api::ConsensusApi::private_key(&handle),
         &handle.hotshot.upgrade_lock,
@@ -36,7 +36,7 @@ async fn test_vote_dependency_handle() {
     let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(node_id)
         .await
         .0;
-    let membership = (*handle.hotshot.memberships).clone();
+    let membership = handle.hotshot.memberships.read().await.clone();
 
     let mut generator = TestViewGenerator::generate(membership.clone());
 
@@ -90,7 +90,7 @@ async fn test_vote_dependency_handle() {
                 consensus: OuterConsensus::new(consensus.clone()),
                 consensus_metrics: Arc::clone(&consensus.read().await.metrics),
                 instance_state: handle.hotshot.instance_state(),
-                quorum_membership: (*handle.hotshot.memberships).clone().into(),
+                quorum_membership: handle.hotshot.memberships.read().await.clone().into(),
                 storage: Arc::clone(&handle.storage()),
                 view_number,
                 sender: event_sender.clone(),
@@ -98,6 +98,9 @@ async fn test_vote_dependency_handle() {
                 upgrade_lock: handle.hotshot.upgrade_lock.clone(),
                 id: handle.hotshot.id,
                 epoch_height: handle.hotshot.config.epoch_height,
+                checkpoint_policy: handle.hotshot.config.checkpoint_policy,
+                may_vote: true,
+                max_undecided_views: handle.hotshot.config.max_undecided_views,
             };
 
         vote_dependency_handle_state
@@ -0,0 +1,111 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use committable::Commitment;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{EpochsTestVersions, TestTypes};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{is_valid_vote, Certificate, VoteAccumulator},
+    ValidatorConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vote_signed_for_one_epoch_is_rejected_in_the_next() {
+    let validator =
+        ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], 0, 1, true);
+    let peers = vec![validator.public_config()];
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let upgrade_lock = UpgradeLock::<TestTypes, EpochsTestVersions>::new();
+    let view = ViewNumber::new(1);
+    let epoch = EpochNumber::new(3);
+    let next_epoch = epoch + 1;
+
+    let vote = DaVote::<TestTypes>::create_signed_vote(
+        DaData {
+            payload_commit: vid_commitment(&[1, 2, 3], 1),
+        },
+        view,
+        epoch,
+        &validator.public_key,
+        &validator.private_key,
+        &upgrade_lock,
+    )
+    .await
+    .unwrap();
+
+    assert!(is_valid_vote(&vote, epoch, &upgrade_lock).await);
+    assert!(!is_valid_vote(&vote, next_epoch, &upgrade_lock).await);
+
+    // The accumulator for the next epoch drops the vote rather than counting it.
+    let mut accumulator = VoteAccumulator::<
+        TestTypes,
+        DaVote<TestTypes>,
+        DaCertificate<TestTypes>,
+        EpochsTestVersions,
+    > {
+        vote_outcomes: HashMap::new(),
+        signers: HashMap::new(),
+        phantom: PhantomData,
+        upgrade_lock: upgrade_lock.clone(),
+        metrics: Arc::default(),
+        on_certificate: None,
+    };
+    assert!(accumulator
+        .accumulate(&vote, view, &membership, next_epoch)
+        .await
+        .is_left());
+
+    // Within its own epoch the vote forms a certificate, which records that epoch.
+    let certificate = accumulator
+        .accumulate(&vote, view, &membership, epoch)
+        .await
+        .right()
+        .unwrap();
+    assert_eq!(certificate.epoch, epoch);
+
+    // The certificate is only valid in that epoch, even against the same stake table
+    assert!(
+        certificate
+            .is_valid_cert(&membership, epoch, &upgrade_lock)
+            .await
+    );
+    assert!(
+        !certificate
+            .is_valid_cert(&membership, next_epoch, &upgrade_lock)
+            .await
+    );
+}
+
+#[test]
+fn test_certificate_stored_without_an_epoch_is_in_genesis() {
+    let certificate = DaCertificate::<TestTypes>::new(
+        DaData {
+            payload_commit: vid_commitment(&[1, 2, 3], 1),
+        },
+        Commitment::from_raw([1; 32]),
+        ViewNumber::new(1),
+        EpochNumber::new(3),
+        None,
+        PhantomData,
+    );
+    let mut stored = serde_json::to_value(&certificate).unwrap();
+    stored.as_object_mut().unwrap().remove("epoch");
+
+    let restored: DaCertificate<TestTypes> = serde_json::from_value(stored).unwrap();
+    assert_eq!(restored.epoch, EpochNumber::genesis());
+    assert_eq!(restored.data, certificate.data);
+}
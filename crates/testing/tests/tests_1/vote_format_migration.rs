@@ -0,0 +1,119 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::marker::PhantomData;
+
+use committable::Committable;
+use hotshot::types::{BLSPubKey, SignatureKey};
+use hotshot_example_types::node_types::{EpochUpgradeTestVersions, TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::UpgradeCertificate,
+    simple_vote::{DaData, DaVote, UpgradeProposalData},
+    traits::{
+        block_contents::vid_commitment,
+        node_implementation::{ConsensusTime, Versions},
+    },
+    vote::is_valid_vote,
+};
+use vbs::version::StaticVersionType;
+
+/// The first view of the new version
+const UPGRADE_VIEW: u64 = 5;
+
+/// A lock which has decided an upgrade taking effect at `UPGRADE_VIEW`
+fn upgraded_lock<V: Versions>(transition_views: u64) -> UpgradeLock<TestTypes, V> {
+    let data = UpgradeProposalData {
+        old_version: V::Base::VERSION,
+        new_version: V::Upgrade::VERSION,
+        decide_by: ViewNumber::new(UPGRADE_VIEW - 2),
+        new_version_hash: vec![0; 12],
+        old_version_last_view: ViewNumber::new(UPGRADE_VIEW - 1),
+        new_version_first_view: ViewNumber::new(UPGRADE_VIEW),
+    };
+    let certificate = UpgradeCertificate::new(
+        data.clone(),
+        data.commit(),
+        ViewNumber::new(UPGRADE_VIEW - 2),
+        EpochNumber::new(0),
+        None,
+        PhantomData,
+    );
+
+    UpgradeLock::from_certificate(&Some(certificate)).with_vote_transition_views(transition_views)
+}
+
+/// A DA vote for `view`, in the format `upgrade_lock` applies to the view
+async fn vote<V: Versions>(
+    view: u64,
+    upgrade_lock: &UpgradeLock<TestTypes, V>,
+) -> DaVote<TestTypes> {
+    let (public_key, private_key) = BLSPubKey::generated_from_seed_indexed([0u8; 32], 0);
+    DaVote::create_signed_vote(
+        DaData {
+            payload_commit: vid_commitment(&[], 10),
+        },
+        ViewNumber::new(view),
+        EpochNumber::new(0),
+        &public_key,
+        &private_key,
+        upgrade_lock,
+    )
+    .await
+    .unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_votes_across_upgrade_validate_in_their_format() {
+    hotshot::helpers::initialize_logging();
+
+    let epoch = EpochNumber::new(0);
+    let upgraded = upgraded_lock::<EpochUpgradeTestVersions>(2);
+    // A node which has not seen the upgrade decided yet signs every view in the old format
+    let not_upgraded = UpgradeLock::<TestTypes, EpochUpgradeTestVersions>::new();
+
+    let before = vote(UPGRADE_VIEW - 1, &upgraded).await;
+    let after = vote(UPGRADE_VIEW + 1, &upgraded).await;
+    assert!(is_valid_vote(&before, epoch, &upgraded).await);
+    assert!(is_valid_vote(&after, epoch, &upgraded).await);
+
+    // The formats differ, so the same vote signed in the old format carries another signature
+    let after_in_old_format = vote(UPGRADE_VIEW + 1, &not_upgraded).await;
+    assert_ne!(after_in_old_format, after);
+    assert!(is_valid_vote(&after_in_old_format, epoch, &not_upgraded).await);
+    assert!(!is_valid_vote(&after, epoch, &not_upgraded).await);
+
+    // Within the transition window the old format is still accepted, but not beyond it
+    assert!(is_valid_vote(&after_in_old_format, epoch, &upgraded).await);
+    let late_in_old_format = vote(UPGRADE_VIEW + 2, &not_upgraded).await;
+    assert!(!is_valid_vote(&late_in_old_format, epoch, &upgraded).await);
+    assert!(
+        !is_valid_vote(
+            &after_in_old_format,
+            epoch,
+            &upgraded_lock::<EpochUpgradeTestVersions>(0)
+        )
+        .await
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_votes_before_epochs_keep_one_format_across_an_upgrade() {
+    hotshot::helpers::initialize_logging();
+
+    let epoch = EpochNumber::new(0);
+    let upgraded = upgraded_lock::<TestVersions>(0);
+    let not_upgraded = UpgradeLock::<TestTypes, TestVersions>::new();
+
+    // Before the epochs version the version is not signed, so both sides of the upgrade sign the
+    // same commitment and votes validate on either side, whatever the transition window
+    let after = vote(UPGRADE_VIEW + 2, &upgraded).await;
+    let after_in_old_format = vote(UPGRADE_VIEW + 2, &not_upgraded).await;
+    assert_eq!(after_in_old_format, after);
+    assert!(is_valid_vote(&after_in_old_format, epoch, &upgraded).await);
+    assert!(is_valid_vote(&after, epoch, &not_upgraded).await);
+}
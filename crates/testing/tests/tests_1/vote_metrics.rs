@@ -0,0 +1,113 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{EpochsTestVersions, MemoryImpl, TestTypes, TestVersions};
+use hotshot_testing::helpers::build_system_handle;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::VoteAccumulator,
+    vote_metrics::{VoteMetrics, VoteRejection},
+    ValidatorConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_metrics_snapshot_after_vote_round() {
+    hotshot::helpers::initialize_logging();
+
+    // A fresh node has not handled any votes.
+    let handle = build_system_handle::<TestTypes, MemoryImpl, TestVersions>(1)
+        .await
+        .0;
+    assert_eq!(handle.export_metrics(), VoteMetrics::default().export());
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let upgrade_lock = UpgradeLock::<TestTypes, EpochsTestVersions>::new();
+    let view = ViewNumber::new(1);
+    let epoch = EpochNumber::new(1);
+    let data = DaData {
+        payload_commit: vid_commitment(&[1, 2, 3], 4),
+    };
+    let vote = |id: usize, view: ViewNumber, epoch: EpochNumber| {
+        DaVote::<TestTypes>::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validators[id].public_key,
+            &validators[id].private_key,
+            &upgrade_lock,
+        )
+    };
+
+    let metrics = Arc::new(VoteMetrics::default());
+    let mut accumulator = VoteAccumulator::<
+        TestTypes,
+        DaVote<TestTypes>,
+        DaCertificate<TestTypes>,
+        EpochsTestVersions,
+    > {
+        vote_outcomes: HashMap::new(),
+        signers: HashMap::new(),
+        phantom: PhantomData,
+        upgrade_lock: upgrade_lock.clone(),
+        metrics: Arc::clone(&metrics),
+        on_certificate: None,
+    };
+
+    let round = [
+        vote(0, view, epoch).await.unwrap(),
+        // A second copy of the same vote
+        vote(0, view, epoch).await.unwrap(),
+        vote(1, view + 1, epoch).await.unwrap(),
+        // Signed in another epoch, so its signature does not verify in this one
+        vote(3, view, epoch + 1).await.unwrap(),
+        vote(1, view, epoch).await.unwrap(),
+        vote(2, view, epoch).await.unwrap(),
+    ];
+    let mut certificates = 0;
+    for vote in &round {
+        if accumulator
+            .accumulate(vote, view, &membership, epoch)
+            .await
+            .is_right()
+        {
+            certificates += 1;
+        }
+    }
+    assert_eq!(certificates, 1);
+
+    let snapshot = metrics.export();
+    assert_eq!(snapshot.votes_verified, 4);
+    assert_eq!(snapshot.certificates_formed, 1);
+    assert_eq!(snapshot.verification_time_buckets.last(), Some(&5));
+    assert_eq!(snapshot.rejected(VoteRejection::Duplicate), 1);
+    assert_eq!(snapshot.rejected(VoteRejection::WrongView), 1);
+    assert_eq!(snapshot.rejected(VoteRejection::InvalidSignature), 1);
+    assert_eq!(snapshot.rejected(VoteRejection::NotInStakeTable), 0);
+
+    let exposition = snapshot.to_prometheus();
+    assert!(exposition.contains("hotshot_votes_verified_total 4\n"));
+    assert!(exposition.contains("hotshot_certificates_formed_total 1\n"));
+    assert!(exposition.contains("hotshot_vote_verification_seconds_count 5\n"));
+    assert!(exposition.contains("hotshot_votes_rejected_total{reason=\"duplicate\"} 1\n"));
+}
@@ -0,0 +1,96 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use committable::{Commitment, Committable};
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{EpochsTestVersions, TestTypes};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_vote::{
+        DaData, DaVote, QuorumData2, QuorumVote2, VersionedVoteData, VoteRole, Voteable,
+    },
+    traits::{block_contents::vid_commitment, node_implementation::ConsensusTime},
+    vote::is_valid_vote,
+    ValidatorConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vote_signatures_are_bound_to_their_role() {
+    let validator =
+        ValidatorConfig::<BLSPubKey>::generated_from_seed_indexed([0u8; 32], 0, 1, true);
+    let upgrade_lock = UpgradeLock::<TestTypes, EpochsTestVersions>::new();
+    let view = ViewNumber::new(1);
+    let epoch = EpochNumber::new(1);
+
+    assert_eq!(<DaData as Voteable>::ROLE, VoteRole::Da);
+    assert_eq!(<QuorumData2<TestTypes> as Voteable>::ROLE, VoteRole::Quorum);
+
+    let payload_commit = vid_commitment(&[1, 2, 3], 4);
+    let da_vote = DaVote::<TestTypes>::create_signed_vote(
+        DaData { payload_commit },
+        view,
+        epoch,
+        &validator.public_key,
+        &validator.private_key,
+        &upgrade_lock,
+    )
+    .await
+    .unwrap();
+    let quorum_vote = QuorumVote2::<TestTypes>::create_signed_vote(
+        QuorumData2 {
+            leaf_commit: Commitment::from_raw([7; 32]),
+        },
+        view,
+        epoch,
+        &validator.public_key,
+        &validator.private_key,
+        &upgrade_lock,
+    )
+    .await
+    .unwrap();
+    assert!(is_valid_vote(&da_vote, epoch, &upgrade_lock).await);
+    assert!(is_valid_vote(&quorum_vote, epoch, &upgrade_lock).await);
+
+    // A DA signature replayed on a quorum vote does not validate.
+    let mut replayed_quorum_vote = quorum_vote.clone();
+    replayed_quorum_vote.signature = da_vote.signature.clone();
+    assert!(!is_valid_vote(&replayed_quorum_vote, epoch, &upgrade_lock).await);
+
+    // Nor does a quorum signature replayed on a DA vote.
+    let mut replayed_da_vote = da_vote.clone();
+    replayed_da_vote.signature = quorum_vote.signature.clone();
+    assert!(!is_valid_vote(&replayed_da_vote, epoch, &upgrade_lock).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_roles_separate_equal_data_commitments() {
+    let upgrade_lock = UpgradeLock::<TestTypes, EpochsTestVersions>::new();
+    let view = ViewNumber::new(1);
+    let epoch = EpochNumber::new(1);
+    let version = upgrade_lock.version(view).await.unwrap();
+
+    // The same data commitment signed in each role gives different vote commitments
+    let data_commitment = [3; 32];
+    let da_commitment: Commitment<DaData> =
+        VoteRole::Da.vote_commitment(&data_commitment, *view, *epoch, version);
+    let quorum_commitment: Commitment<DaData> =
+        VoteRole::Quorum.vote_commitment(&data_commitment, *view, *epoch, version);
+    assert_ne!(da_commitment, quorum_commitment);
+
+    // and those are the commitments votes in each role sign
+    let data = DaData {
+        payload_commit: vid_commitment(&[1, 2, 3], 4),
+    };
+    let versioned_data = VersionedVoteData::new(data.clone(), view, epoch, &upgrade_lock)
+        .await
+        .unwrap();
+    let expected: [u8; 32] = VoteRole::Da
+        .vote_commitment::<DaData>(data.commit().as_ref(), *view, *epoch, version)
+        .into();
+    let actual: [u8; 32] = versioned_data.commit().into();
+    assert_eq!(actual, expected);
+}
@@ -0,0 +1,95 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use either::Either;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::VoteAccumulator,
+    ValidatorConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_accumulator_drops_vote_for_another_view() {
+    hotshot::helpers::initialize_logging();
+
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..4)
+        .map(|node_id| ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, 1, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+
+    let epoch = EpochNumber::new(0);
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let mut accumulator =
+        VoteAccumulator::<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions> {
+            vote_outcomes: HashMap::new(),
+            signers: HashMap::new(),
+            phantom: PhantomData,
+            upgrade_lock: upgrade_lock.clone(),
+            metrics: Arc::default(),
+            on_certificate: None,
+        };
+
+    let vote_view = ViewNumber::new(2);
+    let accumulation_view = ViewNumber::new(3);
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+
+    let mut votes = Vec::new();
+    for validator in &validators {
+        votes.push(
+            DaVote::create_signed_vote(
+                data.clone(),
+                vote_view,
+                epoch,
+                &validator.public_key,
+                &validator.private_key,
+                &upgrade_lock,
+            )
+            .await
+            .unwrap(),
+        );
+    }
+
+    // Every vote is validly signed, but none of them are for the accumulation view.
+    for vote in &votes {
+        assert!(accumulator
+            .accumulate(vote, accumulation_view, &membership, epoch)
+            .await
+            .is_left());
+    }
+    assert!(accumulator.vote_outcomes.is_empty());
+    assert!(accumulator.signers.is_empty());
+
+    // The same votes form a certificate when accumulated for their own view.
+    let mut certificate = None;
+    for vote in &votes {
+        if let Either::Right(cert) = accumulator
+            .accumulate(vote, vote_view, &membership, epoch)
+            .await
+        {
+            certificate = Some(cert);
+            break;
+        }
+    }
+    assert_eq!(certificate.unwrap().view_number, vote_view);
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use either::Either;
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::{TestTypes, TestVersions};
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    message::UpgradeLock,
+    simple_certificate::DaCertificate,
+    simple_vote::{DaData, DaVote},
+    traits::{
+        block_contents::vid_commitment,
+        election::Membership,
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    vote::{counted_vote_weight, Certificate, VoteAccumulator},
+    ValidatorConfig,
+};
+use primitive_types::U256;
+
+/// Accumulate DA votes from `voters` in order under a stake table capping each vote at
+/// `max_vote_weight`. Returns how many votes it took to form a certificate, if one was formed,
+/// and whether the certificate is valid against the same stake table.
+async fn votes_to_certificate(
+    validators: &[ValidatorConfig<BLSPubKey>],
+    voters: &[usize],
+    max_vote_weight: Option<u64>,
+) -> Option<(usize, bool)> {
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let mut membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    membership.set_max_vote_weight(max_vote_weight);
+    let epoch = EpochNumber::new(0);
+    let view = ViewNumber::new(1);
+    let upgrade_lock = UpgradeLock::<TestTypes, TestVersions>::new();
+    let mut accumulator =
+        VoteAccumulator::<TestTypes, DaVote<TestTypes>, DaCertificate<TestTypes>, TestVersions> {
+            vote_outcomes: HashMap::new(),
+            signers: HashMap::new(),
+            phantom: PhantomData,
+            upgrade_lock: upgrade_lock.clone(),
+            metrics: Arc::default(),
+            on_certificate: None,
+        };
+    let data = DaData {
+        payload_commit: vid_commitment(&[], validators.len()),
+    };
+
+    for (count, &voter) in voters.iter().enumerate() {
+        let vote = DaVote::create_signed_vote(
+            data.clone(),
+            view,
+            epoch,
+            &validators[voter].public_key,
+            &validators[voter].private_key,
+            &upgrade_lock,
+        )
+        .await
+        .unwrap();
+        if let Either::Right(certificate) = accumulator
+            .accumulate(&vote, view, &membership, epoch)
+            .await
+        {
+            let valid = certificate
+                .is_valid_cert(&membership, epoch, &upgrade_lock)
+                .await;
+            return Some((count + 1, valid));
+        }
+    }
+
+    None
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_vote_weight_is_clamped_to_cap() {
+    hotshot::helpers::initialize_logging();
+
+    // One validator holds 60% of the stake; the threshold is 4 of the total stake of 10.
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..5)
+        .map(|node_id| {
+            let stake = if node_id == 0 { 6 } else { 1 };
+            ValidatorConfig::generated_from_seed_indexed([0u8; 32], node_id, stake, true)
+        })
+        .collect();
+
+    assert_eq!(counted_vote_weight(U256::from(6), None), U256::from(6));
+    assert_eq!(counted_vote_weight(U256::from(6), Some(2)), U256::from(2));
+    assert_eq!(counted_vote_weight(U256::from(1), Some(2)), U256::from(1));
+
+    // Uncapped, the large validator forms a certificate on its own.
+    assert_eq!(
+        votes_to_certificate(&validators, &[0], None).await,
+        Some((1, true))
+    );
+
+    // Capped, it counts for 2 of a total stake of 6, so the threshold scales down to 3 and it
+    // needs one more vote. Replicas weigh the certificate the same way and accept it.
+    assert_eq!(votes_to_certificate(&validators, &[0], Some(2)).await, None);
+    assert_eq!(
+        votes_to_certificate(&validators, &[0, 1], Some(2)).await,
+        Some((2, true))
+    );
+
+    // However low the cap, the threshold scales with it and certificates can still be formed.
+    assert_eq!(
+        votes_to_certificate(&validators, &[1, 2, 3], Some(1)).await,
+        Some((2, true))
+    );
+}
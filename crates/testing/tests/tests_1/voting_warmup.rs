@@ -0,0 +1,51 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_task_impls::helpers::VotingWarmup;
+use hotshot_types::{data::ViewNumber, traits::node_implementation::ConsensusTime};
+
+#[test]
+fn test_node_joining_mid_run_votes_after_warmup() {
+    // The node starts at genesis, but the first view it sees from the network is view 10.
+    let mut warmup = VotingWarmup::<TestTypes>::new(ViewNumber::genesis(), 3);
+
+    let mut voted_views = Vec::new();
+    for view in 10..=15 {
+        let view = ViewNumber::new(view);
+        warmup.observe(view);
+        if warmup.may_vote(view) {
+            voted_views.push(*view);
+        }
+    }
+
+    // Views 10 through 12 are only observed; voting starts once the warm-up has elapsed.
+    assert_eq!(voted_views, vec![13, 14, 15]);
+}
+
+#[test]
+fn test_warmed_up_node_does_not_vote_while_behind_the_tip() {
+    let mut warmup = VotingWarmup::<TestTypes>::new(ViewNumber::genesis(), 3);
+    warmup.observe(ViewNumber::new(10));
+    warmup.observe(ViewNumber::new(20));
+
+    // The warm-up has elapsed, but the node has not caught up to within one view of the network.
+    assert!(!warmup.may_vote(ViewNumber::new(15)));
+    assert!(warmup.may_vote(ViewNumber::new(19)));
+    assert!(warmup.may_vote(ViewNumber::new(20)));
+}
+
+#[test]
+fn test_node_starting_with_the_network_votes_immediately() {
+    let mut warmup = VotingWarmup::<TestTypes>::new(ViewNumber::genesis(), 3);
+    warmup.observe(ViewNumber::new(1));
+    assert!(warmup.may_vote(ViewNumber::new(1)));
+
+    // Without a warm-up period, a node joining mid-run votes straight away.
+    let mut no_warmup = VotingWarmup::<TestTypes>::new(ViewNumber::genesis(), 0);
+    no_warmup.observe(ViewNumber::new(10));
+    assert!(no_warmup.may_vote(ViewNumber::new(10)));
+}
@@ -0,0 +1,77 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+use hotshot::types::BLSPubKey;
+use hotshot_example_types::node_types::TestTypes;
+use hotshot_types::{
+    data::{EpochNumber, ViewNumber},
+    traits::{
+        election::{Membership, MembershipChange},
+        node_implementation::{ConsensusTime, NodeType},
+    },
+    ValidatorConfig,
+};
+
+/// The stakes of the nodes in the committee
+const STAKES: [u64; 4] = [1, 2, 3, 4];
+
+/// The number of views to draw leaders for
+const VIEWS: u64 = 10_000;
+
+#[test]
+fn test_weighted_leaders_follow_stake() {
+    let validators: Vec<ValidatorConfig<BLSPubKey>> = (0..)
+        .zip(STAKES)
+        .map(|(i, stake)| ValidatorConfig::generated_from_seed_indexed([0u8; 32], i, stake, true))
+        .collect();
+    let peers: Vec<_> = validators
+        .iter()
+        .map(ValidatorConfig::public_config)
+        .collect();
+    let membership = <TestTypes as NodeType>::Membership::new(peers.clone(), peers);
+    let epoch = EpochNumber::new(0);
+
+    // Every node derives the same leader for a view
+    let other_node = membership.clone();
+    let mut picks = vec![0u64; STAKES.len()];
+    for view in (0..VIEWS).map(ViewNumber::new) {
+        let leader = membership.leader_weighted(view, epoch).unwrap();
+        assert_eq!(other_node.leader_weighted(view, epoch).unwrap(), leader);
+
+        let index = validators
+            .iter()
+            .position(|validator| validator.public_key == leader)
+            .unwrap();
+        picks[index] += 1;
+    }
+
+    // Each node leads in proportion to its stake, give or take
+    let total_stake: u64 = STAKES.iter().sum();
+    for (&stake, &picked) in STAKES.iter().zip(&picks) {
+        let expected = VIEWS * stake / total_stake;
+        assert!(picked.abs_diff(expected) < expected / 5, "{picks:?}");
+    }
+
+    // Once a node holds no stake it is never picked
+    let unstaked = validators[3].public_key;
+    let mut without_stake = membership.clone();
+    without_stake
+        .apply_change(MembershipChange::UpdateStake {
+            key: unstaked,
+            stake: 0,
+        })
+        .unwrap();
+    for view in (0..VIEWS).map(ViewNumber::new) {
+        assert_ne!(
+            without_stake.leader_weighted(view, epoch).unwrap(),
+            unstaked
+        );
+    }
+
+    // And with no stake at all there is no leader
+    let empty = <TestTypes as NodeType>::Membership::new(vec![], vec![]);
+    assert!(empty.leader_weighted(ViewNumber::new(1), epoch).is_err());
+}
@@ -23,6 +23,7 @@ use hotshot_example_types::{
     storage_types::TestStorage,
 };
 use hotshot_types::{
+    commit_scheme::DefaultCommitScheme,
     data::{EpochNumber, ViewNumber},
     message::{DataMessage, Message, MessageKind, UpgradeLock},
     signature_key::{BLSPubKey, BuilderKey},
@@ -63,6 +64,7 @@ impl NodeType for Test {
     type InstanceState = TestInstanceState;
     type Membership = StaticCommittee<Test>;
     type BuilderSignatureKey = BuilderKey;
+    type CommitScheme = DefaultCommitScheme;
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
@@ -352,3 +354,54 @@ async fn memory_network_test_in_flight_message_count() {
         Some(0)
     );
 }
+
+#[tokio::test(flavor = "multi_thread")]
+#[instrument]
+async fn memory_network_broadcast_with_ack() {
+    hotshot::helpers::initialize_logging();
+
+    let group: Arc<MasterMap<<Test as NodeType>::SignatureKey>> = MasterMap::new();
+    let pub_key_1 = pubkey();
+    let network1 = MemoryNetwork::new(&pub_key_1, &group, &[Topic::Global], Option::None);
+
+    // Only three of the five recipients are actually on the network.
+    let recipients: Vec<_> = (0..5).map(|_| pubkey()).collect();
+    let live_networks: Vec<_> = recipients[..3]
+        .iter()
+        .map(|key| MemoryNetwork::new(key, &group, &[Topic::Global], Option::None))
+        .collect();
+
+    let upgrade_lock = UpgradeLock::<Test, TestVersions>::new();
+    let message = gen_messages(1, 100, pub_key_1).remove(0);
+    let serialized_message = upgrade_lock.serialize(&message).await.unwrap();
+
+    let acks = network1
+        .broadcast_with_ack(
+            serialized_message,
+            recipients.clone(),
+            3,
+            Duration::from_secs(1),
+        )
+        .await
+        .expect("Failed to collect acks");
+    assert_eq!(acks, 3);
+
+    for network in &live_networks {
+        let recv_message = network
+            .recv_message()
+            .await
+            .expect("Failed to receive message");
+        let deserialized_message = upgrade_lock.deserialize(&recv_message).await.unwrap();
+        fake_message_eq(message.clone(), deserialized_message);
+    }
+
+    // Four acks can never be collected from three live recipients.
+    assert!(network1
+        .broadcast_with_ack(vec![], recipients.clone(), 4, Duration::from_secs(1))
+        .await
+        .is_err());
+    assert!(network1
+        .broadcast_with_ack(vec![], recipients, 6, Duration::from_secs(1))
+        .await
+        .is_err());
+}
@@ -0,0 +1,51 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+//! Compares committing to the vote data of a vote round afresh against committing to it through a
+//! [`CommitmentCache`].
+
+use std::num::NonZeroUsize;
+
+use committable::Committable;
+use criterion::Criterion;
+use hotshot_types::{
+    commitment_cache::CommitmentCache, simple_vote::DaData, traits::block_contents::vid_commitment,
+};
+
+/// Number of nodes voting in the round
+const NUM_NODES: usize = 100;
+
+/// Benchmark the two commitments per node of a vote round, one to sign the vote and one to check
+/// it, with and without the cache
+fn vote_round(c: &mut Criterion) {
+    let data = DaData {
+        payload_commit: vid_commitment(&[], NUM_NODES),
+    };
+
+    let mut group = c.benchmark_group("vote_round");
+    group.bench_function("fresh", |b| {
+        b.iter(|| {
+            for _ in 0..2 * NUM_NODES {
+                std::hint::black_box(data.commit());
+            }
+        });
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            let cache = CommitmentCache::new(NonZeroUsize::new(16).unwrap());
+            for _ in 0..2 * NUM_NODES {
+                std::hint::black_box(cache.commit(&data));
+            }
+        });
+    });
+    group.finish();
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    vote_round(&mut criterion);
+    criterion.final_summary();
+}
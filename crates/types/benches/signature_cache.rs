@@ -0,0 +1,48 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+//! Compares verifying a vote delivered many times afresh against verifying it through a
+//! [`SignatureCache`].
+
+use std::num::NonZeroUsize;
+
+use criterion::Criterion;
+use hotshot_types::{
+    signature_cache::SignatureCache, signature_key::BLSPubKey, traits::signature_key::SignatureKey,
+};
+
+/// Number of times the same vote is delivered
+const DELIVERIES: usize = 50;
+
+/// Benchmark `DELIVERIES` verifications of one vote, with and without the cache
+fn duplicate_deliveries(c: &mut Criterion) {
+    let (key, private_key) = BLSPubKey::generated_from_seed_indexed([0u8; 32], 0);
+    let signature = BLSPubKey::sign(&private_key, b"vote").unwrap();
+
+    let mut group = c.benchmark_group("duplicate_deliveries");
+    group.bench_function("fresh", |b| {
+        b.iter(|| {
+            for _ in 0..DELIVERIES {
+                assert!(key.validate(&signature, b"vote"));
+            }
+        });
+    });
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            let cache = SignatureCache::<BLSPubKey>::new(NonZeroUsize::new(16).unwrap());
+            for _ in 0..DELIVERIES {
+                assert!(cache.validate(&key, &signature, b"vote"));
+            }
+        });
+    });
+    group.finish();
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    duplicate_deliveries(&mut criterion);
+    criterion.final_summary();
+}
@@ -0,0 +1,93 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+//! Pluggable hash functions for the commitments to leaves and vote data.
+//!
+//! By default these commitments use the `committable` builder, like every other commitment.
+//! Applications proving consensus in a circuit can instead select [`RescueCommitScheme`], whose
+//! hash is cheap to verify in a SNARK, through
+//! [`NodeType::CommitScheme`](crate::traits::node_implementation::NodeType::CommitScheme). Every
+//! node of a network must use the same scheme, otherwise their votes will never combine.
+
+use std::fmt::Debug;
+
+use ark_ff::{BigInteger, PrimeField};
+use committable::{Commitment, Committable};
+use jf_crhf::CRHF;
+use jf_rescue::crhf::VariableLengthRescueCRHF;
+use serde::{Deserialize, Serialize};
+
+use crate::light_client::CircuitField;
+
+/// A hash function for the commitments to leaves and vote data
+pub trait CommitScheme:
+    Clone + Copy + Debug + Default + PartialEq + Eq + Send + Sync + 'static
+{
+    /// Compute the commitment to `value`
+    fn commit<T: SchemeCommittable>(value: &T) -> Commitment<T>;
+
+    /// Whether `commitment` is the commitment to `value` under this scheme
+    fn verify<T: SchemeCommittable>(value: &T, commitment: &Commitment<T>) -> bool {
+        Self::commit(value) == *commitment
+    }
+}
+
+/// Data whose commitment is computed by a [`CommitScheme`]
+pub trait SchemeCommittable: Committable {
+    /// Domain separation tag for the commitment
+    const TAG: &'static str;
+
+    /// The commitment under [`DefaultCommitScheme`]
+    fn default_commitment(&self) -> Commitment<Self>;
+
+    /// The canonical encoding of the committed fields, hashed by every other scheme
+    fn commitment_preimage(&self) -> Vec<u8>;
+}
+
+/// The `committable` builder used for every other commitment
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DefaultCommitScheme;
+
+impl CommitScheme for DefaultCommitScheme {
+    fn commit<T: SchemeCommittable>(value: &T) -> Commitment<T> {
+        value.default_commitment()
+    }
+}
+
+/// Commitments computed with the Rescue sponge over the BN254 scalar field, the circuit-friendly
+/// hash the light client and stake table commitments already use
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RescueCommitScheme;
+
+impl RescueCommitScheme {
+    /// Number of preimage bytes packed into each field element, so that no packed element
+    /// overflows the field
+    const BYTES_PER_ELEMENT: usize = 31;
+}
+
+impl CommitScheme for RescueCommitScheme {
+    fn commit<T: SchemeCommittable>(value: &T) -> Commitment<T> {
+        let body = value.commitment_preimage();
+
+        // Length-prefix both parts, so distinct (tag, body) pairs never share an encoding
+        let mut preimage = Vec::with_capacity(16 + T::TAG.len() + body.len());
+        preimage.extend_from_slice(&(T::TAG.len() as u64).to_le_bytes());
+        preimage.extend_from_slice(T::TAG.as_bytes());
+        preimage.extend_from_slice(&(body.len() as u64).to_le_bytes());
+        preimage.extend_from_slice(&body);
+
+        let elements: Vec<CircuitField> = preimage
+            .chunks(Self::BYTES_PER_ELEMENT)
+            .map(CircuitField::from_le_bytes_mod_order)
+            .collect();
+        let digest = VariableLengthRescueCRHF::<CircuitField, 1>::evaluate(&elements)
+            .expect("Rescue accepts inputs of any length")[0];
+
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(&digest.into_bigint().to_bytes_le());
+        Commitment::from_raw(bytes)
+    }
+}
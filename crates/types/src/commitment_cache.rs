@@ -0,0 +1,128 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+//! Caching of commitments for `Committable` types which are expensive to hash.
+//!
+//! Entries are keyed on the content of the value, so two equal values share a cache entry and a
+//! value which is mutated after being cached is simply looked up under its new content.
+//!
+//! Each node owns its cache through its [`UpgradeLock`](crate::message::UpgradeLock), sized by
+//! `HotShotConfig::commitment_cache_capacity`.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    hash::Hash,
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use committable::{Commitment, Committable};
+use lru::LruCache;
+use parking_lot::Mutex;
+
+/// The cached commitments to values of type `T`
+type Entries<T> = LruCache<T, Commitment<T>>;
+
+/// An LRU cache of commitments, holding at most its capacity for each committable type.
+///
+/// Values are located by their `Hash` implementation, which is far cheaper than computing the
+/// commitment, and confirmed by equality, so a hash collision never returns a wrong commitment.
+#[derive(derive_more::Debug)]
+pub struct CommitmentCache {
+    /// Number of commitments held for each type
+    capacity: NonZeroUsize,
+    /// The cached commitments, as an [`Entries`] for each type
+    #[debug(skip)]
+    entries: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+    /// Number of lookups answered from the cache
+    hits: AtomicU64,
+    /// Number of lookups which had to compute the commitment
+    misses: AtomicU64,
+}
+
+impl CommitmentCache {
+    /// Create an empty cache holding at most `capacity` commitments of each type.
+    #[must_use]
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the commitment to `value`, computing and caching it on a miss.
+    pub fn commit<T>(&self, value: &T) -> Commitment<T>
+    where
+        T: Committable + Hash + Eq + Clone + Send + 'static,
+    {
+        if let Some(commitment) = self
+            .entries
+            .lock()
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|entries| entries.downcast_mut::<Entries<T>>())
+            .and_then(|entries| entries.get(value).copied())
+        {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return commitment;
+        }
+
+        // Hash outside of the lock, so concurrent misses on other values are not serialized.
+        let commitment = value.commit();
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        if let Some(entries) = self
+            .entries
+            .lock()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Entries::<T>::new(self.capacity)))
+            .downcast_mut::<Entries<T>>()
+        {
+            entries.put(value.clone(), commitment);
+        }
+
+        commitment
+    }
+
+    /// Whether a commitment to `value` is currently cached.
+    #[must_use]
+    pub fn contains<T>(&self, value: &T) -> bool
+    where
+        T: Committable + Hash + Eq + 'static,
+    {
+        self.entries
+            .lock()
+            .get(&TypeId::of::<T>())
+            .and_then(|entries| entries.downcast_ref::<Entries<T>>())
+            .is_some_and(|entries| entries.contains(value))
+    }
+
+    /// Number of lookups answered from the cache so far.
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of lookups which had to compute the commitment so far.
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+/// Get the commitment to `value`, through `cache` if there is one.
+///
+/// The result is always equal to `value.commit()`.
+pub fn commit_with_cache<T>(cache: Option<&CommitmentCache>, value: &T) -> Commitment<T>
+where
+    T: Committable + Hash + Eq + Clone + Send + 'static,
+{
+    match cache {
+        Some(cache) => cache.commit(value),
+        None => value.commit(),
+    }
+}
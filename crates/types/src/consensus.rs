@@ -7,20 +7,26 @@
 //! Provides the core consensus types
 
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     mem::ManuallyDrop,
+    num::NonZeroUsize,
     ops::{Deref, DerefMut},
     sync::Arc,
+    time::Duration,
 };
 
 use async_lock::{RwLock, RwLockReadGuard, RwLockUpgradableReadGuard, RwLockWriteGuard};
 use committable::{Commitment, Committable};
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
 use utils::anytrace::*;
 use vec1::Vec1;
 
 pub use crate::utils::{View, ViewInner};
 use crate::{
+    constants::VID_SHARE_CACHE_SIZE,
     data::{Leaf2, QuorumProposal2, VidDisperse, VidDisperseShare},
     error::HotShotError,
     event::{HotShotAction, LeafInfo},
@@ -268,6 +274,146 @@ impl<T: ConsensusTime> HotShotActionViews<T> {
         }
     }
 }
+/// A read-through LRU cache of VID shares keyed by `(view, recipient key)`.
+///
+/// Lookups which hit the cache do not need to take the consensus lock. Entries for views which
+/// are garbage collected from [`Consensus`] are invalidated via [`VidShareCache::prune`], and
+/// shares which are replaced in [`Consensus`] are overwritten via [`VidShareCache::replace`].
+#[derive(derive_more::Debug)]
+pub struct VidShareCache<TYPES: NodeType> {
+    /// The cached shares, and the view below which shares have been garbage collected
+    #[debug(skip)]
+    shares: Mutex<(
+        LruCache<(TYPES::View, TYPES::SignatureKey), Proposal<TYPES, VidDisperseShare<TYPES>>>,
+        TYPES::View,
+    )>,
+}
+
+impl<TYPES: NodeType> VidShareCache<TYPES> {
+    /// Create an empty cache holding at most `capacity` shares.
+    #[must_use]
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            shares: Mutex::new((LruCache::new(capacity), TYPES::View::genesis())),
+        }
+    }
+
+    /// Get the VID share for `key` in `view`, reading through to `consensus` on a cache miss.
+    pub async fn get(
+        &self,
+        consensus: &RwLock<Consensus<TYPES>>,
+        view: TYPES::View,
+        key: &TYPES::SignatureKey,
+    ) -> Option<Proposal<TYPES, VidDisperseShare<TYPES>>> {
+        let cached = self.shares.lock().0.get(&(view, key.clone())).cloned();
+        if cached.is_some() {
+            return cached;
+        }
+
+        // The share is cached before the consensus lock is released, so a replacement or garbage
+        // collection of it can't land in between and be undone.
+        let consensus = consensus.read().await;
+        let share = consensus.vid_shares().get(&view)?.get(key).cloned()?;
+        let (shares, gc_view) = &mut *self.shares.lock();
+        if view >= *gc_view {
+            shares.put((view, key.clone()), share.clone());
+        }
+
+        Some(share)
+    }
+
+    /// Overwrite the cached share for `view` and its recipient, if it is cached.
+    pub fn replace(&self, view: TYPES::View, share: &Proposal<TYPES, VidDisperseShare<TYPES>>) {
+        let (shares, _) = &mut *self.shares.lock();
+        if let Some(cached) = shares.peek_mut(&(view, share.data.recipient_key.clone())) {
+            cached.clone_from(share);
+        }
+    }
+
+    /// Invalidate all cached shares for views older than `gc_view`, and keep any from being
+    /// cached again.
+    pub fn prune(&self, gc_view: TYPES::View) {
+        let (shares, watermark) = &mut *self.shares.lock();
+        if gc_view > *watermark {
+            *watermark = gc_view;
+        }
+        let stale: Vec<_> = shares
+            .iter()
+            .filter(|((view, _), _)| *view < gc_view)
+            .map(|(entry, _)| entry.clone())
+            .collect();
+        for entry in stale {
+            shares.pop(&entry);
+        }
+    }
+}
+
+impl<TYPES: NodeType> Default for VidShareCache<TYPES> {
+    fn default() -> Self {
+        Self::new(NonZeroUsize::new(VID_SHARE_CACHE_SIZE).unwrap())
+    }
+}
+
+/// What the DA leader does with a vote received before the proposal for its view
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EarlyVotePolicy {
+    /// Handle the vote immediately, as if the proposal had been seen
+    #[default]
+    Process,
+    /// Hold the vote until the proposal for its view is validated or sent
+    Buffer {
+        /// Maximum number of votes held at once, across all views
+        max_votes: usize,
+        /// How long the votes for a view are held before they are dropped
+        ttl: Duration,
+    },
+}
+
+/// What the leader does when it has no transactions to propose for its view
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EmptyBlockPolicy {
+    /// Propose an empty block immediately
+    #[default]
+    ProposeEmpty,
+    /// Propose nothing and let the view time out, unless a proposal is needed to decide earlier
+    /// blocks
+    SkipView,
+    /// Wait this long and ask for transactions again, proposing an empty block if there still
+    /// are none
+    ProposeAfterDelay(Duration),
+}
+
+/// Whether a node takes part in consensus or only follows it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ParticipationMode {
+    /// Vote, propose and form certificates
+    #[default]
+    Validator,
+    /// Validate and store proposals and certificates and serve payloads, but never vote or
+    /// propose. A read replica holds no stake, so it should be left out of the stake table and
+    /// with it the thresholds
+    ReadReplica,
+}
+
+/// The progress of a single view, as reported by [`Consensus::pending_views`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingViewInfo<TYPES: NodeType> {
+    /// The view
+    pub view: TYPES::View,
+
+    /// Whether we hold a proposal for the view
+    pub proposal_received: bool,
+
+    /// Whether we hold a DA certificate for the view
+    pub da_certificate_formed: bool,
+
+    /// Whether we have seen a quorum certificate for the view
+    pub qc_formed: bool,
+
+    /// Whether the view has been decided
+    pub decided: bool,
+}
+
 /// A reference to the consensus algorithm
 ///
 /// This will contain the state of all rounds.
@@ -322,6 +468,15 @@ pub struct Consensus<TYPES: NodeType> {
 
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+
+    /// Read-through cache of `vid_shares`, invalidated on garbage collection
+    vid_share_cache: Arc<VidShareCache<TYPES>>,
+
+    /// The first valid QC we saw for each view not yet garbage collected
+    certified_views: BTreeMap<TYPES::View, QuorumCertificate2<TYPES>>,
+
+    /// The view of the safety fork which halted this node, if any
+    safety_fork: Option<TYPES::View>,
 }
 
 /// Contains several `ConsensusMetrics` that we're interested in from the consensus interfaces
@@ -357,6 +512,28 @@ pub struct ConsensusMetricsValue {
     pub number_of_empty_blocks_proposed: Box<dyn Counter>,
     /// Number of events in the hotshot event queue
     pub internal_event_queue_len: Box<dyn Gauge>,
+    /// Number of events queued for the DA task when the last one arrived
+    pub da_event_queue_len: Box<dyn Gauge>,
+    /// Number of times appending a DA proposal to storage was retried
+    pub da_storage_retries: Box<dyn Counter>,
+    /// Number of DA proposals which could not be appended to storage after all retries
+    pub da_storage_failures: Box<dyn Counter>,
+    /// Number of DA proposals received from the network
+    pub da_proposals_received: Box<dyn Counter>,
+    /// Number of DA proposals from the network which failed validation
+    pub da_proposals_rejected: Box<dyn Counter>,
+    /// Number of DA votes this node sent
+    pub da_votes_sent: Box<dyn Counter>,
+    /// Number of DA votes this node sent which could not reach the DA leader
+    pub da_votes_undelivered: Box<dyn Counter>,
+    /// Number of DA votes this node sent which the DA leader refused
+    pub da_votes_refused: Box<dyn Counter>,
+    /// Time taken to compute the VID commitment of a validated DA proposal, in milliseconds
+    pub da_vid_commitment_duration: Box<dyn Histogram>,
+    /// Number of transactions from the network rejected by the transaction validator
+    pub rejected_transactions: Box<dyn Counter>,
+    /// Number of proposals whose timestamp was further from local time than the allowed skew
+    pub clock_skewed_proposals: Box<dyn Counter>,
 }
 
 impl ConsensusMetricsValue {
@@ -388,6 +565,23 @@ impl ConsensusMetricsValue {
                 .create_counter(String::from("number_of_empty_blocks_proposed"), None),
             internal_event_queue_len: metrics
                 .create_gauge(String::from("internal_event_queue_len"), None),
+            da_event_queue_len: metrics.create_gauge(String::from("da_event_queue_len"), None),
+            da_storage_retries: metrics.create_counter(String::from("da_storage_retries"), None),
+            da_storage_failures: metrics.create_counter(String::from("da_storage_failures"), None),
+            da_proposals_received: metrics
+                .create_counter(String::from("da_proposals_received"), None),
+            da_proposals_rejected: metrics
+                .create_counter(String::from("da_proposals_rejected"), None),
+            da_votes_sent: metrics.create_counter(String::from("da_votes_sent"), None),
+            da_votes_undelivered: metrics
+                .create_counter(String::from("da_votes_undelivered"), None),
+            da_votes_refused: metrics.create_counter(String::from("da_votes_refused"), None),
+            da_vid_commitment_duration: metrics
+                .create_histogram(String::from("da_vid_commitment_duration"), None),
+            rejected_transactions: metrics
+                .create_counter(String::from("rejected_transactions"), None),
+            clock_skewed_proposals: metrics
+                .create_counter(String::from("clock_skewed_proposals"), None),
         }
     }
 }
@@ -430,6 +624,9 @@ impl<TYPES: NodeType> Consensus<TYPES> {
             high_qc,
             metrics,
             epoch_height,
+            vid_share_cache: Arc::default(),
+            certified_views: BTreeMap::new(),
+            safety_fork: None,
         }
     }
 
@@ -458,6 +655,40 @@ impl<TYPES: NodeType> Consensus<TYPES> {
         &self.high_qc
     }
 
+    /// Record a valid QC, returning the QC seen earlier for the same view if the two certify
+    /// different leaves.
+    ///
+    /// Two such QCs are a safety fork, which means more than a third of the stake is faulty.
+    pub fn record_qc(
+        &mut self,
+        qc: &QuorumCertificate2<TYPES>,
+    ) -> Option<QuorumCertificate2<TYPES>> {
+        let seen = self
+            .certified_views
+            .entry(qc.view_number)
+            .or_insert_with(|| qc.clone());
+
+        (seen.data.leaf_commit != qc.data.leaf_commit).then(|| seen.clone())
+    }
+
+    /// Halt this node after observing a safety fork in `view`. The first fork is kept.
+    pub fn halt_on_safety_fork(&mut self, view: TYPES::View) {
+        tracing::error!("Safety fork observed in view {view:?}, halting");
+        self.safety_fork.get_or_insert(view);
+    }
+
+    /// The view of the safety fork which halted this node, if any
+    pub fn safety_fork(&self) -> Option<TYPES::View> {
+        self.safety_fork
+    }
+
+    /// Number of views the high QC is ahead of the last decided view
+    pub fn undecided_views(&self) -> u64 {
+        self.high_qc
+            .view_number()
+            .saturating_sub(*self.last_decided_view)
+    }
+
     /// Get the validated state map.
     pub fn validated_state_map(&self) -> &BTreeMap<TYPES::View, View<TYPES>> {
         &self.validated_state_map
@@ -478,6 +709,11 @@ impl<TYPES: NodeType> Consensus<TYPES> {
         &self.vid_shares
     }
 
+    /// Get a handle to the read-through VID share cache.
+    pub fn vid_share_cache(&self) -> Arc<VidShareCache<TYPES>> {
+        Arc::clone(&self.vid_share_cache)
+    }
+
     /// Get the saved DA certs.
     pub fn saved_da_certs(&self) -> &HashMap<TYPES::View, DaCertificate<TYPES>> {
         &self.saved_da_certs
@@ -490,6 +726,50 @@ impl<TYPES: NodeType> Consensus<TYPES> {
         &self.last_proposals
     }
 
+    /// The progress of every view from the last decided view up to the current view, or up to
+    /// the latest view we hold data for if that is further ahead.
+    ///
+    /// Meant for diagnosing stalls: the first view which is not decided shows where progress
+    /// halted.
+    pub fn pending_views(&self) -> Vec<PendingViewInfo<TYPES>> {
+        let qc_views: BTreeSet<TYPES::View> = self
+            .saved_leaves
+            .values()
+            .map(|leaf| leaf.justify_qc().view_number())
+            .chain(std::iter::once(self.high_qc.view_number()))
+            .collect();
+        let last_view = [
+            Some(self.cur_view),
+            self.validated_state_map.keys().next_back().copied(),
+            self.saved_da_certs.keys().max().copied(),
+            qc_views.last().copied(),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(self.cur_view);
+
+        let mut view = self.last_decided_view;
+        let mut pending = Vec::new();
+        while view <= last_view {
+            pending.push(PendingViewInfo {
+                view,
+                proposal_received: matches!(
+                    self.validated_state_map.get(&view),
+                    Some(View {
+                        view_inner: ViewInner::Leaf { .. }
+                    })
+                ),
+                da_certificate_formed: self.saved_da_certs.contains_key(&view),
+                qc_formed: qc_views.contains(&view),
+                decided: view <= self.last_decided_view,
+            });
+            view = view + 1;
+        }
+
+        pending
+    }
+
     /// Update the current view.
     /// # Errors
     /// Can return an error when the new view_number is not higher than the existing view number.
@@ -720,6 +1000,28 @@ impl<TYPES: NodeType> Consensus<TYPES> {
         Ok(())
     }
 
+    /// Record a DA proposal we have voted on: track `view_number` in the view map, so that it is
+    /// garbage collected, and save the payload we have promised to make available.
+    ///
+    /// Both updates are always attempted, so the view is tracked even if its payload was saved
+    /// already. If the view already holds a `Leaf`, it is tracked already and only the payload is
+    /// saved.
+    ///
+    /// # Errors
+    /// If a payload for `view_number` is already saved, in which case only the view is tracked.
+    pub fn update_da_view_and_payload(
+        &mut self,
+        view_number: TYPES::View,
+        payload_commitment: VidCommitment,
+        encoded_transactions: Arc<[u8]>,
+    ) -> Result<()> {
+        if let Err(e) = self.update_da_view(view_number, payload_commitment) {
+            tracing::trace!("{e:?}");
+        }
+
+        self.update_saved_payloads(view_number, encoded_transactions)
+    }
+
     /// Update the high QC if given a newer one.
     /// # Errors
     /// Can return an error when the provided high_qc is not newer than the existing entry.
@@ -740,6 +1042,7 @@ impl<TYPES: NodeType> Consensus<TYPES> {
         view_number: TYPES::View,
         disperse: Proposal<TYPES, VidDisperseShare<TYPES>>,
     ) {
+        self.vid_share_cache.replace(view_number, &disperse);
         self.vid_shares
             .entry(view_number)
             .or_default()
@@ -841,7 +1144,9 @@ impl<TYPES: NodeType> Consensus<TYPES> {
         self.validated_state_map = self.validated_state_map.split_off(&gc_view);
         self.saved_payloads = self.saved_payloads.split_off(&gc_view);
         self.vid_shares = self.vid_shares.split_off(&gc_view);
+        self.vid_share_cache.prune(gc_view);
         self.last_proposals = self.last_proposals.split_off(&gc_view);
+        self.certified_views = self.certified_views.split_off(&gc_view);
     }
 
     /// Gets the last decided leaf.
@@ -899,7 +1204,7 @@ impl<TYPES: NodeType> Consensus<TYPES> {
     pub async fn calculate_and_update_vid(
         consensus: OuterConsensus<TYPES>,
         view: <TYPES as NodeType>::View,
-        membership: Arc<TYPES::Membership>,
+        membership: Arc<RwLock<TYPES::Membership>>,
         private_key: &<TYPES::SignatureKey as SignatureKey>::PrivateKey,
         epoch: TYPES::Epoch,
     ) -> Option<()> {
@@ -35,6 +35,15 @@ pub const COMBINED_NETWORK_DELAY_DURATION: u64 = 5000;
 /// The default network data request delay in milliseconds
 pub const REQUEST_DATA_DELAY: u64 = 5000;
 
+/// The default number of times to retry appending a DA proposal to storage before giving up
+pub const DA_STORAGE_RETRIES: usize = 3;
+
+/// The default base backoff in milliseconds between DA storage retries, doubled after each attempt
+pub const DA_STORAGE_RETRY_BACKOFF: u64 = 50;
+
+/// The number of VID shares to keep in the read-through VID share cache
+pub const VID_SHARE_CACHE_SIZE: usize = 1024;
+
 /// Default channel size for consensus event sharing
 pub const EVENT_CHANNEL_SIZE: usize = 100_000;
 
@@ -76,3 +85,65 @@ pub const ORCHESTRATOR_DEFAULT_NUM_ROUNDS: usize = 100;
 pub const ORCHESTRATOR_DEFAULT_TRANSACTIONS_PER_ROUND: usize = 10;
 /// default size of transactions
 pub const ORCHESTRATOR_DEFAULT_TRANSACTION_SIZE: usize = 100;
+
+/// The default maximum number of epochs a view change may advance by
+pub const MAX_EPOCH_SKIP: u64 = 1;
+
+/// The default target size, in bytes, of the transactions in a proposed block
+pub const TARGET_BLOCK_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Default time a task may spend on one event with others queued before it is reported stalled
+pub const TASK_STALL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default for whether the task watchdog restarts stalled tasks
+pub const RESTART_STALLED_TASKS: bool = false;
+
+/// The default number of views a node joining behind the network observes before voting
+pub const WARMUP_VIEWS: u64 = 0;
+
+/// The default network identifier
+pub const NETWORK_ID: u64 = 0;
+
+/// The default number of commitments of each type held by a node's commitment cache, `0`
+/// disables it
+pub const COMMITMENT_CACHE_CAPACITY: usize = 1024;
+
+/// The default number of verification results held by a node's signature cache, `0` disables it
+pub const SIGNATURE_CACHE_CAPACITY: usize = 4096;
+
+/// Default for how many views old a consensus message may be before it is dropped on arrival
+pub const MAX_MESSAGE_AGE_VIEWS: u64 = 100;
+
+/// The default number of views beyond the next one a node buffers DA proposals for
+pub const MAX_FUTURE_BUFFER: u64 = 8;
+
+/// Default grace period for late votes, zero drops the collector as soon as its certificate forms
+pub const LATE_VOTE_GRACE: Duration = Duration::ZERO;
+
+/// Default fraction of the storage nodes VID assumes may be faulty, zero tolerates no lost shares
+pub const VID_FAULT_FRACTION: f64 = 0.0;
+
+/// Default for the most views the high QC may be ahead of the last decided view
+pub const MAX_UNDECIDED_VIEWS: u64 = 1000;
+
+/// Default for whether proposals sharing a justify QC reference it by commitment after the first
+pub const BATCH_JUSTIFY_QCS: bool = false;
+
+/// Default number of views on either side of an upgrade in which votes in the format of the
+/// other version are still accepted
+pub const VOTE_TRANSITION_VIEWS: u64 = 5;
+
+/// The default largest payload, in bytes, a DA proposal may carry before it is rejected unhashed
+pub const MAX_DA_PAYLOAD_BYTES: usize = 64 * 1024 * 1024;
+
+/// Default time the DA leader waits for its certificate to form after votes begin to arrive
+pub const DA_VOTE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default for how far the timestamp of a proposal may be from local time before it is flagged
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(12);
+
+/// The most events queued for the DA task before the event stream waits for it
+pub const DA_EVENT_QUEUE_CAPACITY: usize = 1024;
+
+/// The number of events queued for the DA task at which it is warned to be falling behind
+pub const DA_EVENT_QUEUE_HIGH_WATERMARK: usize = 768;
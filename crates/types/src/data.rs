@@ -14,6 +14,7 @@ use std::{
     fmt::{Debug, Display},
     hash::Hash,
     marker::PhantomData,
+    ops::Range,
     sync::Arc,
 };
 
@@ -30,6 +31,7 @@ use utils::anytrace::*;
 use vec1::Vec1;
 
 use crate::{
+    commit_scheme::{CommitScheme, SchemeCommittable},
     drb::{DrbResult, DrbSeedInput, INITIAL_DRB_RESULT, INITIAL_DRB_SEED_INPUT},
     message::{Proposal, UpgradeLock},
     simple_certificate::{
@@ -39,17 +41,20 @@ use crate::{
     simple_vote::{QuorumData, UpgradeProposalData, VersionedVoteData},
     traits::{
         block_contents::{
-            vid_commitment, BlockHeader, BuilderFee, EncodeBytes, TestableBlock,
-            GENESIS_VID_NUM_STORAGE_NODES,
+            vid_commitment, BlockHeader, BuilderFee, EncodeBytes, NamespaceId, NamespacedMetadata,
+            TestableBlock, GENESIS_VID_NUM_STORAGE_NODES,
         },
-        election::Membership,
+        election::{LeaderProof, Membership},
         node_implementation::{ConsensusTime, NodeType, Versions},
         signature_key::SignatureKey,
         states::TestableState,
         BlockPayload,
     },
     utils::bincode_opts,
-    vid::{vid_scheme, VidCommitment, VidCommon, VidPrecomputeData, VidSchemeType, VidShare},
+    vid::{
+        vid_scheme, InclusionProof, VidCommitment, VidCommon, VidPrecomputeData, VidSchemeType,
+        VidShare,
+    },
     vote::{Certificate, HasViewNumber},
 };
 
@@ -147,6 +152,46 @@ pub struct DaProposal<TYPES: NodeType> {
     pub view_number: TYPES::View,
 }
 
+impl<TYPES: NodeType> DaProposal<TYPES> {
+    /// Prove that the `txn_index`-th transaction of this proposal is included in its payload
+    /// commitment for `num_storage_nodes` storage nodes.
+    ///
+    /// # Errors
+    /// If there is no transaction at `txn_index`, or the proof cannot be computed.
+    pub fn inclusion_proof(
+        &self,
+        txn_index: usize,
+        num_storage_nodes: usize,
+    ) -> Result<InclusionProof> {
+        let payload = TYPES::BlockPayload::from_bytes(&self.encoded_transactions, &self.metadata);
+        let range = payload
+            .transaction_range(&self.metadata, txn_index)
+            .filter(|range| range.end <= self.encoded_transactions.len())
+            .context(warn!("No transaction at index {txn_index}"))?;
+
+        InclusionProof::new(&self.encoded_transactions, range, num_storage_nodes)
+            .wrap()
+            .context(warn!("Failed to compute inclusion proof"))
+    }
+}
+
+impl<TYPES: NodeType> DaProposal<TYPES>
+where
+    <TYPES::BlockPayload as BlockPayload<TYPES>>::Metadata: NamespacedMetadata,
+{
+    /// The byte range of namespace `ns` within `encoded_transactions`, or `None` if the block
+    /// does not contain any transactions for it.
+    ///
+    /// # Errors
+    /// If the namespace table in the metadata does not fit `encoded_transactions`.
+    pub fn namespace_range(&self, ns: NamespaceId) -> Result<Option<Range<usize>>> {
+        self.metadata
+            .validate_namespaces(self.encoded_transactions.len())?;
+
+        Ok(self.metadata.namespace_range(ns))
+    }
+}
+
 /// A proposal to upgrade the network
 #[derive(derive_more::Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
 #[serde(bound = "TYPES: NodeType")]
@@ -209,11 +254,13 @@ impl<TYPES: NodeType> VidDisperse<TYPES> {
     #[allow(clippy::panic)]
     pub async fn calculate_vid_disperse(
         txns: Arc<[u8]>,
-        membership: &Arc<TYPES::Membership>,
+        membership: &Arc<RwLock<TYPES::Membership>>,
         view: TYPES::View,
         epoch: TYPES::Epoch,
         precompute_data: Option<VidPrecomputeData>,
     ) -> Self {
+        // Disperse to the committee as of now, even if it changes while the shares are computed
+        let membership = membership.read().await.clone();
         let num_nodes = membership.total_nodes(epoch);
 
         let vid_disperse = spawn_blocking(move || {
@@ -227,7 +274,7 @@ impl<TYPES: NodeType> VidDisperse<TYPES> {
         // Unwrap here will just propagate any panic from the spawned task, it's not a new place we can panic.
         let vid_disperse = vid_disperse.unwrap();
 
-        Self::from_membership(view, vid_disperse, membership.as_ref(), epoch)
+        Self::from_membership(view, vid_disperse, &membership, epoch)
     }
 }
 
@@ -296,6 +343,7 @@ impl<TYPES: NodeType> VidDisperseShare<TYPES> {
         };
         Some(Proposal {
             signature,
+            leader_proof: LeaderProof::Deterministic,
             _pd: PhantomData,
             data: self,
         })
@@ -344,6 +392,7 @@ impl<TYPES: NodeType> VidDisperseShare<TYPES> {
                     payload_commitment: vid_disperse_proposal.data.payload_commitment,
                 },
                 signature: vid_disperse_proposal.signature.clone(),
+                leader_proof: vid_disperse_proposal.leader_proof.clone(),
                 _pd: vid_disperse_proposal._pd,
             })
             .collect()
@@ -373,6 +422,58 @@ pub struct QuorumProposal<TYPES: NodeType> {
     pub proposal_certificate: Option<ViewChangeEvidence<TYPES>>,
 }
 
+/// A [`QuorumProposal`] sent without its justify QC, which it references by commitment instead.
+///
+/// Sent by a leader whose previous proposal carried the same justify QC, so the QC only goes over
+/// the wire once.
+#[derive(derive_more::Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
+#[serde(bound(deserialize = ""))]
+pub struct QuorumProposalWithQcRef<TYPES: NodeType> {
+    /// The block header to append
+    pub block_header: TYPES::BlockHeader,
+
+    /// CurView from leader when proposing leaf
+    pub view_number: TYPES::View,
+
+    /// Commitment to the justify QC, which was sent with an earlier proposal
+    pub justify_qc: Commitment<QuorumCertificate<TYPES>>,
+
+    /// Possible upgrade certificate, which the leader may optionally attach.
+    pub upgrade_certificate: Option<UpgradeCertificate<TYPES>>,
+
+    /// Possible timeout or view sync certificate.
+    pub proposal_certificate: Option<ViewChangeEvidence<TYPES>>,
+}
+
+impl<TYPES: NodeType> QuorumProposalWithQcRef<TYPES> {
+    /// Restore the full proposal with `justify_qc`, if it is the QC this proposal references
+    #[must_use]
+    pub fn with_justify_qc(
+        self,
+        justify_qc: QuorumCertificate<TYPES>,
+    ) -> Option<QuorumProposal<TYPES>> {
+        (justify_qc.commit() == self.justify_qc).then(|| QuorumProposal {
+            block_header: self.block_header,
+            view_number: self.view_number,
+            justify_qc,
+            upgrade_certificate: self.upgrade_certificate,
+            proposal_certificate: self.proposal_certificate,
+        })
+    }
+}
+
+impl<TYPES: NodeType> From<QuorumProposal<TYPES>> for QuorumProposalWithQcRef<TYPES> {
+    fn from(quorum_proposal: QuorumProposal<TYPES>) -> Self {
+        Self {
+            block_header: quorum_proposal.block_header,
+            view_number: quorum_proposal.view_number,
+            justify_qc: quorum_proposal.justify_qc.commit(),
+            upgrade_certificate: quorum_proposal.upgrade_certificate,
+            proposal_certificate: quorum_proposal.proposal_certificate,
+        }
+    }
+}
+
 /// Proposal to append a block.
 #[derive(derive_more::Debug, Serialize, Deserialize, Clone, Eq, PartialEq, Hash)]
 #[serde(bound(deserialize = ""))]
@@ -473,6 +574,12 @@ impl<TYPES: NodeType> HasViewNumber<TYPES> for QuorumProposal<TYPES> {
     }
 }
 
+impl<TYPES: NodeType> HasViewNumber<TYPES> for QuorumProposalWithQcRef<TYPES> {
+    fn view_number(&self) -> TYPES::View {
+        self.view_number
+    }
+}
+
 impl<TYPES: NodeType> HasViewNumber<TYPES> for QuorumProposal2<TYPES> {
     fn view_number(&self) -> TYPES::View {
         self.view_number
@@ -697,6 +804,14 @@ impl<TYPES: NodeType> Leaf2<TYPES> {
 
 impl<TYPES: NodeType> Committable for Leaf2<TYPES> {
     fn commit(&self) -> committable::Commitment<Self> {
+        TYPES::CommitScheme::commit(self)
+    }
+}
+
+impl<TYPES: NodeType> SchemeCommittable for Leaf2<TYPES> {
+    const TAG: &'static str = "leaf commitment";
+
+    fn default_commitment(&self) -> Commitment<Self> {
         if self.drb_seed == [0; 32] && self.drb_result == [0; 32] {
             RawCommitmentBuilder::new("leaf commitment")
                 .u64_field("view number", *self.view_number)
@@ -717,6 +832,25 @@ impl<TYPES: NodeType> Committable for Leaf2<TYPES> {
                 .finalize()
         }
     }
+
+    fn commitment_preimage(&self) -> Vec<u8> {
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&self.view_number.to_le_bytes());
+        preimage.extend_from_slice(self.parent_commitment.as_ref());
+        preimage.extend_from_slice(self.block_header.commit().as_ref());
+        preimage.extend_from_slice(self.justify_qc.commit().as_ref());
+        match &self.upgrade_certificate {
+            Some(certificate) => {
+                preimage.push(1);
+                preimage.extend_from_slice(certificate.commit().as_ref());
+            }
+            None => preimage.push(0),
+        }
+        preimage.extend_from_slice(&self.drb_seed);
+        preimage.extend_from_slice(&self.drb_result);
+
+        preimage
+    }
 }
 
 impl<TYPES: NodeType> Leaf<TYPES> {
@@ -807,6 +941,7 @@ impl<TYPES: NodeType> QuorumCertificate<TYPES> {
         let upgrade_lock = UpgradeLock::<TYPES, V>::new();
 
         let genesis_view = <TYPES::View as ConsensusTime>::genesis();
+        let genesis_epoch = <TYPES::Epoch as ConsensusTime>::genesis();
 
         let data = QuorumData {
             leaf_commit: Leaf::genesis(validated_state, instance_state)
@@ -815,9 +950,13 @@ impl<TYPES: NodeType> QuorumCertificate<TYPES> {
                 .await,
         };
 
-        let versioned_data =
-            VersionedVoteData::<_, _, V>::new_infallible(data.clone(), genesis_view, &upgrade_lock)
-                .await;
+        let versioned_data = VersionedVoteData::<_, _, V>::new_infallible(
+            data.clone(),
+            genesis_view,
+            genesis_epoch,
+            &upgrade_lock,
+        )
+        .await;
 
         let bytes: [u8; 32] = versioned_data.commit().into();
 
@@ -825,6 +964,7 @@ impl<TYPES: NodeType> QuorumCertificate<TYPES> {
             data,
             Commitment::from_raw(bytes),
             genesis_view,
+            genesis_epoch,
             None,
             PhantomData,
         )
@@ -867,6 +1007,7 @@ impl<TYPES: NodeType> Leaf<TYPES> {
             null_quorum_data.clone(),
             null_quorum_data.commit(),
             <TYPES::View as ConsensusTime>::genesis(),
+            <TYPES::Epoch as ConsensusTime>::genesis(),
             None,
             PhantomData,
         );
@@ -9,6 +9,8 @@
 //! This module provides [`HotShotError`], which is an enum representing possible faults that can
 //! occur while interacting with this crate.
 
+use std::time::Duration;
+
 use committable::Commitment;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -43,6 +45,117 @@ pub enum HotShotError<TYPES: NodeType> {
         /// The state that the round was in when it timed out
         state: RoundTimedoutState,
     },
+
+    /// The config violates invariants between its fields
+    #[error("Invalid config: {0:?}")]
+    InvalidConfig(Vec<ConfigDiagnostic>),
+}
+
+/// An invariant between the fields of a `HotShotConfig` which the config violates
+#[derive(Debug, Clone, PartialEq, Eq, Error, Serialize, Deserialize)]
+pub enum ConfigDiagnostic {
+    /// The number of known nodes does not match the configured number of nodes
+    #[error(
+        "num_nodes_with_stake is {num_nodes_with_stake} but {known_nodes} known nodes are \
+         listed, make known_nodes_with_stake list every staked node"
+    )]
+    NodeCountMismatch {
+        /// The configured number of nodes
+        num_nodes_with_stake: usize,
+        /// The number of nodes in `known_nodes_with_stake`
+        known_nodes: usize,
+    },
+
+    /// The DA committee is larger than the whole committee
+    #[error(
+        "da_staked_committee_size is {da_staked_committee_size} but there are only \
+         {num_nodes_with_stake} nodes, reduce the DA committee size"
+    )]
+    DaCommitteeTooLarge {
+        /// The configured DA committee size
+        da_staked_committee_size: usize,
+        /// The configured number of nodes
+        num_nodes_with_stake: usize,
+    },
+
+    /// The number of known DA nodes does not match the configured DA committee size
+    #[error(
+        "da_staked_committee_size is {da_staked_committee_size} but {known_da_nodes} known DA \
+         nodes are listed, make known_da_nodes list every DA node"
+    )]
+    DaNodeCountMismatch {
+        /// The configured DA committee size
+        da_staked_committee_size: usize,
+        /// The number of nodes in `known_da_nodes`
+        known_da_nodes: usize,
+    },
+
+    /// A key is listed more than once among the known nodes
+    #[error("known_nodes_with_stake lists the key at index {index} more than once, remove it")]
+    DuplicateKnownNode {
+        /// Index of the repeated entry
+        index: usize,
+    },
+
+    /// A key is listed more than once among the known DA nodes
+    #[error("known_da_nodes lists the key at index {index} more than once, remove it")]
+    DuplicateDaNode {
+        /// Index of the repeated entry
+        index: usize,
+    },
+
+    /// The known nodes hold no stake, so no quorum certificate can ever form
+    #[error("known_nodes_with_stake holds no stake, give at least one node a non-zero stake")]
+    NoQuorumStake,
+
+    /// The known DA nodes hold no stake, so no DA certificate can ever form
+    #[error("known_da_nodes holds no stake, give at least one DA node a non-zero stake")]
+    NoDaStake,
+
+    /// The orchestrator start threshold is not a fraction of at most one
+    #[error(
+        "start_threshold is {numerator}/{denominator}, use a non-zero denominator no smaller \
+         than the numerator"
+    )]
+    InvalidStartThreshold {
+        /// The numerator of the start threshold
+        numerator: u64,
+        /// The denominator of the start threshold
+        denominator: u64,
+    },
+
+    /// More fixed GPU VID leaders are configured than there are nodes
+    #[error(
+        "fixed_leader_for_gpuvid is {fixed_leader_for_gpuvid} but there are only \
+         {num_nodes_with_stake} nodes, reduce the number of fixed leaders"
+    )]
+    TooManyFixedLeaders {
+        /// The configured number of fixed leaders
+        fixed_leader_for_gpuvid: usize,
+        /// The configured number of nodes
+        num_nodes_with_stake: usize,
+    },
+
+    /// The next view timeout is zero, so every view would time out immediately
+    #[error("next_view_timeout is zero, set it to a positive number of milliseconds")]
+    ZeroNextViewTimeout,
+
+    /// The quorum formation timeout is longer than the view timeout, so it never applies
+    #[error(
+        "quorum_formation_timeout is {quorum_formation_timeout:?} but next_view_timeout is \
+         {next_view_timeout}ms, use a quorum formation timeout no longer than the view timeout"
+    )]
+    QuorumTimeoutExceedsViewTimeout {
+        /// The configured quorum formation timeout
+        quorum_formation_timeout: Duration,
+        /// The configured view timeout, in milliseconds
+        next_view_timeout: u64,
+    },
+
+    /// The VID fault fraction is outside `[0, 1)`, so no number of shares is guaranteed to
+    /// recover a payload
+    #[error("vid_fault_fraction must be at least 0 and less than 1")]
+    InvalidVidFaultFraction,
 }
 
 /// Contains information about what the state of the hotshot-consensus was when a round timed out
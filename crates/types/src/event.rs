@@ -6,7 +6,7 @@
 
 //! Events that a `HotShot` instance can emit
 
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 
@@ -15,7 +15,8 @@ use crate::{
     error::HotShotError,
     message::Proposal,
     simple_certificate::QuorumCertificate2,
-    traits::{node_implementation::NodeType, ValidatedState},
+    traits::{block_contents::BlockHeader, node_implementation::NodeType, ValidatedState},
+    vid::VidCommitment,
 };
 /// A status event emitted by a `HotShot` instance
 ///
@@ -64,6 +65,38 @@ impl<TYPES: NodeType> LeafInfo<TYPES> {
 /// The chain of decided leaves with its corresponding state and VID info.
 pub type LeafChain<TYPES> = Vec<LeafInfo<TYPES>>;
 
+/// A newly decided leaf and the commitment to its block payload.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "TYPES: NodeType"))]
+pub struct DecidedLeafSummary<TYPES: NodeType> {
+    /// Decided leaf.
+    pub leaf: Leaf2<TYPES>,
+    /// Commitment to the leaf's block payload.
+    pub payload_commitment: VidCommitment,
+}
+
+impl<TYPES: NodeType> DecidedLeafSummary<TYPES> {
+    /// Summarize the leaves of `leaf_chain` decided after `last_decided_view`, oldest first.
+    ///
+    /// `leaf_chain` is in the order of [`EventType::Decide`], newest first. Leaves at or below
+    /// `last_decided_view` were covered by an earlier decide and are skipped.
+    #[must_use]
+    pub fn newly_decided(
+        leaf_chain: &LeafChain<TYPES>,
+        last_decided_view: TYPES::View,
+    ) -> Vec<Self> {
+        leaf_chain
+            .iter()
+            .rev()
+            .filter(|info| info.leaf.view_number() > last_decided_view)
+            .map(|info| Self {
+                leaf: info.leaf.clone(),
+                payload_commitment: info.leaf.block_header().payload_commitment(),
+            })
+            .collect()
+    }
+}
+
 /// Utilities for converting between HotShotError and a string.
 pub mod error_adaptor {
     use serde::{de::Deserializer, ser::Serializer};
@@ -126,6 +159,15 @@ pub enum EventType<TYPES: NodeType> {
         /// Optional information of the number of transactions in the block, for logging purposes.
         block_size: Option<u64>,
     },
+    /// Summary of a decision, emitted once per decision alongside `Decide`
+    DecideSummary {
+        /// Every leaf newly finalized by this decision, in increasing view number order.
+        ///
+        /// No leaf appears in more than one summary.
+        leaf_chain: Vec<DecidedLeafSummary<TYPES>>,
+        /// The QC signing the most recent leaf in `leaf_chain`.
+        qc: Arc<QuorumCertificate2<TYPES>>,
+    },
     /// A replica task was canceled by a timeout interrupt
     ReplicaViewTimeout {
         /// The view that timed out
@@ -172,6 +214,16 @@ pub enum EventType<TYPES: NodeType> {
         sender: TYPES::SignatureKey,
     },
 
+    /// The committee changed on entering a new epoch
+    CommitteeChanged {
+        /// The epoch entered
+        epoch: TYPES::Epoch,
+        /// Nodes which joined the committee
+        added: BTreeSet<TYPES::SignatureKey>,
+        /// Nodes which left the committee
+        removed: BTreeSet<TYPES::SignatureKey>,
+    },
+
     /// A message destined for external listeners was received
     ExternalMessageReceived {
         /// Public Key of the message sender
@@ -10,8 +10,17 @@ use url::Url;
 use vec1::Vec1;
 
 use crate::{
-    constants::REQUEST_DATA_DELAY, traits::signature_key::SignatureKey,
-    upgrade_config::UpgradeConfig, HotShotConfig, PeerConfig, ValidatorConfig,
+    consensus::{EarlyVotePolicy, EmptyBlockPolicy, ParticipationMode},
+    constants::{
+        BATCH_JUSTIFY_QCS, COMMITMENT_CACHE_CAPACITY, DA_STORAGE_RETRIES, DA_STORAGE_RETRY_BACKOFF,
+        DA_VOTE_TIMEOUT, LATE_VOTE_GRACE, MAX_CLOCK_SKEW, MAX_DA_PAYLOAD_BYTES, MAX_EPOCH_SKIP,
+        MAX_FUTURE_BUFFER, MAX_MESSAGE_AGE_VIEWS, MAX_UNDECIDED_VIEWS, NETWORK_ID,
+        REQUEST_DATA_DELAY, RESTART_STALLED_TASKS, SIGNATURE_CACHE_CAPACITY, TARGET_BLOCK_BYTES,
+        TASK_STALL_TIMEOUT, VID_FAULT_FRACTION, VOTE_TRANSITION_VIEWS, WARMUP_VIEWS,
+    },
+    traits::{signature_key::SignatureKey, storage::CheckpointPolicy},
+    upgrade_config::UpgradeConfig,
+    HotShotConfig, PeerConfig, ValidatorConfig,
 };
 
 /// Default builder URL, used as placeholder
@@ -19,6 +28,101 @@ fn default_builder_urls() -> Vec1<Url> {
     vec1::vec1![Url::parse("http://0.0.0.0:3311").unwrap()]
 }
 
+/// Default number of DA storage retries
+fn default_da_storage_retries() -> usize {
+    DA_STORAGE_RETRIES
+}
+
+/// Default maximum epoch skip
+fn default_max_epoch_skip() -> u64 {
+    MAX_EPOCH_SKIP
+}
+
+/// Default target size of the transactions in a proposed block
+fn default_target_block_bytes() -> u64 {
+    TARGET_BLOCK_BYTES
+}
+
+/// Default task stall timeout
+fn default_task_stall_timeout() -> Duration {
+    TASK_STALL_TIMEOUT
+}
+
+/// Default for restarting stalled tasks
+fn default_restart_stalled_tasks() -> bool {
+    RESTART_STALLED_TASKS
+}
+
+/// Default number of warm-up views
+fn default_warmup_views() -> u64 {
+    WARMUP_VIEWS
+}
+
+/// Default network identifier
+fn default_network_id() -> u64 {
+    NETWORK_ID
+}
+
+/// Default for how many views old a consensus message may be before it is dropped on arrival
+fn default_max_message_age_views() -> u64 {
+    MAX_MESSAGE_AGE_VIEWS
+}
+
+/// Default for how many views ahead DA proposals are buffered for
+fn default_max_future_buffer() -> u64 {
+    MAX_FUTURE_BUFFER
+}
+
+/// Default late vote grace period
+fn default_late_vote_grace() -> Duration {
+    LATE_VOTE_GRACE
+}
+
+/// Default fraction of the storage nodes VID assumes may be faulty
+fn default_vid_fault_fraction() -> f64 {
+    VID_FAULT_FRACTION
+}
+
+/// Default for `max_undecided_views`
+fn default_max_undecided_views() -> u64 {
+    MAX_UNDECIDED_VIEWS
+}
+
+/// Default for whether proposals sharing a justify QC reference it by commitment after the first
+fn default_batch_justify_qcs() -> bool {
+    BATCH_JUSTIFY_QCS
+}
+
+/// Default number of views in which votes in the other version's format are accepted
+fn default_vote_transition_views() -> u64 {
+    VOTE_TRANSITION_VIEWS
+}
+
+/// Default largest DA proposal payload, in bytes
+fn default_max_da_payload_bytes() -> usize {
+    MAX_DA_PAYLOAD_BYTES
+}
+
+/// Default DA vote collection timeout
+fn default_da_vote_timeout() -> Duration {
+    DA_VOTE_TIMEOUT
+}
+
+/// Default tolerance for proposal timestamps
+fn default_max_clock_skew() -> Duration {
+    MAX_CLOCK_SKEW
+}
+
+/// Default number of cached vote signature checks
+fn default_signature_cache_capacity() -> usize {
+    SIGNATURE_CACHE_CAPACITY
+}
+
+/// Default number of cached commitments to vote data of each type
+fn default_commitment_cache_capacity() -> usize {
+    COMMITMENT_CACHE_CAPACITY
+}
+
 /// Holds configuration for a `HotShot`
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(bound(deserialize = ""))]
@@ -55,6 +159,83 @@ pub struct HotShotConfigFile<KEY: SignatureKey> {
     pub upgrade: UpgradeConfig,
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+    /// Number of times to retry appending a DA proposal to storage
+    #[serde(default = "default_da_storage_retries")]
+    pub da_storage_retries: usize,
+    /// Base backoff between DA storage retries
+    #[serde(default)]
+    pub da_storage_retry_backoff: Option<Duration>,
+    /// Maximum number of epochs a view change may advance by at once; larger jumps are clamped
+    #[serde(default = "default_max_epoch_skip")]
+    pub max_epoch_skip: u64,
+    /// Target size in bytes of a proposed block; transactions beyond it are left for a later view
+    #[serde(default = "default_target_block_bytes")]
+    pub target_block_bytes: u64,
+    /// How often the undecided consensus state is checkpointed to storage
+    #[serde(default)]
+    pub checkpoint_policy: CheckpointPolicy,
+    /// How long a task may handle one event while others are queued before it is reported stalled
+    #[serde(default = "default_task_stall_timeout")]
+    pub task_stall_timeout: Duration,
+    /// Whether the task watchdog abandons the current event of a stalled task
+    #[serde(default = "default_restart_stalled_tasks")]
+    pub restart_stalled_tasks: bool,
+    /// Number of views a node joining behind the network observes before it starts voting
+    #[serde(default = "default_warmup_views")]
+    pub warmup_views: u64,
+    /// Identifier of the network, checked on every message before it is decoded unless it is 0
+    #[serde(default = "default_network_id")]
+    pub network_id: u64,
+    /// Consensus messages more than this many views old are dropped on arrival from the network
+    #[serde(default = "default_max_message_age_views")]
+    pub max_message_age_views: u64,
+    /// Furthest number of views beyond the next one that DA proposals are buffered for
+    #[serde(default = "default_max_future_buffer")]
+    pub max_future_buffer: u64,
+    /// How long votes for a view are still recorded for participation after its certificate formed
+    #[serde(default = "default_late_vote_grace")]
+    pub late_vote_grace: Duration,
+    /// How long the leader waits for a QC to form once votes for a view arrive, if bounded
+    /// separately from the view timeout
+    #[serde(default)]
+    pub quorum_formation_timeout: Option<Duration>,
+    /// What the DA leader does with a vote received before the proposal for its view
+    #[serde(default)]
+    pub early_vote_policy: EarlyVotePolicy,
+    /// What the leader does when it has no transactions to propose for its view
+    #[serde(default)]
+    pub empty_block_policy: EmptyBlockPolicy,
+    /// Whether this node votes and proposes, or only follows consensus as a read replica
+    #[serde(default)]
+    pub participation_mode: ParticipationMode,
+    /// Fraction of the storage nodes VID assumes may be faulty, which sets the erasure-coding rate
+    #[serde(default = "default_vid_fault_fraction")]
+    pub vid_fault_fraction: f64,
+    /// Most undecided views up to the high QC before we pause proposing and voting
+    #[serde(default = "default_max_undecided_views")]
+    pub max_undecided_views: u64,
+    /// Whether proposals reference a justify QC sent with an earlier proposal by commitment
+    #[serde(default = "default_batch_justify_qcs")]
+    pub batch_justify_qcs: bool,
+    /// Number of views on either side of an upgrade in which votes in the format of the other
+    /// version are still accepted
+    #[serde(default = "default_vote_transition_views")]
+    pub vote_transition_views: u64,
+    /// Largest payload, in bytes, a DA proposal may carry before it is rejected without hashing
+    #[serde(default = "default_max_da_payload_bytes")]
+    pub max_da_payload_bytes: usize,
+    /// How long the DA leader waits for its certificate to form after votes begin to arrive
+    #[serde(default = "default_da_vote_timeout")]
+    pub da_vote_timeout: Duration,
+    /// How far the timestamp of a proposal may be from local time before it is flagged
+    #[serde(default = "default_max_clock_skew")]
+    pub max_clock_skew: Duration,
+    /// Number of vote signature checks whose results are cached, `0` disables the cache
+    #[serde(default = "default_signature_cache_capacity")]
+    pub signature_cache_capacity: usize,
+    /// Number of commitments to vote data of each type which are cached, `0` disables the cache
+    #[serde(default = "default_commitment_cache_capacity")]
+    pub commitment_cache_capacity: usize,
 }
 
 impl<KEY: SignatureKey> From<HotShotConfigFile<KEY>> for HotShotConfig<KEY> {
@@ -83,6 +264,33 @@ impl<KEY: SignatureKey> From<HotShotConfigFile<KEY>> for HotShotConfig<KEY> {
             start_voting_time: val.upgrade.start_voting_time,
             stop_voting_time: val.upgrade.stop_voting_time,
             epoch_height: val.epoch_height,
+            da_storage_retries: val.da_storage_retries,
+            da_storage_retry_backoff: val
+                .da_storage_retry_backoff
+                .unwrap_or(Duration::from_millis(DA_STORAGE_RETRY_BACKOFF)),
+            max_epoch_skip: val.max_epoch_skip,
+            target_block_bytes: val.target_block_bytes,
+            checkpoint_policy: val.checkpoint_policy,
+            task_stall_timeout: val.task_stall_timeout,
+            restart_stalled_tasks: val.restart_stalled_tasks,
+            warmup_views: val.warmup_views,
+            network_id: val.network_id,
+            max_message_age_views: val.max_message_age_views,
+            max_future_buffer: val.max_future_buffer,
+            late_vote_grace: val.late_vote_grace,
+            quorum_formation_timeout: val.quorum_formation_timeout,
+            early_vote_policy: val.early_vote_policy,
+            empty_block_policy: val.empty_block_policy,
+            participation_mode: val.participation_mode,
+            vid_fault_fraction: val.vid_fault_fraction,
+            max_undecided_views: val.max_undecided_views,
+            batch_justify_qcs: val.batch_justify_qcs,
+            vote_transition_views: val.vote_transition_views,
+            max_da_payload_bytes: val.max_da_payload_bytes,
+            da_vote_timeout: val.da_vote_timeout,
+            max_clock_skew: val.max_clock_skew,
+            signature_cache_capacity: val.signature_cache_capacity,
+            commitment_cache_capacity: val.commitment_cache_capacity,
         }
     }
 }
@@ -128,6 +336,31 @@ impl<KEY: SignatureKey> HotShotConfigFile<KEY> {
             builder_urls: default_builder_urls(),
             upgrade: UpgradeConfig::default(),
             epoch_height: 0,
+            da_storage_retries: DA_STORAGE_RETRIES,
+            da_storage_retry_backoff: Some(Duration::from_millis(DA_STORAGE_RETRY_BACKOFF)),
+            max_epoch_skip: MAX_EPOCH_SKIP,
+            target_block_bytes: TARGET_BLOCK_BYTES,
+            checkpoint_policy: CheckpointPolicy::default(),
+            task_stall_timeout: TASK_STALL_TIMEOUT,
+            restart_stalled_tasks: RESTART_STALLED_TASKS,
+            warmup_views: WARMUP_VIEWS,
+            network_id: NETWORK_ID,
+            max_message_age_views: MAX_MESSAGE_AGE_VIEWS,
+            max_future_buffer: MAX_FUTURE_BUFFER,
+            late_vote_grace: LATE_VOTE_GRACE,
+            quorum_formation_timeout: None,
+            early_vote_policy: EarlyVotePolicy::default(),
+            empty_block_policy: EmptyBlockPolicy::default(),
+            participation_mode: ParticipationMode::default(),
+            vid_fault_fraction: VID_FAULT_FRACTION,
+            max_undecided_views: MAX_UNDECIDED_VIEWS,
+            batch_justify_qcs: BATCH_JUSTIFY_QCS,
+            vote_transition_views: VOTE_TRANSITION_VIEWS,
+            max_da_payload_bytes: MAX_DA_PAYLOAD_BYTES,
+            da_vote_timeout: DA_VOTE_TIMEOUT,
+            max_clock_skew: MAX_CLOCK_SKEW,
+            signature_cache_capacity: SIGNATURE_CACHE_CAPACITY,
+            commitment_cache_capacity: COMMITMENT_CACHE_CAPACITY,
         }
     }
 }
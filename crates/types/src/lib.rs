@@ -5,18 +5,23 @@
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
 //! Types and Traits for the `HotShot` consensus module
-use std::{fmt::Debug, future::Future, num::NonZeroUsize, pin::Pin, time::Duration};
+use std::{
+    collections::HashSet, fmt::Debug, future::Future, num::NonZeroUsize, pin::Pin, time::Duration,
+};
 
 use bincode::Options;
 use displaydoc::Display;
 use light_client::StateVerKey;
+use primitive_types::U256;
 use tracing::error;
-use traits::signature_key::SignatureKey;
+use traits::signature_key::{SignatureKey, StakeTableEntryType};
 use url::Url;
 use vec1::Vec1;
 
-use crate::utils::bincode_opts;
+use crate::{error::ConfigDiagnostic, utils::bincode_opts};
 pub mod bundle;
+pub mod commit_scheme;
+pub mod commitment_cache;
 pub mod consensus;
 pub mod constants;
 pub mod data;
@@ -33,6 +38,7 @@ pub mod message;
 pub mod network;
 pub mod qc;
 pub mod request_response;
+pub mod signature_cache;
 pub mod signature_key;
 pub mod simple_certificate;
 pub mod simple_vote;
@@ -46,6 +52,7 @@ pub mod utils;
 pub mod validator_config;
 pub mod vid;
 pub mod vote;
+pub mod vote_metrics;
 
 /// Pinned future that is Send and Sync
 pub type BoxSyncFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + Sync + 'a>>;
@@ -208,6 +215,59 @@ pub struct HotShotConfig<KEY: SignatureKey> {
     pub stop_voting_time: u64,
     /// Number of blocks in an epoch, zero means there are no epochs
     pub epoch_height: u64,
+    /// Number of times to retry appending a DA proposal to storage before giving up on the view
+    pub da_storage_retries: usize,
+    /// Base backoff between DA storage retries, doubled after each failed attempt
+    pub da_storage_retry_backoff: Duration,
+    /// Maximum number of epochs a view change may advance by at once; larger jumps are clamped
+    pub max_epoch_skip: u64,
+    /// Target size in bytes of a proposed block; transactions beyond it are left for a later view
+    pub target_block_bytes: u64,
+    /// How often the undecided consensus state is checkpointed to storage
+    pub checkpoint_policy: traits::storage::CheckpointPolicy,
+    /// How long a task may handle one event while others are queued before it is reported stalled
+    pub task_stall_timeout: Duration,
+    /// Whether the task watchdog abandons the current event of a stalled task
+    pub restart_stalled_tasks: bool,
+    /// Number of views a node joining behind the network observes before it starts voting
+    pub warmup_views: u64,
+    /// Identifier of the network, checked on every message before it is decoded unless it is 0
+    pub network_id: u64,
+    /// Consensus messages more than this many views old are dropped on arrival from the network
+    pub max_message_age_views: u64,
+    /// Furthest number of views beyond the next one that DA proposals are buffered for
+    pub max_future_buffer: u64,
+    /// How long votes for a view are still recorded for participation after its certificate formed
+    pub late_vote_grace: Duration,
+    /// How long the leader waits for a QC to form once votes for a view arrive, if bounded
+    /// separately from the view timeout
+    pub quorum_formation_timeout: Option<Duration>,
+    /// What the DA leader does with a vote received before the proposal for its view
+    pub early_vote_policy: consensus::EarlyVotePolicy,
+    /// What the leader does when it has no transactions to propose for its view
+    pub empty_block_policy: consensus::EmptyBlockPolicy,
+    /// Fraction of the storage nodes VID assumes may be faulty, which sets the erasure-coding rate
+    pub vid_fault_fraction: f64,
+    /// Most undecided views up to the high QC before we pause proposing and voting
+    pub max_undecided_views: u64,
+    /// Whether proposals reference a justify QC sent with an earlier proposal by commitment
+    pub batch_justify_qcs: bool,
+    /// Number of views on either side of an upgrade in which votes in the format of the other
+    /// version are still accepted
+    pub vote_transition_views: u64,
+    /// Largest payload, in bytes, a DA proposal may carry before it is rejected without hashing
+    pub max_da_payload_bytes: usize,
+    /// How long the DA leader waits for its certificate to form after votes begin to arrive
+    pub da_vote_timeout: Duration,
+    /// How far the timestamp of a proposal may be from local time before it is flagged. Flagged
+    /// proposals are still processed on their view number.
+    pub max_clock_skew: Duration,
+    /// Number of vote signature checks whose results are cached, `0` disables the cache
+    pub signature_cache_capacity: usize,
+    /// Number of commitments to vote data of each type which are cached, `0` disables the cache
+    pub commitment_cache_capacity: usize,
+    /// Whether this node votes and proposes, or only follows consensus as a read replica
+    pub participation_mode: consensus::ParticipationMode,
 }
 
 impl<KEY: SignatureKey> HotShotConfig<KEY> {
@@ -222,4 +282,101 @@ impl<KEY: SignatureKey> HotShotConfig<KEY> {
         self.start_voting_time = 0;
         self.stop_voting_time = u64::MAX;
     }
+
+    /// Check the invariants between the fields of this config, reporting every violation at once.
+    ///
+    /// # Errors
+    /// Returns a diagnostic for each invariant the config violates
+    pub fn validate(&self) -> Result<(), Vec<ConfigDiagnostic>> {
+        let mut diagnostics = Vec::new();
+        let num_nodes_with_stake = self.num_nodes_with_stake.get();
+
+        if self.known_nodes_with_stake.len() != num_nodes_with_stake {
+            diagnostics.push(ConfigDiagnostic::NodeCountMismatch {
+                num_nodes_with_stake,
+                known_nodes: self.known_nodes_with_stake.len(),
+            });
+        }
+        if self.da_staked_committee_size > num_nodes_with_stake {
+            diagnostics.push(ConfigDiagnostic::DaCommitteeTooLarge {
+                da_staked_committee_size: self.da_staked_committee_size,
+                num_nodes_with_stake,
+            });
+        }
+        if self.known_da_nodes.len() != self.da_staked_committee_size {
+            diagnostics.push(ConfigDiagnostic::DaNodeCountMismatch {
+                da_staked_committee_size: self.da_staked_committee_size,
+                known_da_nodes: self.known_da_nodes.len(),
+            });
+        }
+
+        diagnostics.extend(
+            duplicate_indices(&self.known_nodes_with_stake)
+                .map(|index| ConfigDiagnostic::DuplicateKnownNode { index }),
+        );
+        diagnostics.extend(
+            duplicate_indices(&self.known_da_nodes)
+                .map(|index| ConfigDiagnostic::DuplicateDaNode { index }),
+        );
+
+        if total_stake(&self.known_nodes_with_stake).is_zero() {
+            diagnostics.push(ConfigDiagnostic::NoQuorumStake);
+        }
+        if total_stake(&self.known_da_nodes).is_zero() {
+            diagnostics.push(ConfigDiagnostic::NoDaStake);
+        }
+
+        let (numerator, denominator) = self.start_threshold;
+        if denominator == 0 || numerator > denominator {
+            diagnostics.push(ConfigDiagnostic::InvalidStartThreshold {
+                numerator,
+                denominator,
+            });
+        }
+        if self.fixed_leader_for_gpuvid > num_nodes_with_stake {
+            diagnostics.push(ConfigDiagnostic::TooManyFixedLeaders {
+                fixed_leader_for_gpuvid: self.fixed_leader_for_gpuvid,
+                num_nodes_with_stake,
+            });
+        }
+        if self.next_view_timeout == 0 {
+            diagnostics.push(ConfigDiagnostic::ZeroNextViewTimeout);
+        }
+        if let Some(quorum_formation_timeout) = self.quorum_formation_timeout {
+            if quorum_formation_timeout > Duration::from_millis(self.next_view_timeout) {
+                diagnostics.push(ConfigDiagnostic::QuorumTimeoutExceedsViewTimeout {
+                    quorum_formation_timeout,
+                    next_view_timeout: self.next_view_timeout,
+                });
+            }
+        }
+        if !(0.0..1.0).contains(&self.vid_fault_fraction) {
+            diagnostics.push(ConfigDiagnostic::InvalidVidFaultFraction);
+        }
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(diagnostics)
+        }
+    }
+}
+
+/// Indices of the peers whose key is listed earlier in `peers` as well
+fn duplicate_indices<KEY: SignatureKey>(
+    peers: &[PeerConfig<KEY>],
+) -> impl Iterator<Item = usize> + '_ {
+    let mut seen = HashSet::new();
+    peers
+        .iter()
+        .enumerate()
+        .filter(move |(_, peer)| !seen.insert(peer.stake_table_entry.public_key()))
+        .map(|(index, _)| index)
+}
+
+/// The total stake held by `peers`
+fn total_stake<KEY: SignatureKey>(peers: &[PeerConfig<KEY>]) -> U256 {
+    peers.iter().fold(U256::zero(), |total, peer| {
+        total + peer.stake_table_entry.stake()
+    })
 }
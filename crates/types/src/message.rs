@@ -12,6 +12,7 @@
 use std::{
     fmt::{self, Debug},
     marker::PhantomData,
+    num::NonZeroUsize,
     sync::Arc,
 };
 
@@ -25,10 +26,14 @@ use vbs::{
 };
 
 use crate::{
+    commitment_cache::CommitmentCache,
+    constants::VOTE_TRANSITION_VIEWS,
     data::{
-        DaProposal, Leaf, Leaf2, QuorumProposal, QuorumProposal2, UpgradeProposal, VidDisperseShare,
+        DaProposal, Leaf, Leaf2, QuorumProposal, QuorumProposal2, QuorumProposalWithQcRef,
+        UpgradeProposal, VidDisperseShare,
     },
     request_response::ProposalRequestPayload,
+    signature_cache::SignatureCache,
     simple_certificate::{
         DaCertificate, QuorumCertificate2, UpgradeCertificate, ViewSyncCommitCertificate2,
         ViewSyncFinalizeCertificate2, ViewSyncPreCommitCertificate2,
@@ -38,7 +43,7 @@ use crate::{
         ViewSyncPreCommitVote,
     },
     traits::{
-        election::Membership,
+        election::{LeaderProof, Membership},
         network::{DataRequest, ResponseMessage, ViewMessage},
         node_implementation::{ConsensusTime, NodeType, Versions},
         signature_key::SignatureKey,
@@ -211,6 +216,9 @@ pub enum GeneralConsensusMessage<TYPES: NodeType> {
 
     /// Message for the next leader containing our highest QC
     HighQc(QuorumCertificate2<TYPES>),
+
+    /// Message with a quorum proposal whose justify QC was sent with an earlier proposal
+    ProposalWithQcRef(Proposal<TYPES, QuorumProposalWithQcRef<TYPES>>),
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Hash, Eq)]
@@ -243,7 +251,75 @@ pub enum SequencingMessage<TYPES: NodeType> {
     Da(DaConsensusMessage<TYPES>),
 }
 
+/// Coarse classification of a [`SequencingMessage`], used to route it to the right queue
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum MessageClass {
+    /// A quorum, DA or upgrade proposal, including VID shares and proposal responses
+    Proposal,
+    /// A vote of any kind
+    Vote,
+    /// A certificate formed from votes
+    Certificate,
+    /// A message which only asks the receiving node to do something, e.g. a proposal request
+    InternalTrigger,
+}
+
 impl<TYPES: NodeType> SequencingMessage<TYPES> {
+    /// Get the [`MessageClass`] of this message
+    #[must_use]
+    pub fn class(&self) -> MessageClass {
+        match self {
+            SequencingMessage::General(general_message) => match general_message {
+                GeneralConsensusMessage::Proposal(_)
+                | GeneralConsensusMessage::ProposalWithQcRef(_)
+                | GeneralConsensusMessage::UpgradeProposal(_)
+                | GeneralConsensusMessage::ProposalResponse(_) => MessageClass::Proposal,
+                GeneralConsensusMessage::Vote(_)
+                | GeneralConsensusMessage::ViewSyncPreCommitVote(_)
+                | GeneralConsensusMessage::ViewSyncCommitVote(_)
+                | GeneralConsensusMessage::ViewSyncFinalizeVote(_)
+                | GeneralConsensusMessage::TimeoutVote(_)
+                | GeneralConsensusMessage::UpgradeVote(_) => MessageClass::Vote,
+                GeneralConsensusMessage::ViewSyncPreCommitCertificate(_)
+                | GeneralConsensusMessage::ViewSyncCommitCertificate(_)
+                | GeneralConsensusMessage::ViewSyncFinalizeCertificate(_)
+                | GeneralConsensusMessage::HighQc(_) => MessageClass::Certificate,
+                GeneralConsensusMessage::ProposalRequested(..) => MessageClass::InternalTrigger,
+            },
+            SequencingMessage::Da(da_message) => match da_message {
+                DaConsensusMessage::DaProposal(_) | DaConsensusMessage::VidDisperseMsg(_) => {
+                    MessageClass::Proposal
+                }
+                DaConsensusMessage::DaVote(_) => MessageClass::Vote,
+                DaConsensusMessage::DaCertificate(_) => MessageClass::Certificate,
+            },
+        }
+    }
+
+    /// Whether this message is a vote of any kind
+    #[must_use]
+    pub fn is_vote(&self) -> bool {
+        self.class() == MessageClass::Vote
+    }
+
+    /// Whether this message carries a proposal
+    #[must_use]
+    pub fn is_proposal(&self) -> bool {
+        self.class() == MessageClass::Proposal
+    }
+
+    /// Whether this message only triggers work on the receiving node
+    #[must_use]
+    pub fn is_internal_trigger(&self) -> bool {
+        self.class() == MessageClass::InternalTrigger
+    }
+
+    /// Get the view and class of this message, for routing it to a per-view queue
+    #[must_use]
+    pub fn routing_key(&self) -> (TYPES::View, MessageClass) {
+        (self.view_number(), self.class())
+    }
+
     /// Get the view number this message relates to
     fn view_number(&self) -> TYPES::View {
         match &self {
@@ -254,6 +330,7 @@ impl<TYPES: NodeType> SequencingMessage<TYPES> {
                         // this should match replica upon receipt
                         p.data.view_number()
                     }
+                    GeneralConsensusMessage::ProposalWithQcRef(p) => p.data.view_number(),
                     GeneralConsensusMessage::ProposalRequested(req, _) => req.view_number,
                     GeneralConsensusMessage::ProposalResponse(proposal) => {
                         proposal.data.view_number()
@@ -321,10 +398,35 @@ pub struct Proposal<TYPES: NodeType, PROPOSAL: HasViewNumber<TYPES> + Deserializ
     pub data: PROPOSAL,
     /// The proposal must be signed by the view leader
     pub signature: <TYPES::SignatureKey as SignatureKey>::PureAssembledSignatureType,
+    /// Proof that the signer was selected as the leader of the view, see
+    /// [`Membership::leader_proof`]. Replicas check it wherever they check the proposal came from
+    /// the expected leader. VID shares a node signs itself carry [`LeaderProof::Deterministic`].
+    pub leader_proof: LeaderProof<TYPES::SignatureKey>,
     /// Phantom for TYPES
     pub _pd: PhantomData<TYPES>,
 }
 
+/// The bytes the leader signs to make `proposal` at `version`.
+///
+/// From the epochs version on, the epoch of the certificate the proposal extends is folded into
+/// the signed bytes, so a proposal signature made in one epoch does not verify in any other.
+/// Leader and replicas both read the epoch from the proposal itself, so they agree on it across
+/// epoch boundaries. Earlier versions sign the leaf commitment alone.
+#[must_use]
+pub fn quorum_proposal_signing_bytes<TYPES: NodeType, V: Versions>(
+    proposal: &QuorumProposal2<TYPES>,
+    version: Version,
+) -> Vec<u8> {
+    let mut bytes = Leaf2::from_quorum_proposal(proposal)
+        .commit()
+        .as_ref()
+        .to_vec();
+    if version >= V::Epochs::VERSION {
+        bytes.extend_from_slice(&proposal.justify_qc.epoch.to_le_bytes());
+    }
+    bytes
+}
+
 /// Convert a `Proposal` by converting the underlying proposal type
 pub fn convert_proposal<TYPES, PROPOSAL, PROPOSAL2>(
     proposal: Proposal<TYPES, PROPOSAL>,
@@ -337,6 +439,7 @@ where
     Proposal {
         data: proposal.data.into(),
         signature: proposal.signature,
+        leader_proof: proposal.leader_proof,
         _pd: proposal._pd,
     }
 }
@@ -345,9 +448,10 @@ impl<TYPES> Proposal<TYPES, QuorumProposal<TYPES>>
 where
     TYPES: NodeType,
 {
-    /// Checks that the signature of the quorum proposal is valid.
+    /// Checks that the signature of the quorum proposal is valid, and that it proves its signer
+    /// was selected as the leader.
     /// # Errors
-    /// Returns an error when the proposal signature is invalid.
+    /// Returns an error when the proposal signature or leader proof is invalid.
     pub async fn validate_signature<V: Versions>(
         &self,
         quorum_membership: &TYPES::Membership,
@@ -356,6 +460,15 @@ where
     ) -> Result<()> {
         let view_number = self.data.view_number();
         let view_leader_key = quorum_membership.leader(view_number, epoch)?;
+        ensure!(
+            quorum_membership.verify_leader_proof(
+                view_number,
+                epoch,
+                &view_leader_key,
+                &self.leader_proof
+            ),
+            "Proposal for view {view_number} does not prove its leader was selected."
+        );
         let proposed_leaf = Leaf::from_quorum_proposal(&self.data);
 
         ensure!(
@@ -374,25 +487,72 @@ impl<TYPES> Proposal<TYPES, QuorumProposal2<TYPES>>
 where
     TYPES: NodeType,
 {
-    /// Checks that the signature of the quorum proposal is valid.
+    /// Checks that the quorum proposal is signed by the leader of its view in `epoch`, see
+    /// [`quorum_proposal_signing_bytes`], and proves that leader was selected, see
+    /// [`Membership::verify_leader_proof`].
     /// # Errors
-    /// Returns an error when the proposal signature is invalid.
-    pub fn validate_signature(
+    /// Returns an error when the proposal signature is invalid, including when it was signed in
+    /// another epoch, or when the leader proof is invalid.
+    pub async fn validate_signature<V: Versions>(
         &self,
         quorum_membership: &TYPES::Membership,
         epoch: TYPES::Epoch,
+        upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> Result<()> {
         let view_number = self.data.view_number();
         let view_leader_key = quorum_membership.leader(view_number, epoch)?;
-        let proposed_leaf = Leaf2::from_quorum_proposal(&self.data);
+        ensure!(
+            quorum_membership.verify_leader_proof(
+                view_number,
+                epoch,
+                &view_leader_key,
+                &self.leader_proof
+            ),
+            "Proposal for view {view_number} does not prove its leader was selected."
+        );
+        let version = upgrade_lock.version(view_number).await?;
+        let signed_bytes = quorum_proposal_signing_bytes::<TYPES, V>(&self.data, version);
 
         ensure!(
-            view_leader_key.validate(&self.signature, proposed_leaf.commit().as_ref()),
+            view_leader_key.validate(&self.signature, &signed_bytes),
             "Proposal signature is invalid."
         );
 
         Ok(())
     }
+
+    /// Checks that the leader which made the quorum proposal still holds stake in `epoch`.
+    ///
+    /// With dynamic stake tables, a leader's stake may be removed in an epoch change before its
+    /// proposal is processed, after which it is no longer a valid proposer. Removing the stake
+    /// may also hand the view to another leader, so `sender` is taken to be the leader if it
+    /// signed the proposal, and the leader `quorum_membership` names for the view otherwise.
+    /// # Errors
+    /// Returns an error when the leader holds no stake in `epoch`.
+    pub async fn validate_leader_stake<V: Versions>(
+        &self,
+        quorum_membership: &TYPES::Membership,
+        epoch: TYPES::Epoch,
+        sender: &TYPES::SignatureKey,
+        upgrade_lock: &UpgradeLock<TYPES, V>,
+    ) -> Result<()> {
+        let view_number = self.data.view_number();
+        let version = upgrade_lock.version(view_number).await?;
+        let signed_bytes = quorum_proposal_signing_bytes::<TYPES, V>(&self.data, version);
+        let leader = if sender.validate(&self.signature, &signed_bytes) {
+            sender.clone()
+        } else {
+            quorum_membership.leader(view_number, epoch)?
+        };
+
+        ensure!(
+            quorum_membership.has_stake(&leader, epoch),
+            "Proposal for view {view_number} is from leader {leader}, which holds no stake in \
+             epoch {epoch}."
+        );
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -403,6 +563,16 @@ pub struct UpgradeLock<TYPES: NodeType, V: Versions> {
 
     /// phantom data for the `Versions` trait
     pub _pd: PhantomData<V>,
+
+    /// Number of views on either side of an upgrade in which votes in the format of the other
+    /// version are still accepted, see [`UpgradeLock::vote_versions`]
+    pub vote_transition_views: u64,
+
+    /// Results of the vote signature checks of this node, if they are cached
+    pub signature_cache: Option<Arc<SignatureCache<TYPES::SignatureKey>>>,
+
+    /// Commitments to the vote data this node signs and checks, if they are cached
+    pub commitment_cache: Option<Arc<CommitmentCache>>,
 }
 
 impl<TYPES: NodeType, V: Versions> UpgradeLock<TYPES, V> {
@@ -412,6 +582,9 @@ impl<TYPES: NodeType, V: Versions> UpgradeLock<TYPES, V> {
         Self {
             decided_upgrade_certificate: Arc::new(RwLock::new(None)),
             _pd: PhantomData::<V>,
+            vote_transition_views: VOTE_TRANSITION_VIEWS,
+            signature_cache: None,
+            commitment_cache: None,
         }
     }
 
@@ -421,9 +594,36 @@ impl<TYPES: NodeType, V: Versions> UpgradeLock<TYPES, V> {
         Self {
             decided_upgrade_certificate: Arc::new(RwLock::new(certificate.clone())),
             _pd: PhantomData::<V>,
+            vote_transition_views: VOTE_TRANSITION_VIEWS,
+            signature_cache: None,
+            commitment_cache: None,
         }
     }
 
+    /// Accept votes in the format of the other version for `views` views on either side of an
+    /// upgrade
+    #[must_use]
+    pub fn with_vote_transition_views(mut self, views: u64) -> Self {
+        self.vote_transition_views = views;
+        self
+    }
+
+    /// Cache the results of up to `capacity` vote signature checks, where `0` disables caching
+    #[must_use]
+    pub fn with_signature_cache(mut self, capacity: usize) -> Self {
+        self.signature_cache =
+            NonZeroUsize::new(capacity).map(|capacity| Arc::new(SignatureCache::new(capacity)));
+        self
+    }
+
+    /// Cache up to `capacity` commitments to vote data of each type, where `0` disables caching
+    #[must_use]
+    pub fn with_commitment_cache(mut self, capacity: usize) -> Self {
+        self.commitment_cache =
+            NonZeroUsize::new(capacity).map(|capacity| Arc::new(CommitmentCache::new(capacity)));
+        self
+    }
+
     /// Calculate the version applied in a view, based on the provided upgrade lock.
     ///
     /// # Errors
@@ -449,6 +649,35 @@ impl<TYPES: NodeType, V: Versions> UpgradeLock<TYPES, V> {
         Ok(version)
     }
 
+    /// The versions whose vote format is accepted for votes in `view`, starting with the version
+    /// applied in the view, which votes are signed in.
+    ///
+    /// From the epochs version votes bind the version they were cast under, and nodes may
+    /// disagree about the version of views near an upgrade until they have all seen it decided.
+    /// So for `vote_transition_views` views on either side of the upgrade, votes in the format of
+    /// the version on the other side are accepted as well.
+    ///
+    /// # Errors
+    /// Returns an error if we do not support the version required by the decided upgrade certificate.
+    pub async fn vote_versions(&self, view: TYPES::View) -> Result<Vec<Version>> {
+        let version = self.version(view).await?;
+        let upgrade_certificate = self.decided_upgrade_certificate.read().await;
+        let Some(cert) = upgrade_certificate.as_ref() else {
+            return Ok(vec![version]);
+        };
+
+        let first_view = *cert.data.new_version_first_view;
+        let other_version = if *view >= first_view {
+            (*view - first_view < self.vote_transition_views).then_some(cert.data.old_version)
+        } else {
+            (first_view - *view <= self.vote_transition_views).then_some(cert.data.new_version)
+        };
+
+        Ok(std::iter::once(version)
+            .chain(other_version.filter(|other| *other != version))
+            .collect())
+    }
+
     /// Calculate the version applied in a view, based on the provided upgrade lock.
     ///
     /// This function does not fail, since it does not check that the version is supported.
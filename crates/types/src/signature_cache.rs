@@ -0,0 +1,125 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+//! Caching of signature verification results, so a signature delivered more than once is only
+//! verified once.
+//!
+//! Entries are keyed on a digest of the key, the signature and the signed bytes together, so a
+//! result is only reused for exactly the inputs it was computed for, without holding on to the
+//! signed bytes themselves.
+//!
+//! Each node owns its cache through its [`UpgradeLock`](crate::message::UpgradeLock), sized by
+//! `HotShotConfig::signature_cache_capacity`.
+
+use std::{
+    marker::PhantomData,
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use bincode::Options;
+use lru::LruCache;
+use parking_lot::Mutex;
+
+use crate::{traits::signature_key::SignatureKey, utils::bincode_opts};
+
+/// Digest of the inputs of a verification: the key, the signature and the signed bytes
+type Verification = [u8; 32];
+
+/// Digest `key`, `signature` and `data` together, or `None` if they can't be serialized.
+fn verification<K: SignatureKey>(
+    key: &K,
+    signature: &K::PureAssembledSignatureType,
+    data: &[u8],
+) -> Option<Verification> {
+    // The serialized key and signature are length prefixed, so they can't run into `data`.
+    let key_and_signature = bincode_opts().serialize(&(key, signature)).ok()?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&(key_and_signature.len() as u64).to_le_bytes());
+    hasher.update(&key_and_signature);
+    hasher.update(data);
+
+    Some(*hasher.finalize().as_bytes())
+}
+
+/// An LRU cache of the results of verifying signatures under keys of type `K`
+#[derive(derive_more::Debug)]
+pub struct SignatureCache<K: SignatureKey> {
+    /// The cached results
+    #[debug(skip)]
+    entries: Mutex<LruCache<Verification, bool>>,
+    /// Number of verifications answered from the cache
+    hits: AtomicU64,
+    /// Number of verifications which had to check the signature
+    misses: AtomicU64,
+    /// Phantom for the key type the results are for
+    #[debug(skip)]
+    _pd: PhantomData<K>,
+}
+
+impl<K: SignatureKey> SignatureCache<K> {
+    /// Create an empty cache holding at most `capacity` results.
+    #[must_use]
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Whether `signature` is `key`'s signature over `data`, checking and caching it on a miss.
+    pub fn validate(
+        &self,
+        key: &K,
+        signature: &K::PureAssembledSignatureType,
+        data: &[u8],
+    ) -> bool {
+        let Some(verification) = verification(key, signature, data) else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return key.validate(signature, data);
+        };
+        if let Some(valid) = self.entries.lock().get(&verification) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return *valid;
+        }
+
+        // Verify outside of the lock, so concurrent misses are not serialized.
+        let valid = key.validate(signature, data);
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.entries.lock().put(verification, valid);
+
+        valid
+    }
+
+    /// Number of verifications answered from the cache so far.
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Number of verifications which had to check the signature so far.
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+}
+
+/// Whether `signature` is `key`'s signature over `data`, through `cache` if there is one.
+///
+/// The result is always equal to `key.validate(signature, data)`.
+pub fn validate_with_cache<K: SignatureKey>(
+    cache: Option<&SignatureCache<K>>,
+    key: &K,
+    signature: &K::PureAssembledSignatureType,
+    data: &[u8],
+) -> bool {
+    match cache {
+        Some(cache) => cache.validate(key, signature, data),
+        None => key.validate(signature, data),
+    }
+}
@@ -6,7 +6,8 @@
 
 //! Types and structs for the hotshot signature keys
 
-use ark_serialize::SerializationError;
+use ark_bn254::G2Projective;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
 use bitvec::{slice::BitSlice, vec::BitVec};
 use digest::generic_array::GenericArray;
 use jf_signature::{
@@ -16,6 +17,7 @@ use jf_signature::{
 use primitive_types::U256;
 use rand::SeedableRng;
 use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
 use tracing::instrument;
 
 use crate::{
@@ -146,6 +148,60 @@ impl SignatureKey for BLSPubKey {
     }
 }
 
+/// The aggregate of a set of BLS public keys, identifying the set compactly.
+///
+/// A signature aggregated from signatures by every key of the set, e.g. the signature of a
+/// certificate all of whose signers are in the set, verifies against this single key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct AggregatedPublicKey(BLSPubKey);
+
+impl AggregatedPublicKey {
+    /// The aggregate as a plain public key
+    #[must_use]
+    pub fn key(&self) -> BLSPubKey {
+        self.0
+    }
+
+    /// Whether `signature` is an aggregate signature on `data` by exactly the aggregated keys
+    #[must_use]
+    pub fn verify(
+        &self,
+        data: &[u8],
+        signature: &<BLSPubKey as SignatureKey>::PureAssembledSignatureType,
+    ) -> bool {
+        self.0.validate(signature, data)
+    }
+}
+
+/// Aggregate `keys` into a single key, against which signatures aggregated from all of them
+/// can be checked.
+///
+/// # Errors
+/// If `keys` is empty, or a key is not a valid curve point
+pub fn aggregate_public_keys(keys: &[BLSPubKey]) -> Result<AggregatedPublicKey, SignatureError> {
+    let Some((first, rest)) = keys.split_first() else {
+        return Err(SignatureError::ParameterError(
+            "Cannot aggregate an empty set of public keys".to_string(),
+        ));
+    };
+
+    // A BLS public key is serialized as its point on G2, which lets us add the points
+    let to_point = |key: &BLSPubKey| {
+        G2Projective::deserialize_compressed(SignatureKey::to_bytes(key).as_slice())
+            .map_err(|e| SignatureError::ParameterError(format!("Invalid public key: {e}")))
+    };
+    let aggregate = rest.iter().try_fold(to_point(first)?, |sum, key| {
+        to_point(key).map(|point| sum + point)
+    })?;
+
+    let mut bytes = Vec::new();
+    aggregate
+        .serialize_compressed(&mut bytes)
+        .and_then(|()| BLSPubKey::deserialize_compressed(bytes.as_slice()))
+        .map(AggregatedPublicKey)
+        .map_err(|e| SignatureError::ParameterError(format!("Invalid aggregate key: {e}")))
+}
+
 // Currently implement builder signature key for BLS
 // So copy pasta here, but actually Sequencer will implement the same trait for ethereum types
 /// Builder signature key
@@ -10,23 +10,24 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
     hash::Hash,
     marker::PhantomData,
-    num::NonZeroU64,
     sync::Arc,
 };
 
 use async_lock::RwLock;
 use committable::{Commitment, Committable};
+use futures::future::join_all;
 use primitive_types::U256;
 use serde::{Deserialize, Serialize};
 use utils::anytrace::*;
+use vbs::version::{StaticVersionType, Version};
 
 use crate::{
-    data::serialize_signature2,
+    data::{serialize_signature2, Leaf2},
     message::UpgradeLock,
     simple_vote::{
-        DaData, QuorumData, QuorumData2, QuorumMarker, TimeoutData, UpgradeProposalData,
-        VersionedVoteData, ViewSyncCommitData, ViewSyncFinalizeData, ViewSyncPreCommitData,
-        Voteable,
+        CustomData, DaData, QuorumData, QuorumData2, QuorumMarker, TimeoutData,
+        UpgradeProposalData, VersionedVoteData, ViewSyncCommitData, ViewSyncFinalizeData,
+        ViewSyncPreCommitData, Voteable,
     },
     traits::{
         election::Membership,
@@ -85,6 +86,12 @@ impl<TYPES: NodeType> Threshold<TYPES> for UpgradeThreshold {
 }
 
 /// A certificate which can be created by aggregating many simple votes on the commitment.
+///
+/// The serialized form carries no version tag of its own. Certificates are encoded with
+/// [`UpgradeLock::serialize`](crate::message::UpgradeLock::serialize), alone or inside a message,
+/// which prefixes the protocol version in effect for the view. On receipt,
+/// [`UpgradeLock::deserialize`](crate::message::UpgradeLock::deserialize) rejects any version
+/// other than that one before decoding the signature.
 #[derive(Serialize, Deserialize, Eq, Hash, PartialEq, Debug, Clone)]
 pub struct SimpleCertificate<TYPES: NodeType, VOTEABLE: Voteable, THRESHOLD: Threshold<TYPES>> {
     /// The data this certificate is for.  I.e the thing that was voted on to create this Certificate
@@ -93,12 +100,21 @@ pub struct SimpleCertificate<TYPES: NodeType, VOTEABLE: Voteable, THRESHOLD: Thr
     vote_commitment: Commitment<VOTEABLE>,
     /// Which view this QC relates to
     pub view_number: TYPES::View,
+    /// The epoch whose votes formed this QC, the genesis epoch for certificates stored before
+    /// they recorded one
+    #[serde(default = "genesis_epoch::<TYPES>")]
+    pub epoch: TYPES::Epoch,
     /// assembled signature for certificate aggregation
     pub signatures: Option<<TYPES::SignatureKey as SignatureKey>::QcType>,
     /// phantom data for `THRESHOLD` and `TYPES`
     pub _pd: PhantomData<(TYPES, THRESHOLD)>,
 }
 
+/// The epoch of a certificate deserialized without one
+fn genesis_epoch<TYPES: NodeType>() -> TYPES::Epoch {
+    TYPES::Epoch::genesis()
+}
+
 impl<TYPES: NodeType, VOTEABLE: Voteable, THRESHOLD: Threshold<TYPES>>
     SimpleCertificate<TYPES, VOTEABLE, THRESHOLD>
 {
@@ -107,6 +123,7 @@ impl<TYPES: NodeType, VOTEABLE: Voteable, THRESHOLD: Threshold<TYPES>>
         data: VOTEABLE,
         vote_commitment: Commitment<VOTEABLE>,
         view_number: TYPES::View,
+        epoch: TYPES::Epoch,
         signatures: Option<<TYPES::SignatureKey as SignatureKey>::QcType>,
         pd: PhantomData<(TYPES, THRESHOLD)>,
     ) -> Self {
@@ -114,12 +131,71 @@ impl<TYPES: NodeType, VOTEABLE: Voteable, THRESHOLD: Threshold<TYPES>>
             data,
             vote_commitment,
             view_number,
+            epoch,
             signatures,
             _pd: pd,
         }
     }
 }
 
+impl<TYPES: NodeType, VOTEABLE: Voteable, THRESHOLD: Threshold<TYPES>>
+    SimpleCertificate<TYPES, VOTEABLE, THRESHOLD>
+{
+    /// The commitment the votes forming this certificate signed, in the format of `version`
+    fn data_commitment_in<V: Versions>(
+        &self,
+        version: Version,
+        upgrade_lock: &UpgradeLock<TYPES, V>,
+    ) -> Commitment<VersionedVoteData<TYPES, VOTEABLE, V>> {
+        VersionedVoteData::with_version(
+            self.data.clone(),
+            self.view_number,
+            self.epoch,
+            version,
+            upgrade_lock.commitment_cache.as_deref(),
+        )
+        .commit()
+    }
+
+    /// Whether the assembled signature is over this certificate by `threshold` of `stake_table`,
+    /// for the checks shared by every kind of certificate, see [`Certificate::is_valid_cert`]
+    async fn is_signed_by<V: Versions>(
+        &self,
+        stake_table: Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>,
+        threshold: U256,
+        epoch: TYPES::Epoch,
+        upgrade_lock: &UpgradeLock<TYPES, V>,
+    ) -> bool {
+        if self.view_number == TYPES::View::genesis() {
+            return true;
+        }
+        let Some(signatures) = self.signatures.as_ref() else {
+            return false;
+        };
+        let Ok(versions) = upgrade_lock.vote_versions(self.view_number).await else {
+            return false;
+        };
+        let real_qc_pp =
+            <TYPES::SignatureKey as SignatureKey>::public_parameter(stake_table, threshold);
+        let is_signed = |commit: &Commitment<VersionedVoteData<TYPES, VOTEABLE, V>>| {
+            <TYPES::SignatureKey as SignatureKey>::check(&real_qc_pp, commit.as_ref(), signatures)
+        };
+
+        // From the epochs version the epoch is signed, and must be the one we check in
+        let mut commitments = versions
+            .into_iter()
+            .filter(|version| *version < V::Epochs::VERSION || self.epoch == epoch)
+            .map(|version| self.data_commitment_in::<V>(version, upgrade_lock));
+        let Some(commit) = commitments.next() else {
+            return false;
+        };
+
+        // Near an upgrade the certificate may be formed from votes in the other version's
+        // format, which is only checked if it commits to something else
+        is_signed(&commit) || commitments.any(|other| other != commit && is_signed(&other))
+    }
+}
+
 impl<TYPES: NodeType, VOTEABLE: Voteable + Committable, THRESHOLD: Threshold<TYPES>> Committable
     for SimpleCertificate<TYPES, VOTEABLE, THRESHOLD>
 {
@@ -148,6 +224,7 @@ impl<TYPES: NodeType, THRESHOLD: Threshold<TYPES>> Certificate<TYPES, DaData>
         data: Self::Voteable,
         sig: <TYPES::SignatureKey as SignatureKey>::QcType,
         view: TYPES::View,
+        epoch: TYPES::Epoch,
     ) -> Self {
         let vote_commitment_bytes: [u8; 32] = vote_commitment.into();
 
@@ -155,31 +232,29 @@ impl<TYPES: NodeType, THRESHOLD: Threshold<TYPES>> Certificate<TYPES, DaData>
             data,
             vote_commitment: Commitment::from_raw(vote_commitment_bytes),
             view_number: view,
+            epoch,
             signatures: Some(sig),
             _pd: PhantomData,
         }
     }
     async fn is_valid_cert<V: Versions>(
         &self,
-        stake_table: Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>,
-        threshold: NonZeroU64,
+        membership: &TYPES::Membership,
+        epoch: TYPES::Epoch,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> bool {
-        if self.view_number == TYPES::View::genesis() {
-            return true;
-        }
-        let real_qc_pp = <TYPES::SignatureKey as SignatureKey>::public_parameter(
-            stake_table,
-            U256::from(u64::from(threshold)),
+        let stake_table = <Self as Certificate<TYPES, DaData>>::effective_stake_table(
+            membership,
+            self.view_number,
+            epoch,
         );
-        let Ok(commit) = self.data_commitment(upgrade_lock).await else {
-            return false;
-        };
-        <TYPES::SignatureKey as SignatureKey>::check(
-            &real_qc_pp,
-            commit.as_ref(),
-            self.signatures.as_ref().unwrap(),
-        )
+        let threshold = <Self as Certificate<TYPES, DaData>>::effective_threshold(
+            membership,
+            self.view_number,
+            epoch,
+        );
+        self.is_signed_by(stake_table, threshold, epoch, upgrade_lock)
+            .await
     }
     /// Proxy's to `Membership.stake`
     fn stake_table_entry<MEMBERSHIP: Membership<TYPES>>(
@@ -217,11 +292,7 @@ impl<TYPES: NodeType, THRESHOLD: Threshold<TYPES>> Certificate<TYPES, DaData>
         &self,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> Result<Commitment<VersionedVoteData<TYPES, DaData, V>>> {
-        Ok(
-            VersionedVoteData::new(self.data.clone(), self.view_number, upgrade_lock)
-                .await?
-                .commit(),
-        )
+        Ok(self.data_commitment_in(upgrade_lock.version(self.view_number).await?, upgrade_lock))
     }
 }
 
@@ -236,6 +307,7 @@ impl<TYPES: NodeType, VOTEABLE: Voteable + 'static + QuorumMarker, THRESHOLD: Th
         data: Self::Voteable,
         sig: <TYPES::SignatureKey as SignatureKey>::QcType,
         view: TYPES::View,
+        epoch: TYPES::Epoch,
     ) -> Self {
         let vote_commitment_bytes: [u8; 32] = vote_commitment.into();
 
@@ -243,31 +315,29 @@ impl<TYPES: NodeType, VOTEABLE: Voteable + 'static + QuorumMarker, THRESHOLD: Th
             data,
             vote_commitment: Commitment::from_raw(vote_commitment_bytes),
             view_number: view,
+            epoch,
             signatures: Some(sig),
             _pd: PhantomData,
         }
     }
     async fn is_valid_cert<V: Versions>(
         &self,
-        stake_table: Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>,
-        threshold: NonZeroU64,
+        membership: &TYPES::Membership,
+        epoch: TYPES::Epoch,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> bool {
-        if self.view_number == TYPES::View::genesis() {
-            return true;
-        }
-        let real_qc_pp = <TYPES::SignatureKey as SignatureKey>::public_parameter(
-            stake_table,
-            U256::from(u64::from(threshold)),
+        let stake_table = <Self as Certificate<TYPES, VOTEABLE>>::effective_stake_table(
+            membership,
+            self.view_number,
+            epoch,
         );
-        let Ok(commit) = self.data_commitment(upgrade_lock).await else {
-            return false;
-        };
-        <TYPES::SignatureKey as SignatureKey>::check(
-            &real_qc_pp,
-            commit.as_ref(),
-            self.signatures.as_ref().unwrap(),
-        )
+        let threshold = <Self as Certificate<TYPES, VOTEABLE>>::effective_threshold(
+            membership,
+            self.view_number,
+            epoch,
+        );
+        self.is_signed_by(stake_table, threshold, epoch, upgrade_lock)
+            .await
     }
     fn threshold<MEMBERSHIP: Membership<TYPES>>(
         membership: &MEMBERSHIP,
@@ -284,6 +354,16 @@ impl<TYPES: NodeType, VOTEABLE: Voteable + 'static + QuorumMarker, THRESHOLD: Th
         membership.stake(pub_key, epoch)
     }
 
+    /// Proxy's to `Membership.effective_stake_at_view`
+    fn effective_stake<MEMBERSHIP: Membership<TYPES>>(
+        membership: &MEMBERSHIP,
+        pub_key: &TYPES::SignatureKey,
+        view_number: TYPES::View,
+        epoch: TYPES::Epoch,
+    ) -> U256 {
+        membership.effective_stake_at_view(pub_key, view_number, epoch)
+    }
+
     fn stake_table<MEMBERSHIP: Membership<TYPES>>(
         membership: &MEMBERSHIP,
         epoch: TYPES::Epoch,
@@ -306,11 +386,7 @@ impl<TYPES: NodeType, VOTEABLE: Voteable + 'static + QuorumMarker, THRESHOLD: Th
         &self,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> Result<Commitment<VersionedVoteData<TYPES, VOTEABLE, V>>> {
-        Ok(
-            VersionedVoteData::new(self.data.clone(), self.view_number, upgrade_lock)
-                .await?
-                .commit(),
-        )
+        Ok(self.data_commitment_in(upgrade_lock.version(self.view_number).await?, upgrade_lock))
     }
 }
 
@@ -361,12 +437,8 @@ impl<TYPES: NodeType> UpgradeCertificate<TYPES> {
     ) -> Result<()> {
         if let Some(ref cert) = upgrade_certificate {
             ensure!(
-                cert.is_valid_cert(
-                    quorum_membership.stake_table(epoch),
-                    quorum_membership.upgrade_threshold(epoch),
-                    upgrade_lock
-                )
-                .await,
+                cert.is_valid_cert(quorum_membership, epoch, upgrade_lock)
+                    .await,
                 "Invalid upgrade certificate."
             );
             Ok(())
@@ -397,6 +469,7 @@ impl<TYPES: NodeType> QuorumCertificate<TYPES> {
             data,
             vote_commitment,
             view_number: self.view_number,
+            epoch: self.epoch,
             signatures: self.signatures.clone(),
             _pd: PhantomData,
         }
@@ -404,6 +477,41 @@ impl<TYPES: NodeType> QuorumCertificate<TYPES> {
 }
 
 impl<TYPES: NodeType> QuorumCertificate2<TYPES> {
+    /// Verify a contiguous run of QCs, such as the ones received when syncing from a checkpoint,
+    /// where `certs[i]` certifies `leaves[i]`. The signatures are checked concurrently, and each
+    /// leaf after the first must extend the leaf before it.
+    ///
+    /// # Errors
+    /// Returns the index of the first certificate which is invalid, certifies a different leaf,
+    /// or does not link to its predecessor
+    pub async fn verify_batch_certificates<V: Versions>(
+        certs: &[Self],
+        leaves: &[Leaf2<TYPES>],
+        membership: &TYPES::Membership,
+        upgrade_lock: &UpgradeLock<TYPES, V>,
+    ) -> std::result::Result<(), usize> {
+        let signatures_valid = join_all(
+            certs
+                .iter()
+                .map(|cert| cert.is_valid_cert(membership, cert.epoch, upgrade_lock)),
+        )
+        .await;
+
+        let checked = certs.iter().zip(leaves).zip(signatures_valid);
+        for (index, ((cert, leaf), signature_valid)) in checked.enumerate() {
+            let links = index == 0 || leaf.parent_commitment() == leaves[index - 1].commit();
+            if !signature_valid || cert.data.leaf_commit != leaf.commit() || !links {
+                return Err(index);
+            }
+        }
+
+        if certs.len() == leaves.len() {
+            Ok(())
+        } else {
+            Err(certs.len().min(leaves.len()))
+        }
+    }
+
     /// Convert a `QuorumCertificate2` into a `QuorumCertificate`
     pub fn to_qc(self) -> QuorumCertificate<TYPES> {
         let bytes: [u8; 32] = self.data.leaf_commit.into();
@@ -418,6 +526,7 @@ impl<TYPES: NodeType> QuorumCertificate2<TYPES> {
             data,
             vote_commitment,
             view_number: self.view_number,
+            epoch: self.epoch,
             signatures: self.signatures.clone(),
             _pd: PhantomData,
         }
@@ -444,3 +553,5 @@ pub type ViewSyncFinalizeCertificate2<TYPES> =
 /// Type alias for a `UpgradeCertificate`, which is a `SimpleCertificate` of `UpgradeProposalData`
 pub type UpgradeCertificate<TYPES> =
     SimpleCertificate<TYPES, UpgradeProposalData<TYPES>, UpgradeThreshold>;
+/// Type alias for a certificate over application-defined `CustomData`
+pub type CustomCertificate<TYPES, C> = SimpleCertificate<TYPES, CustomData<C>, SuccessThreshold>;
@@ -11,9 +11,11 @@ use std::{fmt::Debug, hash::Hash, marker::PhantomData};
 use committable::{Commitment, Committable};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use utils::anytrace::*;
-use vbs::version::Version;
+use vbs::version::{StaticVersionType, Version};
 
 use crate::{
+    commit_scheme::{CommitScheme, SchemeCommittable},
+    commitment_cache::{commit_with_cache, CommitmentCache},
     data::{Leaf, Leaf2},
     message::UpgradeLock,
     traits::{
@@ -95,13 +97,82 @@ pub struct UpgradeProposalData<TYPES: NodeType + DeserializeOwned> {
     /// The first block for which the new version will be in effect.
     pub new_version_first_view: TYPES::View,
 }
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash, Eq)]
+/// Data used for a vote of a kind defined by an application built on HotShot, e.g. a checkpoint
+/// attestation.
+///
+/// The tag identifies the kind of vote and separates the commitments of each kind, so a vote of
+/// one kind can never be counted towards a certificate of another.
+#[serde(bound = "")]
+pub struct CustomData<C: Committable> {
+    /// The kind of vote
+    pub tag: String,
+    /// Commitment to the data voted on
+    pub commitment: Commitment<C>,
+}
+
+/// The committee role a vote is cast in.
+///
+/// From the epochs version on, the role is folded into every signed vote commitment, so a
+/// signature made in one role can never be accepted in the other, even if the underlying data
+/// commitments were to coincide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VoteRole {
+    /// A vote by a member of the DA committee
+    Da,
+    /// A vote by a member of the quorum
+    Quorum,
+}
+
+impl VoteRole {
+    /// The domain separator for signatures made in this role
+    #[must_use]
+    pub const fn domain_separator(self) -> &'static str {
+        match self {
+            VoteRole::Da => "HotShot DA committee vote",
+            VoteRole::Quorum => "HotShot quorum vote",
+        }
+    }
+
+    /// The commitment signed by a vote in this role, on data with commitment `data_commitment`,
+    /// in the format of the epochs version onward
+    #[must_use]
+    pub fn vote_commitment<T: Committable>(
+        self,
+        data_commitment: &[u8],
+        view: u64,
+        epoch: u64,
+        version: Version,
+    ) -> Commitment<T> {
+        committable::RawCommitmentBuilder::new("Vote")
+            .constant_str(self.domain_separator())
+            .var_size_bytes(data_commitment)
+            .u64(view)
+            .u64(epoch)
+            .u16(version.major)
+            .u16(version.minor)
+            .finalize()
+    }
+}
 
 /// Marker trait for data or commitments that can be voted on.
 /// Only structs in this file can implement voteable.  This is enforced with the `Sealed` trait
 /// Sealing this trait prevents creating new vote types outside this file.
 pub trait Voteable:
-    sealed::Sealed + Committable + Clone + Serialize + Debug + PartialEq + Hash + Eq
+    sealed::Sealed
+    + Committable
+    + Clone
+    + Serialize
+    + Debug
+    + PartialEq
+    + Hash
+    + Eq
+    + Send
+    + Sync
+    + 'static
 {
+    /// The committee role votes on this data are cast in
+    const ROLE: VoteRole;
 }
 
 /// Sealed is used to make sure no other files can implement the Voteable trait.
@@ -124,6 +195,7 @@ impl<T: NodeType> QuorumMarker for ViewSyncPreCommitData<T> {}
 impl<T: NodeType> QuorumMarker for ViewSyncCommitData<T> {}
 impl<T: NodeType> QuorumMarker for ViewSyncFinalizeData<T> {}
 impl<T: NodeType + DeserializeOwned> QuorumMarker for UpgradeProposalData<T> {}
+impl<C: Committable> QuorumMarker for CustomData<C> {}
 
 /// A simple yes vote over some votable type.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash, Eq)]
@@ -172,11 +244,12 @@ impl<TYPES: NodeType, DATA: Voteable + 'static> SimpleVote<TYPES, DATA> {
     pub async fn create_signed_vote<V: Versions>(
         data: DATA,
         view: TYPES::View,
+        epoch: TYPES::Epoch,
         pub_key: &TYPES::SignatureKey,
         private_key: &<TYPES::SignatureKey as SignatureKey>::PrivateKey,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> Result<Self> {
-        let commit = VersionedVoteData::new(data.clone(), view, upgrade_lock)
+        let commit = VersionedVoteData::new(data.clone(), view, epoch, upgrade_lock)
             .await?
             .commit();
 
@@ -201,9 +274,15 @@ pub struct VersionedVoteData<TYPES: NodeType, DATA: Voteable, V: Versions> {
     /// underlying vote data
     data: DATA,
 
+    /// commitment to `data`, computed once when this struct is created
+    data_commitment: Commitment<DATA>,
+
     /// view number
     view: TYPES::View,
 
+    /// epoch whose stake table the vote is cast under
+    epoch: TYPES::Epoch,
+
     /// version applied to the view number
     version: Version,
 
@@ -220,31 +299,59 @@ impl<TYPES: NodeType, DATA: Voteable, V: Versions> VersionedVoteData<TYPES, DATA
     pub async fn new(
         data: DATA,
         view: TYPES::View,
+        epoch: TYPES::Epoch,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> Result<Self> {
         let version = upgrade_lock.version(view).await?;
 
         Ok(Self {
+            data_commitment: commit_with_cache(upgrade_lock.commitment_cache.as_deref(), &data),
             data,
             view,
+            epoch,
             version,
             _pd: PhantomData,
         })
     }
 
+    /// Create a new `VersionedVoteData` struct in the format of `version`, for checking votes
+    /// cast under a version other than the one applied in `view`, see
+    /// [`UpgradeLock::vote_versions`]. The commitment to `data` is looked up in `cache` if there
+    /// is one, see [`UpgradeLock::commitment_cache`].
+    #[must_use]
+    pub fn with_version(
+        data: DATA,
+        view: TYPES::View,
+        epoch: TYPES::Epoch,
+        version: Version,
+        cache: Option<&CommitmentCache>,
+    ) -> Self {
+        Self {
+            data_commitment: commit_with_cache(cache, &data),
+            data,
+            view,
+            epoch,
+            version,
+            _pd: PhantomData,
+        }
+    }
+
     /// Create a new `VersionedVoteData` struct
     ///
     /// This function cannot error, but may use an invalid version.
     pub async fn new_infallible(
         data: DATA,
         view: TYPES::View,
+        epoch: TYPES::Epoch,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> Self {
         let version = upgrade_lock.version_infallible(view).await;
 
         Self {
+            data_commitment: commit_with_cache(upgrade_lock.commitment_cache.as_deref(), &data),
             data,
             view,
+            epoch,
             version,
             _pd: PhantomData,
         }
@@ -255,10 +362,51 @@ impl<TYPES: NodeType, DATA: Voteable, V: Versions> Committable
     for VersionedVoteData<TYPES, DATA, V>
 {
     fn commit(&self) -> Commitment<Self> {
-        committable::RawCommitmentBuilder::new("Vote")
-            .var_size_bytes(self.data.commit().as_ref())
-            .u64(*self.view)
-            .finalize()
+        TYPES::CommitScheme::commit(self)
+    }
+}
+
+impl<TYPES: NodeType, DATA: Voteable, V: Versions> SchemeCommittable
+    for VersionedVoteData<TYPES, DATA, V>
+{
+    const TAG: &'static str = "Vote";
+
+    fn default_commitment(&self) -> Commitment<Self> {
+        let data_commitment = self.data_commitment;
+        // Before the epochs version votes keep the format every node already signs
+        if self.version < V::Epochs::VERSION {
+            return committable::RawCommitmentBuilder::new("Vote")
+                .var_size_bytes(data_commitment.as_ref())
+                .u64(*self.view)
+                .finalize();
+        }
+
+        DATA::ROLE.vote_commitment(
+            data_commitment.as_ref(),
+            *self.view,
+            *self.epoch,
+            self.version,
+        )
+    }
+
+    fn commitment_preimage(&self) -> Vec<u8> {
+        let data_commitment = self.data_commitment;
+        // As for the default commitment, the format before the epochs version is unchanged
+        if self.version < V::Epochs::VERSION {
+            let mut preimage = data_commitment.as_ref().to_vec();
+            preimage.extend_from_slice(&self.view.to_le_bytes());
+
+            return preimage;
+        }
+
+        let mut preimage = DATA::ROLE.domain_separator().as_bytes().to_vec();
+        preimage.extend_from_slice(data_commitment.as_ref());
+        preimage.extend_from_slice(&self.view.to_le_bytes());
+        preimage.extend_from_slice(&self.epoch.to_le_bytes());
+        preimage.extend_from_slice(&self.version.major.to_le_bytes());
+        preimage.extend_from_slice(&self.version.minor.to_le_bytes());
+
+        preimage
     }
 }
 
@@ -310,6 +458,17 @@ impl<TYPES: NodeType> Committable for UpgradeProposalData<TYPES> {
     }
 }
 
+impl<C: Committable> Committable for CustomData<C> {
+    fn commit(&self) -> Commitment<Self> {
+        // The tag is length-prefixed under a fixed prefix, so no tag can reproduce the commitment
+        // of any other vote data
+        committable::RawCommitmentBuilder::new("Custom data")
+            .var_size_field("tag", self.tag.as_bytes())
+            .var_size_bytes(self.commitment.as_ref())
+            .finalize()
+    }
+}
+
 /// This implements commit for all the types which contain a view and relay public key.
 fn view_and_relay_commit<TYPES: NodeType, T: Committable>(
     view: TYPES::View,
@@ -337,11 +496,35 @@ impl<TYPES: NodeType> Committable for ViewSyncCommitData<TYPES> {
     }
 }
 
-// impl votable for all the data types in this file sealed marker should ensure nothing is accidently
-// implemented for structs that aren't "voteable"
-impl<V: sealed::Sealed + Committable + Clone + Serialize + Debug + PartialEq + Hash + Eq> Voteable
-    for V
+impl<TYPES: NodeType> Voteable for QuorumData<TYPES> {
+    const ROLE: VoteRole = VoteRole::Quorum;
+}
+impl<TYPES: NodeType> Voteable for QuorumData2<TYPES> {
+    const ROLE: VoteRole = VoteRole::Quorum;
+}
+impl Voteable for DaData {
+    const ROLE: VoteRole = VoteRole::Da;
+}
+impl<TYPES: NodeType> Voteable for TimeoutData<TYPES> {
+    const ROLE: VoteRole = VoteRole::Quorum;
+}
+impl<TYPES: NodeType> Voteable for ViewSyncPreCommitData<TYPES> {
+    const ROLE: VoteRole = VoteRole::Quorum;
+}
+impl<TYPES: NodeType> Voteable for ViewSyncCommitData<TYPES> {
+    const ROLE: VoteRole = VoteRole::Quorum;
+}
+impl<TYPES: NodeType> Voteable for ViewSyncFinalizeData<TYPES> {
+    const ROLE: VoteRole = VoteRole::Quorum;
+}
+impl<TYPES: NodeType + DeserializeOwned> Voteable for UpgradeProposalData<TYPES> {
+    const ROLE: VoteRole = VoteRole::Quorum;
+}
+impl<C> Voteable for CustomData<C>
+where
+    C: Committable + Clone + Debug + PartialEq + Eq + Hash + Send + Sync + 'static,
 {
+    const ROLE: VoteRole = VoteRole::Quorum;
 }
 
 impl<TYPES: NodeType> QuorumVote<TYPES> {
@@ -400,3 +583,5 @@ pub type ViewSyncPreCommitVote<TYPES> = SimpleVote<TYPES, ViewSyncPreCommitData<
 pub type ViewSyncFinalizeVote<TYPES> = SimpleVote<TYPES, ViewSyncFinalizeData<TYPES>>;
 /// Upgrade proposal vote
 pub type UpgradeVote<TYPES> = SimpleVote<TYPES, UpgradeProposalData<TYPES>>;
+/// An application-defined vote over `CustomData`
+pub type CustomVote<TYPES, C> = SimpleVote<TYPES, CustomData<C>>;
@@ -10,10 +10,12 @@
 //! describe the behaviors that a block is expected to have.
 
 use std::{
+    collections::BTreeMap,
     error::Error,
     fmt::{Debug, Display},
     future::Future,
     hash::Hash,
+    ops::Range,
     sync::Arc,
 };
 
@@ -21,6 +23,7 @@ use async_trait::async_trait;
 use committable::{Commitment, Committable};
 use jf_vid::{precomputable::Precomputable, VidScheme};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use utils::anytrace;
 use vbs::version::Version;
 
 use super::signature_key::BuilderSignatureKey;
@@ -48,6 +51,44 @@ pub trait Transaction:
     fn minimum_block_size(&self) -> u64;
 }
 
+/// The reason a transaction was refused admission by a [`TxnValidator`].
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum TxnError {
+    /// The transaction is larger than the validator allows
+    #[error("Transaction of {size} bytes exceeds the maximum of {max} bytes")]
+    TooLarge {
+        /// The size of the transaction
+        size: u64,
+        /// The maximum allowed size
+        max: u64,
+    },
+
+    /// The transaction is malformed or otherwise invalid
+    #[error("Invalid transaction: {0}")]
+    Invalid(String),
+}
+
+/// Application-provided check run on every transaction received from the network, before it is
+/// admitted to the mempool.
+pub trait TxnValidator<TYPES: NodeType>: Send + Sync + 'static {
+    /// Check whether `txn` may be admitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the transaction should be rejected.
+    fn validate(&self, txn: &TYPES::Transaction) -> Result<(), TxnError>;
+}
+
+/// A [`TxnValidator`] which admits every transaction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AcceptAllTxnValidator;
+
+impl<TYPES: NodeType> TxnValidator<TYPES> for AcceptAllTxnValidator {
+    fn validate(&self, _txn: &TYPES::Transaction) -> Result<(), TxnError> {
+        Ok(())
+    }
+}
+
 /// Abstraction over the full contents of a block
 ///
 /// This trait encapsulates the behaviors that the transactions of a block must have in order to be
@@ -128,6 +169,26 @@ pub trait BlockPayload<TYPES: NodeType>:
         &'a self,
         metadata: &'a Self::Metadata,
     ) -> impl 'a + Iterator<Item = Self::Transaction>;
+
+    /// The byte range of the `index`-th transaction within the encoded payload, or `None` if
+    /// there is no such transaction.
+    ///
+    /// Payload types which support transaction inclusion proofs should override this; the
+    /// default does not know the payload's layout and always returns `None`.
+    fn transaction_range(&self, _metadata: &Self::Metadata, _index: usize) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Check that `metadata` is consistent with an encoded payload of `payload_len` bytes.
+    ///
+    /// Payload types whose metadata records offsets into the payload, such as a
+    /// [`NamespaceTable`], should override this; the default accepts any metadata.
+    ///
+    /// # Errors
+    /// If the metadata does not describe a payload of `payload_len` bytes.
+    fn validate_metadata(_metadata: &Self::Metadata, _payload_len: usize) -> anytrace::Result<()> {
+        Ok(())
+    }
 }
 
 /// extra functions required on block to be usable by hotshot-testing
@@ -139,6 +200,124 @@ pub trait TestableBlock<TYPES: NodeType>: BlockPayload<TYPES> + Debug {
     fn txn_count(&self) -> u64;
 }
 
+/// Identifier of the namespace (e.g. rollup) a transaction belongs to.
+pub type NamespaceId = u32;
+
+/// A transaction which is tagged with the namespace it belongs to.
+pub trait NamespacedTransaction: Transaction {
+    /// The namespace this transaction belongs to.
+    fn namespace(&self) -> NamespaceId;
+}
+
+/// Block metadata which records the byte range each namespace occupies in the encoded payload.
+pub trait NamespacedMetadata {
+    /// The byte range of namespace `ns` within the encoded transactions, or `None` if the
+    /// payload does not contain any transactions for `ns`.
+    ///
+    /// The range is only meaningful for a payload the metadata passed
+    /// [`validate_namespaces`](Self::validate_namespaces) for.
+    fn namespace_range(&self, ns: NamespaceId) -> Option<Range<usize>>;
+
+    /// Check that the recorded namespace ranges fit an encoded payload of `payload_len` bytes.
+    ///
+    /// # Errors
+    /// If the ranges are out of order or extend past the end of the payload.
+    fn validate_namespaces(&self, payload_len: usize) -> anytrace::Result<()>;
+}
+
+/// Table of per-namespace offsets into an encoded payload.
+///
+/// Namespaces are laid out contiguously in ascending order of their id, so each entry only stores
+/// the (exclusive) end offset of its namespace; the start is the end of the previous entry.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct NamespaceTable {
+    /// `(namespace, end offset)` pairs, sorted by namespace
+    entries: Vec<(NamespaceId, usize)>,
+}
+
+impl NamespaceTable {
+    /// Group `transactions` by namespace and encode each group with `encode`, returning the
+    /// concatenated payload along with the table of namespace offsets into it.
+    ///
+    /// The relative order of transactions within a namespace is preserved.
+    pub fn encode_transactions<T: NamespacedTransaction>(
+        transactions: impl IntoIterator<Item = T>,
+        encode: impl Fn(&[T]) -> Vec<u8>,
+    ) -> (Vec<u8>, Self) {
+        let mut namespaces: BTreeMap<NamespaceId, Vec<T>> = BTreeMap::new();
+        for txn in transactions {
+            namespaces.entry(txn.namespace()).or_default().push(txn);
+        }
+
+        let mut payload = Vec::new();
+        let mut entries = Vec::with_capacity(namespaces.len());
+        for (ns, txns) in namespaces {
+            payload.extend(encode(&txns));
+            entries.push((ns, payload.len()));
+        }
+
+        (payload, Self { entries })
+    }
+
+    /// The namespaces present in the payload, in ascending order.
+    pub fn namespaces(&self) -> impl '_ + Iterator<Item = NamespaceId> {
+        self.entries.iter().map(|(ns, _)| *ns)
+    }
+}
+
+impl NamespacedMetadata for NamespaceTable {
+    fn namespace_range(&self, ns: NamespaceId) -> Option<Range<usize>> {
+        let index = self
+            .entries
+            .binary_search_by_key(&ns, |(entry_ns, _)| *entry_ns)
+            .ok()?;
+        let start = index.checked_sub(1).map_or(0, |prev| self.entries[prev].1);
+        let end = self.entries[index].1;
+
+        (start <= end).then_some(start..end)
+    }
+
+    fn validate_namespaces(&self, payload_len: usize) -> anytrace::Result<()> {
+        use utils::anytrace::*;
+
+        for ((prev_ns, prev_end), (ns, end)) in self.entries.iter().zip(self.entries.iter().skip(1))
+        {
+            ensure!(
+                prev_ns < ns,
+                warn!("Namespace {ns} is listed after namespace {prev_ns}")
+            );
+            ensure!(
+                prev_end <= end,
+                warn!(
+                    "Namespace {ns} ends at {end}, before namespace {prev_ns} ends at {prev_end}"
+                )
+            );
+        }
+        if let Some((ns, end)) = self.entries.last() {
+            ensure!(
+                *end <= payload_len,
+                warn!(
+                    "Namespace {ns} ends at {end}, past the end of the {payload_len} byte payload"
+                )
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl EncodeBytes for NamespaceTable {
+    fn encode(&self) -> Arc<[u8]> {
+        let mut bytes = Vec::with_capacity(self.entries.len() * 12);
+        for (ns, end) in &self.entries {
+            bytes.extend(ns.to_le_bytes());
+            bytes.extend((*end as u64).to_le_bytes());
+        }
+
+        bytes.into()
+    }
+}
+
 /// Compute the VID payload commitment.
 /// TODO(Gus) delete this function?
 /// # Panics
@@ -248,4 +427,98 @@ pub trait BlockHeader<TYPES: NodeType>:
 
     /// Get the results of the auction for this Header. Only used in post-marketplace versions
     fn get_auction_results(&self) -> Option<TYPES::AuctionResult>;
+
+    /// Get the time the block was built at, in seconds since the Unix epoch, if the header
+    /// records one.
+    fn timestamp(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use committable::RawCommitmentBuilder;
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    struct NsTxn(NamespaceId, Vec<u8>);
+
+    impl Committable for NsTxn {
+        fn commit(&self) -> Commitment<Self> {
+            RawCommitmentBuilder::new("NsTxn")
+                .u64(u64::from(self.0))
+                .var_size_bytes(&self.1)
+                .finalize()
+        }
+    }
+
+    impl Transaction for NsTxn {
+        fn minimum_block_size(&self) -> u64 {
+            self.1.len() as u64
+        }
+    }
+
+    impl NamespacedTransaction for NsTxn {
+        fn namespace(&self) -> NamespaceId {
+            self.0
+        }
+    }
+
+    fn encode(txns: &[NsTxn]) -> Vec<u8> {
+        txns.iter().flat_map(|txn| txn.1.clone()).collect()
+    }
+
+    #[test]
+    fn namespace_ranges_cover_each_namespace() {
+        let txns = vec![
+            NsTxn(7, vec![7, 7]),
+            NsTxn(2, vec![2]),
+            NsTxn(7, vec![7]),
+            NsTxn(5, vec![5, 5, 5, 5]),
+            NsTxn(2, vec![2, 2]),
+        ];
+
+        let (payload, table) = NamespaceTable::encode_transactions(txns, encode);
+
+        assert_eq!(table.namespaces().collect::<Vec<_>>(), vec![2, 5, 7]);
+        assert_eq!(payload.len(), 10);
+        assert_eq!(&payload[table.namespace_range(2).unwrap()], &[2, 2, 2]);
+        assert_eq!(&payload[table.namespace_range(5).unwrap()], &[5, 5, 5, 5]);
+        assert_eq!(&payload[table.namespace_range(7).unwrap()], &[7, 7, 7]);
+        assert_eq!(table.namespace_range(3), None);
+    }
+
+    #[test]
+    fn empty_payload_has_no_namespaces() {
+        let (payload, table) = NamespaceTable::encode_transactions(Vec::<NsTxn>::new(), encode);
+
+        assert!(payload.is_empty());
+        assert_eq!(table.namespaces().count(), 0);
+        assert_eq!(table.namespace_range(0), None);
+        assert!(table.validate_namespaces(0).is_ok());
+    }
+
+    #[test]
+    fn malformed_tables_are_rejected() {
+        let (payload, table) =
+            NamespaceTable::encode_transactions(vec![NsTxn(1, vec![1]), NsTxn(2, vec![2])], encode);
+        assert!(table.validate_namespaces(payload.len()).is_ok());
+
+        // A table for a longer payload than the one it came with
+        assert!(table.validate_namespaces(payload.len() - 1).is_err());
+
+        // Offsets which go backwards give an empty range rather than one which can't be sliced
+        let decreasing = NamespaceTable {
+            entries: vec![(1, 5), (2, 3)],
+        };
+        assert!(decreasing.validate_namespaces(5).is_err());
+        assert_eq!(decreasing.namespace_range(2), None);
+
+        // Namespaces out of order
+        let unsorted = NamespaceTable {
+            entries: vec![(2, 1), (1, 2)],
+        };
+        assert!(unsorted.validate_namespaces(2).is_err());
+    }
 }
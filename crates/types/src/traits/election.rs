@@ -5,19 +5,30 @@
 // along with the HotShot repository. If not, see <https://mit-license.org/>.
 
 //! The election trait, used to decide which node is the leader and determine if a vote is valid.
-use std::{collections::BTreeSet, fmt::Debug, num::NonZeroU64};
+use std::{any::Any, collections::BTreeSet, fmt::Debug, num::NonZeroU64, sync::Arc};
 
-use utils::anytrace::Result;
+use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utils::anytrace::*;
 
-use super::node_implementation::NodeType;
-use crate::{traits::signature_key::SignatureKey, PeerConfig};
+use super::node_implementation::{ConsensusTime, NodeType};
+use crate::{
+    simple_certificate::QuorumCertificate2,
+    traits::signature_key::{SignatureKey, StakeTableEntryType},
+    vote::Certificate,
+    PeerConfig,
+};
 
 /// A protocol for determining membership in and participating in a committee.
-pub trait Membership<TYPES: NodeType>: Clone + Debug + Send + Sync {
+pub trait Membership<TYPES: NodeType>: Clone + Debug + Send + Sync + 'static {
     /// The error type returned by methods like `lookup_leader`.
     type Error: std::fmt::Display;
 
     /// Create a committee
+    ///
+    /// Duplicated public keys are not checked here: a node's config is validated for them on
+    /// startup (see `HotShotConfig::validate`), and [`Membership::try_new`] checks any other list.
     fn new(
         // Note: eligible_leaders is currently a hack because the DA leader == the quorum leader
         // but they should not have voting power.
@@ -25,13 +36,39 @@ pub trait Membership<TYPES: NodeType>: Clone + Debug + Send + Sync {
         da_committee_members: Vec<PeerConfig<TYPES::SignatureKey>>,
     ) -> Self;
 
+    /// Create a committee, after checking that no public key appears twice in either list.
+    ///
+    /// Signers are located in the stake table by their entry, so a duplicated key would make
+    /// certificate signer bit vectors ambiguous.
+    ///
+    /// # Errors
+    /// If either list contains a public key more than once
+    fn try_new(
+        stake_committee_members: Vec<PeerConfig<TYPES::SignatureKey>>,
+        da_committee_members: Vec<PeerConfig<TYPES::SignatureKey>>,
+    ) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        ensure_unique_committees::<TYPES>(&stake_committee_members, &da_committee_members)?;
+
+        Ok(Self::new(stake_committee_members, da_committee_members))
+    }
+
     /// Get all participants in the committee (including their stake) for a specific epoch
+    ///
+    /// Certificate signer bit vectors index into this table, so its order is part of the format of
+    /// certificates: entries keep the order the committee was configured in, and new members are
+    /// appended. This is generally not the key order of [`Membership::committee_members`], which
+    /// must never be used to compute signer positions.
     fn stake_table(
         &self,
         epoch: TYPES::Epoch,
     ) -> Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>;
 
     /// Get all participants in the committee (including their stake) for a specific epoch
+    ///
+    /// Entries are ordered as in [`Membership::stake_table`].
     fn da_stake_table(
         &self,
         epoch: TYPES::Epoch,
@@ -74,6 +111,47 @@ pub trait Membership<TYPES: NodeType>: Clone + Debug + Send + Sync {
         epoch: TYPES::Epoch,
     ) -> Option<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>;
 
+    /// Get the stake a node effectively holds at `view_number` in a specific epoch.
+    ///
+    /// Implementations with scheduled bonding or unbonding should override this so that stake
+    /// which has unbonded by `view_number` no longer counts towards certificates.
+    fn effective_stake_at_view(
+        &self,
+        pub_key: &TYPES::SignatureKey,
+        _view_number: TYPES::View,
+        epoch: TYPES::Epoch,
+    ) -> U256 {
+        self.stake_of(pub_key, epoch).unwrap_or_default()
+    }
+
+    /// The most stake a single vote counts for towards a certificate, or `None` if uncapped.
+    ///
+    /// The cap is part of the stake table rather than of any node's configuration, so the leader
+    /// tallying votes and the replicas checking its certificate weigh every signer alike.
+    fn max_vote_weight(&self) -> Option<u64> {
+        None
+    }
+
+    /// Whether view sync skips relays led by the leader of the view which failed.
+    ///
+    /// Replicas send their view sync votes to the relay this selects, so it must agree across the
+    /// network, or votes would be split between relays and none would reach a certificate.
+    fn skip_failed_leader_relays(&self) -> bool {
+        false
+    }
+
+    /// The stake summed over every entry of the stake table for `epoch`
+    fn total_stake(&self, epoch: TYPES::Epoch) -> U256 {
+        self.stake_table(epoch)
+            .iter()
+            .fold(U256::zero(), |total, entry| total + entry.stake())
+    }
+
+    /// The stake `pub_key` holds in `epoch`, or `None` if it is not in the stake table
+    fn stake_of(&self, pub_key: &TYPES::SignatureKey, epoch: TYPES::Epoch) -> Option<U256> {
+        self.stake(pub_key, epoch).map(|entry| entry.stake())
+    }
+
     /// See if a node has stake in the committee in a specific epoch
     fn has_stake(&self, pub_key: &TYPES::SignatureKey, epoch: TYPES::Epoch) -> bool;
 
@@ -125,4 +203,540 @@ pub trait Membership<TYPES: NodeType>: Clone + Debug + Send + Sync {
 
     /// Returns the threshold required to upgrade the network protocol
     fn upgrade_threshold(&self, epoch: TYPES::Epoch) -> NonZeroU64;
+
+    /// The committees of the `lookahead_epochs` epochs following `epoch`, in order, for those
+    /// epochs whose committee is already known.
+    ///
+    /// Used to connect to upcoming committee members ahead of the epoch change. By default every
+    /// upcoming committee is taken from [`committee_members`](Self::committee_members);
+    /// implementations which do not know their committees in advance should override this.
+    fn upcoming_committee(
+        &self,
+        epoch: TYPES::Epoch,
+        lookahead_epochs: u64,
+    ) -> Vec<(TYPES::Epoch, BTreeSet<TYPES::SignatureKey>)> {
+        (1..=lookahead_epochs)
+            .map(|offset| {
+                let epoch = epoch + offset;
+                (epoch, self.committee_members(TYPES::View::genesis(), epoch))
+            })
+            .collect()
+    }
+
+    /// Take an immutable snapshot of the committee for `epoch`.
+    ///
+    /// The snapshot is unaffected by later changes to this membership, is cheap to clone, and can
+    /// be handed to verification running on another thread.
+    fn snapshot_for_epoch(&self, epoch: TYPES::Epoch) -> EpochCommittee<TYPES, Self> {
+        EpochCommittee::new(self, epoch)
+    }
+
+    /// Apply a single change to the committee in place, without re-running the election.
+    ///
+    /// The result must be the same as creating the committee from the member lists with the
+    /// change applied, where an added node goes at the end of the lists.
+    ///
+    /// # Errors
+    /// If the change does not apply to the current committee, or if this implementation does not
+    /// support incremental changes
+    fn apply_change(&mut self, _change: MembershipChange<TYPES>) -> Result<()> {
+        bail!("This membership does not support incremental committee changes")
+    }
+
+    /// Replace the committee from `epoch` onwards with the one created from the member lists, for
+    /// validator churn on a long-running chain. The committee of earlier epochs is unchanged.
+    ///
+    /// Every stake table query names an epoch, and certificates record the epoch they were formed
+    /// in, so a certificate is always checked against the committee of its own epoch. One formed
+    /// before the reconfiguration stays verifiable after it, and one claiming a later epoch than
+    /// it was signed in does not validate against the new committee.
+    ///
+    /// # Errors
+    /// If the new committee is invalid, or if this implementation does not support reconfiguration
+    fn reconfigure(
+        &mut self,
+        _epoch: TYPES::Epoch,
+        _committee_members: Vec<PeerConfig<TYPES::SignatureKey>>,
+        _da_members: Vec<PeerConfig<TYPES::SignatureKey>>,
+    ) -> Result<()> {
+        bail!("This membership does not support reconfiguration")
+    }
+
+    /// A seed for `view` which every node with the same committee for `epoch` derives
+    /// identically, for applications which need an agreed random value per view.
+    ///
+    /// The seed commits to the view, the epoch and the committee's stake table. It is known in
+    /// advance to anyone who knows the committee, so it must not be relied on to be unpredictable.
+    fn view_seed(&self, view: TYPES::View, epoch: TYPES::Epoch) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(VIEW_SEED_DOMAIN);
+        hasher.update(view.u64().to_le_bytes());
+        hasher.update(epoch.u64().to_le_bytes());
+        for entry in self.stake_table(epoch) {
+            hasher.update(entry.public_key().to_bytes());
+            for limb in entry.stake().0 {
+                hasher.update(limb.to_le_bytes());
+            }
+        }
+
+        hasher.finalize().into()
+    }
+
+    /// The leader of `view` in `epoch`, drawn at random in proportion to stake.
+    ///
+    /// The draw is seeded by [`Membership::view_seed`], so every node with the same committee
+    /// picks the same leader. Nodes without stake are never picked.
+    ///
+    /// # Errors
+    /// If no node in the committee holds any stake
+    fn leader_weighted(
+        &self,
+        view: TYPES::View,
+        epoch: TYPES::Epoch,
+    ) -> Result<TYPES::SignatureKey> {
+        let stake_table = self.stake_table(epoch);
+        let total_stake = self.total_stake(epoch);
+        ensure!(
+            !total_stake.is_zero(),
+            "No node holds stake in epoch {epoch}, so view {view} has no leader"
+        );
+
+        // Walk the stake table until the cumulative stake passes the drawn point
+        let mut point = U256::from_big_endian(&self.view_seed(view, epoch)) % total_stake;
+        for entry in stake_table {
+            if point < entry.stake() {
+                return Ok(entry.public_key());
+            }
+            point -= entry.stake();
+        }
+
+        bail!("The point drawn for view {view} is past the total stake")
+    }
+
+    /// A proof that the node with `private_key` was selected as the leader of `view` in `epoch`,
+    /// for replicas to check with [`Membership::verify_leader_proof`].
+    ///
+    /// Every node can compute the leader of a deterministic membership, so by default there is
+    /// nothing to prove.
+    ///
+    /// # Errors
+    /// If the proof could not be produced
+    fn leader_proof(
+        &self,
+        _view: TYPES::View,
+        _epoch: TYPES::Epoch,
+        _private_key: &<TYPES::SignatureKey as SignatureKey>::PrivateKey,
+    ) -> Result<LeaderProof<TYPES::SignatureKey>> {
+        Ok(LeaderProof::Deterministic)
+    }
+
+    /// Whether `proof` shows that `key` was legitimately selected as the leader of `view` in
+    /// `epoch`.
+    ///
+    /// By default this only checks that `key` is the leader this membership computes.
+    fn verify_leader_proof(
+        &self,
+        view: TYPES::View,
+        epoch: TYPES::Epoch,
+        key: &TYPES::SignatureKey,
+        _proof: &LeaderProof<TYPES::SignatureKey>,
+    ) -> bool {
+        self.leader(view, epoch).is_ok_and(|leader| leader == *key)
+    }
+
+    /// The membership as [`Any`], for downcasting to the concrete implementation.
+    ///
+    /// This is an escape hatch for implementation-specific queries which don't belong on this
+    /// trait; code using it only works with the implementation it downcasts to, so prefer adding
+    /// a method here for anything generic code needs.
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Domain separator of the hash deriving [`Membership::view_seed`]
+const VIEW_SEED_DOMAIN: &[u8] = b"HOTSHOT_VIEW_SEED";
+
+/// Proof that a node was selected as the leader of a view, see [`Membership::leader_proof`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub enum LeaderProof<KEY: SignatureKey> {
+    /// The leader follows from the committee alone, so there is nothing to prove
+    Deterministic,
+    /// The leader's signature over the seed of the view
+    Signed(KEY::PureAssembledSignatureType),
+}
+
+/// A single change to a committee, see [`Membership::apply_change`]
+#[derive(Clone, Debug)]
+pub enum MembershipChange<TYPES: NodeType> {
+    /// Add a node to the committee, and also to the DA committee if `da` is set
+    Add {
+        /// The node to add
+        peer: PeerConfig<TYPES::SignatureKey>,
+        /// Whether the node also joins the DA committee
+        da: bool,
+    },
+    /// Remove a node from both the committee and the DA committee
+    Remove(TYPES::SignatureKey),
+    /// Set the stake of a node in every committee it is on. A stake of zero removes the node.
+    UpdateStake {
+        /// The node whose stake changes
+        key: TYPES::SignatureKey,
+        /// The node's new stake
+        stake: u64,
+    },
+    /// Replace the key of a node in every committee it is on, keeping its stake and its signer
+    /// position. Applied while a later epoch is scheduled, the rotation only takes effect in that
+    /// epoch, so votes and certificates signed with the old key stay valid until then.
+    RotateKey {
+        /// The node's current key
+        key: TYPES::SignatureKey,
+        /// The key replacing it
+        new_key: TYPES::SignatureKey,
+    },
+}
+
+/// An error deriving a certificate threshold from the size of a committee
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ThresholdError {
+    /// The fraction is not between zero and one
+    #[error("Invalid threshold fraction {numerator}/{denominator}")]
+    InvalidFraction {
+        /// Numerator of the fraction
+        numerator: u64,
+        /// Denominator of the fraction
+        denominator: u64,
+    },
+    /// The threshold rounds down to zero, so any set of votes would form a certificate
+    #[error(
+        "A threshold of {numerator}/{denominator} of a committee of {total_nodes} nodes rounds \
+         down to zero"
+    )]
+    Zero {
+        /// Numerator of the fraction
+        numerator: u64,
+        /// Denominator of the fraction
+        denominator: u64,
+        /// The size of the committee
+        total_nodes: usize,
+    },
+}
+
+/// `numerator / denominator` of `total_nodes`, rounded down
+fn fraction_of(
+    total_nodes: usize,
+    numerator: u64,
+    denominator: u64,
+) -> std::result::Result<u64, ThresholdError> {
+    if denominator == 0 || numerator > denominator {
+        return Err(ThresholdError::InvalidFraction {
+            numerator,
+            denominator,
+        });
+    }
+
+    // At most `total_nodes`, and computed in `u128` so the product cannot overflow
+    let share = (total_nodes as u128) * u128::from(numerator) / u128::from(denominator);
+    Ok(u64::try_from(share).unwrap_or(u64::MAX))
+}
+
+/// The stake required for strictly more than `numerator / denominator` of `total_nodes`, e.g.
+/// `threshold_above(n, 2, 3)` for a quorum certificate.
+///
+/// # Errors
+/// If the fraction is not between zero and one
+pub fn threshold_above(
+    total_nodes: usize,
+    numerator: u64,
+    denominator: u64,
+) -> std::result::Result<NonZeroU64, ThresholdError> {
+    let share = fraction_of(total_nodes, numerator, denominator)?;
+    Ok(NonZeroU64::MIN.saturating_add(share))
+}
+
+/// The stake required for at least `numerator / denominator` of `total_nodes`, rounded down.
+///
+/// # Errors
+/// If the fraction is not between zero and one, or if the threshold rounds down to zero because
+/// the committee is too small for the fraction
+pub fn threshold_at_least(
+    total_nodes: usize,
+    numerator: u64,
+    denominator: u64,
+) -> std::result::Result<NonZeroU64, ThresholdError> {
+    let share = fraction_of(total_nodes, numerator, denominator)?;
+    NonZeroU64::new(share).ok_or(ThresholdError::Zero {
+        numerator,
+        denominator,
+        total_nodes,
+    })
+}
+
+/// The threshold for an upgrade certificate: at least 9/10 of `total_nodes`, and never less than
+/// a quorum, which is all a committee too small for 9/10 to be meaningful requires.
+#[must_use]
+pub fn upgrade_threshold_for(total_nodes: usize) -> NonZeroU64 {
+    let quorum = threshold_above(total_nodes, 2, 3).unwrap_or(NonZeroU64::MIN);
+    threshold_at_least(total_nodes, 9, 10).map_or(quorum, |upgrade| upgrade.max(quorum))
+}
+
+/// The nodes which joined and left a committee, e.g. across an epoch change
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitteeDiff<TYPES: NodeType> {
+    /// Nodes on the new committee but not the old one
+    pub added: BTreeSet<TYPES::SignatureKey>,
+    /// Nodes on the old committee but not the new one
+    pub removed: BTreeSet<TYPES::SignatureKey>,
+}
+
+impl<TYPES: NodeType> CommitteeDiff<TYPES> {
+    /// The changes from the committee `old` to the committee `new`
+    #[must_use]
+    pub fn between(
+        old: &BTreeSet<TYPES::SignatureKey>,
+        new: &BTreeSet<TYPES::SignatureKey>,
+    ) -> Self {
+        Self {
+            added: new.difference(old).cloned().collect(),
+            removed: old.difference(new).cloned().collect(),
+        }
+    }
+
+    /// Whether the committee is unchanged
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// The state of an [`EpochCommittee`], shared between its clones
+#[derive(Debug)]
+struct EpochCommitteeInner<TYPES: NodeType, MEMBERSHIP: Membership<TYPES>> {
+    /// The epoch this committee is for
+    epoch: TYPES::Epoch,
+
+    /// The quorum stake table, in signer order
+    stake_table: Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>,
+
+    /// The DA stake table, in signer order
+    da_stake_table: Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>,
+
+    /// The stake required for a quorum certificate
+    success_threshold: NonZeroU64,
+
+    /// The stake required for a DA certificate
+    da_success_threshold: NonZeroU64,
+
+    /// The stake required for a timeout certificate
+    failure_threshold: NonZeroU64,
+
+    /// The stake required for an upgrade certificate
+    upgrade_threshold: NonZeroU64,
+
+    /// A frozen copy of the membership, for per-key and per-view lookups
+    membership: MEMBERSHIP,
+}
+
+/// An immutable view of the committee for a single epoch.
+///
+/// See [`Membership::snapshot_for_epoch`].
+#[derive(Clone, Debug)]
+pub struct EpochCommittee<TYPES: NodeType, MEMBERSHIP: Membership<TYPES>> {
+    /// The snapshotted committee
+    inner: Arc<EpochCommitteeInner<TYPES, MEMBERSHIP>>,
+}
+
+impl<TYPES: NodeType, MEMBERSHIP: Membership<TYPES>> EpochCommittee<TYPES, MEMBERSHIP> {
+    /// Snapshot the committee of `membership` for `epoch`
+    #[must_use]
+    pub fn new(membership: &MEMBERSHIP, epoch: TYPES::Epoch) -> Self {
+        Self {
+            inner: Arc::new(EpochCommitteeInner {
+                epoch,
+                stake_table: membership.stake_table(epoch),
+                da_stake_table: membership.da_stake_table(epoch),
+                success_threshold: membership.success_threshold(epoch),
+                da_success_threshold: membership.da_success_threshold(epoch),
+                failure_threshold: membership.failure_threshold(epoch),
+                upgrade_threshold: membership.upgrade_threshold(epoch),
+                membership: membership.clone(),
+            }),
+        }
+    }
+
+    /// The epoch this committee is for
+    #[must_use]
+    pub fn epoch(&self) -> TYPES::Epoch {
+        self.inner.epoch
+    }
+
+    /// The quorum stake table, in signer order
+    #[must_use]
+    pub fn stake_table(&self) -> &[<TYPES::SignatureKey as SignatureKey>::StakeTableEntry] {
+        &self.inner.stake_table
+    }
+
+    /// The DA stake table, in signer order
+    #[must_use]
+    pub fn da_stake_table(&self) -> &[<TYPES::SignatureKey as SignatureKey>::StakeTableEntry] {
+        &self.inner.da_stake_table
+    }
+
+    /// The keys of the quorum committee
+    #[must_use]
+    pub fn committee_members(&self) -> BTreeSet<TYPES::SignatureKey> {
+        self.inner
+            .stake_table
+            .iter()
+            .map(TYPES::SignatureKey::public_key)
+            .collect()
+    }
+
+    /// The keys of the DA committee
+    #[must_use]
+    pub fn da_committee_members(&self) -> BTreeSet<TYPES::SignatureKey> {
+        self.inner
+            .da_stake_table
+            .iter()
+            .map(TYPES::SignatureKey::public_key)
+            .collect()
+    }
+
+    /// The quorum stake table entry for `pub_key`, if it is on the committee
+    #[must_use]
+    pub fn stake(
+        &self,
+        pub_key: &TYPES::SignatureKey,
+    ) -> Option<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry> {
+        self.inner.membership.stake(pub_key, self.inner.epoch)
+    }
+
+    /// The DA stake table entry for `pub_key`, if it is on the DA committee
+    #[must_use]
+    pub fn da_stake(
+        &self,
+        pub_key: &TYPES::SignatureKey,
+    ) -> Option<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry> {
+        self.inner.membership.da_stake(pub_key, self.inner.epoch)
+    }
+
+    /// The stake `pub_key` effectively holds at `view_number`
+    #[must_use]
+    pub fn effective_stake_at_view(
+        &self,
+        pub_key: &TYPES::SignatureKey,
+        view_number: TYPES::View,
+    ) -> U256 {
+        self.inner
+            .membership
+            .effective_stake_at_view(pub_key, view_number, self.inner.epoch)
+    }
+
+    /// The leader for `view`
+    ///
+    /// # Errors
+    /// Returns an error if the leader cannot be calculated.
+    pub fn leader(&self, view: TYPES::View) -> Result<TYPES::SignatureKey> {
+        self.inner.membership.leader(view, self.inner.epoch)
+    }
+
+    /// The number of nodes on the quorum committee
+    #[must_use]
+    pub fn total_nodes(&self) -> usize {
+        self.inner.stake_table.len()
+    }
+
+    /// The number of nodes on the DA committee
+    #[must_use]
+    pub fn da_total_nodes(&self) -> usize {
+        self.inner.da_stake_table.len()
+    }
+
+    /// The stake required for a quorum certificate
+    #[must_use]
+    pub fn success_threshold(&self) -> NonZeroU64 {
+        self.inner.success_threshold
+    }
+
+    /// The stake required for a DA certificate
+    #[must_use]
+    pub fn da_success_threshold(&self) -> NonZeroU64 {
+        self.inner.da_success_threshold
+    }
+
+    /// The stake required for a timeout certificate
+    #[must_use]
+    pub fn failure_threshold(&self) -> NonZeroU64 {
+        self.inner.failure_threshold
+    }
+
+    /// The stake required for an upgrade certificate
+    #[must_use]
+    pub fn upgrade_threshold(&self) -> NonZeroU64 {
+        self.inner.upgrade_threshold
+    }
+
+    /// Whether votes from `signers` at `view_number` could form a quorum certificate, i.e.
+    /// whether the distinct committee members among them hold the stake the accumulator would
+    /// require.
+    ///
+    /// Signers are weighed by [`Certificate::effective_stake_table`] against
+    /// [`Certificate::effective_threshold`], as in
+    /// [`VoteAccumulator`](crate::vote::VoteAccumulator), so unbonded stake and capped vote
+    /// weights count the same here as they would for the votes themselves.
+    ///
+    /// This is a cheap pre-flight check: no signature is verified, so a `true` result does not
+    /// mean the votes are valid.
+    #[must_use]
+    pub fn could_form_certificate(
+        &self,
+        signers: &[TYPES::SignatureKey],
+        view_number: TYPES::View,
+    ) -> bool {
+        let membership = &self.inner.membership;
+        let epoch = self.inner.epoch;
+        let signers: BTreeSet<_> = signers.iter().collect();
+        let stake =
+            QuorumCertificate2::<TYPES>::effective_stake_table(membership, view_number, epoch)
+                .iter()
+                .filter(|entry| signers.contains(&TYPES::SignatureKey::public_key(entry)))
+                .fold(U256::zero(), |total, entry| total + entry.stake());
+
+        stake >= QuorumCertificate2::<TYPES>::effective_threshold(membership, view_number, epoch)
+    }
+}
+
+/// Check that no public key appears more than once in either the stake committee or the DA
+/// committee.
+///
+/// # Errors
+/// Names the committee and the first public key which is duplicated in it
+pub fn ensure_unique_committees<TYPES: NodeType>(
+    stake_committee_members: &[PeerConfig<TYPES::SignatureKey>],
+    da_committee_members: &[PeerConfig<TYPES::SignatureKey>],
+) -> Result<()> {
+    ensure_unique_stake_table_keys::<TYPES>(stake_committee_members)
+        .context(error!("Invalid stake committee"))?;
+    ensure_unique_stake_table_keys::<TYPES>(da_committee_members)
+        .context(error!("Invalid DA committee"))
+}
+
+/// Check that no public key appears more than once in `peers`.
+///
+/// # Errors
+/// Names the first public key which is duplicated
+pub fn ensure_unique_stake_table_keys<TYPES: NodeType>(
+    peers: &[PeerConfig<TYPES::SignatureKey>],
+) -> Result<()> {
+    let mut seen = BTreeSet::new();
+    for peer in peers {
+        let key = TYPES::SignatureKey::public_key(&peer.stake_table_entry);
+        ensure!(
+            seen.insert(key.clone()),
+            error!("Public key {key} appears more than once in the stake table")
+        );
+    }
+
+    Ok(())
 }
@@ -9,7 +9,7 @@
 //! Contains types and traits used by `HotShot` to abstract over network access
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     fmt::{Debug, Display},
     hash::Hash,
     pin::Pin,
@@ -19,7 +19,7 @@ use std::{
 
 use async_trait::async_trait;
 use dyn_clone::DynClone;
-use futures::{future::join_all, Future};
+use futures::{future::join_all, stream::FuturesUnordered, Future, StreamExt};
 use rand::{
     distributions::{Bernoulli, Uniform},
     prelude::Distribution,
@@ -47,7 +47,7 @@ pub enum TransmitType<TYPES: NodeType> {
 }
 
 /// Errors that can occur in the network
-#[derive(Debug, Error)]
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
 pub enum NetworkError {
     /// Multiple errors. Allows us to roll up multiple errors into one.
     #[error("Multiple errors: {0:?}")]
@@ -108,6 +108,26 @@ pub enum NetworkError {
     /// Failed to look up a node on the network
     #[error("Node lookup failed: {0}")]
     LookupError(String),
+
+    /// There were no peers to send the message to
+    #[error("No peers to send the message to")]
+    NoPeers,
+
+    /// Failed to dial the peer
+    #[error("Failed to dial peer: {0}")]
+    DialFailure(String),
+
+    /// The stream to the peer was reset or closed before the message was acknowledged
+    #[error("Stream reset: {0}")]
+    StreamReset(String),
+
+    /// The peer does not support the protocol the message was sent with
+    #[error("Protocol negotiation failed: {0}")]
+    ProtocolNegotiationFailed(String),
+
+    /// The message was dropped because it exceeded the peer's rate limit
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
 }
 
 /// Trait that bundles what we need from a request ID
@@ -238,6 +258,74 @@ pub trait ConnectedNetwork<K: SignatureKey + 'static>: Clone + Send + Sync + 'st
     /// blocking
     async fn direct_message(&self, message: Vec<u8>, recipient: K) -> Result<(), NetworkError>;
 
+    /// Sends a direct message to a specific node, and waits for the node to acknowledge it
+    ///
+    /// Unlike `direct_message`, which returns once the message has been handed to the network,
+    /// this returns the network's error when the message could not be delivered, e.g.
+    /// [`NetworkError::DialFailure`] if the node could not be reached. Networks which cannot
+    /// observe delivery return the same as `direct_message`.
+    async fn direct_message_acked(
+        &self,
+        message: Vec<u8>,
+        recipient: K,
+    ) -> Result<(), NetworkError> {
+        self.direct_message(message, recipient).await
+    }
+
+    /// Send a message directly to each of `recipients`, returning once `required_acks` of them
+    /// have acknowledged it or `timeout` has elapsed, whichever comes first.
+    ///
+    /// A recipient acknowledges the message when `direct_message_acked` to it succeeds. On
+    /// networks which observe delivery, such as libp2p, that is when the recipient has confirmed
+    /// receiving the message. On networks which cannot, such as the in-memory, CDN and combined
+    /// networks, every send which is handed to the network counts as an acknowledgement, so this
+    /// cannot tell whether a quorum actually received the message.
+    ///
+    /// Returns the number of acknowledgements received.
+    ///
+    /// # Errors
+    /// - If `required_acks` is larger than the number of recipients
+    /// - If every send finished without reaching `required_acks`, with the errors encountered
+    async fn broadcast_with_ack(
+        &self,
+        message: Vec<u8>,
+        recipients: Vec<K>,
+        required_acks: usize,
+        timeout: Duration,
+    ) -> Result<usize, NetworkError> {
+        let num_recipients = recipients.len();
+        if required_acks > num_recipients {
+            return Err(NetworkError::ConfigError(format!(
+                "Cannot require {required_acks} acks from {num_recipients} recipients"
+            )));
+        }
+
+        let mut sends: FuturesUnordered<_> = recipients
+            .into_iter()
+            .map(|recipient| self.direct_message_acked(message.clone(), recipient))
+            .collect();
+
+        let mut acks = 0;
+        let mut errors = Vec::new();
+        let collect_acks = async {
+            while acks < required_acks {
+                match sends.next().await {
+                    Some(Ok(())) => acks += 1,
+                    Some(Err(e)) => errors.push(e),
+                    None => break,
+                }
+            }
+        };
+        // Running out of time is not an error, we just report how many acks we got.
+        let _ = tokio::time::timeout(timeout, collect_acks).await;
+
+        if acks < required_acks && acks + errors.len() == num_recipients {
+            return Err(NetworkError::Multiple(errors));
+        }
+
+        Ok(acks)
+    }
+
     /// Receive one or many messages from the underlying network.
     ///
     /// # Errors
@@ -256,6 +344,18 @@ pub trait ConnectedNetwork<K: SignatureKey + 'static>: Clone + Send + Sync + 'st
         Ok(())
     }
 
+    /// Connect to the members of an upcoming committee ahead of time, so that the epoch change
+    /// does not cause a burst of new connections.
+    ///
+    /// By default this queues a lookup of each member.
+    fn pre_dial_committee(&self, view_number: ViewNumber, members: BTreeSet<K>) {
+        for member in members {
+            let _ = self
+                .queue_node_lookup(view_number, member)
+                .map_err(|err| tracing::warn!("Failed to queue pre-dial of a node: {err}"));
+        }
+    }
+
     /// Update view can be used for any reason, but mostly it's for canceling tasks,
     /// and looking up the address of the leader of a future view.
     async fn update_view<'a, TYPES>(
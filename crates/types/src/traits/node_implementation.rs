@@ -35,6 +35,7 @@ use super::{
     ValidatedState,
 };
 use crate::{
+    commit_scheme::CommitScheme,
     data::{Leaf2, TestableLeaf},
     traits::{
         election::Membership, signature_key::SignatureKey, states::InstanceState, BlockPayload,
@@ -252,6 +253,10 @@ pub trait NodeType:
 
     /// The type builder uses to sign its messages
     type BuilderSignatureKey: BuilderSignatureKey;
+
+    /// The hash function for the commitments to leaves and vote data. Every node of a network
+    /// must use the same scheme.
+    type CommitScheme: CommitScheme;
 }
 
 /// Version information for HotShot
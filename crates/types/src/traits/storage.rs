@@ -14,6 +14,7 @@ use std::collections::BTreeMap;
 use anyhow::Result;
 use async_trait::async_trait;
 use jf_vid::VidScheme;
+use serde::{Deserialize, Serialize};
 
 use super::node_implementation::NodeType;
 use crate::{
@@ -25,6 +26,39 @@ use crate::{
     vid::VidSchemeType,
 };
 
+/// How often the undecided consensus state is checkpointed to storage.
+///
+/// Checkpointing more often makes recovery after a restart more granular, at the cost of more
+/// storage writes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CheckpointPolicy {
+    /// Checkpoint in every view
+    #[default]
+    EveryView,
+    /// Checkpoint in every view which is a multiple of `n`
+    EveryNViews(u64),
+    /// Only checkpoint when a new leaf is decided
+    OnDecideOnly,
+}
+
+impl CheckpointPolicy {
+    /// Whether to checkpoint after updating the undecided state in `view`
+    #[must_use]
+    pub fn checkpoint_on_view(&self, view: u64) -> bool {
+        match self {
+            Self::EveryView => true,
+            Self::EveryNViews(n) => view % (*n).max(1) == 0,
+            Self::OnDecideOnly => false,
+        }
+    }
+
+    /// Whether to checkpoint after a new leaf is decided
+    #[must_use]
+    pub fn checkpoint_on_decide(&self) -> bool {
+        matches!(self, Self::OnDecideOnly)
+    }
+}
+
 /// Abstraction for storing a variety of consensus payload datum.
 #[async_trait]
 pub trait Storage<TYPES: NodeType>: Send + Sync + Clone {
@@ -7,6 +7,7 @@
 //! Utility functions, type aliases, helper structs and enum definitions.
 
 use std::{
+    fmt::Write,
     hash::{Hash, Hasher},
     ops::Deref,
     sync::Arc,
@@ -20,7 +21,7 @@ use bincode::{
     },
     DefaultOptions, Options,
 };
-use committable::Commitment;
+use committable::{Commitment, Committable};
 use digest::OutputSizeUser;
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
@@ -234,3 +235,46 @@ pub fn mnemonic<H: Hash>(bytes: H) -> String {
     bytes.hash(&mut state);
     mnemonic::to_string(state.finish().to_le_bytes())
 }
+
+/// A commitment with a hex form which is stable across versions, unlike its `Debug` output, so
+/// log lines carrying it stay greppable.
+pub trait CommitmentHex {
+    /// The bytes of the commitment as lowercase hex
+    fn to_hex(&self) -> String;
+}
+
+impl<T: Committable> CommitmentHex for Commitment<T> {
+    fn to_hex(&self) -> String {
+        encode_hex(self.as_ref())
+    }
+}
+
+impl CommitmentHex for VidCommitment {
+    fn to_hex(&self) -> String {
+        encode_hex(self.as_ref())
+    }
+}
+
+/// Parse a commitment from its [`CommitmentHex::to_hex`] form, if it is one
+#[must_use]
+pub fn commitment_from_hex<T: Committable>(hex: &str) -> Option<Commitment<T>> {
+    if hex.len() != 64 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, digits) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()?;
+    }
+
+    Some(Commitment::from_raw(bytes))
+}
+
+/// Lowercase hex of `bytes`
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
@@ -15,7 +15,11 @@
 //! via the traits exposed here.
 
 #![allow(missing_docs)]
-use std::{fmt::Debug, ops::Range};
+use std::{
+    fmt::{self, Debug, Display},
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 use ark_bn254::Bn254;
 use jf_pcs::{
@@ -36,7 +40,7 @@ use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 
 use crate::{
-    constants::SRS_DEGREE,
+    constants::{SRS_DEGREE, VID_FAULT_FRACTION},
     data::{VidDisperse as HotShotVidDisperse, VidDisperseShare},
     message::Proposal,
 };
@@ -60,13 +64,72 @@ use crate::{
 /// # Panics
 /// When the construction fails for the underlying VID scheme.
 #[must_use]
-#[memoize::memoize(SharedCache, Capacity: 10)]
 pub fn vid_scheme(num_storage_nodes: usize) -> VidSchemeType {
-    // recovery_threshold is currently num_storage_nodes rounded down to a power of two
-    // TODO recovery_threshold should be a function of the desired erasure code rate
-    // https://github.com/EspressoSystems/HotShot/issues/2152
-    let recovery_threshold = 1 << num_storage_nodes.ilog2();
+    vid_scheme_with_fault_fraction(num_storage_nodes, vid_fault_fraction())
+}
+
+/// Like [`vid_scheme`], but assuming `fault_fraction` of the storage nodes may be faulty instead
+/// of the fraction set by [`set_vid_fault_fraction`].
+///
+/// # Panics
+/// When the construction fails for the underlying VID scheme.
+#[must_use]
+pub fn vid_scheme_with_fault_fraction(
+    num_storage_nodes: usize,
+    fault_fraction: f64,
+) -> VidSchemeType {
+    advz_scheme(
+        num_storage_nodes,
+        auto_vid_rate(num_storage_nodes, fault_fraction),
+    )
+}
 
+lazy_static! {
+    /// Fraction of the storage nodes [`vid_scheme`] assumes may be faulty, as the bits of an `f64`
+    static ref FAULT_FRACTION: AtomicU64 = AtomicU64::new(VID_FAULT_FRACTION.to_bits());
+}
+
+/// Set the fraction of the storage nodes [`vid_scheme`] assumes may be faulty.
+///
+/// Every node must use the same fraction, since it determines the VID commitment of a payload.
+pub fn set_vid_fault_fraction(fault_fraction: f64) {
+    FAULT_FRACTION.store(fault_fraction.to_bits(), Ordering::Relaxed);
+}
+
+/// The fraction of the storage nodes [`vid_scheme`] assumes may be faulty
+#[must_use]
+pub fn vid_fault_fraction() -> f64 {
+    f64::from_bits(FAULT_FRACTION.load(Ordering::Relaxed))
+}
+
+/// Number of shares needed to recover a payload dispersed to `committee_size` storage nodes, up to
+/// `fault_fraction` of which may be faulty. The erasure-coding rate is this over
+/// `committee_size`.
+///
+/// This is the largest power of two no greater than the number of honest nodes, so the shares of
+/// the honest nodes alone always recover the payload. Small committees therefore get more
+/// redundancy than large ones, whose honest count a power of two can approach more closely.
+#[must_use]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn auto_vid_rate(committee_size: usize, fault_fraction: f64) -> usize {
+    let max_faulty = (committee_size as f64 * fault_fraction.clamp(0.0, 1.0)).floor() as usize;
+    let honest = committee_size.saturating_sub(max_faulty).max(1);
+
+    1 << honest.ilog2()
+}
+
+/// Construct the VID scheme for `num_storage_nodes`, `recovery_threshold` of whose shares recover
+/// a payload
+#[memoize::memoize(SharedCache, Capacity: 10)]
+fn advz_scheme(num_storage_nodes: usize, recovery_threshold: usize) -> VidSchemeType {
+    #[allow(clippy::panic)]
+    let recovery_threshold = u32::try_from(recovery_threshold).unwrap_or_else(|err| {
+        panic!("recovery_threshold {recovery_threshold} should fit into u32; error: {err}")
+    });
     #[allow(clippy::panic)]
     let num_storage_nodes = u32::try_from(num_storage_nodes).unwrap_or_else(|err| {
         panic!(
@@ -89,7 +152,11 @@ pub fn vid_scheme(num_storage_nodes: usize) -> VidSchemeType {
 #[cfg(feature = "test-srs")]
 #[memoize::memoize(SharedCache, Capacity: 10)]
 pub fn vid_scheme_for_test(num_storage_nodes: usize) -> VidSchemeType {
-    let recovery_threshold = 1 << num_storage_nodes.ilog2();
+    let recovery_threshold = auto_vid_rate(num_storage_nodes, vid_fault_fraction());
+    #[allow(clippy::panic)]
+    let recovery_threshold = u32::try_from(recovery_threshold).unwrap_or_else(|err| {
+        panic!("recovery_threshold {recovery_threshold} should fit into u32; error: {err}")
+    });
     #[allow(clippy::panic)]
     let num_storage_nodes = u32::try_from(num_storage_nodes).unwrap_or_else(|err| {
         panic!("num_storage_nodes {num_storage_nodes} should fit into u32; error: {err}")
@@ -159,6 +226,62 @@ pub struct SmallRangeProofType(
     SmallRangeProof<<UnivariateKzgPCS<E> as PolynomialCommitmentScheme>::Proof>,
 );
 
+/// Proof that a byte range of a block payload, e.g. a single transaction, is included in the
+/// payload with a given [`VidCommitment`].
+///
+/// A light client can check the proof with [`verify_inclusion`] without the rest of the payload.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InclusionProof {
+    /// The byte range of the proven bytes within the payload
+    pub range: Range<usize>,
+    /// VID common data for the payload
+    pub common: VidCommon,
+    /// The range proof against the payload commitment
+    pub proof: SmallRangeProofType,
+}
+
+impl InclusionProof {
+    /// Prove that `range` of `payload` is included in the payload's commitment for
+    /// `num_storage_nodes` storage nodes.
+    ///
+    /// # Errors
+    /// If `range` is out of bounds for `payload`, or the proof cannot be computed.
+    pub fn new(payload: &[u8], range: Range<usize>, num_storage_nodes: usize) -> VidResult<Self> {
+        let mut vid = vid_scheme(num_storage_nodes);
+        let common = vid.disperse(payload)?.common;
+        let proof = vid.payload_proof(payload, range.clone())?;
+
+        Ok(Self {
+            range,
+            common,
+            proof,
+        })
+    }
+}
+
+/// Check that `txn_bytes` sits at `proof.range` in the payload committed to by `commitment`.
+#[must_use]
+pub fn verify_inclusion(
+    commitment: &VidCommitment,
+    proof: &InclusionProof,
+    txn_bytes: &[u8],
+) -> bool {
+    if txn_bytes.len() != proof.range.len()
+        || VidSchemeType::is_consistent(commitment, &proof.common).is_err()
+    {
+        return false;
+    }
+
+    let vid = vid_scheme(VidSchemeType::get_num_storage_nodes(&proof.common) as usize);
+    let stmt = Statement {
+        payload_subslice: txn_bytes,
+        range: proof.range.clone(),
+        commit: commitment,
+        common: &proof.common,
+    };
+    matches!(vid.payload_verify(stmt, &proof.proof), Ok(Ok(())))
+}
+
 #[cfg(feature = "test-srs")]
 lazy_static! {
     /// SRS for testing only
@@ -192,6 +315,40 @@ type E = Bn254;
 /// Private type alias for the hash type parameter for [`Advz`].
 type H = Sha256;
 
+/// The schemes payloads are hashed, committed to and dispersed with. Nodes using different
+/// schemes can't validate each other's proposals, so compare them when connecting.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProtocolSchemes {
+    /// The digest algorithm hashing the payload
+    pub digest: String,
+    /// The polynomial commitment scheme committing to the payload
+    pub commitment: String,
+    /// The scheme dispersing the payload among the storage nodes
+    pub vid: String,
+}
+
+impl ProtocolSchemes {
+    /// The schemes used by [`VidSchemeType`]
+    #[must_use]
+    pub fn current() -> Self {
+        Self {
+            digest: "sha256".to_string(),
+            commitment: "kzg-bn254".to_string(),
+            vid: "advz".to_string(),
+        }
+    }
+}
+
+impl Display for ProtocolSchemes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "digest {}, commitment {}, vid {}",
+            self.digest, self.commitment, self.vid
+        )
+    }
+}
+
 // THE REST OF THIS FILE IS BOILERPLATE
 //
 // All this boilerplate can be deleted when we finally get
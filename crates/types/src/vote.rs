@@ -7,20 +7,29 @@
 //! Vote, Accumulator, and Certificate Types
 
 use std::{
+    cmp::Reverse,
     collections::{BTreeMap, HashMap},
     marker::PhantomData,
-    num::NonZeroU64,
+    num::NonZeroUsize,
+    sync::Arc,
+    thread::available_parallelism,
+    time::{Duration, Instant},
 };
 
 use bitvec::{bitvec, vec::BitVec};
 use committable::{Commitment, Committable};
 use either::Either;
 use primitive_types::U256;
+use serde::{Deserialize, Serialize};
+use tokio::task::spawn_blocking;
 use tracing::error;
 use utils::anytrace::Result;
+use vbs::version::Version;
 
 use crate::{
+    commitment_cache::CommitmentCache,
     message::UpgradeLock,
+    signature_cache::{validate_with_cache, SignatureCache},
     simple_certificate::Threshold,
     simple_vote::{VersionedVoteData, Voteable},
     traits::{
@@ -28,6 +37,7 @@ use crate::{
         node_implementation::{NodeType, Versions},
         signature_key::{SignatureKey, StakeTableEntryType},
     },
+    vote_metrics::{VoteMetrics, VoteRejection},
 };
 
 /// A simple vote that has a signer and commitment to the data voted on.
@@ -46,6 +56,144 @@ pub trait Vote<TYPES: NodeType>: HasViewNumber<TYPES> {
     fn signing_key(&self) -> TYPES::SignatureKey;
 }
 
+/// Check that `vote` is signed by its signing key over its data, view and the role of its data,
+/// under the rules of `epoch`.
+///
+/// From the epochs version on the role and the epoch are part of the signed commitment, so a DA
+/// vote never validates as a quorum vote and vice versa, and a vote cast in one epoch cannot be
+/// replayed into another. Near an upgrade, votes in the format of either version are accepted,
+/// see [`UpgradeLock::vote_versions`].
+pub async fn is_valid_vote<TYPES: NodeType, VOTE: Vote<TYPES>, V: Versions>(
+    vote: &VOTE,
+    epoch: TYPES::Epoch,
+    upgrade_lock: &UpgradeLock<TYPES, V>,
+) -> bool {
+    match signed_vote_commitment(vote, epoch, upgrade_lock).await {
+        Ok(commitment) => commitment.is_some(),
+        Err(e) => {
+            tracing::warn!("Failed to generate versioned vote data: {e}");
+            false
+        }
+    }
+}
+
+/// The commitment `vote` is signed over, in the format of the first version accepted for its
+/// view it validates under, or `None` if its signature is invalid under all of them.
+///
+/// # Errors
+/// If the version of the vote's view is not supported
+async fn signed_vote_commitment<TYPES: NodeType, VOTE: Vote<TYPES>, V: Versions>(
+    vote: &VOTE,
+    epoch: TYPES::Epoch,
+    upgrade_lock: &UpgradeLock<TYPES, V>,
+) -> Result<Option<Commitment<VersionedVoteData<TYPES, VOTE::Commitment, V>>>> {
+    let versions = upgrade_lock.vote_versions(vote.view_number()).await?;
+
+    Ok(signed_commitment_in_versions::<TYPES, VOTE, V>(
+        vote,
+        epoch,
+        &versions,
+        upgrade_lock.signature_cache.as_deref(),
+        upgrade_lock.commitment_cache.as_deref(),
+    ))
+}
+
+/// The commitment `vote` is signed over, in the format of the first of `versions` it validates
+/// under, or `None` if its signature is invalid under all of them. Signatures are checked through
+/// `signature_cache` and the vote data is committed to through `commitment_cache`, if there are
+/// such caches.
+fn signed_commitment_in_versions<TYPES: NodeType, VOTE: Vote<TYPES>, V: Versions>(
+    vote: &VOTE,
+    epoch: TYPES::Epoch,
+    versions: &[Version],
+    signature_cache: Option<&SignatureCache<TYPES::SignatureKey>>,
+    commitment_cache: Option<&CommitmentCache>,
+) -> Option<Commitment<VersionedVoteData<TYPES, VOTE::Commitment, V>>> {
+    let key = vote.signing_key();
+    let signature = vote.signature();
+
+    versions
+        .iter()
+        .map(|version| {
+            VersionedVoteData::<TYPES, _, V>::with_version(
+                vote.date().clone(),
+                vote.view_number(),
+                epoch,
+                *version,
+                commitment_cache,
+            )
+            .commit()
+        })
+        .find(|commitment| {
+            validate_with_cache(signature_cache, &key, &signature, commitment.as_ref())
+        })
+}
+
+/// A vote, the commitment it is signed over if its signature is valid, and how long checking the
+/// signature took
+type VerifiedVote<TYPES, VOTE, V> = (
+    VOTE,
+    Option<Commitment<VersionedVoteData<TYPES, <VOTE as Vote<TYPES>>::Commitment, V>>>,
+    Duration,
+);
+
+/// Check the signatures of `votes` against the formats of `versions` on the blocking thread
+/// pool, split into one chunk per available core. The votes are returned in their original order.
+async fn verify_votes_in_parallel<TYPES, VOTE, V>(
+    votes: Vec<VOTE>,
+    epoch: TYPES::Epoch,
+    versions: Vec<Version>,
+    signature_cache: Option<Arc<SignatureCache<TYPES::SignatureKey>>>,
+    commitment_cache: Option<Arc<CommitmentCache>>,
+) -> Vec<VerifiedVote<TYPES, VOTE, V>>
+where
+    TYPES: NodeType,
+    VOTE: Vote<TYPES> + Send + 'static,
+    V: Versions,
+{
+    let workers = available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = votes.len().div_ceil(workers).max(1);
+    let versions = Arc::new(versions);
+
+    let mut votes = votes.into_iter();
+    let mut chunks = Vec::new();
+    loop {
+        let chunk: Vec<VOTE> = votes.by_ref().take(chunk_size).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        let versions = Arc::clone(&versions);
+        let signature_cache = signature_cache.clone();
+        let commitment_cache = commitment_cache.clone();
+        chunks.push(spawn_blocking(move || {
+            chunk
+                .into_iter()
+                .map(|vote| {
+                    let start = Instant::now();
+                    let commitment = signed_commitment_in_versions::<TYPES, VOTE, V>(
+                        &vote,
+                        epoch,
+                        &versions,
+                        signature_cache.as_deref(),
+                        commitment_cache.as_deref(),
+                    );
+                    (vote, commitment, start.elapsed())
+                })
+                .collect::<Vec<_>>()
+        }));
+    }
+
+    let mut verified = Vec::new();
+    for chunk in chunks {
+        match chunk.await {
+            Ok(chunk) => verified.extend(chunk),
+            Err(e) => error!("Vote verification task failed: {e}"),
+        }
+    }
+
+    verified
+}
+
 /// Any type that is associated with a view
 pub trait HasViewNumber<TYPES: NodeType> {
     /// Returns the view number the type refers to.
@@ -64,19 +212,22 @@ pub trait Certificate<TYPES: NodeType, T>: HasViewNumber<TYPES> {
     /// Threshold Functions
     type Threshold: Threshold<TYPES>;
 
-    /// Build a certificate from the data commitment and the quorum of signers
+    /// Build a certificate from the data commitment and the quorum of signers in `epoch`
     fn create_signed_certificate<V: Versions>(
         vote_commitment: Commitment<VersionedVoteData<TYPES, Self::Voteable, V>>,
         data: Self::Voteable,
         sig: <TYPES::SignatureKey as SignatureKey>::QcType,
         view: TYPES::View,
+        epoch: TYPES::Epoch,
     ) -> Self;
 
-    /// Checks if the cert is valid in the given epoch
+    /// Checks if the cert is valid in `epoch`, against the stake `membership` weighs each signer
+    /// with at the certificate's view, see [`Self::effective_stake_table`]. From the epochs
+    /// version a certificate formed in any other epoch is rejected.
     fn is_valid_cert<V: Versions>(
         &self,
-        stake_table: Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>,
-        threshold: NonZeroU64,
+        membership: &TYPES::Membership,
+        epoch: TYPES::Epoch,
         upgrade_lock: &UpgradeLock<TYPES, V>,
     ) -> impl std::future::Future<Output = bool>;
     /// Returns the amount of stake needed to create this certificate
@@ -105,6 +256,66 @@ pub trait Certificate<TYPES: NodeType, T>: HasViewNumber<TYPES> {
         epoch: TYPES::Epoch,
     ) -> Option<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry>;
 
+    /// Get the stake `pub_key` contributes towards this certificate at `view_number`.
+    fn effective_stake<MEMBERSHIP: Membership<TYPES>>(
+        membership: &MEMBERSHIP,
+        pub_key: &TYPES::SignatureKey,
+        _view_number: TYPES::View,
+        epoch: TYPES::Epoch,
+    ) -> U256 {
+        Self::stake_table_entry(membership, pub_key, epoch)
+            .map_or(U256::zero(), |entry| entry.stake())
+    }
+
+    /// The stake table of this certificate at `view_number`, with each node's stake replaced by
+    /// its [`Self::effective_stake`], capped at [`Membership::max_vote_weight`].
+    ///
+    /// Votes are tallied and certificates are checked against this table, so the leader and the
+    /// replicas weigh every signer alike. Nodes keep their position in [`Self::stake_table`], so
+    /// the signer bit vector is laid out the same.
+    fn effective_stake_table<MEMBERSHIP: Membership<TYPES>>(
+        membership: &MEMBERSHIP,
+        view_number: TYPES::View,
+        epoch: TYPES::Epoch,
+    ) -> Vec<<TYPES::SignatureKey as SignatureKey>::StakeTableEntry> {
+        Self::stake_table(membership, epoch)
+            .into_iter()
+            .map(|entry| {
+                let key = TYPES::SignatureKey::public_key(&entry);
+                let stake = counted_vote_weight(
+                    Self::effective_stake(membership, &key, view_number, epoch),
+                    membership.max_vote_weight(),
+                );
+                key.stake_table_entry(u64::try_from(stake).unwrap_or(u64::MAX))
+            })
+            .collect()
+    }
+
+    /// The stake needed to form this certificate at `view_number` from votes weighed by
+    /// [`Self::effective_stake_table`].
+    ///
+    /// Capping the weight of each vote and unbonding stake both lower the total stake, so
+    /// [`Self::threshold`] is scaled down in proportion to the effective total, rounding up. A
+    /// certificate can then still be formed by the same share of the stake which remains bonded.
+    fn effective_threshold<MEMBERSHIP: Membership<TYPES>>(
+        membership: &MEMBERSHIP,
+        view_number: TYPES::View,
+        epoch: TYPES::Epoch,
+    ) -> U256 {
+        let threshold = U256::from(Self::threshold(membership, epoch));
+        let total = Self::stake_table(membership, epoch)
+            .iter()
+            .fold(U256::zero(), |total, entry| total + entry.stake());
+        if total.is_zero() {
+            return threshold;
+        }
+        let effective_total = Self::effective_stake_table(membership, view_number, epoch)
+            .iter()
+            .fold(U256::zero(), |total, entry| total + entry.stake());
+
+        (threshold * effective_total + total - 1) / total
+    }
+
     /// Get the commitment which was voted on
     fn data(&self) -> &Self::Voteable;
 
@@ -123,8 +334,18 @@ type SignersMap<COMMITMENT, KEY> = HashMap<
     ),
 >;
 
+/// A callback invoked with each certificate an accumulator forms, see
+/// [`VoteAccumulator::on_certificate`]
+pub type CertificateCallback<CERT> = Arc<dyn Fn(&CERT) + Send + Sync>;
+
+/// How long a certificate callback may run before we warn that it is blocking vote collection
+const CERTIFICATE_CALLBACK_BUDGET: Duration = Duration::from_millis(10);
+
 #[allow(clippy::type_complexity)]
 /// Accumulates votes until a certificate is formed.  This implementation works for all simple vote and certificate pairs
+///
+/// Each accumulator indexes signers by their position in its own certificate's stake table, so a
+/// node voting in both the DA and quorum committees holds unrelated bit positions in the two.
 pub struct VoteAccumulator<
     TYPES: NodeType,
     VOTE: Vote<TYPES>,
@@ -147,6 +368,42 @@ pub struct VoteAccumulator<
     pub phantom: PhantomData<(TYPES, VOTE, CERT)>,
     /// version information
     pub upgrade_lock: UpgradeLock<TYPES, V>,
+    /// Metrics of the votes handled, shared with the node's other accumulators
+    pub metrics: Arc<VoteMetrics>,
+    /// Called with each certificate formed, if registered
+    pub on_certificate: Option<CertificateCallback<CERT>>,
+}
+
+/// A snapshot of the votes in an accumulator, for diagnosing views which fail to form a
+/// certificate, see [`VoteAccumulator::dump`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(deserialize = ""))]
+pub struct AccumulatorDump<TYPES: NodeType> {
+    /// The stake needed to form the certificate
+    pub threshold: U256,
+    /// The votes for each piece of data voted on, ordered by stake accumulated, highest first
+    pub tallies: Vec<VoteTally<TYPES>>,
+}
+
+/// The votes accumulated for one piece of data, see [`AccumulatorDump`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound(deserialize = ""))]
+pub struct VoteTally<TYPES: NodeType> {
+    /// The commitment the votes sign
+    pub commitment: String,
+    /// The committee members which voted, in key order
+    pub signers: Vec<TYPES::SignatureKey>,
+    /// The stake the votes count for
+    pub stake: U256,
+    /// The stake still missing to form the certificate, zero once it has formed
+    pub remaining_stake: U256,
+}
+
+/// The weight a vote from a node with `stake` counts towards a certificate, given the
+/// `max_vote_weight` of the stake table, see [`Membership::max_vote_weight`].
+#[must_use]
+pub fn counted_vote_weight(stake: U256, max_vote_weight: Option<u64>) -> U256 {
+    max_vote_weight.map_or(stake, |cap| stake.min(U256::from(cap)))
 }
 
 impl<
@@ -156,46 +413,178 @@ impl<
         V: Versions,
     > VoteAccumulator<TYPES, VOTE, CERT, V>
 {
-    /// Add a vote to the total accumulated votes for the given epoch.
+    /// Register `callback` to be called with each certificate this accumulator forms, replacing
+    /// any callback registered before.
+    ///
+    /// The callback runs synchronously inside [`Self::accumulate`], before the certificate is
+    /// returned, so it must not block: it should only hand the certificate off, e.g. over a
+    /// channel. A callback exceeding a few milliseconds is reported with a warning.
+    pub fn on_certificate(&mut self, callback: impl Fn(&CERT) + Send + Sync + 'static) {
+        self.on_certificate = Some(Arc::new(callback));
+    }
+
+    /// Snapshot the votes accumulated so far, against the threshold of `membership` at
+    /// `view_number` in `epoch`.
+    ///
+    /// The accumulator is left untouched, so this is safe to call on a live accumulator.
+    #[must_use]
+    pub fn dump(
+        &self,
+        membership: &TYPES::Membership,
+        view_number: TYPES::View,
+        epoch: TYPES::Epoch,
+    ) -> AccumulatorDump<TYPES> {
+        let threshold = CERT::effective_threshold(membership, view_number, epoch);
+        let mut tallies: Vec<_> = self
+            .vote_outcomes
+            .iter()
+            .map(|(commitment, (stake, votes))| VoteTally {
+                commitment: commitment.to_string(),
+                signers: votes.keys().cloned().collect(),
+                stake: *stake,
+                remaining_stake: threshold.saturating_sub(*stake),
+            })
+            .collect();
+        tallies.sort_by_key(|tally| Reverse(tally.stake));
+
+        AccumulatorDump { threshold, tallies }
+    }
+
+    /// Add a vote to the total accumulated votes for `view_number` in the given epoch.
     /// Returns the accumulator or the certificate if we
     /// have accumulated enough votes to exceed the threshold for creating a certificate.
+    ///
+    /// Votes whose own view is not `view_number`, or which were not cast in `epoch`, are dropped.
     pub async fn accumulate(
         &mut self,
         vote: &VOTE,
+        view_number: TYPES::View,
         membership: &TYPES::Membership,
         epoch: TYPES::Epoch,
     ) -> Either<(), CERT> {
-        let key = vote.signing_key();
+        if vote.view_number() != view_number {
+            error!(
+                "Dropping vote for view {:?} passed to the accumulator for view {:?}",
+                vote.view_number(),
+                view_number
+            );
+            self.metrics.record_rejection(VoteRejection::WrongView);
+            return Either::Left(());
+        }
 
-        let vote_commitment = match VersionedVoteData::new(
-            vote.date().clone(),
-            vote.view_number(),
-            &self.upgrade_lock,
-        )
-        .await
-        {
-            Ok(data) => data.commit(),
+        // From the epochs version the commitment includes the role of the vote data, the epoch
+        // and the version, see `is_valid_vote`. Votes are tallied by the commitment they sign,
+        // so near an upgrade the votes in each version's format count towards separate
+        // certificates.
+        let verification_start = Instant::now();
+        let vote_commitment = match signed_vote_commitment(vote, epoch, &self.upgrade_lock).await {
+            Ok(commitment) => commitment,
             Err(e) => {
                 tracing::warn!("Failed to generate versioned vote data: {e}");
+                self.metrics.record_rejection(VoteRejection::UnknownVersion);
                 return Either::Left(());
             }
         };
-
-        if !key.validate(&vote.signature(), vote_commitment.as_ref()) {
+        self.metrics
+            .record_verification(verification_start.elapsed(), vote_commitment.is_some());
+        let Some(vote_commitment) = vote_commitment else {
             error!("Invalid vote! Vote Data {:?}", vote.date());
             return Either::Left(());
+        };
+
+        self.tally(vote, vote_commitment, membership, epoch)
+    }
+
+    /// Add a batch of votes for `view_number` in `epoch`, checking their signatures in parallel
+    /// off the async runtime before tallying them in order.
+    ///
+    /// The result is the same as passing the votes to [`Self::accumulate`] one at a time: the
+    /// certificate is returned as soon as the votes tallied so far cross the threshold, and the
+    /// rest of the batch is ignored.
+    pub async fn accumulate_batch(
+        &mut self,
+        votes: Vec<VOTE>,
+        view_number: TYPES::View,
+        membership: &TYPES::Membership,
+        epoch: TYPES::Epoch,
+    ) -> Either<(), CERT>
+    where
+        VOTE: Send + 'static,
+    {
+        let (votes, wrong_view): (Vec<_>, Vec<_>) = votes
+            .into_iter()
+            .partition(|vote| vote.view_number() == view_number);
+        for vote in wrong_view {
+            error!(
+                "Dropping vote for view {:?} passed to the accumulator for view {:?}",
+                vote.view_number(),
+                view_number
+            );
+            self.metrics.record_rejection(VoteRejection::WrongView);
         }
 
-        let Some(stake_table_entry) = CERT::stake_table_entry(membership, &key, epoch) else {
-            return Either::Left(());
+        // All the votes are for the same view, so they are accepted in the same formats
+        let versions = match self.upgrade_lock.vote_versions(view_number).await {
+            Ok(versions) => versions,
+            Err(e) => {
+                tracing::warn!("Failed to generate versioned vote data: {e}");
+                for _ in &votes {
+                    self.metrics.record_rejection(VoteRejection::UnknownVersion);
+                }
+                return Either::Left(());
+            }
         };
-        let stake_table = CERT::stake_table(membership, epoch);
-        let Some(vote_node_id) = stake_table
-            .iter()
-            .position(|x| *x == stake_table_entry.clone())
+
+        let verified = verify_votes_in_parallel::<TYPES, VOTE, V>(
+            votes,
+            epoch,
+            versions,
+            self.upgrade_lock.signature_cache.clone(),
+            self.upgrade_lock.commitment_cache.clone(),
+        )
+        .await;
+        for (vote, vote_commitment, elapsed) in verified {
+            self.metrics
+                .record_verification(elapsed, vote_commitment.is_some());
+            let Some(vote_commitment) = vote_commitment else {
+                error!("Invalid vote! Vote Data {:?}", vote.date());
+                continue;
+            };
+            if let Either::Right(cert) = self.tally(&vote, vote_commitment, membership, epoch) {
+                return Either::Right(cert);
+            }
+        }
+
+        Either::Left(())
+    }
+
+    /// Count `vote`, whose signature over `vote_commitment` has been checked, towards the
+    /// certificate for that commitment, returning the certificate once it has formed.
+    fn tally(
+        &mut self,
+        vote: &VOTE,
+        vote_commitment: Commitment<VersionedVoteData<TYPES, VOTE::Commitment, V>>,
+        membership: &TYPES::Membership,
+        epoch: TYPES::Epoch,
+    ) -> Either<(), CERT> {
+        let key = vote.signing_key();
+        // The same stakes `Certificate::is_valid_cert` checks the certificate against
+        let stake_table = CERT::effective_stake_table(membership, vote.view_number(), epoch);
+        let Some((vote_node_id, effective_stake)) =
+            stake_table.iter().enumerate().find_map(|(node_id, entry)| {
+                (TYPES::SignatureKey::public_key(entry) == key).then(|| (node_id, entry.stake()))
+            })
         else {
+            self.metrics
+                .record_rejection(VoteRejection::NotInStakeTable);
             return Either::Left(());
         };
+        if effective_stake.is_zero() {
+            tracing::debug!("Ignoring vote from {key:?} which has no effective stake at this view");
+            self.metrics
+                .record_rejection(VoteRejection::NoEffectiveStake);
+            return Either::Left(());
+        }
 
         let original_signature: <TYPES::SignatureKey as SignatureKey>::PureAssembledSignatureType =
             vote.signature();
@@ -207,29 +596,29 @@ impl<
 
         // Check for duplicate vote
         if total_vote_map.contains_key(&key) {
+            self.metrics.record_rejection(VoteRejection::Duplicate);
             return Either::Left(());
         }
         let (signers, sig_list) = self
             .signers
             .entry(vote_commitment)
-            .or_insert((bitvec![0; CERT::total_nodes(membership, epoch)], Vec::new()));
+            .or_insert((bitvec![0; stake_table.len()], Vec::new()));
         if signers.get(vote_node_id).as_deref() == Some(&true) {
             error!("Node id is already in signers list");
+            self.metrics.record_rejection(VoteRejection::Duplicate);
             return Either::Left(());
         }
         signers.set(vote_node_id, true);
         sig_list.push(original_signature);
 
-        *total_stake_casted += stake_table_entry.stake();
+        *total_stake_casted += effective_stake;
         total_vote_map.insert(key, (vote.signature(), vote_commitment));
 
-        if *total_stake_casted >= CERT::threshold(membership, epoch).into() {
+        let threshold = CERT::effective_threshold(membership, vote.view_number(), epoch);
+        if *total_stake_casted >= threshold {
             // Assemble QC
             let real_qc_pp: <<TYPES as NodeType>::SignatureKey as SignatureKey>::QcParams =
-                <TYPES::SignatureKey as SignatureKey>::public_parameter(
-                    stake_table,
-                    U256::from(CERT::threshold(membership, epoch)),
-                );
+                <TYPES::SignatureKey as SignatureKey>::public_parameter(stake_table, threshold);
 
             let real_qc_sig = <TYPES::SignatureKey as SignatureKey>::assemble(
                 &real_qc_pp,
@@ -242,7 +631,17 @@ impl<
                 vote.date().clone(),
                 real_qc_sig,
                 vote.view_number(),
+                epoch,
             );
+            self.metrics.record_certificate();
+            if let Some(callback) = &self.on_certificate {
+                let callback_start = Instant::now();
+                callback(&cert);
+                let elapsed = callback_start.elapsed();
+                if elapsed > CERTIFICATE_CALLBACK_BUDGET {
+                    tracing::warn!("Certificate callback blocked vote collection for {elapsed:?}");
+                }
+            }
             return Either::Right(cert);
         }
         Either::Left(())
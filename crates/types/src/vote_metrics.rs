@@ -0,0 +1,222 @@
+// Copyright (c) 2021-2024 Espresso Systems (espressosys.com)
+// This file is part of the HotShot repository.
+
+// You should have received a copy of the MIT License
+// along with the HotShot repository. If not, see <https://mit-license.org/>.
+
+//! Metrics of the votes exchanged between replicas and leaders.
+//!
+//! Vote accumulators record into a shared [`VoteMetrics`] using only atomic operations, and
+//! operators read everything at once with [`VoteMetrics::export`].
+
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Upper bounds, in microseconds, of the vote verification time histogram buckets
+pub const VERIFICATION_TIME_BUCKETS_MICROS: [u64; 8] =
+    [50, 100, 250, 500, 1000, 2500, 5000, 10_000];
+
+/// Why a vote accumulator rejected a vote
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VoteRejection {
+    /// The vote is for another view than the one being accumulated
+    WrongView,
+    /// The version of the vote's view could not be determined
+    UnknownVersion,
+    /// The vote's signature does not verify
+    InvalidSignature,
+    /// The signer is not in the stake table
+    NotInStakeTable,
+    /// The signer has no effective stake at the vote's view
+    NoEffectiveStake,
+    /// The signer has already voted
+    Duplicate,
+}
+
+impl VoteRejection {
+    /// Every rejection reason, in the order they are exported
+    pub const ALL: [Self; 6] = [
+        Self::WrongView,
+        Self::UnknownVersion,
+        Self::InvalidSignature,
+        Self::NotInStakeTable,
+        Self::NoEffectiveStake,
+        Self::Duplicate,
+    ];
+
+    /// The label of this reason in the Prometheus exposition
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::WrongView => "wrong_view",
+            Self::UnknownVersion => "unknown_version",
+            Self::InvalidSignature => "invalid_signature",
+            Self::NotInStakeTable => "not_in_stake_table",
+            Self::NoEffectiveStake => "no_effective_stake",
+            Self::Duplicate => "duplicate",
+        }
+    }
+}
+
+/// Counters of the votes handled by a node's vote accumulators
+#[derive(Debug, Default)]
+pub struct VoteMetrics {
+    /// Votes whose signature verified
+    votes_verified: AtomicU64,
+    /// Certificates formed
+    certificates_formed: AtomicU64,
+    /// Verifications per bucket of [`VERIFICATION_TIME_BUCKETS_MICROS`], plus one overflow bucket
+    verification_time_buckets: [AtomicU64; VERIFICATION_TIME_BUCKETS_MICROS.len() + 1],
+    /// Total time spent verifying votes, in microseconds
+    verification_time_sum_micros: AtomicU64,
+    /// Rejected votes, indexed like [`VoteRejection::ALL`]
+    rejections: [AtomicU64; VoteRejection::ALL.len()],
+}
+
+impl VoteMetrics {
+    /// Record a signature verification which took `elapsed`, and whether it succeeded.
+    pub fn record_verification(&self, elapsed: Duration, valid: bool) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        let bucket = VERIFICATION_TIME_BUCKETS_MICROS
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(VERIFICATION_TIME_BUCKETS_MICROS.len());
+        self.verification_time_buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.verification_time_sum_micros
+            .fetch_add(micros, Ordering::Relaxed);
+
+        if valid {
+            self.votes_verified.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.record_rejection(VoteRejection::InvalidSignature);
+        }
+    }
+
+    /// Record a certificate being formed.
+    pub fn record_certificate(&self) {
+        self.certificates_formed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a vote being rejected for `reason`.
+    pub fn record_rejection(&self, reason: VoteRejection) {
+        let index = VoteRejection::ALL
+            .iter()
+            .position(|r| *r == reason)
+            .unwrap_or_default();
+        self.rejections[index].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a snapshot of the counters.
+    ///
+    /// This only reads atomics, so it never contends with vote accumulation. The counters are
+    /// read one at a time, so a snapshot taken during accumulation may be off by the votes in
+    /// flight.
+    #[must_use]
+    pub fn export(&self) -> ExchangeMetrics {
+        let mut cumulative = 0;
+        let verification_time_buckets = self
+            .verification_time_buckets
+            .iter()
+            .map(|count| {
+                cumulative += count.load(Ordering::Relaxed);
+                cumulative
+            })
+            .collect();
+
+        ExchangeMetrics {
+            votes_verified: self.votes_verified.load(Ordering::Relaxed),
+            certificates_formed: self.certificates_formed.load(Ordering::Relaxed),
+            verification_time_buckets,
+            verification_time_sum_micros: self.verification_time_sum_micros.load(Ordering::Relaxed),
+            rejections: VoteRejection::ALL
+                .iter()
+                .zip(&self.rejections)
+                .map(|(reason, count)| (*reason, count.load(Ordering::Relaxed)))
+                .collect(),
+        }
+    }
+}
+
+/// A snapshot of a node's [`VoteMetrics`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExchangeMetrics {
+    /// Votes whose signature verified
+    pub votes_verified: u64,
+    /// Certificates formed
+    pub certificates_formed: u64,
+    /// Cumulative verification counts for each bound in [`VERIFICATION_TIME_BUCKETS_MICROS`],
+    /// followed by the total count
+    pub verification_time_buckets: Vec<u64>,
+    /// Total time spent verifying votes, in microseconds
+    pub verification_time_sum_micros: u64,
+    /// Rejected votes by reason
+    pub rejections: Vec<(VoteRejection, u64)>,
+}
+
+impl ExchangeMetrics {
+    /// Number of votes rejected for `reason`
+    #[must_use]
+    pub fn rejected(&self, reason: VoteRejection) -> u64 {
+        self.rejections
+            .iter()
+            .find_map(|(r, count)| (*r == reason).then_some(*count))
+            .unwrap_or_default()
+    }
+
+    /// Render the snapshot in the Prometheus text exposition format.
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        // Writing to a `String` cannot fail.
+        let _ = writeln!(out, "# TYPE hotshot_votes_verified_total counter");
+        let _ = writeln!(out, "hotshot_votes_verified_total {}", self.votes_verified);
+        let _ = writeln!(out, "# TYPE hotshot_certificates_formed_total counter");
+        let _ = writeln!(
+            out,
+            "hotshot_certificates_formed_total {}",
+            self.certificates_formed
+        );
+
+        let _ = writeln!(out, "# TYPE hotshot_vote_verification_seconds histogram");
+        let bounds = VERIFICATION_TIME_BUCKETS_MICROS
+            .iter()
+            .map(|micros| Duration::from_micros(*micros).as_secs_f64().to_string())
+            .chain(std::iter::once("+Inf".to_string()));
+        for (bound, count) in bounds.zip(&self.verification_time_buckets) {
+            let _ = writeln!(
+                out,
+                "hotshot_vote_verification_seconds_bucket{{le=\"{bound}\"}} {count}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "hotshot_vote_verification_seconds_sum {}",
+            Duration::from_micros(self.verification_time_sum_micros).as_secs_f64()
+        );
+        let _ = writeln!(
+            out,
+            "hotshot_vote_verification_seconds_count {}",
+            self.verification_time_buckets
+                .last()
+                .copied()
+                .unwrap_or_default()
+        );
+
+        let _ = writeln!(out, "# TYPE hotshot_votes_rejected_total counter");
+        for (reason, count) in &self.rejections {
+            let _ = writeln!(
+                out,
+                "hotshot_votes_rejected_total{{reason=\"{}\"}} {count}",
+                reason.label()
+            );
+        }
+
+        out
+    }
+}